@@ -0,0 +1,270 @@
+//! Command-line parsing for `vk`. The commands are few and take mostly ids, so they are parsed
+//! by hand rather than with an argument-parsing crate.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, anyhow, bail};
+use uuid::Uuid;
+
+pub const USAGE: &str = "\
+Usage: vk [--url <URL>] [--json] <command>
+
+Commands:
+  projects                                   List projects
+  tasks list --project <ID> [--status <S>]   List a project's tasks
+  tasks create --project <ID> --title <T> [--description <D>]
+                                             Create a task
+  attempts list --task <ID>                  List a task's attempts
+  attempts start --task <ID> --executor <EXECUTOR[:VARIANT]> --base <BRANCH>
+                                             Start an attempt with an executor profile
+  processes <ATTEMPT_ID>                     List an attempt's execution processes
+  logs <PROCESS_ID>                          Print an execution process's output, following
+                                             it until the process finishes
+  diff <ATTEMPT_ID> [--patch]                Show the files an attempt changed, or its
+                                             commits as a patch
+  merge <ATTEMPT_ID> [--allow-protected]     Merge an attempt into its target branch
+
+Options:
+  --url <URL>   Server to talk to; defaults to VIBE_BACKEND_URL, then HOST and BACKEND_PORT or
+                PORT, then the port of the locally running server
+  --json        Print the server's responses as JSON
+";
+
+/// Flags that take no value
+const SWITCHES: &[&str] = &["json", "patch", "allow-protected", "help"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutorProfile {
+    pub executor: String,
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Projects,
+    ListTasks {
+        project_id: Uuid,
+        status: Option<String>,
+    },
+    CreateTask {
+        project_id: Uuid,
+        title: String,
+        description: Option<String>,
+    },
+    ListAttempts {
+        task_id: Uuid,
+    },
+    StartAttempt {
+        task_id: Uuid,
+        profile: ExecutorProfile,
+        base_branch: String,
+    },
+    Processes {
+        attempt_id: Uuid,
+    },
+    Logs {
+        process_id: Uuid,
+    },
+    Diff {
+        attempt_id: Uuid,
+        patch: bool,
+    },
+    Merge {
+        attempt_id: Uuid,
+        allow_protected: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub url: Option<String>,
+    pub json: bool,
+    pub command: Command,
+}
+
+/// The arguments split into words and flags
+struct Parsed {
+    words: Vec<String>,
+    options: HashMap<String, String>,
+    switches: HashSet<String>,
+}
+
+impl Parsed {
+    fn new(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut parsed = Self {
+            words: Vec::new(),
+            options: HashMap::new(),
+            switches: HashSet::new(),
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                parsed.words.push(arg);
+                continue;
+            };
+            if let Some((name, value)) = flag.split_once('=') {
+                parsed.options.insert(name.to_string(), value.to_string());
+            } else if SWITCHES.contains(&flag) {
+                parsed.switches.insert(flag.to_string());
+            } else {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--{flag} needs a value"))?;
+                parsed.options.insert(flag.to_string(), value);
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn option(&mut self, name: &str) -> Option<String> {
+        self.options.remove(name)
+    }
+
+    fn required(&mut self, name: &str) -> anyhow::Result<String> {
+        self.option(name)
+            .ok_or_else(|| anyhow!("--{name} is required"))
+    }
+
+    fn required_id(&mut self, name: &str) -> anyhow::Result<Uuid> {
+        parse_id(&self.required(name)?)
+    }
+
+    fn switch(&mut self, name: &str) -> bool {
+        self.switches.remove(name)
+    }
+}
+
+fn parse_id(value: &str) -> anyhow::Result<Uuid> {
+    Uuid::parse_str(value.trim()).with_context(|| format!("'{value}' is not a valid id"))
+}
+
+impl std::str::FromStr for ExecutorProfile {
+    type Err = anyhow::Error;
+
+    /// `CLAUDE_CODE`, `claude-code` or `CLAUDE_CODE:PLAN`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (executor, variant) = match s.split_once(':') {
+            Some((executor, variant)) => (executor, Some(variant.trim())),
+            None => (s, None),
+        };
+        let executor = executor.trim().replace('-', "_").to_ascii_uppercase();
+        if executor.is_empty() {
+            bail!("Executor must not be empty");
+        }
+        Ok(Self {
+            executor,
+            variant: variant
+                .filter(|variant| !variant.is_empty())
+                .map(|variant| variant.to_ascii_uppercase()),
+        })
+    }
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Cli> {
+    let mut parsed = Parsed::new(args)?;
+    let url = parsed.option("url");
+    let json = parsed.switch("json");
+    if parsed.switch("help") {
+        return Ok(Cli {
+            url,
+            json,
+            command: Command::Help,
+        });
+    }
+
+    let words = std::mem::take(&mut parsed.words);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let command = match words.as_slice() {
+        [] | ["help"] => Command::Help,
+        ["projects"] => Command::Projects,
+        ["tasks", "list"] => Command::ListTasks {
+            project_id: parsed.required_id("project")?,
+            status: parsed.option("status"),
+        },
+        ["tasks", "create"] => Command::CreateTask {
+            project_id: parsed.required_id("project")?,
+            title: parsed.required("title")?,
+            description: parsed.option("description"),
+        },
+        ["attempts", "list"] => Command::ListAttempts {
+            task_id: parsed.required_id("task")?,
+        },
+        ["attempts", "start"] => Command::StartAttempt {
+            task_id: parsed.required_id("task")?,
+            profile: parsed.required("executor")?.parse()?,
+            base_branch: parsed.required("base")?,
+        },
+        ["processes", id] => Command::Processes {
+            attempt_id: parse_id(id)?,
+        },
+        ["logs", id] => Command::Logs {
+            process_id: parse_id(id)?,
+        },
+        ["diff", id] => Command::Diff {
+            attempt_id: parse_id(id)?,
+            patch: parsed.switch("patch"),
+        },
+        ["merge", id] => Command::Merge {
+            attempt_id: parse_id(id)?,
+            allow_protected: parsed.switch("allow-protected"),
+        },
+        _ => bail!("Unknown command '{}'\n\n{USAGE}", words.join(" ")),
+    };
+
+    if let Some(flag) = parsed.options.keys().chain(parsed.switches.iter()).next() {
+        bail!("Unexpected option --{flag}");
+    }
+    Ok(Cli { url, json, command })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &str) -> anyhow::Result<Cli> {
+        parse(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn parses_commands_and_their_flags() {
+        let task_id = Uuid::new_v4();
+        let cli = parse_args(&format!(
+            "--json attempts start --task {task_id} --executor claude-code:plan --base=main"
+        ))
+        .unwrap();
+        assert!(cli.json);
+        assert_eq!(
+            cli.command,
+            Command::StartAttempt {
+                task_id,
+                profile: ExecutorProfile {
+                    executor: "CLAUDE_CODE".to_string(),
+                    variant: Some("PLAN".to_string()),
+                },
+                base_branch: "main".to_string(),
+            }
+        );
+
+        let attempt_id = Uuid::new_v4();
+        let cli = parse_args(&format!("merge {attempt_id} --url http://localhost:3000")).unwrap();
+        assert_eq!(cli.url.as_deref(), Some("http://localhost:3000"));
+        assert_eq!(
+            cli.command,
+            Command::Merge {
+                attempt_id,
+                allow_protected: false,
+            }
+        );
+        assert_eq!(parse_args("").unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn rejects_missing_and_unexpected_arguments() {
+        assert!(parse_args("tasks list").is_err());
+        assert!(parse_args("tasks list --project not-an-id").is_err());
+        assert!(parse_args(&format!("logs {} --patch", Uuid::new_v4())).is_err());
+        assert!(parse_args("tasks create --project").is_err());
+        assert!(parse_args("boards").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, anyhow, bail};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use utils::port_file::read_port_file;
+
+/// The server's `ApiResponse`, whose fields aren't public
+#[derive(Debug, Deserialize)]
+struct ApiResponseEnvelope<T> {
+    success: bool,
+    data: Option<T>,
+    message: Option<String>,
+}
+
+/// Client of the server's HTTP API
+pub struct ApiClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+/// The server to talk to: the one given, else the one in the environment, else the locally
+/// running one, like the MCP server finds it
+pub async fn resolve_base_url(url: Option<String>) -> anyhow::Result<String> {
+    if let Some(url) = url.or_else(|| std::env::var("VIBE_BACKEND_URL").ok()) {
+        return Ok(url);
+    }
+    let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port = match std::env::var("BACKEND_PORT").or_else(|_| std::env::var("PORT")) {
+        Ok(port) => port
+            .parse::<u16>()
+            .map_err(|e| anyhow!("Invalid port value '{}': {}", port, e))?,
+        Err(_) => read_port_file("vibe-kanban")
+            .await
+            .context("No server URL given and no running server found")?,
+    };
+    Ok(format!("http://{}:{}", host, port))
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    /// The response's data, if it has any
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<Option<T>> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.base_url))?;
+        let status = response.status();
+        let body = response.text().await?;
+        let envelope = serde_json::from_str::<ApiResponseEnvelope<T>>(&body).map_err(|e| {
+            if status.is_success() {
+                anyhow!("Failed to parse the server's response: {e}")
+            } else {
+                anyhow!("Server returned {status}: {}", body.trim())
+            }
+        })?;
+        if !envelope.success {
+            bail!(
+                "{}",
+                envelope
+                    .message
+                    .unwrap_or_else(|| format!("Server returned {status}"))
+            );
+        }
+        Ok(envelope.data)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<T> {
+        self.execute(request)
+            .await?
+            .ok_or_else(|| anyhow!("The server's response has no data"))
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> anyhow::Result<T> {
+        self.send(self.client.get(self.url(path)).query(query))
+            .await
+    }
+
+    pub async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        body: &impl Serialize,
+    ) -> anyhow::Result<T> {
+        self.send(self.client.post(self.url(path)).query(query).json(body))
+            .await
+    }
+
+    /// A request whose response carries no data
+    pub async fn post_empty(&self, path: &str, query: &[(&str, String)]) -> anyhow::Result<()> {
+        self.execute::<Value>(self.client.post(self.url(path)).query(query))
+            .await
+            .map(|_| ())
+    }
+
+    /// A response that isn't wrapped in an `ApiResponse`, such as a download
+    pub async fn get_text(&self, path: &str, query: &[(&str, String)]) -> anyhow::Result<String> {
+        let response = self
+            .client
+            .get(self.url(path))
+            .query(query)
+            .send()
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.base_url))?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            let message = serde_json::from_str::<ApiResponseEnvelope<Value>>(&body)
+                .ok()
+                .and_then(|envelope| envelope.message)
+                .unwrap_or(body);
+            bail!("Server returned {status}: {}", message.trim());
+        }
+        Ok(body)
+    }
+
+    /// The JSON messages of one of the server's WebSocket streams, until it closes
+    pub async fn stream_ws(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<Value>>> {
+        let url = self.url(path);
+        let url = match url.split_once("://") {
+            Some(("https", rest)) => format!("wss://{rest}"),
+            Some((_, rest)) => format!("ws://{rest}"),
+            None => url,
+        };
+        let (socket, _) = connect_async(url.as_str())
+            .await
+            .with_context(|| format!("Failed to connect to {url}"))?;
+        Ok(socket.filter_map(|message| async move {
+            match message {
+                Ok(Message::Text(text)) => Some(
+                    serde_json::from_str(text.as_str())
+                        .context("Failed to parse a message from the server"),
+                ),
+                Ok(_) => None,
+                Err(e) => Some(Err(e.into())),
+            }
+        }))
+    }
+}
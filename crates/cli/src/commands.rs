@@ -0,0 +1,325 @@
+//! The commands, with the parts of the server's responses they print. Responses are read
+//! loosely, so the client keeps working with servers that add fields.
+
+use std::io::Write;
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    args::{Command, ExecutorProfile, USAGE},
+    client::ApiClient,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Project {
+    id: Uuid,
+    name: String,
+    git_repo_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Task {
+    id: Uuid,
+    title: String,
+    status: String,
+    #[serde(default)]
+    has_in_progress_attempt: bool,
+    #[serde(default)]
+    last_attempt_failed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DiffStats {
+    files_changed: i64,
+    additions: i64,
+    deletions: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TaskAttempt {
+    id: Uuid,
+    branch: String,
+    target_branch: String,
+    executor: String,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    diff_stats: Option<DiffStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExecutionProcess {
+    id: Uuid,
+    run_reason: String,
+    status: String,
+    exit_code: Option<i64>,
+    started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Diff {
+    change: String,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    additions: Option<usize>,
+    deletions: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DiffFilePage {
+    files: Vec<Diff>,
+    total: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateTask {
+    project_id: Uuid,
+    title: String,
+    description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateTaskAttempt {
+    task_id: Uuid,
+    executor_profile_id: ExecutorProfileId,
+    base_branch: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ExecutorProfileId {
+    executor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variant: Option<String>,
+}
+
+impl From<ExecutorProfile> for ExecutorProfileId {
+    fn from(profile: ExecutorProfile) -> Self {
+        Self {
+            executor: profile.executor,
+            variant: profile.variant,
+        }
+    }
+}
+
+fn print_json(value: &impl Serialize) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+pub async fn run(client: &ApiClient, command: Command, json: bool) -> anyhow::Result<()> {
+    match command {
+        Command::Help => print!("{USAGE}"),
+        Command::Projects => {
+            let projects: Vec<Project> = client.get("projects", &[]).await?;
+            if json {
+                return print_json(&projects);
+            }
+            for project in projects {
+                println!(
+                    "{}  {}  {}",
+                    project.id, project.name, project.git_repo_path
+                );
+            }
+        }
+        Command::ListTasks { project_id, status } => {
+            let mut tasks: Vec<Task> = client
+                .get("tasks", &[("project_id", project_id.to_string())])
+                .await?;
+            if let Some(status) = status {
+                let status = status.replace('-', "").to_ascii_lowercase();
+                tasks.retain(|task| task.status == status);
+            }
+            if json {
+                return print_json(&tasks);
+            }
+            for task in tasks {
+                let state = if task.has_in_progress_attempt {
+                    " (running)"
+                } else if task.last_attempt_failed {
+                    " (failed)"
+                } else {
+                    ""
+                };
+                println!("{}  {:<10}  {}{}", task.id, task.status, task.title, state);
+            }
+        }
+        Command::CreateTask {
+            project_id,
+            title,
+            description,
+        } => {
+            let body = CreateTask {
+                project_id,
+                title,
+                description,
+            };
+            let task: Task = client.post("tasks", &[], &body).await?;
+            if json {
+                return print_json(&task);
+            }
+            println!("{}", task.id);
+        }
+        Command::ListAttempts { task_id } => {
+            let attempts: Vec<TaskAttempt> = client
+                .get("task-attempts", &[("task_id", task_id.to_string())])
+                .await?;
+            if json {
+                return print_json(&attempts);
+            }
+            for attempt in attempts {
+                let stats = attempt
+                    .diff_stats
+                    .map(|stats| {
+                        format!(
+                            "  {} files +{} -{}",
+                            stats.files_changed, stats.additions, stats.deletions
+                        )
+                    })
+                    .unwrap_or_default();
+                println!(
+                    "{}  {}  {} -> {}  {}{}",
+                    attempt.id,
+                    attempt.executor,
+                    attempt.branch,
+                    attempt.target_branch,
+                    attempt.created_at.format("%Y-%m-%d %H:%M"),
+                    stats
+                );
+            }
+        }
+        Command::StartAttempt {
+            task_id,
+            profile,
+            base_branch,
+        } => {
+            let body = CreateTaskAttempt {
+                task_id,
+                executor_profile_id: profile.into(),
+                base_branch,
+            };
+            let attempt: TaskAttempt = client.post("task-attempts", &[], &body).await?;
+            if json {
+                return print_json(&attempt);
+            }
+            println!("{}", attempt.id);
+        }
+        Command::Processes { attempt_id } => {
+            let processes: Vec<ExecutionProcess> = client
+                .get(
+                    "execution-processes",
+                    &[("task_attempt_id", attempt_id.to_string())],
+                )
+                .await?;
+            if json {
+                return print_json(&processes);
+            }
+            for process in processes {
+                let exit_code = process
+                    .exit_code
+                    .map(|code| format!(" (exit {code})"))
+                    .unwrap_or_default();
+                println!(
+                    "{}  {:<13}  {:<9}  {}{}",
+                    process.id,
+                    process.run_reason,
+                    process.status,
+                    process.started_at.format("%Y-%m-%d %H:%M"),
+                    exit_code
+                );
+            }
+        }
+        Command::Logs { process_id } => tail_logs(client, process_id, json).await?,
+        Command::Diff { attempt_id, patch } => {
+            if patch {
+                let patch = client
+                    .get_text(
+                        &format!("task-attempts/{attempt_id}/export"),
+                        &[("format", "patch".to_string())],
+                    )
+                    .await?;
+                print!("{patch}");
+                return Ok(());
+            }
+            let page: DiffFilePage = client
+                .get(&format!("task-attempts/{attempt_id}/diff/files"), &[])
+                .await?;
+            if json {
+                return print_json(&page);
+            }
+            for file in &page.files {
+                let path = match (&file.old_path, &file.new_path) {
+                    (Some(old), Some(new)) if old != new => format!("{old} -> {new}"),
+                    (_, Some(path)) | (Some(path), None) => path.clone(),
+                    (None, None) => continue,
+                };
+                println!(
+                    "{:<16}  +{:<5} -{:<5}  {}",
+                    file.change,
+                    file.additions.unwrap_or(0),
+                    file.deletions.unwrap_or(0),
+                    path
+                );
+            }
+            println!("{} files changed", page.total);
+        }
+        Command::Merge {
+            attempt_id,
+            allow_protected,
+        } => {
+            let query = if allow_protected {
+                vec![("allow_protected", "true".to_string())]
+            } else {
+                Vec::new()
+            };
+            client
+                .post_empty(&format!("task-attempts/{attempt_id}/merge"), &query)
+                .await?;
+            println!("Merged {attempt_id}");
+        }
+    }
+    Ok(())
+}
+
+/// Print the process's output as it is written, until the process finishes
+async fn tail_logs(client: &ApiClient, process_id: Uuid, json: bool) -> anyhow::Result<()> {
+    let mut messages = Box::pin(
+        client
+            .stream_ws(&format!("execution-processes/{process_id}/raw-logs/ws"))
+            .await?,
+    );
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    while let Some(message) = messages.next().await {
+        let message = message?;
+        if message.get("finished").and_then(Value::as_bool) == Some(true) {
+            return Ok(());
+        }
+        if json {
+            println!("{message}");
+            continue;
+        }
+        let Some(patch) = message.get("JsonPatch").and_then(Value::as_array) else {
+            continue;
+        };
+        for entry in patch.iter().filter_map(|op| op.get("value")) {
+            let content = entry.get("content").and_then(Value::as_str).unwrap_or("");
+            match entry.get("type").and_then(Value::as_str) {
+                Some("STDOUT") => {
+                    stdout.write_all(content.as_bytes())?;
+                    stdout.flush()?;
+                }
+                Some("STDERR") => {
+                    stderr.write_all(content.as_bytes())?;
+                    stderr.flush()?;
+                }
+                _ => {}
+            }
+        }
+    }
+    bail!("The server closed the log stream before the process finished")
+}
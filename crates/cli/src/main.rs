@@ -0,0 +1,26 @@
+//! `vk`, a command-line client of the server's API, so tasks can be created, run, followed,
+//! reviewed and merged from scripts.
+
+mod args;
+mod client;
+mod commands;
+
+use client::{ApiClient, resolve_base_url};
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run().await {
+        eprintln!("error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let cli = args::parse(std::env::args().skip(1))?;
+    let base_url = match cli.command {
+        args::Command::Help => String::new(),
+        _ => resolve_base_url(cli.url).await?,
+    };
+    let client = ApiClient::new(&base_url);
+    commands::run(&client, cli.command, cli.json).await
+}
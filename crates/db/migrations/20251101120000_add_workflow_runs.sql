@@ -0,0 +1,20 @@
+-- Track GitHub Actions workflow_dispatch runs triggered from task attempts
+
+CREATE TABLE workflow_runs (
+    id              BLOB PRIMARY KEY,
+    task_attempt_id BLOB NOT NULL,
+    repo_owner      TEXT NOT NULL,
+    repo_name       TEXT NOT NULL,
+    workflow_file   TEXT NOT NULL,
+    git_ref         TEXT NOT NULL,
+    run_id          INTEGER,            -- NULL until the dispatched run shows up on GitHub
+    run_url         TEXT,
+    status          TEXT NOT NULL DEFAULT 'requested',
+    conclusion      TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_workflow_runs_task_attempt_id ON workflow_runs(task_attempt_id);
+CREATE INDEX idx_workflow_runs_status ON workflow_runs(status);
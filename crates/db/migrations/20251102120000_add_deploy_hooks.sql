@@ -0,0 +1,13 @@
+-- Per-project deploy hooks that trigger preview deployments for task attempts
+
+CREATE TABLE deploy_hooks (
+    project_id   BLOB PRIMARY KEY,
+    url_template TEXT NOT NULL,
+    token        TEXT,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Preview URL returned by the most recent deploy hook call for the attempt
+ALTER TABLE task_attempts ADD COLUMN preview_url TEXT;
@@ -0,0 +1,21 @@
+-- Vulnerabilities found in dependencies added by a task attempt
+
+CREATE TABLE vulnerability_findings (
+    id               BLOB PRIMARY KEY,
+    task_attempt_id  BLOB NOT NULL,
+    ecosystem        TEXT NOT NULL,
+    package_name     TEXT NOT NULL,
+    package_version  TEXT NOT NULL,
+    manifest_path    TEXT NOT NULL,
+    vulnerability_id TEXT NOT NULL,
+    summary          TEXT,
+    severity         TEXT NOT NULL DEFAULT 'unknown'
+                        CHECK (severity IN ('critical','high','medium','low','unknown')),
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_vulnerability_findings_task_attempt_id ON vulnerability_findings(task_attempt_id);
+
+-- Refuse to merge attempts with critical findings
+ALTER TABLE projects ADD COLUMN block_merge_on_vulnerabilities BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- Add stalled_at column, set while a coding agent has produced no output
+-- for longer than the configured stall timeout
+ALTER TABLE execution_processes
+    ADD COLUMN stalled_at TEXT;
@@ -0,0 +1,2 @@
+-- Optional per-project sandbox (namespaces + cgroup limits) for executor processes, as JSON
+ALTER TABLE projects ADD COLUMN sandbox TEXT;
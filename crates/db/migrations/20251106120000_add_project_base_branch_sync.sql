@@ -0,0 +1,13 @@
+-- Opt-in background fetching of attempts' base branches, with optional auto-rebase
+ALTER TABLE projects ADD COLUMN sync_base_branch BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE projects ADD COLUMN auto_rebase_on_sync BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Result of the latest base branch check for each attempt
+CREATE TABLE base_branch_syncs (
+    task_attempt_id   BLOB PRIMARY KEY,
+    commits_behind    INTEGER NOT NULL DEFAULT 0,
+    rebased_at        TEXT,
+    error             TEXT,
+    synced_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Remotes used for pushing attempt branches and for base branches / pull requests.
+-- NULL falls back to the repository's first remote.
+ALTER TABLE projects ADD COLUMN push_remote TEXT;
+ALTER TABLE projects ADD COLUMN base_remote TEXT;
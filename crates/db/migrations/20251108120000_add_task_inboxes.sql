@@ -0,0 +1,11 @@
+-- Per-project inboxes that turn inbound webhook payloads and emails into tasks
+
+CREATE TABLE task_inboxes (
+    project_id           BLOB PRIMARY KEY,
+    secret               TEXT NOT NULL UNIQUE,
+    title_template       TEXT NOT NULL,
+    description_template TEXT,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
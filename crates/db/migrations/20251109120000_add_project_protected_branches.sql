@@ -0,0 +1,3 @@
+-- Comma-separated branch patterns (e.g. "main, release/*") that merges and pushes refuse to
+-- write to without an explicit override
+ALTER TABLE projects ADD COLUMN protected_branches TEXT;
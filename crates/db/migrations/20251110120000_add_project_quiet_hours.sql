@@ -0,0 +1,4 @@
+-- IANA time zone for the project's working hours; NULL uses the server's local time
+ALTER TABLE projects ADD COLUMN timezone TEXT;
+-- JSON quiet hours window during which queued work is not started automatically
+ALTER TABLE projects ADD COLUMN quiet_hours TEXT;
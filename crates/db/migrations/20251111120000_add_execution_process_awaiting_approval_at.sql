@@ -0,0 +1,4 @@
+-- Add awaiting_approval_at column, set while an executor is blocked on a
+-- tool approval from the user
+ALTER TABLE execution_processes
+    ADD COLUMN awaiting_approval_at TEXT;
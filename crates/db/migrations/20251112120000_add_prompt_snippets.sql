@@ -0,0 +1,20 @@
+-- Reusable prompt snippets, expanded from /name when a follow-up is sent.
+-- Snippets without a project are global; project snippets override global ones of the same name.
+
+CREATE TABLE prompt_snippets (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB,
+    name        TEXT NOT NULL CHECK(INSTR(name, ' ') = 0 AND name != ''),
+    content     TEXT NOT NULL CHECK(content != ''),
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_prompt_snippets_unique_name_project
+    ON prompt_snippets(project_id, name)
+    WHERE project_id IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_prompt_snippets_unique_name_global
+    ON prompt_snippets(name)
+    WHERE project_id IS NULL;
@@ -0,0 +1,3 @@
+-- Language tag (e.g. `es`) for the server-generated messages of the project; NULL uses the
+-- configured language
+ALTER TABLE projects ADD COLUMN locale TEXT;
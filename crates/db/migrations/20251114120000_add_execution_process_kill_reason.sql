@@ -0,0 +1,4 @@
+-- Add kill_reason column, set when the server stops a process on its own
+-- (time or memory limit exceeded, stalled agent)
+ALTER TABLE execution_processes
+    ADD COLUMN kill_reason TEXT;
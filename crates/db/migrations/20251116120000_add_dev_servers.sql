@@ -0,0 +1,10 @@
+-- Port and crash-restart bookkeeping for each attempt's dev server
+CREATE TABLE dev_servers (
+    task_attempt_id       BLOB PRIMARY KEY,
+    execution_process_id  BLOB,
+    port                  INTEGER NOT NULL,
+    restart_count         INTEGER NOT NULL DEFAULT 0,
+    updated_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
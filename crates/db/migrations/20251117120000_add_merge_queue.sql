@@ -0,0 +1,18 @@
+-- Per-project merge queue: attempts are rebased, verified and merged one at a time
+ALTER TABLE projects ADD COLUMN verify_script TEXT;
+
+CREATE TABLE merge_queue_entries (
+    id               BLOB PRIMARY KEY,
+    project_id       BLOB NOT NULL,
+    task_attempt_id  BLOB NOT NULL UNIQUE,
+    status           TEXT NOT NULL DEFAULT 'queued'
+                        CHECK (status IN ('queued','rebasing','verifying','merging','merged','failed','cancelled')),
+    error            TEXT,
+    merge_commit     TEXT,
+    queued_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_merge_queue_entries_project_status ON merge_queue_entries(project_id, status);
@@ -0,0 +1,2 @@
+-- Captions for a draft's attached images, as a JSON object keyed by image id
+ALTER TABLE drafts ADD COLUMN image_captions TEXT;
@@ -0,0 +1,14 @@
+-- Environment variables given to a project's scripts and coding agents.
+-- Values of secrets are stored encrypted and never returned by the API.
+
+CREATE TABLE project_env_vars (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    key         TEXT NOT NULL CHECK(key != ''),
+    value       TEXT NOT NULL,
+    secret      INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, key)
+);
@@ -0,0 +1,4 @@
+-- JSON policy for re-running setup scripts and coding agents that fail with a transient error
+ALTER TABLE projects ADD COLUMN retry_policy TEXT;
+-- The failed execution process that this one re-runs
+ALTER TABLE execution_processes ADD COLUMN retry_of BLOB REFERENCES execution_processes(id) ON DELETE SET NULL;
@@ -0,0 +1,27 @@
+-- Schedules that start task attempts on a cron expression, such as nightly maintenance runs.
+-- Each run either starts a new task from a tag's content or a new attempt on an existing task.
+CREATE TABLE task_schedules (
+    id                    BLOB PRIMARY KEY,
+    project_id            BLOB NOT NULL,
+    name                  TEXT NOT NULL,
+    cron_expression       TEXT NOT NULL,
+    tag_id                BLOB,
+    task_id               BLOB,
+    executor_profile_id   TEXT NOT NULL,
+    base_branch           TEXT NOT NULL,
+    enabled               BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at           TEXT,
+    last_run_at           TEXT,
+    last_task_attempt_id  BLOB,
+    last_error            TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    CHECK ((tag_id IS NULL) != (task_id IS NULL)),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (last_task_attempt_id) REFERENCES task_attempts(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_task_schedules_project_id ON task_schedules(project_id);
+CREATE INDEX idx_task_schedules_next_run_at ON task_schedules(enabled, next_run_at);
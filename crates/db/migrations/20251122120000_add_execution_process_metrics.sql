@@ -0,0 +1,17 @@
+-- Resource usage recorded when an execution process finishes. Token counts and cost are only
+-- set for coding agents that report them.
+CREATE TABLE execution_process_metrics (
+    execution_process_id  BLOB PRIMARY KEY,
+    wall_time_ms          INTEGER NOT NULL,
+    exit_code             INTEGER,
+    stdout_bytes          INTEGER NOT NULL,
+    stderr_bytes          INTEGER NOT NULL,
+    input_tokens          INTEGER,
+    cached_input_tokens   INTEGER,
+    output_tokens         INTEGER,
+    cost_usd              REAL,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_metrics_created_at ON execution_process_metrics(created_at);
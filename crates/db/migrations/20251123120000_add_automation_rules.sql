@@ -0,0 +1,39 @@
+-- Per-project rules that run actions on the board when something happens, such as marking a
+-- task done once its attempt merges. Every time a rule fires it is logged in
+-- automation_rule_runs, which also keeps a rule from firing twice for the same event.
+CREATE TABLE automation_rules (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    name        TEXT NOT NULL,
+    trigger     TEXT NOT NULL
+                   CHECK (trigger IN ('attempt_merged', 'coding_agent_completed', 'coding_agent_failed')),
+    conditions  TEXT NOT NULL DEFAULT '[]',
+    actions     TEXT NOT NULL DEFAULT '[]',
+    enabled     BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_automation_rules_project_id ON automation_rules(project_id);
+
+CREATE TABLE automation_rule_runs (
+    id               BLOB PRIMARY KEY,
+    rule_id          BLOB NOT NULL,
+    -- Identifies the event that fired the rule, e.g. the merge or execution process
+    event_key        TEXT NOT NULL,
+    task_id          BLOB NOT NULL,
+    task_attempt_id  BLOB,
+    status           TEXT NOT NULL DEFAULT 'running'
+                        CHECK (status IN ('running', 'succeeded', 'failed')),
+    -- What the actions did, or why one of them failed
+    message          TEXT,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    completed_at     TEXT,
+    UNIQUE (rule_id, event_key),
+    FOREIGN KEY (rule_id) REFERENCES automation_rules(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_automation_rule_runs_rule_id ON automation_rule_runs(rule_id, created_at);
@@ -0,0 +1,40 @@
+-- Output lines of finished execution processes, split out of their JSONL logs so they can be
+-- searched. Line numbers count stdout and stderr lines together, in the order they were
+-- printed.
+CREATE TABLE execution_process_log_lines (
+    id                    INTEGER PRIMARY KEY,
+    execution_process_id  BLOB NOT NULL,
+    line_number           INTEGER NOT NULL,
+    stream                TEXT NOT NULL CHECK (stream IN ('stdout', 'stderr')),
+    content               TEXT NOT NULL,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_log_lines_process
+    ON execution_process_log_lines(execution_process_id, line_number);
+
+-- Full-text index over the lines, kept in sync by the triggers below
+CREATE VIRTUAL TABLE execution_process_log_search USING fts5(
+    content,
+    content = 'execution_process_log_lines',
+    content_rowid = 'id'
+);
+
+CREATE TRIGGER execution_process_log_lines_ai AFTER INSERT ON execution_process_log_lines
+BEGIN
+    INSERT INTO execution_process_log_search (rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER execution_process_log_lines_ad AFTER DELETE ON execution_process_log_lines
+BEGIN
+    INSERT INTO execution_process_log_search (execution_process_log_search, rowid, content)
+    VALUES ('delete', old.id, old.content);
+END;
+
+-- Processes whose output has been indexed, including ones that printed nothing
+CREATE TABLE execution_process_log_index (
+    execution_process_id  BLOB PRIMARY KEY,
+    line_count            INTEGER NOT NULL,
+    indexed_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
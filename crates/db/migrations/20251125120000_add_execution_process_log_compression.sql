@@ -0,0 +1,3 @@
+-- Logs compressed by the retention policy are moved here as zstd-compressed JSONL, leaving
+-- `logs` empty. `byte_size` keeps the uncompressed size.
+ALTER TABLE execution_process_logs ADD COLUMN logs_zstd BLOB;
@@ -0,0 +1,15 @@
+-- JSON settings for reusing the output of the setup script between attempts
+ALTER TABLE projects ADD COLUMN setup_cache TEXT;
+
+-- Saved setup script output, keyed by a hash of the script and the project's lockfiles. The
+-- files themselves live in the cache directory; a row is only written once they are complete.
+CREATE TABLE setup_cache_entries (
+    project_id    BLOB NOT NULL,
+    cache_key     TEXT NOT NULL,
+    size_bytes    INTEGER NOT NULL,
+    hit_count     INTEGER NOT NULL DEFAULT 0,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    last_used_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, cache_key),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- What to run when an attempt's cleanup script fails
+ALTER TABLE projects ADD COLUMN on_cleanup_failure TEXT;
@@ -0,0 +1,23 @@
+-- Attempts of one task started together with different executor profiles so their results
+-- can be compared and one of them kept
+CREATE TABLE attempt_comparisons (
+    id                 BLOB PRIMARY KEY,
+    task_id            BLOB NOT NULL,
+    chosen_attempt_id  BLOB,
+    chosen_at          TEXT,
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (chosen_attempt_id) REFERENCES task_attempts(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_attempt_comparisons_task_id ON attempt_comparisons(task_id);
+
+CREATE TABLE attempt_comparison_members (
+    comparison_id        BLOB NOT NULL,
+    task_attempt_id      BLOB NOT NULL UNIQUE,
+    executor_profile_id  TEXT NOT NULL,
+    position             INTEGER NOT NULL,
+    PRIMARY KEY (comparison_id, task_attempt_id),
+    FOREIGN KEY (comparison_id) REFERENCES attempt_comparisons(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
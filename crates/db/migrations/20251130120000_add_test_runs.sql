@@ -0,0 +1,45 @@
+-- Test command run after each coding agent, and how to read its results
+ALTER TABLE projects ADD COLUMN test_run TEXT;
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN run_reason_new TEXT NOT NULL DEFAULT 'setupscript'
+    CHECK (run_reason_new IN ('setupscript',
+                              'cleanupscript',
+                              'codingagent',
+                              'devserver',
+                              'postmergescript',
+                              'testscript'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET run_reason_new = run_reason;
+
+-- 3. Drop the index on the old column
+DROP INDEX IF EXISTS idx_execution_processes_type;
+
+-- 4. Remove the old column
+ALTER TABLE execution_processes DROP COLUMN run_reason;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN run_reason_new TO run_reason;
+
+-- 6. Re-create the index
+CREATE INDEX idx_execution_processes_type
+        ON execution_processes(run_reason);
+
+-- Pass/fail counts parsed from a test run's output
+CREATE TABLE test_run_results (
+    execution_process_id  BLOB PRIMARY KEY,
+    task_attempt_id       BLOB NOT NULL,
+    format                TEXT NOT NULL,
+    passed                INTEGER NOT NULL,
+    failed                INTEGER NOT NULL,
+    skipped               INTEGER NOT NULL,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_test_run_results_task_attempt_id ON test_run_results(task_attempt_id);
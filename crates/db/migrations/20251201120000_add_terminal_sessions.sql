@@ -0,0 +1,13 @@
+-- Interactive shells opened in an attempt's worktree, with what was shown in them
+CREATE TABLE terminal_sessions (
+    id                BLOB PRIMARY KEY,
+    task_attempt_id   BLOB NOT NULL,
+    shell             TEXT NOT NULL,
+    exit_code         INTEGER,
+    transcript        TEXT NOT NULL DEFAULT '',
+    started_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    ended_at          TEXT,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_terminal_sessions_task_attempt_id ON terminal_sessions(task_attempt_id);
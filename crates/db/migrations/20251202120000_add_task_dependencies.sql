@@ -0,0 +1,14 @@
+-- Tasks that must be done before another task can be started. resolved_at is set once the
+-- task depended on reaches done, so unblocking is only announced once.
+CREATE TABLE task_dependencies (
+    task_id             BLOB NOT NULL,
+    depends_on_task_id  BLOB NOT NULL,
+    resolved_at         TEXT,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, depends_on_task_id),
+    CHECK (task_id != depends_on_task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (depends_on_task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_dependencies_depends_on_task_id ON task_dependencies(depends_on_task_id);
@@ -0,0 +1,5 @@
+-- Subtasks: a task can belong to a parent task in the same project. Deleting the parent
+-- leaves its subtasks in place as top-level tasks.
+ALTER TABLE tasks ADD COLUMN parent_task_id BLOB REFERENCES tasks(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tasks_parent_task_id ON tasks(parent_task_id);
@@ -0,0 +1,13 @@
+-- Task priority, preferred by the execution queue, and a manual position within the board.
+-- Existing tasks keep the newest-first order they were shown in.
+ALTER TABLE tasks ADD COLUMN priority TEXT NOT NULL DEFAULT 'normal'
+    CHECK (priority IN ('urgent', 'high', 'normal', 'low'));
+ALTER TABLE tasks ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+UPDATE tasks
+   SET sort_order = ordered.position
+  FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY project_id ORDER BY created_at DESC) AS position
+          FROM tasks) AS ordered
+ WHERE tasks.id = ordered.id;
+
+CREATE INDEX idx_tasks_project_id_sort_order ON tasks(project_id, sort_order);
@@ -0,0 +1,7 @@
+-- Who a task is assigned to, and who started each attempt and execution. Users are the names
+-- the server attributes requests to; NULL means unassigned, or started by the server itself.
+ALTER TABLE tasks ADD COLUMN assignee TEXT;
+ALTER TABLE task_attempts ADD COLUMN started_by TEXT;
+ALTER TABLE execution_processes ADD COLUMN started_by TEXT;
+
+CREATE INDEX idx_tasks_project_id_assignee ON tasks(project_id, assignee);
@@ -0,0 +1,46 @@
+-- Per-project board columns. Each column maps onto one of the task statuses the server
+-- relies on, so a project can split a status over several columns or rename them.
+CREATE TABLE project_columns (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    name        TEXT NOT NULL,
+    status      TEXT NOT NULL
+        CHECK (status IN ('todo', 'inprogress', 'inreview', 'done', 'cancelled')),
+    position    INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_columns_project_id_position ON project_columns(project_id, position);
+
+-- Existing projects, and every project created from now on, start with one column per status
+INSERT INTO project_columns (id, project_id, name, status, position)
+SELECT randomblob(16), projects.id, defaults.name, defaults.status, defaults.position
+  FROM projects
+ CROSS JOIN (SELECT 'To Do' AS name, 'todo' AS status, 0 AS position
+             UNION ALL SELECT 'In Progress', 'inprogress', 1
+             UNION ALL SELECT 'In Review', 'inreview', 2
+             UNION ALL SELECT 'Done', 'done', 3
+             UNION ALL SELECT 'Cancelled', 'cancelled', 4) AS defaults;
+
+CREATE TRIGGER projects_default_columns AFTER INSERT ON projects
+BEGIN
+    INSERT INTO project_columns (id, project_id, name, status, position) VALUES
+        (randomblob(16), NEW.id, 'To Do', 'todo', 0),
+        (randomblob(16), NEW.id, 'In Progress', 'inprogress', 1),
+        (randomblob(16), NEW.id, 'In Review', 'inreview', 2),
+        (randomblob(16), NEW.id, 'Done', 'done', 3),
+        (randomblob(16), NEW.id, 'Cancelled', 'cancelled', 4);
+END;
+
+-- The column a task sits in. Its status always matches the column's.
+ALTER TABLE tasks ADD COLUMN column_id BLOB REFERENCES project_columns(id) ON DELETE SET NULL;
+
+UPDATE tasks
+   SET column_id = (SELECT pc.id
+                      FROM project_columns pc
+                     WHERE pc.project_id = tasks.project_id
+                       AND pc.status = tasks.status);
+
+CREATE INDEX idx_tasks_column_id ON tasks(column_id);
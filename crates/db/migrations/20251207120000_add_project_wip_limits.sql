@@ -0,0 +1,13 @@
+-- Work-in-progress limits: at most max_tasks tasks of a project may have the status.
+-- Exceeding it either only warns or is refused, per limit.
+CREATE TABLE project_wip_limits (
+    project_id   BLOB NOT NULL,
+    status       TEXT NOT NULL
+        CHECK (status IN ('todo', 'inprogress', 'inreview', 'done', 'cancelled')),
+    max_tasks    INTEGER NOT NULL CHECK (max_tasks > 0),
+    enforcement  TEXT NOT NULL DEFAULT 'warn' CHECK (enforcement IN ('warn', 'refuse')),
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, status),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
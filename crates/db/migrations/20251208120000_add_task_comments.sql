@@ -0,0 +1,14 @@
+-- Discussion on a task, optionally about one of its execution processes
+CREATE TABLE task_comments (
+    id                    BLOB PRIMARY KEY,
+    task_id               BLOB NOT NULL,
+    execution_process_id  BLOB,
+    author                TEXT,
+    body                  TEXT NOT NULL,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_task_comments_task_id_created_at ON task_comments(task_id, created_at);
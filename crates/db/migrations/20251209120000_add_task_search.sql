@@ -0,0 +1,88 @@
+-- Full-text index over each task's title, description, comments and the content of its tags,
+-- one row per task, kept in sync by the triggers below
+CREATE VIRTUAL TABLE task_search USING fts5(
+    task_id UNINDEXED,
+    title,
+    description,
+    comments,
+    tags,
+    tokenize = 'porter unicode61'
+);
+
+INSERT INTO task_search (task_id, title, description, comments, tags)
+SELECT
+    t.id,
+    t.title,
+    COALESCE(t.description, ''),
+    COALESCE((SELECT group_concat(c.body, char(10)) FROM task_comments c WHERE c.task_id = t.id), ''),
+    COALESCE((SELECT group_concat(tg.tag_name || ' ' || tg.content, char(10))
+              FROM task_tags tt JOIN tags tg ON tg.id = tt.tag_id
+              WHERE tt.task_id = t.id), '')
+FROM tasks t;
+
+CREATE TRIGGER task_search_tasks_ai AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO task_search (task_id, title, description, comments, tags)
+    VALUES (new.id, new.title, COALESCE(new.description, ''), '', '');
+END;
+
+CREATE TRIGGER task_search_tasks_au AFTER UPDATE OF title, description ON tasks
+BEGIN
+    UPDATE task_search
+    SET title = new.title, description = COALESCE(new.description, '')
+    WHERE task_id = new.id;
+END;
+
+CREATE TRIGGER task_search_tasks_ad AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM task_search WHERE task_id = old.id;
+END;
+
+CREATE TRIGGER task_search_comments_ai AFTER INSERT ON task_comments
+BEGIN
+    UPDATE task_search
+    SET comments = COALESCE((SELECT group_concat(body, char(10)) FROM task_comments WHERE task_id = new.task_id), '')
+    WHERE task_id = new.task_id;
+END;
+
+CREATE TRIGGER task_search_comments_au AFTER UPDATE OF body ON task_comments
+BEGIN
+    UPDATE task_search
+    SET comments = COALESCE((SELECT group_concat(body, char(10)) FROM task_comments WHERE task_id = new.task_id), '')
+    WHERE task_id = new.task_id;
+END;
+
+CREATE TRIGGER task_search_comments_ad AFTER DELETE ON task_comments
+BEGIN
+    UPDATE task_search
+    SET comments = COALESCE((SELECT group_concat(body, char(10)) FROM task_comments WHERE task_id = old.task_id), '')
+    WHERE task_id = old.task_id;
+END;
+
+CREATE TRIGGER task_search_task_tags_ai AFTER INSERT ON task_tags
+BEGIN
+    UPDATE task_search
+    SET tags = COALESCE((SELECT group_concat(tg.tag_name || ' ' || tg.content, char(10))
+                         FROM task_tags tt JOIN tags tg ON tg.id = tt.tag_id
+                         WHERE tt.task_id = new.task_id), '')
+    WHERE task_id = new.task_id;
+END;
+
+CREATE TRIGGER task_search_task_tags_ad AFTER DELETE ON task_tags
+BEGIN
+    UPDATE task_search
+    SET tags = COALESCE((SELECT group_concat(tg.tag_name || ' ' || tg.content, char(10))
+                         FROM task_tags tt JOIN tags tg ON tg.id = tt.tag_id
+                         WHERE tt.task_id = old.task_id), '')
+    WHERE task_id = old.task_id;
+END;
+
+-- Editing a tag changes the indexed text of every task that has it
+CREATE TRIGGER task_search_tags_au AFTER UPDATE OF tag_name, content ON tags
+BEGIN
+    UPDATE task_search
+    SET tags = COALESCE((SELECT group_concat(tg.tag_name || ' ' || tg.content, char(10))
+                         FROM task_tags tt JOIN tags tg ON tg.id = tt.tag_id
+                         WHERE tt.task_id = task_search.task_id), '')
+    WHERE task_id IN (SELECT task_id FROM task_tags WHERE tag_id = new.id);
+END;
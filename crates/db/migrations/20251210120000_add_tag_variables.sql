@@ -0,0 +1,3 @@
+-- Variables a tag's content declares as {{name}} placeholders, as a JSON array of
+-- {name, kind, default, description}
+ALTER TABLE tags ADD COLUMN variables TEXT NOT NULL DEFAULT '[]';
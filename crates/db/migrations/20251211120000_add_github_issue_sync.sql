@@ -0,0 +1,25 @@
+-- Projects that sync tasks with the issues of their GitHub repository, and the issue each
+-- imported task came from
+
+CREATE TABLE github_issue_syncs (
+    project_id     BLOB PRIMARY KEY,
+    label          TEXT,      -- only import issues with this label; NULL imports every issue
+    last_synced_at TEXT,
+    last_error     TEXT,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE TABLE github_issue_links (
+    task_id      BLOB PRIMARY KEY,
+    project_id   BLOB NOT NULL,
+    issue_number INTEGER NOT NULL,
+    issue_url    TEXT NOT NULL,
+    closed       BOOLEAN NOT NULL DEFAULT FALSE, -- issue state as last seen or set by us
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, issue_number)
+);
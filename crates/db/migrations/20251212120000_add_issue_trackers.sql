@@ -0,0 +1,34 @@
+-- Connections from projects to an external issue tracker (Linear or Jira). Issues of the
+-- tracker are imported as tasks that record the issue's key, and the statuses of both are kept
+-- in step. API tokens are stored encrypted and never returned by the API.
+
+ALTER TABLE tasks ADD COLUMN external_key TEXT;
+
+CREATE UNIQUE INDEX idx_tasks_project_id_external_key
+    ON tasks(project_id, external_key)
+    WHERE external_key IS NOT NULL;
+
+CREATE TABLE issue_tracker_connections (
+    project_id     BLOB PRIMARY KEY,
+    kind           TEXT NOT NULL CHECK (kind IN ('linear', 'jira')),
+    base_url       TEXT,          -- Jira site, e.g. https://acme.atlassian.net; unused for Linear
+    scope          TEXT NOT NULL, -- Linear team key or Jira project key
+    email          TEXT,          -- Jira account the token belongs to; unused for Linear
+    api_token      TEXT NOT NULL,
+    last_synced_at TEXT,
+    last_error     TEXT,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE TABLE issue_tracker_links (
+    task_id       BLOB PRIMARY KEY,
+    project_id    BLOB NOT NULL,
+    issue_url     TEXT NOT NULL,
+    synced_status TEXT NOT NULL, -- task status both sides agreed on at the last sync
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
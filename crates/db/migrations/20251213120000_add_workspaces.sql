@@ -0,0 +1,19 @@
+-- Workspaces group projects so their tasks can be shown on one board
+
+CREATE TABLE workspaces (
+    id         BLOB PRIMARY KEY,
+    name       TEXT NOT NULL CHECK(name != ''),
+    created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE TABLE workspace_projects (
+    workspace_id BLOB NOT NULL,
+    project_id   BLOB NOT NULL,
+    position     INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, project_id),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_workspace_projects_project_id ON workspace_projects(project_id);
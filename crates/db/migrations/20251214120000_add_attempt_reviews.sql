@@ -0,0 +1,7 @@
+-- Code-review state of an attempt's output, independent of its task's status
+
+ALTER TABLE task_attempts ADD COLUMN review_state TEXT
+    CHECK (review_state IN ('reviewed', 'approved', 'needs_changes'));
+ALTER TABLE task_attempts ADD COLUMN review_note TEXT;
+ALTER TABLE task_attempts ADD COLUMN reviewed_by TEXT;
+ALTER TABLE task_attempts ADD COLUMN reviewed_at TEXT;
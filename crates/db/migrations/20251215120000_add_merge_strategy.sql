@@ -0,0 +1,7 @@
+-- How a direct merge brought an attempt's changes into its target branch. Direct merges have
+-- always been squashed; PR merges leave it unset, since GitHub doesn't report how a PR was merged.
+
+ALTER TABLE merges ADD COLUMN merge_strategy TEXT
+    CHECK (merge_strategy IN ('squash', 'merge', 'rebase'));
+
+UPDATE merges SET merge_strategy = 'squash' WHERE merge_type = 'direct';
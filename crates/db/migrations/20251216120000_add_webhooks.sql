@@ -0,0 +1,46 @@
+-- Outbound webhooks, called when something happens on the board. A webhook without a project
+-- fires for every project. Every call is logged in webhook_deliveries and retried with
+-- backoff until the endpoint accepts it; the delivery is unique per event, so an event that
+-- is seen several times is still only sent once.
+CREATE TABLE webhooks (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB,
+    url         TEXT NOT NULL,
+    -- Encrypted; signs the payloads
+    secret      TEXT NOT NULL,
+    -- JSON array of the events the webhook is sent for; empty for all of them
+    events      TEXT NOT NULL DEFAULT '[]',
+    enabled     BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhooks_project_id ON webhooks(project_id);
+
+CREATE TABLE webhook_deliveries (
+    id               BLOB PRIMARY KEY,
+    webhook_id       BLOB NOT NULL,
+    event            TEXT NOT NULL
+                        CHECK (event IN ('task_status_changed', 'execution_finished', 'merge_completed', 'approval_requested')),
+    -- Identifies the occurrence of the event, e.g. the merge or execution process
+    event_key        TEXT NOT NULL,
+    -- JSON body, sent as is on every attempt
+    payload          TEXT NOT NULL,
+    status           TEXT NOT NULL DEFAULT 'pending'
+                        CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts         INTEGER NOT NULL DEFAULT 0,
+    -- HTTP status of the last attempt, unset if it got no response
+    response_status  INTEGER,
+    last_error       TEXT,
+    -- When a pending delivery is attempted next
+    next_attempt_at  TEXT,
+    delivered_at     TEXT,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    UNIQUE (webhook_id, event_key),
+    FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, created_at);
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries(status, next_attempt_at);
@@ -0,0 +1,21 @@
+-- Slack and Discord channels a project posts to when a coding agent finishes, fails or asks
+-- for approval. Messages are rendered from the channel's templates, falling back to built-in
+-- ones for events without a template.
+CREATE TABLE project_chat_channels (
+    id           BLOB PRIMARY KEY,
+    project_id   BLOB NOT NULL,
+    kind         TEXT NOT NULL CHECK (kind IN ('slack', 'discord')),
+    name         TEXT NOT NULL,
+    -- Encrypted incoming webhook URL of the channel
+    webhook_url  TEXT NOT NULL,
+    -- JSON array of the events posted to the channel; empty for all of them
+    events       TEXT NOT NULL DEFAULT '[]',
+    -- JSON object of message templates by event
+    templates    TEXT NOT NULL DEFAULT '{}',
+    enabled      BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_chat_channels_project_id ON project_chat_channels(project_id);
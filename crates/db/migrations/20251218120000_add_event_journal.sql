@@ -0,0 +1,10 @@
+-- Patches sent on the events stream, kept for a while so recent board changes can be replayed.
+-- Ids only grow, so they double as a cursor.
+CREATE TABLE event_journal (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- JSON Patch operations, as sent on the stream
+    patch       TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_event_journal_created_at ON event_journal(created_at);
@@ -0,0 +1,10 @@
+-- Size of each attempt's changes, recomputed when one of its execution processes finishes, so
+-- the board doesn't have to diff every attempt to show it.
+CREATE TABLE task_attempt_diff_stats (
+    task_attempt_id  BLOB PRIMARY KEY,
+    files_changed    INTEGER NOT NULL,
+    additions        INTEGER NOT NULL,
+    deletions        INTEGER NOT NULL,
+    computed_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,25 @@
+-- Rules deciding which of the coding agent's tool calls are approved or denied without asking
+-- the user, and what happens when the user doesn't answer in time
+ALTER TABLE projects ADD COLUMN approval_policy TEXT;
+
+-- Every decision on a tool call the coding agent asked approval for, whether made by the user,
+-- the project's approval policy or a timeout
+CREATE TABLE approval_decisions (
+    id                    BLOB PRIMARY KEY,
+    approval_id           TEXT NOT NULL,
+    execution_process_id  BLOB NOT NULL,
+    tool_name             TEXT NOT NULL,
+    -- JSON input of the tool call
+    tool_input            TEXT NOT NULL,
+    outcome               TEXT NOT NULL CHECK (outcome IN ('approved', 'denied', 'timed_out')),
+    decided_by            TEXT NOT NULL CHECK (decided_by IN ('user', 'policy', 'timeout')),
+    -- Name of the user who answered, when known
+    user_name             TEXT,
+    -- Reason the user gave for a denial, or the policy rule that applied
+    reason                TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_approval_decisions_execution_process_id
+    ON approval_decisions(execution_process_id);
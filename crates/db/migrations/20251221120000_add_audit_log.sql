@@ -0,0 +1,35 @@
+-- Every operation that discards or publishes work: force resets, worktree and branch deletions,
+-- merges, pushes and task deletions. Entries can't be changed or removed once written.
+CREATE TABLE audit_log (
+    id               BLOB PRIMARY KEY,
+    action           TEXT NOT NULL CHECK (action IN ('force_reset', 'worktree_delete', 'branch_delete', 'merge', 'push', 'task_delete')),
+    -- User the request acted for; unset for work the server started on its own
+    actor            TEXT,
+    -- Without foreign keys, so entries outlive the projects, tasks and attempts they are about
+    project_id       BLOB,
+    task_id          BLOB,
+    task_attempt_id  BLOB,
+    -- Commit, worktree path, branch or task title the operation acted on
+    target           TEXT NOT NULL,
+    -- JSON object of the operation's parameters
+    parameters       TEXT NOT NULL DEFAULT '{}',
+    -- Why the operation failed; unset when it succeeded
+    error            TEXT,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
+CREATE INDEX idx_audit_log_project_id ON audit_log(project_id);
+CREATE INDEX idx_audit_log_task_attempt_id ON audit_log(task_attempt_id);
+
+CREATE TRIGGER audit_log_no_update
+BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
+
+CREATE TRIGGER audit_log_no_delete
+BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
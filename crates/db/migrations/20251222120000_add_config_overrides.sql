@@ -0,0 +1,10 @@
+-- Settings changed at runtime, applied over the config file and environment variables
+CREATE TABLE config_overrides (
+    -- Dotted path of the setting, e.g. worktree_gc.retention_days
+    path        TEXT PRIMARY KEY,
+    -- JSON value of the setting
+    value       TEXT NOT NULL,
+    -- User the request acted for, when known
+    updated_by  TEXT,
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
@@ -0,0 +1,3 @@
+-- OS process id of the spawned process, so one left running by a server that died without
+-- stopping it can be found and killed at the next start
+ALTER TABLE execution_processes ADD COLUMN pid INTEGER;
@@ -1,10 +1,9 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::Path, str::FromStr, sync::Arc};
 
 use sqlx::{
     Error, Pool, Sqlite, SqlitePool,
     sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions},
 };
-use utils::assets::asset_dir;
 
 pub mod models;
 
@@ -14,18 +13,18 @@ pub struct DBService {
 }
 
 impl DBService {
-    pub async fn new() -> Result<DBService, Error> {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
+    pub async fn new(database_path: &Path) -> Result<DBService, Error> {
+        let database_url = format!("sqlite://{}", database_path.to_string_lossy());
         let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
         let pool = SqlitePool::connect_with(options).await?;
         sqlx::migrate!("./migrations").run(&pool).await?;
         Ok(DBService { pool })
     }
 
-    pub async fn new_with_after_connect<F>(after_connect: F) -> Result<DBService, Error>
+    pub async fn new_with_after_connect<F>(
+        database_path: &Path,
+        after_connect: F,
+    ) -> Result<DBService, Error>
     where
         F: for<'a> Fn(
                 &'a mut SqliteConnection,
@@ -35,11 +34,14 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let pool = Self::create_pool(Some(Arc::new(after_connect))).await?;
+        let pool = Self::create_pool(database_path, Some(Arc::new(after_connect))).await?;
         Ok(DBService { pool })
     }
 
-    async fn create_pool<F>(after_connect: Option<Arc<F>>) -> Result<Pool<Sqlite>, Error>
+    async fn create_pool<F>(
+        database_path: &Path,
+        after_connect: Option<Arc<F>>,
+    ) -> Result<Pool<Sqlite>, Error>
     where
         F: for<'a> Fn(
                 &'a mut SqliteConnection,
@@ -49,10 +51,7 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
+        let database_url = format!("sqlite://{}", database_path.to_string_lossy());
         let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
 
         let pool = if let Some(hook) = after_connect {
@@ -74,3 +73,24 @@ impl DBService {
         Ok(pool)
     }
 }
+
+/// A migrated in-memory database for tests
+#[cfg(test)]
+pub(crate) async fn test_pool() -> Pool<Sqlite> {
+    let pool = unmigrated_test_pool().await;
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    pool
+}
+
+/// An empty in-memory database for tests, to migrate as far as they need
+#[cfg(test)]
+pub(crate) async fn unmigrated_test_pool() -> Pool<Sqlite> {
+    // Every connection to `:memory:` opens a database of its own, so keep exactly one open
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
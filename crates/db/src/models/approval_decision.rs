@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "approval_outcome", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    Approved,
+    Denied,
+    TimedOut,
+}
+
+/// Who made an approval decision
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "approval_decider", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecider {
+    User,
+    /// A rule of the project's approval policy, without asking the user
+    Policy,
+    /// Nobody answered before the request timed out
+    Timeout,
+}
+
+/// A decision on a tool call the coding agent asked approval for
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ApprovalDecision {
+    pub id: Uuid,
+    pub approval_id: String,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    #[ts(type = "JsonValue")]
+    pub tool_input: sqlx::types::Json<Value>,
+    pub outcome: ApprovalOutcome,
+    pub decided_by: ApprovalDecider,
+    /// Name of the user who answered, when known
+    pub user_name: Option<String>,
+    /// Reason the user gave for a denial, or the policy rule that applied
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateApprovalDecision {
+    pub approval_id: String,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    pub tool_input: Value,
+    pub outcome: ApprovalOutcome,
+    pub decided_by: ApprovalDecider,
+    pub user_name: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl ApprovalDecision {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateApprovalDecision,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let tool_input = sqlx::types::Json(&data.tool_input);
+        sqlx::query_as!(
+            ApprovalDecision,
+            r#"INSERT INTO approval_decisions (id, approval_id, execution_process_id, tool_name, tool_input, outcome, decided_by, user_name, reason)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", approval_id, execution_process_id as "execution_process_id!: Uuid", tool_name, tool_input as "tool_input!: sqlx::types::Json<Value>", outcome as "outcome!: ApprovalOutcome", decided_by as "decided_by!: ApprovalDecider", user_name, reason, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.approval_id,
+            data.execution_process_id,
+            data.tool_name,
+            tool_input,
+            data.outcome,
+            data.decided_by,
+            data.user_name,
+            data.reason
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The latest decisions on tool calls of the project's coding agents, newest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalDecision,
+            r#"SELECT ad.id as "id!: Uuid", ad.approval_id, ad.execution_process_id as "execution_process_id!: Uuid", ad.tool_name, ad.tool_input as "tool_input!: sqlx::types::Json<Value>", ad.outcome as "outcome!: ApprovalOutcome", ad.decided_by as "decided_by!: ApprovalDecider", ad.user_name, ad.reason, ad.created_at as "created_at!: DateTime<Utc>"
+               FROM approval_decisions ad
+               JOIN execution_processes ep ON ep.id = ad.execution_process_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1
+               ORDER BY ad.created_at DESC
+               LIMIT $2"#,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Decisions on the attempt's tool calls, oldest first
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ApprovalDecision,
+            r#"SELECT ad.id as "id!: Uuid", ad.approval_id, ad.execution_process_id as "execution_process_id!: Uuid", ad.tool_name, ad.tool_input as "tool_input!: sqlx::types::Json<Value>", ad.outcome as "outcome!: ApprovalOutcome", ad.decided_by as "decided_by!: ApprovalDecider", ad.user_name, ad.reason, ad.created_at as "created_at!: DateTime<Utc>"
+               FROM approval_decisions ad
+               JOIN execution_processes ep ON ep.id = ad.execution_process_id
+               WHERE ep.task_attempt_id = $1
+               ORDER BY ad.created_at ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Attempts of one task started together with different executor profiles, to keep the best
+/// one and discard the rest
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AttemptComparison {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// The attempt that was kept; the others were discarded at `chosen_at`
+    pub chosen_attempt_id: Option<Uuid>,
+    pub chosen_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AttemptComparisonMember {
+    pub comparison_id: Uuid,
+    pub task_attempt_id: Uuid,
+    /// The profile the attempt was started with
+    pub executor_profile_id: sqlx::types::Json<ExecutorProfileId>,
+    pub position: i64,
+}
+
+impl AttemptComparison {
+    pub async fn create(pool: &SqlitePool, task_id: Uuid) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            AttemptComparison,
+            r#"INSERT INTO attempt_comparisons (id, task_id)
+               VALUES ($1, $2)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", chosen_attempt_id as "chosen_attempt_id: Uuid", chosen_at as "chosen_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            task_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptComparison,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", chosen_attempt_id as "chosen_attempt_id: Uuid", chosen_at as "chosen_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM attempt_comparisons
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The task's comparisons, newest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptComparison,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", chosen_attempt_id as "chosen_attempt_id: Uuid", chosen_at as "chosen_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM attempt_comparisons
+               WHERE task_id = $1
+               ORDER BY created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn add_member(
+        pool: &SqlitePool,
+        comparison_id: Uuid,
+        task_attempt_id: Uuid,
+        executor_profile_id: &ExecutorProfileId,
+        position: i64,
+    ) -> Result<(), sqlx::Error> {
+        let executor_profile_id = sqlx::types::Json(executor_profile_id);
+        sqlx::query!(
+            r#"INSERT INTO attempt_comparison_members (comparison_id, task_attempt_id, executor_profile_id, position)
+               VALUES ($1, $2, $3, $4)"#,
+            comparison_id,
+            task_attempt_id,
+            executor_profile_id,
+            position
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The compared attempts in the order they were started
+    pub async fn members(
+        pool: &SqlitePool,
+        comparison_id: Uuid,
+    ) -> Result<Vec<AttemptComparisonMember>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptComparisonMember,
+            r#"SELECT comparison_id as "comparison_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", executor_profile_id as "executor_profile_id!: sqlx::types::Json<ExecutorProfileId>", position
+               FROM attempt_comparison_members
+               WHERE comparison_id = $1
+               ORDER BY position ASC"#,
+            comparison_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record the kept attempt. Returns false if one was already chosen.
+    pub async fn choose(
+        pool: &SqlitePool,
+        id: Uuid,
+        attempt_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE attempt_comparisons
+               SET chosen_attempt_id = $2, chosen_at = datetime('now', 'subsec')
+               WHERE id = $1 AND chosen_at IS NULL"#,
+            id,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
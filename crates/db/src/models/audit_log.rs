@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Operations that discard or publish work
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "audit_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// A worktree reset to an earlier commit, discarding what came after it
+    ForceReset,
+    WorktreeDelete,
+    BranchDelete,
+    Merge,
+    Push,
+    TaskDelete,
+}
+
+/// One destructive operation, as recorded when it was done
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    pub action: AuditAction,
+    /// User the request acted for; unset for work the server started on its own
+    pub actor: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    /// Commit, worktree path, branch or task title the operation acted on
+    pub target: String,
+    #[ts(type = "JsonValue")]
+    pub parameters: sqlx::types::Json<Value>,
+    /// Why the operation failed; unset when it succeeded
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An entry to record. The task and project are looked up from the attempt when unset.
+#[derive(Debug, Clone)]
+pub struct CreateAuditLogEntry {
+    pub action: AuditAction,
+    pub actor: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    pub target: String,
+    pub parameters: Value,
+    pub error: Option<String>,
+}
+
+/// Narrows [`AuditLogEntry::find`]; unset fields match every entry
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub action: Option<AuditAction>,
+    pub actor: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl AuditLogEntry {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateAuditLogEntry,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let parameters = sqlx::types::Json(&data.parameters);
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"INSERT INTO audit_log (id, action, actor, project_id, task_id, task_attempt_id, target, parameters, error)
+               VALUES (
+                   $1, $2, $3,
+                   COALESCE($4, (SELECT t.project_id FROM tasks t WHERE t.id = COALESCE($5, (SELECT ta.task_id FROM task_attempts ta WHERE ta.id = $6)))),
+                   COALESCE($5, (SELECT ta.task_id FROM task_attempts ta WHERE ta.id = $6)),
+                   $6, $7, $8, $9
+               )
+               RETURNING id as "id!: Uuid", action as "action!: AuditAction", actor, project_id as "project_id?: Uuid", task_id as "task_id?: Uuid", task_attempt_id as "task_attempt_id?: Uuid", target, parameters as "parameters!: sqlx::types::Json<Value>", error, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.action,
+            data.actor,
+            data.project_id,
+            data.task_id,
+            data.task_attempt_id,
+            data.target,
+            parameters,
+            data.error
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Entries matching `filter`, newest first
+    pub async fn find(
+        pool: &SqlitePool,
+        filter: &AuditLogFilter,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditLogEntry,
+            r#"SELECT id as "id!: Uuid", action as "action!: AuditAction", actor, project_id as "project_id?: Uuid", task_id as "task_id?: Uuid", task_attempt_id as "task_attempt_id?: Uuid", target, parameters as "parameters!: sqlx::types::Json<Value>", error, created_at as "created_at!: DateTime<Utc>"
+               FROM audit_log
+               WHERE ($1 IS NULL OR action = $1)
+                 AND ($2 IS NULL OR actor = $2)
+                 AND ($3 IS NULL OR project_id = $3)
+                 AND ($4 IS NULL OR task_id = $4)
+                 AND ($5 IS NULL OR task_attempt_id = $5)
+                 AND ($6 IS NULL OR created_at >= datetime($6))
+                 AND ($7 IS NULL OR created_at <= datetime($7))
+               ORDER BY created_at DESC
+               LIMIT $8"#,
+            filter.action,
+            filter.actor,
+            filter.project_id,
+            filter.task_id,
+            filter.task_attempt_id,
+            filter.after,
+            filter.before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,293 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// What happened on the board that makes a rule fire
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "automation_trigger", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationTrigger {
+    /// An attempt was merged directly or its pull request was merged
+    AttemptMerged,
+    /// A coding agent run of an attempt finished successfully
+    CodingAgentCompleted,
+    /// A coding agent run of an attempt failed
+    CodingAgentFailed,
+}
+
+/// Checked against the task when the trigger fires; a rule only runs its actions when all of
+/// its conditions hold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AutomationCondition {
+    TaskStatus {
+        status: TaskStatus,
+    },
+    HasTag {
+        tag_name: String,
+    },
+    /// The attempt's coding agent has failed at least `count` times
+    FailedRunsAtLeast {
+        count: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AutomationAction {
+    SetTaskStatus {
+        status: TaskStatus,
+    },
+    /// Add the tag with this name to the task, creating the tag if none exists
+    AddTag {
+        tag_name: String,
+    },
+    /// Close the GitHub issues the task's title or description refers to, as `#123` or by URL
+    CloseGithubIssue,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AutomationRule {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub trigger: AutomationTrigger,
+    #[ts(type = "Array<AutomationCondition>")]
+    pub conditions: sqlx::types::Json<Vec<AutomationCondition>>,
+    #[ts(type = "Array<AutomationAction>")]
+    pub actions: sqlx::types::Json<Vec<AutomationAction>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateAutomationRule {
+    pub project_id: Uuid,
+    pub name: String,
+    pub trigger: AutomationTrigger,
+    #[serde(default)]
+    #[ts(optional)]
+    pub conditions: Vec<AutomationCondition>,
+    pub actions: Vec<AutomationAction>,
+    #[serde(default = "default_enabled")]
+    #[ts(optional)]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateAutomationRule {
+    pub name: Option<String>,
+    pub trigger: Option<AutomationTrigger>,
+    pub conditions: Option<Vec<AutomationCondition>>,
+    pub actions: Option<Vec<AutomationAction>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "automation_rule_run_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AutomationRuleRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// One firing of a rule
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct AutomationRuleRun {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    /// The event that fired the rule, such as `merge:<id>`
+    pub event_key: String,
+    pub task_id: Uuid,
+    pub task_attempt_id: Option<Uuid>,
+    pub status: AutomationRuleRunStatus,
+    /// What the actions did, or why one of them failed
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl AutomationRule {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AutomationRule,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, trigger as "trigger!: AutomationTrigger", conditions as "conditions!: sqlx::types::Json<Vec<AutomationCondition>>", actions as "actions!: sqlx::types::Json<Vec<AutomationAction>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM automation_rules
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Enabled rules of the project that fire on `trigger`
+    pub async fn find_enabled_by_trigger(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        trigger: AutomationTrigger,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AutomationRule,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, trigger as "trigger!: AutomationTrigger", conditions as "conditions!: sqlx::types::Json<Vec<AutomationCondition>>", actions as "actions!: sqlx::types::Json<Vec<AutomationAction>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM automation_rules
+               WHERE project_id = $1 AND trigger = $2 AND enabled = TRUE
+               ORDER BY created_at ASC"#,
+            project_id,
+            trigger
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AutomationRule,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, trigger as "trigger!: AutomationTrigger", conditions as "conditions!: sqlx::types::Json<Vec<AutomationCondition>>", actions as "actions!: sqlx::types::Json<Vec<AutomationAction>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM automation_rules
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateAutomationRule,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let conditions = sqlx::types::Json(&data.conditions);
+        let actions = sqlx::types::Json(&data.actions);
+        sqlx::query_as!(
+            AutomationRule,
+            r#"INSERT INTO automation_rules (id, project_id, name, trigger, conditions, actions, enabled)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, trigger as "trigger!: AutomationTrigger", conditions as "conditions!: sqlx::types::Json<Vec<AutomationCondition>>", actions as "actions!: sqlx::types::Json<Vec<AutomationAction>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.name,
+            data.trigger,
+            conditions,
+            actions,
+            data.enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        name: &str,
+        trigger: AutomationTrigger,
+        conditions: &[AutomationCondition],
+        actions: &[AutomationAction],
+        enabled: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let conditions = sqlx::types::Json(conditions);
+        let actions = sqlx::types::Json(actions);
+        sqlx::query_as!(
+            AutomationRule,
+            r#"UPDATE automation_rules
+               SET name = $2, trigger = $3, conditions = $4, actions = $5, enabled = $6, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, trigger as "trigger!: AutomationTrigger", conditions as "conditions!: sqlx::types::Json<Vec<AutomationCondition>>", actions as "actions!: sqlx::types::Json<Vec<AutomationAction>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            trigger,
+            conditions,
+            actions,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM automation_rules WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl AutomationRuleRun {
+    /// Log that `rule_id` fired for `event_key`. Returns `None` if it already fired for that
+    /// event, so every event runs a rule's actions at most once.
+    pub async fn start(
+        pool: &SqlitePool,
+        rule_id: Uuid,
+        event_key: &str,
+        task_id: Uuid,
+        task_attempt_id: Option<Uuid>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            AutomationRuleRun,
+            r#"INSERT INTO automation_rule_runs (id, rule_id, event_key, task_id, task_attempt_id)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (rule_id, event_key) DO NOTHING
+               RETURNING id as "id!: Uuid", rule_id as "rule_id!: Uuid", event_key, task_id as "task_id!: Uuid", task_attempt_id as "task_attempt_id: Uuid", status as "status!: AutomationRuleRunStatus", message, created_at as "created_at!: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>""#,
+            id,
+            rule_id,
+            event_key,
+            task_id,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn complete(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: AutomationRuleRunStatus,
+        message: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE automation_rule_runs
+               SET status = $2, message = $3, completed_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            status,
+            message
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The rule's most recent firings, newest first
+    pub async fn find_by_rule_id(
+        pool: &SqlitePool,
+        rule_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AutomationRuleRun,
+            r#"SELECT id as "id!: Uuid", rule_id as "rule_id!: Uuid", event_key, task_id as "task_id!: Uuid", task_attempt_id as "task_attempt_id: Uuid", status as "status!: AutomationRuleRunStatus", message, created_at as "created_at!: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>"
+               FROM automation_rule_runs
+               WHERE rule_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+            rule_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Result of the latest background check of an attempt against its base branch
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct BaseBranchSync {
+    pub task_attempt_id: Uuid,
+    /// Commits on the base branch that the attempt's branch does not have yet
+    pub commits_behind: i64,
+    /// Set when the attempt was last rebased automatically
+    pub rebased_at: Option<DateTime<Utc>>,
+    /// Why the last fetch or automatic rebase failed, if it did
+    pub error: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+impl BaseBranchSync {
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            BaseBranchSync,
+            r#"SELECT task_attempt_id as "task_attempt_id!: Uuid", commits_behind as "commits_behind!: i64", rebased_at as "rebased_at: DateTime<Utc>", error, synced_at as "synced_at!: DateTime<Utc>"
+               FROM base_branch_syncs
+               WHERE task_attempt_id = $1"#,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record a check. `rebased` marks that the attempt was just rebased; otherwise the
+    /// time of the previous automatic rebase is kept.
+    pub async fn record(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        commits_behind: i64,
+        rebased: bool,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO base_branch_syncs (task_attempt_id, commits_behind, rebased_at, error, synced_at)
+               VALUES ($1, $2, CASE WHEN $3 THEN datetime('now', 'subsec') END, $4, datetime('now', 'subsec'))
+               ON CONFLICT(task_attempt_id) DO UPDATE SET
+                   commits_behind = excluded.commits_behind,
+                   rebased_at = COALESCE(excluded.rebased_at, base_branch_syncs.rebased_at),
+                   error = excluded.error,
+                   synced_at = excluded.synced_at"#,
+            task_attempt_id,
+            commits_behind,
+            rebased,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "chat_channel_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ChatChannelKind {
+    Slack,
+    Discord,
+}
+
+/// What a chat channel is told about
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatNotificationEvent {
+    /// A coding agent finished its run and the attempt is ready for review
+    AgentCompleted,
+    /// A coding agent run failed
+    AgentFailed,
+    /// A coding agent is waiting for the user to approve a tool call
+    ApprovalRequested,
+}
+
+/// Message templates by event; events without one use the built-in message
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+pub struct ChatMessageTemplates {
+    #[serde(default)]
+    pub agent_completed: Option<String>,
+    #[serde(default)]
+    pub agent_failed: Option<String>,
+    #[serde(default)]
+    pub approval_requested: Option<String>,
+}
+
+impl ChatMessageTemplates {
+    pub fn for_event(&self, event: ChatNotificationEvent) -> Option<&str> {
+        match event {
+            ChatNotificationEvent::AgentCompleted => self.agent_completed.as_deref(),
+            ChatNotificationEvent::AgentFailed => self.agent_failed.as_deref(),
+            ChatNotificationEvent::ApprovalRequested => self.approval_requested.as_deref(),
+        }
+        .filter(|template| !template.trim().is_empty())
+    }
+}
+
+/// A Slack or Discord channel the project posts to, through an incoming webhook
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ChatChannel {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub kind: ChatChannelKind,
+    pub name: String,
+    /// Encrypted
+    #[serde(skip)]
+    #[ts(skip)]
+    pub webhook_url: String,
+    /// Events posted to the channel; empty for all of them
+    #[ts(type = "Array<ChatNotificationEvent>")]
+    pub events: sqlx::types::Json<Vec<ChatNotificationEvent>>,
+    /// Templates may use `{task}`, `{project}`, `{executor}`, `{branch}` and `{link}`, the
+    /// address of the attempt; approval requests also `{tool}`
+    #[ts(type = "ChatMessageTemplates")]
+    pub templates: sqlx::types::Json<ChatMessageTemplates>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateChatChannel {
+    pub kind: ChatChannelKind,
+    pub name: String,
+    /// Incoming webhook URL of the channel; never returned by the API
+    pub webhook_url: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub events: Vec<ChatNotificationEvent>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub templates: ChatMessageTemplates,
+    #[serde(default = "default_enabled")]
+    #[ts(optional)]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateChatChannel {
+    pub name: Option<String>,
+    /// Leave unset to keep the existing URL
+    pub webhook_url: Option<String>,
+    pub events: Option<Vec<ChatNotificationEvent>>,
+    pub templates: Option<ChatMessageTemplates>,
+    pub enabled: Option<bool>,
+}
+
+impl ChatChannel {
+    /// Whether the channel is told about `event`
+    pub fn posts_on(&self, event: ChatNotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ChatChannel,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", kind as "kind!: ChatChannelKind", name, webhook_url, events as "events!: sqlx::types::Json<Vec<ChatNotificationEvent>>", templates as "templates!: sqlx::types::Json<ChatMessageTemplates>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_chat_channels
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_enabled_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ChatChannel,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", kind as "kind!: ChatChannelKind", name, webhook_url, events as "events!: sqlx::types::Json<Vec<ChatNotificationEvent>>", templates as "templates!: sqlx::types::Json<ChatMessageTemplates>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_chat_channels
+               WHERE project_id = $1 AND enabled = TRUE
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id_and_project_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ChatChannel,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", kind as "kind!: ChatChannelKind", name, webhook_url, events as "events!: sqlx::types::Json<Vec<ChatNotificationEvent>>", templates as "templates!: sqlx::types::Json<ChatMessageTemplates>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_chat_channels
+               WHERE id = $1 AND project_id = $2"#,
+            id,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// `webhook_url` is stored as given, so it should already be encrypted
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &SqlitePool,
+        id: Uuid,
+        project_id: Uuid,
+        kind: ChatChannelKind,
+        name: &str,
+        webhook_url: &str,
+        events: &[ChatNotificationEvent],
+        templates: &ChatMessageTemplates,
+        enabled: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let events = sqlx::types::Json(events);
+        let templates = sqlx::types::Json(templates);
+        sqlx::query_as!(
+            ChatChannel,
+            r#"INSERT INTO project_chat_channels (id, project_id, kind, name, webhook_url, events, templates, enabled)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", kind as "kind!: ChatChannelKind", name, webhook_url, events as "events!: sqlx::types::Json<Vec<ChatNotificationEvent>>", templates as "templates!: sqlx::types::Json<ChatMessageTemplates>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            kind,
+            name,
+            webhook_url,
+            events,
+            templates,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        name: &str,
+        webhook_url: &str,
+        events: &[ChatNotificationEvent],
+        templates: &ChatMessageTemplates,
+        enabled: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let events = sqlx::types::Json(events);
+        let templates = sqlx::types::Json(templates);
+        sqlx::query_as!(
+            ChatChannel,
+            r#"UPDATE project_chat_channels
+               SET name = $2, webhook_url = $3, events = $4, templates = $5, enabled = $6, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", kind as "kind!: ChatChannelKind", name, webhook_url, events as "events!: sqlx::types::Json<Vec<ChatNotificationEvent>>", templates as "templates!: sqlx::types::Json<ChatMessageTemplates>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            webhook_url,
+            events,
+            templates,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM project_chat_channels WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
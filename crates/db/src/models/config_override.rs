@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+
+/// A setting changed at runtime, applied over the config file and environment variables
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ConfigOverride {
+    /// Dotted path of the setting, e.g. `worktree_gc.retention_days`
+    pub path: String,
+    #[ts(type = "JsonValue")]
+    pub value: sqlx::types::Json<Value>,
+    /// User the request acted for, when known
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct SetConfigOverride {
+    pub path: String,
+    #[ts(type = "JsonValue")]
+    pub value: Value,
+}
+
+impl ConfigOverride {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ConfigOverride,
+            r#"SELECT path as "path!", value as "value!: sqlx::types::Json<Value>", updated_by, updated_at as "updated_at!: DateTime<Utc>"
+               FROM config_overrides
+               ORDER BY path ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        data: &SetConfigOverride,
+        updated_by: Option<String>,
+    ) -> Result<Self, sqlx::Error> {
+        let value = sqlx::types::Json(&data.value);
+        sqlx::query_as!(
+            ConfigOverride,
+            r#"INSERT INTO config_overrides (path, value, updated_by)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(path) DO UPDATE SET
+                   value = excluded.value,
+                   updated_by = excluded.updated_by,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING path as "path!", value as "value!: sqlx::types::Json<Value>", updated_by, updated_at as "updated_at!: DateTime<Utc>""#,
+            data.path,
+            value,
+            updated_by
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, path: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM config_overrides WHERE path = $1", path)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Endpoint called to trigger a preview deployment for a project's attempts.
+///
+/// The URL template may contain `{project_id}`, `{task_id}`, `{attempt_id}`,
+/// `{branch}`, `{commit}` and `{event}` placeholders.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct DeployHook {
+    pub project_id: Uuid,
+    pub url_template: String,
+    /// Sent as a bearer token; never returned by the API
+    #[serde(skip_serializing, default)]
+    #[ts(skip)]
+    pub token: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertDeployHook {
+    pub url_template: String,
+    /// Leave unset to keep the existing token
+    pub token: Option<String>,
+}
+
+impl DeployHook {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DeployHook,
+            r#"SELECT project_id as "project_id!: Uuid", url_template, token, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM deploy_hooks
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertDeployHook,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            DeployHook,
+            r#"INSERT INTO deploy_hooks (project_id, url_template, token)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   url_template = excluded.url_template,
+                   token = COALESCE(excluded.token, deploy_hooks.token),
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", url_template, token, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.url_template,
+            data.token
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM deploy_hooks WHERE project_id = $1", project_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
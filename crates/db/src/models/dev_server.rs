@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// The port allocated to an attempt's dev server and its latest process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct DevServer {
+    pub task_attempt_id: Uuid,
+    /// Latest dev server process started for the attempt
+    pub execution_process_id: Option<Uuid>,
+    pub port: u16,
+    /// Automatic restarts after crashes since the dev server was last started by hand
+    pub restart_count: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DevServer {
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DevServer,
+            r#"SELECT task_attempt_id as "task_attempt_id!: Uuid", execution_process_id as "execution_process_id: Uuid", port as "port!: u16", restart_count as "restart_count!: u32", updated_at as "updated_at!: DateTime<Utc>"
+               FROM dev_servers
+               WHERE task_attempt_id = $1"#,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Ports held by dev servers that are currently running
+    pub async fn ports_in_use(pool: &SqlitePool) -> Result<Vec<u16>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT ds.port as "port!: u16"
+               FROM dev_servers ds
+               JOIN execution_processes ep ON ep.id = ds.execution_process_id
+               WHERE ep.status = 'running'"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Dev servers whose latest process was still running when the server last stopped: killed
+    /// by the shutdown, or left running if the server died without one
+    pub async fn find_interrupted(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            DevServer,
+            r#"SELECT ds.task_attempt_id as "task_attempt_id!: Uuid", ds.execution_process_id as "execution_process_id: Uuid", ds.port as "port!: u16", ds.restart_count as "restart_count!: u32", ds.updated_at as "updated_at!: DateTime<Utc>"
+               FROM dev_servers ds
+               JOIN execution_processes ep ON ep.id = ds.execution_process_id
+               WHERE ep.status = 'running'
+                  OR (ep.status = 'killed' AND json_extract(ep.kill_reason, '$.type') = 'shutdown')"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record that a dev server process was started on `port`
+    pub async fn record_start(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        execution_process_id: Uuid,
+        port: u16,
+        restart_count: u32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO dev_servers (task_attempt_id, execution_process_id, port, restart_count, updated_at)
+               VALUES ($1, $2, $3, $4, datetime('now', 'subsec'))
+               ON CONFLICT(task_attempt_id) DO UPDATE SET
+                   execution_process_id = excluded.execution_process_id,
+                   port = excluded.port,
+                   restart_count = excluded.restart_count,
+                   updated_at = excluded.updated_at"#,
+            task_attempt_id,
+            execution_process_id,
+            port,
+            restart_count
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -48,6 +48,9 @@ pub struct Draft {
     pub variant: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image_ids: Option<Vec<Uuid>>,
+    /// Captions of attached images, given to the agent with each image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_captions: Option<HashMap<Uuid, String>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub version: i64,
@@ -64,6 +67,7 @@ struct DraftRow {
     pub sending: bool,
     pub variant: Option<String>,
     pub image_ids: Option<String>,
+    pub image_captions: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub version: i64,
@@ -75,6 +79,10 @@ impl From<DraftRow> for Draft {
             .image_ids
             .as_deref()
             .and_then(|s| serde_json::from_str::<Vec<Uuid>>(s).ok());
+        let image_captions = r
+            .image_captions
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<HashMap<Uuid, String>>(s).ok());
         Draft {
             id: r.id,
             task_attempt_id: r.task_attempt_id,
@@ -85,6 +93,7 @@ impl From<DraftRow> for Draft {
             sending: r.sending,
             variant: r.variant,
             image_ids,
+            image_captions,
             created_at: r.created_at,
             updated_at: r.updated_at,
             version: r.version,
@@ -101,6 +110,7 @@ pub struct UpsertDraft {
     pub queued: bool,
     pub variant: Option<String>,
     pub image_ids: Option<Vec<Uuid>>,
+    pub image_captions: Option<HashMap<Uuid, String>>,
 }
 
 impl Draft {
@@ -117,6 +127,7 @@ impl Draft {
                 sending                  as "sending!: bool",
                 variant,
                 image_ids,
+                image_captions,
                 created_at               as "created_at!: DateTime<Utc>",
                 updated_at               as "updated_at!: DateTime<Utc>",
                 version                  as "version!: i64"
@@ -147,6 +158,7 @@ impl Draft {
                 sending                  as "sending!: bool",
                 variant,
                 image_ids,
+                image_captions,
                 created_at               as "created_at!: DateTime<Utc>",
                 updated_at               as "updated_at!: DateTime<Utc>",
                 version                  as "version!: i64"
@@ -173,19 +185,24 @@ impl Draft {
             .image_ids
             .as_ref()
             .map(|ids| serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string()));
+        let image_captions_json = data
+            .image_captions
+            .as_ref()
+            .map(|captions| serde_json::to_string(captions).unwrap_or_else(|_| "{}".to_string()));
         let draft_type_str = data.draft_type.as_str();
         let prompt = data.prompt.clone();
         let variant = data.variant.clone();
         sqlx::query_as!(
             DraftRow,
-            r#"INSERT INTO drafts (id, task_attempt_id, draft_type, retry_process_id, prompt, queued, variant, image_ids)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            r#"INSERT INTO drafts (id, task_attempt_id, draft_type, retry_process_id, prompt, queued, variant, image_ids, image_captions)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
                ON CONFLICT(task_attempt_id, draft_type) DO UPDATE SET
                  retry_process_id = excluded.retry_process_id,
                  prompt = excluded.prompt,
                  queued = excluded.queued,
                  variant = excluded.variant,
                  image_ids = excluded.image_ids,
+                 image_captions = excluded.image_captions,
                  version = drafts.version + 1
                RETURNING
                  id                       as "id!: Uuid",
@@ -197,6 +214,7 @@ impl Draft {
                  sending                  as "sending!: bool",
                  variant,
                  image_ids,
+                 image_captions,
                  created_at               as "created_at!: DateTime<Utc>",
                  updated_at               as "updated_at!: DateTime<Utc>",
                  version                  as "version!: i64""#,
@@ -207,7 +225,8 @@ impl Draft {
             prompt,
             data.queued,
             variant,
-            image_ids_json
+            image_ids_json,
+            image_captions_json
         )
         .fetch_one(pool)
         .await
@@ -226,7 +245,7 @@ impl Draft {
                 // Follow-up drafts: update to empty
                 sqlx::query(
                     r#"UPDATE drafts
-                       SET prompt = '', queued = 0, sending = 0, image_ids = NULL, updated_at = CURRENT_TIMESTAMP, version = version + 1
+                       SET prompt = '', queued = 0, sending = 0, image_ids = NULL, image_captions = NULL, updated_at = CURRENT_TIMESTAMP, version = version + 1
                      WHERE task_attempt_id = ? AND draft_type = ?"#,
                 )
                 .bind(task_attempt_id)
@@ -256,6 +275,27 @@ impl Draft {
         Ok(())
     }
 
+    /// Attempts with a queued follow-up that is waiting to be sent, limited to projects that
+    /// configure quiet hours (the only reason a queued follow-up is held back)
+    pub async fn find_held_follow_up_attempt_ids(
+        pool: &SqlitePool,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT d.task_attempt_id as "task_attempt_id!: Uuid"
+              FROM drafts d
+              JOIN task_attempts ta ON ta.id = d.task_attempt_id
+              JOIN tasks t ON t.id = ta.task_id
+              JOIN projects p ON p.id = t.project_id
+             WHERE d.draft_type = 'follow_up'
+               AND d.queued = 1
+               AND d.sending = 0
+               AND TRIM(d.prompt) != ''
+               AND p.quiet_hours IS NOT NULL"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Attempt to atomically mark this draft as "sending" if it's currently queued and non-empty.
     /// Returns true if the row was updated (we acquired the send lock), false otherwise.
     pub async fn try_mark_sending(
@@ -283,6 +323,7 @@ impl Draft {
 
     /// Partial update on a draft by attempt and type. Updates only provided fields
     /// and bumps `updated_at` and `version` when any change occurs.
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_partial(
         pool: &SqlitePool,
         task_attempt_id: Uuid,
@@ -290,12 +331,14 @@ impl Draft {
         prompt: Option<String>,
         variant: Option<Option<String>>,
         image_ids: Option<Vec<Uuid>>,
+        image_captions: Option<HashMap<Uuid, String>>,
         retry_process_id: Option<Uuid>,
     ) -> Result<(), sqlx::Error> {
         if retry_process_id.is_none()
             && prompt.is_none()
             && variant.is_none()
             && image_ids.is_none()
+            && image_captions.is_none()
         {
             return Ok(());
         }
@@ -322,6 +365,12 @@ impl Draft {
             separated.push("image_ids = ");
             separated.push_bind_unseparated(image_ids_json);
         }
+        if let Some(captions) = image_captions {
+            let image_captions_json =
+                serde_json::to_string(&captions).unwrap_or_else(|_| "{}".to_string());
+            separated.push("image_captions = ");
+            separated.push_bind_unseparated(image_captions_json);
+        }
         separated.push("updated_at = CURRENT_TIMESTAMP");
         separated.push("version = version + 1");
 
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+
+/// A patch sent on the events stream
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct EventJournalEntry {
+    /// Increases with every entry, so it can be used as a cursor
+    pub id: i64,
+    /// JSON Patch operations, as sent on the stream
+    #[ts(type = "JsonValue")]
+    pub patch: sqlx::types::Json<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EventJournalEntry {
+    pub async fn append(pool: &SqlitePool, patches: &[Value]) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for patch in patches {
+            let patch = sqlx::types::Json(patch);
+            sqlx::query!("INSERT INTO event_journal (patch) VALUES ($1)", patch)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Entries after the entry `after_id`, oldest first
+    pub async fn find_after_id(
+        pool: &SqlitePool,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            EventJournalEntry,
+            r#"SELECT id as "id!: i64", patch as "patch!: sqlx::types::Json<Value>", created_at as "created_at!: DateTime<Utc>"
+               FROM event_journal
+               WHERE id > $1
+               ORDER BY id ASC
+               LIMIT $2"#,
+            after_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Entries made at or after `since`, oldest first
+    pub async fn find_since(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            EventJournalEntry,
+            r#"SELECT id as "id!: i64", patch as "patch!: sqlx::types::Json<Value>", created_at as "created_at!: DateTime<Utc>"
+               FROM event_journal
+               WHERE created_at >= datetime($1)
+               ORDER BY id ASC
+               LIMIT $2"#,
+            since,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete_before(
+        pool: &SqlitePool,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM event_journal WHERE created_at < datetime($1)",
+            before
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete all but the latest `keep` entries
+    pub async fn delete_all_but_latest(pool: &SqlitePool, keep: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM event_journal
+               WHERE id NOT IN (SELECT id FROM event_journal ORDER BY id DESC LIMIT $1)"#,
+            keep
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
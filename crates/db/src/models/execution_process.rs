@@ -5,7 +5,7 @@ use executors::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -33,6 +33,8 @@ pub enum ExecutionProcessError {
 #[serde(rename_all = "lowercase")]
 #[ts(use_ts_enum)]
 pub enum ExecutionProcessStatus {
+    /// Waiting for a free slot under the concurrency limit
+    Queued,
     Running,
     Completed,
     Failed,
@@ -47,6 +49,22 @@ pub enum ExecutionProcessRunReason {
     CleanupScript,
     CodingAgent,
     DevServer,
+    PostMergeScript,
+    TestScript,
+}
+
+/// Why the server stopped a process on its own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KillReason {
+    /// Ran longer than the configured maximum duration
+    TimeLimit { limit_secs: u32 },
+    /// The process and its children used more memory than allowed
+    MemoryLimit { limit_mb: u32, used_mb: u32 },
+    /// Coding agent produced no output for longer than the stall timeout
+    Stalled { timeout_secs: u32 },
+    /// Still running when the server shut down
+    Shutdown,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -68,6 +86,18 @@ pub struct ExecutionProcess {
     pub dropped: bool,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Set while a running coding agent has produced no output for longer
+    /// than the configured stall timeout
+    pub stalled_at: Option<DateTime<Utc>>,
+    /// Why the server stopped the process on its own, if it did
+    #[ts(type = "KillReason | null")]
+    pub kill_reason: Option<sqlx::types::Json<KillReason>>,
+    /// 1-based place in the queue while the process is queued
+    pub queue_position: Option<u32>,
+    /// The failed process this one re-runs under the project's retry policy
+    pub retry_of: Option<Uuid>,
+    /// The user who started the process; unset when the server started it on its own
+    pub started_by: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -77,6 +107,10 @@ pub struct CreateExecutionProcess {
     pub task_attempt_id: Uuid,
     pub executor_action: ExecutorAction,
     pub run_reason: ExecutionProcessRunReason,
+    /// The failed process this one re-runs, if it is a retry
+    pub retry_of: Option<Uuid>,
+    /// The user who started it, if a user did
+    pub started_by: Option<String>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -101,6 +135,36 @@ pub enum ExecutorActionField {
     Other(Value),
 }
 
+/// A process found by [`ExecutionProcess::search`], with the task and executor it ran for
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ExecutionProcessSearchResult {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub executor: String,
+    pub run_reason: ExecutionProcessRunReason,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    #[ts(type = "KillReason | null")]
+    pub kill_reason: Option<sqlx::types::Json<KillReason>>,
+    pub retry_of: Option<Uuid>,
+    pub started_by: Option<String>,
+}
+
+/// Narrows [`ExecutionProcess::search`]; unset fields match every process
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionProcessFilter {
+    pub status: Option<ExecutionProcessStatus>,
+    pub run_reason: Option<ExecutionProcessRunReason>,
+    pub started_after: Option<DateTime<Utc>>,
+    pub started_before: Option<DateTime<Utc>>,
+    pub executor: Option<String>,
+    pub exit_code: Option<i64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MissingBeforeContext {
     pub id: Uuid,
@@ -116,7 +180,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", stalled_at as "stalled_at?: DateTime<Utc>", kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", queue_position as "queue_position?: u32", retry_of as "retry_of?: Uuid", started_by,
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE id = ?"#,
             id
@@ -192,7 +256,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", stalled_at as "stalled_at?: DateTime<Utc>", kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", queue_position as "queue_position?: u32", retry_of as "retry_of?: Uuid", started_by,
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE rowid = ?"#,
             rowid
@@ -220,6 +284,11 @@ impl ExecutionProcess {
                       dropped,
                       started_at      as "started_at!: DateTime<Utc>",
                       completed_at    as "completed_at?: DateTime<Utc>",
+                      stalled_at      as "stalled_at?: DateTime<Utc>",
+                      kill_reason     as "kill_reason?: sqlx::types::Json<KillReason>",
+                      queue_position  as "queue_position?: u32",
+                      retry_of        as "retry_of?: Uuid",
+                      started_by,
                       created_at      as "created_at!: DateTime<Utc>",
                       updated_at      as "updated_at!: DateTime<Utc>"
                FROM execution_processes
@@ -238,7 +307,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", stalled_at as "stalled_at?: DateTime<Utc>", kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", queue_position as "queue_position?: u32", retry_of as "retry_of?: Uuid", started_by,
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE status = 'running' ORDER BY created_at ASC"#,
         )
@@ -246,6 +315,27 @@ impl ExecutionProcess {
         .await
     }
 
+    /// Running and queued execution processes, optionally only the project's
+    pub async fn find_active(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                      ep.before_head_commit, ep.after_head_commit, ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
+                      ep.dropped, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.stalled_at as "stalled_at?: DateTime<Utc>", ep.kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", ep.queue_position as "queue_position?: u32", ep.retry_of as "retry_of?: Uuid", ep.started_by, ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ep.task_attempt_id = ta.id
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE ep.status IN ('running', 'queued') AND ($1 IS NULL OR t.project_id = $1)
+               ORDER BY ep.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find running dev servers for a specific project
     pub async fn find_running_dev_servers_by_project(
         pool: &SqlitePool,
@@ -255,7 +345,7 @@ impl ExecutionProcess {
             ExecutionProcess,
             r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                       ep.before_head_commit, ep.after_head_commit, ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
-                      ep.dropped, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
+                      ep.dropped, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.stalled_at as "stalled_at?: DateTime<Utc>", ep.kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", ep.queue_position as "queue_position?: u32", ep.retry_of as "retry_of?: Uuid", ep.started_by, ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes ep
                JOIN task_attempts ta ON ep.task_attempt_id = ta.id
                JOIN tasks t ON ta.task_id = t.id
@@ -267,6 +357,95 @@ impl ExecutionProcess {
         .await
     }
 
+    /// One page of the project's processes matching `filter`, most recently started first
+    pub async fn search(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        filter: &ExecutionProcessFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ExecutionProcessSearchResult>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessSearchResult,
+            r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", t.id as "task_id!: Uuid", t.title as task_title, ta.executor as "executor!",
+                      ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
+                      ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", ep.retry_of as "retry_of?: Uuid", ep.started_by
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ep.task_attempt_id = ta.id
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1
+                 AND ($2 IS NULL OR ep.status = $2)
+                 AND ($3 IS NULL OR ep.run_reason = $3)
+                 AND ($4 IS NULL OR ep.started_at >= datetime($4))
+                 AND ($5 IS NULL OR ep.started_at <= datetime($5))
+                 AND ($6 IS NULL OR ta.executor = $6)
+                 AND ($7 IS NULL OR ep.exit_code = $7)
+               ORDER BY ep.started_at DESC
+               LIMIT $8 OFFSET $9"#,
+            project_id,
+            filter.status,
+            filter.run_reason,
+            filter.started_after,
+            filter.started_before,
+            filter.executor,
+            filter.exit_code,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// A single process with the task and executor it ran for
+    pub async fn find_search_result_by_id(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<ExecutionProcessSearchResult>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessSearchResult,
+            r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", t.id as "task_id!: Uuid", t.title as task_title, ta.executor as "executor!",
+                      ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
+                      ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", ep.retry_of as "retry_of?: Uuid", ep.started_by
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ep.task_attempt_id = ta.id
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE ep.id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Number of the project's processes matching `filter`, across all pages
+    pub async fn count_matching(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        filter: &ExecutionProcessFilter,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes ep
+               JOIN task_attempts ta ON ep.task_attempt_id = ta.id
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1
+                 AND ($2 IS NULL OR ep.status = $2)
+                 AND ($3 IS NULL OR ep.run_reason = $3)
+                 AND ($4 IS NULL OR ep.started_at >= datetime($4))
+                 AND ($5 IS NULL OR ep.started_at <= datetime($5))
+                 AND ($6 IS NULL OR ta.executor = $6)
+                 AND ($7 IS NULL OR ep.exit_code = $7)"#,
+            project_id,
+            filter.status,
+            filter.run_reason,
+            filter.started_after,
+            filter.started_before,
+            filter.executor,
+            filter.exit_code
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     /// Find latest session_id by task attempt (simple scalar query)
     pub async fn find_latest_session_id_by_task_attempt(
         pool: &SqlitePool,
@@ -305,7 +484,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", stalled_at as "stalled_at?: DateTime<Utc>", kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", queue_position as "queue_position?: u32", retry_of as "retry_of?: Uuid", started_by,
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes
                WHERE task_attempt_id = ? AND run_reason = ? AND dropped = FALSE
@@ -323,6 +502,7 @@ impl ExecutionProcess {
         data: &CreateExecutionProcess,
         process_id: Uuid,
         before_head_commit: Option<&str>,
+        status: ExecutionProcessStatus,
     ) -> Result<Self, sqlx::Error> {
         let now = Utc::now();
         let executor_action_json = sqlx::types::Json(&data.executor_action);
@@ -331,26 +511,65 @@ impl ExecutionProcess {
             ExecutionProcess,
             r#"INSERT INTO execution_processes (
                     id, task_attempt_id, run_reason, executor_action, before_head_commit,
-                    after_head_commit, status, exit_code, started_at, completed_at, created_at, updated_at
-                ) VALUES (?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?) RETURNING
+                    after_head_commit, status, exit_code, started_at, completed_at, created_at, updated_at,
+                    retry_of, started_by
+                ) VALUES (?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING
                     id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                    after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+                    after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", stalled_at as "stalled_at?: DateTime<Utc>", kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", queue_position as "queue_position?: u32", retry_of as "retry_of?: Uuid", started_by, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             process_id,
             data.task_attempt_id,
             data.run_reason,
             executor_action_json,
             before_head_commit,
-            ExecutionProcessStatus::Running,
+            status,
             None::<i64>,
             now,
             None::<DateTime<Utc>>,
             now,
-            now
+            now,
+            data.retry_of,
+            data.started_by
         )
         .fetch_one(pool)
         .await
     }
 
+    /// Insert a process read from a project archive as it was recorded. The caller settles
+    /// processes that were still queued or running, since nothing will finish them.
+    pub async fn restore<'e, E>(executor: E, process: &ExecutionProcess) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO execution_processes (
+                    id, task_attempt_id, run_reason, executor_action, before_head_commit,
+                    after_head_commit, status, exit_code, dropped, started_at, completed_at,
+                    stalled_at, kill_reason, queue_position, retry_of, started_by, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+            process.id,
+            process.task_attempt_id,
+            process.run_reason,
+            process.executor_action,
+            process.before_head_commit,
+            process.after_head_commit,
+            process.status,
+            process.exit_code,
+            process.dropped,
+            process.started_at,
+            process.completed_at,
+            process.stalled_at,
+            process.kill_reason,
+            process.queue_position,
+            process.retry_of,
+            process.started_by,
+            process.created_at,
+            process.updated_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     pub async fn was_stopped(pool: &SqlitePool, id: Uuid) -> bool {
         if let Ok(exp_process) = Self::find_by_id(pool, id).await
             && exp_process.is_some_and(|ep| {
@@ -378,7 +597,8 @@ impl ExecutionProcess {
 
         sqlx::query!(
             r#"UPDATE execution_processes 
-               SET status = $1, exit_code = $2, completed_at = $3
+               SET status = $1, exit_code = $2, completed_at = $3, stalled_at = NULL,
+                   awaiting_approval_at = NULL
                WHERE id = $4"#,
             status,
             exit_code,
@@ -391,6 +611,190 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Number of coding agents currently running, for the concurrency limit
+    pub async fn count_running_coding_agents(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE status = 'running' AND run_reason = 'codingagent'"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Number of failed coding agent runs of an attempt, not counting dropped ones
+    pub async fn count_failed_coding_agents(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE task_attempt_id = $1 AND status = 'failed' AND run_reason = 'codingagent' AND dropped = FALSE"#,
+            task_attempt_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The next queued process to start: that of the highest-priority task, oldest first
+    pub async fn find_next_queued(pool: &SqlitePool) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", stalled_at as "stalled_at?: DateTime<Utc>", kill_reason as "kill_reason?: sqlx::types::Json<KillReason>", queue_position as "queue_position?: u32", retry_of as "retry_of?: Uuid", started_by,
+                      created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes ep
+               WHERE status = 'queued'
+               ORDER BY (SELECT CASE t.priority WHEN 'urgent' THEN 0 WHEN 'high' THEN 1 WHEN 'normal' THEN 2 ELSE 3 END
+                           FROM task_attempts ta JOIN tasks t ON t.id = ta.task_id
+                          WHERE ta.id = ep.task_attempt_id) ASC,
+                        created_at ASC
+               LIMIT 1"#,
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Move a queued process to running. Returns false if it was no longer queued.
+    pub async fn start_queued(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = 'running', queue_position = NULL, started_at = $1
+               WHERE id = $2 AND status = 'queued'"#,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Self::update_queue_positions(pool).await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Take a process off the queue without running it. Returns false if it was not queued.
+    pub async fn cancel_queued(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: ExecutionProcessStatus,
+    ) -> Result<bool, sqlx::Error> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = $1, queue_position = NULL, completed_at = $2
+               WHERE id = $3 AND status = 'queued'"#,
+            status,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Self::update_queue_positions(pool).await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Renumber queued processes in the order they will start, touching only rows whose place
+    /// changed
+    pub async fn update_queue_positions(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET queue_position = q.position
+               FROM (SELECT ep.id, ROW_NUMBER() OVER (
+                               ORDER BY CASE t.priority WHEN 'urgent' THEN 0 WHEN 'high' THEN 1 WHEN 'normal' THEN 2 ELSE 3 END ASC,
+                                        ep.created_at ASC
+                             ) AS position
+                       FROM execution_processes ep
+                       JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+                       JOIN tasks t ON t.id = ta.task_id
+                      WHERE ep.status = 'queued') AS q
+               WHERE execution_processes.id = q.id
+                 AND execution_processes.queue_position IS NOT q.position"#
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a running process as stalled, or clear the flag once output resumes
+    pub async fn update_stalled_at(
+        pool: &SqlitePool,
+        id: Uuid,
+        stalled_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET stalled_at = $1
+               WHERE id = $2"#,
+            stalled_at,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record why the server is about to stop the process
+    pub async fn update_kill_reason(
+        pool: &SqlitePool,
+        id: Uuid,
+        reason: &KillReason,
+    ) -> Result<(), sqlx::Error> {
+        let reason = sqlx::types::Json(reason);
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET kill_reason = $1
+               WHERE id = $2"#,
+            reason,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the OS process id of the spawned process
+    pub async fn update_pid(pool: &SqlitePool, id: Uuid, pid: u32) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET pid = $1
+               WHERE id = $2"#,
+            pid,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// OS process id the process was spawned with, if it got that far
+    pub async fn find_pid(pool: &SqlitePool, id: Uuid) -> Result<Option<u32>, sqlx::Error> {
+        let pid = sqlx::query_scalar!(
+            r#"SELECT pid as "pid?: u32" FROM execution_processes WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(pid.flatten())
+    }
+
+    /// Record that the process is blocked on a tool approval, or clear it once answered
+    pub async fn update_awaiting_approval_at(
+        pool: &SqlitePool,
+        id: Uuid,
+        awaiting_approval_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET awaiting_approval_at = $1
+               WHERE id = $2"#,
+            awaiting_approval_at,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Update the "after" commit oid for the process
     pub async fn update_after_head_commit(
         pool: &SqlitePool,
@@ -577,3 +981,167 @@ impl ExecutionProcess {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use executors::actions::script::{ScriptContext, ScriptRequest, ScriptRequestLanguage};
+
+    use super::*;
+    use crate::test_pool;
+
+    /// A process of its own task, with the given priority
+    async fn create_process(
+        pool: &SqlitePool,
+        priority: &str,
+        run_reason: ExecutionProcessRunReason,
+        status: ExecutionProcessStatus,
+    ) -> Uuid {
+        let project_id = Uuid::new_v4();
+        let task_id = Uuid::new_v4();
+        let task_attempt_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO projects (id, name, git_repo_path) VALUES ($1, 'project', $2)")
+            .bind(project_id)
+            .bind(format!("/repos/{project_id}"))
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO tasks (id, project_id, title, priority) VALUES ($1, $2, 'task', $3)",
+        )
+        .bind(task_id)
+        .bind(project_id)
+        .bind(priority)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO task_attempts (id, task_id) VALUES ($1, $2)")
+            .bind(task_attempt_id)
+            .bind(task_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        let data = CreateExecutionProcess {
+            task_attempt_id,
+            executor_action: ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: "true".to_string(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::SetupScript,
+                    env: HashMap::new(),
+                }),
+                None,
+            ),
+            run_reason,
+            retry_of: None,
+            started_by: None,
+        };
+        ExecutionProcess::create(pool, &data, Uuid::new_v4(), None, status)
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn queue(pool: &SqlitePool, priority: &str) -> Uuid {
+        create_process(
+            pool,
+            priority,
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessStatus::Queued,
+        )
+        .await
+    }
+
+    async fn queue_position(pool: &SqlitePool, id: Uuid) -> Option<u32> {
+        ExecutionProcess::find_by_id(pool, id)
+            .await
+            .unwrap()
+            .unwrap()
+            .queue_position
+    }
+
+    #[tokio::test]
+    async fn counts_only_running_coding_agents() {
+        let pool = test_pool().await;
+        create_process(
+            &pool,
+            "normal",
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessStatus::Running,
+        )
+        .await;
+        create_process(
+            &pool,
+            "normal",
+            ExecutionProcessRunReason::SetupScript,
+            ExecutionProcessStatus::Running,
+        )
+        .await;
+        queue(&pool, "normal").await;
+        let finished = create_process(
+            &pool,
+            "normal",
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutionProcessStatus::Running,
+        )
+        .await;
+        ExecutionProcess::update_completion(
+            &pool,
+            finished,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            ExecutionProcess::count_running_coding_agents(&pool)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn starts_the_highest_priority_then_the_oldest() {
+        let pool = test_pool().await;
+        let normal = queue(&pool, "normal").await;
+        let urgent = queue(&pool, "urgent").await;
+        let later_normal = queue(&pool, "normal").await;
+        let low = queue(&pool, "low").await;
+        ExecutionProcess::update_queue_positions(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queue_position(&pool, urgent).await, Some(1));
+        assert_eq!(queue_position(&pool, normal).await, Some(2));
+        assert_eq!(queue_position(&pool, later_normal).await, Some(3));
+        assert_eq!(queue_position(&pool, low).await, Some(4));
+
+        let next = ExecutionProcess::find_next_queued(&pool).await.unwrap();
+        assert_eq!(next.map(|process| process.id), Some(urgent));
+        assert!(ExecutionProcess::start_queued(&pool, urgent).await.unwrap());
+        // Another scheduler pass got there first
+        assert!(!ExecutionProcess::start_queued(&pool, urgent).await.unwrap());
+
+        let started = ExecutionProcess::find_by_id(&pool, urgent)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(started.status, ExecutionProcessStatus::Running);
+        assert_eq!(started.queue_position, None);
+        assert_eq!(queue_position(&pool, normal).await, Some(1));
+        assert_eq!(queue_position(&pool, later_normal).await, Some(2));
+        assert_eq!(queue_position(&pool, low).await, Some(3));
+
+        assert!(
+            ExecutionProcess::cancel_queued(&pool, normal, ExecutionProcessStatus::Killed)
+                .await
+                .unwrap()
+        );
+        assert_eq!(queue_position(&pool, later_normal).await, Some(1));
+        assert_eq!(queue_position(&pool, low).await, Some(2));
+        let next = ExecutionProcess::find_next_queued(&pool).await.unwrap();
+        assert_eq!(next.map(|process| process.id), Some(later_normal));
+    }
+}
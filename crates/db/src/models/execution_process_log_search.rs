@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "log_stream", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of a process's output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct LogLine {
+    /// 1-based, counting stdout and stderr lines together in the order they were printed
+    pub line_number: i64,
+    pub stream: LogStream,
+    pub content: String,
+}
+
+/// An indexed line matching a full-text search
+#[derive(Debug, Clone, FromRow)]
+pub struct LogSearchHit {
+    pub execution_process_id: Uuid,
+    pub line_number: i64,
+    pub stream: LogStream,
+    pub content: String,
+}
+
+/// Full-text index over the output of finished execution processes
+pub struct ExecutionProcessLogSearch;
+
+impl ExecutionProcessLogSearch {
+    /// Replace the indexed output of a process with `lines`
+    pub async fn index(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        lines: &[LogLine],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM execution_process_log_lines WHERE execution_process_id = $1",
+            execution_process_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for line in lines {
+            sqlx::query!(
+                r#"INSERT INTO execution_process_log_lines (execution_process_id, line_number, stream, content)
+                   VALUES ($1, $2, $3, $4)"#,
+                execution_process_id,
+                line.line_number,
+                line.stream,
+                line.content
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let line_count = lines.len() as i64;
+        sqlx::query!(
+            r#"INSERT INTO execution_process_log_index (execution_process_id, line_count)
+               VALUES ($1, $2)
+               ON CONFLICT (execution_process_id) DO UPDATE
+               SET line_count = EXCLUDED.line_count,
+                   indexed_at = datetime('now', 'subsec')"#,
+            execution_process_id,
+            line_count
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop the indexed lines of a process whose logs were pruned. It stays marked as indexed so
+    /// it is not indexed again.
+    pub async fn remove_lines(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM execution_process_log_lines WHERE execution_process_id = $1",
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Finished processes with stored logs that have not been indexed yet, oldest first
+    pub async fn find_unindexed(pool: &SqlitePool, limit: i64) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT ep.id as "id!: Uuid"
+               FROM execution_processes ep
+               JOIN execution_process_logs l ON l.execution_id = ep.id
+               LEFT JOIN execution_process_log_index i ON i.execution_process_id = ep.id
+               WHERE i.execution_process_id IS NULL AND ep.status NOT IN ('queued', 'running')
+               ORDER BY ep.started_at ASC
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Lines matching the FTS5 query `fts_query`, newest process first, optionally only in the
+    /// project's processes
+    pub async fn search(
+        pool: &SqlitePool,
+        fts_query: &str,
+        project_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<LogSearchHit>, sqlx::Error> {
+        sqlx::query_as!(
+            LogSearchHit,
+            r#"SELECT l.execution_process_id as "execution_process_id!: Uuid", l.line_number as "line_number!: i64", l.stream as "stream!: LogStream", l.content as "content!"
+               FROM execution_process_log_search s
+               JOIN execution_process_log_lines l ON l.id = s.rowid
+               JOIN execution_processes ep ON ep.id = l.execution_process_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE execution_process_log_search MATCH $1
+                 AND ($2 IS NULL OR t.project_id = $2)
+               ORDER BY ep.started_at DESC, ep.id ASC, l.line_number ASC
+               LIMIT $3"#,
+            fts_query,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use ts_rs::TS;
 use utils::log_msg::LogMsg;
 use uuid::Uuid;
@@ -20,6 +20,46 @@ pub struct CreateExecutionProcessLogs {
     pub byte_size: i64,
 }
 
+/// A logs row as stored, with the part compressed by the retention policy
+#[derive(Debug, FromRow)]
+struct StoredExecutionProcessLogs {
+    execution_id: Uuid,
+    logs: String,
+    logs_zstd: Option<Vec<u8>>,
+    byte_size: i64,
+    inserted_at: DateTime<Utc>,
+}
+
+impl StoredExecutionProcessLogs {
+    /// Lines appended after compression stay uncompressed and follow the compressed ones
+    fn decompress(self) -> Result<ExecutionProcessLogs, sqlx::Error> {
+        let logs = match self.logs_zstd {
+            Some(compressed) => {
+                let mut logs = ExecutionProcessLogs::decompress_jsonl(&compressed)
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                logs.push_str(&self.logs);
+                logs
+            }
+            None => self.logs,
+        };
+        Ok(ExecutionProcessLogs {
+            execution_id: self.execution_id,
+            logs,
+            byte_size: self.byte_size,
+            inserted_at: self.inserted_at,
+        })
+    }
+}
+
+/// Space a process's logs take up in the database
+#[derive(Debug, Clone, FromRow)]
+pub struct StoredLogSize {
+    pub execution_id: Uuid,
+    pub stored_bytes: i64,
+    /// False while the process is queued or running
+    pub finished: bool,
+}
+
 impl ExecutionProcessLogs {
     /// Find logs by execution process ID
     pub async fn find_by_execution_id(
@@ -27,10 +67,11 @@ impl ExecutionProcessLogs {
         execution_id: Uuid,
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
-            ExecutionProcessLogs,
+            StoredExecutionProcessLogs,
             r#"SELECT 
                 execution_id as "execution_id!: Uuid",
                 logs,
+                logs_zstd,
                 byte_size,
                 inserted_at as "inserted_at!: DateTime<Utc>"
                FROM execution_process_logs 
@@ -38,7 +79,9 @@ impl ExecutionProcessLogs {
             execution_id
         )
         .fetch_optional(pool)
-        .await
+        .await?
+        .map(StoredExecutionProcessLogs::decompress)
+        .transpose()
     }
 
     /// Create or update execution process logs
@@ -54,6 +97,7 @@ impl ExecutionProcessLogs {
                VALUES ($1, $2, $3, $4)
                ON CONFLICT (execution_id) DO UPDATE
                SET logs = EXCLUDED.logs, 
+                   logs_zstd = NULL,
                    byte_size = EXCLUDED.byte_size,
                    inserted_at = EXCLUDED.inserted_at
                RETURNING 
@@ -70,6 +114,30 @@ impl ExecutionProcessLogs {
         .await
     }
 
+    /// Insert logs read from a project archive, keeping when they were written
+    pub async fn restore<'e, E>(
+        executor: E,
+        execution_id: Uuid,
+        logs: &str,
+        inserted_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let byte_size = logs.len() as i64;
+        sqlx::query!(
+            r#"INSERT INTO execution_process_logs (execution_id, logs, byte_size, inserted_at)
+               VALUES ($1, $2, $3, $4)"#,
+            execution_id,
+            logs,
+            byte_size,
+            inserted_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     /// Parse JSONL logs back into Vec<LogMsg>
     pub fn parse_logs(&self) -> Result<Vec<LogMsg>, serde_json::Error> {
         let mut messages = Vec::new();
@@ -116,4 +184,112 @@ impl ExecutionProcessLogs {
 
         Ok(())
     }
+
+    /// Compress the logs of a process in place. Returns the bytes saved, or `None` if the logs
+    /// are already compressed or were written to meanwhile.
+    pub async fn compress(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let Some(row) = sqlx::query!(
+            r#"SELECT logs, byte_size FROM execution_process_logs
+               WHERE execution_id = $1 AND logs_zstd IS NULL"#,
+            execution_id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let compressed = Self::compress_jsonl(&row.logs).map_err(sqlx::Error::Io)?;
+        let saved = row.logs.len() as i64 - compressed.len() as i64;
+        let result = sqlx::query!(
+            r#"UPDATE execution_process_logs
+               SET logs = '', logs_zstd = $2
+               WHERE execution_id = $1 AND logs_zstd IS NULL AND byte_size = $3"#,
+            execution_id,
+            compressed,
+            row.byte_size
+        )
+        .execute(pool)
+        .await?;
+        Ok((result.rows_affected() > 0).then_some(saved))
+    }
+
+    /// Finished processes whose logs were last written before `before` and are not compressed
+    pub async fn find_compressible(
+        pool: &SqlitePool,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT l.execution_id as "execution_id!: Uuid"
+               FROM execution_process_logs l
+               JOIN execution_processes ep ON ep.id = l.execution_id
+               WHERE l.logs_zstd IS NULL
+                 AND l.inserted_at < datetime($1)
+                 AND ep.status NOT IN ('queued', 'running')
+               ORDER BY l.inserted_at ASC
+               LIMIT $2"#,
+            before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Finished processes whose logs were last written before `before`
+    pub async fn find_expired(
+        pool: &SqlitePool,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT l.execution_id as "execution_id!: Uuid"
+               FROM execution_process_logs l
+               JOIN execution_processes ep ON ep.id = l.execution_id
+               WHERE l.inserted_at < datetime($1)
+                 AND ep.status NOT IN ('queued', 'running')
+               ORDER BY l.inserted_at ASC
+               LIMIT $2"#,
+            before,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Stored size of every process's logs, most recently written first
+    pub async fn stored_sizes(pool: &SqlitePool) -> Result<Vec<StoredLogSize>, sqlx::Error> {
+        sqlx::query_as!(
+            StoredLogSize,
+            r#"SELECT l.execution_id as "execution_id!: Uuid",
+                      length(CAST(l.logs AS BLOB)) + COALESCE(length(l.logs_zstd), 0) as "stored_bytes!: i64",
+                      ep.status NOT IN ('queued', 'running') as "finished!: bool"
+               FROM execution_process_logs l
+               JOIN execution_processes ep ON ep.id = l.execution_id
+               ORDER BY l.inserted_at DESC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, execution_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM execution_process_logs WHERE execution_id = $1",
+            execution_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub fn compress_jsonl(logs: &str) -> std::io::Result<Vec<u8>> {
+        zstd::encode_all(logs.as_bytes(), 0)
+    }
+
+    pub fn decompress_jsonl(compressed: &[u8]) -> std::io::Result<String> {
+        let bytes = zstd::decode_all(compressed)?;
+        String::from_utf8(bytes).map_err(std::io::Error::other)
+    }
 }
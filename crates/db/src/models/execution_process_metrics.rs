@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::execution_process::ExecutionProcessRunReason;
+
+/// Resource usage of a finished execution process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutionProcessMetrics {
+    pub execution_process_id: Uuid,
+    pub wall_time_ms: i64,
+    pub exit_code: Option<i64>,
+    pub stdout_bytes: i64,
+    pub stderr_bytes: i64,
+    /// Token counts and cost are only set for coding agents that report them. Input tokens
+    /// include cached ones.
+    pub input_tokens: Option<i64>,
+    pub cached_input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreateExecutionProcessMetrics {
+    pub wall_time_ms: i64,
+    pub exit_code: Option<i64>,
+    pub stdout_bytes: i64,
+    pub stderr_bytes: i64,
+    pub input_tokens: Option<i64>,
+    pub cached_input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+}
+
+/// Metrics of a project's processes, summed per executor and run reason
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ExecutionMetricsAggregate {
+    pub executor: String,
+    pub run_reason: ExecutionProcessRunReason,
+    pub processes: i64,
+    /// Processes that exited with a non-zero code
+    pub failed: i64,
+    pub total_wall_time_ms: i64,
+    pub max_wall_time_ms: i64,
+    pub output_bytes: i64,
+    pub input_tokens: i64,
+    pub cached_input_tokens: i64,
+    pub output_tokens: i64,
+    /// Unset when none of the processes reported a cost
+    pub cost_usd: Option<f64>,
+}
+
+/// How often processes of a project exited with a given code
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct ExitCodeCount {
+    pub run_reason: ExecutionProcessRunReason,
+    pub exit_code: i64,
+    pub count: i64,
+}
+
+impl ExecutionProcessMetrics {
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessMetrics,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid", wall_time_ms, exit_code, stdout_bytes, stderr_bytes, input_tokens, cached_input_tokens, output_tokens, cost_usd, created_at as "created_at!: DateTime<Utc>"
+               FROM execution_process_metrics
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record the metrics of a process, replacing any recorded earlier
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        data: &CreateExecutionProcessMetrics,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessMetrics,
+            r#"INSERT INTO execution_process_metrics (execution_process_id, wall_time_ms, exit_code, stdout_bytes, stderr_bytes, input_tokens, cached_input_tokens, output_tokens, cost_usd)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               ON CONFLICT (execution_process_id) DO UPDATE
+               SET wall_time_ms = EXCLUDED.wall_time_ms,
+                   exit_code = EXCLUDED.exit_code,
+                   stdout_bytes = EXCLUDED.stdout_bytes,
+                   stderr_bytes = EXCLUDED.stderr_bytes,
+                   input_tokens = EXCLUDED.input_tokens,
+                   cached_input_tokens = EXCLUDED.cached_input_tokens,
+                   output_tokens = EXCLUDED.output_tokens,
+                   cost_usd = EXCLUDED.cost_usd,
+                   created_at = datetime('now', 'subsec')
+               RETURNING execution_process_id as "execution_process_id!: Uuid", wall_time_ms, exit_code, stdout_bytes, stderr_bytes, input_tokens, cached_input_tokens, output_tokens, cost_usd, created_at as "created_at!: DateTime<Utc>""#,
+            execution_process_id,
+            data.wall_time_ms,
+            data.exit_code,
+            data.stdout_bytes,
+            data.stderr_bytes,
+            data.input_tokens,
+            data.cached_input_tokens,
+            data.output_tokens,
+            data.cost_usd
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Totals over the project's processes that started since `since`
+    pub async fn aggregate_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ExecutionMetricsAggregate>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionMetricsAggregate,
+            r#"SELECT ta.executor as "executor!", ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                      COUNT(*) as "processes!: i64",
+                      COALESCE(SUM(CASE WHEN m.exit_code IS NOT NULL AND m.exit_code != 0 THEN 1 ELSE 0 END), 0) as "failed!: i64",
+                      COALESCE(SUM(m.wall_time_ms), 0) as "total_wall_time_ms!: i64",
+                      COALESCE(MAX(m.wall_time_ms), 0) as "max_wall_time_ms!: i64",
+                      COALESCE(SUM(m.stdout_bytes + m.stderr_bytes), 0) as "output_bytes!: i64",
+                      COALESCE(SUM(m.input_tokens), 0) as "input_tokens!: i64",
+                      COALESCE(SUM(m.cached_input_tokens), 0) as "cached_input_tokens!: i64",
+                      COALESCE(SUM(m.output_tokens), 0) as "output_tokens!: i64",
+                      SUM(m.cost_usd) as "cost_usd?: f64"
+               FROM execution_process_metrics m
+               JOIN execution_processes ep ON m.execution_process_id = ep.id
+               JOIN task_attempts ta ON ep.task_attempt_id = ta.id
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1 AND ep.started_at >= datetime($2)
+               GROUP BY ta.executor, ep.run_reason
+               ORDER BY ta.executor ASC, ep.run_reason ASC"#,
+            project_id,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Exit codes of the project's processes that started since `since`, most frequent first
+    pub async fn exit_code_counts_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ExitCodeCount>, sqlx::Error> {
+        sqlx::query_as!(
+            ExitCodeCount,
+            r#"SELECT ep.run_reason as "run_reason!: ExecutionProcessRunReason", m.exit_code as "exit_code!: i64", COUNT(*) as "count!: i64"
+               FROM execution_process_metrics m
+               JOIN execution_processes ep ON m.execution_process_id = ep.id
+               JOIN task_attempts ta ON ep.task_attempt_id = ta.id
+               JOIN tasks t ON ta.task_id = t.id
+               WHERE t.project_id = $1 AND ep.started_at >= datetime($2) AND m.exit_code IS NOT NULL
+               GROUP BY ep.run_reason, m.exit_code
+               ORDER BY COUNT(*) DESC, m.exit_code ASC"#,
+            project_id,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Keeps a project's tasks in step with the issues of its GitHub repository: open issues are
+/// imported as tasks, and the issue of a task is closed when the task is done.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct GitHubIssueSync {
+    pub project_id: Uuid,
+    /// Only issues with this label are imported; unset imports every issue
+    pub label: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Why the last sync failed, cleared by the next one that succeeds
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertGitHubIssueSync {
+    pub label: Option<String>,
+}
+
+/// The GitHub issue a task was imported from
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct GitHubIssueLink {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub issue_number: i64,
+    pub issue_url: String,
+    /// Whether the issue was closed when last seen or changed from here
+    pub closed: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GitHubIssueSync {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueSync,
+            r#"SELECT project_id as "project_id!: Uuid", label, last_synced_at as "last_synced_at: DateTime<Utc>", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM github_issue_syncs"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueSync,
+            r#"SELECT project_id as "project_id!: Uuid", label, last_synced_at as "last_synced_at: DateTime<Utc>", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM github_issue_syncs
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertGitHubIssueSync,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueSync,
+            r#"INSERT INTO github_issue_syncs (project_id, label)
+               VALUES ($1, $2)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   label = excluded.label,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", label, last_synced_at as "last_synced_at: DateTime<Utc>", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.label
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the outcome of a sync; `synced_at` is only stored when it succeeded
+    pub async fn record_sync(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        synced_at: Option<DateTime<Utc>>,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE github_issue_syncs
+               SET last_synced_at = COALESCE($2, last_synced_at), last_error = $3
+               WHERE project_id = $1"#,
+            project_id,
+            synced_at,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM github_issue_syncs WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl GitHubIssueLink {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"SELECT task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", issue_number, issue_url, closed as "closed!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM github_issue_links
+               WHERE task_id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_issue(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        issue_number: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"SELECT task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", issue_number, issue_url, closed as "closed!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM github_issue_links
+               WHERE project_id = $1 AND issue_number = $2"#,
+            project_id,
+            issue_number
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        project_id: Uuid,
+        issue_number: i64,
+        issue_url: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            GitHubIssueLink,
+            r#"INSERT INTO github_issue_links (task_id, project_id, issue_number, issue_url)
+               VALUES ($1, $2, $3, $4)
+               RETURNING task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", issue_number, issue_url, closed as "closed!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            task_id,
+            project_id,
+            issue_number,
+            issue_url
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn set_closed(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        closed: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE github_issue_links
+               SET closed = $2, updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1"#,
+            task_id,
+            closed
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
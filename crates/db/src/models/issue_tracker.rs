@@ -0,0 +1,221 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "issue_tracker_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum IssueTrackerKind {
+    Linear,
+    Jira,
+}
+
+/// Keeps a project's tasks in step with the issues of a Linear team or Jira project
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct IssueTrackerConnection {
+    pub project_id: Uuid,
+    pub kind: IssueTrackerKind,
+    /// Jira site, e.g. `https://acme.atlassian.net`; unset for Linear
+    pub base_url: Option<String>,
+    /// Key of the Linear team or Jira project whose issues are synced
+    pub scope: String,
+    /// Jira account the API token belongs to; unset for Linear
+    pub email: Option<String>,
+    /// Encrypted
+    #[serde(skip)]
+    #[ts(skip)]
+    pub api_token: String,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Why the last sync failed, cleared by the next one that succeeds
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertIssueTrackerConnection {
+    pub kind: IssueTrackerKind,
+    pub base_url: Option<String>,
+    pub scope: String,
+    pub email: Option<String>,
+    /// Keeps the current token when unset
+    pub api_token: Option<String>,
+}
+
+/// The tracker issue a task is synced with; the issue's key is the task's `external_key`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct IssueTrackerLink {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub issue_url: String,
+    /// Status of the task when both sides last agreed, to tell which side changed since
+    pub synced_status: TaskStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IssueTrackerConnection {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            IssueTrackerConnection,
+            r#"SELECT project_id as "project_id!: Uuid", kind as "kind!: IssueTrackerKind", base_url, scope, email, api_token, last_synced_at as "last_synced_at: DateTime<Utc>", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM issue_tracker_connections"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            IssueTrackerConnection,
+            r#"SELECT project_id as "project_id!: Uuid", kind as "kind!: IssueTrackerKind", base_url, scope, email, api_token, last_synced_at as "last_synced_at: DateTime<Utc>", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM issue_tracker_connections
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Connect the project to a tracker, replacing its current connection. `api_token` is
+    /// stored as given, so callers encrypt it first.
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        kind: IssueTrackerKind,
+        base_url: Option<&str>,
+        scope: &str,
+        email: Option<&str>,
+        api_token: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            IssueTrackerConnection,
+            r#"INSERT INTO issue_tracker_connections (project_id, kind, base_url, scope, email, api_token)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   kind = excluded.kind,
+                   base_url = excluded.base_url,
+                   scope = excluded.scope,
+                   email = excluded.email,
+                   api_token = excluded.api_token,
+                   last_error = NULL,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", kind as "kind!: IssueTrackerKind", base_url, scope, email, api_token, last_synced_at as "last_synced_at: DateTime<Utc>", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            kind,
+            base_url,
+            scope,
+            email,
+            api_token
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the outcome of a sync; `synced_at` is only stored when it succeeded
+    pub async fn record_sync(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        synced_at: Option<DateTime<Utc>>,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE issue_tracker_connections
+               SET last_synced_at = COALESCE($2, last_synced_at), last_error = $3
+               WHERE project_id = $1"#,
+            project_id,
+            synced_at,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM issue_tracker_connections WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl IssueTrackerLink {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            IssueTrackerLink,
+            r#"SELECT task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", issue_url, synced_status as "synced_status!: TaskStatus", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM issue_tracker_links
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            IssueTrackerLink,
+            r#"SELECT task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", issue_url, synced_status as "synced_status!: TaskStatus", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM issue_tracker_links
+               WHERE task_id = $1"#,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        project_id: Uuid,
+        issue_url: &str,
+        synced_status: TaskStatus,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            IssueTrackerLink,
+            r#"INSERT INTO issue_tracker_links (task_id, project_id, issue_url, synced_status)
+               VALUES ($1, $2, $3, $4)
+               RETURNING task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", issue_url, synced_status as "synced_status!: TaskStatus", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            task_id,
+            project_id,
+            issue_url,
+            synced_status
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn set_synced_status(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        synced_status: TaskStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE issue_tracker_links
+               SET synced_status = $2, updated_at = datetime('now', 'subsec')
+               WHERE task_id = $1"#,
+            task_id,
+            synced_status
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -14,6 +14,16 @@ pub enum MergeStatus {
     Unknown,
 }
 
+/// How a direct merge brought the attempt's changes into the target branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, Type)]
+#[sqlx(type_name = "merge_strategy", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    Squash,
+    Merge,
+    Rebase,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Merge {
@@ -26,6 +36,7 @@ pub struct DirectMerge {
     pub id: Uuid,
     pub task_attempt_id: Uuid,
     pub merge_commit: String,
+    pub strategy: MergeStrategy,
     pub target_branch_name: String,
     pub created_at: DateTime<Utc>,
 }
@@ -62,6 +73,7 @@ struct MergeRow {
     task_attempt_id: Uuid,
     merge_type: MergeType,
     merge_commit: Option<String>,
+    merge_strategy: Option<MergeStrategy>,
     target_branch_name: String,
     pr_number: Option<i64>,
     pr_url: Option<String>,
@@ -85,6 +97,7 @@ impl Merge {
         task_attempt_id: Uuid,
         target_branch_name: &str,
         merge_commit: &str,
+        strategy: MergeStrategy,
     ) -> Result<DirectMerge, sqlx::Error> {
         let id = Uuid::new_v4();
         let now = Utc::now();
@@ -92,13 +105,14 @@ impl Merge {
         sqlx::query_as!(
             MergeRow,
             r#"INSERT INTO merges (
-                id, task_attempt_id, merge_type, merge_commit, created_at, target_branch_name
-            ) VALUES ($1, $2, 'direct', $3, $4, $5)
+                id, task_attempt_id, merge_type, merge_commit, merge_strategy, created_at, target_branch_name
+            ) VALUES ($1, $2, 'direct', $3, $4, $5, $6)
             RETURNING 
                 id as "id!: Uuid",
                 task_attempt_id as "task_attempt_id!: Uuid",
                 merge_type as "merge_type!: MergeType",
                 merge_commit,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 pr_number,
                 pr_url,
                 pr_status as "pr_status?: MergeStatus",
@@ -110,6 +124,7 @@ impl Merge {
             id,
             task_attempt_id,
             merge_commit,
+            strategy,
             now,
             target_branch_name
         )
@@ -138,6 +153,7 @@ impl Merge {
                 task_attempt_id as "task_attempt_id!: Uuid",
                 merge_type as "merge_type!: MergeType",
                 merge_commit,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 pr_number,
                 pr_url,
                 pr_status as "pr_status?: MergeStatus",
@@ -167,6 +183,7 @@ impl Merge {
                 task_attempt_id as "task_attempt_id!: Uuid",
                 merge_type as "merge_type!: MergeType",
                 merge_commit,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 pr_number,
                 pr_url,
                 pr_status as "pr_status?: MergeStatus",
@@ -226,6 +243,7 @@ impl Merge {
                 task_attempt_id as "task_attempt_id!: Uuid",
                 merge_type as "merge_type!: MergeType",
                 merge_commit,
+                merge_strategy as "merge_strategy?: MergeStrategy",
                 pr_number,
                 pr_url,
                 pr_status as "pr_status?: MergeStatus",
@@ -245,6 +263,32 @@ impl Merge {
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            MergeRow,
+            r#"SELECT 
+                id as "id!: Uuid",
+                task_attempt_id as "task_attempt_id!: Uuid",
+                merge_type as "merge_type!: MergeType",
+                merge_commit,
+                merge_strategy as "merge_strategy?: MergeStrategy",
+                pr_number,
+                pr_url,
+                pr_status as "pr_status?: MergeStatus",
+                pr_merged_at as "pr_merged_at?: DateTime<Utc>",
+                pr_merge_commit_sha,
+                target_branch_name as "target_branch_name!: String",
+                created_at as "created_at!: DateTime<Utc>"
+            FROM merges 
+            WHERE rowid = $1"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(Into::into))
+    }
+
     /// Find the most recent merge for a task attempt
     pub async fn find_latest_by_task_attempt_id(
         pool: &SqlitePool,
@@ -265,6 +309,8 @@ impl From<MergeRow> for DirectMerge {
             merge_commit: row
                 .merge_commit
                 .expect("direct merge must have merge_commit"),
+            // Only PR merges leave the strategy unset
+            strategy: row.merge_strategy.unwrap_or(MergeStrategy::Squash),
             target_branch_name: row.target_branch_name,
             created_at: row.created_at,
         }
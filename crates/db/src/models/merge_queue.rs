@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "merge_queue_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MergeQueueStatus {
+    Queued,
+    Rebasing,
+    Verifying,
+    Merging,
+    Merged,
+    Failed,
+    Cancelled,
+}
+
+/// An attempt waiting in, or taken out of, its project's merge queue
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct MergeQueueEntry {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub status: MergeQueueStatus,
+    /// Why the rebase, verification or merge failed
+    pub error: Option<String>,
+    pub merge_commit: Option<String>,
+    pub queued_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MergeQueueEntry {
+    /// Add an attempt to the back of the queue. Failed and cancelled entries are queued again;
+    /// returns `None` if the attempt is already queued, being processed or merged.
+    pub async fn enqueue(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"INSERT INTO merge_queue_entries (id, project_id, task_attempt_id)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(task_attempt_id) DO UPDATE SET
+                   status = 'queued',
+                   error = NULL,
+                   merge_commit = NULL,
+                   queued_at = datetime('now', 'subsec'),
+                   updated_at = datetime('now', 'subsec')
+               WHERE merge_queue_entries.status IN ('failed', 'cancelled')
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", status as "status!: MergeQueueStatus", error, merge_commit, queued_at as "queued_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", status as "status!: MergeQueueStatus", error, merge_commit, queued_at as "queued_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE task_attempt_id = $1"#,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Entries still in the queue or needing attention, in merge order
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", status as "status!: MergeQueueStatus", error, merge_commit, queued_at as "queued_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE project_id = $1 AND status NOT IN ('merged', 'cancelled')
+               ORDER BY queued_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Projects with entries waiting to be processed
+    pub async fn find_queued_project_ids(pool: &SqlitePool) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT DISTINCT project_id as "project_id!: Uuid"
+               FROM merge_queue_entries
+               WHERE status = 'queued'"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_next_queued(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            MergeQueueEntry,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", status as "status!: MergeQueueStatus", error, merge_commit, queued_at as "queued_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM merge_queue_entries
+               WHERE project_id = $1 AND status = 'queued'
+               ORDER BY queued_at ASC
+               LIMIT 1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Move an entry out of `Queued`. Returns false if it was cancelled in the meantime.
+    pub async fn start(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'rebasing', updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND status = 'queued'"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: MergeQueueStatus,
+        error: Option<&str>,
+        merge_commit: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = $2, error = $3, merge_commit = $4, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            status,
+            error,
+            merge_commit
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Take an attempt out of the queue unless it is already being processed
+    pub async fn cancel(pool: &SqlitePool, task_attempt_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'cancelled', updated_at = datetime('now', 'subsec')
+               WHERE task_attempt_id = $1 AND status IN ('queued', 'failed')"#,
+            task_attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Put entries that were being processed when the server stopped back in the queue
+    pub async fn requeue_interrupted(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE merge_queue_entries
+               SET status = 'queued', updated_at = datetime('now', 'subsec')
+               WHERE status IN ('rebasing', 'verifying', 'merging')"#
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
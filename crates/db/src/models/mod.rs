@@ -1,10 +1,42 @@
+pub mod approval_decision;
+pub mod attempt_comparison;
+pub mod audit_log;
+pub mod automation_rule;
+pub mod base_branch_sync;
+pub mod chat_channel;
+pub mod config_override;
+pub mod deploy_hook;
+pub mod dev_server;
 pub mod draft;
+pub mod event_journal;
 pub mod execution_process;
+pub mod execution_process_log_search;
 pub mod execution_process_logs;
+pub mod execution_process_metrics;
 pub mod executor_session;
+pub mod github_issue;
 pub mod image;
+pub mod issue_tracker;
 pub mod merge;
+pub mod merge_queue;
 pub mod project;
+pub mod project_column;
+pub mod project_env_var;
+pub mod prompt_snippet;
+pub mod setup_cache_entry;
 pub mod tag;
 pub mod task;
 pub mod task_attempt;
+pub mod task_attempt_diff_stats;
+pub mod task_comment;
+pub mod task_dependency;
+pub mod task_inbox;
+pub mod task_schedule;
+pub mod task_search;
+pub mod terminal_session;
+pub mod test_run_result;
+pub mod vulnerability_finding;
+pub mod webhook;
+pub mod wip_limit;
+pub mod workflow_run;
+pub mod workspace;
@@ -1,8 +1,9 @@
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
+use executors::sandbox::SandboxConfig;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -29,7 +30,53 @@ pub struct Project {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    /// Script the merge queue runs in a rebased attempt's worktree; the attempt is only merged
+    /// if it succeeds
+    pub verify_script: Option<String>,
+    /// Script run in the project's repository after an attempt is merged, e.g. to trigger a
+    /// deploy
+    pub post_merge_script: Option<String>,
     pub copy_files: Option<String>,
+    /// Refuse to merge attempts with critical dependency vulnerability findings
+    pub block_merge_on_vulnerabilities: bool,
+    /// Periodically fetch attempts' base branches and record how far behind they are
+    pub sync_base_branch: bool,
+    /// Rebase clean, idle attempts onto their base branch when it moves ahead
+    pub auto_rebase_on_sync: bool,
+    /// Remote that attempt branches are pushed to; unset uses the repository's first remote
+    pub push_remote: Option<String>,
+    /// Remote that base branches and pull requests live on; unset uses the repository's
+    /// first remote
+    pub base_remote: Option<String>,
+    /// Comma-separated branch patterns (e.g. `main, release/*`) that merges and pushes refuse
+    /// to write to unless explicitly overridden
+    pub protected_branches: Option<String>,
+    /// IANA time zone (e.g. `Europe/Berlin`) for quiet hours, schedules and report periods;
+    /// unset uses the server's local time
+    pub timezone: Option<String>,
+    /// Language (`en`, `es`, `ja` or `ko`) of the notifications and commit messages generated
+    /// for the project; unset uses the configured language
+    pub locale: Option<String>,
+    #[ts(type = "QuietHours | null")]
+    pub quiet_hours: Option<sqlx::types::Json<QuietHours>>,
+    /// Run the project's executor processes inside a Linux sandbox
+    #[ts(type = "SandboxConfig | null")]
+    pub sandbox: Option<sqlx::types::Json<SandboxConfig>>,
+    /// Re-run setup scripts and coding agents that fail with a transient error
+    #[ts(type = "RetryPolicy | null")]
+    pub retry_policy: Option<sqlx::types::Json<RetryPolicy>>,
+    /// Reuse the setup script's output between attempts while its inputs are unchanged
+    #[ts(type = "SetupCacheConfig | null")]
+    pub setup_cache: Option<sqlx::types::Json<SetupCacheConfig>>,
+    /// What to run when an attempt's cleanup script fails
+    #[ts(type = "CleanupFailureConfig | null")]
+    pub on_cleanup_failure: Option<sqlx::types::Json<CleanupFailureConfig>>,
+    /// Test command run after each coding agent, whose results are shown on the task
+    #[ts(type = "TestRunConfig | null")]
+    pub test_run: Option<sqlx::types::Json<TestRunConfig>>,
+    /// Which of the coding agent's tool calls are approved or denied without asking the user
+    #[ts(type = "ApprovalPolicy | null")]
+    pub approval_policy: Option<sqlx::types::Json<ApprovalPolicy>>,
 
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
@@ -37,6 +84,132 @@ pub struct Project {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Daily window in which queued follow-ups and scheduled runs are not started automatically.
+/// Manually started work is not affected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Start of the window as `HH:MM` in the project's time zone
+    pub start: String,
+    /// End of the window as `HH:MM`; earlier than `start` for windows that span midnight
+    pub end: String,
+    /// Keep Saturdays and Sundays quiet all day
+    #[serde(default)]
+    pub weekends: bool,
+}
+
+/// How often and how quickly failed setup scripts and coding agents are re-run when their
+/// output shows a transient error such as a network failure or rate limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct RetryPolicy {
+    pub enabled: bool,
+    /// Retries after the first failure
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for every further retry
+    pub initial_backoff_secs: u64,
+    /// Upper bound for the delay between retries
+    pub max_backoff_secs: u64,
+}
+
+/// Which setup script output to save after a successful run, and which files decide whether
+/// a saved copy can be reused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct SetupCacheConfig {
+    pub enabled: bool,
+    /// Directories or files the setup script creates, relative to the repository root, e.g.
+    /// `node_modules`
+    pub paths: Vec<String>,
+    /// Files hashed together with the setup script to tell whether a saved copy is still
+    /// valid. Common lockfiles are used when empty.
+    #[serde(default)]
+    pub key_files: Vec<String>,
+}
+
+/// Action started in place of finalizing an attempt when one of its scripts fails
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FailureAction {
+    /// Run a script in the attempt's worktree, e.g. to collect diagnostics
+    Script { script: String },
+    /// Send the coding agent a follow-up with this prompt and the end of the failed script's
+    /// output
+    FollowUp { prompt: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct CleanupFailureConfig {
+    pub enabled: bool,
+    pub action: FailureAction,
+}
+
+/// How the pass/fail counts are read from a test run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "test_report_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum TestReportFormat {
+    /// `test result:` lines printed by `cargo test`
+    Cargo,
+    /// The `Tests:` summary printed by Jest
+    Jest,
+    /// A JUnit XML report, e.g. from `pytest --junitxml`
+    Junit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct TestRunConfig {
+    pub enabled: bool,
+    /// Run in the attempt's worktree after the coding agent and the cleanup script
+    pub command: String,
+    pub format: TestReportFormat,
+    /// JUnit report written by the command, relative to the worktree
+    pub report_path: Option<String>,
+}
+
+/// What to do with a tool call the coding agent asks approval for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalAction {
+    /// Ask the user
+    Ask,
+    Approve,
+    Deny,
+}
+
+/// Tool calls an approval rule applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApprovalMatcher {
+    /// Calls of a tool by name, e.g. `Bash` or `Write`, ignoring case; `*` matches every tool
+    Tool { name: String },
+    /// Shell commands matching a regular expression, e.g. `^npm (test|run lint)`
+    ShellCommand { pattern: String },
+    /// Calls that delete files, with `rm` or by a patch
+    FileDeletion,
+    /// `git push`
+    GitPush,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct ApprovalRule {
+    pub matcher: ApprovalMatcher,
+    pub action: ApprovalAction,
+}
+
+/// How the coding agent's approval requests are answered. Rules are tried in order and the
+/// first that matches decides; requests no rule matches get the default action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct ApprovalPolicy {
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<ApprovalRule>,
+    pub default_action: ApprovalAction,
+    /// How long the user has to answer; unset waits an hour
+    pub timeout_secs: Option<u64>,
+    /// Answer given when the user doesn't answer in time; only `approve` and `deny` apply
+    pub on_timeout: ApprovalAction,
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateProject {
     pub name: String,
@@ -56,7 +229,54 @@ pub struct UpdateProject {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub verify_script: Option<String>,
+    pub post_merge_script: Option<String>,
     pub copy_files: Option<String>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub block_merge_on_vulnerabilities: Option<bool>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub sync_base_branch: Option<bool>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub auto_rebase_on_sync: Option<bool>,
+    pub push_remote: Option<String>,
+    pub base_remote: Option<String>,
+    pub protected_branches: Option<String>,
+    pub timezone: Option<String>,
+    pub locale: Option<String>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub quiet_hours: Option<QuietHours>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub sandbox: Option<SandboxConfig>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub retry_policy: Option<RetryPolicy>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub setup_cache: Option<SetupCacheConfig>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub on_cleanup_failure: Option<CleanupFailureConfig>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub test_run: Option<TestRunConfig>,
+    /// Leave unset to keep the current value
+    #[serde(default)]
+    #[ts(optional)]
+    pub approval_policy: Option<ApprovalPolicy>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -83,7 +303,7 @@ impl Project {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, verify_script, post_merge_script, copy_files, block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", sync_base_branch as "sync_base_branch!: bool", auto_rebase_on_sync as "auto_rebase_on_sync!: bool", push_remote, base_remote, protected_branches, timezone, locale, quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", test_run as "test_run: sqlx::types::Json<TestRunConfig>", approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
         )
         .fetch_all(pool)
         .await
@@ -94,7 +314,7 @@ impl Project {
         sqlx::query_as!(
             Project,
             r#"
-            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.copy_files, 
+            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.verify_script, p.post_merge_script, p.copy_files, p.block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", p.sync_base_branch as "sync_base_branch!: bool", p.auto_rebase_on_sync as "auto_rebase_on_sync!: bool", p.push_remote, p.base_remote, p.protected_branches, p.timezone, p.locale, p.quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", p.sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", p.retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", p.setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", p.on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", p.test_run as "test_run: sqlx::types::Json<TestRunConfig>", p.approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>",
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
             WHERE p.id IN (
@@ -114,7 +334,7 @@ impl Project {
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, verify_script, post_merge_script, copy_files, block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", sync_base_branch as "sync_base_branch!: bool", auto_rebase_on_sync as "auto_rebase_on_sync!: bool", push_remote, base_remote, protected_branches, timezone, locale, quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", test_run as "test_run: sqlx::types::Json<TestRunConfig>", approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
             id
         )
         .fetch_optional(pool)
@@ -127,7 +347,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, verify_script, post_merge_script, copy_files, block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", sync_base_branch as "sync_base_branch!: bool", auto_rebase_on_sync as "auto_rebase_on_sync!: bool", push_remote, base_remote, protected_branches, timezone, locale, quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", test_run as "test_run: sqlx::types::Json<TestRunConfig>", approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
             git_repo_path
         )
         .fetch_optional(pool)
@@ -141,7 +361,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, verify_script, post_merge_script, copy_files, block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", sync_base_branch as "sync_base_branch!: bool", auto_rebase_on_sync as "auto_rebase_on_sync!: bool", push_remote, base_remote, protected_branches, timezone, locale, quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", test_run as "test_run: sqlx::types::Json<TestRunConfig>", approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
             git_repo_path,
             exclude_id
         )
@@ -149,14 +369,17 @@ impl Project {
         .await
     }
 
-    pub async fn create(
-        pool: &SqlitePool,
+    pub async fn create<'e, E>(
+        executor: E,
         data: &CreateProject,
         project_id: Uuid,
-    ) -> Result<Self, sqlx::Error> {
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Project,
-            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, verify_script, post_merge_script, copy_files, block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", sync_base_branch as "sync_base_branch!: bool", auto_rebase_on_sync as "auto_rebase_on_sync!: bool", push_remote, base_remote, protected_branches, timezone, locale, quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", test_run as "test_run: sqlx::types::Json<TestRunConfig>", approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
             data.name,
             data.git_repo_path,
@@ -165,13 +388,13 @@ impl Project {
             data.cleanup_script,
             data.copy_files
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub async fn update(
-        pool: &SqlitePool,
+    pub async fn update<'e, E>(
+        executor: E,
         id: Uuid,
         name: String,
         git_repo_path: String,
@@ -179,19 +402,63 @@ impl Project {
         dev_script: Option<String>,
         cleanup_script: Option<String>,
         copy_files: Option<String>,
-    ) -> Result<Self, sqlx::Error> {
+        block_merge_on_vulnerabilities: bool,
+        sync_base_branch: bool,
+        auto_rebase_on_sync: bool,
+        push_remote: Option<String>,
+        base_remote: Option<String>,
+        protected_branches: Option<String>,
+        timezone: Option<String>,
+        locale: Option<String>,
+        quiet_hours: Option<QuietHours>,
+        sandbox: Option<SandboxConfig>,
+        verify_script: Option<String>,
+        retry_policy: Option<RetryPolicy>,
+        setup_cache: Option<SetupCacheConfig>,
+        post_merge_script: Option<String>,
+        on_cleanup_failure: Option<CleanupFailureConfig>,
+        test_run: Option<TestRunConfig>,
+        approval_policy: Option<ApprovalPolicy>,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let quiet_hours = quiet_hours.map(sqlx::types::Json);
+        let sandbox = sandbox.map(sqlx::types::Json);
+        let retry_policy = retry_policy.map(sqlx::types::Json);
+        let setup_cache = setup_cache.map(sqlx::types::Json);
+        let on_cleanup_failure = on_cleanup_failure.map(sqlx::types::Json);
+        let test_run = test_run.map(sqlx::types::Json);
+        let approval_policy = approval_policy.map(sqlx::types::Json);
         sqlx::query_as!(
             Project,
-            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, copy_files = $7 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, copy_files = $7, block_merge_on_vulnerabilities = $8, sync_base_branch = $9, auto_rebase_on_sync = $10, push_remote = $11, base_remote = $12, protected_branches = $13, timezone = $14, locale = $15, quiet_hours = $16, sandbox = $17, verify_script = $18, retry_policy = $19, setup_cache = $20, post_merge_script = $21, on_cleanup_failure = $22, test_run = $23, approval_policy = $24 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, verify_script, post_merge_script, copy_files, block_merge_on_vulnerabilities as "block_merge_on_vulnerabilities!: bool", sync_base_branch as "sync_base_branch!: bool", auto_rebase_on_sync as "auto_rebase_on_sync!: bool", push_remote, base_remote, protected_branches, timezone, locale, quiet_hours as "quiet_hours: sqlx::types::Json<QuietHours>", sandbox as "sandbox: sqlx::types::Json<SandboxConfig>", retry_policy as "retry_policy: sqlx::types::Json<RetryPolicy>", setup_cache as "setup_cache: sqlx::types::Json<SetupCacheConfig>", on_cleanup_failure as "on_cleanup_failure: sqlx::types::Json<CleanupFailureConfig>", test_run as "test_run: sqlx::types::Json<TestRunConfig>", approval_policy as "approval_policy: sqlx::types::Json<ApprovalPolicy>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             name,
             git_repo_path,
             setup_script,
             dev_script,
             cleanup_script,
-            copy_files
+            copy_files,
+            block_merge_on_vulnerabilities,
+            sync_base_branch,
+            auto_rebase_on_sync,
+            push_remote,
+            base_remote,
+            protected_branches,
+            timezone,
+            locale,
+            quiet_hours,
+            sandbox,
+            verify_script,
+            retry_policy,
+            setup_cache,
+            post_merge_script,
+            on_cleanup_failure,
+            test_run,
+            approval_policy
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
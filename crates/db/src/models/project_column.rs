@@ -0,0 +1,429 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// A column of a project's board. Columns map onto the task statuses the server relies on;
+/// every status keeps at least one column.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectColumn {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    /// Status of the tasks in this column
+    pub status: TaskStatus,
+    /// Position on the board, left to right
+    pub position: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateProjectColumn {
+    pub name: String,
+    pub status: TaskStatus,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateProjectColumn {
+    pub name: Option<String>,
+    /// Changing the status also changes it for every task in the column
+    pub status: Option<TaskStatus>,
+}
+
+impl ProjectColumn {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectColumn,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, status as "status!: TaskStatus", position as "position!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_columns
+               WHERE project_id = $1
+               ORDER BY position ASC, created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id_and_project_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectColumn,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, status as "status!: TaskStatus", position as "position!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_columns
+               WHERE id = $1 AND project_id = $2"#,
+            id,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Number of the project's columns with this status
+    pub async fn count_by_status(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        status: TaskStatus,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM project_columns WHERE project_id = $1 AND status = $2"#,
+            project_id,
+            status
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The column a deleted column's tasks move to: the first other column with the same
+    /// status. None if this is the status's last column, which can't be removed.
+    pub async fn find_fallback(
+        pool: &SqlitePool,
+        column: &ProjectColumn,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        Ok(Self::find_by_project_id(pool, column.project_id)
+            .await?
+            .into_iter()
+            .find(|other| other.id != column.id && other.status == column.status))
+    }
+
+    /// Add a column at the right end of the board
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreateProjectColumn,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ProjectColumn,
+            r#"INSERT INTO project_columns (id, project_id, name, status, position)
+               VALUES ($1, $2, $3, $4, (SELECT COALESCE(MAX(position), -1) + 1 FROM project_columns WHERE project_id = $2))
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, status as "status!: TaskStatus", position as "position!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.name,
+            data.status
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Rename the column or change its status, moving its tasks to the new status with it
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        name: String,
+        status: TaskStatus,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let column = sqlx::query_as!(
+            ProjectColumn,
+            r#"UPDATE project_columns
+               SET name = $2, status = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, status as "status!: TaskStatus", position as "position!: i64", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            status
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE column_id = $1 AND status != $2",
+            id,
+            status
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(column)
+    }
+
+    /// Delete the column, moving its tasks into `fallback_column_id`, which should have the
+    /// same status
+    pub async fn delete(
+        pool: &SqlitePool,
+        id: Uuid,
+        fallback_column_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "UPDATE tasks SET column_id = $2 WHERE column_id = $1",
+            id,
+            fallback_column_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query!("DELETE FROM project_columns WHERE id = $1", id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Number the given columns of a project in this order. Ids of columns outside the project
+    /// are ignored.
+    pub async fn reorder(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        column_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for (position, column_id) in column_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE project_columns SET position = $3 WHERE id = $1 AND project_id = $2",
+                column_id,
+                project_id,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::{
+        models::{
+            project::{CreateProject, Project},
+            task::{CreateTask, Task},
+        },
+        test_pool, unmigrated_test_pool,
+    };
+
+    async fn create_project(pool: &SqlitePool) -> Uuid {
+        let project_id = Uuid::new_v4();
+        let data = CreateProject {
+            name: "project".to_string(),
+            git_repo_path: format!("/repos/{project_id}"),
+            use_existing_repo: true,
+            git_url: None,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        };
+        Project::create(pool, &data, project_id).await.unwrap();
+        project_id
+    }
+
+    async fn create_task(pool: &SqlitePool, project_id: Uuid) -> Task {
+        let data = CreateTask::from_title_description(project_id, "task".to_string(), None);
+        Task::create(pool, &data, Uuid::new_v4()).await.unwrap()
+    }
+
+    async fn find_task(pool: &SqlitePool, id: Uuid) -> Task {
+        Task::find_by_id(pool, id).await.unwrap().unwrap()
+    }
+
+    /// The project's first column with this status
+    async fn column(pool: &SqlitePool, project_id: Uuid, status: TaskStatus) -> ProjectColumn {
+        ProjectColumn::find_by_project_id(pool, project_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|column| column.status == status)
+            .unwrap()
+    }
+
+    async fn add_column(pool: &SqlitePool, project_id: Uuid, status: TaskStatus) -> ProjectColumn {
+        let data = CreateProjectColumn {
+            name: "QA".to_string(),
+            status,
+        };
+        ProjectColumn::create(pool, project_id, &data)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn migration_puts_existing_tasks_in_their_status_column() {
+        let pool = unmigrated_test_pool().await;
+        let mut migrator = sqlx::migrate!("./migrations");
+        let before_columns: Vec<_> = migrator
+            .migrations
+            .iter()
+            .filter(|migration| migration.version < 20251206120000)
+            .cloned()
+            .collect();
+        let all_migrations =
+            std::mem::replace(&mut migrator.migrations, Cow::Owned(before_columns));
+        migrator.run(&pool).await.unwrap();
+
+        let project_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO projects (id, name, git_repo_path) VALUES ($1, 'project', '/repo')",
+        )
+        .bind(project_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut tasks = Vec::new();
+        for status in ["todo", "inreview", "done"] {
+            let task_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO tasks (id, project_id, title, status) VALUES ($1, $2, 'task', $3)",
+            )
+            .bind(task_id)
+            .bind(project_id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+            tasks.push(task_id);
+        }
+
+        migrator.migrations = all_migrations;
+        migrator.run(&pool).await.unwrap();
+
+        assert_eq!(
+            ProjectColumn::find_by_project_id(&pool, project_id)
+                .await
+                .unwrap()
+                .len(),
+            5
+        );
+        for task_id in tasks {
+            let task = find_task(&pool, task_id).await;
+            let expected = column(&pool, project_id, task.status.clone()).await;
+            assert_eq!(task.column_id, Some(expected.id));
+        }
+    }
+
+    #[tokio::test]
+    async fn status_changes_keep_a_matching_column() {
+        let pool = test_pool().await;
+        let project_id = create_project(&pool).await;
+        let qa = add_column(&pool, project_id, TaskStatus::InReview).await;
+        let task = create_task(&pool, project_id).await;
+        assert_eq!(
+            task.column_id,
+            Some(column(&pool, project_id, TaskStatus::Todo).await.id)
+        );
+
+        // Moving to a status its column doesn't have picks the status's first column
+        Task::update_status(&pool, task.id, TaskStatus::InReview)
+            .await
+            .unwrap();
+        let in_review = column(&pool, project_id, TaskStatus::InReview).await;
+        assert_eq!(
+            find_task(&pool, task.id).await.column_id,
+            Some(in_review.id)
+        );
+
+        Task::update(
+            &pool,
+            task.id,
+            project_id,
+            task.title.clone(),
+            None,
+            TaskStatus::InReview,
+            None,
+            task.priority,
+            None,
+            Some(qa.id),
+        )
+        .await
+        .unwrap();
+        Task::update_status(&pool, task.id, TaskStatus::InReview)
+            .await
+            .unwrap();
+        assert_eq!(find_task(&pool, task.id).await.column_id, Some(qa.id));
+
+        Task::update_status(&pool, task.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        let task = find_task(&pool, task.id).await;
+        assert_eq!(task.status, TaskStatus::Done);
+        assert_eq!(
+            task.column_id,
+            Some(column(&pool, project_id, TaskStatus::Done).await.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn changing_a_columns_status_moves_its_tasks() {
+        let pool = test_pool().await;
+        let project_id = create_project(&pool).await;
+        let todo = column(&pool, project_id, TaskStatus::Todo).await;
+        let in_todo = create_task(&pool, project_id).await;
+        let qa = add_column(&pool, project_id, TaskStatus::Todo).await;
+        let in_qa = create_task(&pool, project_id).await;
+        sqlx::query("UPDATE tasks SET column_id = $1 WHERE id = $2")
+            .bind(qa.id)
+            .bind(in_qa.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let qa = ProjectColumn::update(&pool, qa.id, "Testing".to_string(), TaskStatus::InReview)
+            .await
+            .unwrap();
+        assert_eq!(qa.status, TaskStatus::InReview);
+        let moved = find_task(&pool, in_qa.id).await;
+        assert_eq!(moved.status, TaskStatus::InReview);
+        assert_eq!(moved.column_id, Some(qa.id));
+        let untouched = find_task(&pool, in_todo.id).await;
+        assert_eq!(untouched.status, TaskStatus::Todo);
+        assert_eq!(untouched.column_id, Some(todo.id));
+    }
+
+    #[tokio::test]
+    async fn deleting_a_column_moves_its_tasks_to_the_fallback() {
+        let pool = test_pool().await;
+        let project_id = create_project(&pool).await;
+        let qa = add_column(&pool, project_id, TaskStatus::InReview).await;
+        let task = create_task(&pool, project_id).await;
+        sqlx::query("UPDATE tasks SET status = 'inreview', column_id = $1 WHERE id = $2")
+            .bind(qa.id)
+            .bind(task.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let in_review = column(&pool, project_id, TaskStatus::InReview).await;
+        let fallback = ProjectColumn::find_fallback(&pool, &qa)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fallback.id, in_review.id);
+        assert_eq!(
+            ProjectColumn::delete(&pool, qa.id, fallback.id)
+                .await
+                .unwrap(),
+            1
+        );
+        let task = find_task(&pool, task.id).await;
+        assert_eq!(task.status, TaskStatus::InReview);
+        assert_eq!(task.column_id, Some(in_review.id));
+
+        // In Review is now the status's only column
+        assert!(
+            ProjectColumn::find_fallback(&pool, &in_review)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            ProjectColumn::count_by_status(&pool, project_id, TaskStatus::InReview)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// Environment variable set for a project's scripts and coding agents
+#[derive(Debug, Clone, FromRow)]
+pub struct ProjectEnvVar {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub key: String,
+    /// Encrypted for secrets
+    pub value: String,
+    pub secret: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectEnvVar {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectEnvVar,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", key, value, secret as "secret!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_env_vars
+               WHERE project_id = $1
+               ORDER BY key ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Set `key` for the project, replacing its value and secret flag if it exists
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        key: &str,
+        value: &str,
+        secret: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ProjectEnvVar,
+            r#"INSERT INTO project_env_vars (id, project_id, key, value, secret)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(project_id, key) DO UPDATE SET
+                   value = excluded.value,
+                   secret = excluded.secret,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", key, value, secret as "secret!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            key,
+            value,
+            secret
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        key: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_env_vars WHERE project_id = $1 AND key = $2",
+            project_id,
+            key
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
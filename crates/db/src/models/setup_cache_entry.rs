@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Setup script output saved for a project
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct SetupCacheEntry {
+    pub project_id: Uuid,
+    /// Hash of the setup script, the cached paths and the key files
+    pub cache_key: String,
+    pub size_bytes: i64,
+    /// Attempts that reused this entry instead of running the setup script
+    pub hit_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+impl SetupCacheEntry {
+    pub async fn find(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SetupCacheEntry,
+            r#"SELECT project_id as "project_id!: Uuid", cache_key, size_bytes, hit_count, created_at as "created_at!: DateTime<Utc>", last_used_at as "last_used_at!: DateTime<Utc>"
+               FROM setup_cache_entries
+               WHERE project_id = $1 AND cache_key = $2"#,
+            project_id,
+            cache_key
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The project's entries, most recently used first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SetupCacheEntry,
+            r#"SELECT project_id as "project_id!: Uuid", cache_key, size_bytes, hit_count, created_at as "created_at!: DateTime<Utc>", last_used_at as "last_used_at!: DateTime<Utc>"
+               FROM setup_cache_entries
+               WHERE project_id = $1
+               ORDER BY last_used_at DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record an entry whose files have been saved, replacing an older one with the same key
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+        size_bytes: i64,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            SetupCacheEntry,
+            r#"INSERT INTO setup_cache_entries (project_id, cache_key, size_bytes)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (project_id, cache_key) DO UPDATE
+               SET size_bytes = EXCLUDED.size_bytes,
+                   hit_count = 0,
+                   created_at = datetime('now', 'subsec'),
+                   last_used_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", cache_key, size_bytes, hit_count, created_at as "created_at!: DateTime<Utc>", last_used_at as "last_used_at!: DateTime<Utc>""#,
+            project_id,
+            cache_key,
+            size_bytes
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn record_hit(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE setup_cache_entries
+               SET hit_count = hit_count + 1, last_used_at = datetime('now', 'subsec')
+               WHERE project_id = $1 AND cache_key = $2"#,
+            project_id,
+            cache_key
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM setup_cache_entries WHERE project_id = $1 AND cache_key = $2",
+            project_id,
+            cache_key
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM setup_cache_entries WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -4,11 +4,43 @@ use sqlx::{FromRow, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
+/// What a template variable's value must look like
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateVariableKind {
+    #[default]
+    Text,
+    /// An http(s) URL
+    Url,
+    Number,
+    /// `true` or `false`
+    Boolean,
+}
+
+/// A `{{name}}` placeholder declared by a tag, filled in when a task is created from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct TemplateVariable {
+    /// Lowercase letters, digits and `_`, starting with a letter
+    pub name: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub kind: TemplateVariableKind,
+    /// Used when no value is given; variables without one are required
+    #[serde(default)]
+    #[ts(optional)]
+    pub default: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub description: Option<String>,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Tag {
     pub id: Uuid,
     pub tag_name: String,
     pub content: String,
+    #[ts(type = "Array<TemplateVariable>")]
+    pub variables: sqlx::types::Json<Vec<TemplateVariable>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -17,19 +49,23 @@ pub struct Tag {
 pub struct CreateTag {
     pub tag_name: String,
     pub content: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub variables: Vec<TemplateVariable>,
 }
 
 #[derive(Debug, Deserialize, TS)]
 pub struct UpdateTag {
     pub tag_name: Option<String>,
     pub content: Option<String>,
+    pub variables: Option<Vec<TemplateVariable>>,
 }
 
 impl Tag {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Tag,
-            r#"SELECT id as "id!: Uuid", tag_name, content as "content!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", tag_name, content as "content!", variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tags
                ORDER BY tag_name ASC"#
         )
@@ -40,7 +76,7 @@ impl Tag {
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Tag,
-            r#"SELECT id as "id!: Uuid", tag_name, content as "content!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", tag_name, content as "content!", variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tags
                WHERE id = $1"#,
             id
@@ -49,16 +85,33 @@ impl Tag {
         .await
     }
 
+    pub async fn find_by_name(
+        pool: &SqlitePool,
+        tag_name: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Tag,
+            r#"SELECT id as "id!: Uuid", tag_name, content as "content!", variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tags
+               WHERE tag_name = $1"#,
+            tag_name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     pub async fn create(pool: &SqlitePool, data: &CreateTag) -> Result<Self, sqlx::Error> {
         let id = Uuid::new_v4();
+        let variables = sqlx::types::Json(&data.variables);
         sqlx::query_as!(
             Tag,
-            r#"INSERT INTO tags (id, tag_name, content)
-               VALUES ($1, $2, $3)
-               RETURNING id as "id!: Uuid", tag_name, content as "content!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tags (id, tag_name, content, variables)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id as "id!: Uuid", tag_name, content as "content!", variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             data.tag_name,
-            data.content
+            data.content,
+            variables
         )
         .fetch_one(pool)
         .await
@@ -75,16 +128,18 @@ impl Tag {
 
         let tag_name = data.tag_name.as_ref().unwrap_or(&existing.tag_name);
         let content = data.content.as_ref().unwrap_or(&existing.content);
+        let variables = sqlx::types::Json(data.variables.as_ref().unwrap_or(&existing.variables.0));
 
         sqlx::query_as!(
             Tag,
             r#"UPDATE tags
-               SET tag_name = $2, content = $3, updated_at = datetime('now', 'subsec')
+               SET tag_name = $2, content = $3, variables = $4, updated_at = datetime('now', 'subsec')
                WHERE id = $1
-               RETURNING id as "id!: Uuid", tag_name, content as "content!", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", tag_name, content as "content!", variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             tag_name,
-            content
+            content,
+            variables
         )
         .fetch_one(pool)
         .await
@@ -5,7 +5,13 @@ use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, tag::Tag, task_attempt::TaskAttempt};
+use super::{
+    project::Project,
+    tag::{Tag, TemplateVariable},
+    task_attempt::{AttemptReviewState, TaskAttempt},
+    task_attempt_diff_stats::AttemptDiffStats,
+    test_run_result::TestCounts,
+};
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display)]
 #[sqlx(type_name = "task_status", rename_all = "lowercase")]
@@ -19,6 +25,33 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+/// How urgently a task should be worked on. Queued coding agents of higher-priority tasks
+/// start first.
+#[derive(
+    Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display,
+)]
+#[sqlx(type_name = "task_priority", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TaskPriority {
+    Urgent,
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+/// Why a task needs a human right now
+#[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "attention_reason", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AttentionReason {
+    /// A running executor is waiting for a tool approval
+    PendingApproval,
+    /// A running coding agent has stopped producing output
+    Stalled,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct Task {
     pub id: Uuid,
@@ -27,6 +60,16 @@ pub struct Task {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_task_attempt: Option<Uuid>, // Foreign key to parent TaskAttempt
+    pub parent_task_id: Option<Uuid>,      // Foreign key to parent Task, for subtasks
+    pub priority: TaskPriority,
+    /// Position within the task's board column, lowest first
+    pub sort_order: i64,
+    /// User the task is assigned to
+    pub assignee: Option<String>,
+    /// Board column the task sits in; its status always matches the column's
+    pub column_id: Option<Uuid>,
+    /// Key of the issue in an external tracker (e.g. `ENG-42`) the task is synced with
+    pub external_key: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -41,6 +84,100 @@ pub struct TaskWithAttemptStatus {
     pub last_attempt_failed: bool,
     pub executor: String,
     pub tags: Vec<Tag>,
+    /// Most recent preview deployment URL reported for any of the task's attempts
+    pub preview_url: Option<String>,
+    pub staleness: TaskStaleness,
+    /// Set while one of the task's running executions is blocked on the user
+    pub needs_attention: Option<AttentionReason>,
+    /// Results of the task's most recent test run
+    pub test_results: Option<TestCounts>,
+    /// Set while a task it depends on isn't done
+    pub is_blocked: bool,
+    /// How many of the task's subtasks are done, if it has any
+    pub subtask_progress: Option<SubtaskProgress>,
+    /// Code-review state of the task's latest attempt
+    pub review_state: Option<AttemptReviewState>,
+    /// Size of the latest attempt's changes, as of its last finished execution process
+    pub diff_stats: Option<AttemptDiffStats>,
+}
+
+/// Done subtasks out of all of a task's subtasks; cancelled subtasks count as neither
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+pub struct SubtaskProgress {
+    pub done: i64,
+    pub total: i64,
+}
+
+fn default_stale_execution_days() -> u32 {
+    7
+}
+
+fn default_stale_base_branch_drift() -> u32 {
+    20
+}
+
+fn default_stale_draft_age_hours() -> u32 {
+    24
+}
+
+/// Limits past which open tasks are flagged as stale. A limit of 0 disables that check.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct StalenessThresholds {
+    /// Days since the task's last execution
+    #[serde(default = "default_stale_execution_days")]
+    pub execution_days: u32,
+    /// Commits the latest attempt is behind its base branch
+    #[serde(default = "default_stale_base_branch_drift")]
+    pub base_branch_drift: u32,
+    /// Hours an unsent follow-up draft has been left untouched
+    #[serde(default = "default_stale_draft_age_hours")]
+    pub draft_age_hours: u32,
+}
+
+impl Default for StalenessThresholds {
+    fn default() -> Self {
+        Self {
+            execution_days: default_stale_execution_days(),
+            base_branch_drift: default_stale_base_branch_drift(),
+            draft_age_hours: default_stale_draft_age_hours(),
+        }
+    }
+}
+
+/// How long a task has been left alone, computed when the task list is loaded
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct TaskStaleness {
+    /// Whole days since the most recent execution of any of the task's attempts
+    pub days_since_last_execution: Option<i64>,
+    /// Commits the latest attempt was behind its base branch at the last background sync
+    pub base_branch_drift: Option<i64>,
+    /// Whole hours since an unsent follow-up draft on one of the attempts was last edited
+    pub follow_up_draft_age_hours: Option<i64>,
+    /// Whether any of the above exceeds its threshold on a task that is still open
+    pub is_stale: bool,
+}
+
+impl TaskStaleness {
+    fn new(
+        status: &TaskStatus,
+        days_since_last_execution: Option<i64>,
+        base_branch_drift: Option<i64>,
+        follow_up_draft_age_hours: Option<i64>,
+        thresholds: &StalenessThresholds,
+    ) -> Self {
+        let exceeds =
+            |value: Option<i64>, limit: u32| limit > 0 && value.is_some_and(|v| v >= limit.into());
+        let is_stale = !matches!(status, TaskStatus::Done | TaskStatus::Cancelled)
+            && (exceeds(days_since_last_execution, thresholds.execution_days)
+                || exceeds(base_branch_drift, thresholds.base_branch_drift)
+                || exceeds(follow_up_draft_age_hours, thresholds.draft_age_hours));
+        Self {
+            days_since_last_execution,
+            base_branch_drift,
+            follow_up_draft_age_hours,
+            is_stale,
+        }
+    }
 }
 
 impl std::ops::Deref for TaskWithAttemptStatus {
@@ -69,6 +206,17 @@ pub struct CreateTask {
     pub title: String,
     pub description: Option<String>,
     pub parent_task_attempt: Option<Uuid>,
+    /// Create the task as a subtask of this task
+    #[serde(default)]
+    #[ts(optional)]
+    pub parent_task_id: Option<Uuid>,
+    /// Defaults to normal
+    #[serde(default)]
+    #[ts(optional)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub assignee: Option<String>,
     pub image_ids: Option<Vec<Uuid>>,
     pub tag_ids: Option<Vec<Uuid>>,
 }
@@ -84,6 +232,9 @@ impl CreateTask {
             title,
             description,
             parent_task_attempt: None,
+            parent_task_id: None,
+            priority: None,
+            assignee: None,
             image_ids: None,
             tag_ids: None,
         }
@@ -96,6 +247,17 @@ pub struct UpdateTask {
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub parent_task_attempt: Option<Uuid>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub priority: Option<TaskPriority>,
+    /// An empty string unassigns the task
+    #[serde(default)]
+    #[ts(optional)]
+    pub assignee: Option<String>,
+    /// Moves the task into this board column, taking on the column's status
+    #[serde(default)]
+    #[ts(optional)]
+    pub column_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
     pub tag_ids: Option<Vec<Uuid>>,
 }
@@ -116,6 +278,7 @@ impl Task {
     pub async fn find_by_project_id_with_attempt_status(
         pool: &SqlitePool,
         project_id: Uuid,
+        staleness_thresholds: &StalenessThresholds,
     ) -> Result<Vec<TaskWithAttemptStatus>, sqlx::Error> {
         let records = sqlx::query!(
             r#"SELECT
@@ -125,6 +288,12 @@ impl Task {
   t.description,
   t.status                        AS "status!: TaskStatus",
   t.parent_task_attempt           AS "parent_task_attempt: Uuid",
+  t.parent_task_id                AS "parent_task_id: Uuid",
+  t.priority                      AS "priority!: TaskPriority",
+  t.sort_order                    AS "sort_order!: i64",
+  t.assignee,
+  t.column_id                     AS "column_id: Uuid",
+  t.external_key,
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
 
@@ -134,11 +303,21 @@ impl Task {
       JOIN execution_processes ep
         ON ep.task_attempt_id = ta.id
      WHERE ta.task_id       = t.id
-       AND ep.status        = 'running'
-       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+       AND ep.status       IN ('queued','running')
+       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent','testscript')
      LIMIT 1
   ) THEN 1 ELSE 0 END            AS "has_in_progress_attempt!: i64",
 
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM task_attempts ta
+      JOIN merges m
+        ON m.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+       AND (m.merge_type = 'direct' OR m.pr_status = 'merged')
+     LIMIT 1
+  ) THEN 1 ELSE 0 END            AS "has_merged_attempt!: i64",
+
   CASE WHEN (
     SELECT ep.status
       FROM task_attempts ta
@@ -156,11 +335,111 @@ impl Task {
       WHERE ta.task_id = t.id
      ORDER BY ta.created_at DESC
       LIMIT 1
-    )                               AS "executor!: String"
+    )                               AS "executor!: String",
+
+  ( SELECT ta.preview_url
+      FROM task_attempts ta
+      WHERE ta.task_id = t.id
+        AND ta.preview_url IS NOT NULL
+     ORDER BY ta.updated_at DESC
+      LIMIT 1
+    )                               AS "preview_url: String",
+
+  ( SELECT CAST(julianday('now') - julianday(MAX(ep.started_at)) AS INTEGER)
+      FROM task_attempts ta
+      JOIN execution_processes ep
+        ON ep.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+    )                               AS "days_since_last_execution: i64",
+
+  ( SELECT bbs.commits_behind
+      FROM task_attempts ta
+      JOIN base_branch_syncs bbs
+        ON bbs.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+     ORDER BY ta.created_at DESC
+      LIMIT 1
+    )                               AS "base_branch_drift?: i64",
+
+  ( SELECT CAST((julianday('now') - julianday(MAX(d.updated_at))) * 24 AS INTEGER)
+      FROM task_attempts ta
+      JOIN drafts d
+        ON d.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+       AND d.draft_type = 'follow_up'
+       AND d.sending = 0
+       AND TRIM(d.prompt) != ''
+    )                               AS "follow_up_draft_age_hours: i64",
+
+  ( SELECT CASE WHEN ep.awaiting_approval_at IS NOT NULL
+                THEN 'pending_approval' ELSE 'stalled' END
+      FROM task_attempts ta
+      JOIN execution_processes ep
+        ON ep.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+       AND ep.status = 'running'
+       AND (ep.awaiting_approval_at IS NOT NULL OR ep.stalled_at IS NOT NULL)
+     ORDER BY ep.awaiting_approval_at IS NULL, ep.created_at DESC
+      LIMIT 1
+    )                               AS "needs_attention?: AttentionReason",
+
+  CASE WHEN EXISTS (
+    SELECT 1
+      FROM task_dependencies td
+      JOIN tasks dep
+        ON dep.id = td.depends_on_task_id
+     WHERE td.task_id = t.id
+       AND dep.status != 'done'
+  ) THEN 1 ELSE 0 END            AS "is_blocked!: i64",
+
+  ( SELECT COUNT(*)
+      FROM tasks st
+     WHERE st.parent_task_id = t.id
+       AND st.status = 'done'
+    )                               AS "subtasks_done!: i64",
+
+  ( SELECT COUNT(*)
+      FROM tasks st
+     WHERE st.parent_task_id = t.id
+       AND st.status != 'cancelled'
+    )                               AS "subtasks_total!: i64",
+
+  ( SELECT ta.review_state
+      FROM task_attempts ta
+     WHERE ta.task_id = t.id
+     ORDER BY ta.created_at DESC
+      LIMIT 1
+    )                               AS "review_state?: AttemptReviewState",
+
+  tr.passed                       AS "test_passed?: i64",
+  tr.failed                       AS "test_failed?: i64",
+  tr.skipped                      AS "test_skipped?: i64",
+
+  ds.files_changed                AS "diff_files_changed?: i64",
+  ds.additions                    AS "diff_additions?: i64",
+  ds.deletions                    AS "diff_deletions?: i64"
 
 FROM tasks t
+LEFT JOIN test_run_results tr
+  ON tr.execution_process_id = (
+    SELECT r.execution_process_id
+      FROM task_attempts ta
+      JOIN test_run_results r
+        ON r.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+     ORDER BY r.created_at DESC
+      LIMIT 1
+  )
+LEFT JOIN task_attempt_diff_stats ds
+  ON ds.task_attempt_id = (
+    SELECT ta.id
+      FROM task_attempts ta
+     WHERE ta.task_id = t.id
+     ORDER BY ta.created_at DESC
+      LIMIT 1
+  )
 WHERE t.project_id = $1
-ORDER BY t.created_at DESC"#,
+ORDER BY t.sort_order ASC, t.created_at DESC"#,
             project_id
         )
         .fetch_all(pool)
@@ -179,6 +458,7 @@ ORDER BY t.created_at DESC"#,
                     t.id as "tag_id!: Uuid",
                     t.tag_name,
                     t.content as "content!",
+                    t.variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>",
                     t.created_at as "created_at!: DateTime<Utc>",
                     t.updated_at as "updated_at!: DateTime<Utc>"
                 FROM task_tags tt
@@ -200,6 +480,7 @@ ORDER BY t.created_at DESC"#,
                 id: tag_rec.tag_id,
                 tag_name: tag_rec.tag_name,
                 content: tag_rec.content,
+                variables: tag_rec.variables,
                 created_at: tag_rec.created_at,
                 updated_at: tag_rec.updated_at,
             });
@@ -209,6 +490,13 @@ ORDER BY t.created_at DESC"#,
             .into_iter()
             .map(|rec| {
                 let tags = tags_by_task.get(&rec.id).cloned().unwrap_or_default();
+                let staleness = TaskStaleness::new(
+                    &rec.status,
+                    rec.days_since_last_execution,
+                    rec.base_branch_drift,
+                    rec.follow_up_draft_age_hours,
+                    staleness_thresholds,
+                );
                 TaskWithAttemptStatus {
                     task: Task {
                         id: rec.id,
@@ -217,14 +505,51 @@ ORDER BY t.created_at DESC"#,
                         description: rec.description,
                         status: rec.status,
                         parent_task_attempt: rec.parent_task_attempt,
+                        parent_task_id: rec.parent_task_id,
+                        priority: rec.priority,
+                        sort_order: rec.sort_order,
+                        assignee: rec.assignee,
+                        column_id: rec.column_id,
+                        external_key: rec.external_key,
                         created_at: rec.created_at,
                         updated_at: rec.updated_at,
                     },
                     has_in_progress_attempt: rec.has_in_progress_attempt != 0,
-                    has_merged_attempt: false, // TODO use merges table
+                    has_merged_attempt: rec.has_merged_attempt != 0,
                     last_attempt_failed: rec.last_attempt_failed != 0,
                     executor: rec.executor,
                     tags,
+                    preview_url: rec.preview_url,
+                    staleness,
+                    needs_attention: rec.needs_attention,
+                    test_results: match (rec.test_passed, rec.test_failed, rec.test_skipped) {
+                        (Some(passed), Some(failed), Some(skipped)) => Some(TestCounts {
+                            passed,
+                            failed,
+                            skipped,
+                        }),
+                        _ => None,
+                    },
+                    is_blocked: rec.is_blocked != 0,
+                    subtask_progress: (rec.subtasks_total > 0).then_some(SubtaskProgress {
+                        done: rec.subtasks_done,
+                        total: rec.subtasks_total,
+                    }),
+                    review_state: rec.review_state,
+                    diff_stats: match (
+                        rec.diff_files_changed,
+                        rec.diff_additions,
+                        rec.diff_deletions,
+                    ) {
+                        (Some(files_changed), Some(additions), Some(deletions)) => {
+                            Some(AttemptDiffStats {
+                                files_changed,
+                                additions,
+                                deletions,
+                            })
+                        }
+                        _ => None,
+                    },
                 }
             })
             .collect();
@@ -235,7 +560,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE id = $1"#,
             id
@@ -247,7 +572,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE rowid = $1"#,
             rowid
@@ -263,7 +588,7 @@ ORDER BY t.created_at DESC"#,
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE id = $1 AND project_id = $2"#,
             id,
@@ -280,20 +605,26 @@ ORDER BY t.created_at DESC"#,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt) 
-               VALUES ($1, $2, $3, $4, $5, $6) 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt, parent_task_id, priority, sort_order, assignee, column_id) 
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, (SELECT COALESCE(MIN(sort_order), 1) - 1 FROM tasks WHERE project_id = $2), $9,
+                       (SELECT id FROM project_columns WHERE project_id = $2 AND status = $5 ORDER BY position LIMIT 1)) 
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
             data.description,
             TaskStatus::Todo as TaskStatus,
-            data.parent_task_attempt
+            data.parent_task_attempt,
+            data.parent_task_id,
+            data.priority.unwrap_or_default(),
+            data.assignee
         )
         .fetch_one(pool)
         .await
     }
 
+    /// Without a `column_id` the task stays in its column if that still maps to `status`, and
+    /// otherwise moves to the first column that does. A given `column_id` must map to `status`.
     pub async fn update(
         pool: &SqlitePool,
         id: Uuid,
@@ -302,19 +633,30 @@ ORDER BY t.created_at DESC"#,
         description: Option<String>,
         status: TaskStatus,
         parent_task_attempt: Option<Uuid>,
+        priority: TaskPriority,
+        assignee: Option<String>,
+        column_id: Option<Uuid>,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Task,
             r#"UPDATE tasks 
-               SET title = $3, description = $4, status = $5, parent_task_attempt = $6 
+               SET title = $3, description = $4, status = $5, parent_task_attempt = $6, priority = $7, assignee = $8,
+                   column_id = CASE
+                       WHEN $9 IS NOT NULL THEN $9
+                       WHEN column_id IN (SELECT id FROM project_columns WHERE status = $5) THEN column_id
+                       ELSE (SELECT id FROM project_columns WHERE project_id = $2 AND status = $5 ORDER BY position LIMIT 1)
+                   END 
                WHERE id = $1 AND project_id = $2 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             project_id,
             title,
             description,
             status,
-            parent_task_attempt
+            parent_task_attempt,
+            priority,
+            assignee,
+            column_id
         )
         .fetch_one(pool)
         .await
@@ -326,7 +668,14 @@ ORDER BY t.created_at DESC"#,
         status: TaskStatus,
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
-            "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            r#"UPDATE tasks
+               SET status = $2,
+                   column_id = CASE
+                       WHEN column_id IN (SELECT id FROM project_columns WHERE status = $2) THEN column_id
+                       ELSE (SELECT id FROM project_columns WHERE project_id = tasks.project_id AND status = $2 ORDER BY position LIMIT 1)
+                   END,
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1"#,
             id,
             status
         )
@@ -335,6 +684,30 @@ ORDER BY t.created_at DESC"#,
         Ok(())
     }
 
+    /// Number the given tasks of a project in this order. Positions are only compared within a
+    /// board column, so callers pass a whole column. Ids of tasks outside the project are
+    /// ignored.
+    pub async fn reorder(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        task_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for (position, task_id) in task_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE tasks SET sort_order = $3 WHERE id = $1 AND project_id = $2 AND sort_order != $3",
+                task_id,
+                project_id,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     /// Nullify parent_task_attempt for all tasks that reference the given attempt ID
     /// This breaks parent-child relationships before deleting a parent task
     pub async fn nullify_children_by_attempt_id<'e, E>(
@@ -378,6 +751,115 @@ ORDER BY t.created_at DESC"#,
         Ok(result.is_some())
     }
 
+    /// The project's tasks, oldest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_external_key(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        external_key: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE project_id = $1 AND external_key = $2"#,
+            project_id,
+            external_key
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set_external_key(
+        pool: &SqlitePool,
+        id: Uuid,
+        external_key: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET external_key = $2 WHERE id = $1",
+            id,
+            external_key
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Insert a task read from a project archive, keeping its timestamps. It is put in the first
+    /// column for its status and has no parents; those are set with [`Task::set_parents`] once
+    /// they exist.
+    pub async fn restore<'e, E>(executor: E, task: &Task) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO tasks (id, project_id, title, description, status, priority, sort_order, assignee, column_id, created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8,
+                       (SELECT id FROM project_columns WHERE project_id = $2 AND status = $5 ORDER BY position LIMIT 1),
+                       $9, $10)"#,
+            task.id,
+            task.project_id,
+            task.title,
+            task.description,
+            task.status,
+            task.priority,
+            task.sort_order,
+            task.assignee,
+            task.created_at,
+            task.updated_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_parents<'e, E>(
+        executor: E,
+        id: Uuid,
+        parent_task_attempt: Option<Uuid>,
+        parent_task_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "UPDATE tasks SET parent_task_attempt = $2, parent_task_id = $3 WHERE id = $1",
+            id,
+            parent_task_attempt,
+            parent_task_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_titles_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT title as "title!" FROM tasks WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn find_children_by_attempt_id(
         pool: &SqlitePool,
         attempt_id: Uuid,
@@ -385,7 +867,7 @@ ORDER BY t.created_at DESC"#,
         // Find only child tasks that have this attempt as their parent
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks 
                WHERE parent_task_attempt = $1
                ORDER BY created_at DESC"#,
@@ -395,6 +877,23 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    /// Subtasks of a task, oldest first
+    pub async fn find_subtasks(
+        pool: &SqlitePool,
+        parent_task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", parent_task_id as "parent_task_id: Uuid", priority as "priority!: TaskPriority", sort_order as "sort_order!: i64", assignee, column_id as "column_id: Uuid", external_key, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks 
+               WHERE parent_task_id = $1
+               ORDER BY created_at ASC"#,
+            parent_task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn find_relationships_for_attempt(
         pool: &SqlitePool,
         task_attempt: &TaskAttempt,
@@ -432,7 +931,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_tags_for_task(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Tag>, sqlx::Error> {
         sqlx::query_as!(
             Tag,
-            r#"SELECT t.id as "id!: Uuid", t.tag_name, t.content as "content!", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT t.id as "id!: Uuid", t.tag_name, t.content as "content!", t.variables as "variables!: sqlx::types::Json<Vec<TemplateVariable>>", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
                FROM tags t
                JOIN task_tags tt ON t.id = tt.tag_id
                WHERE tt.task_id = $1
@@ -443,13 +942,16 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
-    pub async fn add_tag(pool: &SqlitePool, task_id: Uuid, tag_id: Uuid) -> Result<(), sqlx::Error> {
+    pub async fn add_tag<'e, E>(executor: E, task_id: Uuid, tag_id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query!(
             "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES ($1, $2)",
             task_id,
             tag_id
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(())
     }
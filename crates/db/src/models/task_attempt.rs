@@ -1,12 +1,16 @@
 use chrono::{DateTime, Utc};
 use executors::executors::BaseCodingAgent;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, task::Task};
+use super::{
+    merge::{MergeStatus, MergeType},
+    project::Project,
+    task::Task,
+};
 
 #[derive(Debug, Error)]
 pub enum TaskAttemptError {
@@ -34,6 +38,16 @@ pub enum TaskAttemptStatus {
     ExecutorFailed,
 }
 
+/// Code-review state of an attempt's output, tracked separately from its task's status
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "attempt_review_state", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptReviewState {
+    Reviewed,
+    Approved,
+    NeedsChanges,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct TaskAttempt {
     pub id: Uuid,
@@ -45,6 +59,12 @@ pub struct TaskAttempt {
     // "GEMINI", etc.)
     pub worktree_deleted: bool, // Flag indicating if worktree has been cleaned up
     pub setup_completed_at: Option<DateTime<Utc>>, // When setup script was last completed
+    pub preview_url: Option<String>, // Preview deployment returned by the project's deploy hook
+    pub started_by: Option<String>, // User who started the attempt, unset if the server did
+    pub review_state: Option<AttemptReviewState>, // Unset until someone reviews the output
+    pub review_note: Option<String>,
+    pub reviewed_by: Option<String>, // User who set the review state
+    pub reviewed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -79,11 +99,44 @@ pub struct TaskAttemptContext {
     pub project: Project,
 }
 
+/// Agent configuration of an attempt and how its work ended up, used for reporting
+#[derive(Debug, Clone)]
+pub struct AttemptOutcome {
+    pub task_attempt_id: Uuid,
+    pub executor: String,
+    /// Variant of the attempt's first coding agent run
+    pub variant: Option<String>,
+    /// Follow-up runs that are still part of the attempt's history
+    pub follow_ups: i64,
+    /// Type of the attempt's latest merge, if any
+    pub merge_type: Option<MergeType>,
+    pub pr_status: Option<MergeStatus>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Worktree of an attempt whose task has been finished for a while
+#[derive(Debug, Clone)]
+pub struct FinishedWorktree {
+    pub attempt_id: Uuid,
+    pub task_id: Uuid,
+    pub container_ref: String,
+    pub git_repo_path: String,
+}
+
 #[derive(Debug, Deserialize, TS)]
 pub struct CreateTaskAttempt {
     pub executor: BaseCodingAgent,
     pub base_branch: String,
     pub branch: String,
+    #[serde(default)]
+    pub started_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateAttemptReview {
+    /// Clears the review when unset
+    pub state: Option<AttemptReviewState>,
+    pub note: Option<String>,
 }
 
 impl TaskAttempt {
@@ -107,6 +160,12 @@ impl TaskAttempt {
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              preview_url,
+                              started_by,
+                              review_state AS "review_state: AttemptReviewState",
+                              review_note,
+                              reviewed_by,
+                              reviewed_at AS "reviewed_at: DateTime<Utc>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM task_attempts
@@ -127,6 +186,12 @@ impl TaskAttempt {
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              preview_url,
+                              started_by,
+                              review_state AS "review_state: AttemptReviewState",
+                              review_note,
+                              reviewed_by,
+                              reviewed_at AS "reviewed_at: DateTime<Utc>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM task_attempts
@@ -158,6 +223,12 @@ impl TaskAttempt {
                        ta.executor AS "executor!",
                        ta.worktree_deleted  AS "worktree_deleted!: bool",
                        ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       ta.preview_url,
+                       ta.started_by,
+                       ta.review_state AS "review_state: AttemptReviewState",
+                       ta.review_note,
+                       ta.reviewed_by,
+                       ta.reviewed_at AS "reviewed_at: DateTime<Utc>",
                        ta.created_at        AS "created_at!: DateTime<Utc>",
                        ta.updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts ta
@@ -231,6 +302,12 @@ impl TaskAttempt {
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       preview_url,
+                       started_by,
+                       review_state AS "review_state: AttemptReviewState",
+                       review_note,
+                       reviewed_by,
+                       reviewed_at AS "reviewed_at: DateTime<Utc>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -252,6 +329,12 @@ impl TaskAttempt {
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       preview_url,
+                       started_by,
+                       review_state AS "review_state: AttemptReviewState",
+                       review_note,
+                       reviewed_by,
+                       reviewed_at AS "reviewed_at: DateTime<Utc>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -299,6 +382,49 @@ impl TaskAttempt {
             .collect())
     }
 
+    /// Attempts whose worktree exists on disk, with the repository each belongs to
+    pub async fn find_active_worktrees(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Uuid, String, String)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"
+            SELECT ta.id as "attempt_id!: Uuid", ta.container_ref as "container_ref!", p.git_repo_path as "git_repo_path!"
+            FROM task_attempts ta
+            JOIN tasks t ON ta.task_id = t.id
+            JOIN projects p ON t.project_id = p.id
+            WHERE ta.worktree_deleted = FALSE AND ta.container_ref IS NOT NULL
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| (r.attempt_id, r.container_ref, r.git_repo_path))
+            .collect())
+    }
+
+    /// Rewrite the container refs under directory `from` to point under `to` instead, after the
+    /// worktree directory was moved. Returns the number of attempts updated.
+    pub async fn relocate_container_refs(
+        pool: &SqlitePool,
+        from: &str,
+        to: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            r#"UPDATE task_attempts
+               SET container_ref = $2 || substr(container_ref, length($1) + 1), updated_at = $3
+               WHERE substr(container_ref, 1, length($1) + 1) = $1 || '/'"#,
+            from,
+            to,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn container_ref_exists(
         pool: &SqlitePool,
         container_ref: &str,
@@ -362,6 +488,171 @@ impl TaskAttempt {
             .collect())
     }
 
+    /// Find the latest attempt of each task whose last process failed, narrowed by the
+    /// given filters. Oldest first.
+    pub async fn find_failed(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+        created_after: Option<DateTime<Utc>>,
+        created_before: Option<DateTime<Utc>>,
+        executor: Option<&str>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttempt,
+            r#"SELECT ta.id AS "id!: Uuid",
+                      ta.task_id AS "task_id!: Uuid",
+                      ta.container_ref,
+                      ta.branch,
+                      ta.target_branch,
+                      ta.executor AS "executor!",
+                      ta.worktree_deleted AS "worktree_deleted!: bool",
+                      ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                      ta.preview_url,
+                      ta.started_by,
+                      ta.review_state AS "review_state: AttemptReviewState",
+                      ta.review_note,
+                      ta.reviewed_by,
+                      ta.reviewed_at AS "reviewed_at: DateTime<Utc>",
+                      ta.created_at AS "created_at!: DateTime<Utc>",
+                      ta.updated_at AS "updated_at!: DateTime<Utc>"
+               FROM task_attempts ta
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE ($1 IS NULL OR t.project_id = $1)
+                 AND ($2 IS NULL OR ta.created_at >= $2)
+                 AND ($3 IS NULL OR ta.created_at <= $3)
+                 AND ($4 IS NULL OR ta.executor = $4)
+                 -- Earlier attempts of a task have already been superseded
+                 AND ta.created_at = (
+                     SELECT MAX(ta2.created_at) FROM task_attempts ta2 WHERE ta2.task_id = ta.task_id
+                 )
+                 AND (
+                     SELECT ep.status
+                       FROM execution_processes ep
+                      WHERE ep.task_attempt_id = ta.id
+                        AND ep.dropped = FALSE
+                        AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+                      ORDER BY ep.created_at DESC
+                      LIMIT 1
+                 ) = 'failed'
+               ORDER BY ta.created_at ASC"#,
+            project_id,
+            created_after,
+            created_before,
+            executor
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Worktrees that still exist for tasks that have been done or cancelled for at least
+    /// `retention_days`, skipping attempts with running processes
+    pub async fn find_finished_worktrees(
+        pool: &SqlitePool,
+        retention_days: u32,
+    ) -> Result<Vec<FinishedWorktree>, sqlx::Error> {
+        let cutoff = format!("-{retention_days} days");
+        sqlx::query_as!(
+            FinishedWorktree,
+            r#"SELECT ta.id AS "attempt_id!: Uuid",
+                      ta.task_id AS "task_id!: Uuid",
+                      ta.container_ref AS "container_ref!",
+                      p.git_repo_path AS "git_repo_path!"
+               FROM task_attempts ta
+               JOIN tasks t ON t.id = ta.task_id
+               JOIN projects p ON p.id = t.project_id
+               WHERE ta.worktree_deleted = FALSE
+                 AND ta.container_ref IS NOT NULL
+                 AND t.status IN ('done', 'cancelled')
+                 AND datetime(t.updated_at) <= datetime('now', $1)
+                 AND NOT EXISTS (
+                     SELECT 1 FROM execution_processes ep
+                      WHERE ep.task_attempt_id = ta.id AND ep.status IN ('queued', 'running')
+                 )
+               ORDER BY t.updated_at ASC"#,
+            cutoff
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Outcomes of the project's attempts created since `since`, oldest first
+    pub async fn find_outcomes_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AttemptOutcome>, sqlx::Error> {
+        sqlx::query_as!(
+            AttemptOutcome,
+            r#"SELECT ta.id AS "task_attempt_id!: Uuid",
+                      ta.executor AS "executor!",
+                      (SELECT json_extract(ep.executor_action, '$.typ.executor_profile_id.variant')
+                         FROM execution_processes ep
+                        WHERE ep.task_attempt_id = ta.id AND ep.run_reason = 'codingagent'
+                        ORDER BY ep.created_at ASC
+                        LIMIT 1) AS "variant?: String",
+                      (SELECT COUNT(*)
+                         FROM execution_processes ep
+                        WHERE ep.task_attempt_id = ta.id
+                          AND ep.executor_action_type = 'CodingAgentFollowUpRequest'
+                          AND ep.dropped = FALSE) AS "follow_ups!: i64",
+                      m.merge_type AS "merge_type?: MergeType",
+                      m.pr_status AS "pr_status?: MergeStatus",
+                      ta.created_at AS "created_at!: DateTime<Utc>"
+               FROM task_attempts ta
+               JOIN tasks t ON t.id = ta.task_id
+               LEFT JOIN merges m ON m.id = (
+                   SELECT id FROM merges
+                    WHERE task_attempt_id = ta.id
+                    ORDER BY created_at DESC
+                    LIMIT 1
+               )
+               WHERE t.project_id = $1 AND ta.created_at >= $2
+               ORDER BY ta.created_at ASC"#,
+            project_id,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Attempts whose base branch should be checked by the background sync: open tasks in
+    /// projects that opted in, with a worktree and no running processes.
+    pub async fn find_for_base_branch_sync(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttempt,
+            r#"SELECT ta.id AS "id!: Uuid",
+                      ta.task_id AS "task_id!: Uuid",
+                      ta.container_ref,
+                      ta.branch,
+                      ta.target_branch,
+                      ta.executor AS "executor!",
+                      ta.worktree_deleted AS "worktree_deleted!: bool",
+                      ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                      ta.preview_url,
+                      ta.started_by,
+                      ta.review_state AS "review_state: AttemptReviewState",
+                      ta.review_note,
+                      ta.reviewed_by,
+                      ta.reviewed_at AS "reviewed_at: DateTime<Utc>",
+                      ta.created_at AS "created_at!: DateTime<Utc>",
+                      ta.updated_at AS "updated_at!: DateTime<Utc>"
+               FROM task_attempts ta
+               JOIN tasks t ON t.id = ta.task_id
+               JOIN projects p ON p.id = t.project_id
+               WHERE p.sync_base_branch = TRUE
+                 AND t.status NOT IN ('done', 'cancelled')
+                 AND ta.worktree_deleted = FALSE
+                 AND ta.container_ref IS NOT NULL
+                 AND NOT EXISTS (
+                     SELECT 1 FROM execution_processes ep
+                      WHERE ep.task_attempt_id = ta.id AND ep.status IN ('queued', 'running')
+                 )
+               ORDER BY ta.created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateTaskAttempt,
@@ -372,9 +663,9 @@ impl TaskAttempt {
         // Insert the record into the database
         Ok(sqlx::query_as!(
             TaskAttempt,
-            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, target_branch, executor, worktree_deleted, setup_completed_at)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, target_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, target_branch, executor, worktree_deleted, setup_completed_at, started_by)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, target_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", preview_url, started_by, review_state as "review_state: AttemptReviewState", review_note, reviewed_by, reviewed_at as "reviewed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             task_id,
             Option::<String>::None, // Container isn't known yet
@@ -382,12 +673,42 @@ impl TaskAttempt {
             data.base_branch, // Target branch is same as base branch during creation
             data.executor,
             false, // worktree_deleted is false during creation
-            Option::<DateTime<Utc>>::None // setup_completed_at is None during creation
+            Option::<DateTime<Utc>>::None, // setup_completed_at is None during creation
+            data.started_by
         )
         .fetch_one(pool)
         .await?)
     }
 
+    /// Insert an attempt read from a project archive, keeping its timestamps. Its worktree
+    /// isn't part of the archive, so it is marked as deleted.
+    pub async fn restore<'e, E>(executor: E, attempt: &TaskAttempt) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, target_branch, executor, worktree_deleted, setup_completed_at, preview_url, started_by, review_state, review_note, reviewed_by, reviewed_at, created_at, updated_at)
+               VALUES ($1, $2, NULL, $3, $4, $5, TRUE, $6, $7, $8, $9, $10, $11, $12, $13, $14)"#,
+            attempt.id,
+            attempt.task_id,
+            attempt.branch,
+            attempt.target_branch,
+            attempt.executor,
+            attempt.setup_completed_at,
+            attempt.preview_url,
+            attempt.started_by,
+            attempt.review_state,
+            attempt.review_note,
+            attempt.reviewed_by,
+            attempt.reviewed_at,
+            attempt.created_at,
+            attempt.updated_at
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_target_branch(
         pool: &SqlitePool,
         attempt_id: Uuid,
@@ -404,6 +725,47 @@ impl TaskAttempt {
         Ok(())
     }
 
+    pub async fn update_preview_url(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        preview_url: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE task_attempts SET preview_url = $1, updated_at = datetime('now') WHERE id = $2",
+            preview_url,
+            attempt_id,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a review of the attempt's output, or clear it when `state` is None
+    pub async fn set_review(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        state: Option<AttemptReviewState>,
+        note: Option<&str>,
+        reviewed_by: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE task_attempts
+               SET review_state = $1,
+                   review_note = $2,
+                   reviewed_by = $3,
+                   reviewed_at = CASE WHEN $1 IS NULL THEN NULL ELSE datetime('now', 'subsec') END,
+                   updated_at = datetime('now')
+               WHERE id = $4"#,
+            state,
+            note,
+            reviewed_by,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn resolve_container_ref(
         pool: &SqlitePool,
         container_ref: &str,
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Size of an attempt's changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct AttemptDiffStats {
+    pub files_changed: i64,
+    pub additions: i64,
+    pub deletions: i64,
+}
+
+/// Size of an attempt's changes as of its last finished execution process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskAttemptDiffStats {
+    pub task_attempt_id: Uuid,
+    pub files_changed: i64,
+    pub additions: i64,
+    pub deletions: i64,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl TaskAttemptDiffStats {
+    pub fn stats(&self) -> AttemptDiffStats {
+        AttemptDiffStats {
+            files_changed: self.files_changed,
+            additions: self.additions,
+            deletions: self.deletions,
+        }
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        stats: &AttemptDiffStats,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptDiffStats,
+            r#"INSERT INTO task_attempt_diff_stats (task_attempt_id, files_changed, additions, deletions)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (task_attempt_id) DO UPDATE
+               SET files_changed = EXCLUDED.files_changed,
+                   additions = EXCLUDED.additions,
+                   deletions = EXCLUDED.deletions,
+                   computed_at = datetime('now', 'subsec')
+               RETURNING task_attempt_id as "task_attempt_id!: Uuid", files_changed, additions, deletions, computed_at as "computed_at!: DateTime<Utc>""#,
+            task_attempt_id,
+            stats.files_changed,
+            stats.additions,
+            stats.deletions
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptDiffStats,
+            r#"SELECT task_attempt_id as "task_attempt_id!: Uuid", files_changed, additions, deletions, computed_at as "computed_at!: DateTime<Utc>"
+               FROM task_attempt_diff_stats
+               WHERE rowid = $1"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Stats of the task's attempts, or of all attempts without a task
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptDiffStats,
+            r#"SELECT s.task_attempt_id as "task_attempt_id!: Uuid", s.files_changed, s.additions, s.deletions, s.computed_at as "computed_at!: DateTime<Utc>"
+               FROM task_attempt_diff_stats s
+               JOIN task_attempts ta ON ta.id = s.task_attempt_id
+               WHERE $1 IS NULL OR ta.task_id = $1"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A comment in a task's discussion. The body is markdown.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskComment {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Execution process the comment is about
+    pub execution_process_id: Option<Uuid>,
+    /// User who wrote the comment, unset when unknown
+    pub author: Option<String>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskComment {
+    pub body: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub execution_process_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateTaskComment {
+    pub body: String,
+}
+
+impl TaskComment {
+    /// The task's comments, oldest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskComment,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", author, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_comments
+               WHERE task_id = $1
+               ORDER BY created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id_and_task_id(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskComment,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", author, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_comments
+               WHERE id = $1 AND task_id = $2"#,
+            id,
+            task_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskComment,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", author, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_comments
+               WHERE rowid = $1"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        author: Option<String>,
+        data: &CreateTaskComment,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            TaskComment,
+            r#"INSERT INTO task_comments (id, task_id, execution_process_id, author, body)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", author, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            data.execution_process_id,
+            author,
+            data.body
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(pool: &SqlitePool, id: Uuid, body: &str) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskComment,
+            r#"UPDATE task_comments
+               SET body = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", author, body, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            body
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_comments WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::task::{Task, TaskPriority, TaskStatus};
+
+/// A task that has to be done before another one can be started
+pub struct TaskDependency;
+
+impl TaskDependency {
+    /// Tasks `task_id` depends on, oldest dependency first
+    pub async fn find_dependencies(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.parent_task_id as "parent_task_id: Uuid", t.priority as "priority!: TaskPriority", t.sort_order as "sort_order!: i64", t.assignee, t.column_id as "column_id: Uuid", t.external_key, t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.depends_on_task_id
+               WHERE td.task_id = $1
+               ORDER BY td.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Dependencies of `task_id` that aren't done yet
+    pub async fn find_blockers(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.parent_task_id as "parent_task_id: Uuid", t.priority as "priority!: TaskPriority", t.sort_order as "sort_order!: i64", t.assignee, t.column_id as "column_id: Uuid", t.external_key, t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.depends_on_task_id
+               WHERE td.task_id = $1 AND t.status != 'done'
+               ORDER BY td.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Whether `task_id` already depends on `other_task_id`, directly or through other tasks
+    pub async fn depends_on_transitively(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        other_task_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let found = sqlx::query_scalar!(
+            r#"WITH RECURSIVE reachable(id) AS (
+                   SELECT depends_on_task_id FROM task_dependencies WHERE task_id = $1
+                   UNION
+                   SELECT td.depends_on_task_id
+                     FROM task_dependencies td
+                     JOIN reachable r ON td.task_id = r.id
+               )
+               SELECT EXISTS(SELECT 1 FROM reachable WHERE id = $2) as "found!: bool""#,
+            task_id,
+            other_task_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(found)
+    }
+
+    /// Add a dependency; the task depended on counts as resolved if it is already done
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO task_dependencies (task_id, depends_on_task_id, resolved_at)
+               SELECT $1, $2, CASE WHEN status = 'done' THEN datetime('now', 'subsec') END
+                 FROM tasks WHERE id = $2
+               ON CONFLICT(task_id, depends_on_task_id) DO NOTHING"#,
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_task_id = $2",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Mark dependencies on `task_id` resolved now that it is done. Returns the dependent tasks
+    /// that had no other unresolved dependency left, i.e. were just unblocked.
+    pub async fn resolve(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+        let dependents = sqlx::query_scalar!(
+            r#"UPDATE task_dependencies
+               SET resolved_at = datetime('now', 'subsec')
+               WHERE depends_on_task_id = $1 AND resolved_at IS NULL
+               RETURNING task_id as "task_id!: Uuid""#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut unblocked = Vec::new();
+        for dependent in dependents {
+            if Self::find_blockers(pool, dependent).await?.is_empty() {
+                unblocked.push(dependent);
+            }
+        }
+        Ok(unblocked)
+    }
+
+    /// Mark dependencies on `task_id` unresolved again after it left done. Returns the
+    /// dependent tasks affected.
+    pub async fn unresolve(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"UPDATE task_dependencies
+               SET resolved_at = NULL
+               WHERE depends_on_task_id = $1 AND resolved_at IS NOT NULL
+               RETURNING task_id as "task_id!: Uuid""#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            project::{CreateProject, Project},
+            task::CreateTask,
+        },
+        test_pool,
+    };
+
+    /// Tasks of one new project, one per title
+    async fn create_tasks<const N: usize>(pool: &SqlitePool, titles: [&str; N]) -> [Uuid; N] {
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            git_repo_path: "/repo".to_string(),
+            use_existing_repo: true,
+            git_url: None,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        };
+        Project::create(pool, &project, project_id).await.unwrap();
+        let mut ids = [Uuid::nil(); N];
+        for (id, title) in ids.iter_mut().zip(titles) {
+            let data = CreateTask::from_title_description(project_id, title.to_string(), None);
+            *id = Task::create(pool, &data, Uuid::new_v4()).await.unwrap().id;
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn finds_dependencies_through_other_tasks() {
+        let pool = test_pool().await;
+        let [a, b, c] = create_tasks(&pool, ["a", "b", "c"]).await;
+        TaskDependency::create(&pool, a, b).await.unwrap();
+        TaskDependency::create(&pool, b, c).await.unwrap();
+
+        assert!(
+            TaskDependency::depends_on_transitively(&pool, a, b)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !TaskDependency::depends_on_transitively(&pool, c, a)
+                .await
+                .unwrap()
+        );
+        // Adding C -> A is refused because A already depends on C, closing a cycle
+        assert!(
+            TaskDependency::depends_on_transitively(&pool, a, c)
+                .await
+                .unwrap()
+        );
+
+        // With A -> B -> C -> A in the table anyway the query still terminates
+        TaskDependency::create(&pool, c, a).await.unwrap();
+        assert!(
+            TaskDependency::depends_on_transitively(&pool, a, a)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn resolving_returns_only_unblocked_dependents() {
+        let pool = test_pool().await;
+        let [design, review, build, docs] =
+            create_tasks(&pool, ["design", "review", "build", "docs"]).await;
+        TaskDependency::create(&pool, build, design).await.unwrap();
+        TaskDependency::create(&pool, build, review).await.unwrap();
+        TaskDependency::create(&pool, docs, design).await.unwrap();
+
+        Task::update_status(&pool, design, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert_eq!(
+            TaskDependency::resolve(&pool, design).await.unwrap(),
+            [docs]
+        );
+        // Already resolved dependencies aren't reported again
+        assert!(
+            TaskDependency::resolve(&pool, design)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let blockers = TaskDependency::find_blockers(&pool, build).await.unwrap();
+        assert_eq!(
+            blockers.iter().map(|task| task.id).collect::<Vec<_>>(),
+            [review]
+        );
+
+        Task::update_status(&pool, review, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert_eq!(
+            TaskDependency::resolve(&pool, review).await.unwrap(),
+            [build]
+        );
+    }
+}
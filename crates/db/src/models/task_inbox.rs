@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Turns payloads posted to `/api/inbox/{secret}` into tasks in a project.
+///
+/// Templates may contain `{field}` placeholders, where `field` is a dot-separated path into
+/// the payload (e.g. `{issue.title}` or `{alerts.0.labels.severity}`). Emails expose
+/// `{subject}`, `{from}`, `{to}`, `{date}` and `{body}`.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskInbox {
+    pub project_id: Uuid,
+    /// Part of the ingestion URL; anyone who knows it can create tasks
+    pub secret: String,
+    pub title_template: String,
+    pub description_template: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertTaskInbox {
+    pub title_template: String,
+    pub description_template: Option<String>,
+    /// Issue a new secret, invalidating the old ingestion URL
+    #[serde(default)]
+    pub rotate_secret: bool,
+}
+
+impl TaskInbox {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskInbox,
+            r#"SELECT project_id as "project_id!: Uuid", secret, title_template, description_template, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_inboxes
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_secret(
+        pool: &SqlitePool,
+        secret: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskInbox,
+            r#"SELECT project_id as "project_id!: Uuid", secret, title_template, description_template, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_inboxes
+               WHERE secret = $1"#,
+            secret
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Create or update the project's inbox. `new_secret` is used for new inboxes and when
+    /// the secret is rotated; otherwise the existing secret is kept.
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertTaskInbox,
+        new_secret: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskInbox,
+            r#"INSERT INTO task_inboxes (project_id, secret, title_template, description_template)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   secret = CASE WHEN $5 THEN excluded.secret ELSE task_inboxes.secret END,
+                   title_template = excluded.title_template,
+                   description_template = excluded.description_template,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", secret, title_template, description_template, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            new_secret,
+            data.title_template,
+            data.description_template,
+            data.rotate_secret
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_inboxes WHERE project_id = $1", project_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
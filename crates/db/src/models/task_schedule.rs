@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Starts a task attempt whenever its cron expression matches, in the project's time zone.
+/// Runs either create a new task from a tag's content or add an attempt to an existing task.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskSchedule {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    /// Five-field cron expression: minute, hour, day of month, month, day of week
+    pub cron_expression: String,
+    /// Tag whose content becomes the description of a new task on every run
+    pub tag_id: Option<Uuid>,
+    /// Existing task that gets a new attempt on every run
+    pub task_id: Option<Uuid>,
+    #[ts(type = "ExecutorProfileId")]
+    pub executor_profile_id: sqlx::types::Json<ExecutorProfileId>,
+    pub base_branch: String,
+    pub enabled: bool,
+    /// Unset while the schedule is disabled
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_task_attempt_id: Option<Uuid>,
+    /// Why the last run did not start
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskSchedule {
+    pub project_id: Uuid,
+    pub name: String,
+    pub cron_expression: String,
+    /// Set exactly one of `tag_id` and `task_id`
+    pub tag_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub executor_profile_id: ExecutorProfileId,
+    pub base_branch: String,
+    #[serde(default = "default_enabled")]
+    #[ts(optional)]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateTaskSchedule {
+    pub name: Option<String>,
+    pub cron_expression: Option<String>,
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    pub base_branch: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+impl TaskSchedule {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, cron_expression, tag_id as "tag_id: Uuid", task_id as "task_id: Uuid", executor_profile_id as "executor_profile_id!: sqlx::types::Json<ExecutorProfileId>", base_branch, enabled as "enabled!: bool", next_run_at as "next_run_at: DateTime<Utc>", last_run_at as "last_run_at: DateTime<Utc>", last_task_attempt_id as "last_task_attempt_id: Uuid", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_schedules
+               WHERE project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, cron_expression, tag_id as "tag_id: Uuid", task_id as "task_id: Uuid", executor_profile_id as "executor_profile_id!: sqlx::types::Json<ExecutorProfileId>", base_branch, enabled as "enabled!: bool", next_run_at as "next_run_at: DateTime<Utc>", last_run_at as "last_run_at: DateTime<Utc>", last_task_attempt_id as "last_task_attempt_id: Uuid", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_schedules
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Enabled schedules whose next run is at or before `now`
+    pub async fn find_due(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, cron_expression, tag_id as "tag_id: Uuid", task_id as "task_id: Uuid", executor_profile_id as "executor_profile_id!: sqlx::types::Json<ExecutorProfileId>", base_branch, enabled as "enabled!: bool", next_run_at as "next_run_at: DateTime<Utc>", last_run_at as "last_run_at: DateTime<Utc>", last_task_attempt_id as "last_task_attempt_id: Uuid", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM task_schedules
+               WHERE enabled = TRUE AND next_run_at IS NOT NULL AND next_run_at <= $1
+               ORDER BY next_run_at ASC"#,
+            now
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateTaskSchedule,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let executor_profile_id = sqlx::types::Json(&data.executor_profile_id);
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"INSERT INTO task_schedules (id, project_id, name, cron_expression, tag_id, task_id, executor_profile_id, base_branch, enabled, next_run_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, cron_expression, tag_id as "tag_id: Uuid", task_id as "task_id: Uuid", executor_profile_id as "executor_profile_id!: sqlx::types::Json<ExecutorProfileId>", base_branch, enabled as "enabled!: bool", next_run_at as "next_run_at: DateTime<Utc>", last_run_at as "last_run_at: DateTime<Utc>", last_task_attempt_id as "last_task_attempt_id: Uuid", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.name,
+            data.cron_expression,
+            data.tag_id,
+            data.task_id,
+            executor_profile_id,
+            data.base_branch,
+            data.enabled,
+            next_run_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        name: &str,
+        cron_expression: &str,
+        executor_profile_id: &ExecutorProfileId,
+        base_branch: &str,
+        enabled: bool,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, sqlx::Error> {
+        let executor_profile_id = sqlx::types::Json(executor_profile_id);
+        sqlx::query_as!(
+            TaskSchedule,
+            r#"UPDATE task_schedules
+               SET name = $2, cron_expression = $3, executor_profile_id = $4, base_branch = $5, enabled = $6, next_run_at = $7, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, cron_expression, tag_id as "tag_id: Uuid", task_id as "task_id: Uuid", executor_profile_id as "executor_profile_id!: sqlx::types::Json<ExecutorProfileId>", base_branch, enabled as "enabled!: bool", next_run_at as "next_run_at: DateTime<Utc>", last_run_at as "last_run_at: DateTime<Utc>", last_task_attempt_id as "last_task_attempt_id: Uuid", last_error, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            cron_expression,
+            executor_profile_id,
+            base_branch,
+            enabled,
+            next_run_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record a run that started `task_attempt_id`, or failed to start with `error`, and move
+    /// the schedule on to its next run
+    pub async fn record_run(
+        pool: &SqlitePool,
+        id: Uuid,
+        next_run_at: Option<DateTime<Utc>>,
+        task_attempt_id: Option<Uuid>,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE task_schedules
+               SET next_run_at = $2,
+                   last_run_at = datetime('now', 'subsec'),
+                   last_task_attempt_id = COALESCE($3, last_task_attempt_id),
+                   last_error = $4,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            next_run_at,
+            task_attempt_id,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM task_schedules WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
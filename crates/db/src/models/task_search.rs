@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// A task matching a full-text search
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskSearchResult {
+    pub task_id: Uuid,
+    pub title: String,
+    pub status: TaskStatus,
+    /// Excerpt of the best-matching field with the matched words in `**bold**`
+    pub snippet: String,
+}
+
+/// Full-text index over task titles, descriptions, comments and tag content
+pub struct TaskSearch;
+
+impl TaskSearch {
+    /// The project's tasks matching the FTS5 query `fts_query`, best match first. Title matches
+    /// rank above description matches, which rank above comment and tag matches.
+    pub async fn search(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        fts_query: &str,
+        limit: i64,
+    ) -> Result<Vec<TaskSearchResult>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskSearchResult,
+            r#"SELECT t.id as "task_id!: Uuid", t.title as "title!", t.status as "status!: TaskStatus", snippet(task_search, -1, '**', '**', '…', 16) as "snippet!: String"
+               FROM task_search s
+               JOIN tasks t ON t.id = s.task_id
+               WHERE task_search MATCH $1
+                 AND t.project_id = $2
+               ORDER BY bm25(task_search, 0.0, 10.0, 4.0, 2.0, 1.0)
+               LIMIT $3"#,
+            fts_query,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{
+            project::{CreateProject, Project},
+            tag::{CreateTag, Tag, UpdateTag},
+            task::{CreateTask, Task},
+            task_comment::{CreateTaskComment, TaskComment},
+        },
+        test_pool,
+    };
+
+    async fn matches(pool: &SqlitePool, project_id: Uuid, fts_query: &str) -> Vec<Uuid> {
+        TaskSearch::search(pool, project_id, fts_query, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|result| result.task_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn triggers_keep_the_index_in_sync() {
+        let pool = test_pool().await;
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            git_repo_path: "/repos/project".to_string(),
+            use_existing_repo: true,
+            git_url: None,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let task = Task::create(
+            &pool,
+            &CreateTask::from_title_description(
+                project_id,
+                "Fix the login form".to_string(),
+                Some("Validation is missing".to_string()),
+            ),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(matches(&pool, project_id, "login").await, vec![task.id]);
+
+        Task::update(
+            &pool,
+            task.id,
+            project_id,
+            "Fix the signup form".to_string(),
+            task.description.clone(),
+            task.status.clone(),
+            None,
+            task.priority,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches(&pool, project_id, "login").await.is_empty());
+        assert_eq!(matches(&pool, project_id, "signup").await, vec![task.id]);
+
+        let comment = TaskComment::create(
+            &pool,
+            task.id,
+            None,
+            &CreateTaskComment {
+                body: "Reproduced on Safari".to_string(),
+                execution_process_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(matches(&pool, project_id, "safari").await, vec![task.id]);
+        TaskComment::delete(&pool, comment.id).await.unwrap();
+        assert!(matches(&pool, project_id, "safari").await.is_empty());
+
+        let tag = Tag::create(
+            &pool,
+            &CreateTag {
+                tag_name: "frontend".to_string(),
+                content: "Use the design system".to_string(),
+                variables: Vec::new(),
+            },
+        )
+        .await
+        .unwrap();
+        Task::add_tag(&pool, task.id, tag.id).await.unwrap();
+        assert_eq!(matches(&pool, project_id, "frontend").await, vec![task.id]);
+        Tag::update(
+            &pool,
+            tag.id,
+            &UpdateTag {
+                tag_name: Some("webapp".to_string()),
+                content: None,
+                variables: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches(&pool, project_id, "frontend").await.is_empty());
+        assert_eq!(matches(&pool, project_id, "webapp").await, vec![task.id]);
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// An interactive shell opened in an attempt's worktree
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TerminalSession {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub shell: String,
+    /// Unset while the shell runs, or if it was killed
+    pub exit_code: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl TerminalSession {
+    pub async fn create(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_attempt_id: Uuid,
+        shell: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TerminalSession,
+            r#"INSERT INTO terminal_sessions (id, task_attempt_id, shell)
+               VALUES ($1, $2, $3)
+               RETURNING id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", shell, exit_code, started_at as "started_at!: DateTime<Utc>", ended_at as "ended_at: DateTime<Utc>""#,
+            id,
+            task_attempt_id,
+            shell
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record that the shell ended, with everything it printed
+    pub async fn finish(
+        pool: &SqlitePool,
+        id: Uuid,
+        exit_code: Option<i64>,
+        transcript: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE terminal_sessions
+               SET exit_code = $2, transcript = $3, ended_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            exit_code,
+            transcript
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The attempt's sessions, newest first
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TerminalSession,
+            r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", shell, exit_code, started_at as "started_at!: DateTime<Utc>", ended_at as "ended_at: DateTime<Utc>"
+               FROM terminal_sessions
+               WHERE task_attempt_id = $1
+               ORDER BY started_at DESC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_transcript(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT transcript FROM terminal_sessions WHERE id = $1 AND task_attempt_id = $2"#,
+            id,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Mark sessions left open by a previous run of the server as ended
+    pub async fn finish_orphaned(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE terminal_sessions SET ended_at = datetime('now', 'subsec') WHERE ended_at IS NULL"#
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
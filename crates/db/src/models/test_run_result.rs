@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::project::TestReportFormat;
+
+/// Pass/fail counts of a test run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct TestCounts {
+    pub passed: i64,
+    pub failed: i64,
+    /// Ignored, skipped and todo tests
+    pub skipped: i64,
+}
+
+/// Counts parsed from the output of a test run process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TestRunResult {
+    pub execution_process_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub format: TestReportFormat,
+    pub passed: i64,
+    pub failed: i64,
+    pub skipped: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TestRunResult {
+    pub fn counts(&self) -> TestCounts {
+        TestCounts {
+            passed: self.passed,
+            failed: self.failed,
+            skipped: self.skipped,
+        }
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        task_attempt_id: Uuid,
+        format: TestReportFormat,
+        counts: &TestCounts,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TestRunResult,
+            r#"INSERT INTO test_run_results (execution_process_id, task_attempt_id, format, passed, failed, skipped)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT (execution_process_id) DO UPDATE
+               SET format = EXCLUDED.format,
+                   passed = EXCLUDED.passed,
+                   failed = EXCLUDED.failed,
+                   skipped = EXCLUDED.skipped
+               RETURNING execution_process_id as "execution_process_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", format as "format!: TestReportFormat", passed, failed, skipped, created_at as "created_at!: DateTime<Utc>""#,
+            execution_process_id,
+            task_attempt_id,
+            format,
+            counts.passed,
+            counts.failed,
+            counts.skipped
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The attempt's test runs, newest first
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TestRunResult,
+            r#"SELECT execution_process_id as "execution_process_id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", format as "format!: TestReportFormat", passed, failed, skipped, created_at as "created_at!: DateTime<Utc>"
+               FROM test_run_results
+               WHERE task_attempt_id = $1
+               ORDER BY created_at DESC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(
+    Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, TS,
+)]
+#[sqlx(type_name = "vulnerability_severity", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum VulnerabilitySeverity {
+    #[default]
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// A known vulnerability in a dependency that a task attempt added or upgraded
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct VulnerabilityFinding {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    /// OSV ecosystem name, e.g. `npm`, `crates.io`, `PyPI`, `Go`
+    pub ecosystem: String,
+    pub package_name: String,
+    pub package_version: String,
+    /// Manifest or lockfile the package was found in, relative to the repo root
+    pub manifest_path: String,
+    /// Advisory id, e.g. `GHSA-xxxx-xxxx-xxxx` or `RUSTSEC-2024-0001`
+    pub vulnerability_id: String,
+    pub summary: Option<String>,
+    pub severity: VulnerabilitySeverity,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateVulnerabilityFinding {
+    pub ecosystem: String,
+    pub package_name: String,
+    pub package_version: String,
+    pub manifest_path: String,
+    pub vulnerability_id: String,
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub severity: VulnerabilitySeverity,
+}
+
+impl VulnerabilityFinding {
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            VulnerabilityFinding,
+            r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", ecosystem, package_name, package_version, manifest_path, vulnerability_id, summary, severity as "severity!: VulnerabilitySeverity", created_at as "created_at!: DateTime<Utc>"
+               FROM vulnerability_findings
+               WHERE task_attempt_id = $1
+               ORDER BY package_name ASC, vulnerability_id ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Replace the attempt's findings with the results of the latest scan
+    pub async fn replace_for_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        findings: &[CreateVulnerabilityFinding],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM vulnerability_findings WHERE task_attempt_id = $1",
+            task_attempt_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for finding in findings {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO vulnerability_findings (id, task_attempt_id, ecosystem, package_name, package_version, manifest_path, vulnerability_id, summary, severity)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+                id,
+                task_attempt_id,
+                finding.ecosystem,
+                finding.package_name,
+                finding.package_version,
+                finding.manifest_path,
+                finding.vulnerability_id,
+                finding.summary,
+                finding.severity
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
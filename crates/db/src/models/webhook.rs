@@ -0,0 +1,374 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// What happened on the board that a webhook is called for
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "webhook_event", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A task moved to another status
+    TaskStatusChanged,
+    /// An execution process of an attempt completed, failed or was killed
+    ExecutionFinished,
+    /// An attempt was merged directly or its pull request was merged
+    MergeCompleted,
+    /// A coding agent is waiting for the user to approve a tool call
+    ApprovalRequested,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct Webhook {
+    pub id: Uuid,
+    /// Unset for webhooks that fire for every project
+    pub project_id: Option<Uuid>,
+    pub url: String,
+    /// Encrypted
+    #[serde(skip)]
+    #[ts(skip)]
+    pub secret: String,
+    /// Events the webhook is called for; empty for all of them
+    #[ts(type = "Array<WebhookEvent>")]
+    pub events: sqlx::types::Json<Vec<WebhookEvent>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateWebhook {
+    /// Leave unset for a webhook that fires for every project
+    pub project_id: Option<Uuid>,
+    pub url: String,
+    /// Signs the payloads; never returned by the API
+    pub secret: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_enabled")]
+    #[ts(optional)]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateWebhook {
+    pub url: Option<String>,
+    /// Leave unset to keep the existing secret
+    pub secret: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Not accepted yet; attempted again at `next_attempt_at`
+    Pending,
+    Delivered,
+    /// Gave up after the last retry
+    Failed,
+}
+
+/// One event sent to a webhook, with the outcome of its latest attempt
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: WebhookEvent,
+    /// The occurrence of the event, such as `merge:<id>`
+    pub event_key: String,
+    /// JSON body sent to the webhook
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i64,
+    /// HTTP status of the last attempt, unset if it got no response
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether the webhook is called for `event`
+    pub fn fires_on(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", url, secret, events as "events!: sqlx::types::Json<Vec<WebhookEvent>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Webhooks of the project itself, without the global ones
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", url, secret, events as "events!: sqlx::types::Json<Vec<WebhookEvent>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Enabled webhooks that fire for the project: its own and the global ones
+    pub async fn find_enabled_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", url, secret, events as "events!: sqlx::types::Json<Vec<WebhookEvent>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE (project_id = $1 OR project_id IS NULL) AND enabled = TRUE
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Webhook,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", url, secret, events as "events!: sqlx::types::Json<Vec<WebhookEvent>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhooks
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// `secret` is stored as given, so it should already be encrypted
+    pub async fn create(
+        pool: &SqlitePool,
+        id: Uuid,
+        project_id: Option<Uuid>,
+        url: &str,
+        secret: &str,
+        events: &[WebhookEvent],
+        enabled: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let events = sqlx::types::Json(events);
+        sqlx::query_as!(
+            Webhook,
+            r#"INSERT INTO webhooks (id, project_id, url, secret, events, enabled)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", project_id as "project_id: Uuid", url, secret, events as "events!: sqlx::types::Json<Vec<WebhookEvent>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            url,
+            secret,
+            events,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        url: &str,
+        secret: &str,
+        events: &[WebhookEvent],
+        enabled: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let events = sqlx::types::Json(events);
+        sqlx::query_as!(
+            Webhook,
+            r#"UPDATE webhooks
+               SET url = $2, secret = $3, events = $4, enabled = $5, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id: Uuid", url, secret, events as "events!: sqlx::types::Json<Vec<WebhookEvent>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            url,
+            secret,
+            events,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM webhooks WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl WebhookDelivery {
+    /// Queue `event_key` for the webhook, to be attempted right away. Returns `None` if it was
+    /// queued before, so every event is sent to a webhook at most once.
+    pub async fn create(
+        pool: &SqlitePool,
+        webhook_id: Uuid,
+        event: WebhookEvent,
+        event_key: &str,
+        payload: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"INSERT INTO webhook_deliveries (id, webhook_id, event, event_key, payload, next_attempt_at)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT (webhook_id, event_key) DO NOTHING
+               RETURNING id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event as "event!: WebhookEvent", event_key, payload, status as "status!: WebhookDeliveryStatus", attempts as "attempts!: i64", response_status, last_error, next_attempt_at as "next_attempt_at: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            webhook_id,
+            event,
+            event_key,
+            payload,
+            now
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event as "event!: WebhookEvent", event_key, payload, status as "status!: WebhookDeliveryStatus", attempts as "attempts!: i64", response_status, last_error, next_attempt_at as "next_attempt_at: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Pending deliveries whose next attempt is due at `now`, oldest first
+    pub async fn find_due(
+        pool: &SqlitePool,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event as "event!: WebhookEvent", event_key, payload, status as "status!: WebhookDeliveryStatus", attempts as "attempts!: i64", response_status, last_error, next_attempt_at as "next_attempt_at: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE status = 'pending' AND next_attempt_at <= $1
+               ORDER BY next_attempt_at ASC
+               LIMIT $2"#,
+            now,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The webhook's most recent deliveries, newest first
+    pub async fn find_by_webhook_id(
+        pool: &SqlitePool,
+        webhook_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event as "event!: WebhookEvent", event_key, payload, status as "status!: WebhookDeliveryStatus", attempts as "attempts!: i64", response_status, last_error, next_attempt_at as "next_attempt_at: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE webhook_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+            webhook_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The most recent deliveries of all webhooks, newest first, optionally only those in
+    /// `status`
+    pub async fn find_recent(
+        pool: &SqlitePool,
+        status: Option<WebhookDeliveryStatus>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event as "event!: WebhookEvent", event_key, payload, status as "status!: WebhookDeliveryStatus", attempts as "attempts!: i64", response_status, last_error, next_attempt_at as "next_attempt_at: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE $1 IS NULL OR status = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+            status,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record the outcome of an attempt. `next_attempt_at` is only kept while the delivery is
+    /// pending.
+    pub async fn record_attempt(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: WebhookDeliveryStatus,
+        response_status: Option<i64>,
+        last_error: Option<&str>,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE webhook_deliveries
+               SET status = $2,
+                   attempts = attempts + 1,
+                   response_status = $3,
+                   last_error = $4,
+                   next_attempt_at = CASE WHEN $2 = 'pending' THEN $5 ELSE NULL END,
+                   delivered_at = CASE WHEN $2 = 'delivered' THEN datetime('now', 'subsec') ELSE delivered_at END,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            status,
+            response_status,
+            last_error,
+            next_attempt_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queue the delivery again with a fresh set of retries, to be attempted at `now`
+    pub async fn redeliver(
+        pool: &SqlitePool,
+        id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"UPDATE webhook_deliveries
+               SET status = 'pending', attempts = 0, next_attempt_at = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", webhook_id as "webhook_id!: Uuid", event as "event!: WebhookEvent", event_key, payload, status as "status!: WebhookDeliveryStatus", attempts as "attempts!: i64", response_status, last_error, next_attempt_at as "next_attempt_at: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            now
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// What happens when a task would take a status past its limit
+#[derive(Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "wip_limit_enforcement", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WipLimitEnforcement {
+    /// Allow it, but tell the user
+    #[default]
+    Warn,
+    /// Refuse to move the task or start its attempt
+    Refuse,
+}
+
+/// Most tasks of a project that may have a status at once
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WipLimit {
+    pub project_id: Uuid,
+    pub status: TaskStatus,
+    pub max_tasks: i64,
+    pub enforcement: WipLimitEnforcement,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertWipLimit {
+    pub status: TaskStatus,
+    pub max_tasks: i64,
+    #[serde(default)]
+    #[ts(optional)]
+    pub enforcement: Option<WipLimitEnforcement>,
+}
+
+impl WipLimit {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WipLimit,
+            r#"SELECT project_id as "project_id!: Uuid", status as "status!: TaskStatus", max_tasks as "max_tasks!: i64", enforcement as "enforcement!: WipLimitEnforcement", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_wip_limits
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        status: TaskStatus,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WipLimit,
+            r#"SELECT project_id as "project_id!: Uuid", status as "status!: TaskStatus", max_tasks as "max_tasks!: i64", enforcement as "enforcement!: WipLimitEnforcement", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_wip_limits
+               WHERE project_id = $1 AND status = $2"#,
+            project_id,
+            status
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Set the limit for `data.status`, replacing an existing one
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertWipLimit,
+    ) -> Result<Self, sqlx::Error> {
+        let enforcement = data.enforcement.unwrap_or_default();
+        sqlx::query_as!(
+            WipLimit,
+            r#"INSERT INTO project_wip_limits (project_id, status, max_tasks, enforcement)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(project_id, status) DO UPDATE SET
+                   max_tasks = excluded.max_tasks,
+                   enforcement = excluded.enforcement,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", status as "status!: TaskStatus", max_tasks as "max_tasks!: i64", enforcement as "enforcement!: WipLimitEnforcement", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.status,
+            data.max_tasks,
+            enforcement
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        status: TaskStatus,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_wip_limits WHERE project_id = $1 AND status = $2",
+            project_id,
+            status
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Number of the project's tasks with each status that has any
+    pub async fn count_tasks_by_status(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<(TaskStatus, i64)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT status as "status!: TaskStatus", COUNT(*) as "count!: i64"
+               FROM tasks
+               WHERE project_id = $1
+               GROUP BY status"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|record| (record.status, record.count))
+            .collect())
+    }
+}
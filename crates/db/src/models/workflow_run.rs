@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Status reported by GitHub once a run completes
+pub const WORKFLOW_RUN_COMPLETED: &str = "completed";
+
+/// A GitHub Actions workflow_dispatch triggered for a task attempt's branch
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkflowRun {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub workflow_file: String,
+    pub git_ref: String,
+    pub run_id: Option<i64>,
+    pub run_url: Option<String>,
+    /// `requested` until the run is found on GitHub, then GitHub's run status
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WorkflowRun {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        repo_owner: &str,
+        repo_name: &str,
+        workflow_file: &str,
+        git_ref: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkflowRun,
+            r#"INSERT INTO workflow_runs (id, task_attempt_id, repo_owner, repo_name, workflow_file, git_ref)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", repo_owner, repo_name, workflow_file, git_ref, run_id, run_url, status, conclusion, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            task_attempt_id,
+            repo_owner,
+            repo_name,
+            workflow_file,
+            git_ref
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkflowRun,
+            r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", repo_owner, repo_name, workflow_file, git_ref, run_id, run_url, status, conclusion, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM workflow_runs
+               WHERE task_attempt_id = $1
+               ORDER BY created_at DESC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Runs that have not reported completion yet
+    pub async fn find_active(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkflowRun,
+            r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", repo_owner, repo_name, workflow_file, git_ref, run_id, run_url, status, conclusion, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM workflow_runs
+               WHERE status != $1
+               ORDER BY created_at ASC"#,
+            WORKFLOW_RUN_COMPLETED
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Ids of the GitHub runs already matched to a dispatch of the workflow in the repository
+    pub async fn find_matched_run_ids(
+        pool: &SqlitePool,
+        repo_owner: &str,
+        repo_name: &str,
+        workflow_file: &str,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT run_id as "run_id!: i64"
+               FROM workflow_runs
+               WHERE repo_owner = $1 AND repo_name = $2 AND workflow_file = $3
+                 AND run_id IS NOT NULL"#,
+            repo_owner,
+            repo_name,
+            workflow_file
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update_run(
+        pool: &SqlitePool,
+        id: Uuid,
+        run_id: i64,
+        run_url: &str,
+        status: &str,
+        conclusion: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE workflow_runs
+               SET run_id = $1, run_url = $2, status = $3, conclusion = $4, updated_at = datetime('now', 'subsec')
+               WHERE id = $5"#,
+            run_id,
+            run_url,
+            status,
+            conclusion,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Close out a dispatch whose run never showed up on GitHub
+    pub async fn mark_not_found(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE workflow_runs
+               SET status = $1, conclusion = 'not_found', updated_at = datetime('now', 'subsec')
+               WHERE id = $2"#,
+            WORKFLOW_RUN_COMPLETED,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
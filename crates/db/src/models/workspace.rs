@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Sqlite, SqliteConnection, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A named group of projects whose tasks are shown together on one board
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Workspace {
+    pub id: Uuid,
+    pub name: String,
+    /// Projects of the workspace, in board order
+    pub project_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateWorkspace {
+    pub name: String,
+    #[serde(default)]
+    pub project_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateWorkspace {
+    pub name: Option<String>,
+    /// Replaces the workspace's projects, in this order
+    pub project_ids: Option<Vec<Uuid>>,
+}
+
+struct WorkspaceRow {
+    id: Uuid,
+    name: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl WorkspaceRow {
+    fn with_projects(self, project_ids: Vec<Uuid>) -> Workspace {
+        Workspace {
+            id: self.id,
+            name: self.name,
+            project_ids,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl Workspace {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            WorkspaceRow,
+            r#"SELECT id as "id!: Uuid", name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspaces
+               ORDER BY name ASC"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let members = sqlx::query!(
+            r#"SELECT workspace_id as "workspace_id!: Uuid", project_id as "project_id!: Uuid"
+               FROM workspace_projects
+               ORDER BY position ASC"#
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut project_ids: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for member in members {
+            project_ids
+                .entry(member.workspace_id)
+                .or_default()
+                .push(member.project_id);
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let ids = project_ids.remove(&row.id).unwrap_or_default();
+                row.with_projects(ids)
+            })
+            .collect())
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let Some(row) = sqlx::query_as!(
+            WorkspaceRow,
+            r#"SELECT id as "id!: Uuid", name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM workspaces
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let project_ids = Self::find_project_ids(pool, id).await?;
+        Ok(Some(row.with_projects(project_ids)))
+    }
+
+    async fn find_project_ids<'e, E>(executor: E, id: Uuid) -> Result<Vec<Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let records = sqlx::query!(
+            r#"SELECT project_id as "project_id!: Uuid"
+               FROM workspace_projects
+               WHERE workspace_id = $1
+               ORDER BY position ASC"#,
+            id
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(records.into_iter().map(|r| r.project_id).collect())
+    }
+
+    /// Replace the workspace's projects. Ids that aren't projects are skipped.
+    async fn set_projects(
+        conn: &mut SqliteConnection,
+        id: Uuid,
+        project_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM workspace_projects WHERE workspace_id = $1", id)
+            .execute(&mut *conn)
+            .await?;
+        for (position, project_id) in project_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                r#"INSERT OR IGNORE INTO workspace_projects (workspace_id, project_id, position)
+                   SELECT $1, id, $3 FROM projects WHERE id = $2"#,
+                id,
+                project_id,
+                position
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateWorkspace) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let mut tx = pool.begin().await?;
+        let row = sqlx::query_as!(
+            WorkspaceRow,
+            r#"INSERT INTO workspaces (id, name)
+               VALUES ($1, $2)
+               RETURNING id as "id!: Uuid", name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::set_projects(&mut *tx, id, &data.project_ids).await?;
+        let project_ids = Self::find_project_ids(&mut *tx, id).await?;
+        tx.commit().await?;
+        Ok(row.with_projects(project_ids))
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateWorkspace,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+
+        let mut tx = pool.begin().await?;
+        let row = sqlx::query_as!(
+            WorkspaceRow,
+            r#"UPDATE workspaces
+               SET name = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", name, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if let Some(project_ids) = &data.project_ids {
+            Self::set_projects(&mut *tx, id, project_ids).await?;
+        }
+        let project_ids = Self::find_project_ids(&mut *tx, id).await?;
+        tx.commit().await?;
+        Ok(row.with_projects(project_ids))
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM workspaces WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
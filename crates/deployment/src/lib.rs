@@ -6,13 +6,15 @@ use axum::response::sse::Event;
 use db::{
     DBService,
     models::{
+        dev_server::DevServer,
         execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
         project::{CreateProject, Project},
         task::{Task, TaskStatus},
         task_attempt::{TaskAttempt, TaskAttemptError},
+        terminal_session::TerminalSession,
     },
 };
-use executors::executors::ExecutorError;
+use executors::{actions::ExecutorActionType, executors::ExecutorError};
 use futures::{StreamExt, TryStreamExt};
 use git2::Error as Git2Error;
 use serde_json::Value;
@@ -20,22 +22,37 @@ use services::services::{
     analytics::{AnalyticsContext, AnalyticsService},
     approvals::Approvals,
     auth::{AuthError, AuthService},
-    config::{Config, ConfigError},
+    base_branch_sync::BaseBranchSyncService,
+    config::{Config, ConfigError, layers::ConfigLayers},
     container::{ContainerError, ContainerService},
     drafts::DraftsService,
-    events::{EventError, EventService},
+    event_journal::EventJournalService,
+    events::{EventError, EventService, EventStreamFilter},
     file_search_cache::FileSearchCache,
     filesystem::{FilesystemError, FilesystemService},
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
+    github_issue_sync::GitHubIssueSyncService,
     image::{ImageError, ImageService},
+    issue_tracker::IssueTrackerSyncService,
+    log_retention::LogRetentionService,
+    log_search,
+    merge_queue::MergeQueueService,
     pr_monitor::PrMonitorService,
+    rate_limit::RateLimitService,
+    telemetry::TelemetryService,
+    webhook::WebhookDeliveryService,
+    workflow_monitor::WorkflowMonitorService,
+    worktree_gc::WorktreeGcService,
     worktree_manager::WorktreeError,
 };
 use sqlx::{Error as SqlxError, types::Uuid};
 use thiserror::Error;
 use tokio::sync::RwLock;
-use utils::{msg_store::MsgStore, sentry as sentry_utils};
+use utils::{
+    msg_store::{MsgCursor, MsgStore},
+    sentry as sentry_utils,
+};
 
 #[derive(Debug, Error)]
 pub enum DeploymentError {
@@ -81,6 +98,9 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn config(&self) -> &Arc<RwLock<Config>>;
 
+    /// The layers the config in use was merged from
+    fn config_layers(&self) -> &Arc<RwLock<ConfigLayers>>;
+
     fn db(&self) -> &DBService;
 
     fn analytics(&self) -> &Option<AnalyticsService>;
@@ -105,6 +125,10 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn drafts(&self) -> &DraftsService;
 
+    fn telemetry(&self) -> &TelemetryService;
+
+    fn rate_limits(&self) -> &RateLimitService;
+
     async fn update_sentry_scope(&self) -> Result<(), DeploymentError> {
         let user_id = self.user_id();
         let config = self.config().read().await;
@@ -128,7 +152,86 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         PrMonitorService::spawn(db, config, analytics).await
     }
 
+    async fn spawn_workflow_monitor_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        let config = self.config().clone();
+        WorkflowMonitorService::spawn(db, config).await
+    }
+
+    async fn spawn_github_issue_sync_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        let config = self.config().clone();
+        GitHubIssueSyncService::spawn(db, config).await
+    }
+
+    async fn spawn_issue_tracker_sync_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        IssueTrackerSyncService::spawn(db).await
+    }
+
+    async fn spawn_webhook_delivery_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        WebhookDeliveryService::spawn(db).await
+    }
+
+    async fn spawn_base_branch_sync_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        let config = self.config().clone();
+        let git = self.git().clone();
+        BaseBranchSyncService::spawn(db, config, git).await
+    }
+
+    async fn spawn_merge_queue_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        let config = self.config().clone();
+        let git = self.git().clone();
+        MergeQueueService::spawn(db, config, git).await
+    }
+
+    fn worktree_gc(&self) -> WorktreeGcService {
+        let analytics = self
+            .analytics()
+            .as_ref()
+            .map(|analytics_service| AnalyticsContext {
+                user_id: self.user_id().to_string(),
+                analytics_service: analytics_service.clone(),
+            });
+        WorktreeGcService::new(
+            self.db().clone(),
+            self.config().clone(),
+            self.container().terminals().clone(),
+            analytics,
+        )
+    }
+
+    async fn spawn_worktree_gc_service(&self) -> tokio::task::JoinHandle<()> {
+        self.worktree_gc().spawn().await
+    }
+
+    fn log_retention(&self) -> LogRetentionService {
+        LogRetentionService::new(self.db().clone(), self.config().clone())
+    }
+
+    async fn spawn_log_retention_service(&self) -> tokio::task::JoinHandle<()> {
+        self.log_retention().spawn().await
+    }
+
+    fn event_journal(&self) -> EventJournalService {
+        EventJournalService::new(
+            self.db().clone(),
+            self.config().clone(),
+            self.events().msg_store().clone(),
+        )
+    }
+
+    async fn spawn_event_journal_service(&self) -> tokio::task::JoinHandle<()> {
+        self.event_journal().spawn().await
+    }
+
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
+        // Local telemetry only keeps a per-event counter, never the properties
+        self.telemetry().record_feature(event_name).await;
+
         let analytics_enabled = self.config().read().await.analytics_enabled;
         // Only skip tracking if user explicitly opted out (Some(false))
         // Send for None (undecided) and Some(true) (opted in)
@@ -139,7 +242,22 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         }
     }
 
-    /// Cleanup executions marked as running in the db, call at startup
+    /// Reconcile executions with what happened to them while the server was down, call at
+    /// startup: orphaned ones are cleaned up and, if configured, interrupted dev servers are
+    /// started again
+    async fn recover_executions(&self) -> Result<(), DeploymentError> {
+        // Found before the cleanup marks the orphaned ones as failed
+        let interrupted = DevServer::find_interrupted(&self.db().pool).await?;
+        self.cleanup_orphan_executions().await?;
+        if self.config().read().await.dev_server.resume_after_restart {
+            for dev_server in interrupted {
+                self.resume_dev_server(&dev_server).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Cleanup executions marked as running in the db, killing what is left of their processes
     async fn cleanup_orphan_executions(&self) -> Result<(), DeploymentError> {
         let running_processes = ExecutionProcess::find_running(&self.db().pool).await?;
         for process in running_processes {
@@ -148,6 +266,16 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 process.id,
                 process.task_attempt_id
             );
+            // The server died without stopping it, so it may well still be running
+            if let Ok(Some(pid)) = ExecutionProcess::find_pid(&self.db().pool, process.id).await
+                && let Err(e) = self.container().kill_orphaned_process(&process, pid).await
+            {
+                tracing::error!(
+                    "Failed to kill orphaned execution process {}: {}",
+                    process.id,
+                    e
+                );
+            }
             // Update the execution process status first
             if let Err(e) = ExecutionProcess::update_completion(
                 &self.db().pool,
@@ -187,6 +315,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 ExecutionProcessRunReason::CodingAgent
                     | ExecutionProcessRunReason::SetupScript
                     | ExecutionProcessRunReason::CleanupScript
+                    | ExecutionProcessRunReason::TestScript
             ) && let Ok(Some(task_attempt)) =
                 TaskAttempt::find_by_id(&self.db().pool, process.task_attempt_id).await
                 && let Ok(Some(task)) = task_attempt.parent_task(&self.db().pool).await
@@ -199,9 +328,59 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 );
             }
         }
+        // Shells don't survive a restart of the server
+        let orphaned_terminals = TerminalSession::finish_orphaned(&self.db().pool).await?;
+        if orphaned_terminals > 0 {
+            tracing::info!(
+                "Marked {} orphaned terminal sessions as ended",
+                orphaned_terminals
+            );
+        }
         Ok(())
     }
 
+    /// Start an interrupted dev server again with the script it was running, on the same port
+    /// if that is still free
+    async fn resume_dev_server(&self, dev_server: &DevServer) {
+        let pool = &self.db().pool;
+        let Some(process_id) = dev_server.execution_process_id else {
+            return;
+        };
+        let (Ok(Some(process)), Ok(Some(task_attempt))) = (
+            ExecutionProcess::find_by_id(pool, process_id).await,
+            TaskAttempt::find_by_id(pool, dev_server.task_attempt_id).await,
+        ) else {
+            return;
+        };
+        let Ok(ExecutorActionType::ScriptRequest(request)) =
+            process.executor_action().map(|action| action.typ())
+        else {
+            return;
+        };
+        let result = async {
+            // The worktree may have been cleaned up in the meantime
+            self.container()
+                .ensure_container_exists(&task_attempt)
+                .await?;
+            self.container()
+                .start_dev_server(
+                    &task_attempt,
+                    request.script.clone(),
+                    dev_server.restart_count,
+                )
+                .await
+        }
+        .await;
+        match result {
+            Ok(_) => tracing::info!("Resumed dev server for task attempt {}", task_attempt.id),
+            Err(e) => tracing::warn!(
+                "Failed to resume dev server for task attempt {}: {}",
+                task_attempt.id,
+                e
+            ),
+        }
+    }
+
     /// Backfill before_head_commit for legacy execution processes.
     /// Rules:
     /// - If a process has after_head_commit and missing before_head_commit,
@@ -250,6 +429,11 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         Ok(())
     }
 
+    /// Index the logs of processes that finished before log search existed
+    fn spawn_log_search_backfill(&self) {
+        log_search::spawn_backfill(self.db().clone());
+    }
+
     /// Trigger background auto-setup of default projects for new users
     async fn trigger_auto_project_setup(&self) {
         // soft timeout to give the filesystem search a chance to complete
@@ -323,13 +507,21 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         }
     }
 
+    /// `None` when the filter's project, task or attempt doesn't exist. Each event carries its
+    /// cursor as the SSE id; given the last one, the stream resumes after it.
     async fn stream_events(
         &self,
-    ) -> futures::stream::BoxStream<'static, Result<Event, std::io::Error>> {
-        self.events()
-            .msg_store()
-            .history_plus_stream()
-            .map_ok(|m| m.to_sse_event())
-            .boxed()
+        filter: EventStreamFilter,
+        last: Option<MsgCursor>,
+    ) -> Result<
+        Option<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>>,
+        EventError,
+    > {
+        let stream = self.events().stream_events_filtered(filter, last).await?;
+        Ok(stream.map(|stream| {
+            stream
+                .map_ok(|(cursor, m)| m.to_sse_event().id(cursor.to_string()))
+                .boxed()
+        }))
     }
 }
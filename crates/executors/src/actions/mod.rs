@@ -29,12 +29,25 @@ pub enum ExecutorActionType {
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct ExecutorAction {
     pub typ: ExecutorActionType,
+    /// Run after this action succeeds
     pub next_action: Option<Box<ExecutorAction>>,
+    /// Run instead of `next_action` if this action fails
+    #[serde(default)]
+    pub on_failure: Option<Box<ExecutorAction>>,
 }
 
 impl ExecutorAction {
     pub fn new(typ: ExecutorActionType, next_action: Option<Box<ExecutorAction>>) -> Self {
-        Self { typ, next_action }
+        Self {
+            typ,
+            next_action,
+            on_failure: None,
+        }
+    }
+
+    pub fn with_on_failure(mut self, on_failure: Option<ExecutorAction>) -> Self {
+        self.on_failure = on_failure.map(Box::new);
+        self
     }
 
     pub fn typ(&self) -> &ExecutorActionType {
@@ -45,6 +58,10 @@ impl ExecutorAction {
         self.next_action.as_deref()
     }
 
+    pub fn on_failure(&self) -> Option<&ExecutorAction> {
+        self.on_failure.as_deref()
+    }
+
     pub fn base_executor(&self) -> Option<BaseCodingAgent> {
         match self.typ() {
             ExecutorActionType::CodingAgentInitialRequest(request) => Some(request.base_executor()),
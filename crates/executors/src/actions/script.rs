@@ -1,9 +1,8 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 use async_trait::async_trait;
 use command_group::AsyncCommandGroup;
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::shell::get_shell_command;
 
@@ -11,6 +10,7 @@ use crate::{
     actions::Executable,
     approvals::ExecutorApprovalService,
     executors::{ExecutorError, SpawnedChild},
+    sandbox,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -23,6 +23,11 @@ pub enum ScriptContext {
     SetupScript,
     CleanupScript,
     DevServer,
+    PostMergeScript,
+    /// Runs the project's tests after the coding agent; its output is parsed for results
+    TestScript,
+    /// Run when a cleanup script fails, e.g. to collect diagnostics
+    DiagnosticScript,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -30,6 +35,9 @@ pub struct ScriptRequest {
     pub script: String,
     pub language: ScriptRequestLanguage,
     pub context: ScriptContext,
+    /// Extra environment variables for the script
+    #[serde(default)]
+    pub env: HashMap<String, String>,
 }
 
 #[async_trait]
@@ -40,7 +48,7 @@ impl Executable for ScriptRequest {
         _approvals: Arc<dyn ExecutorApprovalService>,
     ) -> Result<SpawnedChild, ExecutorError> {
         let (shell_cmd, shell_arg) = get_shell_command();
-        let mut command = Command::new(shell_cmd);
+        let mut command = sandbox::shell_command(shell_cmd);
         command
             .kill_on_drop(true)
             .stdin(std::process::Stdio::null())
@@ -48,6 +56,7 @@ impl Executable for ScriptRequest {
             .stderr(std::process::Stdio::piped())
             .arg(shell_arg)
             .arg(&self.script)
+            .envs(&self.env)
             .current_dir(current_dir);
 
         let child = command.group_spawn()?;
@@ -0,0 +1,31 @@
+//! Environment variables added to every process started inside [`scope`], such as a
+//! project's configured variables and secrets.
+//!
+//! They are applied when the process is spawned rather than stored in its
+//! [`ExecutorAction`](crate::actions::ExecutorAction), so secret values never reach the
+//! database with the action.
+
+use std::{collections::HashMap, future::Future};
+
+use tokio::process::Command;
+
+tokio::task_local! {
+    static ENV_VARS: HashMap<String, String>;
+}
+
+/// Run `f` so that every command built with [`crate::sandbox::shell_command`] inside it gets
+/// `vars` in its environment
+pub async fn scope<F: Future>(vars: HashMap<String, String>, f: F) -> F::Output {
+    ENV_VARS.scope(vars, f).await
+}
+
+/// The variables of the current [`scope`], to carry them into spawned tasks
+pub(crate) fn current() -> HashMap<String, String> {
+    ENV_VARS.try_with(Clone::clone).unwrap_or_default()
+}
+
+pub(crate) fn apply(command: &mut Command) {
+    let _ = ENV_VARS.try_with(|vars| {
+        command.envs(vars);
+    });
+}
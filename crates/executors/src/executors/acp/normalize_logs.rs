@@ -74,6 +74,9 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                             }
                             if let Some(ref mut s) = streaming.assistant_text {
                                 s.content.push_str(&text.text);
+                                if !is_new {
+                                    msg_store.push_token(s.index, text.text.as_str());
+                                }
                                 let entry = NormalizedEntry {
                                     timestamp: None,
                                     entry_type: NormalizedEntryType::AssistantMessage,
@@ -102,6 +105,9 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                             }
                             if let Some(ref mut s) = streaming.thinking_text {
                                 s.content.push_str(&text.text);
+                                if !is_new {
+                                    msg_store.push_token(s.index, text.text.as_str());
+                                }
                                 let entry = NormalizedEntry {
                                     timestamp: None,
                                     entry_type: NormalizedEntryType::Thinking,
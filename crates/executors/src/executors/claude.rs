@@ -10,12 +10,11 @@ use command_group::AsyncCommandGroup;
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::{
     approvals::ApprovalStatus,
     diff::{concatenate_diff_hunks, create_unified_diff, create_unified_diff_hunk},
-    log_msg::LogMsg,
+    log_msg::{LogMsg, TokenDelta},
     msg_store::MsgStore,
     path::make_path_relative,
     shell::get_shell_command,
@@ -34,6 +33,7 @@ use crate::{
         stderr_processor::normalize_stderr_logs,
         utils::{EntryIndexProvider, patch::ConversationPatch},
     },
+    sandbox,
     stdout_dup::create_stdout_pipe_writer,
 };
 
@@ -206,7 +206,7 @@ impl ClaudeCode {
         let (shell_cmd, shell_arg) = get_shell_command();
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(shell_cmd);
+        let mut command = sandbox::shell_command(shell_cmd);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
@@ -283,6 +283,8 @@ pub struct ClaudeLogProcessor {
     strategy: HistoryStrategy,
     streaming_messages: HashMap<String, StreamingMessageState>,
     streaming_message_id: Option<String>,
+    // Incremental text produced by stream events, drained by the caller after each line
+    token_deltas: Vec<TokenDelta>,
 }
 
 impl ClaudeLogProcessor {
@@ -298,9 +300,14 @@ impl ClaudeLogProcessor {
             strategy,
             streaming_messages: HashMap::new(),
             streaming_message_id: None,
+            token_deltas: Vec::new(),
         }
     }
 
+    fn take_token_deltas(&mut self) -> Vec<TokenDelta> {
+        std::mem::take(&mut self.token_deltas)
+    }
+
     /// Process raw logs and convert them to normalized entries with patches
     pub fn process_logs(
         msg_store: Arc<MsgStore>,
@@ -363,6 +370,9 @@ impl ClaudeLogProcessor {
                             for patch in patches {
                                 msg_store.push_patch(patch);
                             }
+                            for token in processor.take_token_deltas() {
+                                msg_store.push_token(token.entry_index, token.delta);
+                            }
                         }
                         Err(_) => {
                             // Handle non-JSON output as raw system message
@@ -1055,14 +1065,26 @@ impl ClaudeLogProcessor {
                         .streaming_message_id
                         .as_ref()
                         .and_then(|id| self.streaming_messages.get_mut(id))
-                        && let Some(patch) = state.apply_content_block_delta(
+                    {
+                        // The first delta arrives with the entry itself; later ones are also
+                        // forwarded as tokens
+                        let existing_entry = state.content_entry_index(*index);
+                        if let Some(patch) = state.apply_content_block_delta(
                             *index,
                             delta,
                             worktree_path,
                             entry_index_provider,
-                        )
-                    {
-                        patches.push(patch);
+                        ) {
+                            patches.push(patch);
+                            if let Some(entry_index) = existing_entry
+                                && let Some(text) = delta.text()
+                            {
+                                self.token_deltas.push(TokenDelta {
+                                    entry_index,
+                                    delta: text.to_string(),
+                                });
+                            }
+                        }
                     }
                 }
                 ClaudeStreamEvent::ContentBlockStop { .. } => {}
@@ -1542,6 +1564,16 @@ pub enum ClaudeContentBlockDelta {
     Unknown,
 }
 
+impl ClaudeContentBlockDelta {
+    fn text(&self) -> Option<&str> {
+        match self {
+            Self::TextDelta { text } => Some(text),
+            Self::ThinkingDelta { thinking } => Some(thinking),
+            Self::Unknown => None,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
 pub struct ClaudeMessageDelta {
     #[serde(default)]
@@ -1969,6 +2001,7 @@ mod tests {
             cmd: crate::command::CmdOverrides {
                 base_command_override: None,
                 additional_params: None,
+                output_parser: None,
             },
             approvals_service: None,
         };
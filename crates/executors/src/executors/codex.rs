@@ -36,6 +36,7 @@ use crate::{
         AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
         codex::{jsonrpc::ExitSignalSender, normalize_logs::Error},
     },
+    sandbox,
     stdout_dup::create_stdout_pipe_writer,
 };
 
@@ -253,7 +254,7 @@ impl Codex {
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
         let (shell_cmd, shell_arg) = get_shell_command();
 
-        let mut process = Command::new(shell_cmd);
+        let mut process = sandbox::shell_command(shell_cmd);
         process
             .kill_on_drop(true)
             .stdin(std::process::Stdio::piped())
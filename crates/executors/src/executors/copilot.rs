@@ -13,7 +13,6 @@ use serde::{Deserialize, Serialize};
 use tokio::{
     fs,
     io::AsyncWriteExt,
-    process::Command,
     time::{interval, timeout},
 };
 use ts_rs::TS;
@@ -29,6 +28,7 @@ use crate::{
         NormalizedEntry, NormalizedEntryType, plain_text_processor::PlainTextLogProcessor,
         stderr_processor::normalize_stderr_logs, utils::EntryIndexProvider,
     },
+    sandbox,
     stdout_dup::{self, StdoutAppender},
 };
 
@@ -105,7 +105,7 @@ impl StandardCodingAgentExecutor for Copilot {
 
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(shell_cmd);
+        let mut command = sandbox::shell_command(shell_cmd);
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
@@ -144,7 +144,7 @@ impl StandardCodingAgentExecutor for Copilot {
 
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(shell_cmd);
+        let mut command = sandbox::shell_command(shell_cmd);
 
         command
             .kill_on_drop(true)
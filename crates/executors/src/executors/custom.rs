@@ -0,0 +1,380 @@
+//! Coding agents defined without changing the crate. Each JSON file in the custom executors
+//! directory (see [`workspace_utils::assets::custom_executors_path`]) describes one agent: the
+//! commands that start and resume it, its environment, how to find its session id and how to
+//! normalize its output. The file `in-house.json` becomes the `CUSTOM` executor's `IN_HOUSE`
+//! variant:
+//!
+//! ```json
+//! {
+//!   "command": "in-house-agent run --json",
+//!   "follow_up_command": "in-house-agent resume {session_id} --json",
+//!   "env": { "IN_HOUSE_MODE": "auto" },
+//!   "session_id_regex": "^session: (\\S+)$",
+//!   "log_format": "claude_stream_json"
+//! }
+//! ```
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use futures::StreamExt;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use ts_rs::TS;
+use workspace_utils::{
+    msg_store::MsgStore,
+    shell::{get_shell_command, resolve_executable_path},
+};
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder},
+    executors::{
+        AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+        claude::{ClaudeLogProcessor, HistoryStrategy},
+    },
+    logs::{
+        NormalizedEntry, NormalizedEntryType, plain_text_processor::PlainTextLogProcessor,
+        stderr_processor::normalize_stderr_logs, utils::EntryIndexProvider,
+    },
+    profile::{ExecutorConfig, canonical_variant_key},
+    sandbox,
+};
+
+/// Built-in normalizer reading a custom agent's output: plain text shown as the agent's reply,
+/// or JSON lines in the format of Claude Code's `--output-format stream-json`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomLogFormat {
+    #[default]
+    PlainText,
+    ClaudeStreamJson,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct CustomAgent {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[schemars(
+        title = "Command",
+        description = "Command starting a session. `{prompt}` is replaced with the prompt quoted for the shell; without it the prompt is written to stdin."
+    )]
+    pub command: String,
+    #[schemars(
+        title = "Follow-up Command",
+        description = "Command continuing a session, with `{session_id}` and optionally `{prompt}`. Follow-ups aren't supported when unset."
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up_command: Option<String>,
+    #[schemars(
+        title = "Environment",
+        description = "Environment variables set for the agent"
+    )]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[schemars(
+        title = "Session ID Regex",
+        description = "Regex matched against output lines; its `session_id` group, or else its first group, is the session id follow-ups resume"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id_regex: Option<String>,
+    #[serde(default)]
+    pub log_format: CustomLogFormat,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl CustomAgent {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.command.trim().is_empty() {
+            return Err("`command` is empty".to_string());
+        }
+        if let Some(pattern) = &self.session_id_regex {
+            Regex::new(pattern).map_err(|e| format!("invalid `session_id_regex`: {e}"))?;
+        }
+        if let Some(follow_up) = &self.follow_up_command
+            && !follow_up.contains("{session_id}")
+        {
+            return Err("`follow_up_command` doesn't contain `{session_id}`".to_string());
+        }
+        Ok(())
+    }
+
+    /// The shell command for `template`, with the placeholders filled in. Returns whether the
+    /// prompt still has to be written to stdin.
+    fn render_command(
+        &self,
+        template: &str,
+        prompt: &str,
+        session_id: Option<&str>,
+    ) -> Result<(String, bool), ExecutorError> {
+        let quote = |value: &str| {
+            shlex::try_quote(value)
+                .map(|quoted| quoted.into_owned())
+                .map_err(|e| ExecutorError::Io(std::io::Error::other(e)))
+        };
+        // The template is the whole command, so only the additional parameters apply
+        let mut command = CommandBuilder::new(template)
+            .extend_params(self.cmd.additional_params.clone().unwrap_or_default())
+            .build_initial();
+        if let Some(session_id) = session_id {
+            command = command.replace("{session_id}", &quote(session_id)?);
+        }
+        let prompt_in_command = command.contains("{prompt}");
+        if prompt_in_command {
+            command = command.replace("{prompt}", &quote(prompt)?);
+        }
+        Ok((command, !prompt_in_command))
+    }
+
+    async fn spawn_command(
+        &self,
+        current_dir: &Path,
+        template: &str,
+        prompt: &str,
+        session_id: Option<&str>,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let prompt = self.append_prompt.combine_prompt(prompt);
+        let (agent_command, prompt_on_stdin) =
+            self.render_command(template, &prompt, session_id)?;
+
+        let mut command = sandbox::shell_command(shell_cmd);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .arg(shell_arg)
+            .arg(agent_command)
+            .envs(&self.env);
+
+        let mut child = command.group_spawn()?;
+
+        // Close stdin either way, so agents reading a prompt from it see EOF
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            if prompt_on_stdin {
+                stdin.write_all(prompt.as_bytes()).await?;
+            }
+            stdin.shutdown().await?;
+        }
+
+        Ok(child.into())
+    }
+
+    fn spawn_session_id_watcher(msg_store: Arc<MsgStore>, regex: Regex) {
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                if let Some(session_id) = session_id_from_line(&regex, &line) {
+                    msg_store.push_session_id(session_id);
+                    break;
+                }
+            }
+        });
+    }
+
+    fn plain_text_normalizer(index_provider: EntryIndexProvider) -> PlainTextLogProcessor {
+        PlainTextLogProcessor::builder()
+            .normalized_entry_producer(Box::new(|content: String| NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content,
+                metadata: None,
+            }))
+            .transform_lines(Box::new(|lines| {
+                lines.iter_mut().for_each(|line| {
+                    *line = strip_ansi_escapes::strip_str(&line);
+                })
+            }))
+            .index_provider(index_provider)
+            .build()
+    }
+}
+
+fn session_id_from_line(regex: &Regex, line: &str) -> Option<String> {
+    let captures = regex.captures(line.trim_end())?;
+    captures
+        .name("session_id")
+        .or_else(|| captures.get(1))
+        .map(|found| found.as_str().trim().to_string())
+        .filter(|session_id| !session_id.is_empty())
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for CustomAgent {
+    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_command(current_dir, &self.command, prompt, None)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let Some(template) = &self.follow_up_command else {
+            return Err(ExecutorError::FollowUpNotSupported(
+                "custom agent has no `follow_up_command`".to_string(),
+            ));
+        };
+        self.spawn_command(current_dir, template, prompt, Some(session_id))
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+        if let Some(pattern) = &self.session_id_regex {
+            match Regex::new(pattern) {
+                Ok(regex) => Self::spawn_session_id_watcher(msg_store.clone(), regex),
+                Err(e) => tracing::error!("Invalid session id regex of custom agent: {}", e),
+            }
+        }
+
+        match self.log_format {
+            CustomLogFormat::PlainText => {
+                normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+                tokio::spawn(async move {
+                    let mut stdout_lines = msg_store.stdout_lines_stream();
+                    let mut processor = Self::plain_text_normalizer(entry_index_provider);
+                    while let Some(Ok(line)) = stdout_lines.next().await {
+                        for patch in processor.process(line + "\n") {
+                            msg_store.push_patch(patch);
+                        }
+                    }
+                });
+            }
+            CustomLogFormat::ClaudeStreamJson => {
+                ClaudeLogProcessor::process_logs(
+                    msg_store.clone(),
+                    worktree_path,
+                    entry_index_provider.clone(),
+                    HistoryStrategy::Default,
+                );
+                normalize_stderr_logs(msg_store, entry_index_provider);
+            }
+        }
+    }
+
+    fn default_mcp_config_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    async fn check_availability(&self) -> bool {
+        self.command
+            .split_whitespace()
+            .next()
+            .and_then(resolve_executable_path)
+            .is_some()
+    }
+}
+
+/// The custom agents defined in `dir`, by variant name. The agent in `default.json`, or else
+/// the first by name, is also the `DEFAULT` variant. Invalid files are skipped.
+pub fn load_custom_agents(dir: &Path) -> Option<ExecutorConfig> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut configurations = HashMap::new();
+    let mut first = None;
+    for path in paths {
+        let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy()) else {
+            continue;
+        };
+        let agent = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<CustomAgent>(&content).map_err(|e| e.to_string())
+            })
+            .and_then(|agent| agent.validate().map(|_| agent));
+        match agent {
+            Ok(agent) => {
+                let key = canonical_variant_key(&name);
+                tracing::info!("Loaded custom agent {} from {:?}", key, path);
+                first.get_or_insert_with(|| agent.clone());
+                configurations.insert(key, agent.into());
+            }
+            Err(e) => tracing::error!("Skipping custom agent {:?}: {}", path, e),
+        }
+    }
+
+    if !configurations.contains_key("DEFAULT") {
+        configurations.insert("DEFAULT".to_string(), first?.into());
+    }
+    Some(ExecutorConfig { configurations })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executors::CodingAgent;
+
+    fn agent(command: &str) -> CustomAgent {
+        serde_json::from_value(serde_json::json!({ "command": command })).unwrap()
+    }
+
+    #[test]
+    fn fills_in_command_templates() {
+        let agent = agent("agent run --prompt {prompt}");
+        let (command, on_stdin) = agent
+            .render_command("agent run --prompt {prompt}", "fix the bug", None)
+            .unwrap();
+        assert_eq!(command, "agent run --prompt 'fix the bug'");
+        assert!(!on_stdin);
+
+        let (command, on_stdin) = agent
+            .render_command("agent resume {session_id}", "more", Some("abc 1"))
+            .unwrap();
+        assert_eq!(command, "agent resume 'abc 1'");
+        assert!(on_stdin);
+
+        let regex = Regex::new(r"^session: (\S+)$").unwrap();
+        assert_eq!(
+            session_id_from_line(&regex, "session: 42\n").as_deref(),
+            Some("42")
+        );
+        assert_eq!(session_id_from_line(&regex, "hello"), None);
+    }
+
+    #[test]
+    fn loads_agents_from_a_directory() {
+        let dir = std::env::temp_dir().join(format!("vk-custom-agents-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("in-house.json"),
+            r#"{ "command": "in-house run" }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("broken.json"),
+            r#"{ "command": "x", "session_id_regex": "(" }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "not an agent").unwrap();
+
+        let config = load_custom_agents(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut names = config.configuration_names();
+        names.sort();
+        assert_eq!(names, vec!["DEFAULT", "IN_HOUSE"]);
+        assert!(matches!(
+            config.get_default(),
+            Some(CodingAgent::Custom(agent)) if agent.command == "in-house run"
+        ));
+        assert!(load_custom_agents(&dir).is_none());
+    }
+}
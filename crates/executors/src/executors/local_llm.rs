@@ -0,0 +1,442 @@
+//! Runs attempts with a local model, served through an OpenAI-compatible chat API such as
+//! Ollama's, so no coding agent CLI is needed. The model works on the worktree through a small
+//! set of tools (see [`tools`]) until it answers without calling one.
+//!
+//! The loop runs in the server. Its output is written, in the format of Claude Code's
+//! stream-json, into the stdout of an idle child process, which gives the container a process
+//! to supervise like for any other executor. Conversations are kept on disk so follow-ups can
+//! continue them.
+
+pub mod client;
+pub mod tools;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{io::AsyncWriteExt, process::ChildStdin};
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::{msg_store::MsgStore, shell::get_shell_command};
+
+use self::{
+    client::{ChatClient, ChatMessage, Role, ToolCall},
+    tools::{Tool, ToolOutcome},
+};
+use crate::{
+    command::CmdOverrides,
+    env_vars,
+    executors::{
+        AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+        claude::{
+            ClaudeContentItem, ClaudeJson, ClaudeLogProcessor, ClaudeMessage, ClaudeToolData,
+            HistoryStrategy,
+        },
+        codex::client::LogWriter,
+    },
+    logs::{stderr_processor::normalize_stderr_logs, utils::EntryIndexProvider},
+    sandbox::{self, Sandbox},
+    stdout_dup::create_stdout_pipe_writer,
+};
+
+const DEFAULT_MAX_TURNS: u32 = 50;
+
+/// Waits on stdin without printing anything, until the run finishes or the process is stopped
+#[cfg(unix)]
+const IDLE_COMMAND: &str = "cat > /dev/null";
+#[cfg(windows)]
+const IDLE_COMMAND: &str = "more > NUL";
+
+const SYSTEM_PROMPT: &str = "You are a coding agent working in a git repository. Use the tools \
+to inspect and change the repository's files and, when available, to run commands such as \
+builds and tests. Paths are relative to the repository root. When the task is done, reply \
+with a short summary of your changes without calling a tool.";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct LocalLlm {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[schemars(
+        title = "Base URL",
+        description = "Root of the OpenAI-compatible API, e.g. http://localhost:11434/v1 for Ollama"
+    )]
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    #[schemars(
+        title = "Model",
+        description = "Model name as the server knows it, e.g. qwen2.5-coder:14b"
+    )]
+    pub model: String,
+    #[schemars(
+        title = "API Key Variable",
+        description = "Environment variable holding the API key, for servers that require one"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    #[schemars(
+        title = "Max Turns",
+        description = "Requests to the model before the run is stopped (50 by default)"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>,
+    #[schemars(
+        title = "Allow Commands",
+        description = "Let the model run shell commands in the worktree (on by default)"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_commands: Option<bool>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+fn default_base_url() -> String {
+    "http://localhost:11434/v1".to_string()
+}
+
+impl LocalLlm {
+    fn client(&self) -> Result<ChatClient, ExecutorError> {
+        let api_key = self
+            .api_key_env
+            .as_deref()
+            .and_then(|name| std::env::var(name).ok());
+        ChatClient::new(&self.base_url, api_key)
+    }
+
+    async fn spawn_run(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        resume_session: Option<&str>,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let messages = match resume_session {
+            Some(session_id) => load_session(session_id)?,
+            None => vec![ChatMessage::new(Role::System, SYSTEM_PROMPT)],
+        };
+        let allow_commands = self.allow_commands.unwrap_or(true);
+
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let mut command = sandbox::shell_command(shell_cmd);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .arg(shell_arg)
+            .arg(IDLE_COMMAND);
+        let mut child = command.group_spawn()?;
+
+        let idle_stdin = child.inner().stdin.take().ok_or_else(|| {
+            ExecutorError::Io(std::io::Error::other("Local LLM process missing stdin"))
+        })?;
+        let log_writer = LogWriter::new(create_stdout_pipe_writer(&mut child)?);
+        let (exit_signal_tx, exit_signal_rx) = tokio::sync::oneshot::channel();
+
+        let run = Run {
+            client: self.client()?,
+            model: self.model.clone(),
+            tools: tools::definitions(allow_commands),
+            allow_commands,
+            max_turns: self.max_turns.unwrap_or(DEFAULT_MAX_TURNS),
+            worktree_path: current_dir.to_path_buf(),
+            session_id: Uuid::new_v4().to_string(),
+            messages,
+            log_writer,
+            idle_stdin,
+        };
+        let prompt = self.append_prompt.combine_prompt(prompt);
+        // Commands the model runs must get the sandbox and variables of the spawn
+        let sandbox = Sandbox::current();
+        let env = env_vars::current();
+        tokio::spawn(async move {
+            let run = env_vars::scope(env, run.run(prompt));
+            match sandbox {
+                Some(sandbox) => sandbox.scope(run).await,
+                None => run.await,
+            }
+            let _ = exit_signal_tx.send(());
+        });
+
+        Ok(SpawnedChild {
+            child,
+            exit_signal: Some(exit_signal_rx),
+        })
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for LocalLlm {
+    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_run(current_dir, prompt, None).await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_run(current_dir, prompt, Some(session_id)).await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+        ClaudeLogProcessor::process_logs(
+            msg_store.clone(),
+            worktree_path,
+            entry_index_provider.clone(),
+            HistoryStrategy::Default,
+        );
+        normalize_stderr_logs(msg_store, entry_index_provider);
+    }
+
+    fn default_mcp_config_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    async fn check_availability(&self) -> bool {
+        match self.client() {
+            Ok(client) => client.ping().await,
+            Err(_) => false,
+        }
+    }
+}
+
+/// One run of the tool loop
+struct Run {
+    client: ChatClient,
+    model: String,
+    tools: Value,
+    allow_commands: bool,
+    max_turns: u32,
+    worktree_path: PathBuf,
+    /// Every run starts a new session, so follow-ups to earlier turns stay possible
+    session_id: String,
+    messages: Vec<ChatMessage>,
+    log_writer: LogWriter,
+    idle_stdin: ChildStdin,
+}
+
+impl Run {
+    async fn run(mut self, prompt: String) {
+        if let Err(e) = self.converse(prompt).await {
+            if matches!(&e, ExecutorError::Io(io_err) if io_err.kind() == std::io::ErrorKind::BrokenPipe)
+            {
+                // The process was stopped
+                return;
+            }
+            tracing::error!("Local LLM run failed: {}", e);
+            self.log_writer.log_raw(&format!("Error: {e}")).await.ok();
+        }
+    }
+
+    async fn converse(&mut self, prompt: String) -> Result<(), ExecutorError> {
+        self.log(ClaudeJson::System {
+            subtype: Some("init".to_string()),
+            session_id: Some(self.session_id.clone()),
+            cwd: Some(self.worktree_path.to_string_lossy().to_string()),
+            tools: None,
+            model: Some(self.model.clone()),
+            api_key_source: None,
+        })
+        .await?;
+        self.messages.push(ChatMessage::new(Role::User, prompt));
+
+        for turn in 1..=self.max_turns {
+            self.check_running().await?;
+            let reply = self
+                .client
+                .complete(&self.model, &self.messages, &self.tools)
+                .await?;
+            let calls: Vec<_> = reply
+                .tool_calls
+                .iter()
+                .map(|call| {
+                    let tool = Tool::parse(&call.function);
+                    let log_data = tool_log_data(call, &tool);
+                    (call.id.clone(), tool, log_data)
+                })
+                .collect();
+
+            let mut content = Vec::new();
+            if let Some(text) = reply
+                .content
+                .as_deref()
+                .filter(|text| !text.trim().is_empty())
+            {
+                content.push(ClaudeContentItem::Text {
+                    text: text.to_string(),
+                });
+            }
+            content.extend(
+                calls
+                    .iter()
+                    .map(|(id, _, log_data)| ClaudeContentItem::ToolUse {
+                        id: id.clone(),
+                        tool_data: log_data.clone(),
+                    }),
+            );
+            self.log_message("assistant", content).await?;
+            self.messages.push(reply);
+            save_session(&self.session_id, &self.messages)?;
+
+            if calls.is_empty() {
+                return self
+                    .log(ClaudeJson::Result {
+                        subtype: Some("success".to_string()),
+                        is_error: Some(false),
+                        duration_ms: None,
+                        result: None,
+                        error: None,
+                        num_turns: Some(turn),
+                        session_id: Some(self.session_id.clone()),
+                    })
+                    .await;
+            }
+
+            for (id, tool, log_data) in calls {
+                self.check_running().await?;
+                let outcome = match tool {
+                    Ok(tool) => tool.run(&self.worktree_path, self.allow_commands).await,
+                    Err(e) => ToolOutcome::error(e),
+                };
+                // Command results are logged with their exit code, as Amp reports them
+                let logged = match (&log_data, outcome.exit_code) {
+                    (ClaudeToolData::Bash { .. }, Some(exit_code)) => Value::String(
+                        serde_json::json!({ "output": outcome.output, "exitCode": exit_code })
+                            .to_string(),
+                    ),
+                    _ => Value::String(outcome.output.clone()),
+                };
+                self.log_message(
+                    "user",
+                    vec![ClaudeContentItem::ToolResult {
+                        tool_use_id: id.clone(),
+                        content: logged,
+                        is_error: Some(outcome.is_error),
+                    }],
+                )
+                .await?;
+
+                let mut result = outcome.output;
+                if let Some(exit_code) = outcome.exit_code {
+                    result = format!("exit code {exit_code}\n{result}");
+                }
+                self.messages.push(ChatMessage::tool_result(&id, result));
+            }
+            save_session(&self.session_id, &self.messages)?;
+        }
+
+        Err(ExecutorError::Io(std::io::Error::other(format!(
+            "stopped after {} turns without finishing",
+            self.max_turns
+        ))))
+    }
+
+    /// Fails once the idle process was stopped, which stops the run with it
+    async fn check_running(&mut self) -> Result<(), ExecutorError> {
+        self.idle_stdin
+            .write_all(b"\n")
+            .await
+            .map_err(ExecutorError::Io)?;
+        self.idle_stdin.flush().await.map_err(ExecutorError::Io)
+    }
+
+    async fn log(&self, message: ClaudeJson) -> Result<(), ExecutorError> {
+        self.log_writer
+            .log_raw(&serde_json::to_string(&message)?)
+            .await
+    }
+
+    async fn log_message(
+        &self,
+        role: &str,
+        content: Vec<ClaudeContentItem>,
+    ) -> Result<(), ExecutorError> {
+        let message = ClaudeMessage {
+            id: None,
+            message_type: Some("message".to_string()),
+            role: role.to_string(),
+            model: Some(self.model.clone()),
+            content,
+            stop_reason: None,
+        };
+        let session_id = Some(self.session_id.clone());
+        self.log(match role {
+            "assistant" => ClaudeJson::Assistant {
+                message,
+                session_id,
+            },
+            _ => ClaudeJson::User {
+                message,
+                session_id,
+            },
+        })
+        .await
+    }
+}
+
+/// How a tool call is logged. Calls the model got wrong are shown as they were made.
+fn tool_log_data(call: &ToolCall, tool: &Result<Tool, String>) -> ClaudeToolData {
+    match tool {
+        Ok(tool) => tool.log_data(),
+        Err(_) => ClaudeToolData::Unknown {
+            data: [
+                (
+                    "name".to_string(),
+                    Value::String(call.function.name.clone()),
+                ),
+                (
+                    "input".to_string(),
+                    serde_json::from_str(&call.function.arguments).unwrap_or(Value::Null),
+                ),
+            ]
+            .into(),
+        },
+    }
+}
+
+fn sessions_dir() -> Result<PathBuf, ExecutorError> {
+    let mut dir = dirs::home_dir()
+        .ok_or_else(|| {
+            ExecutorError::Io(std::io::Error::other("Could not determine home directory"))
+        })?
+        .join(".vibe-kanban");
+    if cfg!(debug_assertions) {
+        dir = dir.join("dev");
+    }
+    Ok(dir.join("local_llm_sessions"))
+}
+
+fn session_path(session_id: &str) -> Result<PathBuf, ExecutorError> {
+    // Session ids name files, so only accept the ids this executor creates
+    let session_id = Uuid::parse_str(session_id).map_err(|_| {
+        ExecutorError::FollowUpNotSupported(format!("unknown local LLM session {session_id}"))
+    })?;
+    Ok(sessions_dir()?.join(format!("{session_id}.json")))
+}
+
+fn load_session(session_id: &str) -> Result<Vec<ChatMessage>, ExecutorError> {
+    let content = fs::read_to_string(session_path(session_id)?).map_err(|e| {
+        ExecutorError::FollowUpNotSupported(format!(
+            "local LLM session {session_id} can't be read: {e}"
+        ))
+    })?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn save_session(session_id: &str, messages: &[ChatMessage]) -> Result<(), ExecutorError> {
+    let path = session_path(session_id)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(ExecutorError::Io)?;
+    }
+    fs::write(path, serde_json::to_string(messages)?).map_err(ExecutorError::Io)
+}
@@ -0,0 +1,193 @@
+//! Minimal client for the chat completions endpoint of OpenAI-compatible APIs, as served by
+//! Ollama, llama.cpp, vLLM and LM Studio.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::executors::ExecutorError;
+
+/// Local models can take minutes to answer on modest hardware
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatMessage {
+    pub role: Role,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: Some(content.into()),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn tool_result(tool_call_id: &str, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.to_string()),
+            ..Self::new(Role::Tool, content)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments. Some servers send an object instead of a string.
+    #[serde(deserialize_with = "arguments_as_string")]
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+fn arguments_as_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(arguments) => arguments,
+        Value::Null => "{}".to_string(),
+        arguments => arguments.to_string(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+pub struct ChatClient {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl ChatClient {
+    pub fn new(base_url: &str, api_key: Option<String>) -> Result<Self, ExecutorError> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| ExecutorError::Io(std::io::Error::other(e)))?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{path}", self.base_url));
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    /// Whether the server answers, used to report the executor as available
+    pub async fn ping(&self) -> bool {
+        self.request(reqwest::Method::GET, "/models")
+            .timeout(Duration::from_secs(3))
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success())
+    }
+
+    /// The model's next message in the conversation
+    pub async fn complete(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        tools: &Value,
+    ) -> Result<ChatMessage, ExecutorError> {
+        let response = self
+            .request(reqwest::Method::POST, "/chat/completions")
+            .json(&serde_json::json!({
+                "model": model,
+                "messages": messages,
+                "tools": tools,
+                "stream": false,
+            }))
+            .send()
+            .await
+            .map_err(|e| ExecutorError::Io(std::io::Error::other(e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ExecutorError::Io(std::io::Error::other(format!(
+                "{} returned {status}: {body}",
+                self.base_url
+            ))));
+        }
+        let response: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| ExecutorError::Io(std::io::Error::other(e)))?;
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| {
+                ExecutorError::Io(std::io::Error::other("the model returned no choices"))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_object_and_string_arguments() {
+        let response: ChatResponse = serde_json::from_str(
+            r#"{"choices":[{"message":{"role":"assistant","content":null,"tool_calls":[
+                {"id":"a","type":"function","function":{"name":"read_file","arguments":"{\"path\":\"x\"}"}},
+                {"id":"b","function":{"name":"read_file","arguments":{"path":"y"}}}
+            ]}}]}"#,
+        )
+        .unwrap();
+        let message = &response.choices[0].message;
+        assert_eq!(message.role, Role::Assistant);
+        let arguments: Vec<&str> = message
+            .tool_calls
+            .iter()
+            .map(|call| call.function.arguments.as_str())
+            .collect();
+        assert_eq!(arguments, vec![r#"{"path":"x"}"#, r#"{"path":"y"}"#]);
+        assert_eq!(message.tool_calls[1].kind, "function");
+    }
+}
@@ -0,0 +1,350 @@
+//! The tools offered to the model. File paths are resolved against the worktree and may not
+//! leave it; commands run in the worktree through [`sandbox::shell_command`], so they are
+//! sandboxed like any other executor process.
+
+use std::{
+    path::{Component, Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use workspace_utils::{path::is_within_root, shell::get_shell_command};
+
+use super::client::FunctionCall;
+use crate::{executors::claude::ClaudeToolData, sandbox};
+
+/// Output beyond this is cut off before it reaches the model
+const MAX_OUTPUT_BYTES: usize = 30_000;
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tool {
+    ReadFile { path: String },
+    WriteFile { path: String, content: String },
+    RunCommand { command: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolOutcome {
+    pub output: String,
+    pub is_error: bool,
+    /// Exit code of commands
+    pub exit_code: Option<i32>,
+}
+
+impl ToolOutcome {
+    fn ok(output: impl Into<String>) -> Self {
+        Self {
+            output: output.into(),
+            is_error: false,
+            exit_code: None,
+        }
+    }
+
+    pub fn error(output: impl Into<String>) -> Self {
+        Self {
+            output: output.into(),
+            is_error: true,
+            exit_code: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PathArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct WriteArgs {
+    path: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct CommandArgs {
+    command: String,
+}
+
+/// The tool definitions sent with every request
+pub fn definitions(allow_commands: bool) -> Value {
+    let path = json!({
+        "type": "string",
+        "description": "Path relative to the repository root"
+    });
+    let mut tools = vec![
+        function(
+            "read_file",
+            "Read a file of the repository",
+            json!({ "path": path }),
+        ),
+        function(
+            "write_file",
+            "Create or overwrite a file of the repository with the given content",
+            json!({
+                "path": path,
+                "content": {
+                    "type": "string",
+                    "description": "The complete new content of the file"
+                }
+            }),
+        ),
+    ];
+    if allow_commands {
+        tools.push(function(
+            "run_command",
+            "Run a shell command in the repository root and return its output",
+            json!({ "command": { "type": "string" } }),
+        ));
+    }
+    Value::Array(tools)
+}
+
+fn function(name: &str, description: &str, properties: Value) -> Value {
+    let required: Vec<&String> = properties
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(key, _)| key)
+        .collect();
+    json!({
+        "type": "function",
+        "function": {
+            "name": name,
+            "description": description,
+            "parameters": { "type": "object", "properties": properties, "required": required },
+        }
+    })
+}
+
+impl Tool {
+    pub fn parse(call: &FunctionCall) -> Result<Self, String> {
+        let invalid = |e: serde_json::Error| format!("invalid arguments for {}: {e}", call.name);
+        match call.name.as_str() {
+            "read_file" => {
+                let args: PathArgs = serde_json::from_str(&call.arguments).map_err(invalid)?;
+                Ok(Self::ReadFile { path: args.path })
+            }
+            "write_file" => {
+                let args: WriteArgs = serde_json::from_str(&call.arguments).map_err(invalid)?;
+                Ok(Self::WriteFile {
+                    path: args.path,
+                    content: args.content,
+                })
+            }
+            "run_command" => {
+                let args: CommandArgs = serde_json::from_str(&call.arguments).map_err(invalid)?;
+                Ok(Self::RunCommand {
+                    command: args.command,
+                })
+            }
+            name => Err(format!("unknown tool {name}")),
+        }
+    }
+
+    /// The equivalent Claude Code tool, which the logs are normalized as
+    pub fn log_data(&self) -> ClaudeToolData {
+        match self {
+            Self::ReadFile { path } => ClaudeToolData::Read {
+                file_path: path.clone(),
+            },
+            Self::WriteFile { path, content } => ClaudeToolData::Write {
+                file_path: path.clone(),
+                content: content.clone(),
+            },
+            Self::RunCommand { command } => ClaudeToolData::Bash {
+                command: command.clone(),
+                description: None,
+            },
+        }
+    }
+
+    pub async fn run(&self, worktree_path: &Path, allow_commands: bool) -> ToolOutcome {
+        match self {
+            Self::ReadFile { path } => {
+                let path = match resolve(worktree_path, path) {
+                    Ok(path) => path,
+                    Err(e) => return ToolOutcome::error(e),
+                };
+                match tokio::fs::read_to_string(&path).await {
+                    Ok(content) => ToolOutcome::ok(truncate(content)),
+                    Err(e) => ToolOutcome::error(format!("failed to read {}: {e}", path.display())),
+                }
+            }
+            Self::WriteFile { path, content } => {
+                let path = match resolve(worktree_path, path) {
+                    Ok(path) => path,
+                    Err(e) => return ToolOutcome::error(e),
+                };
+                if let Some(parent) = path.parent()
+                    && let Err(e) = tokio::fs::create_dir_all(parent).await
+                {
+                    return ToolOutcome::error(format!(
+                        "failed to create {}: {e}",
+                        parent.display()
+                    ));
+                }
+                match tokio::fs::write(&path, content).await {
+                    Ok(()) => ToolOutcome::ok(format!("wrote {} bytes", content.len())),
+                    Err(e) => {
+                        ToolOutcome::error(format!("failed to write {}: {e}", path.display()))
+                    }
+                }
+            }
+            Self::RunCommand { .. } if !allow_commands => {
+                ToolOutcome::error("running commands is disabled")
+            }
+            Self::RunCommand { command } => run_command(worktree_path, command).await,
+        }
+    }
+}
+
+async fn run_command(worktree_path: &Path, command: &str) -> ToolOutcome {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let mut process = sandbox::shell_command(shell_cmd);
+    process
+        .kill_on_drop(true)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .current_dir(worktree_path)
+        .arg(shell_arg)
+        .arg(command);
+
+    let output = match tokio::time::timeout(COMMAND_TIMEOUT, process.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return ToolOutcome::error(format!("failed to run the command: {e}")),
+        Err(_) => {
+            return ToolOutcome::error(format!(
+                "the command was stopped after {} seconds",
+                COMMAND_TIMEOUT.as_secs()
+            ));
+        }
+    };
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    ToolOutcome {
+        output: truncate(text),
+        is_error: !output.status.success(),
+        exit_code: output.status.code(),
+    }
+}
+
+/// `path` within the worktree. Paths leaving the worktree, through `..`, by being absolute or
+/// through symlinks, are refused.
+fn resolve(worktree_path: &Path, path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    let relative = path.strip_prefix(worktree_path).unwrap_or(path);
+    let mut resolved = worktree_path.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if resolved != worktree_path => {
+                resolved.pop();
+            }
+            _ => return Err(format!("{} is outside the repository", path.display())),
+        }
+    }
+    if resolved == worktree_path {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    match is_within_root(worktree_path, &resolved) {
+        Ok(true) => Ok(resolved),
+        Ok(false) => Err(format!("{} is outside the repository", path.display())),
+        Err(e) => Err(format!("failed to resolve {}: {e}", path.display())),
+    }
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT_BYTES {
+        let mut end = MAX_OUTPUT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n[output truncated]");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_worktree() -> PathBuf {
+        let worktree = std::env::temp_dir().join(format!("vk-local-llm-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&worktree).unwrap();
+        worktree
+    }
+
+    #[test]
+    fn paths_stay_in_the_worktree() {
+        let worktree = temp_worktree();
+        let resolved = (
+            resolve(&worktree, "src/../src/./main.rs"),
+            resolve(&worktree, &format!("{}/README.md", worktree.display())),
+        );
+        let outside: Vec<bool> = ["../other/file", "/etc/passwd", "src/../../x", "."]
+            .iter()
+            .map(|path| resolve(&worktree, path).is_err())
+            .collect();
+        std::fs::remove_dir_all(&worktree).unwrap();
+
+        assert_eq!(resolved.0.unwrap(), worktree.join("src/main.rs"));
+        assert_eq!(resolved.1.unwrap(), worktree.join("README.md"));
+        assert_eq!(outside, vec![true; 4]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinks_out_of_the_worktree_are_refused() {
+        let worktree = temp_worktree();
+        let outside = temp_worktree();
+        std::os::unix::fs::symlink(&outside, worktree.join("link")).unwrap();
+
+        let resolved = resolve(&worktree, "link/secret.txt");
+        let write = Tool::WriteFile {
+            path: "link/secret.txt".to_string(),
+            content: "x".to_string(),
+        }
+        .run(&worktree, false)
+        .await;
+        let written = outside.join("secret.txt").exists();
+        std::fs::remove_dir_all(&worktree).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+
+        assert!(resolved.is_err());
+        assert!(write.is_error);
+        assert!(!written);
+    }
+
+    #[tokio::test]
+    async fn runs_tools_in_the_worktree() {
+        let worktree = temp_worktree();
+
+        let call = |name: &str, arguments: Value| FunctionCall {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        };
+        let write = Tool::parse(&call(
+            "write_file",
+            json!({ "path": "notes/todo.txt", "content": "ship it" }),
+        ))
+        .unwrap();
+        assert!(!write.run(&worktree, false).await.is_error);
+        let read = Tool::parse(&call("read_file", json!({ "path": "notes/todo.txt" }))).unwrap();
+        assert_eq!(read.run(&worktree, false).await.output, "ship it");
+
+        let command = Tool::parse(&call("run_command", json!({ "command": "ls notes" }))).unwrap();
+        assert!(command.run(&worktree, false).await.is_error);
+        let outcome = command.run(&worktree, true).await;
+        std::fs::remove_dir_all(&worktree).unwrap();
+        assert_eq!(outcome.exit_code, Some(0));
+        assert_eq!(outcome.output.trim(), "todo.txt");
+
+        assert!(Tool::parse(&call("delete_everything", json!({}))).is_err());
+    }
+}
@@ -14,10 +14,20 @@ use workspace_utils::msg_store::MsgStore;
 
 use crate::{
     approvals::ExecutorApprovalService,
+    command::CmdOverrides,
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
-        gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        amp::Amp,
+        claude::ClaudeCode,
+        codex::{Codex, ReasoningEffort},
+        copilot::Copilot,
+        cursor::CursorAgent,
+        custom::CustomAgent,
+        gemini::{Gemini, GeminiModel},
+        local_llm::LocalLlm,
+        opencode::Opencode,
+        qwen::QwenCode,
     },
+    logs::output_parser::{self, OutputParser},
     mcp_config::McpConfig,
 };
 
@@ -27,7 +37,9 @@ pub mod claude;
 pub mod codex;
 pub mod copilot;
 pub mod cursor;
+pub mod custom;
 pub mod gemini;
+pub mod local_llm;
 pub mod opencode;
 pub mod qwen;
 
@@ -37,6 +49,16 @@ pub enum BaseAgentCapability {
     SessionFork,
 }
 
+/// Rough relative cost of running a configuration, inferred from its model and settings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CostHint {
+    Low,
+    Standard,
+    High,
+}
+
 #[derive(Debug, Error)]
 pub enum ExecutorError {
     #[error("Follow-up is not supported: {0}")]
@@ -84,6 +106,9 @@ pub enum CodingAgent {
     CursorAgent,
     QwenCode,
     Copilot,
+    /// An agent defined in the custom executors directory
+    Custom(CustomAgent),
+    LocalLlm,
 }
 
 impl CodingAgent {
@@ -136,8 +161,89 @@ impl CodingAgent {
             Self::Codex(_) => vec![BaseAgentCapability::SessionFork],
             Self::Gemini(_) => vec![BaseAgentCapability::SessionFork],
             Self::QwenCode(_) => vec![BaseAgentCapability::SessionFork],
-            Self::Opencode(_) | Self::CursorAgent(_) | Self::Copilot(_) => vec![],
+            Self::Opencode(_)
+            | Self::CursorAgent(_)
+            | Self::Copilot(_)
+            | Self::Custom(_)
+            | Self::LocalLlm(_) => vec![],
+        }
+    }
+
+    /// Model requested by this configuration, if it overrides the agent's default
+    pub fn model(&self) -> Option<String> {
+        match self {
+            Self::ClaudeCode(executor) => executor.model.clone(),
+            Self::Gemini(executor) => match executor.model {
+                GeminiModel::Default => None,
+                GeminiModel::Flash => Some("gemini-2.5-flash".to_string()),
+            },
+            Self::Codex(executor) => executor.model.clone(),
+            Self::Opencode(executor) => executor.model.clone(),
+            Self::CursorAgent(executor) => executor.model.clone(),
+            Self::Copilot(executor) => executor.model.clone(),
+            Self::LocalLlm(executor) => Some(executor.model.clone()),
+            Self::Amp(_) | Self::QwenCode(_) | Self::Custom(_) => None,
+        }
+    }
+
+    pub fn cost_hint(&self) -> CostHint {
+        // Local models cost nothing beyond the machine running them
+        if let Self::LocalLlm(_) = self {
+            return CostHint::Low;
+        }
+        if let Self::Codex(executor) = self
+            && executor.model_reasoning_effort == Some(ReasoningEffort::High)
+        {
+            return CostHint::High;
+        }
+        let Some(model) = self.model().map(|model| model.to_lowercase()) else {
+            return CostHint::Standard;
+        };
+        if ["flash", "haiku", "mini", "nano"]
+            .iter()
+            .any(|name| model.contains(name))
+        {
+            CostHint::Low
+        } else if model.contains("opus") {
+            CostHint::High
+        } else {
+            CostHint::Standard
+        }
+    }
+
+    pub fn cmd_overrides(&self) -> &CmdOverrides {
+        match self {
+            Self::ClaudeCode(executor) => &executor.cmd,
+            Self::Amp(executor) => &executor.cmd,
+            Self::Gemini(executor) => &executor.cmd,
+            Self::Codex(executor) => &executor.cmd,
+            Self::Opencode(executor) => &executor.cmd,
+            Self::CursorAgent(executor) => &executor.cmd,
+            Self::QwenCode(executor) => &executor.cmd,
+            Self::Copilot(executor) => &executor.cmd,
+            Self::Custom(executor) => &executor.cmd,
+            Self::LocalLlm(executor) => &executor.cmd,
+        }
+    }
+
+    /// Normalize logs with the configured output parser, or the executor's built-in normalizer
+    pub fn normalize_output(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        if let Some(config) = &self.cmd_overrides().output_parser {
+            match OutputParser::new(config) {
+                Ok(parser) => {
+                    output_parser::normalize_logs(parser, msg_store, worktree_path);
+                    return;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Invalid output parser for {}, using built-in normalization: {}",
+                        self,
+                        e
+                    );
+                }
+            }
         }
+        self.normalize_logs(msg_store, worktree_path);
     }
 }
 
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use workspace_utils::approvals::ApprovalStatus;
 
+pub mod output_parser;
 pub mod plain_text_processor;
 pub mod stderr_processor;
 pub mod utils;
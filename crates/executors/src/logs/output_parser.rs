@@ -0,0 +1,454 @@
+//! Declarative stdout normalization for executors.
+//!
+//! An [`OutputParserConfig`] maps raw output lines to normalized entries with an ordered list of
+//! rules, so a new CLI agent's log format can be integrated from `profiles.json` without
+//! recompiling. Each rule matches a line either with a regex or with a JSON pointer (RFC 6901) into
+//! the line parsed as JSON, and renders the entry content from a template:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "json_pointer": "/session", "entry_type": "session_id", "content": "{/session}" },
+//!     { "json_pointer": "/type", "equals": "thinking", "entry_type": "thinking", "content": "{/text}" },
+//!     { "regex": "^TOOL (?P<name>\\S+) (?P<args>.*)$", "entry_type": "tool_use", "tool_name": "{name}", "content": "{args}" }
+//!   ],
+//!   "fallback": "assistant_message"
+//! }
+//! ```
+//!
+//! Template placeholders are `{name}` / `{1}` for regex capture groups and `{/pointer}` for JSON
+//! values. Lines that match no rule are coalesced into a single `fallback` entry, or dropped when
+//! no fallback is configured.
+use std::{path::Path, sync::Arc};
+
+use futures::StreamExt;
+use json_patch::Patch;
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use ts_rs::TS;
+use workspace_utils::msg_store::MsgStore;
+
+use super::{
+    ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus,
+    stderr_processor::normalize_stderr_logs,
+    utils::{ConversationPatch, EntryIndexProvider},
+};
+
+lazy_static::lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{([^{}]+)\}").unwrap();
+}
+
+#[derive(Debug, Error)]
+pub enum OutputParserError {
+    #[error("Rule {index} must set exactly one of `regex` or `json_pointer`")]
+    InvalidMatcher { index: usize },
+    #[error("Rule {index} has an invalid regex: {source}")]
+    InvalidRegex {
+        index: usize,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("Rule {index} has an invalid JSON pointer '{pointer}'")]
+    InvalidJsonPointer { index: usize, pointer: String },
+}
+
+/// Kind of normalized entry a parser rule produces
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputParserEntryType {
+    AssistantMessage,
+    Thinking,
+    SystemMessage,
+    ErrorMessage,
+    ToolUse,
+    SessionId,
+    Ignore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct OutputParserRule {
+    /// Regex matched against each output line; named and numbered groups are available to templates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// JSON pointer into the line parsed as JSON; the rule matches when the pointer resolves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_pointer: Option<String>,
+    /// Only match when the value at `json_pointer` equals this string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+    pub entry_type: OutputParserEntryType,
+    /// Template for the entry content; defaults to the whole line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Template for the tool name of `tool_use` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[schemars(
+    title = "Output Parser",
+    description = "Declarative rules mapping raw output lines to conversation entries. Replaces the built-in log normalization when set."
+)]
+pub struct OutputParserConfig {
+    #[serde(default)]
+    pub rules: Vec<OutputParserRule>,
+    /// Entry type for lines that match no rule; unmatched lines are dropped when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<OutputParserEntryType>,
+}
+
+impl OutputParserConfig {
+    pub fn validate(&self) -> Result<(), OutputParserError> {
+        OutputParser::new(self).map(|_| ())
+    }
+}
+
+enum Matcher {
+    Regex(Regex),
+    JsonPointer {
+        pointer: String,
+        equals: Option<String>,
+    },
+}
+
+struct CompiledRule {
+    matcher: Matcher,
+    entry_type: OutputParserEntryType,
+    content: Option<String>,
+    tool_name: Option<String>,
+}
+
+/// Result of parsing a single output line
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum ParsedLine {
+    Entry(NormalizedEntry),
+    SessionId(String),
+    Ignored,
+    Unmatched,
+}
+
+/// Compiled form of an [`OutputParserConfig`]
+pub struct OutputParser {
+    rules: Vec<CompiledRule>,
+    fallback: Option<OutputParserEntryType>,
+}
+
+impl OutputParser {
+    pub fn new(config: &OutputParserConfig) -> Result<Self, OutputParserError> {
+        let rules = config
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let matcher = match (&rule.regex, &rule.json_pointer) {
+                    (Some(pattern), None) => Matcher::Regex(
+                        Regex::new(pattern)
+                            .map_err(|source| OutputParserError::InvalidRegex { index, source })?,
+                    ),
+                    (None, Some(pointer)) => {
+                        if !pointer.is_empty() && !pointer.starts_with('/') {
+                            return Err(OutputParserError::InvalidJsonPointer {
+                                index,
+                                pointer: pointer.clone(),
+                            });
+                        }
+                        Matcher::JsonPointer {
+                            pointer: pointer.clone(),
+                            equals: rule.equals.clone(),
+                        }
+                    }
+                    _ => return Err(OutputParserError::InvalidMatcher { index }),
+                };
+                Ok(CompiledRule {
+                    matcher,
+                    entry_type: rule.entry_type,
+                    content: rule.content.clone(),
+                    tool_name: rule.tool_name.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            rules,
+            fallback: config.fallback,
+        })
+    }
+
+    /// Parse a single line (without trailing newline) using the first matching rule
+    pub fn parse_line(&self, line: &str) -> ParsedLine {
+        let mut json: Option<Option<Value>> = None;
+
+        for rule in &self.rules {
+            let rendered = match &rule.matcher {
+                Matcher::Regex(regex) => regex.captures(line).map(|captures| {
+                    let render = |template: &str| {
+                        render_template(template, |key| capture_value(&captures, key))
+                    };
+                    (
+                        rule.content.as_deref().map_or(line.to_string(), render),
+                        rule.tool_name.as_deref().map(render),
+                    )
+                }),
+                Matcher::JsonPointer { pointer, equals } => {
+                    let value = json
+                        .get_or_insert_with(|| serde_json::from_str::<Value>(line).ok())
+                        .as_ref();
+                    value
+                        .filter(|value| {
+                            value.pointer(pointer).is_some_and(|found| match equals {
+                                Some(expected) => value_to_string(found) == *expected,
+                                None => !found.is_null(),
+                            })
+                        })
+                        .map(|value| {
+                            let render = |template: &str| {
+                                render_template(template, |key| {
+                                    key.starts_with('/')
+                                        .then(|| value.pointer(key).map(value_to_string))
+                                        .flatten()
+                                })
+                            };
+                            (
+                                rule.content.as_deref().map_or(line.to_string(), render),
+                                rule.tool_name.as_deref().map(render),
+                            )
+                        })
+                }
+            };
+
+            if let Some((content, tool_name)) = rendered {
+                return build_entry(rule.entry_type, content, tool_name);
+            }
+        }
+
+        ParsedLine::Unmatched
+    }
+}
+
+fn capture_value(captures: &Captures, key: &str) -> Option<String> {
+    let group = match key.parse::<usize>() {
+        Ok(index) => captures.get(index),
+        Err(_) => captures.name(key),
+    };
+    group.map(|m| m.as_str().to_string())
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replace `{key}` placeholders; unknown keys render as empty strings
+fn render_template(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &Captures| {
+            lookup(&caps[1]).unwrap_or_default()
+        })
+        .into_owned()
+}
+
+fn build_entry(
+    entry_type: OutputParserEntryType,
+    content: String,
+    tool_name: Option<String>,
+) -> ParsedLine {
+    let entry_type = match entry_type {
+        OutputParserEntryType::AssistantMessage => NormalizedEntryType::AssistantMessage,
+        OutputParserEntryType::Thinking => NormalizedEntryType::Thinking,
+        OutputParserEntryType::SystemMessage => NormalizedEntryType::SystemMessage,
+        OutputParserEntryType::ErrorMessage => NormalizedEntryType::ErrorMessage,
+        OutputParserEntryType::ToolUse => {
+            let tool_name = tool_name
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "tool".to_string());
+            NormalizedEntryType::ToolUse {
+                tool_name: tool_name.clone(),
+                action_type: ActionType::Tool {
+                    tool_name,
+                    arguments: None,
+                    result: None,
+                },
+                status: ToolStatus::Success,
+            }
+        }
+        OutputParserEntryType::SessionId => {
+            let session_id = content.trim();
+            return if session_id.is_empty() {
+                ParsedLine::Ignored
+            } else {
+                ParsedLine::SessionId(session_id.to_string())
+            };
+        }
+        OutputParserEntryType::Ignore => return ParsedLine::Ignored,
+    };
+
+    ParsedLine::Entry(NormalizedEntry {
+        timestamp: None,
+        entry_type,
+        content,
+        metadata: None,
+    })
+}
+
+/// Streams stdout lines through an [`OutputParser`], coalescing consecutive fallback lines
+struct OutputParserProcessor {
+    parser: OutputParser,
+    index_provider: EntryIndexProvider,
+    /// Index and accumulated content of the fallback entry currently being extended
+    fallback_entry: Option<(usize, String)>,
+}
+
+impl OutputParserProcessor {
+    fn process_line(&mut self, line: &str, msg_store: &MsgStore) -> Option<Patch> {
+        match self.parser.parse_line(line) {
+            ParsedLine::Entry(entry) => {
+                self.fallback_entry = None;
+                Some(ConversationPatch::add_normalized_entry(
+                    self.index_provider.next(),
+                    entry,
+                ))
+            }
+            ParsedLine::SessionId(session_id) => {
+                msg_store.push_session_id(session_id);
+                None
+            }
+            ParsedLine::Ignored => None,
+            ParsedLine::Unmatched => {
+                let fallback = self.parser.fallback?;
+                if line.trim().is_empty() && self.fallback_entry.is_none() {
+                    return None;
+                }
+                match &mut self.fallback_entry {
+                    Some((index, content)) => {
+                        content.push('\n');
+                        content.push_str(line);
+                        match build_entry(fallback, content.clone(), None) {
+                            ParsedLine::Entry(entry) => {
+                                Some(ConversationPatch::replace(*index, entry))
+                            }
+                            _ => None,
+                        }
+                    }
+                    None => match build_entry(fallback, line.to_string(), None) {
+                        ParsedLine::Entry(entry) => {
+                            let index = self.index_provider.next();
+                            self.fallback_entry = Some((index, line.to_string()));
+                            Some(ConversationPatch::add_normalized_entry(index, entry))
+                        }
+                        ParsedLine::SessionId(session_id) => {
+                            msg_store.push_session_id(session_id);
+                            None
+                        }
+                        _ => None,
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Normalize an executor's logs with a declarative parser instead of its built-in normalizer.
+/// Stderr is still normalized as error messages.
+pub fn normalize_logs(parser: OutputParser, msg_store: Arc<MsgStore>, _worktree_path: &Path) {
+    let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+    normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+    tokio::spawn(async move {
+        let mut stdout_lines = msg_store.stdout_lines_stream();
+        let mut processor = OutputParserProcessor {
+            parser,
+            index_provider: entry_index_provider,
+            fallback_entry: None,
+        };
+
+        while let Some(Ok(line)) = stdout_lines.next().await {
+            let line = strip_ansi_escapes::strip_str(&line);
+            if let Some(patch) = processor.process_line(line.trim_end_matches('\r'), &msg_store) {
+                msg_store.push_patch(patch);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser(config: serde_json::Value) -> OutputParser {
+        OutputParser::new(&serde_json::from_value(config).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn regex_and_json_rules_map_lines_to_entries() {
+        let parser = parser(serde_json::json!({
+            "rules": [
+                { "json_pointer": "/session", "entry_type": "session_id", "content": "{/session}" },
+                { "json_pointer": "/type", "equals": "thinking", "entry_type": "thinking", "content": "{/text}" },
+                { "regex": "^TOOL (?P<name>\\S+) (.*)$", "entry_type": "tool_use", "tool_name": "{name}", "content": "{2}" },
+                { "regex": "^DEBUG", "entry_type": "ignore" }
+            ],
+            "fallback": "assistant_message"
+        }));
+
+        assert!(matches!(
+            parser.parse_line(r#"{"session":"abc-123"}"#),
+            ParsedLine::SessionId(id) if id == "abc-123"
+        ));
+
+        match parser.parse_line(r#"{"type":"thinking","text":"pondering"}"#) {
+            ParsedLine::Entry(entry) => {
+                assert!(matches!(entry.entry_type, NormalizedEntryType::Thinking));
+                assert_eq!(entry.content, "pondering");
+            }
+            other => panic!("expected thinking entry, got {other:?}"),
+        }
+
+        match parser.parse_line("TOOL grep -rn foo src") {
+            ParsedLine::Entry(entry) => {
+                assert!(matches!(
+                    entry.entry_type,
+                    NormalizedEntryType::ToolUse { ref tool_name, .. } if tool_name == "grep"
+                ));
+                assert_eq!(entry.content, "-rn foo src");
+            }
+            other => panic!("expected tool entry, got {other:?}"),
+        }
+
+        assert!(matches!(
+            parser.parse_line("DEBUG noisy"),
+            ParsedLine::Ignored
+        ));
+        assert!(matches!(
+            parser.parse_line(r#"{"type":"message"}"#),
+            ParsedLine::Unmatched
+        ));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let config: OutputParserConfig = serde_json::from_value(serde_json::json!({
+            "rules": [{ "regex": "(unclosed", "entry_type": "assistant_message" }]
+        }))
+        .unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(OutputParserError::InvalidRegex { index: 0, .. })
+        ));
+
+        let config: OutputParserConfig = serde_json::from_value(serde_json::json!({
+            "rules": [{ "entry_type": "assistant_message" }]
+        }))
+        .unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(OutputParserError::InvalidMatcher { index: 0 })
+        ));
+    }
+}
@@ -6,7 +6,9 @@ use serde::{Deserialize, Deserializer, Serialize, de::Error as DeError};
 use thiserror::Error;
 use ts_rs::TS;
 
-use crate::executors::{BaseCodingAgent, CodingAgent, StandardCodingAgentExecutor};
+use crate::executors::{
+    BaseCodingAgent, CodingAgent, CostHint, StandardCodingAgentExecutor, custom::load_custom_agents,
+};
 
 /// Return the canonical form for variant keys.
 /// – "DEFAULT" is kept as-is  
@@ -43,6 +45,16 @@ pub enum ProfileError {
 
     #[error("No available executor profile")]
     NoAvailableExecutorProfile,
+
+    #[error("Executor '{0}' is not configured")]
+    UnknownExecutor(BaseCodingAgent),
+
+    #[error("Unknown variant '{variant}' for executor '{executor}'. Available variants: {}", available.join(", "))]
+    UnknownVariant {
+        executor: BaseCodingAgent,
+        variant: String,
+        available: Vec<String>,
+    },
 }
 
 lazy_static! {
@@ -164,6 +176,71 @@ impl ExecutorConfig {
             .filter(|k| *k != "DEFAULT")
             .collect()
     }
+
+    /// Get all configuration names, "DEFAULT" first and the rest sorted
+    pub fn configuration_names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.configurations.keys().collect();
+        names.sort_by_key(|name| (*name != "DEFAULT", *name));
+        names
+    }
+}
+
+/// A selectable configuration of an executor, as listed by the variants API
+#[derive(Debug, Clone, Serialize, PartialEq, TS)]
+pub struct VariantInfo {
+    /// Value to store as the variant; `DEFAULT` is equivalent to no variant
+    pub name: String,
+    /// Summary of the settings that set this configuration apart
+    pub description: String,
+    pub model: Option<String>,
+    pub cost_hint: CostHint,
+}
+
+impl VariantInfo {
+    fn new(name: &str, config: &CodingAgent) -> Self {
+        Self {
+            name: name.to_string(),
+            description: describe_settings(config),
+            model: config.model(),
+            cost_hint: config.cost_hint(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, TS)]
+pub struct ExecutorVariants {
+    pub executor: BaseCodingAgent,
+    /// `DEFAULT` first, then the other variants by name
+    pub variants: Vec<VariantInfo>,
+}
+
+/// Settings that don't change how the agent behaves, or are already reported separately
+const UNDESCRIBED_SETTINGS: [&str; 4] = [
+    "append_prompt",
+    "model",
+    "additional_params",
+    "output_parser",
+];
+
+fn describe_settings(config: &CodingAgent) -> String {
+    let settings = serde_json::to_value(config)
+        .ok()
+        .and_then(|value| value.as_object()?.values().next()?.as_object().cloned())
+        .unwrap_or_default();
+    let described: Vec<String> = settings
+        .iter()
+        .filter(|(key, value)| !value.is_null() && !UNDESCRIBED_SETTINGS.contains(&key.as_str()))
+        .map(|(key, value)| match (key.as_str(), value) {
+            ("base_command_override", _) => "custom command".to_string(),
+            (_, serde_json::Value::String(s)) => format!("{key}: {s}"),
+            _ => format!("{key}: {value}"),
+        })
+        .collect();
+    if described.is_empty() {
+        "Default settings".to_string()
+    } else {
+        described.join(", ")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -207,7 +284,7 @@ impl ExecutorConfigs {
         let profiles_path = workspace_utils::assets::profiles_path();
 
         // Load defaults first
-        let mut defaults = Self::from_defaults();
+        let mut defaults = Self::from_defaults().with_custom_agents();
         defaults.canonicalise();
 
         // Try to load user overrides
@@ -236,10 +313,20 @@ impl ExecutorConfigs {
         }
     }
 
+    /// Add the agents defined in the custom executors directory. Like the built-in defaults,
+    /// they can be overridden in profiles.json but are never written to it.
+    fn with_custom_agents(mut self) -> Self {
+        let dir = workspace_utils::assets::custom_executors_path();
+        if let Some(custom) = load_custom_agents(&dir) {
+            self.executors.insert(BaseCodingAgent::Custom, custom);
+        }
+        self
+    }
+
     /// Save user profile overrides to file (only saves what differs from defaults)
     pub fn save_overrides(&self) -> Result<(), ProfileError> {
         let profiles_path = workspace_utils::assets::profiles_path();
-        let mut defaults = Self::from_defaults();
+        let mut defaults = Self::from_defaults().with_custom_agents();
         defaults.canonicalise();
 
         // Canonicalise current config before computing overrides
@@ -371,6 +458,23 @@ impl ExecutorConfigs {
                     )));
                 }
             }
+
+            for (config_name, config) in &profile.configurations {
+                if let CodingAgent::Custom(custom) = config {
+                    custom.validate().map_err(|e| {
+                        ProfileError::Validation(format!(
+                            "Configuration '{executor_key}:{config_name}' is an invalid custom agent: {e}"
+                        ))
+                    })?;
+                }
+                if let Some(output_parser) = &config.cmd_overrides().output_parser {
+                    output_parser.validate().map_err(|e| {
+                        ProfileError::Validation(format!(
+                            "Configuration '{executor_key}:{config_name}' has an invalid output parser: {e}"
+                        ))
+                    })?;
+                }
+            }
         }
         Ok(())
     }
@@ -409,6 +513,47 @@ impl ExecutorConfigs {
                     .expect("No default variant found")
             })
     }
+    /// Valid variants of every configured executor, ordered by executor name
+    pub fn variant_catalog(&self) -> Vec<ExecutorVariants> {
+        let mut catalog: Vec<ExecutorVariants> = self
+            .executors
+            .iter()
+            .map(|(executor, config)| ExecutorVariants {
+                executor: *executor,
+                variants: config
+                    .configuration_names()
+                    .into_iter()
+                    .map(|name| VariantInfo::new(name, &config.configurations[name]))
+                    .collect(),
+            })
+            .collect();
+        catalog.sort_by_key(|entry| entry.executor.to_string());
+        catalog
+    }
+
+    /// Check that the executor is configured and has the requested variant
+    pub fn validate_profile_id(
+        &self,
+        executor_profile_id: &ExecutorProfileId,
+    ) -> Result<(), ProfileError> {
+        let executor = executor_profile_id.executor;
+        let config = self
+            .executors
+            .get(&executor)
+            .ok_or(ProfileError::UnknownExecutor(executor))?;
+        let Some(variant) = &executor_profile_id.variant else {
+            return Ok(());
+        };
+        if variant == "DEFAULT" || config.get_variant(variant).is_some() {
+            return Ok(());
+        }
+        Err(ProfileError::UnknownVariant {
+            executor,
+            variant: variant.clone(),
+            available: config.configuration_names().into_iter().cloned().collect(),
+        })
+    }
+
     /// Get the first available executor profile for new users
     pub async fn get_recommended_executor_profile(
         &self,
@@ -432,3 +577,48 @@ pub fn to_default_variant(id: &ExecutorProfileId) -> ExecutorProfileId {
         variant: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_variants_against_configured_ones() {
+        let configs = ExecutorConfigs::from_defaults();
+        let claude = BaseCodingAgent::ClaudeCode;
+        assert!(
+            configs
+                .validate_profile_id(&ExecutorProfileId::new(claude))
+                .is_ok()
+        );
+        assert!(
+            configs
+                .validate_profile_id(&ExecutorProfileId::with_variant(claude, "PLAN".into()))
+                .is_ok()
+        );
+
+        let err = configs
+            .validate_profile_id(&ExecutorProfileId::with_variant(claude, "FAST".into()))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown variant 'FAST' for executor 'CLAUDE_CODE'. Available variants: DEFAULT, APPROVALS, PLAN"
+        );
+    }
+
+    #[test]
+    fn catalog_lists_default_variant_first() {
+        let catalog = ExecutorConfigs::from_defaults().variant_catalog();
+        let gemini = catalog
+            .iter()
+            .find(|entry| entry.executor == BaseCodingAgent::Gemini)
+            .unwrap();
+        let names: Vec<&str> = gemini.variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["DEFAULT", "FLASH"]);
+
+        let flash = &gemini.variants[1];
+        assert_eq!(flash.model.as_deref(), Some("gemini-2.5-flash"));
+        assert_eq!(flash.cost_hint, CostHint::Low);
+        assert_eq!(flash.description, "yolo: true");
+    }
+}
@@ -0,0 +1,281 @@
+//! Optional Linux sandbox for executor processes.
+//!
+//! Sandboxed processes run under `bwrap` in their own pid/ipc/uts namespaces with the
+//! whole filesystem mounted read-only, except for the worktree and an explicit list of
+//! writable paths. cgroup v2 limits are applied by starting the process in a transient
+//! systemd scope.
+//!
+//! Outbound network access is controlled by [`EgressMode`]. `DenyAll` removes network
+//! access entirely with a private network namespace. `Allowlist` points the standard proxy
+//! environment variables at a filtering proxy run by the server; traffic from programs that
+//! ignore those variables is not filtered.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::process::Command;
+use ts_rs::TS;
+use workspace_utils::{path::expand_tilde, shell::resolve_executable_path};
+
+use crate::env_vars;
+
+#[derive(Debug, Error)]
+pub enum SandboxError {
+    #[error("Sandboxing is only supported on Linux")]
+    UnsupportedPlatform,
+    #[error("`{0}` is required for sandboxing but was not found on PATH")]
+    MissingTool(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[ts(use_ts_enum)]
+pub enum EgressMode {
+    /// No restrictions on outbound connections
+    #[default]
+    Allow,
+    /// No network access apart from a private loopback interface
+    DenyAll,
+    /// Only the allowed hosts are reachable, through the server's filtering proxy
+    Allowlist,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, TS)]
+pub struct SandboxConfig {
+    /// Run setup scripts, coding agents and dev servers inside the sandbox
+    #[serde(default)]
+    pub enabled: bool,
+    /// Paths outside the worktree that stay writable, e.g. `~/.claude` or `~/.cache`
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+    /// cgroup memory limit in megabytes
+    #[serde(default)]
+    pub memory_max_mb: Option<u32>,
+    /// cgroup CPU limit as a percentage of one core (200 = two cores)
+    #[serde(default)]
+    pub cpu_max_percent: Option<u32>,
+    /// cgroup limit on the number of processes and threads
+    #[serde(default)]
+    pub pids_max: Option<u32>,
+    /// Outbound network policy
+    #[serde(default)]
+    pub egress: EgressMode,
+    /// Hosts reachable in `ALLOWLIST` mode; `*.example.com` matches any subdomain
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+}
+
+impl SandboxConfig {
+    fn has_limits(&self) -> bool {
+        self.memory_max_mb.is_some() || self.cpu_max_percent.is_some() || self.pids_max.is_some()
+    }
+}
+
+tokio::task_local! {
+    static SANDBOX: Sandbox;
+}
+
+/// A sandbox resolved for one worktree.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    bwrap: String,
+    systemd_run: Option<String>,
+    config: SandboxConfig,
+    writable: Vec<PathBuf>,
+    proxy_url: Option<String>,
+}
+
+impl Sandbox {
+    /// Resolve `config` for `worktree_path`. `extra_writable` lists paths the process
+    /// needs on top of the configured ones, such as the repository's git directory.
+    pub fn new(
+        config: &SandboxConfig,
+        worktree_path: &Path,
+        extra_writable: Vec<PathBuf>,
+    ) -> Result<Self, SandboxError> {
+        if !cfg!(target_os = "linux") {
+            return Err(SandboxError::UnsupportedPlatform);
+        }
+        let bwrap = resolve_executable_path("bwrap").ok_or(SandboxError::MissingTool("bwrap"))?;
+        let systemd_run = if config.has_limits() {
+            Some(
+                resolve_executable_path("systemd-run")
+                    .ok_or(SandboxError::MissingTool("systemd-run"))?,
+            )
+        } else {
+            None
+        };
+
+        let mut writable = vec![worktree_path.to_path_buf(), std::env::temp_dir()];
+        writable.extend(extra_writable);
+        writable.extend(config.writable_paths.iter().map(|p| expand_tilde(p)));
+
+        Ok(Self {
+            bwrap,
+            systemd_run,
+            config: config.clone(),
+            writable,
+            proxy_url: None,
+        })
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    /// Route the sandboxed processes' HTTP(S) traffic through the proxy at `url`
+    pub fn with_proxy(mut self, url: String) -> Self {
+        self.proxy_url = Some(url);
+        self
+    }
+
+    /// Run `f` so that every command built with [`shell_command`] inside it is sandboxed.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        SANDBOX.scope(self, f).await
+    }
+
+    /// The sandbox of the current [`Sandbox::scope`], to carry it into spawned tasks
+    pub(crate) fn current() -> Option<Self> {
+        SANDBOX.try_with(Clone::clone).ok()
+    }
+
+    fn args(&self, shell_cmd: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(systemd_run) = &self.systemd_run {
+            args.extend([
+                systemd_run.clone(),
+                "--user".to_string(),
+                "--scope".to_string(),
+                "--quiet".to_string(),
+                "--collect".to_string(),
+            ]);
+            if let Some(mb) = self.config.memory_max_mb {
+                args.extend(["-p".to_string(), format!("MemoryMax={mb}M")]);
+            }
+            if let Some(percent) = self.config.cpu_max_percent {
+                args.extend(["-p".to_string(), format!("CPUQuota={percent}%")]);
+            }
+            if let Some(pids) = self.config.pids_max {
+                args.extend(["-p".to_string(), format!("TasksMax={pids}")]);
+            }
+            args.push("--".to_string());
+        }
+
+        args.extend(
+            [
+                &self.bwrap,
+                "--die-with-parent",
+                "--unshare-pid",
+                "--unshare-ipc",
+                "--unshare-uts",
+                "--ro-bind",
+                "/",
+                "/",
+                "--dev",
+                "/dev",
+                "--proc",
+                "/proc",
+            ]
+            .map(String::from),
+        );
+        if self.config.egress == EgressMode::DenyAll {
+            args.push("--unshare-net".to_string());
+        }
+        for path in &self.writable {
+            let path = path.to_string_lossy().to_string();
+            args.extend(["--bind-try".to_string(), path.clone(), path]);
+        }
+        args.extend(["--".to_string(), shell_cmd.to_string()]);
+        args
+    }
+}
+
+/// Start building a command that runs `shell_cmd`, wrapped in the sandbox of the
+/// current [`Sandbox::scope`] if there is one, with the variables of the current
+/// [`env_vars::scope`] in its environment. Callers append the shell's arguments.
+pub fn shell_command(shell_cmd: &str) -> Command {
+    SANDBOX
+        .try_with(|sandbox| {
+            let args = sandbox.args(shell_cmd);
+            let mut command = Command::new(&args[0]);
+            command.args(&args[1..]);
+            env_vars::apply(&mut command);
+            if let Some(proxy_url) = &sandbox.proxy_url {
+                for var in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+                    command.env(var, proxy_url);
+                    command.env(var.to_lowercase(), proxy_url);
+                }
+                command.env("NO_PROXY", "localhost,127.0.0.1,::1");
+                command.env("no_proxy", "localhost,127.0.0.1,::1");
+            }
+            command
+        })
+        .unwrap_or_else(|_| {
+            let mut command = Command::new(shell_cmd);
+            env_vars::apply(&mut command);
+            command
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(config: SandboxConfig) -> Sandbox {
+        Sandbox {
+            bwrap: "/usr/bin/bwrap".to_string(),
+            systemd_run: config
+                .has_limits()
+                .then(|| "/usr/bin/systemd-run".to_string()),
+            config,
+            writable: vec![PathBuf::from("/work/tree")],
+            proxy_url: None,
+        }
+    }
+
+    #[test]
+    fn wraps_shell_in_bwrap_with_writable_worktree() {
+        let args = sandbox(SandboxConfig {
+            enabled: true,
+            ..Default::default()
+        })
+        .args("bash");
+        assert_eq!(args[0], "/usr/bin/bwrap");
+        assert!(args.windows(3).any(|w| w == ["--ro-bind", "/", "/"]));
+        assert!(
+            args.windows(3)
+                .any(|w| w == ["--bind-try", "/work/tree", "/work/tree"])
+        );
+        assert_eq!(&args[args.len() - 2..], ["--", "bash"]);
+        assert!(!args.contains(&"--unshare-net".to_string()));
+
+        let args = sandbox(SandboxConfig {
+            enabled: true,
+            egress: EgressMode::DenyAll,
+            ..Default::default()
+        })
+        .args("bash");
+        assert!(args.contains(&"--unshare-net".to_string()));
+    }
+
+    #[test]
+    fn limits_run_inside_a_systemd_scope() {
+        let args = sandbox(SandboxConfig {
+            enabled: true,
+            memory_max_mb: Some(2048),
+            pids_max: Some(256),
+            ..Default::default()
+        })
+        .args("bash");
+        assert_eq!(args[0], "/usr/bin/systemd-run");
+        assert!(args.contains(&"MemoryMax=2048M".to_string()));
+        assert!(args.contains(&"TasksMax=256".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("CPUQuota")));
+        let bwrap = args.iter().position(|a| a == "/usr/bin/bwrap").unwrap();
+        assert_eq!(args[bwrap - 1], "--");
+    }
+}
@@ -1,10 +1,12 @@
 use command_group::AsyncGroupChild;
 #[cfg(unix)]
 use nix::{
-    sys::signal::{Signal, killpg},
+    sys::signal::{Signal, kill, killpg},
     unistd::{Pid, getpgid},
 };
 use services::services::container::ContainerError;
+#[cfg(unix)]
+use services::services::process_tree;
 use tokio::time::Duration;
 
 pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), ContainerError> {
@@ -15,6 +17,22 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
             let pgid = getpgid(Some(Pid::from_raw(pid as i32)))
                 .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?;
 
+            // Descendants that started their own process group are not reached by killpg.
+            // Find them now, while they are still linked to the group by their parents.
+            let escaped: Vec<u32> = match process_tree::snapshot().await {
+                Some(entries) => {
+                    let members = process_tree::members(&entries, pid);
+                    entries
+                        .iter()
+                        .filter(|entry| {
+                            members.contains(&entry.pid) && entry.pgid as i32 != pgid.as_raw()
+                        })
+                        .map(|entry| entry.pid)
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+
             for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
                 if let Err(e) = killpg(pgid, sig) {
                     tracing::warn!(
@@ -34,6 +52,10 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
                     break;
                 }
             }
+
+            if !escaped.is_empty() {
+                kill_processes(&escaped).await;
+            }
         }
     }
 
@@ -41,3 +63,95 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
     let _ = child.wait().await;
     Ok(())
 }
+
+/// Send SIGTERM to the given processes, then SIGKILL to any still running after a grace
+/// period. Does nothing on Windows.
+pub async fn kill_processes(pids: &[u32]) {
+    #[cfg(unix)]
+    {
+        let pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_raw(*pid as i32)).collect();
+        for pid in &pids {
+            if let Err(e) = kill(*pid, Signal::SIGTERM) {
+                tracing::debug!("Failed to send SIGTERM to process {}: {}", pid, e);
+            }
+        }
+        // Signal `None` only checks that the process still exists
+        for _ in 0..20 {
+            if pids.iter().all(|pid| kill(*pid, None).is_err()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        for pid in &pids {
+            if kill(*pid, None).is_ok() {
+                let _ = kill(*pid, Signal::SIGKILL);
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pids;
+        tracing::debug!("Killing individual processes is not supported on this platform");
+    }
+}
+
+/// Set the CPU niceness of every process in the child's process group. Processes the
+/// group starts later inherit it. Not supported on Windows.
+pub fn set_process_group_priority(child: &AsyncGroupChild, nice: i8) -> Result<(), ContainerError> {
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.id() {
+            let pgid = getpgid(Some(Pid::from_raw(pid as i32)))
+                .map_err(|e| ContainerError::Io(std::io::Error::other(e)))?;
+            // SAFETY: setpriority only reads its integer arguments
+            let result = unsafe {
+                libc::setpriority(
+                    libc::PRIO_PGRP,
+                    pgid.as_raw() as libc::id_t,
+                    nice.clamp(-20, 19).into(),
+                )
+            };
+            if result != 0 {
+                return Err(ContainerError::Io(std::io::Error::last_os_error()));
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (child, nice);
+        tracing::debug!("Process priority is not supported on this platform");
+    }
+    Ok(())
+}
+
+/// Resident memory used by all processes in the process group led by `pid`, in bytes.
+/// `None` when it cannot be measured, including on Windows.
+pub async fn process_group_memory(pid: u32) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        let pgid = getpgid(Some(Pid::from_raw(pid as i32))).ok()?.as_raw();
+        let output = tokio::process::Command::new("ps")
+            .args(["-A", "-o", "pgid=,rss="])
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let rss_kb: u64 = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let group: i32 = fields.next()?.parse().ok()?;
+                let rss: u64 = fields.next()?.parse().ok()?;
+                (group == pgid).then_some(rss)
+            })
+            .sum();
+        Some(rss_kb * 1024)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        None
+    }
+}
@@ -8,26 +8,35 @@ use std::{
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use chrono::Utc;
 use command_group::AsyncGroupChild;
 use db::{
     DBService,
     models::{
+        audit_log::AuditAction,
+        dev_server::DevServer,
         draft::{Draft, DraftType},
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+            KillReason,
         },
+        execution_process_metrics::ExecutionProcessMetrics,
         executor_session::ExecutorSession,
         image::TaskImage,
         merge::Merge,
         project::Project,
         task::{Task, TaskStatus},
         task_attempt::TaskAttempt,
+        task_attempt_diff_stats::{AttemptDiffStats, TaskAttemptDiffStats},
+        task_schedule::TaskSchedule,
+        terminal_session::TerminalSession,
     },
 };
 use deployment::DeploymentError;
 use executors::{
-    actions::{Executable, ExecutorAction},
+    actions::{Executable, ExecutorAction, ExecutorActionType, script::ScriptContext},
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
+    env_vars,
     executors::BaseCodingAgent,
     logs::{
         NormalizedEntryType,
@@ -36,30 +45,70 @@ use executors::{
             patch::{escape_json_pointer_segment, extract_normalized_entry_from_patch},
         },
     },
+    sandbox::{EgressMode, Sandbox},
 };
 use futures::{FutureExt, StreamExt, TryStreamExt, stream::select};
 use serde_json::json;
 use services::services::{
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
-    config::Config,
+    attempt_comparison::DiffStats,
+    audit_log::AuditEvent,
+    chat_notifier,
+    config::{Config, ResourceLimits, StallAction},
     container::{ContainerError, ContainerRef, ContainerService},
+    deploy_hook::{DeployHookEvent, DeployHookService},
     diff_stream::{self, DiffStreamHandle},
-    git::{Commit, DiffTarget, GitService},
+    egress_proxy::EgressProxy,
+    email_notification, execution_metrics, execution_retry,
+    git::{Commit, DiffContentMode, DiffTarget, GitService},
+    i18n::{Locale, Message},
     image::ImageService,
+    log_search,
+    merge_queue::MergeQueueService,
     notification::NotificationService,
+    process_tree::{self, ProcessTreeNode},
+    project_env::{self, SecretCipher},
+    project_locale, prompt_snippets, quiet_hours,
+    resource_usage::ResourceMonitor,
+    shutdown, task_schedule,
+    terminal::{self, Terminal, TerminalInput, TerminalRegistry, TerminalSize, Transcript},
+    vulnerability_scan::VulnerabilityScanService,
     worktree_manager::WorktreeManager,
 };
-use tokio::{sync::RwLock, task::JoinHandle};
-use tokio_util::io::ReaderStream;
+use tokio::{
+    io::AsyncWriteExt,
+    sync::{
+        RwLock,
+        broadcast::{self, error::RecvError},
+        mpsc,
+    },
+    task::JoinHandle,
+};
+use tokio_util::{io::ReaderStream, sync::CancellationToken};
+use tracing::Instrument;
 use utils::{
+    diff::Diff,
     log_msg::LogMsg,
     msg_store::MsgStore,
+    shell::resolve_executable_path,
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
 
-use crate::command;
+use crate::{
+    command, pty,
+    stall_monitor::{StallEvent, StallMonitor},
+};
+
+/// How often the memory of a process with a memory limit is measured
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the CPU and memory use of running executions is sampled
+const RESOURCE_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Pause before restarting a crashed dev server
+const DEV_SERVER_RESTART_DELAY: Duration = Duration::from_secs(2);
 
 #[derive(Clone)]
 pub struct LocalContainerService {
@@ -71,6 +120,8 @@ pub struct LocalContainerService {
     image_service: ImageService,
     analytics: Option<AnalyticsContext>,
     approvals: Approvals,
+    resource_monitor: ResourceMonitor,
+    terminals: TerminalRegistry,
 }
 
 impl LocalContainerService {
@@ -94,6 +145,8 @@ impl LocalContainerService {
             image_service,
             analytics,
             approvals,
+            resource_monitor: ResourceMonitor::new(),
+            terminals: TerminalRegistry::default(),
         }
     }
 
@@ -123,7 +176,7 @@ impl LocalContainerService {
             .is_none()
             && (!matches!(
                 ctx.execution_process.run_reason,
-                ExecutionProcessRunReason::DevServer
+                ExecutionProcessRunReason::DevServer | ExecutionProcessRunReason::PostMergeScript
             ))
     }
 
@@ -133,11 +186,56 @@ impl LocalContainerService {
             tracing::error!("Failed to update task status to InReview: {e}");
         }
         let notify_cfg = config.read().await.notifications.clone();
-        NotificationService::notify_execution_halted(notify_cfg, ctx).await;
+        let locale = Self::message_locale(db, config, ctx.task.project_id).await;
+        NotificationService::notify_execution_halted(notify_cfg, locale, ctx).await;
+        chat_notifier::spawn_notify_execution_halted(db.clone(), config.clone(), ctx);
+    }
+
+    /// Email the user about the finished run, with a summary of the attempt's changes
+    async fn send_completion_email(&self, ctx: &ExecutionContext) {
+        if !self.config.read().await.email_notifications.enabled {
+            return;
+        }
+        let diffs = if ctx.task_attempt.worktree_deleted {
+            None
+        } else {
+            self.list_diff_files(&ctx.task_attempt, None)
+                .await
+                .inspect_err(|e| {
+                    tracing::debug!(
+                        "Failed to list changes of task attempt {}: {}",
+                        ctx.task_attempt.id,
+                        e
+                    )
+                })
+                .ok()
+        };
+        email_notification::spawn_notify_execution_halted(
+            self.db.clone(),
+            self.config.clone(),
+            ctx,
+            diffs,
+        );
+    }
+
+    /// Language of the notifications and commit messages generated for a project
+    async fn message_locale(
+        db: &DBService,
+        config: &Arc<RwLock<Config>>,
+        project_id: Uuid,
+    ) -> Locale {
+        let language = config.read().await.language;
+        match Project::find_by_id(&db.pool, project_id).await {
+            Ok(Some(project)) => project_locale::locale(&project, &language),
+            _ => Locale::from_config(&language),
+        }
     }
 
     /// Defensively check for externally deleted worktrees and mark them as deleted in the database
-    async fn check_externally_deleted_worktrees(db: &DBService) -> Result<(), DeploymentError> {
+    async fn check_externally_deleted_worktrees(
+        db: &DBService,
+        terminals: &TerminalRegistry,
+    ) -> Result<(), DeploymentError> {
         let active_attempts = TaskAttempt::find_by_worktree_deleted(&db.pool).await?;
         tracing::debug!(
             "Checking {} active worktrees for external deletion...",
@@ -146,6 +244,7 @@ impl LocalContainerService {
         for (attempt_id, worktree_path) in active_attempts {
             // Check if worktree directory exists
             if !std::path::Path::new(&worktree_path).exists() {
+                terminals.close_attempt(attempt_id);
                 // Worktree was deleted externally, mark as deleted in database
                 if let Err(e) = TaskAttempt::mark_worktree_deleted(&db.pool, attempt_id).await {
                     tracing::error!(
@@ -213,7 +312,13 @@ impl LocalContainerService {
             {
                 // This is an orphaned worktree - delete it
                 tracing::info!("Found orphaned worktree: {}", worktree_path_str);
-                if let Err(e) = WorktreeManager::cleanup_worktree(&path, None).await {
+                let result = WorktreeManager::cleanup_worktree(&path, None).await;
+                AuditEvent::new(AuditAction::WorktreeDelete, &worktree_path_str)
+                    .parameters(json!({ "reason": "orphaned" }))
+                    .outcome(&result)
+                    .record(&self.db().pool)
+                    .await;
+                if let Err(e) = result {
                     tracing::error!(
                         "Failed to remove orphaned worktree {}: {}",
                         worktree_path_str,
@@ -231,18 +336,30 @@ impl LocalContainerService {
 
     pub async fn cleanup_expired_attempt(
         db: &DBService,
+        terminals: &TerminalRegistry,
         attempt_id: Uuid,
         worktree_path: PathBuf,
         git_repo_path: PathBuf,
     ) -> Result<(), DeploymentError> {
-        WorktreeManager::cleanup_worktree(&worktree_path, Some(&git_repo_path)).await?;
+        terminals.close_attempt(attempt_id);
+        let result = WorktreeManager::cleanup_worktree(&worktree_path, Some(&git_repo_path)).await;
+        AuditEvent::new(AuditAction::WorktreeDelete, worktree_path.to_string_lossy())
+            .attempt(attempt_id)
+            .parameters(json!({ "reason": "expired" }))
+            .outcome(&result)
+            .record(&db.pool)
+            .await;
+        result?;
         // Mark worktree as deleted in database after successful cleanup
         TaskAttempt::mark_worktree_deleted(&db.pool, attempt_id).await?;
         tracing::info!("Successfully marked worktree as deleted for attempt {attempt_id}",);
         Ok(())
     }
 
-    pub async fn cleanup_expired_attempts(db: &DBService) -> Result<(), DeploymentError> {
+    pub async fn cleanup_expired_attempts(
+        db: &DBService,
+        terminals: &TerminalRegistry,
+    ) -> Result<(), DeploymentError> {
         let expired_attempts = TaskAttempt::find_expired_for_cleanup(&db.pool).await?;
         if expired_attempts.is_empty() {
             tracing::debug!("No expired worktrees found");
@@ -255,6 +372,7 @@ impl LocalContainerService {
         for (attempt_id, worktree_path, git_repo_path) in expired_attempts {
             Self::cleanup_expired_attempt(
                 db,
+                terminals,
                 attempt_id,
                 PathBuf::from(worktree_path),
                 PathBuf::from(git_repo_path),
@@ -269,18 +387,19 @@ impl LocalContainerService {
 
     pub async fn spawn_worktree_cleanup(&self) {
         let db = self.db.clone();
+        let terminals = self.terminals.clone();
         let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
         self.cleanup_orphaned_worktrees().await;
         tokio::spawn(async move {
             loop {
                 cleanup_interval.tick().await;
                 tracing::info!("Starting periodic worktree cleanup...");
-                Self::check_externally_deleted_worktrees(&db)
+                Self::check_externally_deleted_worktrees(&db, &terminals)
                     .await
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to check externally deleted worktrees: {}", e);
                     });
-                Self::cleanup_expired_attempts(&db)
+                Self::cleanup_expired_attempts(&db, &terminals)
                     .await
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to clean up expired worktree attempts: {}", e)
@@ -306,7 +425,7 @@ impl LocalContainerService {
 
         let mut process_exit_rx = self.spawn_os_exit_watcher(exec_id);
 
-        tokio::spawn(async move {
+        let monitor = async move {
             let mut exit_signal_future = exit_signal
                 .map(|rx| rx.map(|_| ()).boxed()) // wait for signal
                 .unwrap_or_else(|| std::future::pending::<()>().boxed()); // no signal, stall forever
@@ -347,19 +466,67 @@ impl LocalContainerService {
                 Err(_) => (None, ExecutionProcessStatus::Failed),
             };
 
-            if !ExecutionProcess::was_stopped(&db.pool, exec_id).await
+            let stopped = ExecutionProcess::was_stopped(&db.pool, exec_id).await;
+            // Stored before the process completes, so the task update it triggers has them
+            if !stopped
+                && let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await
+                && let Err(e) = container.record_test_results(&ctx).await
+            {
+                tracing::warn!("Failed to record test results: {}", e);
+            }
+            if !stopped
                 && let Err(e) =
                     ExecutionProcess::update_completion(&db.pool, exec_id, status, exit_code).await
             {
                 tracing::error!("Failed to update execution process completion: {}", e);
             }
 
+            // A coding agent slot may have been freed
+            if let Err(e) = container.start_queued_executions().await {
+                tracing::error!("Failed to start queued execution processes: {}", e);
+            }
+
             if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
+                let retry_delay = if stopped {
+                    None
+                } else {
+                    container.retry_delay(&ctx).await
+                };
+                if let Some(delay) = retry_delay {
+                    container.spawn_retry(
+                        ctx.task_attempt.clone(),
+                        ctx.execution_process.clone(),
+                        delay,
+                    );
+                }
+
+                if !stopped
+                    && ctx.execution_process.run_reason == ExecutionProcessRunReason::DevServer
+                    && ctx.execution_process.status == ExecutionProcessStatus::Failed
+                {
+                    container.spawn_dev_server_restart(
+                        ctx.task_attempt.clone(),
+                        ctx.execution_process.clone(),
+                    );
+                }
+
                 // Update executor session summary if available
                 if let Err(e) = container.update_executor_session_summary(&exec_id).await {
                     tracing::warn!("Failed to update executor session summary: {}", e);
                 }
 
+                if let Err(e) = container.record_metrics(&ctx).await {
+                    tracing::warn!("Failed to record execution process metrics: {}", e);
+                }
+
+                let history = container
+                    .get_msg_store_by_id(&exec_id)
+                    .await
+                    .map(|store| store.get_history());
+                if let Err(e) = log_search::index(&db.pool, exec_id, history).await {
+                    tracing::warn!("Failed to index execution process logs: {}", e);
+                }
+
                 let success = matches!(
                     ctx.execution_process.status,
                     ExecutionProcessStatus::Completed
@@ -373,6 +540,10 @@ impl LocalContainerService {
                     ExecutionProcessStatus::Running
                 );
 
+                if success && let Err(e) = container.save_setup_cache(&ctx).await {
+                    tracing::warn!("Failed to cache setup script output: {}", e);
+                }
+
                 if success || cleanup_done {
                     // Commit changes (if any) and get feedback about whether changes were made
                     let changes_committed = match container.try_commit_changes(&ctx).await {
@@ -406,11 +577,53 @@ impl LocalContainerService {
 
                         // Manually finalize task since we're bypassing normal execution flow
                         Self::finalize_task(&db, &config, &ctx).await;
+                        container.send_completion_email(&ctx).await;
                     }
                 }
 
-                if Self::should_finalize(&ctx) {
+                // After the commit, so the stats match the attempt's branch
+                if let Err(e) = container.record_diff_stats(&ctx).await {
+                    tracing::warn!("Failed to record diff stats: {}", e);
+                }
+
+                let failure_action_started = !success
+                    && !stopped
+                    && retry_delay.is_none()
+                    && ctx.execution_process.status == ExecutionProcessStatus::Failed
+                    && container
+                        .try_start_failure_action(&ctx)
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::error!("Failed to start failure action: {}", e);
+                            false
+                        });
+
+                // A retried process, or one whose failure action is running, isn't finished yet
+                if Self::should_finalize(&ctx) && retry_delay.is_none() && !failure_action_started {
                     Self::finalize_task(&db, &config, &ctx).await;
+                    container.send_completion_email(&ctx).await;
+                    if success {
+                        let worktree_dir = container.task_attempt_to_current_dir(&ctx.task_attempt);
+                        let commit = container
+                            .git()
+                            .get_head_info(&worktree_dir)
+                            .ok()
+                            .map(|head| head.oid);
+                        DeployHookService::spawn_trigger(
+                            db.clone(),
+                            ctx.task.project_id,
+                            ctx.task_attempt.clone(),
+                            DeployHookEvent::AttemptCompleted,
+                            commit,
+                        );
+                        VulnerabilityScanService::spawn_scan(
+                            db.clone(),
+                            container.git().clone(),
+                            config.read().await.vulnerability_scan.clone(),
+                            ctx.task_attempt.clone(),
+                            worktree_dir,
+                        );
+                    }
                     // After finalization, check if a queued follow-up exists and start it
                     if let Err(e) = container.try_consume_queued_followup(&ctx).await {
                         tracing::error!(
@@ -468,6 +681,96 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+        };
+        tokio::spawn(monitor.instrument(tracing::info_span!(
+            "execution.monitor",
+            execution_process_id = %exec_id
+        )))
+    }
+
+    /// Keep the execution's egress proxy running until the process finishes, logging
+    /// every blocked connection to the process output
+    fn spawn_egress_monitor(
+        exec_id: Uuid,
+        proxy: EgressProxy,
+        mut denied_rx: mpsc::UnboundedReceiver<String>,
+        store: Arc<MsgStore>,
+    ) -> JoinHandle<()> {
+        let mut log_rx = store.get_receiver();
+
+        tokio::spawn(async move {
+            let _proxy = proxy;
+            loop {
+                tokio::select! {
+                    Some(host) = denied_rx.recv() => {
+                        tracing::warn!("Execution process {} blocked from {}", exec_id, host);
+                        store.push_stderr(format!(
+                            "[sandbox] Blocked connection to {host}: not in the egress allowlist\n"
+                        ));
+                    }
+                    msg = log_rx.recv() => match msg {
+                        Ok(LogMsg::Finished) | Err(RecvError::Closed) => break,
+                        _ => {}
+                    },
+                }
+            }
+        })
+    }
+
+    /// Spawn a background task that flags a coding agent as stalled when it produces
+    /// no output for the configured interval, and clears the flag once output resumes.
+    pub fn spawn_stall_monitor(
+        &self,
+        exec_id: Uuid,
+        mut log_rx: broadcast::Receiver<LogMsg>,
+    ) -> JoinHandle<()> {
+        let container = self.clone();
+
+        tokio::spawn(async move {
+            let stall_detection = container.config.read().await.stall_detection.clone();
+            if !stall_detection.enabled || stall_detection.timeout_secs == 0 {
+                return;
+            }
+
+            let pool = &container.db.pool;
+            let mut monitor = StallMonitor::new(stall_detection.timeout_secs);
+            let approval_pending = || container.approvals.has_pending(&exec_id);
+            while let Some(event) = monitor.next_event(&mut log_rx, approval_pending).await {
+                match event {
+                    StallEvent::Resumed => {
+                        tracing::info!("Execution process {} resumed output", exec_id);
+                        if let Err(e) =
+                            ExecutionProcess::update_stalled_at(pool, exec_id, None).await
+                        {
+                            tracing::warn!("Failed to clear stalled_at: {}", e);
+                        }
+                    }
+                    StallEvent::Stalled => {
+                        tracing::warn!(
+                            "Execution process {} produced no output for {}s",
+                            exec_id,
+                            stall_detection.timeout_secs
+                        );
+                        let now = Some(Utc::now());
+                        if let Err(e) =
+                            ExecutionProcess::update_stalled_at(pool, exec_id, now).await
+                        {
+                            tracing::warn!("Failed to set stalled_at: {}", e);
+                        }
+                        if let Some(store) = container.msg_stores.read().await.get(&exec_id) {
+                            store.push_stderr(format!("{}\n", monitor.warning()));
+                        }
+
+                        if stall_detection.action == StallAction::Cancel {
+                            let reason = KillReason::Stalled {
+                                timeout_secs: stall_detection.timeout_secs,
+                            };
+                            container.stop_for_reason(exec_id, reason).await;
+                            break;
+                        }
+                    }
+                }
+            }
         })
     }
 
@@ -572,6 +875,376 @@ impl LocalContainerService {
         Ok(project_repo_path)
     }
 
+    /// Resolve the project's sandbox for processes running in `worktree_path`, if enabled
+    /// Stop the execution when it runs longer or uses more memory than `limits` allow
+    pub fn spawn_resource_watchdog(
+        &self,
+        exec_id: Uuid,
+        limits: ResourceLimits,
+        mut log_rx: broadcast::Receiver<LogMsg>,
+    ) -> JoinHandle<()> {
+        let container = self.clone();
+
+        tokio::spawn(async move {
+            let deadline = limits
+                .timeout_secs
+                .map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs.into()));
+            let time_limit =
+                tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now));
+            tokio::pin!(time_limit);
+            let mut memory_check = tokio::time::interval(MEMORY_CHECK_INTERVAL);
+            let reason = loop {
+                tokio::select! {
+                    msg = log_rx.recv() => match msg {
+                        Ok(LogMsg::Finished) | Err(RecvError::Closed) => return,
+                        _ => {}
+                    },
+                    _ = &mut time_limit, if deadline.is_some() => {
+                        break KillReason::TimeLimit {
+                            limit_secs: limits.timeout_secs.unwrap_or_default(),
+                        };
+                    }
+                    _ = memory_check.tick(), if limits.memory_max_mb.is_some() => {
+                        let Some(limit_mb) = limits.memory_max_mb else {
+                            continue;
+                        };
+                        let Some(child) = container.get_child_from_store(&exec_id).await else {
+                            continue;
+                        };
+                        let Some(pid) = child.read().await.id() else {
+                            continue;
+                        };
+                        let Some(used) = command::process_group_memory(pid).await else {
+                            continue;
+                        };
+                        let used_mb = u32::try_from(used / (1024 * 1024)).unwrap_or(u32::MAX);
+                        if used_mb > limit_mb {
+                            break KillReason::MemoryLimit { limit_mb, used_mb };
+                        }
+                    }
+                }
+            };
+            container.stop_for_reason(exec_id, reason).await;
+        })
+    }
+
+    /// Pass a terminal's input to its shell and the shell's output back until the shell exits,
+    /// the client goes away or the attempt's terminals are closed, then save the transcript
+    async fn run_terminal(
+        self,
+        session: TerminalSession,
+        pty: pty::Pty,
+        mut input: mpsc::Receiver<TerminalInput>,
+        output: mpsc::Sender<Vec<u8>>,
+        cancel: CancellationToken,
+    ) {
+        let pty::Pty {
+            mut child,
+            mut reader,
+            mut writer,
+        } = pty;
+
+        // Reads block; they fail once the shell and everything it started have closed the
+        // terminal
+        let (chunks_tx, mut chunks) = mpsc::channel::<Vec<u8>>(64);
+        tokio::task::spawn_blocking(move || {
+            use std::io::Read;
+
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = reader.read(&mut buf) {
+                if n == 0 || chunks_tx.blocking_send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut transcript = Transcript::default();
+        let exit_code = loop {
+            tokio::select! {
+                chunk = chunks.recv() => match chunk {
+                    Some(chunk) => {
+                        transcript.push(&chunk);
+                        // The client may be gone while the shell winds down
+                        let _ = output.send(chunk).await;
+                    }
+                    None => {
+                        break child
+                            .wait()
+                            .await
+                            .ok()
+                            .and_then(|status| status.code())
+                            .map(i64::from);
+                    }
+                },
+                message = input.recv() => match message {
+                    Some(TerminalInput::Data(data)) => {
+                        if let Err(e) = writer.write_all(&data).await {
+                            tracing::debug!("Failed to write to terminal {}: {}", session.id, e);
+                        }
+                    }
+                    Some(TerminalInput::Resize(size)) => {
+                        if let Err(e) = pty::resize(&writer, size) {
+                            tracing::debug!("Failed to resize terminal {}: {}", session.id, e);
+                        }
+                    }
+                    None => {
+                        pty::hang_up(&mut child).await;
+                        break None;
+                    }
+                },
+                _ = cancel.cancelled() => {
+                    pty::hang_up(&mut child).await;
+                    break None;
+                }
+            }
+        };
+
+        self.terminals
+            .unregister(session.task_attempt_id, session.id);
+        if let Err(e) = TerminalSession::finish(
+            &self.db.pool,
+            session.id,
+            exit_code,
+            &transcript.into_string(),
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to save transcript of terminal {}: {}",
+                session.id,
+                e
+            );
+        }
+    }
+
+    /// Start a crashed dev server again after a short delay, if restarts are enabled and the
+    /// restart limit has not been reached
+    fn spawn_dev_server_restart(&self, task_attempt: TaskAttempt, crashed: ExecutionProcess) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let config = container.config.read().await.dev_server.clone();
+            if !config.restart_on_crash {
+                return;
+            }
+            let pool = &container.db.pool;
+            let Ok(Some(dev_server)) =
+                DevServer::find_by_task_attempt_id(pool, task_attempt.id).await
+            else {
+                return;
+            };
+            // A newer dev server has already replaced this one
+            if dev_server.execution_process_id != Some(crashed.id) {
+                return;
+            }
+            if dev_server.restart_count >= config.max_restarts {
+                tracing::warn!(
+                    "Dev server for attempt {} crashed {} times, not restarting",
+                    task_attempt.id,
+                    dev_server.restart_count + 1
+                );
+                return;
+            }
+            let Ok(ExecutorActionType::ScriptRequest(request)) =
+                crashed.executor_action().map(|action| action.typ())
+            else {
+                return;
+            };
+
+            tokio::time::sleep(DEV_SERVER_RESTART_DELAY).await;
+            tracing::info!(
+                "Restarting crashed dev server for attempt {} (restart {} of {})",
+                task_attempt.id,
+                dev_server.restart_count + 1,
+                config.max_restarts
+            );
+            if let Err(e) = container
+                .start_dev_server(
+                    &task_attempt,
+                    request.script.clone(),
+                    dev_server.restart_count + 1,
+                )
+                .await
+            {
+                tracing::error!(
+                    "Failed to restart dev server for attempt {}: {}",
+                    task_attempt.id,
+                    e
+                );
+            }
+        });
+    }
+
+    /// Delay before re-running a setup script or coding agent that failed with a transient
+    /// error, if the project's retry policy allows another retry
+    async fn retry_delay(&self, ctx: &ExecutionContext) -> Option<Duration> {
+        let process = &ctx.execution_process;
+        if process.status != ExecutionProcessStatus::Failed
+            || !execution_retry::is_retryable(&process.run_reason)
+        {
+            return None;
+        }
+        let project = Project::find_by_id(&self.db.pool, ctx.task.project_id)
+            .await
+            .ok()
+            .flatten()?;
+        let policy = project.retry_policy?.0;
+        if !policy.enabled {
+            return None;
+        }
+        let store = self.get_msg_store_by_id(&process.id).await?;
+        let error = execution_retry::transient_error(&store.get_history())?;
+
+        let retries = match execution_retry::previous_retries(&self.db.pool, process).await {
+            Ok(retries) => retries,
+            Err(e) => {
+                tracing::error!("Failed to count retries of execution {}: {}", process.id, e);
+                return None;
+            }
+        };
+        if retries >= policy.max_retries {
+            tracing::info!(
+                "Execution {} failed with a transient error ({}) after {} retries, giving up",
+                process.id,
+                error,
+                retries
+            );
+            return None;
+        }
+        let delay = execution_retry::backoff(&policy, retries + 1);
+        store.push_stderr(format!(
+            "Transient error detected ({error}), retrying in {}s (retry {} of {})",
+            delay.as_secs(),
+            retries + 1,
+            policy.max_retries
+        ));
+        Some(delay)
+    }
+
+    /// Re-run a failed process after `delay`, unless other work has started on the attempt
+    /// in the meantime
+    fn spawn_retry(&self, task_attempt: TaskAttempt, failed: ExecutionProcess, delay: Duration) {
+        let container = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let latest = ExecutionProcess::find_by_task_attempt_id(
+                &container.db.pool,
+                task_attempt.id,
+                false,
+            )
+            .await
+            .ok()
+            .and_then(|processes| processes.last().map(|process| process.id));
+            if latest != Some(failed.id) {
+                tracing::info!(
+                    "Not retrying execution {}: attempt {} has moved on",
+                    failed.id,
+                    task_attempt.id
+                );
+                return;
+            }
+            match container.retry_execution(&task_attempt, &failed).await {
+                Ok(retry) => tracing::info!("Retrying execution {} as {}", failed.id, retry.id),
+                Err(e) => tracing::error!("Failed to retry execution {}: {}", failed.id, e),
+            }
+        });
+    }
+
+    /// Kill a running execution on the server's behalf, recording why
+    async fn stop_for_reason(&self, exec_id: Uuid, reason: KillReason) {
+        let pool = &self.db.pool;
+        let Ok(Some(process)) = ExecutionProcess::find_by_id(pool, exec_id).await else {
+            return;
+        };
+        if process.status != ExecutionProcessStatus::Running {
+            return;
+        }
+
+        let message = match &reason {
+            KillReason::TimeLimit { limit_secs } => {
+                format!("Stopped after reaching the {limit_secs}s time limit")
+            }
+            KillReason::MemoryLimit { limit_mb, used_mb } => {
+                format!("Stopped after using {used_mb} MB of memory, over the {limit_mb} MB limit")
+            }
+            KillReason::Stalled { timeout_secs } => {
+                format!("Stopped after producing no output for {timeout_secs}s")
+            }
+        };
+        tracing::warn!("Execution process {}: {}", exec_id, message);
+        if let Some(store) = self.msg_stores.read().await.get(&exec_id) {
+            store.push_stderr(format!("{message}\n"));
+        }
+
+        if let Err(e) = ExecutionProcess::update_kill_reason(pool, exec_id, &reason).await {
+            tracing::warn!("Failed to record kill reason: {}", e);
+        }
+        if let Err(e) = self
+            .stop_execution(&process, ExecutionProcessStatus::Killed)
+            .await
+        {
+            tracing::error!("Failed to stop execution process {}: {}", exec_id, e);
+        }
+    }
+
+    /// Limits configured for the kind of process being started
+    async fn resource_limits(&self, run_reason: &ExecutionProcessRunReason) -> ResourceLimits {
+        let limits = &self.config.read().await.resource_limits;
+        match run_reason {
+            ExecutionProcessRunReason::CodingAgent => limits.coding_agent.clone(),
+            ExecutionProcessRunReason::SetupScript
+            | ExecutionProcessRunReason::CleanupScript
+            | ExecutionProcessRunReason::PostMergeScript
+            | ExecutionProcessRunReason::TestScript => limits.scripts.clone(),
+            ExecutionProcessRunReason::DevServer => limits.dev_server.clone(),
+        }
+    }
+
+    async fn project_sandbox(
+        &self,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+        limits: &ResourceLimits,
+    ) -> Result<Option<Sandbox>, ContainerError> {
+        let project = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?
+            .parent_project(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent project not found")))?;
+        let Some(mut config) = project.sandbox.filter(|sandbox| sandbox.enabled) else {
+            return Ok(None);
+        };
+        // Let the kernel enforce the memory limit as well when cgroups are available
+        if config.memory_max_mb.is_none() && resolve_executable_path("systemd-run").is_some() {
+            config.memory_max_mb = limits.memory_max_mb;
+        }
+
+        // Commits made in the worktree are written to the main repository's git directory
+        let git_dir = project.git_repo_path.join(".git");
+        let sandbox = Sandbox::new(&config, worktree_path, vec![git_dir])
+            .map_err(|e| ContainerError::Other(anyhow!("Failed to set up sandbox: {e}")))?;
+        Ok(Some(sandbox))
+    }
+
+    /// The project's environment variables, with secrets decrypted
+    async fn project_env_vars(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<HashMap<String, String>, ContainerError> {
+        let task = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?;
+        let cipher = SecretCipher::global()
+            .map_err(|e| ContainerError::Other(anyhow!("Failed to load secret key: {e}")))?;
+        project_env::resolve(&self.db.pool, cipher, task.project_id)
+            .await
+            .map_err(|e| {
+                ContainerError::Other(anyhow!("Failed to load project environment variables: {e}"))
+            })
+    }
+
     /// Create a diff log stream for merged attempts (never changes) for WebSocket
     fn create_merged_diff_stream(
         &self,
@@ -579,13 +1252,22 @@ impl LocalContainerService {
         merge_commit_id: &str,
         stats_only: bool,
     ) -> Result<DiffStreamHandle, ContainerError> {
-        let diffs = self.git().get_diffs(
+        self.create_static_diff_stream(
             DiffTarget::Commit {
                 repo_path: project_repo_path,
                 commit_sha: merge_commit_id,
             },
-            None,
-        )?;
+            stats_only,
+        )
+    }
+
+    /// Create a one-shot diff stream for a fixed target that won't change while watched
+    fn create_static_diff_stream(
+        &self,
+        target: DiffTarget,
+        stats_only: bool,
+    ) -> Result<DiffStreamHandle, ContainerError> {
+        let diffs = self.git().get_diffs(target, None)?;
 
         let cum = Arc::new(AtomicUsize::new(0));
         let diffs: Vec<_> = diffs
@@ -610,30 +1292,103 @@ impl LocalContainerService {
         Ok(diff_stream::DiffStreamHandle::new(stream, None))
     }
 
-    /// Create a live diff log stream for ongoing attempts for WebSocket
-    /// Returns a stream that owns the filesystem watcher - when dropped, watcher is cleaned up
-    async fn create_live_diff_stream(
+    /// The merge commit to diff instead of the worktree, once an attempt has been merged
+    /// and has no further changes
+    async fn merged_diff_commit(
         &self,
-        worktree_path: &Path,
-        base_commit: &Commit,
-        stats_only: bool,
-    ) -> Result<DiffStreamHandle, ContainerError> {
-        diff_stream::create(
-            self.git().clone(),
-            worktree_path.to_path_buf(),
-            base_commit.clone(),
-            stats_only,
-        )
-        .await
-        .map_err(|e| ContainerError::Other(anyhow!("{e}")))
-    }
-}
+        task_attempt: &TaskAttempt,
+        project_repo_path: &Path,
+    ) -> Result<Option<String>, ContainerError> {
+        let latest_merge =
+            Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
 
-fn success_exit_status() -> std::process::ExitStatus {
-    #[cfg(unix)]
-    {
-        use std::os::unix::process::ExitStatusExt;
-        ExitStatusExt::from_raw(0)
+        let is_ahead = if let Ok((ahead, _)) = self.git().get_branch_status(
+            project_repo_path,
+            &task_attempt.branch,
+            &task_attempt.target_branch,
+        ) {
+            ahead > 0
+        } else {
+            false
+        };
+
+        if let Some(merge) = &latest_merge
+            && let Some(commit) = merge.merge_commit()
+            && self.is_container_clean(task_attempt).await?
+            && !is_ahead
+        {
+            return Ok(Some(commit));
+        }
+        Ok(None)
+    }
+
+    /// Compute an attempt's diffs once, against the same target the diff stream uses
+    async fn get_attempt_diffs(
+        &self,
+        task_attempt: &TaskAttempt,
+        range: Option<(&Commit, &Commit)>,
+        path_filter: Option<&[&str]>,
+        mode: DiffContentMode,
+    ) -> Result<Vec<Diff>, ContainerError> {
+        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
+        if let Some((from_commit, to_commit)) = range {
+            let target = DiffTarget::Range {
+                repo_path: &project_repo_path,
+                from_commit,
+                to_commit,
+            };
+            return Ok(self.git().get_diffs_with_mode(target, path_filter, mode)?);
+        }
+
+        if let Some(commit) = self
+            .merged_diff_commit(task_attempt, &project_repo_path)
+            .await?
+        {
+            let target = DiffTarget::Commit {
+                repo_path: &project_repo_path,
+                commit_sha: &commit,
+            };
+            return Ok(self.git().get_diffs_with_mode(target, path_filter, mode)?);
+        }
+
+        let container_ref = self.ensure_container_exists(task_attempt).await?;
+        let worktree_path = PathBuf::from(container_ref);
+        let base_commit = self.git().get_base_commit(
+            &project_repo_path,
+            &task_attempt.branch,
+            &task_attempt.target_branch,
+        )?;
+        let target = DiffTarget::Worktree {
+            worktree_path: &worktree_path,
+            base_commit: &base_commit,
+        };
+        Ok(self.git().get_diffs_with_mode(target, path_filter, mode)?)
+    }
+
+    /// Create a live diff log stream for ongoing attempts for WebSocket
+    /// Returns a stream that owns the filesystem watcher - when dropped, watcher is cleaned up
+    async fn create_live_diff_stream(
+        &self,
+        worktree_path: &Path,
+        base_commit: &Commit,
+        stats_only: bool,
+    ) -> Result<DiffStreamHandle, ContainerError> {
+        diff_stream::create(
+            self.git().clone(),
+            worktree_path.to_path_buf(),
+            base_commit.clone(),
+            stats_only,
+        )
+        .await
+        .map_err(|e| ContainerError::Other(anyhow!("{e}")))
+    }
+}
+
+fn success_exit_status() -> std::process::ExitStatus {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatusExt::from_raw(0)
     }
     #[cfg(windows)]
     {
@@ -656,14 +1411,35 @@ impl ContainerService for LocalContainerService {
         &self.git
     }
 
+    fn resource_monitor(&self) -> &ResourceMonitor {
+        &self.resource_monitor
+    }
+
+    fn terminals(&self) -> &TerminalRegistry {
+        &self.terminals
+    }
+
     async fn git_branch_prefix(&self) -> String {
         self.config.read().await.git_branch_prefix.clone()
     }
 
+    async fn max_concurrent_coding_agents(&self) -> Option<u32> {
+        self.config
+            .read()
+            .await
+            .resource_limits
+            .max_concurrent_coding_agents
+    }
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf {
         PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default())
     }
     /// Create a container
+    #[tracing::instrument(
+        name = "container.create",
+        skip_all,
+        fields(task_attempt_id = %task_attempt.id)
+    )]
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError> {
         let task = task_attempt
             .parent_task(&self.db.pool)
@@ -792,6 +1568,15 @@ impl ContainerService for LocalContainerService {
         }
     }
 
+    #[tracing::instrument(
+        name = "executor.spawn",
+        skip_all,
+        fields(
+            task_attempt_id = %task_attempt.id,
+            execution_process_id = %execution_process.id,
+            run_reason = ?execution_process.run_reason
+        )
+    )]
     async fn start_execution_inner(
         &self,
         task_attempt: &TaskAttempt,
@@ -805,7 +1590,13 @@ impl ContainerService for LocalContainerService {
             .ok_or(ContainerError::Other(anyhow!(
                 "Container ref not found for task attempt"
             )))?;
-        let current_dir = PathBuf::from(container_ref);
+        // Post-merge scripts run against the merged base branch rather than the attempt
+        let current_dir =
+            if execution_process.run_reason == ExecutionProcessRunReason::PostMergeScript {
+                self.get_project_repo_path(task_attempt).await?
+            } else {
+                PathBuf::from(container_ref)
+            };
 
         let approvals_service: Arc<dyn ExecutorApprovalService> =
             match executor_action.base_executor() {
@@ -813,20 +1604,80 @@ impl ContainerService for LocalContainerService {
                     ExecutorApprovalBridge::new(
                         self.approvals.clone(),
                         self.db.clone(),
+                        self.config.clone(),
                         execution_process.id,
                     )
                 }
                 _ => Arc::new(NoopExecutorApprovalService {}),
             };
 
-        // Create the child and stream, add to execution tracker
-        let mut spawned = executor_action
-            .spawn(&current_dir, approvals_service)
+        let limits = self.resource_limits(&execution_process.run_reason).await;
+        let mut sandbox = self
+            .project_sandbox(task_attempt, &current_dir, &limits)
             .await?;
+        let mut egress_proxy = None;
+        if let Some(config) = sandbox.as_ref().map(|sandbox| sandbox.config())
+            && config.egress == EgressMode::Allowlist
+        {
+            let (proxy, denied_rx) = EgressProxy::start(config.allowed_hosts.clone())
+                .await
+                .map_err(|e| ContainerError::Other(anyhow!("Failed to start egress proxy: {e}")))?;
+            sandbox = sandbox.map(|sandbox| sandbox.with_proxy(proxy.url()));
+            egress_proxy = Some((proxy, denied_rx));
+        }
+
+        let env = self.project_env_vars(task_attempt).await?;
+
+        // Create the child and stream, add to execution tracker
+        let spawn = env_vars::scope(env, executor_action.spawn(&current_dir, approvals_service));
+        let mut spawned = match sandbox {
+            Some(sandbox) => sandbox.scope(spawn).await?,
+            None => spawn.await?,
+        };
+
+        if let Some(nice) = limits.nice
+            && let Err(e) = command::set_process_group_priority(&spawned.child, nice)
+        {
+            tracing::warn!(
+                "Failed to set priority of execution process {}: {}",
+                execution_process.id,
+                e
+            );
+        }
+
+        // Kept so the process can be found if the server dies without stopping it
+        if let Some(pid) = spawned.child.id()
+            && let Err(e) =
+                ExecutionProcess::update_pid(&self.db.pool, execution_process.id, pid).await
+        {
+            tracing::warn!(
+                "Failed to record pid of execution process {}: {}",
+                execution_process.id,
+                e
+            );
+        }
 
         self.track_child_msgs_in_store(execution_process.id, &mut spawned.child)
             .await;
 
+        if execution_process.run_reason == ExecutionProcessRunReason::CodingAgent
+            && let Some(store) = self.msg_stores.read().await.get(&execution_process.id)
+        {
+            self.spawn_stall_monitor(execution_process.id, store.get_receiver());
+        }
+
+        if (limits.timeout_secs.is_some() || limits.memory_max_mb.is_some())
+            && let Some(store) = self.msg_stores.read().await.get(&execution_process.id)
+        {
+            self.spawn_resource_watchdog(execution_process.id, limits, store.get_receiver());
+        }
+
+        if let Some((proxy, denied_rx)) = egress_proxy
+            && let Some(store) = self.msg_stores.read().await.get(&execution_process.id)
+        {
+            Self::spawn_egress_monitor(execution_process.id, proxy, denied_rx, store.clone());
+        }
+
         self.add_child_to_store(execution_process.id, spawned.child)
             .await;
 
@@ -841,34 +1692,42 @@ impl ContainerService for LocalContainerService {
         execution_process: &ExecutionProcess,
         status: ExecutionProcessStatus,
     ) -> Result<(), ContainerError> {
-        let child = self
-            .get_child_from_store(&execution_process.id)
-            .await
-            .ok_or_else(|| {
-                ContainerError::Other(anyhow!("Child process not found for execution"))
-            })?;
-        let exit_code = if status == ExecutionProcessStatus::Completed {
-            Some(0)
-        } else {
-            None
-        };
+        if let Some(child) = self.get_child_from_store(&execution_process.id).await {
+            let exit_code = if status == ExecutionProcessStatus::Completed {
+                Some(0)
+            } else {
+                None
+            };
 
-        ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, exit_code)
+            ExecutionProcess::update_completion(
+                &self.db.pool,
+                execution_process.id,
+                status,
+                exit_code,
+            )
             .await?;
 
-        // Kill the child process and remove from the store
-        {
-            let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
-                tracing::error!(
-                    "Failed to stop execution process {}: {}",
-                    execution_process.id,
-                    e
-                );
-                return Err(e);
+            // Kill the child process and remove from the store
+            {
+                let mut child_guard = child.write().await;
+                if let Err(e) = command::kill_process_group(&mut child_guard).await {
+                    tracing::error!(
+                        "Failed to stop execution process {}: {}",
+                        execution_process.id,
+                        e
+                    );
+                    return Err(e);
+                }
             }
+            self.remove_child_from_store(&execution_process.id).await;
+        } else if !ExecutionProcess::cancel_queued(&self.db.pool, execution_process.id, status)
+            .await?
+        {
+            // Queued processes have no child yet; anything else should have one
+            return Err(ContainerError::Other(anyhow!(
+                "Child process not found for execution"
+            )));
         }
-        self.remove_child_from_store(&execution_process.id).await;
 
         // Mark the process finished in the MsgStore
         if let Some(msg) = self.msg_stores.write().await.remove(&execution_process.id) {
@@ -908,6 +1767,104 @@ impl ContainerService for LocalContainerService {
         Ok(())
     }
 
+    async fn process_tree(
+        &self,
+        execution_process: &ExecutionProcess,
+    ) -> Result<Vec<ProcessTreeNode>, ContainerError> {
+        let Some(child) = self.get_child_from_store(&execution_process.id).await else {
+            return Ok(Vec::new());
+        };
+        let Some(pid) = child.read().await.id() else {
+            return Ok(Vec::new());
+        };
+        let entries = process_tree::snapshot().await.unwrap_or_default();
+        Ok(process_tree::build_tree(&entries, pid))
+    }
+
+    async fn kill_child_process(
+        &self,
+        execution_process: &ExecutionProcess,
+        pid: u32,
+    ) -> Result<bool, ContainerError> {
+        let Some(child) = self.get_child_from_store(&execution_process.id).await else {
+            return Ok(false);
+        };
+        let Some(root_pid) = child.read().await.id() else {
+            return Ok(false);
+        };
+        // Killing the spawned process itself ends the run; that is what stop is for
+        let entries = process_tree::snapshot().await.unwrap_or_default();
+        if pid == root_pid || !process_tree::members(&entries, root_pid).contains(&pid) {
+            return Ok(false);
+        }
+
+        let command_line = entries
+            .iter()
+            .find(|entry| entry.pid == pid)
+            .map(|entry| entry.command.clone())
+            .unwrap_or_default();
+        tracing::info!(
+            "Killing process {} ({}) of execution process {}",
+            pid,
+            command_line,
+            execution_process.id
+        );
+        if let Some(store) = self.msg_stores.read().await.get(&execution_process.id) {
+            store.push_stderr(format!("Killed process {pid}: {command_line}\n"));
+        }
+        command::kill_processes(&process_tree::descendants(&entries, pid)).await;
+        Ok(true)
+    }
+
+    async fn kill_orphaned_process(
+        &self,
+        execution_process: &ExecutionProcess,
+        pid: u32,
+    ) -> Result<bool, ContainerError> {
+        let Some(entries) = process_tree::snapshot().await else {
+            return Ok(false);
+        };
+        let age_secs = (Utc::now() - execution_process.started_at)
+            .num_seconds()
+            .max(0) as u64;
+        let orphans = process_tree::orphaned_members(&entries, pid, age_secs);
+        if orphans.is_empty() {
+            return Ok(false);
+        }
+        tracing::info!(
+            "Killing {} processes left running by execution process {}",
+            orphans.len(),
+            execution_process.id
+        );
+        command::kill_processes(&orphans).await;
+        Ok(true)
+    }
+
+    async fn open_terminal(
+        &self,
+        task_attempt: &TaskAttempt,
+        size: TerminalSize,
+    ) -> Result<Terminal, ContainerError> {
+        let worktree_path = PathBuf::from(self.ensure_container_exists(task_attempt).await?);
+        let shell = terminal::default_shell();
+        let pty = pty::spawn(&shell, &worktree_path, size)?;
+        let session =
+            TerminalSession::create(&self.db.pool, Uuid::new_v4(), task_attempt.id, &shell).await?;
+        let cancel = self.terminals.register(task_attempt.id, session.id);
+
+        let (input_tx, input_rx) = mpsc::channel(64);
+        let (output_tx, output_rx) = mpsc::channel(64);
+        tokio::spawn(
+            self.clone()
+                .run_terminal(session.clone(), pty, input_rx, output_tx, cancel),
+        );
+        Ok(Terminal {
+            session,
+            input: input_tx,
+            output: output_rx,
+        })
+    }
+
     async fn stream_diff(
         &self,
         task_attempt: &TaskAttempt,
@@ -915,23 +1872,9 @@ impl ContainerService for LocalContainerService {
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>
     {
         let project_repo_path = self.get_project_repo_path(task_attempt).await?;
-        let latest_merge =
-            Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
-
-        let is_ahead = if let Ok((ahead, _)) = self.git().get_branch_status(
-            &project_repo_path,
-            &task_attempt.branch,
-            &task_attempt.target_branch,
-        ) {
-            ahead > 0
-        } else {
-            false
-        };
-
-        if let Some(merge) = &latest_merge
-            && let Some(commit) = merge.merge_commit()
-            && self.is_container_clean(task_attempt).await?
-            && !is_ahead
+        if let Some(commit) = self
+            .merged_diff_commit(task_attempt, &project_repo_path)
+            .await?
         {
             let wrapper =
                 self.create_merged_diff_stream(&project_repo_path, &commit, stats_only)?;
@@ -952,6 +1895,57 @@ impl ContainerService for LocalContainerService {
         Ok(Box::pin(wrapper))
     }
 
+    async fn stream_commit_range_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        from_commit: &Commit,
+        to_commit: &Commit,
+        stats_only: bool,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>
+    {
+        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
+        let wrapper = self.create_static_diff_stream(
+            DiffTarget::Range {
+                repo_path: &project_repo_path,
+                from_commit,
+                to_commit,
+            },
+            stats_only,
+        )?;
+        Ok(Box::pin(wrapper))
+    }
+
+    async fn list_diff_files(
+        &self,
+        task_attempt: &TaskAttempt,
+        range: Option<(&Commit, &Commit)>,
+    ) -> Result<Vec<Diff>, ContainerError> {
+        let mut diffs = self
+            .get_attempt_diffs(task_attempt, range, None, DiffContentMode::StatsOnly)
+            .await?;
+        diffs.sort_by_key(GitService::diff_path);
+        Ok(diffs)
+    }
+
+    async fn get_diff_file(
+        &self,
+        task_attempt: &TaskAttempt,
+        range: Option<(&Commit, &Commit)>,
+        path: &str,
+    ) -> Result<Option<Diff>, ContainerError> {
+        let diffs = self
+            .get_attempt_diffs(task_attempt, range, Some(&[path]), DiffContentMode::Full)
+            .await?;
+        Ok(diffs.into_iter().find(|diff| {
+            diff.new_path.as_deref() == Some(path) || diff.old_path.as_deref() == Some(path)
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "git.commit",
+        skip_all,
+        fields(execution_process_id = %ctx.execution_process.id)
+    )]
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
         if !matches!(
             ctx.execution_process.run_reason,
@@ -959,7 +1953,16 @@ impl ContainerService for LocalContainerService {
         ) {
             return Ok(false);
         }
+        // Diagnostics collected after a failed cleanup script are not part of the work
+        if let Ok(action) = ctx.execution_process.executor_action()
+            && let ExecutorActionType::ScriptRequest(request) = action.typ()
+            && request.context == ScriptContext::DiagnosticScript
+        {
+            return Ok(false);
+        }
 
+        let locale = Self::message_locale(&self.db, &self.config, ctx.task.project_id).await;
+        let attempt_id = ctx.task_attempt.id.to_string();
         let message = match ctx.execution_process.run_reason {
             ExecutionProcessRunReason::CodingAgent => {
                 // Try to retrieve the task summary from the executor session
@@ -976,10 +1979,10 @@ impl ContainerService for LocalContainerService {
                             "No summary found for execution process {}, using default message",
                             ctx.execution_process.id
                         );
-                        format!(
-                            "Commit changes from coding agent for task attempt {}",
-                            ctx.task_attempt.id
-                        )
+                        Message::AgentCommit {
+                            attempt_id: &attempt_id,
+                        }
+                        .localize(locale)
                     }
                     Err(e) => {
                         tracing::debug!(
@@ -987,19 +1990,17 @@ impl ContainerService for LocalContainerService {
                             ctx.execution_process.id,
                             e
                         );
-                        format!(
-                            "Commit changes from coding agent for task attempt {}",
-                            ctx.task_attempt.id
-                        )
+                        Message::AgentCommit {
+                            attempt_id: &attempt_id,
+                        }
+                        .localize(locale)
                     }
                 }
             }
-            ExecutionProcessRunReason::CleanupScript => {
-                format!(
-                    "Cleanup script changes for task attempt {}",
-                    ctx.task_attempt.id
-                )
+            ExecutionProcessRunReason::CleanupScript => Message::CleanupScriptCommit {
+                attempt_id: &attempt_id,
             }
+            .localize(locale),
             _ => Err(ContainerError::Other(anyhow::anyhow!(
                 "Invalid run reason for commit"
             )))?,
@@ -1116,12 +2117,47 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Store the wall time, exit code, output size and token usage of a finished process
+    async fn record_metrics(&self, ctx: &ExecutionContext) -> Result<(), anyhow::Error> {
+        let history = match self.get_msg_store_by_id(&ctx.execution_process.id).await {
+            Some(store) => store.get_history(),
+            None => Vec::new(),
+        };
+        let metrics = execution_metrics::measure(&ctx.execution_process, &history);
+        ExecutionProcessMetrics::upsert(&self.db.pool, ctx.execution_process.id, &metrics).await?;
+        Ok(())
+    }
+
+    /// Store the size of the attempt's changes, so task and attempt listings can show it
+    /// without reading the worktree
+    async fn record_diff_stats(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
+        if ctx.task_attempt.worktree_deleted
+            || ctx.execution_process.run_reason == ExecutionProcessRunReason::DevServer
+        {
+            return Ok(());
+        }
+        let diffs = self.list_diff_files(&ctx.task_attempt, None).await?;
+        let stats = DiffStats::from_diffs(&diffs);
+        let stats = AttemptDiffStats {
+            files_changed: stats.files_changed.into(),
+            additions: stats.additions.into(),
+            deletions: stats.deletions.into(),
+        };
+        TaskAttemptDiffStats::upsert(&self.db.pool, ctx.task_attempt.id, &stats).await?;
+        Ok(())
+    }
+
     /// If a queued follow-up draft exists for this attempt and nothing is running,
-    /// start it immediately and clear the draft.
+    /// start it immediately and clear the draft. During the project's quiet hours the draft
+    /// stays queued and is picked up by the queued follow-up dispatcher later.
     async fn try_consume_queued_followup(
         &self,
         ctx: &ExecutionContext,
     ) -> Result<(), ContainerError> {
+        // Held until the server is back, like the queued coding agents
+        if shutdown::in_progress() {
+            return Ok(());
+        }
         // Only consider CodingAgent/cleanup chains; skip DevServer completions
         if matches!(
             ctx.execution_process.run_reason,
@@ -1130,21 +2166,250 @@ impl LocalContainerService {
             return Ok(());
         }
 
+        let Some(project) = ctx.task.parent_project(&self.db.pool).await? else {
+            return Ok(());
+        };
+        if quiet_hours::is_quiet(&project, Utc::now()) {
+            tracing::debug!(
+                "Holding queued follow-up for attempt {} during quiet hours",
+                ctx.task_attempt.id
+            );
+            return Ok(());
+        }
+
+        self.consume_queued_followup(&ctx.task_attempt, &ctx.task, &project)
+            .await
+    }
+
+    /// Start follow-ups that were held back by quiet hours once their project's window ends
+    pub async fn spawn_queued_followup_dispatch(&self) {
+        let container = self.clone();
+        let mut dispatch_interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        tokio::spawn(async move {
+            loop {
+                dispatch_interval.tick().await;
+                container
+                    .dispatch_held_followups()
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to dispatch queued follow-ups: {}", e)
+                    });
+            }
+        });
+    }
+
+    async fn dispatch_held_followups(&self) -> Result<(), ContainerError> {
+        let now = Utc::now();
+        for attempt_id in Draft::find_held_follow_up_attempt_ids(&self.db.pool).await? {
+            let Some(task_attempt) = TaskAttempt::find_by_id(&self.db.pool, attempt_id).await?
+            else {
+                continue;
+            };
+            let Some(task) = task_attempt.parent_task(&self.db.pool).await? else {
+                continue;
+            };
+            let Some(project) = task.parent_project(&self.db.pool).await? else {
+                continue;
+            };
+            if quiet_hours::is_quiet(&project, now) {
+                continue;
+            }
+            if let Err(e) = self
+                .consume_queued_followup(&task_attempt, &task, &project)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to start queued follow-up for attempt {}: {}",
+                    attempt_id,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the post-merge script of every attempt the merge queue lands
+    pub async fn spawn_post_merge_dispatch(&self) {
+        let container = self.clone();
+        let mut landed = MergeQueueService::subscribe_landed();
+        tokio::spawn(async move {
+            loop {
+                match landed.recv().await {
+                    Ok(attempt_id) => {
+                        if let Err(e) = container.start_landed_post_merge_script(attempt_id).await {
+                            tracing::error!(
+                                "Failed to start post-merge script for attempt {}: {}",
+                                attempt_id,
+                                e
+                            );
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Missed {} merged attempts' post-merge scripts", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    async fn start_landed_post_merge_script(&self, attempt_id: Uuid) -> Result<(), ContainerError> {
+        let Some(task_attempt) = TaskAttempt::find_by_id(&self.db.pool, attempt_id).await? else {
+            return Ok(());
+        };
+        let Some(task) = task_attempt.parent_task(&self.db.pool).await? else {
+            return Ok(());
+        };
+        let Some(project) = task.parent_project(&self.db.pool).await? else {
+            return Ok(());
+        };
+        self.start_post_merge_script(&task_attempt, &project)
+            .await?;
+        Ok(())
+    }
+
+    /// Sample the CPU and memory use of running executions for the resource monitor
+    pub async fn spawn_resource_sampler(&self) {
+        let container = self.clone();
+        let mut sample_interval = tokio::time::interval(RESOURCE_SAMPLE_INTERVAL);
+        tokio::spawn(async move {
+            loop {
+                sample_interval.tick().await;
+                container.sample_resource_usage().await;
+            }
+        });
+    }
+
+    async fn sample_resource_usage(&self) {
+        let children: Vec<_> = self
+            .child_store
+            .read()
+            .await
+            .iter()
+            .map(|(id, child)| (*id, child.clone()))
+            .collect();
+        let mut roots = Vec::with_capacity(children.len());
+        for (id, child) in children {
+            if let Some(pid) = child.read().await.id() {
+                roots.push((id, pid));
+            }
+        }
+        // Skip running `ps` while the machine is idle
+        if roots.is_empty() {
+            self.resource_monitor.clear().await;
+            return;
+        }
+        let Some(entries) = process_tree::snapshot().await else {
+            return;
+        };
+        self.resource_monitor.record(&entries, &roots).await;
+    }
+
+    /// Start task attempts for schedules whose next run has come
+    pub async fn spawn_schedule_dispatch(&self) {
+        let container = self.clone();
+        let mut dispatch_interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        tokio::spawn(async move {
+            loop {
+                dispatch_interval.tick().await;
+                container
+                    .dispatch_due_schedules()
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to dispatch scheduled runs: {}", e)
+                    });
+            }
+        });
+    }
+
+    async fn dispatch_due_schedules(&self) -> Result<(), ContainerError> {
+        let now = Utc::now();
+        for schedule in TaskSchedule::find_due(&self.db.pool, now).await? {
+            let Some(project) = Project::find_by_id(&self.db.pool, schedule.project_id).await?
+            else {
+                continue;
+            };
+            // Due runs wait for quiet hours to end; missed runs start once, not once per miss
+            if quiet_hours::is_quiet(&project, now) {
+                continue;
+            }
+            let next_run_at =
+                task_schedule::next_run_for(&schedule, project.timezone.as_deref(), now);
+
+            if let Some(attempt_id) = schedule.last_task_attempt_id
+                && let Some(previous) = TaskAttempt::find_by_id(&self.db.pool, attempt_id).await?
+                && self.has_running_processes(previous.task_id).await?
+            {
+                tracing::info!(
+                    "Skipping run of schedule {} while its previous run is in progress",
+                    schedule.id
+                );
+                TaskSchedule::record_run(
+                    &self.db.pool,
+                    schedule.id,
+                    next_run_at,
+                    None,
+                    Some("Skipped because the previous run was still in progress"),
+                )
+                .await?;
+                continue;
+            }
+
+            match self.start_scheduled_run(&schedule).await {
+                Ok(task_attempt) => {
+                    tracing::info!(
+                        "Schedule {} started attempt {}",
+                        schedule.id,
+                        task_attempt.id
+                    );
+                    TaskSchedule::record_run(
+                        &self.db.pool,
+                        schedule.id,
+                        next_run_at,
+                        Some(task_attempt.id),
+                        None,
+                    )
+                    .await?;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to start run of schedule {}: {}", schedule.id, e);
+                    TaskSchedule::record_run(
+                        &self.db.pool,
+                        schedule.id,
+                        next_run_at,
+                        None,
+                        Some(&e.to_string()),
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn consume_queued_followup(
+        &self,
+        task_attempt: &TaskAttempt,
+        task: &Task,
+        project: &Project,
+    ) -> Result<(), ContainerError> {
         // If anything is running for this attempt, bail
         let procs =
-            ExecutionProcess::find_by_task_attempt_id(&self.db.pool, ctx.task_attempt.id, false)
+            ExecutionProcess::find_by_task_attempt_id(&self.db.pool, task_attempt.id, false)
                 .await?;
-        if procs
-            .iter()
-            .any(|p| matches!(p.status, ExecutionProcessStatus::Running))
-        {
+        if procs.iter().any(|p| {
+            matches!(
+                p.status,
+                ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+            )
+        }) {
             return Ok(());
         }
 
         // Load draft and ensure it's eligible
         let Some(draft) = Draft::find_by_task_attempt_and_type(
             &self.db.pool,
-            ctx.task_attempt.id,
+            task_attempt.id,
             DraftType::FollowUp,
         )
         .await?
@@ -1157,7 +2422,7 @@ impl LocalContainerService {
         }
 
         // Atomically acquire sending lock; if not acquired, someone else is sending.
-        if !Draft::try_mark_sending(&self.db.pool, ctx.task_attempt.id, DraftType::FollowUp)
+        if !Draft::try_mark_sending(&self.db.pool, task_attempt.id, DraftType::FollowUp)
             .await
             .unwrap_or(false)
         {
@@ -1165,18 +2430,18 @@ impl LocalContainerService {
         }
 
         // Ensure worktree exists
-        let container_ref = self.ensure_container_exists(&ctx.task_attempt).await?;
+        let container_ref = self.ensure_container_exists(task_attempt).await?;
 
         // Get session id
         let Some(session_id) = ExecutionProcess::find_latest_session_id_by_task_attempt(
             &self.db.pool,
-            ctx.task_attempt.id,
+            task_attempt.id,
         )
         .await?
         else {
             tracing::warn!(
                 "No session id found for attempt {}. Cannot start queued follow-up.",
-                ctx.task_attempt.id
+                task_attempt.id
             );
             return Ok(());
         };
@@ -1184,26 +2449,25 @@ impl LocalContainerService {
         // Get last coding agent process to inherit executor profile
         let Some(latest) = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
             &self.db.pool,
-            ctx.task_attempt.id,
+            task_attempt.id,
             &ExecutionProcessRunReason::CodingAgent,
         )
         .await?
         else {
             tracing::warn!(
                 "No prior CodingAgent process for attempt {}. Cannot start queued follow-up.",
-                ctx.task_attempt.id
+                task_attempt.id
             );
             return Ok(());
         };
 
-        use executors::actions::ExecutorActionType;
         let initial_executor_profile_id = match &latest.executor_action()?.typ {
             ExecutorActionType::CodingAgentInitialRequest(req) => req.executor_profile_id.clone(),
             ExecutorActionType::CodingAgentFollowUpRequest(req) => req.executor_profile_id.clone(),
             _ => {
                 tracing::warn!(
                     "Latest process for attempt {} is not a coding agent; skipping queued follow-up",
-                    ctx.task_attempt.id
+                    task_attempt.id
                 );
                 return Ok(());
             }
@@ -1215,28 +2479,29 @@ impl LocalContainerService {
         };
 
         // Prepare cleanup action
-        let cleanup_action = ctx
-            .task
-            .parent_project(&self.db.pool)
-            .await?
-            .and_then(|project| self.cleanup_action(project.cleanup_script));
+        let cleanup_action = self.cleanup_action(project);
 
-        // Handle images: associate, copy to worktree, canonicalize prompt
-        let mut prompt = draft.prompt.clone();
+        // Handle images: associate, place with captions, copy to worktree, canonicalize prompt
+        let mut prompt =
+            prompt_snippets::expand_for_project(&self.db.pool, project.id, &draft.prompt).await?;
         if let Some(image_ids) = &draft.image_ids {
             // Associate to task
-            let _ = TaskImage::associate_many_dedup(&self.db.pool, ctx.task.id, image_ids).await;
+            let _ = TaskImage::associate_many_dedup(&self.db.pool, task.id, image_ids).await;
 
-            // Copy to worktree and canonicalize
+            // Place, copy to worktree and canonicalize
             let worktree_path = std::path::PathBuf::from(&container_ref);
-            if let Err(e) = self
+            match self
                 .image_service
-                .copy_images_by_ids_to_worktree(&worktree_path, image_ids)
+                .prepare_prompt_images(
+                    &prompt,
+                    image_ids,
+                    &draft.image_captions.clone().unwrap_or_default(),
+                    &worktree_path,
+                )
                 .await
             {
-                tracing::warn!("Failed to copy images to worktree: {}", e);
-            } else {
-                prompt = ImageService::canonicalise_image_paths(&prompt, &worktree_path);
+                Ok(prepared) => prompt = prepared,
+                Err(e) => tracing::warn!("Failed to prepare images for queued follow-up: {}", e),
             }
         }
 
@@ -1255,15 +2520,14 @@ impl LocalContainerService {
         // Start the execution
         let _ = self
             .start_execution(
-                &ctx.task_attempt,
+                task_attempt,
                 &follow_up_action,
                 &ExecutionProcessRunReason::CodingAgent,
             )
             .await?;
 
         // Clear the draft to reflect that it has been consumed
-        let _ =
-            Draft::clear_after_send(&self.db.pool, ctx.task_attempt.id, DraftType::FollowUp).await;
+        let _ = Draft::clear_after_send(&self.db.pool, task_attempt.id, DraftType::FollowUp).await;
 
         Ok(())
     }
@@ -1,14 +1,14 @@
 use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
-use db::DBService;
+use db::{DBService, models::config_override::ConfigOverride};
 use deployment::{Deployment, DeploymentError};
 use executors::profile::ExecutorConfigs;
 use services::services::{
     analytics::{AnalyticsConfig, AnalyticsContext, AnalyticsService, generate_user_id},
     approvals::Approvals,
     auth::AuthService,
-    config::{Config, load_config_from_file, save_config_to_file},
+    config::{Config, layers::ConfigLayers, load_config_from_file, save_config_to_file},
     container::ContainerService,
     drafts::DraftsService,
     events::EventService,
@@ -16,6 +16,10 @@ use services::services::{
     filesystem::FilesystemService,
     git::GitService,
     image::ImageService,
+    rate_limit::RateLimitService,
+    storage::{self, StorageRoots},
+    telemetry::TelemetryService,
+    worktree_manager::WorktreeManager,
 };
 use tokio::sync::RwLock;
 use utils::{assets::config_path, msg_store::MsgStore};
@@ -24,10 +28,13 @@ use uuid::Uuid;
 use crate::container::LocalContainerService;
 mod command;
 pub mod container;
+mod pty;
+mod stall_monitor;
 
 #[derive(Clone)]
 pub struct LocalDeployment {
     config: Arc<RwLock<Config>>,
+    config_layers: Arc<RwLock<ConfigLayers>>,
     user_id: String,
     db: DBService,
     analytics: Option<AnalyticsService>,
@@ -41,6 +48,8 @@ pub struct LocalDeployment {
     file_search_cache: Arc<FileSearchCache>,
     approvals: Approvals,
     drafts: DraftsService,
+    telemetry: TelemetryService,
+    rate_limits: RateLimitService,
 }
 
 #[async_trait]
@@ -67,8 +76,17 @@ impl Deployment for LocalDeployment {
             }
         }
 
+        // Move data requested through the storage API before anything opens it
+        let worktree_move = storage::apply_relocation(&mut raw_config.storage);
+
         // Always save config (may have been migrated or version updated)
-        save_config_to_file(&raw_config, &config_path()).await?;
+        let config_layers = Arc::new(RwLock::new(ConfigLayers::default()));
+        save_config_to_file(&raw_config, &config_layers, &config_path()).await?;
+
+        let storage_roots = StorageRoots::resolve(&raw_config.storage);
+        WorktreeManager::set_worktree_base_dir(storage_roots.worktrees.clone());
+        std::fs::create_dir_all(&storage_roots.database)?;
+        let database_path = storage_roots.database_file();
 
         let config = Arc::new(RwLock::new(raw_config));
         let user_id = generate_user_id();
@@ -87,12 +105,31 @@ impl Deployment for LocalDeployment {
             let hook = EventService::create_hook(
                 events_msg_store.clone(),
                 events_entry_count.clone(),
-                DBService::new().await?, // Temporary DB service for the hook
+                DBService::new(&database_path).await?, // Temporary DB service for the hook
+                config.clone(),
             );
-            DBService::new_with_after_connect(hook).await?
+            DBService::new_with_after_connect(&database_path, hook).await?
         };
 
-        let image = ImageService::new(db.clone().pool)?;
+        if let Some(worktree_move) = worktree_move
+            && let Err(e) = storage::update_worktree_refs(&db.pool, &worktree_move).await
+        {
+            tracing::error!("Failed to update task attempts for moved worktrees: {}", e);
+        }
+
+        // Environment variables and runtime overrides apply over the file from here on
+        let overrides = ConfigOverride::find_all(&db.pool).await?;
+        let layers = ConfigLayers::load(
+            &config.read().await,
+            overrides
+                .into_iter()
+                .map(|config_override| (config_override.path, config_override.value.0)),
+        )?;
+        *config.write().await = layers.merge()?;
+        *config_layers.write().await = layers;
+        git.set_commit_signing(&config.read().await.commit_signing);
+
+        let image = ImageService::new(db.clone().pool, storage_roots.images)?;
         {
             let image_service = image.clone();
             tokio::spawn(async move {
@@ -121,13 +158,26 @@ impl Deployment for LocalDeployment {
             approvals.clone(),
         );
         container.spawn_worktree_cleanup().await;
+        container.spawn_queued_followup_dispatch().await;
+        container.spawn_schedule_dispatch().await;
+        container.spawn_post_merge_dispatch().await;
+        container.spawn_resource_sampler().await;
 
-        let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
+        let events = EventService::new(
+            db.clone(),
+            events_msg_store,
+            events_entry_count,
+            config.clone(),
+        );
         let drafts = DraftsService::new(db.clone(), image.clone());
         let file_search_cache = Arc::new(FileSearchCache::new());
+        let telemetry = TelemetryService::new(config.clone());
+        telemetry.spawn_forwarder();
+        let rate_limits = RateLimitService::new(config.clone());
 
         Ok(Self {
             config,
+            config_layers,
             user_id,
             db,
             analytics,
@@ -141,6 +191,8 @@ impl Deployment for LocalDeployment {
             file_search_cache,
             approvals,
             drafts,
+            telemetry,
+            rate_limits,
         })
     }
 
@@ -156,6 +208,10 @@ impl Deployment for LocalDeployment {
         &self.config
     }
 
+    fn config_layers(&self) -> &Arc<RwLock<ConfigLayers>> {
+        &self.config_layers
+    }
+
     fn db(&self) -> &DBService {
         &self.db
     }
@@ -202,4 +258,12 @@ impl Deployment for LocalDeployment {
     fn drafts(&self) -> &DraftsService {
         &self.drafts
     }
+
+    fn telemetry(&self) -> &TelemetryService {
+        &self.telemetry
+    }
+
+    fn rate_limits(&self) -> &RateLimitService {
+        &self.rate_limits
+    }
 }
@@ -0,0 +1,111 @@
+//! Shells on pseudo-terminals, for interactive terminals in a worktree. Not supported on
+//! Windows.
+
+use std::{io, path::Path};
+
+use services::services::terminal::TerminalSize;
+use tokio::process::Child;
+
+/// A shell running on a pseudo-terminal
+pub struct Pty {
+    pub child: Child,
+    /// Master side of the terminal, read on a blocking thread for the shell's output
+    pub reader: std::fs::File,
+    /// Master side of the terminal, for the shell's input
+    pub writer: tokio::fs::File,
+}
+
+#[cfg(unix)]
+pub fn spawn(shell: &str, cwd: &Path, size: TerminalSize) -> io::Result<Pty> {
+    use std::process::Stdio;
+
+    let pty = nix::pty::openpty(Some(&winsize(size)), None)?;
+    let slave = std::fs::File::from(pty.slave);
+    let mut command = tokio::process::Command::new(shell);
+    command
+        .current_dir(cwd)
+        .env("TERM", "xterm-256color")
+        .stdin(Stdio::from(slave.try_clone()?))
+        .stdout(Stdio::from(slave.try_clone()?))
+        .stderr(Stdio::from(slave))
+        .kill_on_drop(true);
+    // SAFETY: setsid and ioctl are async-signal-safe, as required between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            // Lead a new session, so the terminal becomes the shell's controlling terminal
+            if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let child = command.spawn()?;
+
+    let master = std::fs::File::from(pty.master);
+    Ok(Pty {
+        child,
+        reader: master.try_clone()?,
+        writer: tokio::fs::File::from_std(master),
+    })
+}
+
+#[cfg(not(unix))]
+pub fn spawn(_shell: &str, _cwd: &Path, _size: TerminalSize) -> io::Result<Pty> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Terminals are not supported on this platform",
+    ))
+}
+
+pub fn resize(writer: &tokio::fs::File, size: TerminalSize) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: TIOCSWINSZ only reads the winsize it is given
+        if unsafe { libc::ioctl(writer.as_raw_fd(), libc::TIOCSWINSZ as _, &winsize(size)) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (writer, size);
+    }
+    Ok(())
+}
+
+/// End the shell like closing its terminal window would: hang up, then kill it if it's
+/// still running after a grace period
+pub async fn hang_up(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        use nix::{
+            sys::signal::{Signal, killpg},
+            unistd::Pid,
+        };
+
+        if let Some(pid) = child.id() {
+            // The shell leads its own process group; it passes the hangup on to its jobs
+            if let Err(e) = killpg(Pid::from_raw(pid as i32), Signal::SIGHUP) {
+                tracing::debug!("Failed to hang up terminal shell {}: {}", pid, e);
+            }
+            if tokio::time::timeout(std::time::Duration::from_secs(2), child.wait())
+                .await
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+    let _ = child.kill().await;
+}
+
+#[cfg(unix)]
+fn winsize(size: TerminalSize) -> libc::winsize {
+    libc::winsize {
+        ws_row: size.rows,
+        ws_col: size.cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
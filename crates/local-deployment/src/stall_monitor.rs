@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::Instant,
+};
+use utils::log_msg::LogMsg;
+
+/// A change in whether a coding agent is stalled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallEvent {
+    /// No output for the whole timeout while no approval was pending
+    Stalled,
+    /// Output arrived again after a stall
+    Resumed,
+}
+
+/// Tracks how long a coding agent has gone without output. Time spent waiting on a tool
+/// approval doesn't count, and neither does the warning line pushed when it stalls.
+pub struct StallMonitor {
+    timeout: Duration,
+    warning: String,
+    last_output: Instant,
+    stalled: bool,
+}
+
+impl StallMonitor {
+    pub fn new(timeout_secs: u32) -> Self {
+        Self {
+            timeout: Duration::from_secs(timeout_secs.into()),
+            warning: format!(
+                "No output from the coding agent for {timeout_secs}s; it may be stalled"
+            ),
+            last_output: Instant::now(),
+            stalled: false,
+        }
+    }
+
+    /// The line to push to the agent's stderr when it stalls
+    pub fn warning(&self) -> &str {
+        &self.warning
+    }
+
+    /// Wait for the agent to stall or, once stalled, to resume. None when its logs end.
+    pub async fn next_event(
+        &mut self,
+        log_rx: &mut broadcast::Receiver<LogMsg>,
+        approval_pending: impl Fn() -> bool,
+    ) -> Option<StallEvent> {
+        loop {
+            tokio::select! {
+                msg = log_rx.recv() => match msg {
+                    Ok(LogMsg::Finished) | Err(RecvError::Closed) => return None,
+                    // Our own warning goes through stderr; it is not agent output
+                    Ok(LogMsg::Stderr(line)) if line.trim_end() == self.warning => {}
+                    Ok(LogMsg::Stdout(_) | LogMsg::Stderr(_)) | Err(RecvError::Lagged(_)) => {
+                        self.last_output = Instant::now();
+                        if self.stalled {
+                            self.stalled = false;
+                            return Some(StallEvent::Resumed);
+                        }
+                    }
+                    Ok(_) => {}
+                },
+                _ = tokio::time::sleep_until(self.last_output + self.timeout), if !self.stalled => {
+                    // Waiting on the user to approve a tool call is not a stall
+                    if approval_pending() {
+                        self.last_output = Instant::now();
+                        continue;
+                    }
+                    self.stalled = true;
+                    return Some(StallEvent::Stalled);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn monitor() -> (
+        StallMonitor,
+        broadcast::Sender<LogMsg>,
+        broadcast::Receiver<LogMsg>,
+    ) {
+        let (tx, rx) = broadcast::channel(16);
+        (StallMonitor::new(60), tx, rx)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalls_once_output_stops_for_the_timeout() {
+        let (mut monitor, tx, mut rx) = monitor();
+        let start = Instant::now();
+
+        tokio::time::advance(Duration::from_secs(20)).await;
+        tx.send(LogMsg::Stdout("working\n".to_string())).unwrap();
+        assert_eq!(
+            monitor.next_event(&mut rx, || false).await,
+            Some(StallEvent::Stalled)
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(20) + TIMEOUT);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_stall_while_an_approval_is_pending() {
+        let (mut monitor, _tx, mut rx) = monitor();
+
+        let waiting = tokio::time::timeout(TIMEOUT * 5 / 2, monitor.next_event(&mut rx, || true));
+        assert!(waiting.await.is_err());
+
+        // The timeout restarts from the last check that found the approval still pending
+        let start = Instant::now();
+        assert_eq!(
+            monitor.next_event(&mut rx, || false).await,
+            Some(StallEvent::Stalled)
+        );
+        assert_eq!(start.elapsed(), TIMEOUT / 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resumes_once_output_does() {
+        let (mut monitor, tx, mut rx) = monitor();
+        assert_eq!(
+            monitor.next_event(&mut rx, || false).await,
+            Some(StallEvent::Stalled)
+        );
+
+        tokio::time::advance(TIMEOUT * 3).await;
+        tx.send(LogMsg::Stderr("still here\n".to_string())).unwrap();
+        assert_eq!(
+            monitor.next_event(&mut rx, || false).await,
+            Some(StallEvent::Resumed)
+        );
+
+        let start = Instant::now();
+        assert_eq!(
+            monitor.next_event(&mut rx, || false).await,
+            Some(StallEvent::Stalled)
+        );
+        assert_eq!(start.elapsed(), TIMEOUT);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignores_its_own_warning() {
+        let (mut monitor, tx, mut rx) = monitor();
+        let warning = format!("{}\n", monitor.warning());
+        let start = Instant::now();
+
+        tokio::time::advance(TIMEOUT / 2).await;
+        tx.send(LogMsg::Stderr(warning.clone())).unwrap();
+        assert_eq!(
+            monitor.next_event(&mut rx, || false).await,
+            Some(StallEvent::Stalled)
+        );
+        assert_eq!(start.elapsed(), TIMEOUT);
+
+        // Once stalled, the warning it pushes doesn't count as the agent resuming
+        tx.send(LogMsg::Stderr(warning)).unwrap();
+        tx.send(LogMsg::Finished).unwrap();
+        assert_eq!(monitor.next_event(&mut rx, || false).await, None);
+    }
+}
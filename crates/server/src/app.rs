@@ -0,0 +1,226 @@
+//! Running vibe-kanban's backend inside another Rust program. [`App`] starts a deployment and
+//! either serves it on a listener of the caller's choosing or hands back its router, to be
+//! mounted in an existing axum app, e.g. under a path prefix with [`Router::nest`].
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::Router;
+use deployment::{Deployment, DeploymentError};
+use services::services::{container::ContainerService, public_url};
+use tokio::{net::TcpListener, sync::Notify};
+use tokio_rustls::rustls::ServerConfig;
+use utils::assets::asset_dir;
+
+use crate::{DeploymentImpl, routes, tls::TlsListener};
+
+/// How long connections still open after running executions were stopped for a shutdown, such
+/// as event streams, are given before the server stops anyway
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Changes the API routes before they are put behind the API's middleware
+pub type RouterHook = Box<dyn FnOnce(Router<DeploymentImpl>) -> Router<DeploymentImpl> + Send>;
+
+pub struct App {
+    deployment: DeploymentImpl,
+    headless: bool,
+    hooks: Vec<RouterHook>,
+}
+
+impl App {
+    /// Create the deployment from the usual asset directory and start its background services
+    pub async fn start() -> Result<Self, DeploymentError> {
+        if !asset_dir().exists() {
+            std::fs::create_dir_all(asset_dir())?;
+        }
+        let deployment = DeploymentImpl::new().await?;
+        deployment.update_sentry_scope().await?;
+        start_background_services(&deployment).await?;
+        Ok(Self::new(deployment))
+    }
+
+    /// Serve an existing deployment. Its background services are left to the caller, see
+    /// [`start_background_services`].
+    pub fn new(deployment: DeploymentImpl) -> Self {
+        Self {
+            deployment,
+            headless: false,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Serve only the API under `/api`, without the bundled frontend. The frontend expects to be
+    /// served from the root of its origin, so apps mounted under a path prefix should be headless.
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
+
+    /// Add routes to the API or replace some of them. Hooks run in the order they were added and
+    /// their routes get the same middleware and state as the built-in ones.
+    pub fn customize_api(
+        mut self,
+        hook: impl FnOnce(Router<DeploymentImpl>) -> Router<DeploymentImpl> + Send + 'static,
+    ) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    pub fn deployment(&self) -> &DeploymentImpl {
+        &self.deployment
+    }
+
+    /// The API under `/api`, with the frontend at every other path unless headless
+    pub fn into_router(self) -> Router {
+        let routes = self
+            .hooks
+            .into_iter()
+            .fold(routes::api_routes(&self.deployment), |routes, hook| {
+                hook(routes)
+            });
+        let api = routes::api_router(routes, self.deployment);
+        if self.headless {
+            Router::new().nest("/api", api)
+        } else {
+            routes::app_router(api)
+        }
+    }
+
+    /// Serve on `listener` over HTTPS when given a TLS config, until Ctrl+C or SIGTERM. Running
+    /// executions are then stopped before the server exits. Links the server builds for itself
+    /// point at the listener's port on the loopback address, and forwarding headers are only
+    /// believed from the configured `trusted_proxies`.
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        tls_config: Option<ServerConfig>,
+    ) -> std::io::Result<()> {
+        let scheme = if tls_config.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        let port = listener.local_addr()?.port();
+        public_url::set_local_origin(format!("{scheme}://127.0.0.1:{port}"));
+
+        let deployment = self.deployment.clone();
+        let router = self
+            .into_router()
+            .into_make_service_with_connect_info::<SocketAddr>();
+        let stopped = Arc::new(Notify::new());
+        let graceful = {
+            let stopped = stopped.clone();
+            async move {
+                shutdown_signal().await;
+                stop_executions(&deployment).await;
+                stopped.notify_one();
+            }
+        };
+        let server = async {
+            match tls_config {
+                Some(tls_config) => {
+                    axum::serve(TlsListener::new(listener, tls_config)?, router)
+                        .with_graceful_shutdown(graceful)
+                        .await
+                }
+                None => {
+                    axum::serve(listener, router)
+                        .with_graceful_shutdown(graceful)
+                        .await
+                }
+            }
+        };
+        tokio::select! {
+            result = server => result,
+            _ = async {
+                stopped.notified().await;
+                tokio::time::sleep(SHUTDOWN_GRACE).await;
+            } => {
+                tracing::info!("Closing connections still open after shutdown");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Resolves on Ctrl+C, or SIGTERM on Unix
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Stop the running executions so none is left running, or marked as running, once the server
+/// is gone. Call before exiting when serving the router yourself.
+pub async fn stop_executions(deployment: &DeploymentImpl) {
+    tracing::info!("Shutting down, stopping running executions");
+    match deployment.container().stop_for_shutdown().await {
+        Ok(summary) => tracing::info!(
+            "Stopped {} execution processes ({} failed)",
+            summary.stopped.len(),
+            summary.failed.len()
+        ),
+        Err(e) => tracing::error!("Failed to stop running executions: {}", e),
+    }
+}
+
+/// Recover from the last shutdown and start the services working in the background: queued
+/// executions, monitors, syncs, garbage collection and the rest
+pub async fn start_background_services(deployment: &DeploymentImpl) -> Result<(), DeploymentError> {
+    deployment.recover_executions().await?;
+    // Queued processes survive restarts; start as many as the concurrency limit allows
+    deployment
+        .container()
+        .start_queued_executions()
+        .await
+        .map_err(DeploymentError::from)?;
+    deployment.backfill_before_head_commits().await?;
+    deployment.spawn_log_search_backfill();
+    deployment.spawn_pr_monitor_service().await;
+    deployment.spawn_workflow_monitor_service().await;
+    deployment.spawn_github_issue_sync_service().await;
+    deployment.spawn_issue_tracker_sync_service().await;
+    deployment.spawn_webhook_delivery_service().await;
+    deployment.spawn_base_branch_sync_service().await;
+    deployment.spawn_merge_queue_service().await;
+    deployment.spawn_worktree_gc_service().await;
+    deployment.spawn_log_retention_service().await;
+    deployment.spawn_event_journal_service().await;
+    deployment
+        .track_if_analytics_allowed("session_start", serde_json::json!({}))
+        .await;
+
+    // Pre-warm file search cache for most active projects
+    let deployment_for_cache = deployment.clone();
+    tokio::spawn(async move {
+        if let Err(e) = deployment_for_cache
+            .file_search_cache()
+            .warm_most_active(&deployment_for_cache.db().pool, 3)
+            .await
+        {
+            tracing::warn!("Failed to warm file search cache: {}", e);
+        }
+    });
+    Ok(())
+}
@@ -15,8 +15,30 @@ fn generate_types_content() -> String {
         db::models::project::Project::decl(),
         db::models::project::CreateProject::decl(),
         db::models::project::UpdateProject::decl(),
+        db::models::project::QuietHours::decl(),
+        db::models::project::RetryPolicy::decl(),
+        db::models::project::SetupCacheConfig::decl(),
+        db::models::project::FailureAction::decl(),
+        db::models::project::CleanupFailureConfig::decl(),
+        db::models::project::TestReportFormat::decl(),
+        db::models::project::TestRunConfig::decl(),
+        db::models::project::ApprovalAction::decl(),
+        db::models::project::ApprovalMatcher::decl(),
+        db::models::project::ApprovalRule::decl(),
+        db::models::project::ApprovalPolicy::decl(),
+        services::services::attempt_report::ReportPeriod::decl(),
+        services::services::attempt_report::OutcomeStats::decl(),
+        services::services::attempt_report::PeriodStats::decl(),
+        services::services::attempt_report::AgentConfigurationReport::decl(),
+        services::services::attempt_report::AttemptReport::decl(),
+        services::services::repo_health::RepoHealthIssueKind::decl(),
+        services::services::repo_health::RepoHealthIssue::decl(),
+        services::services::repo_health::RepoHealthReport::decl(),
+        services::services::repo_health::RepoRepairReport::decl(),
         db::models::project::SearchResult::decl(),
         db::models::project::SearchMatchType::decl(),
+        executors::sandbox::EgressMode::decl(),
+        executors::sandbox::SandboxConfig::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
         executors::actions::ExecutorActionType::decl(),
@@ -25,16 +47,82 @@ fn generate_types_content() -> String {
         executors::actions::script::ScriptRequestLanguage::decl(),
         executors::executors::BaseCodingAgent::decl(),
         executors::executors::CodingAgent::decl(),
+        db::models::prompt_snippet::PromptSnippet::decl(),
+        db::models::prompt_snippet::CreatePromptSnippet::decl(),
+        db::models::prompt_snippet::UpdatePromptSnippet::decl(),
+        db::models::setup_cache_entry::SetupCacheEntry::decl(),
+        db::models::tag::TemplateVariableKind::decl(),
+        db::models::tag::TemplateVariable::decl(),
         db::models::tag::Tag::decl(),
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
         server::routes::tags::TagSearchParams::decl(),
+        server::routes::tags::CreateTaskFromTemplate::decl(),
+        db::models::task_schedule::TaskSchedule::decl(),
+        db::models::task_schedule::CreateTaskSchedule::decl(),
+        db::models::task_schedule::UpdateTaskSchedule::decl(),
+        db::models::automation_rule::AutomationTrigger::decl(),
+        db::models::automation_rule::AutomationCondition::decl(),
+        db::models::automation_rule::AutomationAction::decl(),
+        db::models::automation_rule::AutomationRule::decl(),
+        db::models::automation_rule::CreateAutomationRule::decl(),
+        db::models::automation_rule::UpdateAutomationRule::decl(),
+        db::models::automation_rule::AutomationRuleRunStatus::decl(),
+        db::models::automation_rule::AutomationRuleRun::decl(),
+        db::models::webhook::WebhookEvent::decl(),
+        db::models::webhook::Webhook::decl(),
+        db::models::webhook::CreateWebhook::decl(),
+        db::models::webhook::UpdateWebhook::decl(),
+        db::models::webhook::WebhookDeliveryStatus::decl(),
+        db::models::webhook::WebhookDelivery::decl(),
+        db::models::chat_channel::ChatChannelKind::decl(),
+        db::models::chat_channel::ChatNotificationEvent::decl(),
+        db::models::chat_channel::ChatMessageTemplates::decl(),
+        db::models::chat_channel::ChatChannel::decl(),
+        db::models::chat_channel::CreateChatChannel::decl(),
+        db::models::chat_channel::UpdateChatChannel::decl(),
+        db::models::approval_decision::ApprovalOutcome::decl(),
+        db::models::approval_decision::ApprovalDecider::decl(),
+        db::models::approval_decision::ApprovalDecision::decl(),
+        db::models::audit_log::AuditAction::decl(),
+        db::models::audit_log::AuditLogEntry::decl(),
+        services::services::notification_feed::NotificationSeverity::decl(),
+        services::services::notification_feed::NotificationKind::decl(),
+        services::services::notification_feed::AppNotification::decl(),
         db::models::task::TaskStatus::decl(),
+        db::models::task::TaskPriority::decl(),
         db::models::task::Task::decl(),
         db::models::task::TaskWithAttemptStatus::decl(),
+        db::models::task::SubtaskProgress::decl(),
+        db::models::task::TaskStaleness::decl(),
+        db::models::task::AttentionReason::decl(),
+        db::models::task::StalenessThresholds::decl(),
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
+        server::routes::tasks::dependencies::AddTaskDependencyRequest::decl(),
+        server::routes::tasks::subtasks::CreateSubtaskRequest::decl(),
+        db::models::project_column::ProjectColumn::decl(),
+        db::models::project_column::CreateProjectColumn::decl(),
+        db::models::project_column::UpdateProjectColumn::decl(),
+        server::routes::projects::columns::ReorderProjectColumnsRequest::decl(),
+        db::models::wip_limit::WipLimitEnforcement::decl(),
+        db::models::wip_limit::WipLimit::decl(),
+        db::models::wip_limit::UpsertWipLimit::decl(),
+        services::services::wip_limits::WipUsage::decl(),
+        db::models::task_comment::TaskComment::decl(),
+        db::models::task_comment::CreateTaskComment::decl(),
+        db::models::task_comment::UpdateTaskComment::decl(),
+        db::models::task_search::TaskSearchResult::decl(),
+        services::services::task_import::TaskImportFormat::decl(),
+        services::services::task_import::TaskImportMapping::decl(),
+        services::services::task_import::TaskImportRequest::decl(),
+        services::services::task_import::SkippedImportRow::decl(),
+        services::services::task_import::TaskImportSummary::decl(),
+        services::services::project_archive::ProjectImportSummary::decl(),
+        services::services::project_clone::CloneProjectRequest::decl(),
+        services::services::project_clone::ProjectCloneStatus::decl(),
+        services::services::project_clone::ProjectClone::decl(),
         db::models::image::Image::decl(),
         db::models::image::CreateImage::decl(),
         utils::response::ApiResponse::<()>::decl(),
@@ -49,8 +137,29 @@ fn generate_types_content() -> String {
         services::services::drafts::UpdateRetryFollowUpDraftRequest::decl(),
         server::routes::task_attempts::ChangeTargetBranchRequest::decl(),
         server::routes::task_attempts::ChangeTargetBranchResponse::decl(),
+        server::routes::task_attempts::DiffFilePage::decl(),
+        services::services::dependency_diff::DependencyChangeKind::decl(),
+        services::services::dependency_diff::DependencyChange::decl(),
+        services::services::dependency_diff::LockfileDependencyDiff::decl(),
+        server::routes::task_attempts::bulk_rerun::RerunMode::decl(),
+        server::routes::task_attempts::bulk_rerun::BulkRerunRequest::decl(),
+        server::routes::task_attempts::bulk_rerun::BulkRerunEntry::decl(),
+        server::routes::task_attempts::bulk_rerun::BulkRerunResponse::decl(),
+        db::models::attempt_comparison::AttemptComparison::decl(),
+        services::services::attempt_comparison::CheckResult::decl(),
+        services::services::attempt_comparison::DiffStats::decl(),
+        services::services::attempt_comparison::ComparedAttempt::decl(),
+        services::services::attempt_comparison::AttemptComparisonDetails::decl(),
+        server::routes::task_attempts::comparisons::StartAttemptComparison::decl(),
+        server::routes::task_attempts::comparisons::AttemptComparisonQuery::decl(),
+        server::routes::task_attempts::comparisons::ChooseComparedAttempt::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
+        server::routes::tasks::ReorderTasksRequest::decl(),
         server::routes::task_attempts::CreateGitHubPrRequest::decl(),
+        server::routes::task_attempts::CreateTagRequest::decl(),
+        server::routes::task_attempts::CreateReleaseOptions::decl(),
+        server::routes::task_attempts::CreateTagResponse::decl(),
+        server::routes::task_attempts::DispatchWorkflowRequest::decl(),
         server::routes::images::ImageResponse::decl(),
         services::services::github_service::GitHubServiceError::decl(),
         services::services::config::Config::decl(),
@@ -62,16 +171,71 @@ fn generate_types_content() -> String {
         services::services::config::SoundFile::decl(),
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
+        services::services::config::TelemetryConfig::decl(),
+        services::services::config::CommitSigningConfig::decl(),
+        services::services::config::CommitSigningFormat::decl(),
+        services::services::config::VulnerabilityScanConfig::decl(),
+        services::services::config::VulnerabilityScanner::decl(),
+        services::services::config::StallDetectionConfig::decl(),
+        services::services::config::WorktreeGcConfig::decl(),
+        services::services::config::GitCloneConfig::decl(),
+        services::services::config::CloneFilter::decl(),
+        services::services::config::StorageConfig::decl(),
+        services::services::config::StorageRelocation::decl(),
+        services::services::storage::StorageInfo::decl(),
+        services::services::config::ResourceLimits::decl(),
+        services::services::config::ResourceLimitsConfig::decl(),
+        services::services::config::DevServerConfig::decl(),
+        services::services::config::LogRetentionConfig::decl(),
+        services::services::log_retention::LogRetentionReport::decl(),
+        services::services::config::EventJournalConfig::decl(),
+        db::models::event_journal::EventJournalEntry::decl(),
+        server::routes::events::ws::EventsWsStream::decl(),
+        server::routes::events::ws::EventsWsClientMessage::decl(),
+        server::routes::events::ws::EventsWsServerMessage::decl(),
+        services::services::config::RateLimit::decl(),
+        services::services::config::RateLimitConfig::decl(),
+        services::services::rate_limit::LimitedRoute::decl(),
+        services::services::rate_limit::RouteRateLimitStats::decl(),
+        services::services::rate_limit::RateLimitSnapshot::decl(),
+        services::services::share_link::CreateShareLink::decl(),
+        services::services::share_link::ShareLink::decl(),
+        server::routes::shared::SharedExecutionProcess::decl(),
+        server::routes::shared::SharedAttempt::decl(),
+        services::services::config::ChatNotificationConfig::decl(),
+        services::services::config::SmtpSecurity::decl(),
+        services::services::config::EmailNotificationConfig::decl(),
+        services::services::config::layers::ConfigSource::decl(),
+        services::services::config::layers::EffectiveConfig::decl(),
+        db::models::config_override::ConfigOverride::decl(),
+        db::models::config_override::SetConfigOverride::decl(),
+        db::models::dev_server::DevServer::decl(),
+        services::services::dev_server::DevServerStatus::decl(),
+        services::services::worktree_gc::CollectedWorktree::decl(),
+        services::services::worktree_gc::WorktreeGcReport::decl(),
+        services::services::config::StallAction::decl(),
+        services::services::telemetry::TelemetrySnapshot::decl(),
+        services::services::telemetry::RouteTelemetry::decl(),
         services::services::auth::DeviceFlowStartResponse::decl(),
         server::routes::auth::DevicePollStatus::decl(),
         server::routes::auth::CheckTokenResponse::decl(),
         services::services::git::GitBranch::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
+        utils::diff::DiffHunk::decl(),
+        utils::diff::DiffLine::decl(),
+        utils::diff::DiffLineKind::decl(),
+        utils::diff::DiffRange::decl(),
         services::services::github_service::RepositoryInfo::decl(),
         executors::command::CommandBuilder::decl(),
+        executors::logs::output_parser::OutputParserConfig::decl(),
+        executors::logs::output_parser::OutputParserRule::decl(),
+        executors::logs::output_parser::OutputParserEntryType::decl(),
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
+        executors::profile::VariantInfo::decl(),
+        executors::profile::ExecutorVariants::decl(),
+        executors::executors::CostHint::decl(),
         executors::executors::BaseAgentCapability::decl(),
         executors::executors::claude::ClaudeCode::decl(),
         executors::executors::gemini::Gemini::decl(),
@@ -85,27 +249,87 @@ fn generate_types_content() -> String {
         executors::executors::codex::ReasoningSummaryFormat::decl(),
         executors::executors::cursor::CursorAgent::decl(),
         executors::executors::copilot::Copilot::decl(),
+        executors::executors::custom::CustomAgent::decl(),
+        executors::executors::custom::CustomLogFormat::decl(),
+        executors::executors::local_llm::LocalLlm::decl(),
         executors::executors::opencode::Opencode::decl(),
         executors::executors::qwen::QwenCode::decl(),
         executors::executors::AppendPrompt::decl(),
         executors::actions::coding_agent_initial::CodingAgentInitialRequest::decl(),
         executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest::decl(),
+        server::routes::task_attempts::TaskAttemptSummary::decl(),
+        server::routes::task_attempts::TaskAttemptDetails::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
         server::routes::task_attempts::RebaseTaskAttemptRequest::decl(),
         server::routes::task_attempts::GitOperationError::decl(),
+        server::routes::task_attempts::CherryPickRequest::decl(),
+        server::routes::task_attempts::CherryPickResult::decl(),
+        server::routes::task_attempts::ExportFormat::decl(),
         server::routes::task_attempts::ReplaceProcessRequest::decl(),
+        services::services::checkpoint::Checkpoint::decl(),
+        server::routes::task_attempts::checkpoints::RollbackToCheckpointRequest::decl(),
+        server::routes::task_attempts::checkpoints::RollbackResult::decl(),
+        server::routes::task_attempts::files::WorktreeFile::decl(),
+        server::routes::task_attempts::files::WriteWorktreeFileRequest::decl(),
+        server::routes::task_attempts::files::WriteWorktreeFileResult::decl(),
+        db::models::terminal_session::TerminalSession::decl(),
+        services::services::terminal::TerminalSize::decl(),
+        services::services::terminal::TerminalClientMessage::decl(),
         server::routes::task_attempts::CommitInfo::decl(),
         server::routes::task_attempts::BranchStatus::decl(),
+        db::models::base_branch_sync::BaseBranchSync::decl(),
         services::services::git::ConflictOp::decl(),
         db::models::task_attempt::TaskAttempt::decl(),
+        db::models::task_attempt::AttemptReviewState::decl(),
+        db::models::task_attempt_diff_stats::AttemptDiffStats::decl(),
+        db::models::task_attempt::UpdateAttemptReview::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
+        db::models::execution_process::KillReason::decl(),
+        services::services::process_tree::ProcessTreeNode::decl(),
+        server::routes::execution_processes::ExecutionProcessWithUsage::decl(),
+        services::services::resource_usage::ExecutionResourceUsage::decl(),
+        services::services::container::StoppedProcess::decl(),
+        services::services::container::StopSummary::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process::ExecutionProcessSearchResult::decl(),
+        server::routes::projects::ExecutionProcessPage::decl(),
+        db::models::execution_process_log_search::LogStream::decl(),
+        db::models::execution_process_log_search::LogLine::decl(),
+        services::services::log_search::ExecutionProcessLogMatches::decl(),
+        db::models::execution_process_metrics::ExecutionProcessMetrics::decl(),
+        db::models::execution_process_metrics::ExecutionMetricsAggregate::decl(),
+        db::models::execution_process_metrics::ExitCodeCount::decl(),
+        services::services::execution_metrics::ExecutionMetricsReport::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
         db::models::merge::MergeStatus::decl(),
+        db::models::merge::MergeStrategy::decl(),
         db::models::merge::PullRequestInfo::decl(),
+        db::models::merge_queue::MergeQueueStatus::decl(),
+        db::models::merge_queue::MergeQueueEntry::decl(),
+        db::models::workflow_run::WorkflowRun::decl(),
+        db::models::test_run_result::TestCounts::decl(),
+        db::models::test_run_result::TestRunResult::decl(),
+        db::models::deploy_hook::DeployHook::decl(),
+        db::models::deploy_hook::UpsertDeployHook::decl(),
+        services::services::project_env::ProjectEnvVarResponse::decl(),
+        services::services::project_env::UpsertProjectEnvVar::decl(),
+        db::models::task_inbox::TaskInbox::decl(),
+        db::models::task_inbox::UpsertTaskInbox::decl(),
+        db::models::github_issue::GitHubIssueSync::decl(),
+        db::models::github_issue::UpsertGitHubIssueSync::decl(),
+        services::services::github_issue_sync::GitHubIssueSyncSummary::decl(),
+        db::models::issue_tracker::IssueTrackerKind::decl(),
+        db::models::issue_tracker::IssueTrackerConnection::decl(),
+        db::models::issue_tracker::UpsertIssueTrackerConnection::decl(),
+        services::services::issue_tracker::IssueTrackerSyncSummary::decl(),
+        db::models::workspace::Workspace::decl(),
+        db::models::workspace::CreateWorkspace::decl(),
+        db::models::workspace::UpdateWorkspace::decl(),
+        db::models::vulnerability_finding::VulnerabilityFinding::decl(),
+        db::models::vulnerability_finding::VulnerabilitySeverity::decl(),
         db::models::draft::Draft::decl(),
         db::models::draft::DraftType::decl(),
         executors::logs::CommandExitStatus::decl(),
@@ -119,6 +343,7 @@ fn generate_types_content() -> String {
         executors::logs::ToolResultValueType::decl(),
         executors::logs::ToolStatus::decl(),
         executors::logs::utils::patch::PatchType::decl(),
+        utils::log_msg::TokenDelta::decl(),
         utils::approvals::ApprovalStatus::decl(),
         utils::approvals::CreateApprovalRequest::decl(),
         utils::approvals::ApprovalResponse::decl(),
@@ -195,6 +420,14 @@ fn generate_schemas() -> Result<HashMap<&'static str, String>, serde_json::Error
             "copilot",
             generate_json_schema::<executors::executors::copilot::Copilot>()?,
         ),
+        (
+            "custom",
+            generate_json_schema::<executors::executors::custom::CustomAgent>()?,
+        ),
+        (
+            "local_llm",
+            generate_json_schema::<executors::executors::local_llm::LocalLlm>()?,
+        ),
     ]);
     println!(
         "✅ JSON schemas generated. {} schemas created.",
@@ -8,16 +8,20 @@ use db::models::{
     execution_process::ExecutionProcessError, project::ProjectError, task_attempt::TaskAttemptError,
 };
 use deployment::DeploymentError;
-use executors::executors::ExecutorError;
+use executors::{executors::ExecutorError, profile::ProfileError};
 use git2::Error as Git2Error;
 use services::services::{
     auth::AuthError, config::ConfigError, container::ContainerError, drafts::DraftsServiceError,
-    git::GitServiceError, github_service::GitHubServiceError, image::ImageError,
-    worktree_manager::WorktreeError,
+    git::GitServiceError, github_service::GitHubServiceError, i18n::Message, image::ImageError,
+    log_retention::LogRetentionError, project_archive::ProjectArchiveError,
+    project_env::ProjectEnvError, setup_cache::SetupCacheError, share_link::ShareLinkError,
+    task_inbox::TaskInboxError, worktree_gc::WorktreeGcError, worktree_manager::WorktreeError,
 };
 use thiserror::Error;
 use utils::response::ApiResponse;
 
+use crate::middleware::request_locale;
+
 #[derive(Debug, Error, ts_rs::TS)]
 #[ts(type = "string")]
 pub enum ApiError {
@@ -40,6 +44,8 @@ pub enum ApiError {
     #[error(transparent)]
     Executor(#[from] ExecutorError),
     #[error(transparent)]
+    Profile(#[from] ProfileError),
+    #[error(transparent)]
     Database(#[from] sqlx::Error),
     #[error(transparent)]
     Worktree(#[from] WorktreeError),
@@ -49,6 +55,12 @@ pub enum ApiError {
     Image(#[from] ImageError),
     #[error(transparent)]
     Drafts(#[from] DraftsServiceError),
+    #[error(transparent)]
+    TaskInbox(#[from] TaskInboxError),
+    #[error(transparent)]
+    ProjectEnv(#[from] ProjectEnvError),
+    #[error(transparent)]
+    ShareLink(#[from] ShareLinkError),
     #[error("Multipart error: {0}")]
     Multipart(#[from] MultipartError),
     #[error("IO error: {0}")]
@@ -63,6 +75,52 @@ impl From<Git2Error> for ApiError {
     }
 }
 
+impl From<WorktreeGcError> for ApiError {
+    fn from(err: WorktreeGcError) -> Self {
+        match err {
+            WorktreeGcError::Sqlx(e) => ApiError::Database(e),
+            WorktreeGcError::Container(e) => ApiError::Container(e),
+        }
+    }
+}
+
+impl From<LogRetentionError> for ApiError {
+    fn from(err: LogRetentionError) -> Self {
+        match err {
+            LogRetentionError::Sqlx(e) => ApiError::Database(e),
+            e @ LogRetentionError::Archive { .. } => {
+                ApiError::Io(std::io::Error::other(e.to_string()))
+            }
+        }
+    }
+}
+
+impl From<ProjectArchiveError> for ApiError {
+    fn from(err: ProjectArchiveError) -> Self {
+        match err {
+            ProjectArchiveError::Database(e) => ApiError::Database(e),
+            ProjectArchiveError::TaskAttempt(e) => ApiError::TaskAttempt(e),
+            ProjectArchiveError::Io(e) => ApiError::Io(e),
+            e @ (ProjectArchiveError::Invalid(_) | ProjectArchiveError::UnsupportedVersion(_)) => {
+                ApiError::Conflict(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<SetupCacheError> for ApiError {
+    fn from(err: SetupCacheError) -> Self {
+        match err {
+            SetupCacheError::Io(e) => ApiError::Io(e),
+            SetupCacheError::Database(e) => ApiError::Database(e),
+            SetupCacheError::Git(e) => ApiError::GitService(e),
+            e @ (SetupCacheError::InvalidPath(_) | SetupCacheError::NoPaths) => {
+                ApiError::Conflict(e.to_string())
+            }
+        }
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status_code, error_type) = match &self {
@@ -82,20 +140,40 @@ impl IntoResponse for ApiError {
                 services::services::git::GitServiceError::RebaseInProgress => {
                     (StatusCode::CONFLICT, "GitServiceError")
                 }
+                services::services::git::GitServiceError::StashConflict(_) => {
+                    (StatusCode::CONFLICT, "GitServiceError")
+                }
+                services::services::git::GitServiceError::ProtectedBranch(_) => {
+                    (StatusCode::FORBIDDEN, "GitServiceError")
+                }
+                services::services::git::GitServiceError::InvalidTag(_) => {
+                    (StatusCode::BAD_REQUEST, "GitServiceError")
+                }
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             },
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
             ApiError::Auth(_) => (StatusCode::INTERNAL_SERVER_ERROR, "AuthError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            ApiError::Container(
+                ContainerError::TaskBlocked(_) | ContainerError::WipLimitReached(_),
+            ) => (StatusCode::CONFLICT, "ContainerError"),
+            ApiError::Container(ContainerError::ShuttingDown) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "ContainerError")
+            }
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
+            ApiError::Profile(_) => (StatusCode::BAD_REQUEST, "ProfileError"),
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
             ApiError::Worktree(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WorktreeError"),
+            ApiError::Config(ConfigError::ValidationError(_)) => {
+                (StatusCode::BAD_REQUEST, "ConfigError")
+            }
             ApiError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ConfigError"),
             ApiError::Image(img_err) => match img_err {
                 ImageError::InvalidFormat => (StatusCode::BAD_REQUEST, "InvalidImageFormat"),
                 ImageError::TooLarge(_, _) => (StatusCode::PAYLOAD_TOO_LARGE, "ImageTooLarge"),
                 ImageError::NotFound => (StatusCode::NOT_FOUND, "ImageNotFound"),
+                ImageError::NotPlaced(_) => (StatusCode::BAD_REQUEST, "ImageNotPlaced"),
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "ImageError"),
             },
             ApiError::Drafts(drafts_err) => match drafts_err {
@@ -106,46 +184,93 @@ impl IntoResponse for ApiError {
                 DraftsServiceError::Container(_) => {
                     (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError")
                 }
+                DraftsServiceError::Image(ImageError::NotPlaced(_)) => {
+                    (StatusCode::BAD_REQUEST, "ImageNotPlaced")
+                }
                 DraftsServiceError::Image(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ImageError"),
                 DraftsServiceError::ExecutionProcess(_) => {
                     (StatusCode::INTERNAL_SERVER_ERROR, "ExecutionProcessError")
                 }
+                DraftsServiceError::Profile(_) => (StatusCode::BAD_REQUEST, "ProfileError"),
+            },
+            ApiError::TaskInbox(inbox_err) => match inbox_err {
+                TaskInboxError::NotFound => (StatusCode::NOT_FOUND, "TaskInboxError"),
+                _ => (StatusCode::BAD_REQUEST, "TaskInboxError"),
+            },
+            ApiError::ProjectEnv(env_err) => match env_err {
+                ProjectEnvError::InvalidKey(_) => (StatusCode::BAD_REQUEST, "ProjectEnvError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectEnvError"),
+            },
+            ApiError::ShareLink(link_err) => match link_err {
+                ShareLinkError::Invalid | ShareLinkError::Expired(_) => {
+                    (StatusCode::FORBIDDEN, "ShareLinkError")
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ShareLinkError"),
             },
             ApiError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
             ApiError::Multipart(_) => (StatusCode::BAD_REQUEST, "MultipartError"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
         };
 
+        let locale = request_locale();
         let error_message = match &self {
             ApiError::Image(img_err) => match img_err {
-                ImageError::InvalidFormat => "This file type is not supported. Please upload an image file (PNG, JPG, GIF, WebP, or BMP).".to_string(),
-                ImageError::TooLarge(size, max) => format!(
-                    "This image is too large ({:.1} MB). Maximum file size is {:.1} MB.",
-                    *size as f64 / 1_048_576.0,
-                    *max as f64 / 1_048_576.0
-                ),
-                ImageError::NotFound => "Image not found.".to_string(),
-                _ => {
-                    "Failed to process image. Please try again.".to_string()
+                ImageError::InvalidFormat => Message::ImageInvalidFormat.localize(locale),
+                ImageError::TooLarge(size, max) => Message::ImageTooLarge {
+                    size: *size as f64 / 1_048_576.0,
+                    max: *max as f64 / 1_048_576.0,
                 }
+                .localize(locale),
+                ImageError::NotFound => Message::ImageNotFound.localize(locale),
+                ImageError::NotPlaced(names) => Message::ImagesNotPlaced {
+                    names: &names.join(", "),
+                }
+                .localize(locale),
+                _ => Message::ImageProcessingFailed.localize(locale),
             },
             ApiError::GitService(git_err) => match git_err {
                 services::services::git::GitServiceError::MergeConflicts(msg) => msg.clone(),
+                services::services::git::GitServiceError::StashConflict(msg) => msg.clone(),
+                services::services::git::GitServiceError::InvalidTag(msg) => msg.clone(),
+                services::services::git::GitServiceError::ProtectedBranch(branch) => {
+                    Message::ProtectedBranch { branch }.localize(locale)
+                }
                 services::services::git::GitServiceError::RebaseInProgress => {
-                    "A rebase is already in progress. Resolve conflicts or abort the rebase, then retry.".to_string()
+                    Message::RebaseInProgress.localize(locale)
                 }
                 _ => format!("{}: {}", error_type, self),
             },
-            ApiError::Multipart(_) => "Failed to upload file. Please ensure the file is valid and try again.".to_string(),
+            ApiError::Container(ContainerError::TaskBlocked(titles)) => Message::TaskBlocked {
+                tasks: &titles.join(", "),
+            }
+            .localize(locale),
+            ApiError::Container(ContainerError::WipLimitReached(exceeded)) => {
+                exceeded.message(locale)
+            }
+            ApiError::Multipart(_) => Message::UploadFailed.localize(locale),
             ApiError::Conflict(msg) => msg.clone(),
+            ApiError::TaskInbox(inbox_err) => inbox_err.to_string(),
+            ApiError::ProjectEnv(ProjectEnvError::InvalidKey(_)) => self.to_string(),
+            ApiError::ShareLink(ShareLinkError::Invalid | ShareLinkError::Expired(_)) => {
+                self.to_string()
+            }
+            ApiError::Profile(profile_err) => profile_err.to_string(),
+            ApiError::Config(ConfigError::ValidationError(msg)) => msg.clone(),
             ApiError::Drafts(drafts_err) => match drafts_err {
                 DraftsServiceError::Conflict(msg) => msg.clone(),
                 DraftsServiceError::Database(_) => format!("{}: {}", error_type, drafts_err),
                 DraftsServiceError::Container(_) => format!("{}: {}", error_type, drafts_err),
+                DraftsServiceError::Image(ImageError::NotPlaced(names)) => {
+                    Message::ImagesNotPlaced {
+                        names: &names.join(", "),
+                    }
+                    .localize(locale)
+                }
                 DraftsServiceError::Image(_) => format!("{}: {}", error_type, drafts_err),
                 DraftsServiceError::ExecutionProcess(_) => {
                     format!("{}: {}", error_type, drafts_err)
                 }
+                DraftsServiceError::Profile(profile_err) => profile_err.to_string(),
             },
             _ => format!("{}: {}", error_type, self),
         };
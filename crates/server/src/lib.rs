@@ -1,7 +1,10 @@
+pub mod app;
 pub mod error;
 pub mod mcp;
 pub mod middleware;
+pub mod otel;
 pub mod routes;
+pub mod tls;
 
 // #[cfg(feature = "cloud")]
 // type DeploymentImpl = vibe_kanban_cloud::deployment::CloudDeployment;
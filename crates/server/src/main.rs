@@ -1,12 +1,11 @@
 use anyhow::{self, Error as AnyhowError};
-use deployment::{Deployment, DeploymentError};
-use server::{DeploymentImpl, routes};
+use deployment::DeploymentError;
+use server::{app::App, otel, tls};
 use sqlx::Error as SqlxError;
 use strip_ansi_escapes::strip;
 use thiserror::Error;
 use tracing_subscriber::{EnvFilter, prelude::*};
 use utils::{
-    assets::asset_dir,
     browser::open_browser,
     port_file::write_port_file,
     sentry::{self as sentry_utils, SentrySource, sentry_layer},
@@ -33,39 +32,29 @@ async fn main() -> Result<(), VibeKanbanError> {
         "warn,server={level},services={level},db={level},executors={level},deployment={level},local_deployment={level},utils={level}",
         level = log_level
     );
-    let env_filter = EnvFilter::try_new(filter_string).expect("Failed to create tracing filter");
+    let env_filter = EnvFilter::try_new(&filter_string).expect("Failed to create tracing filter");
+    let otel_filter = EnvFilter::try_new(&filter_string).expect("Failed to create tracing filter");
     tracing_subscriber::registry()
         .with(tracing_subscriber::fmt::layer().with_filter(env_filter))
         .with(sentry_layer())
+        .with(otel::layer().map(|layer| layer.with_filter(otel_filter)))
         .init();
 
-    // Create asset directory if it doesn't exist
-    if !asset_dir().exists() {
-        std::fs::create_dir_all(asset_dir())?;
-    }
-
-    let deployment = DeploymentImpl::new().await?;
-    deployment.update_sentry_scope().await?;
-    deployment.cleanup_orphan_executions().await?;
-    deployment.backfill_before_head_commits().await?;
-    deployment.spawn_pr_monitor_service().await;
-    deployment
-        .track_if_analytics_allowed("session_start", serde_json::json!({}))
-        .await;
+    // Fail before starting anything if HTTPS is misconfigured
+    let tls_config = match tls::paths_from_env()? {
+        Some((cert_path, key_path)) => Some(tls::load_server_config(
+            cert_path.as_ref(),
+            key_path.as_ref(),
+        )?),
+        None => None,
+    };
 
-    // Pre-warm file search cache for most active projects
-    let deployment_for_cache = deployment.clone();
-    tokio::spawn(async move {
-        if let Err(e) = deployment_for_cache
-            .file_search_cache()
-            .warm_most_active(&deployment_for_cache.db().pool, 3)
-            .await
-        {
-            tracing::warn!("Failed to warm file search cache: {}", e);
-        }
-    });
-
-    let app_router = routes::router(deployment);
+    let mut app = App::start().await?;
+    // Only the API, for running behind another frontend or without a browser
+    let headless = std::env::var("VIBE_HEADLESS").is_ok_and(|v| v == "1" || v == "true");
+    if headless {
+        app = app.headless();
+    }
 
     let port = std::env::var("BACKEND_PORT")
         .or_else(|_| std::env::var("PORT"))
@@ -90,21 +79,27 @@ async fn main() -> Result<(), VibeKanbanError> {
         tracing::warn!("Failed to write port file: {}", e);
     }
 
-    tracing::info!("Server running on http://{host}:{actual_port}");
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    tracing::info!("Server running on {scheme}://{host}:{actual_port}");
 
-    if !cfg!(debug_assertions) {
+    if !cfg!(debug_assertions) && !headless {
         tracing::info!("Opening browser...");
         tokio::spawn(async move {
-            if let Err(e) = open_browser(&format!("http://127.0.0.1:{actual_port}")).await {
+            let url = format!("{scheme}://127.0.0.1:{actual_port}");
+            if let Err(e) = open_browser(&url).await {
                 tracing::warn!(
-                    "Failed to open browser automatically: {}. Please open http://127.0.0.1:{} manually.",
+                    "Failed to open browser automatically: {}. Please open {} manually.",
                     e,
-                    actual_port
+                    url
                 );
             }
         });
     }
 
-    axum::serve(listener, app_router).await?;
+    app.serve(listener, tls_config).await?;
     Ok(())
 }
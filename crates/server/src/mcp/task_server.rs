@@ -533,6 +533,9 @@ impl TaskServer {
             description,
             status,
             parent_task_attempt: None,
+            priority: None,
+            assignee: None,
+            column_id: None,
             image_ids: None,
             tag_ids: None,
         };
@@ -0,0 +1,58 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::public_url;
+
+use crate::DeploymentImpl;
+
+/// Tells nginx-style proxies to pass a response on as it is written instead of buffering it
+const ACCEL_BUFFERING_HEADER: HeaderName = HeaderName::from_static("x-accel-buffering");
+
+/// The origin a request was made to, for the absolute links made in answer to it
+#[derive(Clone, Debug)]
+pub struct RequestOrigin(pub Option<String>);
+
+/// Whether the request was passed on by one of the configured `trusted_proxies`. Requests served
+/// without connection info, e.g. by an embedding app's own server, are never.
+pub(crate) async fn is_from_trusted_proxy(deployment: &DeploymentImpl, request: &Request) -> bool {
+    let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        return false;
+    };
+    let config = deployment.config().read().await;
+    public_url::is_trusted_proxy(peer.ip(), &config.trusted_proxies)
+}
+
+/// Note the address the request was made to, through a trusted reverse proxy or from the
+/// network, for absolute links, and keep proxies from buffering event streams
+pub async fn forwarded_middleware(
+    State(deployment): State<DeploymentImpl>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let from_trusted_proxy = is_from_trusted_proxy(&deployment, &request).await;
+    let origin = public_url::request_origin(
+        request.headers(),
+        public_url::local_scheme(),
+        from_trusted_proxy,
+    );
+    request.extensions_mut().insert(RequestOrigin(origin));
+
+    let mut response = next.run(request).await;
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    if is_event_stream {
+        response
+            .headers_mut()
+            .insert(ACCEL_BUFFERING_HEADER, HeaderValue::from_static("no"));
+    }
+    response
+}
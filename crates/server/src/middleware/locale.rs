@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::i18n::Locale;
+
+use crate::DeploymentImpl;
+
+tokio::task_local! {
+    static REQUEST_LOCALE: Locale;
+}
+
+/// Locale of the request being handled, for localizing server-generated messages
+pub fn request_locale() -> Locale {
+    REQUEST_LOCALE
+        .try_with(|locale| *locale)
+        .unwrap_or_default()
+}
+
+/// Negotiate the locale from the configured language and the `Accept-Language` header,
+/// and make it available to the handler and to error responses via [`request_locale`]
+pub async fn locale_middleware(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let accept_language = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok());
+    let locale = Locale::negotiate(&deployment.config().read().await.language, accept_language);
+    REQUEST_LOCALE.scope(locale, next.run(request)).await
+}
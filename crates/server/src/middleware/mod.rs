@@ -1,3 +1,13 @@
+pub mod forwarded;
+pub mod locale;
 pub mod model_loaders;
+pub mod rate_limit;
+pub mod trace;
+pub mod user;
 
+pub use forwarded::*;
+pub use locale::*;
 pub use model_loaders::*;
+pub use rate_limit::*;
+pub use trace::*;
+pub use user::*;
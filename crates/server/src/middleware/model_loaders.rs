@@ -5,8 +5,9 @@ use axum::{
     response::Response,
 };
 use db::models::{
-    execution_process::ExecutionProcess, project::Project, tag::Tag, task::Task,
-    task_attempt::TaskAttempt,
+    automation_rule::AutomationRule, execution_process::ExecutionProcess, project::Project,
+    prompt_snippet::PromptSnippet, tag::Tag, task::Task, task_attempt::TaskAttempt,
+    task_schedule::TaskSchedule, webhook::Webhook, workspace::Workspace,
 };
 use deployment::Deployment;
 use uuid::Uuid;
@@ -203,3 +204,113 @@ pub async fn load_tag_middleware(
     // Continue with the next middleware/handler
     Ok(next.run(request).await)
 }
+
+pub async fn load_prompt_snippet_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(snippet_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let snippet = match PromptSnippet::find_by_id(&deployment.db().pool, snippet_id).await {
+        Ok(Some(snippet)) => snippet,
+        Ok(None) => {
+            tracing::warn!("Prompt snippet {} not found", snippet_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch prompt snippet {}: {}", snippet_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(snippet);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_task_schedule_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(schedule_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let schedule = match TaskSchedule::find_by_id(&deployment.db().pool, schedule_id).await {
+        Ok(Some(schedule)) => schedule,
+        Ok(None) => {
+            tracing::warn!("Task schedule {} not found", schedule_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch task schedule {}: {}", schedule_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(schedule);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_automation_rule_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(rule_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let rule = match AutomationRule::find_by_id(&deployment.db().pool, rule_id).await {
+        Ok(Some(rule)) => rule,
+        Ok(None) => {
+            tracing::warn!("Automation rule {} not found", rule_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch automation rule {}: {}", rule_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(rule);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_workspace_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(workspace_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let workspace = match Workspace::find_by_id(&deployment.db().pool, workspace_id).await {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            tracing::warn!("Workspace {} not found", workspace_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch workspace {}: {}", workspace_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(workspace);
+    Ok(next.run(request).await)
+}
+
+pub async fn load_webhook_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(webhook_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let webhook = match Webhook::find_by_id(&deployment.db().pool, webhook_id).await {
+        Ok(Some(webhook)) => webhook,
+        Ok(None) => {
+            tracing::warn!("Webhook {} not found", webhook_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch webhook {}: {}", webhook_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    request.extensions_mut().insert(webhook);
+    Ok(next.run(request).await)
+}
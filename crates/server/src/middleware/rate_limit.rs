@@ -0,0 +1,124 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use deployment::Deployment;
+use services::services::{attribution::USER_HEADER, i18n::Message, rate_limit::LimitedRoute};
+use utils::response::ApiResponse;
+
+use crate::{
+    DeploymentImpl,
+    middleware::{forwarded::is_from_trusted_proxy, request_locale},
+};
+
+/// Header proxies put the address of the client they forward a request for in
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Routes that start coding agents when posted to
+const ATTEMPT_START_ROUTES: &[&str] = &[
+    "/task-attempts",
+    "/task-attempts/bulk-rerun",
+    "/task-attempts/comparisons",
+    "/task-attempts/{id}/follow-up",
+    "/task-attempts/{id}/replace-process",
+    "/tasks/create-and-start",
+];
+
+/// Prefixes of the routes computing an attempt's diff
+const DIFF_ROUTES: &[&str] = &["/task-attempts/{id}/diff/", "/shared/{token}/diff/"];
+
+const LOG_STREAM_ROUTES: &[&str] = &[
+    "/execution-processes/{id}/raw-logs/ws",
+    "/execution-processes/{id}/normalized-logs/ws",
+    "/execution-processes/{id}/logs/download",
+    "/task-attempts/{id}/logs/download",
+    "/shared/{token}/processes/{process_id}/logs",
+];
+
+/// The kind of limited route a request is for, from the template of the route it matched
+fn limited_route(method: &Method, route: &str) -> Option<LimitedRoute> {
+    let route = route.trim_end_matches('/');
+    if *method == Method::POST && ATTEMPT_START_ROUTES.iter().any(|r| route.ends_with(r)) {
+        Some(LimitedRoute::AttemptStart)
+    } else if DIFF_ROUTES.iter().any(|r| route.contains(r)) {
+        Some(LimitedRoute::Diff)
+    } else if LOG_STREAM_ROUTES.iter().any(|r| route.ends_with(r)) {
+        Some(LimitedRoute::LogStream)
+    } else {
+        None
+    }
+}
+
+/// Who the request counts against: the address it came from. Behind a trusted proxy, the user
+/// it's made as or the address the proxy forwarded it for instead. Requests without a known
+/// address share one budget.
+fn client_key(headers: &HeaderMap, peer: Option<IpAddr>, from_trusted_proxy: bool) -> String {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    if from_trusted_proxy {
+        if let Some(user) = header(USER_HEADER) {
+            return format!("user:{user}");
+        }
+        if let Some(address) =
+            header(FORWARDED_FOR_HEADER).and_then(|value| value.split(',').next())
+        {
+            return format!("address:{}", address.trim());
+        }
+    }
+    match peer {
+        Some(peer) => format!("address:{}", peer.to_canonical()),
+        None => "local".to_string(),
+    }
+}
+
+/// Answer requests to expensive routes with 429 Too Many Requests once their client has used
+/// up its budget, as configured in `rate_limits`
+pub async fn rate_limit_middleware(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| limited_route(request.method(), path.as_str()));
+    let Some(route) = route else {
+        return next.run(request).await;
+    };
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| peer.ip());
+    let from_trusted_proxy = is_from_trusted_proxy(&deployment, &request).await;
+    let client = client_key(request.headers(), peer, from_trusted_proxy);
+    let Err(retry_after) = deployment.rate_limits().check(route, &client).await else {
+        return next.run(request).await;
+    };
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    tracing::warn!(
+        "Rate limited {} on {:?} routes; retry in {}s",
+        client,
+        route,
+        retry_after_secs
+    );
+    let message = Message::RateLimited { retry_after_secs }.localize(request_locale());
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        ResponseJson(ApiResponse::<()>::error(&message)),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::attribution::{self, USER_HEADER};
+
+use crate::DeploymentImpl;
+
+/// Attribute the request to the user named by the user header, or else to the GitHub account
+/// the server is signed in with, for [`attribution::current_user`]
+pub async fn user_middleware(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user = match request
+        .headers()
+        .get(USER_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(attribution::normalize_user)
+    {
+        Some(user) => Some(user),
+        None => deployment
+            .config()
+            .read()
+            .await
+            .github
+            .username
+            .as_deref()
+            .and_then(attribution::normalize_user),
+    };
+    attribution::with_user(user, next.run(request)).await
+}
@@ -0,0 +1,389 @@
+//! Export of tracing spans to an OpenTelemetry collector over OTLP/HTTP with JSON encoding, for
+//! following an attempt from the request that started it through its worktree, executor, logs,
+//! commit and merge.
+//!
+//! Export is on when `OTEL_EXPORTER_OTLP_ENDPOINT` (e.g. `http://localhost:4318`) or
+//! `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is set. `OTEL_EXPORTER_OTLP_HEADERS` and
+//! `OTEL_SERVICE_NAME` are read as other OpenTelemetry SDKs read them. Spans can set the
+//! `otel.name`, `otel.kind` and `otel.status_code` fields to override what is exported.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+use uuid::Uuid;
+
+const ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const TRACES_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
+const HEADERS_ENV: &str = "OTEL_EXPORTER_OTLP_HEADERS";
+const SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+
+/// Finished spans waiting to be exported; more are dropped while the collector is unreachable
+const QUEUE_SIZE: usize = 4096;
+const MAX_BATCH_SIZE: usize = 512;
+const EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+/// Events kept per span, so a chatty process doesn't grow its span without bound
+const MAX_EVENTS_PER_SPAN: usize = 128;
+
+#[derive(Debug, Clone)]
+enum AttributeValue {
+    String(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+impl AttributeValue {
+    fn to_json(&self) -> Value {
+        match self {
+            Self::String(value) => json!({ "stringValue": value }),
+            // 64-bit integers are strings in OTLP JSON
+            Self::Int(value) => json!({ "intValue": value.to_string() }),
+            Self::Double(value) => json!({ "doubleValue": value }),
+            Self::Bool(value) => json!({ "boolValue": value }),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldValues(Vec<(&'static str, AttributeValue)>);
+
+impl FieldValues {
+    fn set(&mut self, key: &'static str, value: AttributeValue) {
+        match self.0.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.0.push((key, value)),
+        }
+    }
+
+    fn take_string(&mut self, key: &str) -> Option<String> {
+        let position = self.0.iter().position(|(existing, _)| *existing == key)?;
+        match self.0.remove(position).1 {
+            AttributeValue::String(value) => Some(value),
+            other => Some(format!("{other:?}")),
+        }
+    }
+
+    fn to_json(&self) -> Vec<Value> {
+        self.0
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": value.to_json() }))
+            .collect()
+    }
+}
+
+impl Visit for FieldValues {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field.name(), AttributeValue::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field.name(), AttributeValue::Int(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set(
+            field.name(),
+            AttributeValue::Int(i64::try_from(value).unwrap_or(i64::MAX)),
+        );
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set(field.name(), AttributeValue::Double(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field.name(), AttributeValue::Bool(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.set(field.name(), AttributeValue::String(format!("{value:?}")));
+    }
+}
+
+struct SpanEvent {
+    time: SystemTime,
+    name: String,
+    attributes: FieldValues,
+}
+
+/// A span as it is exported, kept in the span's extensions until it closes
+struct SpanRecord {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    name: &'static str,
+    start: SystemTime,
+    /// When the span was last exited. Spans stay open while child spans, e.g. of tasks spawned
+    /// from a request, do, so their end is when they were last left rather than when they close.
+    end: Option<SystemTime>,
+    attributes: FieldValues,
+    events: Vec<SpanEvent>,
+    error: Option<String>,
+}
+
+impl SpanRecord {
+    fn to_json(mut self) -> Value {
+        let name = self
+            .attributes
+            .take_string("otel.name")
+            .unwrap_or_else(|| self.name.to_string());
+        let kind = match self.attributes.take_string("otel.kind").as_deref() {
+            Some("server") => 2,
+            Some("client") => 3,
+            _ => 1,
+        };
+        let status = match self.attributes.take_string("otel.status_code").as_deref() {
+            Some("error") => json!({ "code": 2 }),
+            Some("ok") => json!({ "code": 1 }),
+            _ => match &self.error {
+                Some(message) => json!({ "code": 2, "message": message }),
+                None => json!({}),
+            },
+        };
+        json!({
+            "traceId": hex(&self.trace_id),
+            "spanId": hex(&self.span_id),
+            "parentSpanId": self.parent_span_id.map(|id| hex(&id)).unwrap_or_default(),
+            "name": name,
+            "kind": kind,
+            "startTimeUnixNano": unix_nanos(self.start),
+            "endTimeUnixNano": unix_nanos(self.end.unwrap_or(self.start)),
+            "attributes": self.attributes.to_json(),
+            "events": self.events.iter().map(|event| json!({
+                "timeUnixNano": unix_nanos(event.time),
+                "name": event.name,
+                "attributes": event.attributes.to_json(),
+            })).collect::<Vec<_>>(),
+            "status": status,
+        })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+fn new_span_id() -> [u8; 8] {
+    let bytes = Uuid::new_v4().into_bytes();
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&bytes[..8]);
+    id
+}
+
+/// Layer recording spans and handing them to the exporter when they close
+pub struct OtlpLayer {
+    spans: mpsc::Sender<SpanRecord>,
+}
+
+impl<S> Layer<S> for OtlpLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            parent
+                .extensions()
+                .get::<SpanRecord>()
+                .map(|record| (record.trace_id, record.span_id))
+        });
+        let (trace_id, parent_span_id) = match parent {
+            Some((trace_id, span_id)) => (trace_id, Some(span_id)),
+            None => (Uuid::new_v4().into_bytes(), None),
+        };
+        let mut attributes = FieldValues::default();
+        attrs.record(&mut attributes);
+        span.extensions_mut().insert(SpanRecord {
+            trace_id,
+            span_id: new_span_id(),
+            parent_span_id,
+            name: attrs.metadata().name(),
+            start: SystemTime::now(),
+            end: None,
+            attributes,
+            events: Vec::new(),
+            error: None,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(record) = span.extensions_mut().get_mut::<SpanRecord>() {
+            values.record(&mut record.attributes);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(record) = extensions.get_mut::<SpanRecord>() else {
+            return;
+        };
+        let mut attributes = FieldValues::default();
+        event.record(&mut attributes);
+        let name = attributes
+            .take_string("message")
+            .unwrap_or_else(|| event.metadata().name().to_string());
+        let level = *event.metadata().level();
+        if level == Level::ERROR {
+            record.error = Some(name.clone());
+        }
+        if record.events.len() < MAX_EVENTS_PER_SPAN {
+            attributes.set("level", AttributeValue::String(level.to_string()));
+            record.events.push(SpanEvent {
+                time: SystemTime::now(),
+                name,
+                attributes,
+            });
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(record) = span.extensions_mut().get_mut::<SpanRecord>() {
+            record.end = Some(SystemTime::now());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(mut record) = span.extensions_mut().remove::<SpanRecord>() else {
+            return;
+        };
+        record.end.get_or_insert_with(SystemTime::now);
+        // Dropped when the exporter has fallen behind
+        let _ = self.spans.try_send(record);
+    }
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Headers from a `key=value,key=value` list, e.g. for a collector's API key
+fn parse_headers(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for pair in value.split(',') {
+        let Some((name, value)) = pair.split_once('=') else {
+            continue;
+        };
+        match (
+            HeaderName::from_bytes(name.trim().as_bytes()),
+            HeaderValue::from_str(value.trim()),
+        ) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => eprintln!("Ignoring invalid header `{}` in {HEADERS_ENV}", name.trim()),
+        }
+    }
+    headers
+}
+
+/// The layer exporting spans, when an OTLP endpoint is configured. Must be called from within
+/// the runtime, which the exporter runs on.
+pub fn layer() -> Option<OtlpLayer> {
+    let endpoint = env(TRACES_ENDPOINT_ENV).or_else(|| {
+        env(ENDPOINT_ENV).map(|base| format!("{}/v1/traces", base.trim_end_matches('/')))
+    })?;
+    let headers = env(HEADERS_ENV)
+        .map(|value| parse_headers(&value))
+        .unwrap_or_default();
+    let service_name = env(SERVICE_NAME_ENV).unwrap_or_else(|| "vibe-kanban".to_string());
+
+    let (spans, receiver) = mpsc::channel(QUEUE_SIZE);
+    tokio::spawn(export_spans(receiver, endpoint, headers, service_name));
+    Some(OtlpLayer { spans })
+}
+
+async fn export_spans(
+    mut receiver: mpsc::Receiver<SpanRecord>,
+    endpoint: String,
+    headers: HeaderMap,
+    service_name: String,
+) {
+    let client = reqwest::Client::new();
+    let mut interval = tokio::time::interval(EXPORT_INTERVAL);
+    let mut batch = Vec::new();
+    let mut failing = false;
+    loop {
+        tokio::select! {
+            span = receiver.recv() => match span {
+                Some(span) => {
+                    batch.push(span);
+                    if batch.len() < MAX_BATCH_SIZE {
+                        continue;
+                    }
+                }
+                None => return,
+            },
+            _ = interval.tick() => {}
+        }
+        if batch.is_empty() {
+            continue;
+        }
+
+        let spans = std::mem::take(&mut batch)
+            .into_iter()
+            .map(SpanRecord::to_json)
+            .collect::<Vec<_>>();
+        let body = json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [
+                        { "key": "service.name", "value": { "stringValue": service_name } },
+                        { "key": "service.version", "value": { "stringValue": utils::version::APP_VERSION } },
+                    ],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "vibe-kanban" },
+                    "spans": spans,
+                }],
+            }],
+        });
+        let result = client
+            .post(&endpoint)
+            .headers(headers.clone())
+            .json(&body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => failing = false,
+            // Logged once per outage; these events are outside any span, so aren't exported
+            Err(e) if !failing => {
+                failing = true;
+                tracing::warn!("Failed to export traces to {}: {}", endpoint, e);
+            }
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    audit_log::{AuditAction, AuditLogEntry, AuditLogFilter},
+    webhook::{WebhookDelivery, WebhookDeliveryStatus},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    config::{StorageRelocation, save_config_to_file},
+    log_retention::LogRetentionReport,
+    rate_limit::RateLimitSnapshot,
+    storage::{self, StorageInfo},
+    worktree_gc::WorktreeGcReport,
+};
+use sqlx::Error as SqlxError;
+use utils::{assets::config_path, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Deliveries listed when the query doesn't say
+const DEFAULT_DELIVERY_LIMIT: i64 = 100;
+const MAX_DELIVERY_LIMIT: i64 = 1000;
+/// Audit log entries listed when the query doesn't say
+const DEFAULT_AUDIT_LOG_LIMIT: i64 = 100;
+const MAX_AUDIT_LOG_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct WorktreeGcQuery {
+    /// Overrides the configured retention for this run
+    pub retention_days: Option<u32>,
+}
+
+/// Remove worktrees of long-finished tasks now instead of waiting for the periodic pass
+pub async fn collect_worktrees(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WorktreeGcQuery>,
+) -> Result<ResponseJson<ApiResponse<WorktreeGcReport>>, ApiError> {
+    let report = deployment
+        .worktree_gc()
+        .collect(query.retention_days)
+        .await?;
+    deployment
+        .track_if_analytics_allowed(
+            "worktree_gc_triggered",
+            serde_json::json!({
+                "removed": report.removed.len(),
+                "failed": report.failed,
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Compress, archive and prune execution logs now as configured, instead of waiting for the
+/// periodic pass. Runs even if the periodic pass is disabled.
+pub async fn apply_log_retention(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<LogRetentionReport>>, ApiError> {
+    let report = deployment.log_retention().run().await?;
+    deployment
+        .track_if_analytics_allowed(
+            "log_retention_triggered",
+            serde_json::json!({
+                "compressed": report.compressed,
+                "archived": report.archived,
+                "deleted": report.deleted_expired + report.deleted_over_cap,
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+/// Storage directories in use, and the move waiting for the next start if there is one
+pub async fn get_storage(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<StorageInfo>> {
+    let config = deployment.config().read().await;
+    ResponseJson(ApiResponse::success(StorageInfo::new(&config.storage)))
+}
+
+/// Schedule moving storage directories. The database and worktrees are in use while the server
+/// runs, so the data is moved and container refs are updated on the next start.
+pub async fn relocate_storage(
+    State(deployment): State<DeploymentImpl>,
+    Json(relocation): Json<StorageRelocation>,
+) -> Result<ResponseJson<ApiResponse<StorageInfo>>, ApiError> {
+    let mut config = deployment.config().write().await;
+    let relocation = match storage::validate_relocation(&config.storage, &relocation) {
+        Ok(relocation) => relocation,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    let mut updated = config.clone();
+    updated.storage.relocation = Some(relocation.clone());
+    save_config_to_file(&updated, deployment.config_layers(), &config_path()).await?;
+    *config = updated;
+    let info = StorageInfo::new(&config.storage);
+    drop(config);
+
+    deployment
+        .track_if_analytics_allowed(
+            "storage_relocation_scheduled",
+            serde_json::json!({
+                "worktrees": relocation.worktree_dir.is_some(),
+                "images": relocation.image_dir.is_some(),
+                "database": relocation.database_dir.is_some(),
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(info)))
+}
+
+pub async fn cancel_storage_relocation(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<StorageInfo>>, ApiError> {
+    let mut config = deployment.config().write().await;
+    let mut updated = config.clone();
+    updated.storage.relocation = None;
+    save_config_to_file(&updated, deployment.config_layers(), &config_path()).await?;
+    *config = updated;
+    Ok(ResponseJson(ApiResponse::success(StorageInfo::new(
+        &config.storage,
+    ))))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub status: Option<WebhookDeliveryStatus>,
+    pub limit: Option<i64>,
+}
+
+/// Delivery log of all webhooks, newest first, e.g. `?status=failed` for the ones given up on
+pub async fn get_webhook_deliveries(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WebhookDeliveryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebhookDelivery>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, MAX_DELIVERY_LIMIT);
+    let deliveries =
+        WebhookDelivery::find_recent(&deployment.db().pool, query.status, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(deliveries)))
+}
+
+/// Send a delivery again with a fresh set of retries, e.g. once a failing endpoint is fixed
+pub async fn redeliver_webhook_delivery(
+    State(deployment): State<DeploymentImpl>,
+    Path(delivery_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<WebhookDelivery>>, ApiError> {
+    let pool = &deployment.db().pool;
+    WebhookDelivery::find_by_id(pool, delivery_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let delivery = WebhookDelivery::redeliver(pool, delivery_id, Utc::now()).await?;
+    Ok(ResponseJson(ApiResponse::success(delivery)))
+}
+
+/// Configured rate limits, with how many requests each kind of route allowed and refused
+pub async fn get_rate_limits(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<RateLimitSnapshot>> {
+    let snapshot = deployment.rate_limits().snapshot().await;
+    ResponseJson(ApiResponse::success(snapshot))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub action: Option<AuditAction>,
+    pub actor: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Destructive operations, newest first, e.g. `?action=force_reset&task_attempt_id=...` for
+/// what discarded an attempt's work
+pub async fn get_audit_log(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AuditLogEntry>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)
+        .clamp(1, MAX_AUDIT_LOG_LIMIT);
+    let filter = AuditLogFilter {
+        action: query.action,
+        actor: query.actor,
+        project_id: query.project_id,
+        task_id: query.task_id,
+        task_attempt_id: query.task_attempt_id,
+        after: query.after,
+        before: query.before,
+    };
+    let entries = AuditLogEntry::find(&deployment.db().pool, &filter, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/admin/worktree-gc", post(collect_worktrees))
+        .route("/admin/log-retention", post(apply_log_retention))
+        .route("/admin/storage", get(get_storage))
+        .route(
+            "/admin/storage/relocation",
+            post(relocate_storage).delete(cancel_storage_relocation),
+        )
+        .route("/admin/webhook-deliveries", get(get_webhook_deliveries))
+        .route(
+            "/admin/webhook-deliveries/{delivery_id}/redeliver",
+            post(redeliver_webhook_delivery),
+        )
+        .route("/admin/rate-limits", get(get_rate_limits))
+        .route("/admin/audit-log", get(get_audit_log))
+}
@@ -80,7 +80,7 @@ async fn device_poll(
         config.github.primary_email = user_info.primary_email.clone();
         config.github.oauth_token = Some(user_info.token.to_string());
         config.github_login_acknowledged = true; // Also acknowledge the GitHub login step
-        save_config_to_file(&config.clone(), &config_path).await?;
+        save_config_to_file(&config.clone(), deployment.config_layers(), &config_path).await?;
     }
     let _ = deployment.update_sentry_scope().await;
     let props = serde_json::json!({
@@ -0,0 +1,138 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{
+    automation_rule::{
+        AutomationRule, AutomationRuleRun, CreateAutomationRule, UpdateAutomationRule,
+    },
+    project::Project,
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::automation;
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_automation_rule_middleware};
+
+/// Firings listed per rule, newest first
+const RULE_RUN_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct AutomationRuleQuery {
+    pub project_id: Uuid,
+}
+
+pub async fn get_automation_rules(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AutomationRuleQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AutomationRule>>>, ApiError> {
+    let rules = AutomationRule::find_by_project_id(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(rules)))
+}
+
+pub async fn get_automation_rule(
+    Extension(rule): Extension<AutomationRule>,
+) -> Result<ResponseJson<ApiResponse<AutomationRule>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn create_automation_rule(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateAutomationRule>,
+) -> Result<ResponseJson<ApiResponse<AutomationRule>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if let Err(e) = automation::validate(&payload.conditions, &payload.actions) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    Project::find_by_id(pool, payload.project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    let rule = AutomationRule::create(pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "automation_rule_created",
+            serde_json::json!({
+                "rule_id": rule.id.to_string(),
+                "project_id": rule.project_id.to_string(),
+                "trigger": rule.trigger,
+                "actions": rule.actions.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn update_automation_rule(
+    Extension(rule): Extension<AutomationRule>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateAutomationRule>,
+) -> Result<ResponseJson<ApiResponse<AutomationRule>>, ApiError> {
+    let conditions = payload.conditions.unwrap_or(rule.conditions.0);
+    let actions = payload.actions.unwrap_or(rule.actions.0);
+    if let Err(e) = automation::validate(&conditions, &actions) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    let updated = AutomationRule::update(
+        &deployment.db().pool,
+        rule.id,
+        payload.name.as_deref().unwrap_or(&rule.name),
+        payload.trigger.unwrap_or(rule.trigger),
+        &conditions,
+        &actions,
+        payload.enabled.unwrap_or(rule.enabled),
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_automation_rule(
+    Extension(rule): Extension<AutomationRule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = AutomationRule::delete(&deployment.db().pool, rule.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(SqlxError::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Execution log of the rule: its most recent firings and what their actions did
+pub async fn get_automation_rule_runs(
+    Extension(rule): Extension<AutomationRule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AutomationRuleRun>>>, ApiError> {
+    let runs =
+        AutomationRuleRun::find_by_rule_id(&deployment.db().pool, rule.id, RULE_RUN_LIMIT).await?;
+    Ok(ResponseJson(ApiResponse::success(runs)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let rule_router = Router::new()
+        .route(
+            "/",
+            get(get_automation_rule)
+                .put(update_automation_rule)
+                .delete(delete_automation_rule),
+        )
+        .route("/runs", get(get_automation_rule_runs))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_automation_rule_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_automation_rules).post(create_automation_rule))
+        .nest("/{rule_id}", rule_router);
+
+    Router::new().nest("/automation-rules", inner)
+}
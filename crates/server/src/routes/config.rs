@@ -6,17 +6,27 @@ use axum::{
     extract::{Path, Query, State},
     http,
     response::{Json as ResponseJson, Response},
-    routing::{get, put},
+    routing::{delete, get, post, put},
 };
+use db::models::config_override::{ConfigOverride, SetConfigOverride};
 use deployment::{Deployment, DeploymentError};
 use executors::{
     executors::{BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor},
     mcp_config::{McpConfig, read_agent_config, write_agent_config},
-    profile::{ExecutorConfigs, ExecutorProfileId},
+    profile::{ExecutorConfigs, ExecutorProfileId, ExecutorVariants},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use services::services::config::{Config, ConfigError, SoundFile, save_config_to_file};
+use services::services::{
+    attribution,
+    config::{
+        Config, ConfigError, EmailNotificationConfig, SoundFile, layers::EffectiveConfig,
+        save_config_to_file, validate_config,
+    },
+    container::ContainerService,
+    email_notification,
+};
+use sqlx::Error as SqlxError;
 use tokio::fs;
 use ts_rs::TS;
 use utils::{assets::config_path, response::ApiResponse};
@@ -27,9 +37,18 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/info", get(get_user_system_info))
         .route("/config", put(update_config))
+        .route("/config/effective", get(get_effective_config))
+        .route(
+            "/config/overrides",
+            get(get_config_overrides).put(set_config_override),
+        )
+        .route("/config/overrides/{path}", delete(delete_config_override))
+        .route("/config/email/test", post(send_test_email))
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
+        .route("/profiles/variants", get(get_executor_variants))
+        .route("/profiles/reload", post(reload_profiles))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -102,21 +121,31 @@ async fn update_config(
 ) -> ResponseJson<ApiResponse<Config>> {
     let config_path = config_path();
 
-    // Validate git branch prefix
-    if !utils::git::is_valid_branch_prefix(&new_config.git_branch_prefix) {
-        return ResponseJson(ApiResponse::error(
-            "Invalid git branch prefix. Must be a valid git branch name component without slashes.",
-        ));
+    if let Err(ConfigError::ValidationError(message)) = validate_config(&new_config) {
+        return ResponseJson(ApiResponse::error(&message));
     }
 
     // Get old config state before updating
     let old_config = deployment.config().read().await.clone();
 
-    match save_config_to_file(&new_config, &config_path).await {
-        Ok(_) => {
+    // Storage directories only change by moving the data, through the storage endpoints
+    let mut new_config = new_config;
+    new_config.storage = old_config.storage.clone();
+
+    // Settings from the environment or overrides win over what was sent
+    let layers = deployment.config_layers();
+    let saved = match save_config_to_file(&new_config, layers, &config_path).await {
+        Ok(()) => layers.read().await.merge(),
+        Err(e) => Err(e),
+    };
+    match saved {
+        Ok(new_config) => {
             let mut config = deployment.config().write().await;
             *config = new_config.clone();
             drop(config);
+            deployment
+                .git()
+                .set_commit_signing(&new_config.commit_signing);
 
             // Track config events when fields transition from false → true and run side effects
             handle_config_events(&deployment, &old_config, &new_config).await;
@@ -127,6 +156,74 @@ async fn update_config(
     }
 }
 
+/// The config in use, with the layer each setting that isn't at its default comes from
+async fn get_effective_config(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<EffectiveConfig>>, ApiError> {
+    let config = deployment.config().read().await.clone();
+    let effective = deployment.config_layers().read().await.describe(&config)?;
+    Ok(ResponseJson(ApiResponse::success(effective)))
+}
+
+async fn get_config_overrides(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ConfigOverride>>>, ApiError> {
+    let overrides = ConfigOverride::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(overrides)))
+}
+
+/// Override a setting until the override is removed, over the config file and environment
+async fn set_config_override(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetConfigOverride>,
+) -> Result<ResponseJson<ApiResponse<EffectiveConfig>>, ApiError> {
+    deployment
+        .config_layers()
+        .read()
+        .await
+        .check_override(&payload.path, &payload.value)?;
+    ConfigOverride::upsert(&deployment.db().pool, &payload, attribution::current_user()).await?;
+    let new_config = deployment
+        .config_layers()
+        .write()
+        .await
+        .set_override(&payload.path, payload.value)?;
+    apply_config(&deployment, new_config).await
+}
+
+async fn delete_config_override(
+    State(deployment): State<DeploymentImpl>,
+    Path(path): Path<String>,
+) -> Result<ResponseJson<ApiResponse<EffectiveConfig>>, ApiError> {
+    if ConfigOverride::delete(&deployment.db().pool, &path).await? == 0 {
+        return Err(ApiError::Database(SqlxError::RowNotFound));
+    }
+    let new_config = deployment
+        .config_layers()
+        .write()
+        .await
+        .remove_override(&path)?;
+    apply_config(&deployment, new_config).await
+}
+
+/// Put a config changed by an override in use
+async fn apply_config(
+    deployment: &DeploymentImpl,
+    new_config: Config,
+) -> Result<ResponseJson<ApiResponse<EffectiveConfig>>, ApiError> {
+    let old_config = std::mem::replace(&mut *deployment.config().write().await, new_config.clone());
+    deployment
+        .git()
+        .set_commit_signing(&new_config.commit_signing);
+    handle_config_events(deployment, &old_config, &new_config).await;
+    let effective = deployment
+        .config_layers()
+        .read()
+        .await
+        .describe(&new_config)?;
+    Ok(ResponseJson(ApiResponse::success(effective)))
+}
+
 /// Track config events when fields transition from false → true
 async fn track_config_events(deployment: &DeploymentImpl, old: &Config, new: &Config) {
     let events = [
@@ -187,6 +284,28 @@ async fn handle_config_events(deployment: &DeploymentImpl, old: &Config, new: &C
             deployment_clone.trigger_auto_project_setup().await;
         });
     }
+
+    // A raised or removed concurrency limit frees slots for queued coding agents
+    if old.resource_limits.max_concurrent_coding_agents
+        != new.resource_limits.max_concurrent_coding_agents
+    {
+        let deployment_clone = deployment.clone();
+        tokio::spawn(async move {
+            if let Err(e) = deployment_clone.container().start_queued_executions().await {
+                tracing::error!("Failed to start queued execution processes: {}", e);
+            }
+        });
+    }
+}
+
+/// Send a test email with the given settings, so they can be checked before saving them
+async fn send_test_email(
+    Json(email_config): Json<EmailNotificationConfig>,
+) -> ResponseJson<ApiResponse<()>> {
+    match email_notification::send_test(&email_config).await {
+        Ok(()) => ResponseJson(ApiResponse::success(())),
+        Err(e) => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
 }
 
 async fn get_sound(Path(sound): Path<SoundFile>) -> Result<Response, ApiError> {
@@ -409,6 +528,24 @@ async fn get_profiles(
     }))
 }
 
+async fn get_executor_variants(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ExecutorVariants>>> {
+    ResponseJson(ApiResponse::success(
+        ExecutorConfigs::get_cached().variant_catalog(),
+    ))
+}
+
+/// Load the profiles again, picking up custom agents added or changed since the last load
+async fn reload_profiles(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ExecutorVariants>>> {
+    ExecutorConfigs::reload();
+    ResponseJson(ApiResponse::success(
+        ExecutorConfigs::get_cached().variant_catalog(),
+    ))
+}
+
 async fn update_profiles(
     State(_deployment): State<DeploymentImpl>,
     body: String,
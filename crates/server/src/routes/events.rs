@@ -1,28 +1,119 @@
+pub mod ws;
+
+use std::str::FromStr;
+
 use axum::{
     BoxError, Router,
-    extract::State,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
     response::{
-        Sse,
+        Json as ResponseJson, Sse,
         sse::{Event, KeepAlive},
     },
     routing::get,
 };
+use db::models::event_journal::EventJournalEntry;
 use deployment::Deployment;
 use futures_util::TryStreamExt;
+use serde::Deserialize;
+use services::services::{
+    event_journal::JournalCursor,
+    events::{EventRecordType, EventStreamFilter},
+};
+use utils::{msg_store::MsgCursor, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    /// Comma-separated record types, e.g. `task,execution_process`; all of them when unset
+    pub record_types: Option<String>,
+    /// Id of the last event received, for clients that can't set `Last-Event-ID`
+    pub cursor: Option<String>,
+}
 
-use crate::DeploymentImpl;
+impl EventsQuery {
+    fn into_filter(self) -> Result<EventStreamFilter, StatusCode> {
+        let record_types = self
+            .record_types
+            .map(|types| {
+                types
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|record_type| !record_type.is_empty())
+                    .map(EventRecordType::from_str)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+        Ok(EventStreamFilter {
+            project_id: self.project_id,
+            task_id: self.task_id,
+            task_attempt_id: self.task_attempt_id,
+            record_types,
+        })
+    }
+}
 
+/// Clients that reconnect with `Last-Event-ID`, or the `cursor` parameter, get the events they
+/// missed rather than the whole history again
 pub async fn events(
     State(deployment): State<DeploymentImpl>,
-) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, axum::http::StatusCode>
-{
-    // Ask the container service for a combined "history + live" stream
-    let stream = deployment.stream_events().await;
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, StatusCode> {
+    let last = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .or(query.cursor.as_deref())
+        .and_then(|value| value.parse::<MsgCursor>().ok());
+    let filter = query.into_filter()?;
+    // Ask the container service for a combined "history + live" stream, scoped to the filter
+    let stream = match deployment.stream_events(filter, last).await {
+        Ok(Some(stream)) => stream,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to start events stream: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
     Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct EventHistoryQuery {
+    /// Id of the last entry already seen, or an RFC 3339 timestamp; the oldest entries kept
+    /// when unset
+    pub since: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Patches recorded in the event journal, oldest first, to replay recent board changes
+pub async fn get_event_history(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<EventHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<EventJournalEntry>>>, ApiError> {
+    let cursor = match query.since.as_deref().map(str::parse::<JournalCursor>) {
+        Some(Ok(cursor)) => Some(cursor),
+        Some(Err(e)) => return Ok(ResponseJson(ApiResponse::error(&e))),
+        None => None,
+    };
+    let entries = deployment
+        .event_journal()
+        .history(cursor, query.limit)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
 pub fn router(_: &DeploymentImpl) -> Router<DeploymentImpl> {
-    let events_router = Router::new().route("/", get(events));
+    let events_router = Router::new()
+        .route("/", get(events))
+        .route("/history", get(get_event_history))
+        .route("/ws", get(ws::events_ws));
 
     Router::new().nest("/events", events_router)
 }
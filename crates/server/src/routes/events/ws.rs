@@ -0,0 +1,296 @@
+//! One WebSocket carrying several of the streams otherwise served by their own SSE or WebSocket
+//! endpoints, for deployments behind proxies that buffer SSE.
+//!
+//! The client sends `subscribe` messages naming a stream and an id of its choosing, and
+//! `unsubscribe` to stop one. Every message of a stream comes back wrapped with its
+//! subscription's id, in the form the stream's own WebSocket endpoint sends it.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::IntoResponse,
+};
+use db::models::task_attempt::TaskAttempt;
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt, TryStreamExt, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use services::services::container::ContainerService;
+use tokio::{sync::mpsc, task::JoinHandle};
+use ts_rs::TS;
+use utils::{log_msg::LogMsg, msg_store::MsgCursor};
+use uuid::Uuid;
+
+use super::EventsQuery;
+use crate::DeploymentImpl;
+
+/// Messages waiting to be written to the socket before subscriptions have to wait
+const OUTGOING_BUFFER: usize = 256;
+
+/// A stream to subscribe to
+#[derive(Debug, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventsWsStream {
+    /// Board events, narrowed down like with the query of `/api/events`
+    Events {
+        #[serde(default)]
+        #[ts(optional)]
+        project_id: Option<Uuid>,
+        #[serde(default)]
+        #[ts(optional)]
+        task_id: Option<Uuid>,
+        #[serde(default)]
+        #[ts(optional)]
+        task_attempt_id: Option<Uuid>,
+        /// Comma-separated record types, e.g. `task,execution_process`
+        #[serde(default)]
+        #[ts(optional)]
+        record_types: Option<String>,
+        /// Cursor of the last event received, to resume after it
+        #[serde(default)]
+        #[ts(optional)]
+        cursor: Option<String>,
+    },
+    Diff {
+        task_attempt_id: Uuid,
+        #[serde(default)]
+        #[ts(optional)]
+        stats_only: bool,
+    },
+    RawLogs {
+        execution_process_id: Uuid,
+    },
+    NormalizedLogs {
+        execution_process_id: Uuid,
+    },
+}
+
+/// Message sent by the client over the events WebSocket
+#[derive(Debug, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventsWsClientMessage {
+    Subscribe { id: String, stream: EventsWsStream },
+    Unsubscribe { id: String },
+}
+
+/// Message sent by the server over the events WebSocket
+#[derive(Debug, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventsWsServerMessage {
+    Message {
+        id: String,
+        #[ts(type = "JsonValue")]
+        msg: Value,
+        /// Cursor of the event, for event subscriptions
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        cursor: Option<String>,
+    },
+    /// The stream is over and the subscription is gone
+    Ended { id: String },
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        id: Option<String>,
+        message: String,
+    },
+}
+
+type SubscriptionStream = BoxStream<'static, Result<(Option<MsgCursor>, LogMsg), std::io::Error>>;
+
+pub async fn events_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_events_ws(socket, deployment).await {
+            tracing::warn!("events WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_events_ws(socket: WebSocket, deployment: DeploymentImpl) -> anyhow::Result<()> {
+    let (mut sender, mut receiver) = socket.split();
+    let (tx, mut rx) = mpsc::channel::<EventsWsServerMessage>(OUTGOING_BUFFER);
+    let mut subscriptions: HashMap<String, JoinHandle<()>> = HashMap::new();
+
+    loop {
+        let outgoing = tokio::select! {
+            outgoing = rx.recv() => match outgoing {
+                Some(outgoing) => outgoing,
+                None => break,
+            },
+            incoming = receiver.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<EventsWsClientMessage>(text.as_str()) {
+                        Ok(EventsWsClientMessage::Subscribe { id, stream }) => {
+                            subscriptions.retain(|_, handle| !handle.is_finished());
+                            if subscriptions.contains_key(&id) {
+                                EventsWsServerMessage::Error {
+                                    message: format!("Subscription '{id}' already exists"),
+                                    id: Some(id),
+                                }
+                            } else {
+                                let handle = tokio::spawn(forward(
+                                    deployment.clone(),
+                                    id.clone(),
+                                    stream,
+                                    tx.clone(),
+                                ));
+                                subscriptions.insert(id, handle);
+                                continue;
+                            }
+                        }
+                        Ok(EventsWsClientMessage::Unsubscribe { id }) => {
+                            if let Some(handle) = subscriptions.remove(&id) {
+                                handle.abort();
+                            }
+                            continue;
+                        }
+                        Err(e) => EventsWsServerMessage::Error {
+                            id: None,
+                            message: format!("Invalid message: {e}"),
+                        },
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        let text = serde_json::to_string(&outgoing)?;
+        if sender.send(Message::Text(text.into())).await.is_err() {
+            break; // client disconnected
+        }
+    }
+
+    for handle in subscriptions.values() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+/// Send the subscription's stream to the socket until it ends
+async fn forward(
+    deployment: DeploymentImpl,
+    id: String,
+    stream: EventsWsStream,
+    tx: mpsc::Sender<EventsWsServerMessage>,
+) {
+    let mut stream = match open(&deployment, stream).await {
+        Ok(stream) => stream,
+        Err(message) => {
+            let _ = tx
+                .send(EventsWsServerMessage::Error {
+                    id: Some(id),
+                    message,
+                })
+                .await;
+            return;
+        }
+    };
+
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok((cursor, msg)) => {
+                let message = EventsWsServerMessage::Message {
+                    id: id.clone(),
+                    msg: to_value(&msg),
+                    cursor: cursor.map(|cursor| cursor.to_string()),
+                };
+                if tx.send(message).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                tracing::error!("stream error: {}", e);
+                break;
+            }
+        }
+    }
+    let _ = tx.send(EventsWsServerMessage::Ended { id }).await;
+}
+
+/// The message as the dedicated WebSocket endpoints send it
+fn to_value(msg: &LogMsg) -> Value {
+    match msg {
+        LogMsg::Finished => json!({ "finished": true }),
+        _ => {
+            serde_json::to_value(msg).unwrap_or_else(|_| json!({ "error": "serialization_failed" }))
+        }
+    }
+}
+
+fn without_cursor(
+    stream: BoxStream<'static, Result<LogMsg, std::io::Error>>,
+) -> SubscriptionStream {
+    stream.map_ok(|msg| (None, msg)).boxed()
+}
+
+async fn open(
+    deployment: &DeploymentImpl,
+    stream: EventsWsStream,
+) -> Result<SubscriptionStream, String> {
+    match stream {
+        EventsWsStream::Events {
+            project_id,
+            task_id,
+            task_attempt_id,
+            record_types,
+            cursor,
+        } => {
+            let filter = EventsQuery {
+                project_id,
+                task_id,
+                task_attempt_id,
+                record_types,
+                cursor: None,
+            }
+            .into_filter()
+            .map_err(|_| "Unknown record type".to_string())?;
+            let last = cursor.and_then(|cursor| cursor.parse::<MsgCursor>().ok());
+            let stream = deployment
+                .events()
+                .stream_events_filtered(filter, last)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "Project, task or attempt not found".to_string())?;
+            Ok(stream.map_ok(|(cursor, msg)| (Some(cursor), msg)).boxed())
+        }
+        EventsWsStream::Diff {
+            task_attempt_id,
+            stats_only,
+        } => {
+            let task_attempt = TaskAttempt::find_by_id(&deployment.db().pool, task_attempt_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "Task attempt not found".to_string())?;
+            let stream = deployment
+                .container()
+                .stream_diff(&task_attempt, stats_only)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(without_cursor(stream))
+        }
+        EventsWsStream::RawLogs {
+            execution_process_id,
+        } => deployment
+            .container()
+            .stream_raw_logs(&execution_process_id)
+            .await
+            .map(without_cursor)
+            .ok_or_else(|| "Execution process not found".to_string()),
+        EventsWsStream::NormalizedLogs {
+            execution_process_id,
+        } => deployment
+            .container()
+            .stream_normalized_logs(&execution_process_id)
+            .await
+            .map(without_cursor)
+            .ok_or_else(|| "Execution process not found".to_string()),
+    }
+}
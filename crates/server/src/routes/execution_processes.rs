@@ -3,20 +3,33 @@ use axum::{
     Extension, Router,
     extract::{
         Path, Query, State,
-        ws::{WebSocket, WebSocketUpgrade},
+        ws::{Message, WebSocket, WebSocketUpgrade},
     },
+    http::header,
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::{get, post},
 };
-use db::models::execution_process::{
-    ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus,
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
+    execution_process_log_search::{LogLine, LogStream},
+    execution_process_metrics::ExecutionProcessMetrics,
 };
 use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use serde::Deserialize;
-use services::services::container::ContainerService;
-use utils::{log_msg::LogMsg, response::ApiResponse};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::{ContainerService, StopSummary},
+    log_export,
+    log_search::{self, ExecutionProcessLogMatches, LogGrep},
+    process_tree::ProcessTreeNode,
+    resource_usage::ExecutionResourceUsage,
+};
+use ts_rs::TS;
+use utils::{
+    log_msg::{LogMsg, TokenDelta},
+    response::ApiResponse,
+};
 use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
@@ -29,10 +42,19 @@ pub struct ExecutionProcessQuery {
     pub show_soft_deleted: Option<bool>,
 }
 
+#[derive(Debug, Serialize, TS)]
+pub struct ExecutionProcessWithUsage {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub process: ExecutionProcess,
+    /// Latest CPU and memory sample while the process is running
+    pub resource_usage: Option<ExecutionResourceUsage>,
+}
+
 pub async fn get_execution_processes(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<ExecutionProcessQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcess>>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcessWithUsage>>>, ApiError> {
     let pool = &deployment.db().pool;
     let execution_processes = ExecutionProcess::find_by_task_attempt_id(
         pool,
@@ -41,7 +63,122 @@ pub async fn get_execution_processes(
     )
     .await?;
 
-    Ok(ResponseJson(ApiResponse::success(execution_processes)))
+    let monitor = deployment.container().resource_monitor();
+    let mut with_usage = Vec::with_capacity(execution_processes.len());
+    for process in execution_processes {
+        let resource_usage = monitor.get(&process.id).await;
+        with_usage.push(ExecutionProcessWithUsage {
+            process,
+            resource_usage,
+        });
+    }
+    Ok(ResponseJson(ApiResponse::success(with_usage)))
+}
+
+/// Matching lines read per log search, across all processes
+const LOG_SEARCH_LINE_LIMIT: i64 = 500;
+
+/// Lines returned by a grep of one process's logs
+const LOG_GREP_LIMIT: usize = 1_000;
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchQuery {
+    /// Words that must all appear in a line
+    pub q: String,
+    pub project_id: Option<Uuid>,
+}
+
+/// Find processes whose output contains the words of `q`, with the lines that matched
+pub async fn search_execution_process_logs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcessLogMatches>>>, ApiError> {
+    let fts_query = match log_search::fts_query(&query.q) {
+        Ok(fts_query) => fts_query,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    let results = log_search::search(
+        &deployment.db().pool,
+        &fts_query,
+        query.project_id,
+        LOG_SEARCH_LINE_LIMIT,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(results)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogGrepQuery {
+    pub pattern: String,
+    /// Treat the pattern as a regular expression instead of plain text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub ignore_case: bool,
+}
+
+/// Lines of the process's output matching a pattern, including while it is still running
+pub async fn grep_execution_process_logs(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogGrepQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<LogLine>>>, ApiError> {
+    let grep = match LogGrep::new(&query.pattern, query.regex, query.ignore_case) {
+        Ok(grep) => grep,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    let history = deployment
+        .container()
+        .get_msg_store_by_id(&execution_process.id)
+        .await
+        .map(|store| store.get_history());
+    let lines =
+        log_search::process_lines(&deployment.db().pool, execution_process.id, history).await?;
+    Ok(ResponseJson(ApiResponse::success(
+        grep.matches(lines, LOG_GREP_LIMIT),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogDownloadQuery {
+    /// Only this stream; both interleaved line by line if omitted
+    pub stream: Option<LogStream>,
+}
+
+/// Download the process's output as a text file, including while it is still running
+pub async fn download_execution_process_logs(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<LogDownloadQuery>,
+) -> Result<Response, ApiError> {
+    let in_memory = deployment
+        .container()
+        .get_msg_store_by_id(&execution_process.id)
+        .await
+        .map(|store| store.get_history());
+    let history =
+        log_export::load_history(&deployment.db().pool, execution_process.id, in_memory).await?;
+
+    let suffix = match query.stream {
+        Some(LogStream::Stdout) => "-stdout",
+        Some(LogStream::Stderr) => "-stderr",
+        None => "",
+    };
+    let file_name = format!("{}{suffix}.log", execution_process.id);
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        log_export::raw_text(&history, query.stream),
+    )
+        .into_response())
 }
 
 pub async fn get_execution_process_by_id(
@@ -51,6 +188,19 @@ pub async fn get_execution_process_by_id(
     Ok(ResponseJson(ApiResponse::success(execution_process)))
 }
 
+/// Resource usage of the process; `null` until it has finished
+pub async fn get_execution_process_metrics(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionProcessMetrics>>>, ApiError> {
+    let metrics = ExecutionProcessMetrics::find_by_execution_process_id(
+        &deployment.db().pool,
+        execution_process.id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(metrics)))
+}
+
 pub async fn stream_raw_logs_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -180,6 +330,72 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+pub async fn stream_tokens_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+    Path(exec_id): Path<Uuid>,
+) -> impl IntoResponse {
+    // Processes without an in-memory store have nothing left to stream
+    let stream = deployment
+        .container()
+        .stream_tokens(&exec_id)
+        .await
+        .unwrap_or_else(|| futures_util::stream::empty().boxed());
+
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_tokens_ws(socket, stream).await {
+            tracing::warn!("token stream WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_tokens_ws(
+    socket: WebSocket,
+    mut stream: futures_util::stream::BoxStream<'static, TokenDelta>,
+) -> anyhow::Result<()> {
+    let (mut sender, mut receiver) = socket.split();
+    tokio::spawn(async move { while let Some(Ok(_)) = receiver.next().await {} });
+    while let Some(delta) = stream.next().await {
+        let json = serde_json::to_string(&delta)?;
+        if sender.send(Message::Text(json.into())).await.is_err() {
+            return Ok(());
+        }
+    }
+    let _ = sender
+        .send(LogMsg::Finished.to_ws_message_unchecked())
+        .await;
+    Ok(())
+}
+
+/// Usage of every running execution process, busiest first, sent again on each new sample
+pub async fn stream_resource_usage_ws(
+    ws: WebSocketUpgrade,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    let stream = deployment.container().resource_monitor().stream().await;
+
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_resource_usage_ws(socket, stream).await {
+            tracing::warn!("resource usage WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_resource_usage_ws(
+    socket: WebSocket,
+    mut stream: futures_util::stream::BoxStream<'static, Vec<ExecutionResourceUsage>>,
+) -> anyhow::Result<()> {
+    let (mut sender, mut receiver) = socket.split();
+    tokio::spawn(async move { while let Some(Ok(_)) = receiver.next().await {} });
+    while let Some(usage) = stream.next().await {
+        let json = serde_json::to_string(&usage)?;
+        if sender.send(Message::Text(json.into())).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
 pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
@@ -192,6 +408,52 @@ pub async fn stop_execution_process(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Stop every running or queued execution process on the server
+pub async fn stop_all_execution_processes(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<StopSummary>>, ApiError> {
+    let summary = deployment.container().stop_all_processes().await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "all_processes_stopped",
+            serde_json::json!({
+                "stopped": summary.stopped.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+pub async fn get_process_tree(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProcessTreeNode>>>, ApiError> {
+    let tree = deployment
+        .container()
+        .process_tree(&execution_process)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(tree)))
+}
+
+pub async fn kill_child_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, pid)): Path<(Uuid, u32)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !deployment
+        .container()
+        .kill_child_process(&execution_process, pid)
+        .await?
+    {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "Process {pid} is not a running child of this execution process"
+        ))));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub async fn stream_execution_processes_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -251,8 +513,14 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
+        .route("/metrics", get(get_execution_process_metrics))
+        .route("/logs/grep", get(grep_execution_process_logs))
+        .route("/logs/download", get(download_execution_process_logs))
+        .route("/processes", get(get_process_tree))
+        .route("/processes/{pid}/kill", post(kill_child_process))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
+        .route("/tokens/ws", get(stream_tokens_ws))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
@@ -261,6 +529,9 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempts_router = Router::new()
         .route("/", get(get_execution_processes))
         .route("/stream/ws", get(stream_execution_processes_ws))
+        .route("/resources/ws", get(stream_resource_usage_ws))
+        .route("/search", get(search_execution_process_logs))
+        .route("/stop-all", post(stop_all_execution_processes))
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/execution-processes", task_attempts_router)
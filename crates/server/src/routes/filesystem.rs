@@ -35,6 +35,7 @@ pub async fn list_directory(
                 e
             ))))
         }
+        Err(e) => Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
     }
 }
 
@@ -68,6 +69,7 @@ pub async fn list_git_repos(
                 e
             ))))
         }
+        Err(e) => Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
     }
 }
 
@@ -1,8 +1,14 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::LazyLock,
+};
+
 use axum::{
     body::Body,
     http::HeaderValue,
     response::{IntoResponse, Response},
 };
+use regex::Regex;
 use reqwest::{StatusCode, header};
 use rust_embed::RustEmbed;
 
@@ -10,21 +16,57 @@ use rust_embed::RustEmbed;
 #[folder = "../../frontend/dist"]
 pub struct Assets;
 
+/// Vite puts a content hash in the name of everything it emits here
+const HASHED_ASSETS_PREFIX: &str = "assets/";
+
+const IMMUTABLE_CACHE: &str = "public, max-age=31536000, immutable";
+const SHORT_CACHE: &str = "public, max-age=3600";
+
+/// Serve the frontend from this directory instead of the embedded build, so a remote instance
+/// can run a newer UI without rebuilding the server
+static FRONTEND_DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let dir = PathBuf::from(std::env::var_os("VIBE_FRONTEND_DIR")?);
+    if dir.join("index.html").is_file() {
+        tracing::info!("Serving frontend from {}", dir.display());
+        Some(dir)
+    } else {
+        tracing::warn!(
+            "VIBE_FRONTEND_DIR {} has no index.html, serving the embedded frontend",
+            dir.display()
+        );
+        None
+    }
+});
+
+// Matches the tags Vite writes for the entry bundle, with attributes in the order it emits them
+static ENTRY_SCRIPT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<script\b[^>]*\btype="module"[^>]*\bsrc="([^"]+)"[^>]*>"#).unwrap()
+});
+static ENTRY_STYLESHEET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<link\b[^>]*\brel="stylesheet"[^>]*\bhref="([^"]+)"[^>]*>"#).unwrap()
+});
+
 pub async fn serve_frontend(uri: axum::extract::Path<String>) -> impl IntoResponse {
     let path = uri.trim_start_matches('/');
     serve_file(path).await
 }
 
 pub async fn serve_frontend_root() -> impl IntoResponse {
-    serve_file("index.html").await
+    serve_index().await
 }
 
-async fn serve_file(path: &str) -> impl IntoResponse + use<> {
-    let file = Assets::get(path);
-
-    match file {
+async fn serve_file(path: &str) -> Response {
+    if path == "index.html" {
+        return serve_index().await;
+    }
+    match load(path).await {
         Some(content) => {
             let mime = mime_guess::from_path(path).first_or_octet_stream();
+            let cache_control = if path.starts_with(HASHED_ASSETS_PREFIX) {
+                IMMUTABLE_CACHE
+            } else {
+                SHORT_CACHE
+            };
 
             Response::builder()
                 .status(StatusCode::OK)
@@ -32,23 +74,83 @@ async fn serve_file(path: &str) -> impl IntoResponse + use<> {
                     header::CONTENT_TYPE,
                     HeaderValue::from_str(mime.as_ref()).unwrap(),
                 )
-                .body(Body::from(content.data.into_owned()))
+                .header(header::CACHE_CONTROL, cache_control)
+                .body(Body::from(content))
                 .unwrap()
         }
-        None => {
-            // For SPA routing, serve index.html for unknown routes
-            if let Some(index) = Assets::get("index.html") {
-                Response::builder()
-                    .status(StatusCode::OK)
-                    .header(header::CONTENT_TYPE, HeaderValue::from_static("text/html"))
-                    .body(Body::from(index.data.into_owned()))
-                    .unwrap()
-            } else {
-                Response::builder()
-                    .status(StatusCode::NOT_FOUND)
-                    .body(Body::from("404 Not Found"))
-                    .unwrap()
-            }
-        }
+        // Deep links into the app get index.html. Missing files do not, so a stale or
+        // mistyped asset URL fails instead of being parsed as HTML.
+        None if is_client_route(path) => serve_index().await,
+        None => not_found(),
     }
 }
+
+async fn serve_index() -> Response {
+    let Some(index) = load("index.html").await else {
+        return not_found();
+    };
+
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        )
+        // Always revalidate so a new build is picked up on the next load
+        .header(header::CACHE_CONTROL, "no-cache");
+    if let Some(links) = preload_links(&String::from_utf8_lossy(&index))
+        && let Ok(value) = HeaderValue::from_str(&links)
+    {
+        response = response.header(header::LINK, value);
+    }
+    response.body(Body::from(index)).unwrap()
+}
+
+fn not_found() -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("404 Not Found"))
+        .unwrap()
+}
+
+async fn load(path: &str) -> Option<Vec<u8>> {
+    let Some(dir) = FRONTEND_DIR.as_ref() else {
+        return Assets::get(path).map(|file| file.data.into_owned());
+    };
+    let relative = Path::new(path);
+    // Never leave the frontend directory
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+    tokio::fs::read(dir.join(relative)).await.ok()
+}
+
+fn is_client_route(path: &str) -> bool {
+    !path.starts_with(HASHED_ASSETS_PREFIX) && Path::new(path).extension().is_none()
+}
+
+/// `Link` header asking the browser to fetch the entry script and stylesheet while it is
+/// still parsing index.html
+fn preload_links(index: &str) -> Option<String> {
+    let scripts = ENTRY_SCRIPT
+        .captures_iter(index)
+        .map(|caps| preload_link(&caps, "rel=modulepreload"));
+    let stylesheets = ENTRY_STYLESHEET
+        .captures_iter(index)
+        .map(|caps| preload_link(&caps, "rel=preload; as=style"));
+    let links: Vec<String> = scripts.chain(stylesheets).collect();
+    (!links.is_empty()).then(|| links.join(", "))
+}
+
+fn preload_link(caps: &regex::Captures, rel: &str) -> String {
+    // The preload is only reused if its CORS mode matches the tag's
+    let crossorigin = if caps[0].contains("crossorigin") {
+        "; crossorigin"
+    } else {
+        ""
+    };
+    format!("<{}>; {rel}{crossorigin}", &caps[1])
+}
@@ -0,0 +1,64 @@
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, header},
+    response::Json as ResponseJson,
+    routing::post,
+};
+use db::models::{
+    task::{CreateTask, Task},
+    task_inbox::TaskInbox,
+};
+use deployment::Deployment;
+use services::services::task_inbox::{self, TaskInboxError};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Create a task from a webhook payload or raw email posted to a project's inbox URL
+pub async fn ingest_inbox_payload(
+    State(deployment): State<DeploymentImpl>,
+    Path(secret): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let inbox = TaskInbox::find_by_secret(pool, &secret)
+        .await?
+        .ok_or(TaskInboxError::NotFound)?;
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let payload = task_inbox::parse_payload(content_type, &body)?;
+    let inbox_task = task_inbox::task_from_payload(&inbox, &payload);
+
+    let task = Task::create(
+        pool,
+        &CreateTask::from_title_description(
+            inbox.project_id,
+            inbox_task.title,
+            inbox_task.description,
+        ),
+        Uuid::new_v4(),
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_created_from_inbox",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": inbox.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/inbox/{secret}", post(ingest_inbox_payload))
+}
@@ -1,13 +1,24 @@
+use std::net::SocketAddr;
+
 use axum::{
     Router,
-    middleware::from_fn_with_state,
-    routing::{IntoMakeService, get},
+    extract::connect_info::IntoMakeServiceWithConnectInfo,
+    middleware::{from_fn, from_fn_with_state},
+    routing::get,
 };
 
-use crate::DeploymentImpl;
+use crate::{
+    DeploymentImpl,
+    middleware::{
+        forwarded_middleware, locale_middleware, rate_limit_middleware, trace_middleware,
+        user_middleware,
+    },
+};
 
+pub mod admin;
 pub mod approvals;
 pub mod auth;
+pub mod automation_rules;
 pub mod config;
 pub mod containers;
 pub mod filesystem;
@@ -18,37 +29,79 @@ pub mod execution_processes;
 pub mod frontend;
 pub mod health;
 pub mod images;
+pub mod inbox;
+pub mod notifications;
 pub mod projects;
+pub mod prompt_snippets;
+pub mod schedules;
+pub mod shared;
 pub mod tags;
 pub mod task_attempts;
 pub mod tasks;
+pub mod telemetry;
+pub mod webhooks;
+pub mod workspaces;
 
-pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
-    // Create routers with different middleware layers
-    let base_routes = Router::new()
+/// Every API route, without the middleware they run behind
+pub fn api_routes(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    Router::new()
         .route("/health", get(health::health_check))
         .merge(config::router())
-        .merge(containers::router(&deployment))
-        .merge(projects::router(&deployment))
-        .merge(drafts::router(&deployment))
-        .merge(tasks::router(&deployment))
-        .merge(task_attempts::router(&deployment))
-        .merge(execution_processes::router(&deployment))
-        .merge(tags::router(&deployment))
-        .merge(auth::router(&deployment))
+        .merge(containers::router(deployment))
+        .merge(projects::router(deployment))
+        .merge(drafts::router(deployment))
+        .merge(tasks::router(deployment))
+        .merge(task_attempts::router(deployment))
+        .merge(execution_processes::router(deployment))
+        .merge(prompt_snippets::router(deployment))
+        .merge(schedules::router(deployment))
+        .merge(automation_rules::router(deployment))
+        .merge(tags::router(deployment))
+        .merge(workspaces::router(deployment))
+        .merge(webhooks::router(deployment))
+        .merge(shared::router(deployment))
+        .merge(auth::router(deployment))
         .merge(filesystem::router())
-        .merge(events::router(&deployment))
+        .merge(events::router(deployment))
         .merge(approvals::router())
+        .merge(inbox::router())
+        .merge(notifications::router())
+        .merge(telemetry::router())
+        .merge(admin::router())
         .nest("/images", images::routes())
+}
+
+/// Put `routes` behind the middleware of the API and give them the deployment
+pub fn api_router(routes: Router<DeploymentImpl>, deployment: DeploymentImpl) -> Router {
+    routes
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            rate_limit_middleware,
+        ))
         .layer(from_fn_with_state(
             deployment.clone(),
             auth::sentry_user_context_middleware,
         ))
-        .with_state(deployment);
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            telemetry::telemetry_middleware,
+        ))
+        .layer(from_fn_with_state(deployment.clone(), user_middleware))
+        .layer(from_fn_with_state(deployment.clone(), locale_middleware))
+        .layer(from_fn(trace_middleware))
+        .layer(from_fn_with_state(deployment.clone(), forwarded_middleware))
+        .with_state(deployment)
+}
 
+/// The frontend, with `api` under `/api`
+pub fn app_router(api: Router) -> Router {
     Router::new()
         .route("/", get(frontend::serve_frontend_root))
         .route("/{*path}", get(frontend::serve_frontend))
-        .nest("/api", base_routes)
-        .into_make_service()
+        .nest("/api", api)
+}
+
+pub fn router(deployment: DeploymentImpl) -> IntoMakeServiceWithConnectInfo<Router, SocketAddr> {
+    let api = api_router(api_routes(&deployment), deployment);
+    app_router(api).into_make_service_with_connect_info::<SocketAddr>()
 }
@@ -0,0 +1,48 @@
+use axum::{
+    BoxError, Router,
+    http::HeaderMap,
+    response::{
+        Json as ResponseJson, Sse,
+        sse::{Event, KeepAlive},
+    },
+    routing::get,
+};
+use futures_util::{Stream, StreamExt};
+use services::services::notification_feed::{AppNotification, NotificationFeed};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::DeploymentImpl;
+
+/// Latest notifications, newest first
+pub async fn get_notifications() -> ResponseJson<ApiResponse<Vec<AppNotification>>> {
+    ResponseJson(ApiResponse::success(NotificationFeed::global().recent()))
+}
+
+fn to_event(notification: AppNotification) -> Result<Event, BoxError> {
+    Ok(Event::default()
+        .event("notification")
+        .id(notification.id.to_string())
+        .json_data(&notification)?)
+}
+
+/// Notifications as they are published. Clients that reconnect with `Last-Event-ID` first get
+/// the ones they missed.
+pub async fn stream_notifications(
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, BoxError>>> {
+    let last_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::parse_str(value).ok());
+    let stream = NotificationFeed::global().stream(last_id).map(to_event);
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_notifications))
+        .route("/stream", get(stream_notifications));
+
+    Router::new().nest("/notifications", inner)
+}
@@ -1,23 +1,66 @@
+pub mod chat_channels;
+pub mod columns;
+
 use std::path::Path;
 
 use axum::{
     Extension, Json, Router,
-    extract::{Query, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path as AxumPath, Query, State},
+    http::{StatusCode, header},
     middleware::from_fn_with_state,
-    response::Json as ResponseJson,
-    routing::{get, post},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, post, put},
 };
-use db::models::project::{
-    CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject,
+use chrono::{DateTime, Utc};
+use db::models::{
+    approval_decision::ApprovalDecision,
+    audit_log::AuditAction,
+    deploy_hook::{DeployHook, UpsertDeployHook},
+    execution_process::{
+        ExecutionProcess, ExecutionProcessFilter, ExecutionProcessRunReason,
+        ExecutionProcessSearchResult, ExecutionProcessStatus,
+    },
+    execution_process_metrics::ExecutionProcessMetrics,
+    github_issue::{GitHubIssueSync, UpsertGitHubIssueSync},
+    issue_tracker::{IssueTrackerConnection, UpsertIssueTrackerConnection},
+    merge_queue::MergeQueueEntry,
+    project::{CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject},
+    project_env_var::ProjectEnvVar,
+    setup_cache_entry::SetupCacheEntry,
+    task::TaskStatus,
+    task_attempt::TaskAttempt,
+    task_inbox::{TaskInbox, UpsertTaskInbox},
+    task_search::{TaskSearch, TaskSearchResult},
+    wip_limit::{UpsertWipLimit, WipLimit},
 };
 use deployment::Deployment;
+use executors::executors::BaseCodingAgent;
 use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
 use services::services::{
+    approval_policy,
+    attempt_report::{self, AttemptReport, ReportPeriod},
+    audit_log::AuditEvent,
+    container::{ContainerService, StopSummary},
+    execution_metrics::ExecutionMetricsReport,
+    execution_retry, failure_action,
     file_ranker::FileRanker,
     file_search_cache::{CacheError, SearchMode, SearchQuery},
     git::GitBranch,
+    github_issue_sync::{self, GitHubIssueSyncError, GitHubIssueSyncSummary},
+    issue_tracker::{self, IssueTrackerError, IssueTrackerSyncSummary},
+    log_search,
+    project_archive::{self, ProjectArchiveError, ProjectImportSummary},
+    project_clone::{self, CloneProjectRequest, ProjectClone},
+    project_env::{self, ProjectEnvVarResponse, SecretCipher, UpsertProjectEnvVar},
+    project_locale, quiet_hours,
+    repo_health::{self, RepoHealthIssueKind, RepoHealthReport, RepoRepairReport},
+    setup_cache::{self, SetupCache},
+    task_import::{self, TaskImportError, TaskImportRequest, TaskImportSummary},
+    test_report,
 };
+use ts_rs::TS;
 use utils::{path::expand_tilde, response::ApiResponse};
 use uuid::Uuid;
 
@@ -88,8 +131,12 @@ pub async fn create_project(
             )));
         }
 
-        // Clone the repository
-        if let Err(e) = deployment.git().clone_repository(&url, &path, None) {
+        // Clone the repository, partially or shallowly if configured
+        let clone_config = deployment.config().read().await.git_clone.clone();
+        if let Err(e) = deployment
+            .git()
+            .clone_repository(&url, &path, None, &clone_config)
+        {
             tracing::error!("Failed to clone repository from {}: {}", url, e);
             return Ok(ResponseJson(ApiResponse::error(&format!(
                 "Failed to clone repository: {}",
@@ -199,6 +246,38 @@ pub async fn create_project(
     }
 }
 
+/// Clone a repository into the app's repos directory and register it as a project. Responds
+/// as soon as cloning starts; progress and the new project's id arrive on the event stream.
+pub async fn clone_project(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CloneProjectRequest>,
+) -> Result<ResponseJson<ApiResponse<ProjectClone>>, ApiError> {
+    let clone = match project_clone::start(
+        deployment.db().clone(),
+        deployment.git().clone(),
+        deployment.config().clone(),
+        deployment.events().msg_store().clone(),
+        payload,
+    )
+    .await
+    {
+        Ok(clone) => clone,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_clone_started",
+            serde_json::json!({
+                "clone_id": clone.id.to_string(),
+                "trigger": "manual",
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(clone)))
+}
+
 pub async fn update_project(
     Extension(existing_project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -213,7 +292,24 @@ pub async fn update_project(
         setup_script,
         dev_script,
         cleanup_script,
+        verify_script,
+        post_merge_script,
         copy_files,
+        block_merge_on_vulnerabilities,
+        sync_base_branch,
+        auto_rebase_on_sync,
+        push_remote,
+        base_remote,
+        protected_branches,
+        timezone,
+        locale,
+        quiet_hours,
+        sandbox,
+        retry_policy,
+        setup_cache,
+        on_cleanup_failure,
+        test_run,
+        approval_policy,
     } = payload;
     // If git_repo_path is being changed, check if the new path is already used by another project
     let git_repo_path = if let Some(new_git_repo_path) = git_repo_path.map(|s| expand_tilde(&s))
@@ -241,6 +337,97 @@ pub async fn update_project(
         existing_project.git_repo_path
     };
 
+    // Blank remotes fall back to the repository's first remote
+    let push_remote = push_remote
+        .map(|remote| remote.trim().to_string())
+        .filter(|remote| !remote.is_empty());
+    let base_remote = base_remote
+        .map(|remote| remote.trim().to_string())
+        .filter(|remote| !remote.is_empty());
+    if push_remote.is_some() || base_remote.is_some() {
+        let remotes = deployment
+            .git()
+            .list_remote_names(&git_repo_path)
+            .unwrap_or_default();
+        if let Some(missing) = [&push_remote, &base_remote]
+            .into_iter()
+            .flatten()
+            .find(|remote| !remotes.contains(remote))
+        {
+            return Ok(ResponseJson(ApiResponse::error(&format!(
+                "The repository has no remote named '{missing}'"
+            ))));
+        }
+    }
+
+    let protected_branches = protected_branches
+        .map(|patterns| {
+            patterns
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .filter(|patterns| !patterns.is_empty());
+
+    let timezone = timezone
+        .map(|timezone| timezone.trim().to_string())
+        .filter(|timezone| !timezone.is_empty());
+    if let Some(timezone) = &timezone
+        && let Err(e) = project_locale::parse_timezone(timezone)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    let locale = locale
+        .map(|locale| locale.trim().to_string())
+        .filter(|locale| !locale.is_empty());
+    if let Some(locale) = &locale
+        && let Err(e) = project_locale::parse_locale(locale)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if let Some(window) = &quiet_hours
+        && let Err(e) = quiet_hours::validate(window)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if let Some(policy) = &retry_policy
+        && let Err(e) = execution_retry::validate(policy)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if let Some(config) = &setup_cache
+        && let Err(e) = setup_cache::validate(config)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if let Some(config) = &on_cleanup_failure
+        && config.enabled
+        && let Err(e) = failure_action::validate(&config.action)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if let Some(config) = &test_run
+        && config.enabled
+        && let Err(e) = test_report::validate(config)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if let Some(policy) = &approval_policy
+        && let Err(e) = approval_policy::validate(policy)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    if block_merge_on_vulnerabilities == Some(true)
+        && !existing_project.block_merge_on_vulnerabilities
+        && !deployment.config().read().await.vulnerability_scan.enabled
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Enable dependency vulnerability scanning in the settings before blocking merges on it",
+        )));
+    }
+
     match Project::update(
         &deployment.db().pool,
         existing_project.id,
@@ -250,6 +437,23 @@ pub async fn update_project(
         dev_script,
         cleanup_script,
         copy_files,
+        block_merge_on_vulnerabilities.unwrap_or(existing_project.block_merge_on_vulnerabilities),
+        sync_base_branch.unwrap_or(existing_project.sync_base_branch),
+        auto_rebase_on_sync.unwrap_or(existing_project.auto_rebase_on_sync),
+        push_remote,
+        base_remote,
+        protected_branches,
+        timezone,
+        locale,
+        quiet_hours.or(existing_project.quiet_hours.map(|window| window.0)),
+        sandbox.or(existing_project.sandbox.map(|sandbox| sandbox.0)),
+        verify_script,
+        retry_policy.or(existing_project.retry_policy.map(|policy| policy.0)),
+        setup_cache.or(existing_project.setup_cache.map(|config| config.0)),
+        post_merge_script,
+        on_cleanup_failure.or(existing_project.on_cleanup_failure.map(|config| config.0)),
+        test_run.or(existing_project.test_run.map(|config| config.0)),
+        approval_policy.or(existing_project.approval_policy.map(|policy| policy.0)),
     )
     .await
     {
@@ -270,6 +474,16 @@ pub async fn delete_project(
             if rows_affected == 0 {
                 Err(StatusCode::NOT_FOUND)
             } else {
+                if let Err(e) = SetupCache::default()
+                    .clear(&deployment.db().pool, project.id)
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to delete cached setup script output of project {}: {}",
+                        project.id,
+                        e
+                    );
+                }
                 deployment
                     .track_if_analytics_allowed(
                         "project_deleted",
@@ -289,6 +503,407 @@ pub async fn delete_project(
     }
 }
 
+pub async fn get_project_deploy_hook(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<DeployHook>>>, ApiError> {
+    let hook = DeployHook::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(hook)))
+}
+
+pub async fn upsert_project_deploy_hook(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertDeployHook>,
+) -> Result<ResponseJson<ApiResponse<DeployHook>>, ApiError> {
+    let url_template = payload.url_template.trim();
+    if !(url_template.starts_with("http://") || url_template.starts_with("https://")) {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Deploy hook URL must start with http:// or https://",
+        )));
+    }
+
+    let hook = DeployHook::upsert(
+        &deployment.db().pool,
+        project.id,
+        &UpsertDeployHook {
+            url_template: url_template.to_string(),
+            token: payload.token,
+        },
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(hook)))
+}
+
+pub async fn delete_project_deploy_hook(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    DeployHook::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_env_vars(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectEnvVarResponse>>>, ApiError> {
+    let vars = project_env::list(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(vars)))
+}
+
+pub async fn upsert_project_env_var(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectEnvVar>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnvVarResponse>>, ApiError> {
+    let var = project_env::set(
+        &deployment.db().pool,
+        SecretCipher::global()?,
+        project.id,
+        &payload,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(var)))
+}
+
+pub async fn delete_project_env_var(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    AxumPath((_, key)): AxumPath<(Uuid, String)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectEnvVar::delete(&deployment.db().pool, project.id, &key).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_wip_limits(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WipLimit>>>, ApiError> {
+    let limits = WipLimit::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(limits)))
+}
+
+pub async fn upsert_project_wip_limit(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertWipLimit>,
+) -> Result<ResponseJson<ApiResponse<WipLimit>>, ApiError> {
+    if payload.max_tasks < 1 {
+        return Ok(ResponseJson(ApiResponse::error(
+            "A work-in-progress limit must allow at least one task",
+        )));
+    }
+    let limit = WipLimit::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(limit)))
+}
+
+pub async fn delete_project_wip_limit(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    AxumPath((_, status)): AxumPath<(Uuid, TaskStatus)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    WipLimit::delete(&deployment.db().pool, project.id, status).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Setup script output saved for the project, most recently used first
+pub async fn get_project_setup_cache(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<SetupCacheEntry>>>, ApiError> {
+    let entries = SetupCacheEntry::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+/// Delete all setup script output saved for the project. Returns the number of entries deleted.
+pub async fn clear_project_setup_cache(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<u64>>, ApiError> {
+    let deleted = SetupCache::default()
+        .clear(&deployment.db().pool, project.id)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_setup_cache_cleared",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "entries": deleted,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(deleted)))
+}
+
+pub async fn delete_project_setup_cache_entry(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    AxumPath((_, cache_key)): AxumPath<(Uuid, String)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !SetupCache::default()
+        .remove(&deployment.db().pool, project.id, &cache_key)
+        .await?
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No cached setup script output with that key",
+        )));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttemptReportQuery {
+    /// How many days back to report on; defaults to 90
+    pub days: Option<u32>,
+    #[serde(default)]
+    pub period: ReportPeriod,
+}
+
+pub async fn get_project_attempt_report(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AttemptReportQuery>,
+) -> Result<ResponseJson<ApiResponse<AttemptReport>>, ApiError> {
+    let days = query.days.unwrap_or(90).clamp(1, 3650);
+    let since = Utc::now() - chrono::Duration::days(days.into());
+    let outcomes =
+        TaskAttempt::find_outcomes_by_project_id(&deployment.db().pool, project.id, since).await?;
+    Ok(ResponseJson(ApiResponse::success(
+        attempt_report::build_report(&outcomes, since, query.period, project.timezone.as_deref()),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExecutionMetricsQuery {
+    /// How many days back to aggregate; defaults to 30
+    pub days: Option<u32>,
+}
+
+pub async fn get_project_execution_metrics(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExecutionMetricsQuery>,
+) -> Result<ResponseJson<ApiResponse<ExecutionMetricsReport>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let days = query.days.unwrap_or(30).clamp(1, 3650);
+    let since = Utc::now() - chrono::Duration::days(days.into());
+    let aggregates =
+        ExecutionProcessMetrics::aggregate_by_project_id(pool, project.id, since).await?;
+    let exit_codes =
+        ExecutionProcessMetrics::exit_code_counts_by_project_id(pool, project.id, since).await?;
+    Ok(ResponseJson(ApiResponse::success(ExecutionMetricsReport {
+        since,
+        aggregates,
+        exit_codes,
+    })))
+}
+
+pub async fn get_project_repo_health(
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<RepoHealthReport>>, ApiError> {
+    let report = repo_health::check(&project.git_repo_path).await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn repair_project_repo(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<RepoRepairReport>>, ApiError> {
+    let report = repo_health::repair(&project.git_repo_path).await?;
+    // Other repairs only remove leftovers of interrupted git commands
+    for issue in report
+        .repaired
+        .iter()
+        .filter(|issue| issue.kind == RepoHealthIssueKind::BrokenRef)
+    {
+        AuditEvent::new(
+            AuditAction::BranchDelete,
+            issue.target.clone().unwrap_or_default(),
+        )
+        .project(project.id)
+        .parameters(serde_json::json!({
+            "reason": "repo_repair",
+            "detail": issue.detail,
+        }))
+        .record(&deployment.db().pool)
+        .await;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_repo_repaired",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "repaired": report.repaired.len(),
+                "healthy": report.health.healthy,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn get_project_task_inbox(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<TaskInbox>>>, ApiError> {
+    let inbox = TaskInbox::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(inbox)))
+}
+
+pub async fn upsert_project_task_inbox(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertTaskInbox>,
+) -> Result<ResponseJson<ApiResponse<TaskInbox>>, ApiError> {
+    if payload.title_template.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "A title template is required",
+        )));
+    }
+
+    let secret = Uuid::new_v4().simple().to_string();
+    let inbox = TaskInbox::upsert(&deployment.db().pool, project.id, &payload, &secret).await?;
+    Ok(ResponseJson(ApiResponse::success(inbox)))
+}
+
+pub async fn delete_project_task_inbox(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    TaskInbox::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn get_project_github_issue_sync(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<GitHubIssueSync>>>, ApiError> {
+    let sync = GitHubIssueSync::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(sync)))
+}
+
+pub async fn upsert_project_github_issue_sync(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertGitHubIssueSync>,
+) -> Result<ResponseJson<ApiResponse<GitHubIssueSync>>, ApiError> {
+    if let Err(e) = deployment
+        .git()
+        .get_github_repo_info(&project.git_repo_path, project.base_remote.as_deref())
+    {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "The project's repository has no GitHub remote: {e}"
+        ))));
+    }
+    let payload = UpsertGitHubIssueSync {
+        label: payload
+            .label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty()),
+    };
+
+    let sync = GitHubIssueSync::upsert(&deployment.db().pool, project.id, &payload).await?;
+    deployment
+        .track_if_analytics_allowed(
+            "github_issue_sync_enabled",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "has_label": sync.label.is_some(),
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(sync)))
+}
+
+pub async fn delete_project_github_issue_sync(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    GitHubIssueSync::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Sync the project with its GitHub issues now instead of waiting for the next poll
+pub async fn run_project_github_issue_sync(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<GitHubIssueSyncSummary>>, ApiError> {
+    let Some(sync) = GitHubIssueSync::find_by_project_id(&deployment.db().pool, project.id).await?
+    else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "GitHub issue sync is not enabled for this project",
+        )));
+    };
+    match github_issue_sync::sync_project(deployment.db(), deployment.config(), &sync).await {
+        Ok(summary) => Ok(ResponseJson(ApiResponse::success(summary))),
+        Err(GitHubIssueSyncError::Database(e)) => Err(e.into()),
+        Err(e) => Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    }
+}
+
+pub async fn get_project_issue_tracker(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<IssueTrackerConnection>>>, ApiError> {
+    let connection =
+        IssueTrackerConnection::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(connection)))
+}
+
+pub async fn upsert_project_issue_tracker(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertIssueTrackerConnection>,
+) -> Result<ResponseJson<ApiResponse<IssueTrackerConnection>>, ApiError> {
+    let connection =
+        match issue_tracker::save_connection(&deployment.db().pool, project.id, &payload).await {
+            Ok(connection) => connection,
+            Err(IssueTrackerError::Database(e)) => return Err(e.into()),
+            Err(IssueTrackerError::Secret(e)) => return Err(e.into()),
+            Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+        };
+    deployment
+        .track_if_analytics_allowed(
+            "issue_tracker_connected",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "kind": connection.kind,
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(connection)))
+}
+
+pub async fn delete_project_issue_tracker(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    IssueTrackerConnection::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Sync the project with its issue tracker now instead of waiting for the next poll
+pub async fn run_project_issue_tracker_sync(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<IssueTrackerSyncSummary>>, ApiError> {
+    let Some(connection) =
+        IssueTrackerConnection::find_by_project_id(&deployment.db().pool, project.id).await?
+    else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "This project is not connected to an issue tracker",
+        )));
+    };
+    match issue_tracker::sync_project(deployment.db(), &connection).await {
+        Ok(summary) => Ok(ResponseJson(ApiResponse::success(summary))),
+        Err(IssueTrackerError::Database(e)) => Err(e.into()),
+        Err(e) => Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    }
+}
+
 #[derive(serde::Deserialize)]
 pub struct OpenEditorRequest {
     editor_type: Option<String>,
@@ -299,6 +914,29 @@ pub struct OpenEditorResponse {
     pub url: Option<String>,
 }
 
+/// Stop every running or queued execution process of the project's tasks
+pub async fn stop_project_processes(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<StopSummary>>, ApiError> {
+    let summary = deployment
+        .container()
+        .stop_project_processes(project.id)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_processes_stopped",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "stopped": summary.stopped.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
 pub async fn open_project_in_editor(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -534,6 +1172,253 @@ async fn search_files_in_repo(
     Ok(results)
 }
 
+/// Most approval decisions returned when no smaller `limit` is given
+const MAX_APPROVAL_DECISIONS: u32 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ApprovalDecisionQuery {
+    /// Maximum number of decisions to return; defaults to 100
+    pub limit: Option<u32>,
+}
+
+/// Audit trail of the decisions on the tool calls of the project's coding agents, newest first
+pub async fn get_project_approval_decisions(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ApprovalDecisionQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<ApprovalDecision>>>, ApiError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_APPROVAL_DECISIONS);
+    let decisions =
+        ApprovalDecision::find_by_project_id(&deployment.db().pool, project.id, limit.into())
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(decisions)))
+}
+
+pub async fn get_project_merge_queue(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<MergeQueueEntry>>>, ApiError> {
+    let entries = MergeQueueEntry::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+/// Most processes returned per page when no smaller `limit` is given
+const MAX_EXECUTION_PROCESS_PAGE: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ExecutionProcessSearchQuery {
+    pub status: Option<ExecutionProcessStatus>,
+    pub run_reason: Option<ExecutionProcessRunReason>,
+    /// Only processes started at or after this time
+    pub started_after: Option<DateTime<Utc>>,
+    /// Only processes started at or before this time
+    pub started_before: Option<DateTime<Utc>>,
+    pub executor: Option<BaseCodingAgent>,
+    pub exit_code: Option<i64>,
+    /// Index of the first process to return
+    #[serde(default)]
+    pub offset: u32,
+    /// Maximum number of processes to return; defaults to 50
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ExecutionProcessPage {
+    pub processes: Vec<ExecutionProcessSearchResult>,
+    /// Number of matching processes across all pages
+    pub total: i64,
+}
+
+/// Search the execution processes of all the project's attempts, e.g. for failed setup
+/// scripts in the last week
+pub async fn search_project_execution_processes(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExecutionProcessSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcessPage>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let filter = ExecutionProcessFilter {
+        status: query.status,
+        run_reason: query.run_reason,
+        started_after: query.started_after,
+        started_before: query.started_before,
+        executor: query.executor.map(|executor| executor.to_string()),
+        exit_code: query.exit_code,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(50)
+        .clamp(1, MAX_EXECUTION_PROCESS_PAGE);
+    let processes =
+        ExecutionProcess::search(pool, project.id, &filter, limit.into(), query.offset.into())
+            .await?;
+    let total = ExecutionProcess::count_matching(pool, project.id, &filter).await?;
+    Ok(ResponseJson(ApiResponse::success(ExecutionProcessPage {
+        processes,
+        total,
+    })))
+}
+
+/// Most tasks returned by a task search
+const MAX_TASK_SEARCH_RESULTS: u32 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct TaskSearchQuery {
+    /// Words that must all appear in the task, its comments or its tags
+    pub q: String,
+    /// Maximum number of tasks to return; defaults to 20
+    pub limit: Option<u32>,
+}
+
+/// Find the project's tasks by their title, description, comments and tags, best match first
+pub async fn search_project_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskSearchResult>>>, ApiError> {
+    let fts_query = match log_search::fts_query(&query.q) {
+        Ok(fts_query) => fts_query,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_TASK_SEARCH_RESULTS);
+    let results =
+        TaskSearch::search(&deployment.db().pool, project.id, &fts_query, limit.into()).await?;
+    Ok(ResponseJson(ApiResponse::success(results)))
+}
+
+/// Create tasks from the rows of a CSV or JSON backlog, or with `dry_run` only report which
+/// rows would be imported or skipped
+pub async fn import_project_tasks(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<TaskImportRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskImportSummary>>, ApiError> {
+    let summary = match task_import::import(&deployment.db().pool, project.id, &payload).await {
+        Ok(summary) => summary,
+        Err(TaskImportError::Database(e)) => return Err(e.into()),
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+
+    if !summary.dry_run {
+        deployment
+            .track_if_analytics_allowed(
+                "tasks_imported",
+                serde_json::json!({
+                    "project_id": project.id.to_string(),
+                    "created": summary.created,
+                    "skipped": summary.skipped.len(),
+                }),
+            )
+            .await;
+    }
+
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectExportQuery {
+    /// Also include the stored logs of every execution process
+    #[serde(default)]
+    pub include_logs: bool,
+}
+
+/// Download the project's tasks, attempts and execution processes as a zip archive that
+/// [`import_project`] can read back
+pub async fn export_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProjectExportQuery>,
+) -> Result<Response, ApiError> {
+    let bytes =
+        project_archive::export(&deployment.db().pool, &project, query.include_logs).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_exported",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "include_logs": query.include_logs,
+            }),
+        )
+        .await;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}\"",
+                    project_archive::file_name(&project)
+                ),
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectImportQuery {
+    /// Existing git repository the imported project works in
+    pub git_repo_path: String,
+    /// Defaults to the exported project's name
+    pub name: Option<String>,
+}
+
+/// Create a project from an archive made by [`export_project`], sent as the request body
+pub async fn import_project(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProjectImportQuery>,
+    body: Bytes,
+) -> Result<ResponseJson<ApiResponse<ProjectImportSummary>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let path = std::path::absolute(expand_tilde(&query.git_repo_path))?;
+    if !path.is_dir() || !path.join(".git").exists() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "The specified directory is not a git repository",
+        )));
+    }
+    if Project::find_by_git_repo_path(pool, path.to_string_lossy().as_ref())
+        .await?
+        .is_some()
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "A project with this git repository path already exists",
+        )));
+    }
+    let name = query.name.filter(|name| !name.trim().is_empty());
+
+    let summary = match project_archive::import(
+        pool,
+        &body,
+        path.to_string_lossy().to_string(),
+        name,
+    )
+    .await
+    {
+        Ok(summary) => summary,
+        Err(e @ (ProjectArchiveError::Invalid(_) | ProjectArchiveError::UnsupportedVersion(_))) => {
+            return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_imported",
+            serde_json::json!({
+                "project_id": summary.project.id.to_string(),
+                "tasks": summary.tasks,
+                "task_attempts": summary.task_attempts,
+                "logs": summary.logs,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -542,7 +1427,88 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         )
         .route("/branches", get(get_project_branches))
         .route("/search", get(search_project_files))
+        .route("/search/tasks", get(search_project_tasks))
+        .route("/tasks/import", post(import_project_tasks))
+        .route("/export", get(export_project))
         .route("/open-editor", post(open_project_in_editor))
+        .route("/stop", post(stop_project_processes))
+        .route("/attempt-report", get(get_project_attempt_report))
+        .route("/execution-metrics", get(get_project_execution_metrics))
+        .route(
+            "/repo/health",
+            get(get_project_repo_health).post(repair_project_repo),
+        )
+        .route(
+            "/deploy-hook",
+            get(get_project_deploy_hook)
+                .put(upsert_project_deploy_hook)
+                .delete(delete_project_deploy_hook),
+        )
+        .route(
+            "/inbox",
+            get(get_project_task_inbox)
+                .put(upsert_project_task_inbox)
+                .delete(delete_project_task_inbox),
+        )
+        .route(
+            "/github-issues",
+            get(get_project_github_issue_sync)
+                .put(upsert_project_github_issue_sync)
+                .delete(delete_project_github_issue_sync),
+        )
+        .route("/github-issues/sync", post(run_project_github_issue_sync))
+        .route(
+            "/issue-tracker",
+            get(get_project_issue_tracker)
+                .put(upsert_project_issue_tracker)
+                .delete(delete_project_issue_tracker),
+        )
+        .route("/issue-tracker/sync", post(run_project_issue_tracker_sync))
+        .route("/merge-queue", get(get_project_merge_queue))
+        .route("/approval-decisions", get(get_project_approval_decisions))
+        .route(
+            "/execution_processes",
+            get(search_project_execution_processes),
+        )
+        .route(
+            "/env",
+            get(get_project_env_vars).put(upsert_project_env_var),
+        )
+        .route("/env/{key}", delete(delete_project_env_var))
+        .route(
+            "/columns",
+            get(columns::get_project_columns).post(columns::create_project_column),
+        )
+        .route("/columns/reorder", post(columns::reorder_project_columns))
+        .route(
+            "/wip-limits",
+            get(get_project_wip_limits).put(upsert_project_wip_limit),
+        )
+        .route("/wip-limits/{status}", delete(delete_project_wip_limit))
+        .route(
+            "/columns/{column_id}",
+            put(columns::update_project_column).delete(columns::delete_project_column),
+        )
+        .route(
+            "/chat-channels",
+            get(chat_channels::get_chat_channels).post(chat_channels::create_chat_channel),
+        )
+        .route(
+            "/chat-channels/{channel_id}",
+            put(chat_channels::update_chat_channel).delete(chat_channels::delete_chat_channel),
+        )
+        .route(
+            "/chat-channels/{channel_id}/test",
+            post(chat_channels::test_chat_channel),
+        )
+        .route(
+            "/setup-cache",
+            get(get_project_setup_cache).delete(clear_project_setup_cache),
+        )
+        .route(
+            "/setup-cache/{key}",
+            delete(delete_project_setup_cache_entry),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -550,6 +1516,11 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let projects_router = Router::new()
         .route("/", get(get_projects).post(create_project))
+        .route("/clone", post(clone_project))
+        .route(
+            "/import",
+            post(import_project).layer(DefaultBodyLimit::max(512 * 1024 * 1024)),
+        )
         .nest("/{id}", project_id_router);
 
     Router::new().nest("/projects", projects_router)
@@ -0,0 +1,107 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    chat_channel::{ChatChannel, CreateChatChannel, UpdateChatChannel},
+    project::Project,
+};
+use deployment::Deployment;
+use services::services::chat_notifier::{self, ChatNotifierError};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+async fn find_channel(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    channel_id: Uuid,
+) -> Result<ChatChannel, ApiError> {
+    ChatChannel::find_by_id_and_project_id(&deployment.db().pool, channel_id, project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))
+}
+
+/// Slack and Discord channels the project posts agent runs and approval requests to
+pub async fn get_chat_channels(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ChatChannel>>>, ApiError> {
+    let channels = ChatChannel::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(channels)))
+}
+
+pub async fn create_chat_channel(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateChatChannel>,
+) -> Result<ResponseJson<ApiResponse<ChatChannel>>, ApiError> {
+    let channel = match chat_notifier::create(&deployment.db().pool, project.id, &payload).await {
+        Ok(channel) => channel,
+        Err(ChatNotifierError::Database(e)) => return Err(e.into()),
+        Err(ChatNotifierError::Secret(e)) => return Err(e.into()),
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "chat_channel_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "kind": channel.kind,
+                "events": channel.events.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(channel)))
+}
+
+pub async fn update_chat_channel(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, channel_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateChatChannel>,
+) -> Result<ResponseJson<ApiResponse<ChatChannel>>, ApiError> {
+    let channel = find_channel(&deployment, project.id, channel_id).await?;
+    let updated = match chat_notifier::update(&deployment.db().pool, &channel, &payload).await {
+        Ok(updated) => updated,
+        Err(ChatNotifierError::Database(e)) => return Err(e.into()),
+        Err(ChatNotifierError::Secret(e)) => return Err(e.into()),
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_chat_channel(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, channel_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let channel = find_channel(&deployment, project.id, channel_id).await?;
+    ChatChannel::delete(&deployment.db().pool, channel.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Post a test message so the user can check the channel is set up right
+pub async fn test_chat_channel(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, channel_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let channel = find_channel(&deployment, project.id, channel_id).await?;
+    let message = format!(
+        "Vibe Kanban will post updates about {} to this channel",
+        project.name
+    );
+    match chat_notifier::post(&channel, &message).await {
+        Ok(()) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(ChatNotifierError::Secret(e)) => Err(e.into()),
+        Err(e) => Ok(ResponseJson(ApiResponse::error(&format!(
+            "Failed to post to the channel: {e}"
+        )))),
+    }
+}
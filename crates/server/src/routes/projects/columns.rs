@@ -0,0 +1,130 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    project::Project,
+    project_column::{CreateProjectColumn, ProjectColumn, UpdateProjectColumn},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ReorderProjectColumnsRequest {
+    /// All of the project's columns, left to right
+    pub column_ids: Vec<Uuid>,
+}
+
+async fn find_column(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    column_id: Uuid,
+) -> Result<ProjectColumn, ApiError> {
+    ProjectColumn::find_by_id_and_project_id(&deployment.db().pool, column_id, project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))
+}
+
+/// The project's board columns, left to right
+pub async fn get_project_columns(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectColumn>>>, ApiError> {
+    let columns = ProjectColumn::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(columns)))
+}
+
+pub async fn create_project_column(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(mut payload): Json<CreateProjectColumn>,
+) -> Result<ResponseJson<ApiResponse<ProjectColumn>>, ApiError> {
+    payload.name = payload.name.trim().to_string();
+    if payload.name.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("Column name is required")));
+    }
+
+    let column = ProjectColumn::create(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_column_created",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "status": column.status.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(column)))
+}
+
+pub async fn update_project_column(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, column_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectColumn>,
+) -> Result<ResponseJson<ApiResponse<ProjectColumn>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let existing = find_column(&deployment, project.id, column_id).await?;
+
+    let name = match payload.name {
+        Some(name) if name.trim().is_empty() => {
+            return Ok(ResponseJson(ApiResponse::error("Column name is required")));
+        }
+        Some(name) => name.trim().to_string(),
+        None => existing.name,
+    };
+    let status = payload.status.unwrap_or(existing.status.clone());
+    if status != existing.status
+        && ProjectColumn::count_by_status(pool, project.id, existing.status.clone()).await? <= 1
+    {
+        return Err(ApiError::Conflict(format!(
+            "This is the only column with status '{}'",
+            existing.status
+        )));
+    }
+
+    let column = ProjectColumn::update(pool, column_id, name, status).await?;
+    Ok(ResponseJson(ApiResponse::success(column)))
+}
+
+/// Delete a column. Its tasks move to the next column with the same status, so a status's
+/// last column can't be deleted.
+pub async fn delete_project_column(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, column_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let column = find_column(&deployment, project.id, column_id).await?;
+
+    let Some(fallback) = ProjectColumn::find_fallback(pool, &column).await? else {
+        return Err(ApiError::Conflict(format!(
+            "This is the only column with status '{}'",
+            column.status
+        )));
+    };
+
+    ProjectColumn::delete(pool, column.id, fallback.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Persist the left-to-right order of the project's columns
+pub async fn reorder_project_columns(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderProjectColumnsRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectColumn>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    ProjectColumn::reorder(pool, project.id, &payload.column_ids).await?;
+    let columns = ProjectColumn::find_by_project_id(pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(columns)))
+}
@@ -0,0 +1,109 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::prompt_snippet::{CreatePromptSnippet, PromptSnippet, UpdatePromptSnippet};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::prompt_snippets;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_prompt_snippet_middleware};
+
+#[derive(Debug, Deserialize)]
+pub struct PromptSnippetQuery {
+    /// Include this project's snippets alongside the global ones
+    pub project_id: Option<Uuid>,
+}
+
+fn invalid_name(name: &str) -> ResponseJson<ApiResponse<PromptSnippet>> {
+    ResponseJson(ApiResponse::error(&format!(
+        "Invalid snippet name '{name}': use lowercase letters, digits, '-' and '_'"
+    )))
+}
+
+pub async fn get_prompt_snippets(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<PromptSnippetQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PromptSnippet>>>, ApiError> {
+    let snippets = PromptSnippet::find_all(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(snippets)))
+}
+
+pub async fn get_prompt_snippet(
+    Extension(snippet): Extension<PromptSnippet>,
+) -> Result<ResponseJson<ApiResponse<PromptSnippet>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(snippet)))
+}
+
+pub async fn create_prompt_snippet(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreatePromptSnippet>,
+) -> Result<ResponseJson<ApiResponse<PromptSnippet>>, ApiError> {
+    if !prompt_snippets::is_valid_name(&payload.name) {
+        return Ok(invalid_name(&payload.name));
+    }
+    let snippet = PromptSnippet::create(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "prompt_snippet_created",
+            serde_json::json!({
+                "snippet_id": snippet.id.to_string(),
+                "global": snippet.project_id.is_none(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(snippet)))
+}
+
+pub async fn update_prompt_snippet(
+    Extension(snippet): Extension<PromptSnippet>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdatePromptSnippet>,
+) -> Result<ResponseJson<ApiResponse<PromptSnippet>>, ApiError> {
+    if let Some(name) = payload.name.as_deref()
+        && !prompt_snippets::is_valid_name(name)
+    {
+        return Ok(invalid_name(name));
+    }
+    let updated = PromptSnippet::update(&deployment.db().pool, snippet.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_prompt_snippet(
+    Extension(snippet): Extension<PromptSnippet>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = PromptSnippet::delete(&deployment.db().pool, snippet.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let snippet_router = Router::new()
+        .route(
+            "/",
+            get(get_prompt_snippet)
+                .put(update_prompt_snippet)
+                .delete(delete_prompt_snippet),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_prompt_snippet_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_prompt_snippets).post(create_prompt_snippet))
+        .nest("/{snippet_id}", snippet_router);
+
+    Router::new().nest("/prompt-snippets", inner)
+}
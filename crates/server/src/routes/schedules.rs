@@ -0,0 +1,198 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use chrono::Utc;
+use db::models::{
+    project::Project,
+    tag::Tag,
+    task::Task,
+    task_attempt::TaskAttempt,
+    task_schedule::{CreateTaskSchedule, TaskSchedule, UpdateTaskSchedule},
+};
+use deployment::Deployment;
+use executors::profile::ExecutorConfigs;
+use serde::Deserialize;
+use services::services::{container::ContainerService, task_schedule};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_task_schedule_middleware};
+
+#[derive(Debug, Deserialize)]
+pub struct TaskScheduleQuery {
+    pub project_id: Uuid,
+}
+
+pub async fn get_task_schedules(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TaskScheduleQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskSchedule>>>, ApiError> {
+    let schedules =
+        TaskSchedule::find_by_project_id(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(schedules)))
+}
+
+pub async fn get_task_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(schedule)))
+}
+
+pub async fn create_task_schedule(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskSchedule>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    let pool = &deployment.db().pool;
+    ExecutorConfigs::get_cached().validate_profile_id(&payload.executor_profile_id)?;
+    let project = Project::find_by_id(pool, payload.project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let cron = match task_schedule::validate_source(payload.tag_id, payload.task_id)
+        .and_then(|_| task_schedule::parse(&payload.cron_expression))
+    {
+        Ok(cron) => cron,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    if let Some(tag_id) = payload.tag_id
+        && Tag::find_by_id(pool, tag_id).await?.is_none()
+    {
+        return Ok(ResponseJson(ApiResponse::error("Tag not found")));
+    }
+    if let Some(task_id) = payload.task_id
+        && Task::find_by_id_and_project_id(pool, task_id, project.id)
+            .await?
+            .is_none()
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Task not found in this project",
+        )));
+    }
+
+    let next_run_at = payload
+        .enabled
+        .then(|| task_schedule::next_run(&cron, project.timezone.as_deref(), Utc::now()))
+        .flatten();
+    let schedule = TaskSchedule::create(pool, &payload, next_run_at).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_schedule_created",
+            serde_json::json!({
+                "schedule_id": schedule.id.to_string(),
+                "project_id": schedule.project_id.to_string(),
+                "runs_existing_task": schedule.task_id.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(schedule)))
+}
+
+pub async fn update_task_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateTaskSchedule>,
+) -> Result<ResponseJson<ApiResponse<TaskSchedule>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let executor_profile_id = payload
+        .executor_profile_id
+        .unwrap_or(schedule.executor_profile_id.0);
+    ExecutorConfigs::get_cached().validate_profile_id(&executor_profile_id)?;
+    let cron_expression = payload.cron_expression.unwrap_or(schedule.cron_expression);
+    let cron = match task_schedule::parse(&cron_expression) {
+        Ok(cron) => cron,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    let project = Project::find_by_id(pool, schedule.project_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    let enabled = payload.enabled.unwrap_or(schedule.enabled);
+    let next_run_at = enabled
+        .then(|| task_schedule::next_run(&cron, project.timezone.as_deref(), Utc::now()))
+        .flatten();
+    let updated = TaskSchedule::update(
+        pool,
+        schedule.id,
+        payload.name.as_deref().unwrap_or(&schedule.name),
+        &cron_expression,
+        &executor_profile_id,
+        payload
+            .base_branch
+            .as_deref()
+            .unwrap_or(&schedule.base_branch),
+        enabled,
+        next_run_at,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_task_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = TaskSchedule::delete(&deployment.db().pool, schedule.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(SqlxError::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Start a run now, without waiting for the schedule or moving its next run
+pub async fn run_task_schedule(
+    Extension(schedule): Extension<TaskSchedule>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let task_attempt = deployment
+        .container()
+        .start_scheduled_run(&schedule)
+        .await?;
+    TaskSchedule::record_run(
+        &deployment.db().pool,
+        schedule.id,
+        schedule.next_run_at,
+        Some(task_attempt.id),
+        None,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_schedule_run_manually",
+            serde_json::json!({
+                "schedule_id": schedule.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task_attempt)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let schedule_router = Router::new()
+        .route(
+            "/",
+            get(get_task_schedule)
+                .put(update_task_schedule)
+                .delete(delete_task_schedule),
+        )
+        .route("/run", post(run_task_schedule))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_task_schedule_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_task_schedules).post(create_task_schedule))
+        .nest("/{schedule_id}", schedule_router);
+
+    Router::new().nest("/schedules", inner)
+}
@@ -0,0 +1,164 @@
+//! Read-only views of an attempt for people holding one of its share links, who don't use the
+//! app themselves
+
+use std::collections::HashMap;
+
+use axum::{
+    Extension, Router,
+    extract::{Path, Request, State},
+    http::header,
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get},
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{
+        ExecutionProcess, ExecutionProcessError, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::{
+    container::ContainerService,
+    log_export,
+    share_link::{ShareLinkError, ShareLinkSigner},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts};
+
+#[derive(Debug, Serialize, TS)]
+pub struct SharedExecutionProcess {
+    pub id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    pub status: ExecutionProcessStatus,
+    pub exit_code: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<ExecutionProcess> for SharedExecutionProcess {
+    fn from(process: ExecutionProcess) -> Self {
+        Self {
+            id: process.id,
+            run_reason: process.run_reason,
+            status: process.status,
+            exit_code: process.exit_code,
+            started_at: process.started_at,
+            completed_at: process.completed_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct SharedAttempt {
+    pub task_title: String,
+    pub task_description: Option<String>,
+    pub branch: String,
+    pub target_branch: String,
+    pub executor: String,
+    pub created_at: DateTime<Utc>,
+    pub processes: Vec<SharedExecutionProcess>,
+}
+
+/// Load the attempt the share link in the path grants access to
+async fn load_shared_attempt_middleware(
+    State(deployment): State<DeploymentImpl>,
+    Path(params): Path<HashMap<String, String>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let token = params
+        .get("token")
+        .ok_or(ApiError::ShareLink(ShareLinkError::Invalid))?;
+    let task_attempt_id = ShareLinkSigner::global()?.verify(token, Utc::now())?;
+    // Links outlive attempts deleted after they were shared
+    let task_attempt = TaskAttempt::find_by_id(&deployment.db().pool, task_attempt_id)
+        .await?
+        .ok_or(ApiError::ShareLink(ShareLinkError::Invalid))?;
+    request.extensions_mut().insert(task_attempt);
+    Ok(next.run(request).await)
+}
+
+pub async fn get_shared_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SharedAttempt>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false)
+        .await?
+        .into_iter()
+        .map(SharedExecutionProcess::from)
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(SharedAttempt {
+        task_title: task.title,
+        task_description: task.description,
+        branch: task_attempt.branch,
+        target_branch: task_attempt.target_branch,
+        executor: task_attempt.executor,
+        created_at: task_attempt.created_at,
+        processes,
+    })))
+}
+
+/// The output of one of the attempt's processes as plain text
+pub async fn get_shared_process_logs(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, process_id)): Path<(String, Uuid)>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let process = ExecutionProcess::find_by_id(pool, process_id)
+        .await?
+        .filter(|process| process.task_attempt_id == task_attempt.id)
+        .ok_or(ApiError::ExecutionProcess(
+            ExecutionProcessError::ExecutionProcessNotFound,
+        ))?;
+    let in_memory = deployment
+        .container()
+        .get_msg_store_by_id(&process.id)
+        .await
+        .map(|store| store.get_history());
+    let history = log_export::load_history(pool, process.id, in_memory).await?;
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; charset=utf-8".to_string(),
+        )],
+        log_export::raw_text(&history, None),
+    )
+        .into_response())
+}
+
+/// Revoke every share link created so far
+pub async fn revoke_share_links() -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ShareLinkSigner::global()?.rotate()?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let shared_attempt_router = Router::new()
+        .route("/", get(get_shared_attempt))
+        .route(
+            "/diff/files",
+            get(task_attempts::get_task_attempt_diff_files),
+        )
+        .route("/diff/file", get(task_attempts::get_task_attempt_diff_file))
+        .route("/processes/{process_id}/logs", get(get_shared_process_logs))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_shared_attempt_middleware,
+        ));
+
+    Router::new()
+        .route("/share-links", delete(revoke_share_links))
+        .nest("/shared/{token}", shared_attempt_router)
+}
@@ -1,15 +1,23 @@
+use std::collections::HashMap;
+
 use axum::{
     Extension, Json, Router,
     extract::{Query, State},
     middleware::from_fn_with_state,
     response::Json as ResponseJson,
-    routing::get,
+    routing::{get, post},
+};
+use db::models::{
+    project::Project,
+    tag::{CreateTag, Tag, UpdateTag},
+    task::{CreateTask, Task},
 };
-use db::models::tag::{CreateTag, Tag, UpdateTag};
 use deployment::Deployment;
 use serde::Deserialize;
+use services::services::task_template;
 use ts_rs::TS;
 use utils::response::ApiResponse;
+use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_tag_middleware};
 
@@ -44,6 +52,9 @@ pub async fn create_tag(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTag>,
 ) -> Result<ResponseJson<ApiResponse<Tag>>, ApiError> {
+    if let Err(e) = task_template::validate_declarations(&payload.variables) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
     let tag = Tag::create(&deployment.db().pool, &payload).await?;
 
     deployment
@@ -64,6 +75,11 @@ pub async fn update_tag(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<UpdateTag>,
 ) -> Result<ResponseJson<ApiResponse<Tag>>, ApiError> {
+    if let Some(variables) = &payload.variables
+        && let Err(e) = task_template::validate_declarations(variables)
+    {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
     let updated_tag = Tag::update(&deployment.db().pool, tag.id, &payload).await?;
 
     deployment
@@ -91,9 +107,66 @@ pub async fn delete_tag(
     }
 }
 
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskFromTemplate {
+    pub project_id: Uuid,
+    /// May contain the tag's `{{name}}` placeholders
+    pub title: String,
+    /// Values of the tag's variables by name; variables left out use their default
+    #[serde(default)]
+    #[ts(optional)]
+    pub values: HashMap<String, String>,
+}
+
+/// Create a task whose description is the tag's content, with its variables filled in. The
+/// task gets the tag.
+pub async fn create_task_from_tag(
+    Extension(tag): Extension<Tag>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskFromTemplate>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if Project::find_by_id(pool, payload.project_id)
+        .await?
+        .is_none()
+    {
+        return Ok(ResponseJson(ApiResponse::error("Project not found")));
+    }
+    let values = match task_template::resolve(&tag.variables, &payload.values) {
+        Ok(values) => values,
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    let title = task_template::render(payload.title.trim(), &values);
+    if title.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("A title is required")));
+    }
+
+    let create = CreateTask::from_title_description(
+        payload.project_id,
+        title,
+        Some(task_template::render(&tag.content, &values)),
+    );
+    let task = Task::create(pool, &create, Uuid::new_v4()).await?;
+    Task::set_tags(pool, task.id, vec![tag.id]).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_created_from_template",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "tag_id": tag.id.to_string(),
+                "variable_count": tag.variables.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let tag_router = Router::new()
         .route("/", get(get_tag).put(update_tag).delete(delete_tag))
+        .route("/create-task", post(create_task_from_tag))
         .layer(from_fn_with_state(deployment.clone(), load_tag_middleware));
 
     let inner = Router::new()
@@ -1,57 +1,112 @@
+pub mod bulk_rerun;
+pub mod checkpoints;
+pub mod comparisons;
+pub mod dev_server;
 pub mod drafts;
+pub mod files;
+pub mod merge_queue;
+pub mod terminal;
 pub mod util;
 
+use std::collections::HashMap;
+
 use axum::{
     Extension, Json, Router,
     extract::{
         Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{StatusCode, header},
     middleware::from_fn_with_state,
-    response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{get, post, put},
 };
 use db::models::{
+    audit_log::AuditAction,
+    base_branch_sync::BaseBranchSync,
     draft::{Draft, DraftType},
-    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
-    merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+    execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessRunReason},
+    github_issue::GitHubIssueLink,
+    merge::{Merge, MergeStatus, MergeStrategy, PrMerge, PullRequestInfo},
     project::{Project, ProjectError},
     task::{Task, TaskRelationships, TaskStatus},
-    task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+    task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError, UpdateAttemptReview},
+    task_attempt_diff_stats::{AttemptDiffStats, TaskAttemptDiffStats},
+    test_run_result::TestRunResult,
+    vulnerability_finding::VulnerabilityFinding,
+    workflow_run::WorkflowRun,
 };
 use deployment::Deployment;
 use executors::{
     actions::{
-        ExecutorAction, ExecutorActionType,
-        coding_agent_follow_up::CodingAgentFollowUpRequest,
-        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
     },
-    profile::ExecutorProfileId,
+    profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
+    attribution,
+    audit_log::AuditEvent,
     container::ContainerService,
-    git::{ConflictOp, WorktreeResetOptions},
-    github_service::{CreatePrRequest, GitHubService, GitHubServiceError},
+    dependency_diff::{self, LockfileDependencyDiff},
+    deploy_hook::{DeployHookEvent, DeployHookService},
+    dev_server::DevServerStatus,
+    git::{Commit, ConflictOp, GitService, GitServiceError, WorktreeResetOptions},
+    github_issue_sync,
+    github_service::{CreatePrRequest, CreateReleaseRequest, GitHubService, GitHubServiceError},
+    log_export,
+    merge_queue::{merge_commit_message, vulnerability_block},
+    prompt_snippets, public_url,
+    share_link::{CreateShareLink, ShareLink, ShareLinkSigner},
+    vulnerability_scan::{VulnerabilityScanError, VulnerabilityScanService},
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{diff::Diff, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{
     DeploymentImpl,
     error::ApiError,
-    middleware::load_task_attempt_middleware,
-    routes::task_attempts::util::{ensure_worktree_path, handle_images_for_prompt},
+    middleware::{RequestOrigin, load_task_attempt_middleware},
+    routes::{
+        task_attempts::util::{ensure_worktree_path, handle_images_for_prompt},
+        tasks::with_wip_warning,
+    },
 };
 
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct RebaseTaskAttemptRequest {
     pub old_base_branch: Option<String>,
     pub new_base_branch: Option<String>,
+    /// Stash uncommitted changes to tracked files before rebasing and reapply them afterwards
+    #[serde(default)]
+    #[ts(optional)]
+    pub auto_stash: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct CherryPickRequest {
+    /// Attempt whose commits should be applied to this attempt's branch
+    pub source_attempt_id: Uuid,
+    /// Commits to apply, oldest first. Defaults to every commit on the source attempt's branch
+    #[serde(default)]
+    #[ts(optional)]
+    pub commits: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProtectedBranchOverride {
+    /// Write to the branch even if it matches one of the project's protected patterns
+    #[serde(default)]
+    pub allow_protected: bool,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct CherryPickResult {
+    pub head_oid: String,
+    pub applied_commits: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -60,6 +115,7 @@ pub struct RebaseTaskAttemptRequest {
 pub enum GitOperationError {
     MergeConflicts { message: String, op: ConflictOp },
     RebaseInProgress,
+    StashConflict { message: String },
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -92,6 +148,51 @@ pub struct CreateGitHubPrRequest {
     pub target_branch: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct CreateTagRequest {
+    pub tag_name: String,
+    /// Tag annotation; defaults to the task title
+    pub message: Option<String>,
+    /// Sign the tag with the configured commit signing key
+    #[serde(default)]
+    #[ts(optional)]
+    pub sign: Option<bool>,
+    /// Push the tag to the project's base remote
+    #[serde(default)]
+    #[ts(optional)]
+    pub push: Option<bool>,
+    /// Publish a GitHub release for the tag; the tag is pushed first
+    #[serde(default)]
+    #[ts(optional)]
+    pub release: Option<CreateReleaseOptions>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct CreateReleaseOptions {
+    /// Release title; defaults to the tag name
+    pub name: Option<String>,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct CreateTagResponse {
+    pub tag_name: String,
+    pub commit_sha: String,
+    pub pushed: bool,
+    pub release_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct DispatchWorkflowRequest {
+    /// Workflow file name (e.g. `ci.yml`) or numeric workflow id
+    pub workflow_file: String,
+    pub inputs: Option<HashMap<String, String>>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct FollowUpResponse {
     pub message: String,
@@ -108,22 +209,112 @@ pub struct TaskAttemptQuery {
 pub struct DiffStreamQuery {
     #[serde(default)]
     pub stats_only: bool,
+    /// Only show the changes made by this execution process
+    #[serde(default)]
+    pub execution_process_id: Option<Uuid>,
+    /// Only show the changes between these two commits
+    #[serde(default)]
+    pub from_commit: Option<String>,
+    #[serde(default)]
+    pub to_commit: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffFilesQuery {
+    #[serde(default)]
+    pub execution_process_id: Option<Uuid>,
+    #[serde(default)]
+    pub from_commit: Option<String>,
+    #[serde(default)]
+    pub to_commit: Option<String>,
+    /// Index of the first file to return
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of files to return; all remaining files when unset
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffFileQuery {
+    pub path: String,
+    #[serde(default)]
+    pub execution_process_id: Option<Uuid>,
+    #[serde(default)]
+    pub from_commit: Option<String>,
+    #[serde(default)]
+    pub to_commit: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DependencyDiffQuery {
+    #[serde(default)]
+    pub execution_process_id: Option<Uuid>,
+    #[serde(default)]
+    pub from_commit: Option<String>,
+    #[serde(default)]
+    pub to_commit: Option<String>,
+}
+
+/// One page of an attempt's changed files, with stats but without contents
+#[derive(Debug, Serialize, TS)]
+pub struct DiffFilePage {
+    pub files: Vec<Diff>,
+    /// Number of changed files across all pages
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct TaskAttemptSummary {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub attempt: TaskAttempt,
+    /// Size of the attempt's changes as of its last finished execution process, unset until one
+    /// has finished
+    pub diff_stats: Option<AttemptDiffStats>,
 }
 
 pub async fn get_task_attempts(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskAttemptQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<TaskAttempt>>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<Vec<TaskAttemptSummary>>>, ApiError> {
     let pool = &deployment.db().pool;
     let attempts = TaskAttempt::fetch_all(pool, query.task_id).await?;
-    Ok(ResponseJson(ApiResponse::success(attempts)))
+    let diff_stats: HashMap<Uuid, AttemptDiffStats> =
+        TaskAttemptDiffStats::find_by_task_id(pool, query.task_id)
+            .await?
+            .into_iter()
+            .map(|row| (row.task_attempt_id, row.stats()))
+            .collect();
+    let summaries = attempts
+        .into_iter()
+        .map(|attempt| TaskAttemptSummary {
+            diff_stats: diff_stats.get(&attempt.id).copied(),
+            attempt,
+        })
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(summaries)))
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct TaskAttemptDetails {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub attempt: TaskAttempt,
+    /// Address of the attempt's dev server while it is running
+    pub dev_server_url: Option<String>,
 }
 
 pub async fn get_task_attempt(
     Extension(task_attempt): Extension<TaskAttempt>,
-    State(_deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
-    Ok(ResponseJson(ApiResponse::success(task_attempt)))
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskAttemptDetails>>, ApiError> {
+    let dev_server =
+        DevServerStatus::for_task_attempt(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(TaskAttemptDetails {
+        attempt: task_attempt,
+        dev_server_url: dev_server.url,
+    })))
 }
 
 #[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
@@ -150,7 +341,31 @@ pub async fn create_task_attempt(
     let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
+    let wip_warning = deployment.container().check_wip_limit(&task).await?;
+
+    let task_attempt = start_new_attempt(
+        &deployment,
+        &task,
+        executor_profile_id,
+        &payload.base_branch,
+    )
+    .await?;
 
+    Ok(ResponseJson(with_wip_warning(task_attempt, wip_warning)))
+}
+
+/// Create a new attempt for `task` on a fresh branch and start it
+async fn start_new_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    executor_profile_id: ExecutorProfileId,
+    base_branch: &str,
+) -> Result<TaskAttempt, ApiError> {
+    ExecutorConfigs::get_cached().validate_profile_id(&executor_profile_id)?;
+    deployment
+        .container()
+        .ensure_task_unblocked(task.id)
+        .await?;
     let attempt_id = Uuid::new_v4();
     let git_branch_name = deployment
         .container()
@@ -161,11 +376,12 @@ pub async fn create_task_attempt(
         &deployment.db().pool,
         &CreateTaskAttempt {
             executor: executor_profile_id.executor,
-            base_branch: payload.base_branch.clone(),
+            base_branch: base_branch.to_string(),
             branch: git_branch_name.clone(),
+            started_by: attribution::current_user(),
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
@@ -188,7 +404,7 @@ pub async fn create_task_attempt(
 
     tracing::info!("Started execution process {}", execution_process.id);
 
-    Ok(ResponseJson(ApiResponse::success(task_attempt)))
+    Ok(task_attempt)
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -196,6 +412,7 @@ pub struct CreateFollowUpAttempt {
     pub prompt: String,
     pub variant: Option<String>,
     pub image_ids: Option<Vec<Uuid>>,
+    pub image_captions: Option<HashMap<Uuid, String>>,
     pub retry_process_id: Option<Uuid>,
     pub force_when_dirty: Option<bool>,
     pub perform_git_reset: Option<bool>,
@@ -222,6 +439,7 @@ pub async fn follow_up(
         executor: initial_executor_profile_id.executor,
         variant: payload.variant,
     };
+    ExecutorConfigs::get_cached().validate_profile_id(&executor_profile_id)?;
 
     // Get parent task
     let task = task_attempt
@@ -272,7 +490,7 @@ pub async fn follow_up(
                 .map(|is_clean| !is_clean)
                 .unwrap_or(false);
 
-            deployment.git().reconcile_worktree_to_commit(
+            let outcome = deployment.git().reconcile_worktree_to_commit(
                 wt,
                 target_oid,
                 WorktreeResetOptions::new(
@@ -282,6 +500,17 @@ pub async fn follow_up(
                     perform_git_reset,
                 ),
             );
+            if outcome.applied {
+                AuditEvent::new(AuditAction::ForceReset, target_oid)
+                    .attempt(task_attempt.id)
+                    .parameters(serde_json::json!({
+                        "reason": "retry",
+                        "process_id": proc_id,
+                        "discarded_uncommitted": is_dirty,
+                    }))
+                    .record(pool)
+                    .await;
+            }
         }
 
         // Stop any running processes for this attempt
@@ -294,21 +523,57 @@ pub async fn follow_up(
         let _ = Draft::clear_after_send(pool, task_attempt.id, DraftType::Retry).await;
     }
 
+    let mut prompt =
+        prompt_snippets::expand_for_project(&deployment.db().pool, project.id, &payload.prompt)
+            .await?;
+    if let Some(image_ids) = &payload.image_ids {
+        prompt = handle_images_for_prompt(
+            &deployment,
+            &task_attempt,
+            task.id,
+            image_ids,
+            &payload.image_captions.clone().unwrap_or_default(),
+            &prompt,
+        )
+        .await?;
+    }
+
+    let execution_process = start_follow_up_execution(
+        &deployment,
+        &task_attempt,
+        &project,
+        prompt,
+        executor_profile_id,
+    )
+    .await?;
+
+    // Clear drafts post-send:
+    // - If this was a retry send, the retry draft has already been cleared above.
+    // - Otherwise, clear the follow-up draft to avoid.
+    if payload.retry_process_id.is_none() {
+        let _ =
+            Draft::clear_after_send(&deployment.db().pool, task_attempt.id, DraftType::FollowUp)
+                .await;
+    }
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
+/// Send `prompt` to the attempt's coding agent, continuing its latest session if it has one
+async fn start_follow_up_execution(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+    project: &Project,
+    prompt: String,
+    executor_profile_id: ExecutorProfileId,
+) -> Result<ExecutionProcess, ApiError> {
     let latest_session_id = ExecutionProcess::find_latest_session_id_by_task_attempt(
         &deployment.db().pool,
         task_attempt.id,
     )
     .await?;
 
-    let mut prompt = payload.prompt;
-    if let Some(image_ids) = &payload.image_ids {
-        prompt = handle_images_for_prompt(&deployment, &task_attempt, task.id, image_ids, &prompt)
-            .await?;
-    }
-
-    let cleanup_action = deployment
-        .container()
-        .cleanup_action(project.cleanup_script);
+    let cleanup_action = deployment.container().cleanup_action(project);
 
     let action_type = if let Some(session_id) = latest_session_id {
         ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
@@ -327,25 +592,14 @@ pub async fn follow_up(
 
     let action = ExecutorAction::new(action_type, cleanup_action);
 
-    let execution_process = deployment
+    Ok(deployment
         .container()
         .start_execution(
-            &task_attempt,
+            task_attempt,
             &action,
             &ExecutionProcessRunReason::CodingAgent,
         )
-        .await?;
-
-    // Clear drafts post-send:
-    // - If this was a retry send, the retry draft has already been cleared above.
-    // - Otherwise, clear the follow-up draft to avoid.
-    if payload.retry_process_id.is_none() {
-        let _ =
-            Draft::clear_after_send(&deployment.db().pool, task_attempt.id, DraftType::FollowUp)
-                .await;
-    }
-
-    Ok(ResponseJson(ApiResponse::success(execution_process)))
+        .await?)
 }
 
 #[axum::debug_handler]
@@ -372,6 +626,31 @@ pub async fn replace_process(
         )));
     }
 
+    // Resolve the executor profile from the original process before changing anything
+    let initial_executor_profile_id = match &process
+        .executor_action()
+        .map_err(|e| ApiError::TaskAttempt(TaskAttemptError::ValidationError(e.to_string())))?
+        .typ
+    {
+        ExecutorActionType::CodingAgentInitialRequest(request) => {
+            Ok(request.executor_profile_id.clone())
+        }
+        ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+            Ok(request.executor_profile_id.clone())
+        }
+        _ => Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "Couldn't find profile from executor action".to_string(),
+        ))),
+    }?;
+
+    let executor_profile_id = ExecutorProfileId {
+        executor: initial_executor_profile_id.executor,
+        variant: payload
+            .variant
+            .or(initial_executor_profile_id.variant.clone()),
+    };
+    ExecutorConfigs::get_cached().validate_profile_id(&executor_profile_id)?;
+
     // Determine target reset OID: before the target process
     let mut target_before_oid = process.before_head_commit.clone();
     if target_before_oid.is_none() {
@@ -400,6 +679,17 @@ pub async fn replace_process(
         );
         git_reset_needed = outcome.needed;
         git_reset_applied = outcome.applied;
+        if outcome.applied {
+            AuditEvent::new(AuditAction::ForceReset, target_oid)
+                .attempt(task_attempt.id)
+                .parameters(serde_json::json!({
+                    "reason": "replace_process",
+                    "process_id": proc_id,
+                    "discarded_uncommitted": is_dirty,
+                }))
+                .record(pool)
+                .await;
+        }
     }
 
     // Stop any running processes for this attempt
@@ -408,30 +698,6 @@ pub async fn replace_process(
     // Soft-drop the target process and all later processes
     let deleted_count = ExecutionProcess::drop_at_and_after(pool, task_attempt.id, proc_id).await?;
 
-    // Build follow-up executor action using the original process profile
-    let initial_executor_profile_id = match &process
-        .executor_action()
-        .map_err(|e| ApiError::TaskAttempt(TaskAttemptError::ValidationError(e.to_string())))?
-        .typ
-    {
-        ExecutorActionType::CodingAgentInitialRequest(request) => {
-            Ok(request.executor_profile_id.clone())
-        }
-        ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-            Ok(request.executor_profile_id.clone())
-        }
-        _ => Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-            "Couldn't find profile from executor action".to_string(),
-        ))),
-    }?;
-
-    let executor_profile_id = ExecutorProfileId {
-        executor: initial_executor_profile_id.executor,
-        variant: payload
-            .variant
-            .or(initial_executor_profile_id.variant.clone()),
-    };
-
     // Use latest session_id from remaining (earlier) processes; if none exists, start a fresh initial request
     let latest_session_id =
         ExecutionProcess::find_latest_session_id_by_task_attempt(pool, task_attempt.id).await?;
@@ -477,36 +743,217 @@ pub async fn replace_process(
     })))
 }
 
+/// Resolve the commit range requested by a diff stream query, if any.
+/// Returns None for the default cumulative worktree-vs-base diff.
+async fn resolve_diff_range(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+    execution_process_id: Option<Uuid>,
+    from_commit: Option<&str>,
+    to_commit: Option<&str>,
+) -> Result<Option<(Commit, Commit)>, ApiError> {
+    let (from_sha, to_sha) = if let Some(execution_process_id) = execution_process_id {
+        let process = ExecutionProcess::find_by_id(&deployment.db().pool, execution_process_id)
+            .await?
+            .filter(|process| process.task_attempt_id == task_attempt.id)
+            .ok_or(ApiError::ExecutionProcess(
+                ExecutionProcessError::ExecutionProcessNotFound,
+            ))?;
+        match (process.before_head_commit, process.after_head_commit) {
+            (Some(before), Some(after)) => (before, after),
+            _ => {
+                return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                    "Execution process has no recorded commits to diff yet".to_string(),
+                )));
+            }
+        }
+    } else {
+        match (from_commit, to_commit) {
+            (None, None) => return Ok(None),
+            (Some(from), Some(to)) => (from.to_string(), to.to_string()),
+            _ => {
+                return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                    "Both from_commit and to_commit are required for a commit range".to_string(),
+                )));
+            }
+        }
+    };
+
+    let parse_commit = |sha: &str| {
+        git2::Oid::from_str(sha).map(Commit::new).map_err(|_| {
+            ApiError::TaskAttempt(TaskAttemptError::ValidationError(format!(
+                "Invalid commit SHA: {sha}"
+            )))
+        })
+    };
+    let (from, to) = (parse_commit(&from_sha)?, parse_commit(&to_sha)?);
+
+    // Ranges asked for directly must lie on the attempt's branch. A process's recorded commits
+    // are kept diffable even once a rebase has moved the branch away from them.
+    if execution_process_id.is_none() {
+        let project = task_attempt
+            .parent_task(&deployment.db().pool)
+            .await?
+            .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?
+            .parent_project(&deployment.db().pool)
+            .await?
+            .ok_or(ApiError::TaskAttempt(TaskAttemptError::ProjectNotFound))?;
+        deployment
+            .git()
+            .check_commit_range(&project.git_repo_path, &task_attempt.branch, &from, &to)
+            .map_err(|e| match e {
+                GitServiceError::InvalidCommitRange(reason) => {
+                    ApiError::TaskAttempt(TaskAttemptError::ValidationError(reason))
+                }
+                e => ApiError::GitService(e),
+            })?;
+    }
+    Ok(Some((from, to)))
+}
+
 #[axum::debug_handler]
 pub async fn stream_task_attempt_diff_ws(
     ws: WebSocketUpgrade,
     Query(params): Query<DiffStreamQuery>,
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
     let stats_only = params.stats_only;
-    ws.on_upgrade(move |socket| async move {
+    let range = resolve_diff_range(
+        &deployment,
+        &task_attempt,
+        params.execution_process_id,
+        params.from_commit.as_deref(),
+        params.to_commit.as_deref(),
+    )
+    .await?;
+    Ok(ws.on_upgrade(move |socket| async move {
         if let Err(e) =
-            handle_task_attempt_diff_ws(socket, deployment, task_attempt, stats_only).await
+            handle_task_attempt_diff_ws(socket, deployment, task_attempt, range, stats_only).await
         {
             tracing::warn!("diff WS closed: {}", e);
         }
-    })
+    }))
+}
+
+/// List the attempt's changed files page by page, so large diffs don't have to be
+/// streamed all at once
+pub async fn get_task_attempt_diff_files(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(params): Query<DiffFilesQuery>,
+) -> Result<ResponseJson<ApiResponse<DiffFilePage>>, ApiError> {
+    let range = resolve_diff_range(
+        &deployment,
+        &task_attempt,
+        params.execution_process_id,
+        params.from_commit.as_deref(),
+        params.to_commit.as_deref(),
+    )
+    .await?;
+    let files = deployment
+        .container()
+        .list_diff_files(&task_attempt, range.as_ref().map(|(from, to)| (from, to)))
+        .await?;
+
+    let total = files.len();
+    let files = files
+        .into_iter()
+        .skip(params.offset)
+        .take(params.limit.unwrap_or(usize::MAX))
+        .collect();
+    Ok(ResponseJson(ApiResponse::success(DiffFilePage {
+        files,
+        total,
+    })))
+}
+
+/// Load one changed file with its contents, including files too large for the diff stream
+pub async fn get_task_attempt_diff_file(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(params): Query<DiffFileQuery>,
+) -> Result<ResponseJson<ApiResponse<Diff>>, ApiError> {
+    let range = resolve_diff_range(
+        &deployment,
+        &task_attempt,
+        params.execution_process_id,
+        params.from_commit.as_deref(),
+        params.to_commit.as_deref(),
+    )
+    .await?;
+    let diff = deployment
+        .container()
+        .get_diff_file(
+            &task_attempt,
+            range.as_ref().map(|(from, to)| (from, to)),
+            &params.path,
+        )
+        .await?;
+
+    match diff {
+        Some(diff) => Ok(ResponseJson(ApiResponse::success(diff))),
+        None => Ok(ResponseJson(ApiResponse::error(&format!(
+            "{} is not changed by this attempt",
+            params.path
+        )))),
+    }
+}
+
+/// Summarize the attempt's lockfile changes as dependencies added, removed, upgraded or
+/// downgraded
+pub async fn get_task_attempt_dependency_diff(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(params): Query<DependencyDiffQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<LockfileDependencyDiff>>>, ApiError> {
+    let range = resolve_diff_range(
+        &deployment,
+        &task_attempt,
+        params.execution_process_id,
+        params.from_commit.as_deref(),
+        params.to_commit.as_deref(),
+    )
+    .await?;
+    let range = range.as_ref().map(|(from, to)| (from, to));
+
+    let container = deployment.container();
+    let mut lockfiles = Vec::new();
+    for file in container.list_diff_files(&task_attempt, range).await? {
+        let path = GitService::diff_path(&file);
+        if !dependency_diff::is_lockfile(&path) {
+            continue;
+        }
+        let diff = container.get_diff_file(&task_attempt, range, &path).await?;
+        lockfiles.extend(diff.as_ref().and_then(dependency_diff::lockfile_diff));
+    }
+    Ok(ResponseJson(ApiResponse::success(lockfiles)))
 }
 
 async fn handle_task_attempt_diff_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
     task_attempt: TaskAttempt,
+    range: Option<(Commit, Commit)>,
     stats_only: bool,
 ) -> anyhow::Result<()> {
     use futures_util::{SinkExt, StreamExt, TryStreamExt};
     use utils::log_msg::LogMsg;
 
-    let stream = deployment
-        .container()
-        .stream_diff(&task_attempt, stats_only)
-        .await?;
+    let stream = match &range {
+        Some((from_commit, to_commit)) => {
+            deployment
+                .container()
+                .stream_commit_range_diff(&task_attempt, from_commit, to_commit, stats_only)
+                .await?
+        }
+        None => {
+            deployment
+                .container()
+                .stream_diff(&task_attempt, stats_only)
+                .await?
+        }
+    };
 
     let mut stream = stream.map_ok(|msg: LogMsg| msg.to_ws_message_unchecked());
 
@@ -605,6 +1052,7 @@ pub async fn compare_commit_to_head(
 pub async fn merge_task_attempt(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProtectedBranchOverride>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     let pool = &deployment.db().pool;
 
@@ -614,40 +1062,85 @@ pub async fn merge_task_attempt(
         .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
     let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
 
+    deployment.git().ensure_branch_writable(
+        &ctx.project.git_repo_path,
+        &ctx.task_attempt.target_branch,
+        ctx.project.protected_branches.as_deref(),
+        query.allow_protected,
+    )?;
+
     let worktree_path_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
     let worktree_path = worktree_path_buf.as_path();
 
-    let task_uuid_str = task.id.to_string();
-    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
-
-    // Create commit message with task title and description
-    let mut commit_message = format!("{} (vibe-kanban {})", ctx.task.title, first_uuid_section);
-
-    // Add description on next line if it exists
-    if let Some(description) = &ctx.task.description
-        && !description.trim().is_empty()
+    let scan_config = deployment.config().read().await.vulnerability_scan.clone();
+    if let Some(reason) = vulnerability_block(
+        deployment.db(),
+        deployment.git(),
+        &scan_config,
+        &ctx.project,
+        &ctx.task_attempt,
+        worktree_path,
+    )
+    .await?
     {
-        commit_message.push_str("\n\n");
-        commit_message.push_str(description);
+        return Err(ApiError::Conflict(reason));
     }
 
-    let merge_commit_id = deployment.git().merge_changes(
+    let commit_message = merge_commit_message(&ctx.task);
+
+    let merged = deployment.git().merge_changes(
         &ctx.project.git_repo_path,
         worktree_path,
         &ctx.task_attempt.branch,
         &ctx.task_attempt.target_branch,
         &commit_message,
-    )?;
+    );
+    AuditEvent::new(AuditAction::Merge, &ctx.task_attempt.target_branch)
+        .task(ctx.project.id, ctx.task.id)
+        .attempt(task_attempt.id)
+        .parameters(serde_json::json!({
+            "branch": ctx.task_attempt.branch,
+            "strategy": MergeStrategy::Squash,
+            "allow_protected": query.allow_protected,
+            "merge_commit": merged.as_ref().ok(),
+        }))
+        .outcome(&merged)
+        .record(pool)
+        .await;
+    let merge_commit_id = merged?;
 
+    // merge_changes squashes the attempt's commits
     Merge::create_direct(
         pool,
         task_attempt.id,
         &ctx.task_attempt.target_branch,
         &merge_commit_id,
+        MergeStrategy::Squash,
     )
     .await?;
     Task::update_status(pool, ctx.task.id, TaskStatus::Done).await?;
 
+    DeployHookService::spawn_trigger(
+        deployment.db().clone(),
+        ctx.project.id,
+        ctx.task_attempt.clone(),
+        DeployHookEvent::Merged,
+        Some(merge_commit_id),
+    );
+
+    // The merge already happened, so a script that fails to start doesn't fail the request
+    if let Err(e) = deployment
+        .container()
+        .start_post_merge_script(&ctx.task_attempt, &ctx.project)
+        .await
+    {
+        tracing::error!(
+            "Failed to start post-merge script for attempt {}: {}",
+            task_attempt.id,
+            e
+        );
+    }
+
     deployment
         .track_if_analytics_allowed(
             "task_attempt_merged",
@@ -665,6 +1158,7 @@ pub async fn merge_task_attempt(
 pub async fn push_task_attempt_branch(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProtectedBranchOverride>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
     let github_config = deployment.config().read().await.github.clone();
     let Some(github_token) = github_config.token() else {
@@ -674,11 +1168,41 @@ pub async fn push_task_attempt_branch(
     let github_service = GitHubService::new(&github_token)?;
     github_service.check_token().await?;
 
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+
+    deployment.git().ensure_branch_writable(
+        &project.git_repo_path,
+        &task_attempt.branch,
+        project.protected_branches.as_deref(),
+        query.allow_protected,
+    )?;
+
     let ws_path = ensure_worktree_path(&deployment, &task_attempt).await?;
 
-    deployment
-        .git()
-        .push_to_github(&ws_path, &task_attempt.branch, &github_token)?;
+    let pushed = deployment.git().push_to_github(
+        &ws_path,
+        &task_attempt.branch,
+        &github_token,
+        project.push_remote.as_deref(),
+    );
+    AuditEvent::new(AuditAction::Push, &task_attempt.branch)
+        .task(project.id, task.id)
+        .attempt(task_attempt.id)
+        .parameters(serde_json::json!({
+            "remote": project.push_remote,
+            "allow_protected": query.allow_protected,
+        }))
+        .outcome(&pushed)
+        .record(pool)
+        .await;
+    pushed?;
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
@@ -720,12 +1244,33 @@ pub async fn create_github_pr(
 
     let workspace_path = ensure_worktree_path(&deployment, &task_attempt).await?;
 
+    if let Err(e) = deployment.git().ensure_branch_writable(
+        &project.git_repo_path,
+        &task_attempt.branch,
+        project.protected_branches.as_deref(),
+        false,
+    ) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+
     // Push the branch to GitHub first
-    if let Err(e) =
-        deployment
-            .git()
-            .push_to_github(&workspace_path, &task_attempt.branch, &github_token)
-    {
+    let pushed = deployment.git().push_to_github(
+        &workspace_path,
+        &task_attempt.branch,
+        &github_token,
+        project.push_remote.as_deref(),
+    );
+    AuditEvent::new(AuditAction::Push, &task_attempt.branch)
+        .task(project.id, task.id)
+        .attempt(task_attempt.id)
+        .parameters(serde_json::json!({
+            "remote": project.push_remote,
+            "pull_request_base": target_branch,
+        }))
+        .outcome(&pushed)
+        .record(pool)
+        .await;
+    if let Err(e) = pushed {
         tracing::error!("Failed to push branch to GitHub: {}", e);
         let gh_e = GitHubServiceError::from(e);
         if gh_e.is_api_data() {
@@ -756,17 +1301,30 @@ pub async fn create_github_pr(
     } else {
         target_branch
     };
+    // Use GitService to get the remote URLs, then create GitHubRepoInfo
+    let repo_info = deployment
+        .git()
+        .get_github_repo_info(&project.git_repo_path, project.base_remote.as_deref())?;
+    let head_repo_info = deployment
+        .git()
+        .get_github_repo_info(&project.git_repo_path, project.push_remote.as_deref())?;
+    // Tasks imported from an issue reference it, so the PR shows up on the issue
+    let body = match GitHubIssueLink::find_by_task_id(pool, task.id).await? {
+        Some(link) => Some(github_issue_sync::reference_issue(
+            request.body.as_deref(),
+            &link,
+            &repo_info,
+        )),
+        None => request.body.clone(),
+    };
     // Create the PR using GitHub service
     let pr_request = CreatePrRequest {
         title: request.title.clone(),
-        body: request.body.clone(),
+        body,
         head_branch: task_attempt.branch.clone(),
         base_branch: norm_target_branch_name.clone(),
+        head_repo: (head_repo_info != repo_info).then_some(head_repo_info),
     };
-    // Use GitService to get the remote URL, then create GitHubRepoInfo
-    let repo_info = deployment
-        .git()
-        .get_github_repo_info(&project.git_repo_path)?;
 
     match github_service.create_pr(&repo_info, &pr_request).await {
         Ok(pr_info) => {
@@ -783,37 +1341,411 @@ pub async fn create_github_pr(
                 tracing::error!("Failed to update task attempt PR status: {}", e);
             }
 
-            // Auto-open PR in browser
-            if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
-                tracing::warn!("Failed to open PR in browser: {}", e);
-            }
-            deployment
-                .track_if_analytics_allowed(
-                    "github_pr_created",
-                    serde_json::json!({
-                        "task_id": task.id.to_string(),
-                        "project_id": project.id.to_string(),
-                        "attempt_id": task_attempt.id.to_string(),
-                    }),
-                )
-                .await;
+            // Auto-open PR in browser
+            if let Err(e) = utils::browser::open_browser(&pr_info.url).await {
+                tracing::warn!("Failed to open PR in browser: {}", e);
+            }
+            deployment
+                .track_if_analytics_allowed(
+                    "github_pr_created",
+                    serde_json::json!({
+                        "task_id": task.id.to_string(),
+                        "project_id": project.id.to_string(),
+                        "attempt_id": task_attempt.id.to_string(),
+                    }),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(pr_info.url)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to create GitHub PR for attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            if e.is_api_data() {
+                Ok(ResponseJson(ApiResponse::error_with_data(e)))
+            } else {
+                Ok(ResponseJson(ApiResponse::error(
+                    format!("Failed to create PR: {}", e).as_str(),
+                )))
+            }
+        }
+    }
+}
+
+/// Tag the commit the attempt was last merged as, optionally pushing the tag and publishing a
+/// GitHub release for it
+pub async fn create_task_attempt_tag(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<CreateTagRequest>,
+) -> Result<ResponseJson<ApiResponse<CreateTagResponse, GitHubServiceError>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+
+    let tag_name = request.tag_name.trim().to_string();
+    if tag_name.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("Tag name is required")));
+    }
+    let Some(merge) = Merge::find_latest_by_task_attempt_id(pool, task_attempt.id).await? else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "This attempt has not been merged yet",
+        )));
+    };
+    let Some(commit_sha) = merge.merge_commit() else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "The pull request for this attempt has not been merged yet",
+        )));
+    };
+
+    let github_token = deployment.config().read().await.github.token();
+    let repo_path = &project.git_repo_path;
+    let message = request
+        .message
+        .as_deref()
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .unwrap_or(&task.title);
+
+    // Pull requests are merged on GitHub, so their merge commit may not be local yet
+    if let Err(e) = deployment.git().ensure_commit_fetched(
+        repo_path,
+        &commit_sha,
+        github_token.clone(),
+        project.base_remote.as_deref(),
+    ) {
+        tracing::error!("Failed to fetch merge commit {}: {}", commit_sha, e);
+        let gh_e = GitHubServiceError::from(e);
+        if gh_e.is_api_data() {
+            return Ok(ResponseJson(ApiResponse::error_with_data(gh_e)));
+        }
+        return Ok(ResponseJson(ApiResponse::error(
+            format!("Failed to fetch merge commit: {}", gh_e).as_str(),
+        )));
+    }
+    deployment.git().create_tag(
+        repo_path,
+        &tag_name,
+        &commit_sha,
+        message,
+        request.sign.unwrap_or(false),
+    )?;
+
+    let push = request.push.unwrap_or(false) || request.release.is_some();
+    let mut release_url = None;
+    if push {
+        let Some(github_token) = github_token else {
+            return Ok(ResponseJson(ApiResponse::error_with_data(
+                GitHubServiceError::TokenInvalid,
+            )));
+        };
+        let pushed = deployment.git().push_tag(
+            repo_path,
+            &tag_name,
+            &github_token,
+            project.base_remote.as_deref(),
+        );
+        AuditEvent::new(AuditAction::Push, &tag_name)
+            .task(project.id, task.id)
+            .attempt(task_attempt.id)
+            .parameters(serde_json::json!({
+                "remote": project.base_remote,
+                "tag": true,
+                "commit": commit_sha,
+            }))
+            .outcome(&pushed)
+            .record(pool)
+            .await;
+        if let Err(e) = pushed {
+            tracing::error!("Failed to push tag {}: {}", tag_name, e);
+            let gh_e = GitHubServiceError::from(e);
+            if gh_e.is_api_data() {
+                return Ok(ResponseJson(ApiResponse::error_with_data(gh_e)));
+            }
+            return Ok(ResponseJson(ApiResponse::error(
+                format!("Tag created but could not be pushed: {}", gh_e).as_str(),
+            )));
+        }
+
+        if let Some(release) = request.release {
+            let repo_info = deployment
+                .git()
+                .get_github_repo_info(repo_path, project.base_remote.as_deref())?;
+            let release_request = CreateReleaseRequest {
+                tag_name: tag_name.clone(),
+                name: release.name.or_else(|| Some(tag_name.clone())),
+                body: release.body,
+                draft: release.draft,
+                prerelease: release.prerelease,
+            };
+            match GitHubService::new(&github_token)?
+                .create_release(&repo_info, &release_request)
+                .await
+            {
+                Ok(url) => release_url = Some(url),
+                Err(e) => {
+                    tracing::error!("Failed to create release for {}: {}", tag_name, e);
+                    if e.is_api_data() {
+                        return Ok(ResponseJson(ApiResponse::error_with_data(e)));
+                    }
+                    return Ok(ResponseJson(ApiResponse::error(
+                        format!("Tag pushed but the release could not be created: {}", e).as_str(),
+                    )));
+                }
+            }
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_tagged",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": project.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+                "pushed": push,
+                "release": release_url.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(CreateTagResponse {
+        tag_name,
+        commit_sha,
+        pushed: push,
+        release_url,
+    })))
+}
+
+/// Trigger a workflow_dispatch for the attempt's branch on the project's GitHub repo
+pub async fn dispatch_workflow(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<DispatchWorkflowRequest>,
+) -> Result<ResponseJson<ApiResponse<WorkflowRun, GitHubServiceError>>, ApiError> {
+    let workflow_file = request.workflow_file.trim();
+    if workflow_file.is_empty() {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "workflow_file must not be empty".to_string(),
+        )));
+    }
+
+    let github_config = deployment.config().read().await.github.clone();
+    let Some(github_token) = github_config.token() else {
+        return Ok(ResponseJson(ApiResponse::error_with_data(
+            GitHubServiceError::TokenInvalid,
+        )));
+    };
+    let github_service = GitHubService::new(&github_token)?;
+
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+
+    let workspace_path = ensure_worktree_path(&deployment, &task_attempt).await?;
+
+    if let Err(e) = deployment.git().ensure_branch_writable(
+        &project.git_repo_path,
+        &task_attempt.branch,
+        project.protected_branches.as_deref(),
+        false,
+    ) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+
+    // The workflow runs against the remote branch, so push local commits first
+    let pushed = deployment.git().push_to_github(
+        &workspace_path,
+        &task_attempt.branch,
+        &github_token,
+        project.push_remote.as_deref(),
+    );
+    AuditEvent::new(AuditAction::Push, &task_attempt.branch)
+        .task(project.id, task.id)
+        .attempt(task_attempt.id)
+        .parameters(serde_json::json!({
+            "remote": project.push_remote,
+            "workflow_file": workflow_file,
+        }))
+        .outcome(&pushed)
+        .record(pool)
+        .await;
+    if let Err(e) = pushed {
+        tracing::error!("Failed to push branch to GitHub: {}", e);
+        let gh_e = GitHubServiceError::from(e);
+        if gh_e.is_api_data() {
+            return Ok(ResponseJson(ApiResponse::error_with_data(gh_e)));
+        } else {
+            return Ok(ResponseJson(ApiResponse::error(
+                format!("Failed to push branch to GitHub: {}", gh_e).as_str(),
+            )));
+        }
+    }
+
+    // Workflows run in the repository the branch was pushed to
+    let repo_info = deployment
+        .git()
+        .get_github_repo_info(&project.git_repo_path, project.push_remote.as_deref())?;
+    let inputs = serde_json::json!(request.inputs.unwrap_or_default());
+
+    if let Err(e) = github_service
+        .dispatch_workflow(&repo_info, workflow_file, &task_attempt.branch, &inputs)
+        .await
+    {
+        tracing::error!(
+            "Failed to dispatch workflow {} for attempt {}: {}",
+            workflow_file,
+            task_attempt.id,
+            e
+        );
+        if e.is_api_data() {
+            return Ok(ResponseJson(ApiResponse::error_with_data(e)));
+        } else {
+            return Ok(ResponseJson(ApiResponse::error(
+                format!("Failed to dispatch workflow: {}", e).as_str(),
+            )));
+        }
+    }
+
+    let workflow_run = WorkflowRun::create(
+        pool,
+        task_attempt.id,
+        &repo_info.owner,
+        &repo_info.repo_name,
+        workflow_file,
+        &task_attempt.branch,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "github_workflow_dispatched",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": project.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(workflow_run)))
+}
+
+pub async fn get_workflow_runs(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkflowRun>>>, ApiError> {
+    let workflow_runs =
+        WorkflowRun::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(workflow_runs)))
+}
+
+/// Set or clear the code-review state of the attempt's output. The acting user is recorded as
+/// the reviewer.
+pub async fn update_task_attempt_review(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateAttemptReview>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let pool = &deployment.db().pool;
+    // Clearing the review drops its note too
+    let note = payload
+        .state
+        .and(payload.note.as_deref())
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    let reviewed_by = payload.state.and(attribution::current_user());
+    TaskAttempt::set_review(
+        pool,
+        task_attempt.id,
+        payload.state,
+        note,
+        reviewed_by.as_deref(),
+    )
+    .await?;
+    let updated = TaskAttempt::find_by_id(pool, task_attempt.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_reviewed",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+                "review_state": payload.state,
+                "has_note": note.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+/// Direct merges and pull requests of the attempt, newest first
+pub async fn get_task_attempt_merges(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Merge>>>, ApiError> {
+    let merges = Merge::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(merges)))
+}
 
-            Ok(ResponseJson(ApiResponse::success(pr_info.url)))
-        }
-        Err(e) => {
-            tracing::error!(
-                "Failed to create GitHub PR for attempt {}: {}",
-                task_attempt.id,
-                e
-            );
-            if e.is_api_data() {
-                Ok(ResponseJson(ApiResponse::error_with_data(e)))
-            } else {
-                Ok(ResponseJson(ApiResponse::error(
-                    format!("Failed to create PR: {}", e).as_str(),
-                )))
-            }
-        }
+/// Pass/fail counts of the attempt's test runs, newest first
+pub async fn get_test_results(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TestRunResult>>>, ApiError> {
+    let results =
+        TestRunResult::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(results)))
+}
+
+pub async fn get_vulnerability_findings(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<VulnerabilityFinding>>>, ApiError> {
+    let findings =
+        VulnerabilityFinding::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(findings)))
+}
+
+/// Rescan dependencies added by the attempt, even if automatic scanning is disabled
+pub async fn scan_vulnerabilities(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<VulnerabilityFinding>>>, ApiError> {
+    let worktree_path = ensure_worktree_path(&deployment, &task_attempt).await?;
+    let config = deployment.config().read().await.vulnerability_scan.clone();
+
+    match VulnerabilityScanService::scan_attempt(
+        deployment.db(),
+        deployment.git(),
+        &config,
+        &task_attempt,
+        &worktree_path,
+    )
+    .await
+    {
+        Ok(findings) => Ok(ResponseJson(ApiResponse::success(findings))),
+        Err(VulnerabilityScanError::GitService(e)) => Err(e.into()),
+        Err(VulnerabilityScanError::Database(e)) => Err(e.into()),
+        Err(e) => Ok(ResponseJson(ApiResponse::error(&format!(
+            "Vulnerability scan failed: {e}"
+        )))),
     }
 }
 
@@ -905,6 +1837,10 @@ pub struct BranchStatus {
     pub conflict_op: Option<ConflictOp>,
     /// List of files currently in conflicted (unmerged) state
     pub conflicted_files: Vec<String>,
+    /// Latest background check against the base branch, if the project enables base branch sync
+    pub base_branch_sync: Option<BaseBranchSync>,
+    /// Merging into the target branch requires an explicit override
+    pub target_branch_protected: bool,
 }
 
 pub async fn get_task_attempt_branch_status(
@@ -1008,6 +1944,20 @@ pub async fn get_task_attempt_branch_status(
     } else {
         (None, None)
     };
+    let base_branch_sync = if ctx.project.sync_base_branch {
+        BaseBranchSync::find_by_task_attempt_id(pool, task_attempt.id).await?
+    } else {
+        None
+    };
+    let target_branch_protected = matches!(
+        deployment.git().ensure_branch_writable(
+            &ctx.project.git_repo_path,
+            &task_attempt.target_branch,
+            ctx.project.protected_branches.as_deref(),
+            false,
+        ),
+        Err(GitServiceError::ProtectedBranch(_))
+    );
 
     let branch_status = BranchStatus {
         commits_ahead,
@@ -1023,6 +1973,8 @@ pub async fn get_task_attempt_branch_status(
         is_rebase_in_progress,
         conflict_op,
         conflicted_files,
+        base_branch_sync,
+        target_branch_protected,
     };
     Ok(ResponseJson(ApiResponse::success(branch_status)))
 }
@@ -1145,6 +2097,15 @@ pub async fn rebase_task_attempt(
     let worktree_path_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
     let worktree_path = worktree_path_buf.as_path();
 
+    // Rebasing refuses to touch a dirty worktree, so optionally move tracked changes aside
+    let stashed = if payload.auto_stash.unwrap_or(false) {
+        deployment
+            .git()
+            .stash_push(worktree_path, "vibe-kanban: auto-stash before rebase")?
+    } else {
+        None
+    };
+
     let result = deployment.git().rebase_branch(
         &ctx.project.git_repo_path,
         worktree_path,
@@ -1154,27 +2115,61 @@ pub async fn rebase_task_attempt(
         github_config.token(),
     );
     if let Err(e) = result {
-        use services::services::git::GitServiceError;
         return match e {
-            GitServiceError::MergeConflicts(msg) => Ok(ResponseJson(ApiResponse::<
-                (),
-                GitOperationError,
-            >::error_with_data(
-                GitOperationError::MergeConflicts {
-                    message: msg,
-                    op: ConflictOp::Rebase,
-                },
-            ))),
-            GitServiceError::RebaseInProgress => Ok(ResponseJson(ApiResponse::<
-                (),
-                GitOperationError,
-            >::error_with_data(
-                GitOperationError::RebaseInProgress,
-            ))),
-            other => Err(ApiError::GitService(other)),
+            GitServiceError::MergeConflicts(msg) => {
+                // Leave the stash alone while the rebase is stopped on conflicts
+                let message = match &stashed {
+                    Some(sha) => format!(
+                        "{msg} Your uncommitted changes were stashed; run `git stash apply {sha}` once the rebase is finished."
+                    ),
+                    None => msg,
+                };
+                Ok(ResponseJson(
+                    ApiResponse::<(), GitOperationError>::error_with_data(
+                        GitOperationError::MergeConflicts {
+                            message,
+                            op: ConflictOp::Rebase,
+                        },
+                    ),
+                ))
+            }
+            GitServiceError::RebaseInProgress => {
+                if let Some(sha) = &stashed
+                    && let Err(e) = deployment.git().stash_pop(worktree_path, sha)
+                {
+                    tracing::error!("Failed to reapply stashed changes: {}", e);
+                }
+                Ok(ResponseJson(
+                    ApiResponse::<(), GitOperationError>::error_with_data(
+                        GitOperationError::RebaseInProgress,
+                    ),
+                ))
+            }
+            other => {
+                if let Some(sha) = &stashed
+                    && let Err(e) = deployment.git().stash_pop(worktree_path, sha)
+                {
+                    tracing::error!("Failed to reapply stashed changes: {}", e);
+                }
+                Err(ApiError::GitService(other))
+            }
         };
     }
 
+    if let Some(sha) = &stashed {
+        match deployment.git().stash_pop(worktree_path, sha) {
+            Ok(()) => {}
+            Err(services::services::git::GitServiceError::StashConflict(message)) => {
+                return Ok(ResponseJson(
+                    ApiResponse::<(), GitOperationError>::error_with_data(
+                        GitOperationError::StashConflict { message },
+                    ),
+                ));
+            }
+            Err(e) => return Err(ApiError::GitService(e)),
+        }
+    }
+
     deployment
         .track_if_analytics_allowed(
             "task_attempt_rebased",
@@ -1203,6 +2198,268 @@ pub async fn abort_conflicts_task_attempt(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+#[axum::debug_handler]
+pub async fn cherry_pick_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CherryPickRequest>,
+) -> Result<ResponseJson<ApiResponse<CherryPickResult, GitOperationError>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    if payload.source_attempt_id == task_attempt.id {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Cannot cherry-pick commits from an attempt onto itself",
+        )));
+    }
+    let Some(source_attempt) = TaskAttempt::find_by_id(pool, payload.source_attempt_id).await?
+    else {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "Source attempt not found".to_string(),
+        )));
+    };
+
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let source_task = source_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    if source_task.project_id != task.project_id {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Source attempt belongs to a different project",
+        )));
+    }
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+
+    // Only commits the source attempt made on top of its own target branch can be picked
+    let source_commits = deployment.git().list_commits_between(
+        &ctx.project.git_repo_path,
+        &source_attempt.target_branch,
+        &source_attempt.branch,
+    )?;
+    let commits = match payload.commits {
+        Some(selected) => {
+            let mut resolved = Vec::with_capacity(selected.len());
+            for sha in selected {
+                let sha = sha.trim();
+                let matches: Vec<&String> = source_commits
+                    .iter()
+                    .filter(|c| !sha.is_empty() && c.starts_with(sha))
+                    .collect();
+                match matches.as_slice() {
+                    [full] => resolved.push((*full).clone()),
+                    [] => {
+                        return Ok(ResponseJson(ApiResponse::error(
+                            format!("Commit '{sha}' is not on the source attempt's branch")
+                                .as_str(),
+                        )));
+                    }
+                    _ => {
+                        return Ok(ResponseJson(ApiResponse::error(
+                            format!(
+                                "Commit '{sha}' is ambiguous; it matches {} commits on the source attempt's branch",
+                                matches.len()
+                            )
+                            .as_str(),
+                        )));
+                    }
+                }
+            }
+            resolved
+        }
+        None => source_commits,
+    };
+    if commits.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "The source attempt has no commits to cherry-pick",
+        )));
+    }
+
+    let worktree_path_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
+    let worktree_path = worktree_path_buf.as_path();
+
+    // Don't start a cherry-pick on top of another unresolved operation
+    if let Some(op) = deployment.git().detect_conflict_op(worktree_path)? {
+        return Ok(ResponseJson(ApiResponse::error_with_data(
+            GitOperationError::MergeConflicts {
+                message: "Resolve or abort the operation in progress before cherry-picking."
+                    .to_string(),
+                op,
+            },
+        )));
+    }
+
+    let head_oid = match deployment
+        .git()
+        .cherry_pick_commits(worktree_path, &commits)
+    {
+        Ok(head_oid) => head_oid,
+        Err(services::services::git::GitServiceError::MergeConflicts(message)) => {
+            return Ok(ResponseJson(ApiResponse::error_with_data(
+                GitOperationError::MergeConflicts {
+                    message,
+                    op: ConflictOp::CherryPick,
+                },
+            )));
+        }
+        Err(services::services::git::GitServiceError::RebaseInProgress) => {
+            return Ok(ResponseJson(ApiResponse::error_with_data(
+                GitOperationError::RebaseInProgress,
+            )));
+        }
+        Err(e) => return Err(ApiError::GitService(e)),
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_cherry_picked",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+                "source_attempt_id": source_attempt.id.to_string(),
+                "commit_count": commits.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(CherryPickResult {
+        head_oid,
+        applied_commits: commits,
+    })))
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// `git format-patch` series, applied with `git am`
+    Patch,
+    /// Git bundle, fetched or cloned from like a remote
+    Bundle,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub format: ExportFormat,
+}
+
+/// Download the commits the attempt made on top of its target branch, so they can be
+/// applied to a repository this server cannot push to. Uncommitted changes are not included.
+#[axum::debug_handler]
+pub async fn export_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+    let repo_path = ctx.project.git_repo_path.as_path();
+
+    let commits = deployment.git().list_commits_between(
+        repo_path,
+        &task_attempt.target_branch,
+        &task_attempt.branch,
+    )?;
+    if commits.is_empty() {
+        return Err(ApiError::Conflict(
+            "The attempt has no commits to export".to_string(),
+        ));
+    }
+
+    let (bytes, content_type, extension) = match query.format {
+        ExportFormat::Patch => (
+            deployment.git().export_patch(
+                repo_path,
+                &task_attempt.branch,
+                &task_attempt.target_branch,
+            )?,
+            "text/x-patch; charset=utf-8",
+            "patch",
+        ),
+        ExportFormat::Bundle => (
+            deployment.git().export_bundle(
+                repo_path,
+                &task_attempt.branch,
+                &task_attempt.target_branch,
+            )?,
+            "application/octet-stream",
+            "bundle",
+        ),
+    };
+
+    let file_name = format!("{}.{extension}", task_attempt.branch.replace('/', "-"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// Create an expiring link giving read-only access to the attempt's diff and logs
+pub async fn create_task_attempt_share_link(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    Extension(RequestOrigin(origin)): Extension<RequestOrigin>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateShareLink>,
+) -> Result<ResponseJson<ApiResponse<ShareLink>>, ApiError> {
+    let base_url = public_url::base_url(deployment.config(), origin.as_deref()).await;
+    let link = ShareLinkSigner::global()?.create(task_attempt.id, &payload, base_url);
+    Ok(ResponseJson(ApiResponse::success(link)))
+}
+
+/// Download the output of every process the attempt ran as a zip of text files, one per
+/// process in the order they ran
+pub async fn download_task_attempt_logs(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+    if processes.is_empty() {
+        return Err(ApiError::Conflict(
+            "The attempt has not run any processes".to_string(),
+        ));
+    }
+
+    let mut files = Vec::with_capacity(processes.len());
+    for (position, process) in processes.iter().enumerate() {
+        let in_memory = deployment
+            .container()
+            .get_msg_store_by_id(&process.id)
+            .await
+            .map(|store| store.get_history());
+        let history = log_export::load_history(pool, process.id, in_memory).await?;
+        files.push((
+            log_export::file_name(position, process),
+            log_export::raw_text(&history, None),
+        ));
+    }
+    let bytes = log_export::zip_files(files)?;
+
+    let file_name = format!("{}-logs.zip", task_attempt.branch.replace('/', "-"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
 #[derive(serde::Deserialize)]
 pub struct DeleteFileQuery {
     file_path: String,
@@ -1237,96 +2494,6 @@ pub async fn delete_task_attempt_file(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
-#[axum::debug_handler]
-pub async fn start_dev_server(
-    Extension(task_attempt): Extension<TaskAttempt>,
-    State(deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
-    let pool = &deployment.db().pool;
-
-    // Get parent task
-    let task = task_attempt
-        .parent_task(&deployment.db().pool)
-        .await?
-        .ok_or(SqlxError::RowNotFound)?;
-
-    // Get parent project
-    let project = task
-        .parent_project(&deployment.db().pool)
-        .await?
-        .ok_or(SqlxError::RowNotFound)?;
-
-    // Stop any existing dev servers for this project
-    let existing_dev_servers =
-        match ExecutionProcess::find_running_dev_servers_by_project(pool, project.id).await {
-            Ok(servers) => servers,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to find running dev servers for project {}: {}",
-                    project.id,
-                    e
-                );
-                return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-                    e.to_string(),
-                )));
-            }
-        };
-
-    for dev_server in existing_dev_servers {
-        tracing::info!(
-            "Stopping existing dev server {} for project {}",
-            dev_server.id,
-            project.id
-        );
-
-        if let Err(e) = deployment
-            .container()
-            .stop_execution(&dev_server, ExecutionProcessStatus::Killed)
-            .await
-        {
-            tracing::error!("Failed to stop dev server {}: {}", dev_server.id, e);
-        }
-    }
-
-    if let Some(dev_server) = project.dev_script {
-        // TODO: Derive script language from system config
-        let executor_action = ExecutorAction::new(
-            ExecutorActionType::ScriptRequest(ScriptRequest {
-                script: dev_server,
-                language: ScriptRequestLanguage::Bash,
-                context: ScriptContext::DevServer,
-            }),
-            None,
-        );
-
-        deployment
-            .container()
-            .start_execution(
-                &task_attempt,
-                &executor_action,
-                &ExecutionProcessRunReason::DevServer,
-            )
-            .await?
-    } else {
-        return Ok(ResponseJson(ApiResponse::error(
-            "No dev server script configured for this project",
-        )));
-    };
-
-    deployment
-        .track_if_analytics_allowed(
-            "dev_server_started",
-            serde_json::json!({
-                "task_id": task.id.to_string(),
-                "project_id": project.id.to_string(),
-                "attempt_id": task_attempt.id.to_string(),
-            }),
-        )
-        .await;
-
-    Ok(ResponseJson(ApiResponse::success(())))
-}
-
 pub async fn get_task_attempt_children(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
@@ -1418,11 +2585,14 @@ pub async fn attach_existing_pr(
     let github_service = GitHubService::new(&github_token)?;
     let repo_info = deployment
         .git()
-        .get_github_repo_info(&project.git_repo_path)?;
+        .get_github_repo_info(&project.git_repo_path, project.base_remote.as_deref())?;
+    let head_repo_info = deployment
+        .git()
+        .get_github_repo_info(&project.git_repo_path, project.push_remote.as_deref())?;
 
     // List all PRs for branch (open, closed, and merged)
     let prs = github_service
-        .list_all_prs_for_branch(&repo_info, &task_attempt.branch)
+        .list_all_prs_for_branch(&repo_info, &head_repo_info.owner, &task_attempt.branch)
         .await?;
 
     // Take the first PR (prefer open, but also accept merged/closed)
@@ -1481,19 +2651,57 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         )
         .route("/draft/queue", post(drafts::set_draft_queue))
         .route("/replace-process", post(replace_process))
+        .route("/checkpoints", get(checkpoints::get_checkpoints))
+        .route(
+            "/checkpoints/{process_id}/rollback",
+            post(checkpoints::rollback_to_checkpoint),
+        )
         .route("/commit-info", get(get_commit_info))
         .route("/commit-compare", get(compare_commit_to_head))
-        .route("/start-dev-server", post(start_dev_server))
+        .route("/dev-server", get(dev_server::get_dev_server))
+        .route("/dev-server/start", post(dev_server::start_dev_server))
+        .route("/dev-server/stop", post(dev_server::stop_dev_server))
         .route("/branch-status", get(get_task_attempt_branch_status))
         .route("/diff/ws", get(stream_task_attempt_diff_ws))
+        .route("/diff/files", get(get_task_attempt_diff_files))
+        .route("/diff/file", get(get_task_attempt_diff_file))
+        .route("/diff/dependencies", get(get_task_attempt_dependency_diff))
         .route("/merge", post(merge_task_attempt))
+        .route("/merges", get(get_task_attempt_merges))
+        .route(
+            "/merge-queue",
+            post(merge_queue::enqueue_merge).delete(merge_queue::cancel_merge),
+        )
         .route("/push", post(push_task_attempt_branch))
+        .route("/tag", post(create_task_attempt_tag))
         .route("/rebase", post(rebase_task_attempt))
         .route("/conflicts/abort", post(abort_conflicts_task_attempt))
+        .route("/cherry-pick", post(cherry_pick_task_attempt))
+        .route("/export", get(export_task_attempt))
+        .route("/logs/download", get(download_task_attempt_logs))
+        .route("/share", post(create_task_attempt_share_link))
         .route("/pr", post(create_github_pr))
         .route("/pr/attach", post(attach_existing_pr))
+        .route(
+            "/workflow-runs",
+            get(get_workflow_runs).post(dispatch_workflow),
+        )
+        .route("/review", put(update_task_attempt_review))
+        .route("/test-results", get(get_test_results))
+        .route("/vulnerabilities", get(get_vulnerability_findings))
+        .route("/vulnerabilities/scan", post(scan_vulnerabilities))
         .route("/open-editor", post(open_task_attempt_in_editor))
         .route("/delete-file", post(delete_task_attempt_file))
+        .route("/terminal/ws", get(terminal::terminal_ws))
+        .route("/terminal/sessions", get(terminal::get_terminal_sessions))
+        .route(
+            "/terminal/sessions/{session_id}/transcript",
+            get(terminal::get_terminal_transcript),
+        )
+        .route(
+            "/file",
+            get(files::get_worktree_file).put(files::write_worktree_file),
+        )
         .route("/children", get(get_task_attempt_children))
         .route("/stop", post(stop_task_attempt_execution))
         .route("/change-target-branch", post(change_target_branch))
@@ -1504,6 +2712,19 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_attempts_router = Router::new()
         .route("/", get(get_task_attempts).post(create_task_attempt))
+        .route("/bulk-rerun", post(bulk_rerun::bulk_rerun_failed_attempts))
+        .route(
+            "/comparisons",
+            get(comparisons::get_comparisons).post(comparisons::start_comparison),
+        )
+        .route(
+            "/comparisons/{comparison_id}",
+            get(comparisons::get_comparison),
+        )
+        .route(
+            "/comparisons/{comparison_id}/choose",
+            post(comparisons::choose_attempt),
+        )
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/task-attempts", task_attempts_router)
@@ -0,0 +1,179 @@
+use axum::{Json, extract::State, response::Json as ResponseJson};
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::ExecutionProcess,
+    task::Task,
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use deployment::Deployment;
+use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::{start_follow_up_execution, start_new_attempt, util::ensure_worktree_path};
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RerunMode {
+    /// Start a new attempt on a fresh branch from the same base branch
+    FreshAttempt,
+    /// Send a follow-up prompt to the failed attempt
+    FollowUp,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct BulkRerunRequest {
+    pub project_id: Option<Uuid>,
+    /// Only attempts created at or after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only attempts created at or before this time
+    pub created_before: Option<DateTime<Utc>>,
+    pub executor: Option<BaseCodingAgent>,
+    pub mode: RerunMode,
+    /// Prompt to send in `follow_up` mode
+    pub prompt: Option<String>,
+    /// Only report which attempts would be re-run
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BulkRerunEntry {
+    pub task_id: Uuid,
+    pub task_title: String,
+    /// The failed attempt
+    pub attempt_id: Uuid,
+    pub executor: String,
+    /// The attempt the re-run was started in; unset for dry runs and failures
+    pub rerun_attempt_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BulkRerunResponse {
+    pub dry_run: bool,
+    pub entries: Vec<BulkRerunEntry>,
+}
+
+/// Re-run every failed attempt matching the filter, e.g. after fixing a setup script that
+/// broke many runs. Use `dry_run` to preview the selection first.
+pub async fn bulk_rerun_failed_attempts(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<BulkRerunRequest>,
+) -> Result<ResponseJson<ApiResponse<BulkRerunResponse>>, ApiError> {
+    let prompt = match payload.mode {
+        RerunMode::FollowUp => Some(
+            payload
+                .prompt
+                .clone()
+                .filter(|prompt| !prompt.trim().is_empty())
+                .ok_or(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                    "A prompt is required to re-run attempts as follow-ups".to_string(),
+                )))?,
+        ),
+        RerunMode::FreshAttempt => None,
+    };
+
+    let pool = &deployment.db().pool;
+    let executor = payload.executor.map(|executor| executor.to_string());
+    let attempts = TaskAttempt::find_failed(
+        pool,
+        payload.project_id,
+        payload.created_after,
+        payload.created_before,
+        executor.as_deref(),
+    )
+    .await?;
+
+    let mut entries = Vec::with_capacity(attempts.len());
+    for attempt in attempts {
+        let Some(task) = Task::find_by_id(pool, attempt.task_id).await? else {
+            continue;
+        };
+        let mut entry = BulkRerunEntry {
+            task_id: task.id,
+            task_title: task.title.clone(),
+            attempt_id: attempt.id,
+            executor: attempt.executor.clone(),
+            rerun_attempt_id: None,
+            error: None,
+        };
+
+        if !payload.dry_run {
+            match rerun_attempt(&deployment, &task, &attempt, prompt.clone()).await {
+                Ok(rerun_attempt_id) => entry.rerun_attempt_id = Some(rerun_attempt_id),
+                Err(e) => {
+                    tracing::warn!("Failed to re-run task attempt {}: {}", attempt.id, e);
+                    entry.error = Some(e.to_string());
+                }
+            }
+        }
+        entries.push(entry);
+    }
+
+    if !payload.dry_run {
+        deployment
+            .track_if_analytics_allowed(
+                "task_attempts_bulk_rerun",
+                serde_json::json!({
+                    "mode": payload.mode,
+                    "count": entries.len(),
+                    "failed": entries.iter().filter(|entry| entry.error.is_some()).count(),
+                }),
+            )
+            .await;
+    }
+
+    Ok(ResponseJson(ApiResponse::success(BulkRerunResponse {
+        dry_run: payload.dry_run,
+        entries,
+    })))
+}
+
+/// Re-run one failed attempt with the executor profile it last used. A `prompt` makes it a
+/// follow-up on the same attempt; otherwise a new attempt is started.
+async fn rerun_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    attempt: &TaskAttempt,
+    prompt: Option<String>,
+) -> Result<Uuid, ApiError> {
+    let pool = &deployment.db().pool;
+    let executor_profile_id =
+        match ExecutionProcess::latest_executor_profile_for_attempt(pool, attempt.id).await {
+            Ok(profile) => profile,
+            // The setup script may have failed before the coding agent ever started
+            Err(_) => ExecutorProfileId::new(attempt.executor.parse().map_err(|_| {
+                ApiError::TaskAttempt(TaskAttemptError::ValidationError(format!(
+                    "Unknown executor {}",
+                    attempt.executor
+                )))
+            })?),
+        };
+
+    match prompt {
+        Some(prompt) => {
+            let project = task
+                .parent_project(pool)
+                .await?
+                .ok_or(ApiError::TaskAttempt(TaskAttemptError::ProjectNotFound))?;
+            ensure_worktree_path(deployment, attempt).await?;
+            start_follow_up_execution(deployment, attempt, &project, prompt, executor_profile_id)
+                .await?;
+            Ok(attempt.id)
+        }
+        None => {
+            let new_attempt = start_new_attempt(
+                deployment,
+                task,
+                executor_profile_id,
+                &attempt.target_branch,
+            )
+            .await?;
+            Ok(new_attempt.id)
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    audit_log::AuditAction,
+    execution_process::ExecutionProcess,
+    executor_session::ExecutorSession,
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    audit_log::AuditEvent,
+    checkpoint::{self, Checkpoint},
+    container::ContainerService,
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts::util::ensure_worktree_path};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RollbackToCheckpointRequest {
+    /// Discard uncommitted changes in the worktree instead of refusing to roll back
+    #[serde(default)]
+    #[ts(optional)]
+    pub force_when_dirty: Option<bool>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RollbackResult {
+    /// The commit the worktree was reset to
+    pub commit: String,
+    /// Processes dropped from the attempt's history, including the checkpoint's own
+    pub dropped_count: i64,
+    /// Agent session the next follow-up continues; unset starts a new one
+    pub session_id: Option<String>,
+}
+
+/// The attempt's checkpoints, oldest first
+pub async fn get_checkpoints(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Checkpoint>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+    let sessions = ExecutorSession::find_by_task_attempt_id(pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(checkpoint::checkpoints(
+        &processes, &sessions,
+    ))))
+}
+
+/// Stop the attempt's processes, reset its worktree to the HEAD before the checkpoint's
+/// process ran and drop that process and all later ones, so follow-ups continue the agent
+/// session from that point
+pub async fn rollback_to_checkpoint(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, process_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RollbackToCheckpointRequest>,
+) -> Result<ResponseJson<ApiResponse<RollbackResult>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+    let sessions = ExecutorSession::find_by_task_attempt_id(pool, task_attempt.id).await?;
+    let Some(checkpoint) = checkpoint::checkpoints(&processes, &sessions)
+        .into_iter()
+        .find(|checkpoint| checkpoint.execution_process_id == process_id)
+    else {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "Checkpoint not found".to_string(),
+        )));
+    };
+    let Some(commit) = checkpoint.commit else {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "No commit was recorded for this checkpoint".to_string(),
+        )));
+    };
+
+    let worktree_path = ensure_worktree_path(&deployment, &task_attempt).await?;
+    let force_when_dirty = payload.force_when_dirty.unwrap_or(false);
+    if !force_when_dirty
+        && !deployment
+            .container()
+            .is_container_clean(&task_attempt)
+            .await?
+    {
+        return Err(ApiError::Conflict(
+            "The worktree has uncommitted changes; force the rollback to discard them".to_string(),
+        ));
+    }
+
+    // Nothing may keep writing to the worktree while it is reset
+    deployment.container().try_stop(&task_attempt).await;
+    let reset =
+        deployment
+            .git()
+            .reset_worktree_to_commit(&worktree_path, &commit, force_when_dirty);
+    AuditEvent::new(AuditAction::ForceReset, &commit)
+        .attempt(task_attempt.id)
+        .parameters(serde_json::json!({
+            "reason": "rollback",
+            "process_id": process_id,
+            "force_when_dirty": force_when_dirty,
+        }))
+        .outcome(&reset)
+        .record(pool)
+        .await;
+    reset?;
+
+    let dropped_count =
+        ExecutionProcess::drop_at_and_after(pool, task_attempt.id, process_id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_rolled_back",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+                "run_reason": checkpoint.run_reason,
+                "dropped_count": dropped_count,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(RollbackResult {
+        commit,
+        dropped_count,
+        session_id: checkpoint.session_id,
+    })))
+}
@@ -0,0 +1,237 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    attempt_comparison::AttemptComparison, execution_process::ExecutionProcess, task::Task,
+    task_attempt::TaskAttempt, workflow_run::WorkflowRun,
+};
+use deployment::Deployment;
+use executors::profile::{ExecutorConfigs, ExecutorProfileId};
+use serde::Deserialize;
+use services::services::{
+    attempt_comparison::{self, AttemptComparisonDetails, AttemptComparisonError, ComparedAttempt},
+    container::ContainerService,
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use super::start_new_attempt;
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct StartAttemptComparison {
+    pub task_id: Uuid,
+    /// One attempt is started per profile; the same profile may be listed more than once
+    pub executor_profile_ids: Vec<ExecutorProfileId>,
+    pub base_branch: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct AttemptComparisonQuery {
+    pub task_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ChooseComparedAttempt {
+    pub attempt_id: Uuid,
+}
+
+/// Start an attempt of the task for each profile at once and group them for comparison
+pub async fn start_comparison(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<StartAttemptComparison>,
+) -> Result<ResponseJson<ApiResponse<AttemptComparisonDetails>>, ApiError> {
+    if let Err(e) = attempt_comparison::validate_profiles(&payload.executor_profile_ids) {
+        return Ok(ResponseJson(ApiResponse::error(&e.to_string())));
+    }
+    for executor_profile_id in &payload.executor_profile_ids {
+        ExecutorConfigs::get_cached().validate_profile_id(executor_profile_id)?;
+    }
+
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, payload.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    // Attempts that started before one fails to start stay in the comparison
+    let comparison = AttemptComparison::create(pool, task.id).await?;
+    for (position, executor_profile_id) in payload.executor_profile_ids.iter().enumerate() {
+        let task_attempt = start_new_attempt(
+            &deployment,
+            &task,
+            executor_profile_id.clone(),
+            &payload.base_branch,
+        )
+        .await?;
+        AttemptComparison::add_member(
+            pool,
+            comparison.id,
+            task_attempt.id,
+            executor_profile_id,
+            position as i64,
+        )
+        .await?;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempt_comparison_started",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "comparison_id": comparison.id.to_string(),
+                "attempts": payload.executor_profile_ids.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(
+        comparison_details(&deployment, comparison).await?,
+    )))
+}
+
+pub async fn get_comparisons(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AttemptComparisonQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptComparison>>>, ApiError> {
+    let comparisons =
+        AttemptComparison::find_by_task_id(&deployment.db().pool, query.task_id).await?;
+    Ok(ResponseJson(ApiResponse::success(comparisons)))
+}
+
+/// Diff stats, check results and duration of each compared attempt
+pub async fn get_comparison(
+    State(deployment): State<DeploymentImpl>,
+    Path(comparison_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<AttemptComparisonDetails>>, ApiError> {
+    let comparison = AttemptComparison::find_by_id(&deployment.db().pool, comparison_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(
+        comparison_details(&deployment, comparison).await?,
+    )))
+}
+
+/// Keep one attempt and discard the others: their processes are stopped and their worktrees
+/// removed. Their branches are left in place.
+pub async fn choose_attempt(
+    State(deployment): State<DeploymentImpl>,
+    Path(comparison_id): Path<Uuid>,
+    Json(payload): Json<ChooseComparedAttempt>,
+) -> Result<ResponseJson<ApiResponse<AttemptComparisonDetails>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let comparison = AttemptComparison::find_by_id(pool, comparison_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let members = AttemptComparison::members(pool, comparison.id).await?;
+    if !members
+        .iter()
+        .any(|member| member.task_attempt_id == payload.attempt_id)
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            &AttemptComparisonError::NotAMember.to_string(),
+        )));
+    }
+    if !AttemptComparison::choose(pool, comparison.id, payload.attempt_id).await? {
+        return Err(ApiError::Conflict(
+            "An attempt has already been chosen in this comparison".to_string(),
+        ));
+    }
+
+    for member in members
+        .iter()
+        .filter(|member| member.task_attempt_id != payload.attempt_id)
+    {
+        let Some(task_attempt) = TaskAttempt::find_by_id(pool, member.task_attempt_id).await?
+        else {
+            continue;
+        };
+        discard_attempt(&deployment, &task_attempt).await;
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "attempt_comparison_chosen",
+            serde_json::json!({
+                "task_id": comparison.task_id.to_string(),
+                "comparison_id": comparison.id.to_string(),
+                "attempt_id": payload.attempt_id.to_string(),
+            }),
+        )
+        .await;
+
+    let comparison = AttemptComparison::find_by_id(pool, comparison.id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(
+        comparison_details(&deployment, comparison).await?,
+    )))
+}
+
+/// Stop the attempt's processes and remove its worktree, logging failures
+async fn discard_attempt(deployment: &DeploymentImpl, task_attempt: &TaskAttempt) {
+    if task_attempt.worktree_deleted || task_attempt.container_ref.is_none() {
+        deployment.container().try_stop(task_attempt).await;
+        return;
+    }
+    if let Err(e) = deployment.container().delete(task_attempt).await {
+        tracing::warn!("Failed to discard task attempt {}: {}", task_attempt.id, e);
+        return;
+    }
+    if let Err(e) = TaskAttempt::mark_worktree_deleted(&deployment.db().pool, task_attempt.id).await
+    {
+        tracing::warn!(
+            "Failed to mark worktree of task attempt {} as deleted: {}",
+            task_attempt.id,
+            e
+        );
+    }
+}
+
+async fn comparison_details(
+    deployment: &DeploymentImpl,
+    comparison: AttemptComparison,
+) -> Result<AttemptComparisonDetails, ApiError> {
+    let pool = &deployment.db().pool;
+    let members = AttemptComparison::members(pool, comparison.id).await?;
+    let mut attempts = Vec::with_capacity(members.len());
+    for member in members {
+        let Some(task_attempt) = TaskAttempt::find_by_id(pool, member.task_attempt_id).await?
+        else {
+            continue;
+        };
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+        let workflow_runs = WorkflowRun::find_by_task_attempt_id(pool, task_attempt.id).await?;
+        let diff = if task_attempt.worktree_deleted {
+            None
+        } else {
+            deployment
+                .container()
+                .list_diff_files(&task_attempt, None)
+                .await
+                .inspect_err(|e| {
+                    tracing::debug!(
+                        "Failed to list changes of task attempt {}: {}",
+                        task_attempt.id,
+                        e
+                    )
+                })
+                .ok()
+        };
+        attempts.push(ComparedAttempt::new(
+            task_attempt,
+            member.executor_profile_id.0,
+            &processes,
+            diff.as_deref(),
+            &workflow_runs,
+        ));
+    }
+    Ok(AttemptComparisonDetails {
+        comparison,
+        attempts,
+    })
+}
@@ -0,0 +1,97 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    task_attempt::TaskAttempt,
+};
+use deployment::Deployment;
+use services::services::{container::ContainerService, dev_server::DevServerStatus};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+pub async fn get_dev_server(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    let status = DevServerStatus::for_task_attempt(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+#[axum::debug_handler]
+pub async fn start_dev_server(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let Some(dev_script) = project.dev_script else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No dev server script configured for this project",
+        )));
+    };
+
+    // Each attempt's dev server has its own port, so only this attempt's server is replaced
+    stop_running_dev_servers(&deployment, &task_attempt).await?;
+
+    deployment
+        .container()
+        .start_dev_server(&task_attempt, dev_script, 0)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "dev_server_started",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": project.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    let status = DevServerStatus::for_task_attempt(pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+pub async fn stop_dev_server(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DevServerStatus>>, ApiError> {
+    stop_running_dev_servers(&deployment, &task_attempt).await?;
+    let status = DevServerStatus::for_task_attempt(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+async fn stop_running_dev_servers(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+) -> Result<(), ApiError> {
+    let processes =
+        ExecutionProcess::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id, false)
+            .await?;
+    for process in processes.into_iter().filter(|process| {
+        process.run_reason == ExecutionProcessRunReason::DevServer
+            && process.status == ExecutionProcessStatus::Running
+    }) {
+        tracing::info!(
+            "Stopping dev server {} for attempt {}",
+            process.id,
+            task_attempt.id
+        );
+        deployment
+            .container()
+            .stop_execution(&process, ExecutionProcessStatus::Killed)
+            .await?;
+    }
+    Ok(())
+}
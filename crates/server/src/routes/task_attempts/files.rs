@@ -0,0 +1,127 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::task_attempt::TaskAttempt;
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::filesystem::{self, FilesystemError};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts::util::ensure_worktree_path};
+
+#[derive(Debug, Deserialize)]
+pub struct WorktreeFileQuery {
+    path: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct WorktreeFile {
+    /// Path relative to the worktree root
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct WriteWorktreeFileRequest {
+    /// Path relative to the worktree root; missing parent directories are created
+    pub path: String,
+    pub content: String,
+    /// Commit just this file on the attempt's branch after writing it
+    #[serde(default)]
+    #[ts(optional)]
+    pub commit: Option<bool>,
+    /// Defaults to "Edit <path>"
+    #[serde(default)]
+    #[ts(optional)]
+    pub commit_message: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct WriteWorktreeFileResult {
+    /// The commit made for the edit, if one was requested
+    pub commit: Option<String>,
+}
+
+fn filesystem_error<T>(
+    task_attempt: &TaskAttempt,
+    path: &str,
+    err: FilesystemError,
+) -> ResponseJson<ApiResponse<T>> {
+    match err {
+        FilesystemError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            ResponseJson(ApiResponse::error("File does not exist"))
+        }
+        FilesystemError::Io(e) => {
+            tracing::error!(
+                "Failed to access '{}' in task attempt {}: {}",
+                path,
+                task_attempt.id,
+                e
+            );
+            ResponseJson(ApiResponse::error(&format!("Failed to access file: {e}")))
+        }
+        e => ResponseJson(ApiResponse::error(&e.to_string())),
+    }
+}
+
+pub async fn get_worktree_file(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WorktreeFileQuery>,
+) -> Result<ResponseJson<ApiResponse<WorktreeFile>>, ApiError> {
+    let worktree_path = ensure_worktree_path(&deployment, &task_attempt).await?;
+    match filesystem::read_text_file(&worktree_path, &query.path) {
+        Ok(content) => Ok(ResponseJson(ApiResponse::success(WorktreeFile {
+            path: query.path,
+            content,
+        }))),
+        Err(e) => Ok(filesystem_error(&task_attempt, &query.path, e)),
+    }
+}
+
+/// Write a file in the attempt's worktree by hand, for small fixes while the agent isn't the
+/// right tool
+pub async fn write_worktree_file(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<WriteWorktreeFileRequest>,
+) -> Result<ResponseJson<ApiResponse<WriteWorktreeFileResult>>, ApiError> {
+    let worktree_path = ensure_worktree_path(&deployment, &task_attempt).await?;
+    if let Err(e) = filesystem::write_text_file(&worktree_path, &payload.path, &payload.content) {
+        return Ok(filesystem_error(&task_attempt, &payload.path, e));
+    }
+
+    let commit = if payload.commit.unwrap_or(false) {
+        let relative = filesystem::normalize_relative(&payload.path)
+            .expect("path was validated by write_text_file");
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let message = payload
+            .commit_message
+            .filter(|message| !message.trim().is_empty())
+            .unwrap_or_else(|| format!("Edit {relative}"));
+        Some(
+            deployment
+                .git()
+                .commit_file(&worktree_path, &relative, &message)?,
+        )
+    } else {
+        None
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_file_edited",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+                "committed": commit.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(
+        WriteWorktreeFileResult { commit },
+    )))
+}
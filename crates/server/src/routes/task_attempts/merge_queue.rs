@@ -0,0 +1,72 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    merge_queue::MergeQueueEntry,
+    project::{Project, ProjectError},
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use deployment::Deployment;
+use services::services::merge_queue::MergeQueueService;
+use utils::response::ApiResponse;
+
+use super::ProtectedBranchOverride;
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Add the attempt to its project's merge queue. The protected branch check happens here;
+/// the queue itself only rebases, verifies and merges.
+pub async fn enqueue_merge(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<ProtectedBranchOverride>,
+) -> Result<ResponseJson<ApiResponse<MergeQueueEntry>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+
+    deployment.git().ensure_branch_writable(
+        &project.git_repo_path,
+        &task_attempt.target_branch,
+        project.protected_branches.as_deref(),
+        query.allow_protected,
+    )?;
+
+    let Some(entry) = MergeQueueEntry::enqueue(pool, project.id, task_attempt.id).await? else {
+        return Err(ApiError::Conflict(
+            "This attempt is already in the merge queue or has been merged by it".to_string(),
+        ));
+    };
+    MergeQueueService::wake();
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_merge_queued",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": project.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(entry)))
+}
+
+pub async fn cancel_merge(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !MergeQueueEntry::cancel(&deployment.db().pool, task_attempt.id).await? {
+        return Err(ApiError::Conflict(
+            "Only queued or failed attempts can be removed from the merge queue".to_string(),
+        ));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
@@ -0,0 +1,132 @@
+use axum::{
+    Extension,
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{IntoResponse, Json as ResponseJson},
+};
+use db::models::{
+    task_attempt::{TaskAttempt, TaskAttemptError},
+    terminal_session::TerminalSession,
+};
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    terminal::{Terminal, TerminalClientMessage, TerminalSize},
+};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct TerminalQuery {
+    cols: Option<u16>,
+    rows: Option<u16>,
+}
+
+/// Open a shell in the attempt's worktree. The socket carries the shell's output as binary
+/// frames and takes `TerminalClientMessage`s as text frames; closing it ends the shell.
+pub async fn terminal_ws(
+    ws: WebSocketUpgrade,
+    Query(query): Query<TerminalQuery>,
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<impl IntoResponse, ApiError> {
+    let default_size = TerminalSize::default();
+    let size = TerminalSize {
+        cols: query.cols.unwrap_or(default_size.cols),
+        rows: query.rows.unwrap_or(default_size.rows),
+    };
+    let terminal = deployment
+        .container()
+        .open_terminal(&task_attempt, size)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_terminal_opened",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ws.on_upgrade(move |socket| handle_terminal_ws(socket, terminal)))
+}
+
+async fn handle_terminal_ws(socket: WebSocket, terminal: Terminal) {
+    let Terminal {
+        session,
+        input,
+        mut output,
+    } = terminal;
+    let (mut sender, mut receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            chunk = output.recv() => match chunk {
+                Some(chunk) => {
+                    if sender.send(Message::Binary(chunk.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // The shell exited
+                None => {
+                    let _ = sender.send(Message::Close(None)).await;
+                    break;
+                }
+            },
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<TerminalClientMessage>(text.as_str()) {
+                        Ok(message) => {
+                            if input.send(message.into()).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::debug!(
+                                "Ignoring invalid message for terminal {}: {}",
+                                session.id,
+                                e
+                            );
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// The attempt's terminal sessions, newest first
+pub async fn get_terminal_sessions(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TerminalSession>>>, ApiError> {
+    let sessions =
+        TerminalSession::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(sessions)))
+}
+
+/// Everything a terminal session's shell printed, once the session has ended
+pub async fn get_terminal_transcript(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, session_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    let Some(transcript) =
+        TerminalSession::find_transcript(&deployment.db().pool, session_id, task_attempt.id)
+            .await?
+    else {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "Terminal session not found".to_string(),
+        )));
+    };
+    Ok(ResponseJson(ApiResponse::success(transcript)))
+}
@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use db::models::image::TaskImage;
 use deployment::Deployment;
-use services::services::{container::ContainerService, image::ImageService};
+use services::services::container::ContainerService;
 use uuid::Uuid;
 
 use crate::error::ApiError;
@@ -17,13 +19,14 @@ pub async fn ensure_worktree_path(
     Ok(std::path::PathBuf::from(container_ref))
 }
 
-/// Associate images to the task, copy into worktree, and canonicalize paths in the prompt.
-/// Returns the transformed prompt.
+/// Associate images to the task, place them in the prompt with their captions, copy into
+/// worktree, and canonicalize paths in the prompt. Returns the transformed prompt.
 pub async fn handle_images_for_prompt(
     deployment: &crate::DeploymentImpl,
     attempt: &db::models::task_attempt::TaskAttempt,
     task_id: Uuid,
     image_ids: &[Uuid],
+    image_captions: &HashMap<Uuid, String>,
     prompt: &str,
 ) -> Result<String, ApiError> {
     if image_ids.is_empty() {
@@ -32,14 +35,10 @@ pub async fn handle_images_for_prompt(
 
     TaskImage::associate_many_dedup(&deployment.db().pool, task_id, image_ids).await?;
 
-    // Copy to worktree and canonicalize
+    // Place, copy to worktree and canonicalize
     let worktree_path = ensure_worktree_path(deployment, attempt).await?;
-    deployment
+    Ok(deployment
         .image()
-        .copy_images_by_ids_to_worktree(&worktree_path, image_ids)
-        .await?;
-    Ok(ImageService::canonicalise_image_paths(
-        prompt,
-        &worktree_path,
-    ))
+        .prepare_prompt_images(prompt, image_ids, image_captions, &worktree_path)
+        .await?)
 }
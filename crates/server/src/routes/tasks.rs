@@ -1,3 +1,7 @@
+pub mod comments;
+pub mod dependencies;
+pub mod subtasks;
+
 use std::path::PathBuf;
 
 use anyhow;
@@ -10,41 +14,62 @@ use axum::{
     http::StatusCode,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use db::models::{
+    audit_log::AuditAction,
+    execution_process::ExecutionProcess,
     image::TaskImage,
-    task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    project_column::ProjectColumn,
+    task::{CreateTask, Task, TaskStaleness, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     task_attempt::{CreateTaskAttempt, TaskAttempt},
+    wip_limit::WipLimitEnforcement,
 };
 use deployment::Deployment;
-use executors::profile::ExecutorProfileId;
+use executors::profile::{ExecutorConfigs, ExecutorProfileId};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use services::services::container::{
-    ContainerService, WorktreeCleanupData, cleanup_worktrees_direct,
+use services::services::{
+    attribution,
+    audit_log::AuditEvent,
+    container::{
+        ContainerError, ContainerService, StopSummary, WorktreeCleanupData,
+        cleanup_worktrees_direct,
+    },
+    wip_limits::{self, WipLimitExceeded, WipUsage},
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_task_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{load_task_middleware, request_locale},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskQuery {
     pub project_id: Uuid,
     #[serde(default)]
     pub tag_ids: Option<String>, // Comma-separated tag IDs for filtering
+    /// Only tasks assigned to this user; an empty value selects unassigned tasks
+    #[serde(default)]
+    pub assignee: Option<String>,
 }
 
 pub async fn get_tasks(
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
 ) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, ApiError> {
-    let mut tasks =
-        Task::find_by_project_id_with_attempt_status(&deployment.db().pool, query.project_id)
-            .await?;
+    let staleness_thresholds = deployment.config().read().await.staleness.clone();
+    let mut tasks = Task::find_by_project_id_with_attempt_status(
+        &deployment.db().pool,
+        query.project_id,
+        &staleness_thresholds,
+    )
+    .await?;
 
     // Filter by tags if tag_ids are provided
     if let Some(tag_ids_str) = query.tag_ids {
@@ -61,30 +86,50 @@ pub async fn get_tasks(
         }
     }
 
+    if let Some(assignee) = query.assignee {
+        let assignee = attribution::normalize_user(&assignee);
+        tasks.retain(|task| task.assignee == assignee);
+    }
+
     Ok(ResponseJson(ApiResponse::success(tasks)))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct WipUsageQuery {
+    pub project_id: Uuid,
+}
+
+/// How many of the project's tasks have each status, against the status's limit
+pub async fn get_wip_usage(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WipUsageQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WipUsage>>>, ApiError> {
+    let usage = wip_limits::usage(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
 pub async fn stream_tasks_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
     Query(query): Query<TaskQuery>,
 ) -> impl IntoResponse {
     ws.on_upgrade(move |socket| async move {
-        if let Err(e) = handle_tasks_ws(socket, deployment, query.project_id).await {
+        if let Err(e) = handle_tasks_ws(socket, deployment, vec![query.project_id]).await {
             tracing::warn!("tasks WS closed: {}", e);
         }
     })
 }
 
-async fn handle_tasks_ws(
+/// Forward task updates of the given projects to the socket until either side closes
+pub(crate) async fn handle_tasks_ws(
     socket: WebSocket,
     deployment: DeploymentImpl,
-    project_id: Uuid,
+    project_ids: Vec<Uuid>,
 ) -> anyhow::Result<()> {
     // Get the raw stream and convert LogMsg to WebSocket messages
     let mut stream = deployment
         .events()
-        .stream_tasks_raw(project_id)
+        .stream_tasks_for_projects_raw(project_ids)
         .await?
         .map_ok(|msg| msg.to_ws_message_unchecked());
 
@@ -120,8 +165,20 @@ pub async fn get_task(
 
 pub async fn create_task(
     State(deployment): State<DeploymentImpl>,
-    Json(payload): Json<CreateTask>,
+    Json(mut payload): Json<CreateTask>,
 ) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    payload.assignee = payload
+        .assignee
+        .as_deref()
+        .and_then(attribution::normalize_user);
+    if let Some(parent_task_id) = payload.parent_task_id
+        && !Task::exists(&deployment.db().pool, parent_task_id, payload.project_id).await?
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Parent task not found in this project",
+        )));
+    }
+
     let id = Uuid::new_v4();
 
     tracing::debug!(
@@ -165,8 +222,40 @@ pub struct CreateAndStartTaskRequest {
 
 pub async fn create_task_and_start(
     State(deployment): State<DeploymentImpl>,
-    Json(payload): Json<CreateAndStartTaskRequest>,
+    Json(mut payload): Json<CreateAndStartTaskRequest>,
 ) -> Result<ResponseJson<ApiResponse<TaskWithAttemptStatus>>, ApiError> {
+    payload.task.assignee = payload
+        .task
+        .assignee
+        .as_deref()
+        .and_then(attribution::normalize_user);
+    ExecutorConfigs::get_cached().validate_profile_id(&payload.executor_profile_id)?;
+    if let Some(parent_task_id) = payload.task.parent_task_id
+        && !Task::exists(
+            &deployment.db().pool,
+            parent_task_id,
+            payload.task.project_id,
+        )
+        .await?
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Parent task not found in this project",
+        )));
+    }
+    // Starting moves the new task to in progress
+    let wip_warning = match wip_limits::check_move(
+        &deployment.db().pool,
+        payload.task.project_id,
+        &TaskStatus::Todo,
+        TaskStatus::InProgress,
+    )
+    .await?
+    {
+        Some(exceeded) if exceeded.enforcement == WipLimitEnforcement::Refuse => {
+            return Err(ContainerError::WipLimitReached(exceeded).into());
+        }
+        exceeded => exceeded,
+    };
     let task_id = Uuid::new_v4();
     let task = Task::create(&deployment.db().pool, &payload.task, task_id).await?;
 
@@ -202,6 +291,7 @@ pub async fn create_task_and_start(
             executor: payload.executor_profile_id.executor,
             base_branch: payload.base_branch,
             branch: git_branch_name,
+            started_by: attribution::current_user(),
         },
         attempt_id,
         task.id,
@@ -230,14 +320,35 @@ pub async fn create_task_and_start(
     let tags = Task::find_tags_for_task(&deployment.db().pool, task.id).await?;
 
     tracing::info!("Started execution process {}", execution_process.id);
-    Ok(ResponseJson(ApiResponse::success(TaskWithAttemptStatus {
-        task,
-        has_in_progress_attempt: true,
-        has_merged_attempt: false,
-        last_attempt_failed: false,
-        executor: task_attempt.executor,
-        tags,
-    })))
+    Ok(ResponseJson(with_wip_warning(
+        TaskWithAttemptStatus {
+            task,
+            has_in_progress_attempt: true,
+            has_merged_attempt: false,
+            last_attempt_failed: false,
+            executor: task_attempt.executor,
+            tags,
+            preview_url: None,
+            staleness: TaskStaleness::default(),
+            needs_attention: None,
+            test_results: None,
+            is_blocked: false,
+            subtask_progress: None,
+            review_state: None,
+            diff_stats: None,
+        },
+        wip_warning,
+    )))
+}
+
+/// Respond with `data`, passing on a warning about an exceeded work-in-progress limit
+pub(crate) fn with_wip_warning<T>(data: T, warning: Option<WipLimitExceeded>) -> ApiResponse<T> {
+    match warning {
+        Some(exceeded) => {
+            ApiResponse::success_with_message(data, &exceeded.message(request_locale()))
+        }
+        None => ApiResponse::success(data),
+    }
 }
 
 pub async fn update_task(
@@ -252,10 +363,41 @@ pub async fn update_task(
         Some(s) => Some(s),                     // Non-empty string = update description
         None => existing_task.description,      // Field omitted = keep existing
     };
-    let status = payload.status.unwrap_or(existing_task.status);
+    let mut status = payload.status.unwrap_or(existing_task.status.clone());
+    // Moving to a column takes on its status
+    if let Some(column_id) = payload.column_id {
+        let Some(column) = ProjectColumn::find_by_id_and_project_id(
+            &deployment.db().pool,
+            column_id,
+            existing_task.project_id,
+        )
+        .await?
+        else {
+            return Ok(ResponseJson(ApiResponse::error("Column not found")));
+        };
+        status = column.status;
+    }
+    let wip_warning = match wip_limits::check_move(
+        &deployment.db().pool,
+        existing_task.project_id,
+        &existing_task.status,
+        status.clone(),
+    )
+    .await?
+    {
+        Some(exceeded) if exceeded.enforcement == WipLimitEnforcement::Refuse => {
+            return Err(ApiError::Conflict(exceeded.message(request_locale())));
+        }
+        exceeded => exceeded,
+    };
     let parent_task_attempt = payload
         .parent_task_attempt
         .or(existing_task.parent_task_attempt);
+    let priority = payload.priority.unwrap_or(existing_task.priority);
+    let assignee = match payload.assignee {
+        Some(assignee) => attribution::normalize_user(&assignee), // Empty string = unassign
+        None => existing_task.assignee,
+    };
 
     let task = Task::update(
         &deployment.db().pool,
@@ -265,9 +407,17 @@ pub async fn update_task(
         description,
         status,
         parent_task_attempt,
+        priority,
+        assignee,
+        payload.column_id,
     )
     .await?;
 
+    // Queued coding agents start in priority order
+    if task.priority != existing_task.priority {
+        ExecutionProcess::update_queue_positions(&deployment.db().pool).await?;
+    }
+
     if let Some(image_ids) = &payload.image_ids {
         TaskImage::delete_by_task_id(&deployment.db().pool, task.id).await?;
         TaskImage::associate_many_dedup(&deployment.db().pool, task.id, image_ids).await?;
@@ -277,7 +427,47 @@ pub async fn update_task(
         Task::set_tags(&deployment.db().pool, task.id, tag_ids.clone()).await?;
     }
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    Ok(ResponseJson(with_wip_warning(task, wip_warning)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ReorderTasksRequest {
+    pub project_id: Uuid,
+    /// A board column's tasks, top to bottom
+    pub task_ids: Vec<Uuid>,
+}
+
+/// Persist the manual order of a board column
+pub async fn reorder_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderTasksRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    Task::reorder(&deployment.db().pool, payload.project_id, &payload.task_ids).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Stop every running or queued process of all the task's attempts
+pub async fn stop_task_processes(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<StopSummary>>, ApiError> {
+    let attempts = TaskAttempt::fetch_all(&deployment.db().pool, Some(task.id)).await?;
+    let summary = deployment
+        .container()
+        .stop_task_processes(&attempts)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_processes_stopped",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "stopped": summary.stopped.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(summary)))
 }
 
 pub async fn delete_task(
@@ -342,6 +532,41 @@ pub async fn delete_task(
     // Commit the transaction - if this fails, all changes are rolled back
     tx.commit().await?;
 
+    let pool = deployment.db().pool.clone();
+    AuditEvent::new(AuditAction::TaskDelete, &task.title)
+        .task(task.project_id, task.id)
+        .parameters(serde_json::json!({
+            "attempt_ids": attempts.iter().map(|attempt| attempt.id).collect::<Vec<_>>(),
+            "children_detached": total_children_affected,
+        }))
+        .record(&pool)
+        .await;
+    // Built now, as the request's user isn't known in the background cleanup
+    let worktree_events: Vec<AuditEvent> = cleanup_data
+        .iter()
+        .map(|data| {
+            AuditEvent::new(
+                AuditAction::WorktreeDelete,
+                data.worktree_path.to_string_lossy(),
+            )
+            .task(task.project_id, task.id)
+            .attempt(data.attempt_id)
+            .parameters(serde_json::json!({ "reason": "task_delete" }))
+        })
+        .collect();
+
+    // The parent's subtask progress changed; the delete hook only removes the task itself
+    if let Some(parent_task_id) = task.parent_task_id
+        && let Err(e) = deployment.events().refresh_task(parent_task_id).await
+    {
+        tracing::error!(
+            "Failed to refresh parent task {} after deleting subtask {}: {}",
+            parent_task_id,
+            task.id,
+            e
+        );
+    }
+
     if total_children_affected > 0 {
         tracing::info!(
             "Nullified {} child task references before deleting task {}",
@@ -363,6 +588,7 @@ pub async fn delete_task(
 
     // Spawn background worktree cleanup task
     let task_id = task.id;
+    let terminals = deployment.container().terminals().clone();
     tokio::spawn(async move {
         let span = tracing::info_span!("background_worktree_cleanup", task_id = %task_id);
         let _enter = span.enter();
@@ -373,7 +599,7 @@ pub async fn delete_task(
             cleanup_data.len()
         );
 
-        if let Err(e) = cleanup_worktrees_direct(&cleanup_data).await {
+        if let Err(e) = cleanup_worktrees_direct(&cleanup_data, &terminals).await {
             tracing::error!(
                 "Background worktree cleanup failed for task {}: {}",
                 task_id,
@@ -382,6 +608,16 @@ pub async fn delete_task(
         } else {
             tracing::info!("Background cleanup completed for task {}", task_id);
         }
+
+        for (event, data) in worktree_events.into_iter().zip(&cleanup_data) {
+            // cleanup_worktrees_direct only logs failures, so check what is actually gone
+            let result = if data.worktree_path.exists() {
+                Err("The worktree could not be removed")
+            } else {
+                Ok(())
+            };
+            event.outcome(&result).record(&pool).await;
+        }
     });
 
     // Return 202 Accepted to indicate deletion was scheduled
@@ -391,12 +627,39 @@ pub async fn delete_task(
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_id_router = Router::new()
         .route("/", get(get_task).put(update_task).delete(delete_task))
+        .route("/stop", post(stop_task_processes))
+        .route(
+            "/dependencies",
+            get(dependencies::get_task_dependencies).post(dependencies::add_task_dependency),
+        )
+        .route(
+            "/dependencies/{depends_on_task_id}",
+            delete(dependencies::remove_task_dependency),
+        )
+        .route(
+            "/subtasks",
+            get(subtasks::get_subtasks).post(subtasks::create_subtask),
+        )
+        .route(
+            "/comments",
+            get(comments::get_task_comments).post(comments::create_task_comment),
+        )
+        .route(
+            "/comments/stream/ws",
+            get(comments::stream_task_comments_ws),
+        )
+        .route(
+            "/comments/{comment_id}",
+            put(comments::update_task_comment).delete(comments::delete_task_comment),
+        )
         .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
 
     let inner = Router::new()
         .route("/", get(get_tasks).post(create_task))
         .route("/stream/ws", get(stream_tasks_ws))
         .route("/create-and-start", post(create_task_and_start))
+        .route("/reorder", post(reorder_tasks))
+        .route("/wip", get(get_wip_usage))
         .nest("/{task_id}", task_id_router);
 
     // mount under /projects/:project_id/tasks
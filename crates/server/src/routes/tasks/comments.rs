@@ -0,0 +1,144 @@
+use axum::{
+    Extension, Json,
+    extract::{
+        Path, State,
+        ws::{WebSocket, WebSocketUpgrade},
+    },
+    response::{IntoResponse, Json as ResponseJson},
+};
+use db::models::{
+    execution_process::ExecutionProcess,
+    task::Task,
+    task_comment::{CreateTaskComment, TaskComment, UpdateTaskComment},
+};
+use deployment::Deployment;
+use futures_util::{SinkExt, StreamExt, TryStreamExt};
+use services::services::attribution;
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// The task's discussion, oldest comment first
+pub async fn get_task_comments(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskComment>>>, ApiError> {
+    let comments = TaskComment::find_by_task_id(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(comments)))
+}
+
+pub async fn create_task_comment(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskComment>,
+) -> Result<ResponseJson<ApiResponse<TaskComment>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if payload.body.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("Comment can't be empty")));
+    }
+    if let Some(execution_process_id) = payload.execution_process_id {
+        let belongs_to_task = match ExecutionProcess::load_context(pool, execution_process_id).await
+        {
+            Ok(context) => context.task.id == task.id,
+            Err(SqlxError::RowNotFound) => false,
+            Err(e) => return Err(e.into()),
+        };
+        if !belongs_to_task {
+            return Ok(ResponseJson(ApiResponse::error(
+                "Execution process not found for this task",
+            )));
+        }
+    }
+
+    let comment = TaskComment::create(pool, task.id, attribution::current_user(), &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_comment_created",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "has_execution_process": comment.execution_process_id.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(comment)))
+}
+
+pub async fn update_task_comment(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, comment_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateTaskComment>,
+) -> Result<ResponseJson<ApiResponse<TaskComment>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if payload.body.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("Comment can't be empty")));
+    }
+    TaskComment::find_by_id_and_task_id(pool, comment_id, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    let comment = TaskComment::update(pool, comment_id, &payload.body).await?;
+    Ok(ResponseJson(ApiResponse::success(comment)))
+}
+
+pub async fn delete_task_comment(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    TaskComment::find_by_id_and_task_id(pool, comment_id, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    TaskComment::delete(pool, comment_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn stream_task_comments_ws(
+    ws: WebSocketUpgrade,
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_task_comments_ws(socket, deployment, task.id).await {
+            tracing::warn!("task comments WS closed: {}", e);
+        }
+    })
+}
+
+async fn handle_task_comments_ws(
+    socket: WebSocket,
+    deployment: DeploymentImpl,
+    task_id: Uuid,
+) -> anyhow::Result<()> {
+    let mut stream = deployment
+        .events()
+        .stream_task_comments_raw(task_id)
+        .await?
+        .map_ok(|msg| msg.to_ws_message_unchecked());
+
+    let (mut sender, mut receiver) = socket.split();
+
+    // Drain (and ignore) any client->server messages so pings/pongs work
+    tokio::spawn(async move { while let Some(Ok(_)) = receiver.next().await {} });
+
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(msg) => {
+                if sender.send(msg).await.is_err() {
+                    break; // client disconnected
+                }
+            }
+            Err(e) => {
+                tracing::error!("stream error: {}", e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
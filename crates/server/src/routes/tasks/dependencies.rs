@@ -0,0 +1,86 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{task::Task, task_dependency::TaskDependency};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct AddTaskDependencyRequest {
+    /// Task that has to be done before this one can be started
+    pub depends_on_task_id: Uuid,
+}
+
+/// The tasks this task is blocked by until they are done
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let dependencies = TaskDependency::find_dependencies(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<AddTaskDependencyRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if payload.depends_on_task_id == task.id {
+        return Ok(ResponseJson(ApiResponse::error(
+            "A task cannot depend on itself",
+        )));
+    }
+    let Some(depends_on) = Task::find_by_id(pool, payload.depends_on_task_id).await? else {
+        return Ok(ResponseJson(ApiResponse::error("Task not found")));
+    };
+    if depends_on.project_id != task.project_id {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Tasks can only depend on tasks in the same project",
+        )));
+    }
+    if TaskDependency::depends_on_transitively(pool, depends_on.id, task.id).await? {
+        return Err(ApiError::Conflict(format!(
+            "'{}' already depends on '{}'",
+            depends_on.title, task.title
+        )));
+    }
+
+    TaskDependency::create(pool, task.id, depends_on.id).await?;
+    deployment.events().refresh_task(task.id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_dependency_added",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "depends_on_task_id": depends_on.id.to_string(),
+            }),
+        )
+        .await;
+
+    let dependencies = TaskDependency::find_dependencies(pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+pub async fn remove_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, depends_on_task_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if TaskDependency::delete(pool, task.id, depends_on_task_id).await? == 0 {
+        return Ok(ResponseJson(ApiResponse::error("Dependency not found")));
+    }
+    deployment.events().refresh_task(task.id).await?;
+
+    let dependencies = TaskDependency::find_dependencies(pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
@@ -0,0 +1,55 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::task::{CreateTask, Task};
+use deployment::Deployment;
+use serde::Deserialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateSubtaskRequest {
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// The task's subtasks, oldest first
+pub async fn get_subtasks(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let subtasks = Task::find_subtasks(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(subtasks)))
+}
+
+pub async fn create_subtask(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateSubtaskRequest>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    if payload.title.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Subtask title is required",
+        )));
+    }
+
+    let create = CreateTask {
+        parent_task_id: Some(task.id),
+        ..CreateTask::from_title_description(task.project_id, payload.title, payload.description)
+    };
+    let subtask = Task::create(&deployment.db().pool, &create, Uuid::new_v4()).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "subtask_created",
+            serde_json::json!({
+                "task_id": subtask.id.to_string(),
+                "parent_task_id": task.id.to_string(),
+                "project_id": task.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(subtask)))
+}
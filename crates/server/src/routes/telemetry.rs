@@ -0,0 +1,52 @@
+use axum::{
+    Router,
+    extract::{MatchedPath, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{Json as ResponseJson, Response},
+    routing::get,
+};
+use deployment::Deployment;
+use services::services::telemetry::TelemetrySnapshot;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Locally aggregated usage statistics, for the instance operator
+pub async fn get_telemetry(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TelemetrySnapshot>>, ApiError> {
+    let snapshot = deployment.telemetry().snapshot().await;
+    Ok(ResponseJson(ApiResponse::success(snapshot)))
+}
+
+pub async fn reset_telemetry(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment.telemetry().reset().await;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Count requests and error responses per route template
+pub async fn telemetry_middleware(
+    State(deployment): State<DeploymentImpl>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let response = next.run(req).await;
+    if let Some(route) = route {
+        deployment
+            .telemetry()
+            .record_request(&route, response.status().as_u16())
+            .await;
+    }
+    Ok(response)
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/telemetry", get(get_telemetry).delete(reset_telemetry))
+}
@@ -0,0 +1,134 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Query, State},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{
+    project::Project,
+    webhook::{CreateWebhook, UpdateWebhook, Webhook, WebhookDelivery},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::webhook::{self, WebhookError};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_webhook_middleware};
+
+/// Deliveries listed per webhook, newest first
+const DELIVERY_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookQuery {
+    /// Only the project's own webhooks; all webhooks when unset
+    pub project_id: Option<Uuid>,
+}
+
+pub async fn get_webhooks(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WebhookQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<Webhook>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let webhooks = match query.project_id {
+        Some(project_id) => Webhook::find_by_project_id(pool, project_id).await?,
+        None => Webhook::find_all(pool).await?,
+    };
+    Ok(ResponseJson(ApiResponse::success(webhooks)))
+}
+
+pub async fn get_webhook(
+    Extension(webhook): Extension<Webhook>,
+) -> Result<ResponseJson<ApiResponse<Webhook>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(webhook)))
+}
+
+pub async fn create_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateWebhook>,
+) -> Result<ResponseJson<ApiResponse<Webhook>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if let Some(project_id) = payload.project_id {
+        Project::find_by_id(pool, project_id)
+            .await?
+            .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    }
+
+    let webhook = match webhook::create(pool, &payload).await {
+        Ok(webhook) => webhook,
+        Err(WebhookError::Database(e)) => return Err(e.into()),
+        Err(WebhookError::Secret(e)) => return Err(e.into()),
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+
+    deployment
+        .track_if_analytics_allowed(
+            "webhook_created",
+            serde_json::json!({
+                "webhook_id": webhook.id.to_string(),
+                "global": webhook.project_id.is_none(),
+                "events": webhook.events.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(webhook)))
+}
+
+pub async fn update_webhook(
+    Extension(webhook): Extension<Webhook>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateWebhook>,
+) -> Result<ResponseJson<ApiResponse<Webhook>>, ApiError> {
+    let updated = match webhook::update(&deployment.db().pool, &webhook, &payload).await {
+        Ok(updated) => updated,
+        Err(WebhookError::Database(e)) => return Err(e.into()),
+        Err(WebhookError::Secret(e)) => return Err(e.into()),
+        Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.to_string()))),
+    };
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_webhook(
+    Extension(webhook): Extension<Webhook>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = Webhook::delete(&deployment.db().pool, webhook.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(SqlxError::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Delivery log of the webhook: what was sent most recently and how the endpoint responded
+pub async fn get_webhook_deliveries(
+    Extension(webhook): Extension<Webhook>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebhookDelivery>>>, ApiError> {
+    let deliveries =
+        WebhookDelivery::find_by_webhook_id(&deployment.db().pool, webhook.id, DELIVERY_LIMIT)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(deliveries)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let webhook_router = Router::new()
+        .route(
+            "/",
+            get(get_webhook).put(update_webhook).delete(delete_webhook),
+        )
+        .route("/deliveries", get(get_webhook_deliveries))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_webhook_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_webhooks).post(create_webhook))
+        .nest("/{webhook_id}", webhook_router);
+
+    Router::new().nest("/webhooks", inner)
+}
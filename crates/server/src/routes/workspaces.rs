@@ -0,0 +1,139 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{State, ws::WebSocketUpgrade},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Json as ResponseJson},
+    routing::get,
+};
+use db::models::{
+    task::{Task, TaskWithAttemptStatus},
+    workspace::{CreateWorkspace, UpdateWorkspace, Workspace},
+};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_workspace_middleware,
+    routes::tasks::handle_tasks_ws,
+};
+
+fn empty_name() -> ResponseJson<ApiResponse<Workspace>> {
+    ResponseJson(ApiResponse::error("Workspace name cannot be empty"))
+}
+
+pub async fn get_workspaces(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Workspace>>>, ApiError> {
+    let workspaces = Workspace::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(workspaces)))
+}
+
+pub async fn get_workspace(
+    Extension(workspace): Extension<Workspace>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+pub async fn create_workspace(
+    State(deployment): State<DeploymentImpl>,
+    Json(mut payload): Json<CreateWorkspace>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    payload.name = payload.name.trim().to_string();
+    if payload.name.is_empty() {
+        return Ok(empty_name());
+    }
+    let workspace = Workspace::create(&deployment.db().pool, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_created",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "project_count": workspace.project_ids.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+pub async fn update_workspace(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Json(mut payload): Json<UpdateWorkspace>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    payload.name = payload.name.map(|name| name.trim().to_string());
+    if payload.name.as_deref() == Some("") {
+        return Ok(empty_name());
+    }
+    let updated = Workspace::update(&deployment.db().pool, workspace.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_workspace(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = Workspace::delete(&deployment.db().pool, workspace.id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// The tasks of every project in the workspace, grouped by project in workspace order
+pub async fn get_workspace_tasks(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, ApiError> {
+    let staleness_thresholds = deployment.config().read().await.staleness.clone();
+    let mut tasks = Vec::new();
+    for project_id in workspace.project_ids {
+        tasks.extend(
+            Task::find_by_project_id_with_attempt_status(
+                &deployment.db().pool,
+                project_id,
+                &staleness_thresholds,
+            )
+            .await?,
+        );
+    }
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
+/// Task updates of every project in the workspace. Clients reconnect after changing the
+/// workspace's projects to pick up the new set.
+pub async fn stream_workspace_tasks_ws(
+    ws: WebSocketUpgrade,
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_tasks_ws(socket, deployment, workspace.project_ids).await {
+            tracing::warn!("workspace tasks WS closed: {}", e);
+        }
+    })
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let workspace_router = Router::new()
+        .route(
+            "/",
+            get(get_workspace)
+                .put(update_workspace)
+                .delete(delete_workspace),
+        )
+        .route("/tasks", get(get_workspace_tasks))
+        .route("/tasks/stream/ws", get(stream_workspace_tasks_ws))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_middleware,
+        ));
+
+    let inner = Router::new()
+        .route("/", get(get_workspaces).post(create_workspace))
+        .nest("/{workspace_id}", workspace_router);
+
+    Router::new().nest("/workspaces", inner)
+}
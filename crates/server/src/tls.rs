@@ -0,0 +1,126 @@
+//! Serving over HTTPS with a certificate and key from PEM files, for exposing the server on a
+//! network without a TLS-terminating proxy in front of it.
+
+use std::{fs::File, io::BufReader, net::SocketAddr, path::Path, sync::Arc, time::Duration};
+
+use anyhow::{Context, anyhow};
+use axum::serve::Listener;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{self, ServerConfig},
+    server::TlsStream,
+};
+
+/// Path of the PEM certificate chain to serve HTTPS with
+pub const TLS_CERT_ENV: &str = "TLS_CERT_PATH";
+/// Path of the PEM private key of the certificate
+pub const TLS_KEY_ENV: &str = "TLS_KEY_PATH";
+
+/// Connections dropped when their handshake hasn't finished by then
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Handshaken connections waiting for the server to take them
+const ACCEPT_BACKLOG: usize = 64;
+
+/// The certificate and key paths from the environment, if HTTPS is configured
+pub fn paths_from_env() -> anyhow::Result<Option<(String, String)>> {
+    let path = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+    };
+    match (path(TLS_CERT_ENV), path(TLS_KEY_ENV)) {
+        (Some(cert), Some(key)) => Ok(Some((cert, key))),
+        (None, None) => Ok(None),
+        _ => Err(anyhow!(
+            "{TLS_CERT_ENV} and {TLS_KEY_ENV} must be set together to serve HTTPS"
+        )),
+    }
+}
+
+pub fn load_server_config(cert_path: &Path, key_path: &Path) -> anyhow::Result<ServerConfig> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(
+        File::open(cert_path).with_context(|| format!("Failed to open {}", cert_path.display()))?,
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .with_context(|| format!("Invalid certificate in {}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(anyhow!("No certificate found in {}", cert_path.display()));
+    }
+    let key = rustls_pemfile::private_key(&mut BufReader::new(
+        File::open(key_path).with_context(|| format!("Failed to open {}", key_path.display()))?,
+    ))
+    .with_context(|| format!("Invalid private key in {}", key_path.display()))?
+    .ok_or_else(|| anyhow!("No private key found in {}", key_path.display()))?;
+
+    let mut config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("The private key doesn't match the certificate")?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Listener handing out connections once their TLS handshake is done. Handshakes run
+/// concurrently, so a slow client doesn't hold up the others.
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, config: ServerConfig) -> std::io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let (sender, connections) = mpsc::channel(ACCEPT_BACKLOG);
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        tracing::warn!("Failed to accept connection: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = sender.send((stream, addr)).await;
+                        }
+                        Ok(Err(e)) => tracing::debug!("TLS handshake with {} failed: {}", addr, e),
+                        Err(_) => tracing::debug!("TLS handshake with {} timed out", addr),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            local_addr,
+            connections,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accept loop only stops once the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
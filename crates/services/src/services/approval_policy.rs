@@ -0,0 +1,317 @@
+//! Applies a project's [`ApprovalPolicy`] to the tool calls its coding agents ask approval for,
+//! so routine calls are answered without waiting for the user.
+//!
+//! Shell commands and file deletions are read from the input of the tools the executors ask
+//! about: Claude Code's `Bash`, whose `command` is a string, and Codex's `bash` and `edit`,
+//! whose input is the command's arguments or the patch's changes by file.
+
+use std::sync::LazyLock;
+
+use chrono::Duration;
+use db::models::project::{ApprovalAction, ApprovalMatcher, ApprovalPolicy};
+use regex::Regex;
+use serde_json::Value;
+use thiserror::Error;
+use utils::approvals::{APPROVAL_TIMEOUT_SECONDS, ApprovalStatus};
+
+/// Commands that delete files, at the start of the command or of one chained to it
+static DELETE_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(^|[;&|(]|\$\()\s*(sudo\s+)?(rm|rmdir|unlink|shred|git\s+rm)\b").unwrap()
+});
+static PUSH_COMMAND: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bgit\s+(-[Cc]\s+\S+\s+|-\S+\s+)*push\b").unwrap());
+/// Longest time the user can be given to answer
+const MAX_TIMEOUT_SECS: u64 = 7 * 24 * 3600;
+
+#[derive(Debug, Error)]
+pub enum ApprovalPolicyError {
+    #[error("Invalid command pattern '{0}': {1}")]
+    InvalidPattern(String, regex::Error),
+    #[error("Tool rules need a tool name")]
+    MissingToolName,
+    #[error("The timeout must be between one second and a week")]
+    InvalidTimeout,
+    #[error("Requests that time out must be approved or denied")]
+    InvalidTimeoutAction,
+}
+
+fn command_pattern(pattern: &str) -> Result<Regex, ApprovalPolicyError> {
+    Regex::new(pattern).map_err(|e| ApprovalPolicyError::InvalidPattern(pattern.to_string(), e))
+}
+
+/// Check that the policy's patterns compile and that timeouts have an answer
+pub fn validate(policy: &ApprovalPolicy) -> Result<(), ApprovalPolicyError> {
+    for rule in &policy.rules {
+        match &rule.matcher {
+            ApprovalMatcher::Tool { name } if name.trim().is_empty() => {
+                return Err(ApprovalPolicyError::MissingToolName);
+            }
+            ApprovalMatcher::ShellCommand { pattern } => {
+                command_pattern(pattern)?;
+            }
+            _ => {}
+        }
+    }
+    if policy
+        .timeout_secs
+        .is_some_and(|secs| secs == 0 || secs > MAX_TIMEOUT_SECS)
+    {
+        return Err(ApprovalPolicyError::InvalidTimeout);
+    }
+    if policy.on_timeout == ApprovalAction::Ask {
+        return Err(ApprovalPolicyError::InvalidTimeoutAction);
+    }
+    Ok(())
+}
+
+/// How a tool call is answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Ask the user
+    Ask,
+    /// Approved by the rule described
+    Approve { rule: String },
+    /// Denied by the rule described
+    Deny { rule: String },
+}
+
+/// What the agent wants to do, as far as the rules can tell
+struct ToolCall<'a> {
+    name: &'a str,
+    command: Option<String>,
+    deletes_files: bool,
+}
+
+impl<'a> ToolCall<'a> {
+    fn new(name: &'a str, input: &Value) -> Self {
+        let command = shell_command(name, input);
+        let deletes_files = command
+            .as_deref()
+            .is_some_and(|command| DELETE_COMMAND.is_match(command))
+            || patch_deletes_files(input);
+        Self {
+            name,
+            command,
+            deletes_files,
+        }
+    }
+
+    fn matches(&self, matcher: &ApprovalMatcher) -> bool {
+        match matcher {
+            ApprovalMatcher::Tool { name } => {
+                let name = name.trim();
+                name == "*" || name.eq_ignore_ascii_case(self.name)
+            }
+            ApprovalMatcher::ShellCommand { pattern } => {
+                match (&self.command, command_pattern(pattern)) {
+                    (Some(command), Ok(pattern)) => pattern.is_match(command),
+                    _ => false,
+                }
+            }
+            ApprovalMatcher::FileDeletion => self.deletes_files,
+            ApprovalMatcher::GitPush => self
+                .command
+                .as_deref()
+                .is_some_and(|command| PUSH_COMMAND.is_match(command)),
+        }
+    }
+}
+
+fn shell_command(tool_name: &str, input: &Value) -> Option<String> {
+    if !["bash", "shell", "exec_command"]
+        .iter()
+        .any(|shell| tool_name.eq_ignore_ascii_case(shell))
+    {
+        return None;
+    }
+    match input.get("command")? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(args) => {
+            let args: Vec<&str> = args.iter().filter_map(Value::as_str).collect();
+            // Codex runs scripts as `bash -lc <script>`
+            match args.as_slice() {
+                [_, flag, script] if matches!(*flag, "-c" | "-lc") => Some(script.to_string()),
+                _ => Some(args.join(" ")),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether a patch's changes, by file, remove one
+fn patch_deletes_files(input: &Value) -> bool {
+    let Some(changes) = input.get("changes").and_then(Value::as_object) else {
+        return false;
+    };
+    changes.values().any(|change| {
+        change.get("delete").is_some()
+            || change.get("type").and_then(Value::as_str) == Some("delete")
+    })
+}
+
+fn describe(matcher: &ApprovalMatcher) -> String {
+    match matcher {
+        ApprovalMatcher::Tool { name } => format!("tool {}", name.trim()),
+        ApprovalMatcher::ShellCommand { pattern } => format!("shell command /{pattern}/"),
+        ApprovalMatcher::FileDeletion => "file deletion".to_string(),
+        ApprovalMatcher::GitPush => "git push".to_string(),
+    }
+}
+
+fn decision(action: ApprovalAction, rule: String) -> PolicyDecision {
+    match action {
+        ApprovalAction::Ask => PolicyDecision::Ask,
+        ApprovalAction::Approve => PolicyDecision::Approve { rule },
+        ApprovalAction::Deny => PolicyDecision::Deny { rule },
+    }
+}
+
+/// Apply the policy to a tool call: the first matching rule decides, otherwise the default
+/// action. Without an enabled policy the user is asked.
+pub fn decide(
+    policy: Option<&ApprovalPolicy>,
+    tool_name: &str,
+    tool_input: &Value,
+) -> PolicyDecision {
+    let Some(policy) = policy.filter(|policy| policy.enabled) else {
+        return PolicyDecision::Ask;
+    };
+    let call = ToolCall::new(tool_name, tool_input);
+    match policy.rules.iter().find(|rule| call.matches(&rule.matcher)) {
+        Some(rule) => decision(rule.action, format!("Rule: {}", describe(&rule.matcher))),
+        None => decision(policy.default_action, "Default action".to_string()),
+    }
+}
+
+/// How long the user has to answer, and the answer given when they don't
+pub fn timeout(policy: Option<&ApprovalPolicy>) -> (Duration, ApprovalStatus) {
+    let policy = policy.filter(|policy| policy.enabled);
+    let secs = match policy.and_then(|policy| policy.timeout_secs) {
+        Some(secs) => secs.clamp(1, MAX_TIMEOUT_SECS) as i64,
+        None => APPROVAL_TIMEOUT_SECONDS,
+    };
+    let status = match policy.map(|policy| policy.on_timeout) {
+        Some(ApprovalAction::Approve) => ApprovalStatus::Approved,
+        _ => ApprovalStatus::TimedOut,
+    };
+    (Duration::seconds(secs), status)
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::project::ApprovalRule;
+    use serde_json::json;
+
+    use super::*;
+
+    fn policy(rules: Vec<(ApprovalMatcher, ApprovalAction)>) -> ApprovalPolicy {
+        ApprovalPolicy {
+            enabled: true,
+            rules: rules
+                .into_iter()
+                .map(|(matcher, action)| ApprovalRule { matcher, action })
+                .collect(),
+            default_action: ApprovalAction::Ask,
+            timeout_secs: None,
+            on_timeout: ApprovalAction::Deny,
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let policy = policy(vec![
+            (ApprovalMatcher::GitPush, ApprovalAction::Deny),
+            (ApprovalMatcher::FileDeletion, ApprovalAction::Deny),
+            (
+                ApprovalMatcher::ShellCommand {
+                    pattern: "^(npm|pnpm) (test|run lint)".to_string(),
+                },
+                ApprovalAction::Approve,
+            ),
+            (
+                ApprovalMatcher::Tool {
+                    name: "read".to_string(),
+                },
+                ApprovalAction::Approve,
+            ),
+        ]);
+        let check = |tool: &str, input: Value| decide(Some(&policy), tool, &input);
+
+        assert_eq!(
+            check("Bash", json!({ "command": "git -C repo push origin main" })),
+            PolicyDecision::Deny {
+                rule: "Rule: git push".to_string()
+            }
+        );
+        assert_eq!(
+            check("Bash", json!({ "command": "cd src && rm -rf build" })),
+            PolicyDecision::Deny {
+                rule: "Rule: file deletion".to_string()
+            }
+        );
+        assert!(matches!(
+            check(
+                "bash",
+                json!({ "command": ["bash", "-lc", "pnpm test -- --watch=false"] })
+            ),
+            PolicyDecision::Approve { .. }
+        ));
+        assert!(matches!(
+            check("Read", json!({ "file_path": "src/main.rs" })),
+            PolicyDecision::Approve { .. }
+        ));
+        // `rm` in an argument isn't a deletion, and nothing else matches
+        assert_eq!(
+            check("Bash", json!({ "command": "grep -r rm src" })),
+            PolicyDecision::Ask
+        );
+        assert_eq!(
+            check(
+                "edit",
+                json!({ "changes": { "old.rs": { "delete": { "content": "" } } } })
+            ),
+            PolicyDecision::Deny {
+                rule: "Rule: file deletion".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn disabled_policies_ask_and_wait_the_default_time() {
+        let mut policy = policy(vec![(
+            ApprovalMatcher::Tool {
+                name: "*".to_string(),
+            },
+            ApprovalAction::Approve,
+        )]);
+        policy.timeout_secs = Some(60);
+        policy.on_timeout = ApprovalAction::Approve;
+        let (wait, status) = timeout(Some(&policy));
+        assert_eq!(wait, Duration::seconds(60));
+        assert!(matches!(status, ApprovalStatus::Approved));
+
+        policy.enabled = false;
+        assert_eq!(
+            decide(Some(&policy), "Bash", &json!({ "command": "ls" })),
+            PolicyDecision::Ask
+        );
+        let (wait, status) = timeout(Some(&policy));
+        assert_eq!(wait, Duration::seconds(APPROVAL_TIMEOUT_SECONDS));
+        assert!(matches!(status, ApprovalStatus::TimedOut));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns_and_timeouts() {
+        let mut policy = policy(vec![(
+            ApprovalMatcher::ShellCommand {
+                pattern: "(unclosed".to_string(),
+            },
+            ApprovalAction::Approve,
+        )]);
+        assert!(validate(&policy).is_err());
+        policy.rules.clear();
+        assert!(validate(&policy).is_ok());
+        policy.on_timeout = ApprovalAction::Ask;
+        assert!(validate(&policy).is_err());
+    }
+}
@@ -4,6 +4,9 @@ use std::{collections::HashMap, sync::Arc, time::Duration as StdDuration};
 
 use dashmap::DashMap;
 use db::models::{
+    approval_decision::{
+        ApprovalDecider, ApprovalDecision, ApprovalOutcome, CreateApprovalDecision,
+    },
     execution_process::ExecutionProcess,
     task::{Task, TaskStatus},
 };
@@ -15,6 +18,7 @@ use executors::{
     },
 };
 use futures::future::{BoxFuture, FutureExt, Shared};
+use serde_json::Value;
 use sqlx::{Error as SqlxError, SqlitePool};
 use thiserror::Error;
 use tokio::sync::{RwLock, oneshot};
@@ -25,12 +29,15 @@ use utils::{
 };
 use uuid::Uuid;
 
+use crate::services::attribution;
+
 #[derive(Debug)]
 struct PendingApproval {
     entry_index: usize,
     entry: NormalizedEntry,
     execution_process_id: Uuid,
     tool_name: String,
+    tool_input: Value,
     response_tx: oneshot::Sender<ApprovalStatus>,
 }
 
@@ -74,9 +81,13 @@ impl Approvals {
         }
     }
 
+    /// Register the request and wait for the user's answer until `request.timeout_at`, when
+    /// `on_timeout` is given instead
     pub async fn create_with_waiter(
         &self,
+        pool: &SqlitePool,
         request: ApprovalRequest,
+        on_timeout: ApprovalStatus,
     ) -> Result<(ApprovalRequest, ApprovalWaiter), ApprovalError> {
         let (tx, rx) = oneshot::channel();
         let waiter: ApprovalWaiter = rx
@@ -106,6 +117,7 @@ impl Approvals {
                         entry: matching_tool,
                         execution_process_id: request.execution_process_id,
                         tool_name: request.tool_name.clone(),
+                        tool_input: request.tool_input.clone(),
                         response_tx: tx,
                     },
                 );
@@ -129,10 +141,23 @@ impl Approvals {
             );
         }
 
-        self.spawn_timeout_watcher(req_id.clone(), request.timeout_at, waiter.clone());
+        self.spawn_timeout_watcher(
+            pool.clone(),
+            req_id.clone(),
+            request.timeout_at,
+            on_timeout,
+            waiter.clone(),
+        );
         Ok((request, waiter))
     }
 
+    /// Whether the execution process is currently blocked on a user decision
+    pub fn has_pending(&self, execution_process_id: &Uuid) -> bool {
+        self.pending
+            .iter()
+            .any(|entry| entry.execution_process_id == *execution_process_id)
+    }
+
     #[tracing::instrument(skip(self, id, req))]
     pub async fn respond(
         &self,
@@ -161,6 +186,23 @@ impl Approvals {
                 );
             }
 
+            if let Some((outcome, reason)) = outcome_of(&req.status) {
+                record_decision(
+                    pool,
+                    CreateApprovalDecision {
+                        approval_id: id.to_string(),
+                        execution_process_id: p.execution_process_id,
+                        tool_name: p.tool_name.clone(),
+                        tool_input: p.tool_input,
+                        outcome,
+                        decided_by: ApprovalDecider::User,
+                        user_name: attribution::current_user(),
+                        reason,
+                    },
+                )
+                .await;
+            }
+
             let tool_ctx = ToolContext {
                 tool_name: p.tool_name,
                 execution_process_id: p.execution_process_id,
@@ -189,11 +231,13 @@ impl Approvals {
         }
     }
 
-    #[tracing::instrument(skip(self, id, timeout_at, waiter))]
+    #[tracing::instrument(skip(self, pool, id, timeout_at, on_timeout, waiter))]
     fn spawn_timeout_watcher(
         &self,
+        pool: SqlitePool,
         id: String,
         timeout_at: chrono::DateTime<chrono::Utc>,
+        on_timeout: ApprovalStatus,
         waiter: ApprovalWaiter,
     ) {
         let pending = self.pending.clone();
@@ -207,14 +251,13 @@ impl Approvals {
         let deadline = tokio::time::Instant::now() + to_wait;
 
         tokio::spawn(async move {
-            let status = tokio::select! {
+            let (status, is_timeout) = tokio::select! {
                 biased;
 
-                resolved = waiter.clone() => resolved,
-                _ = tokio::time::sleep_until(deadline) => ApprovalStatus::TimedOut,
+                resolved = waiter.clone() => (resolved, false),
+                _ = tokio::time::sleep_until(deadline) => (on_timeout, true),
             };
 
-            let is_timeout = matches!(&status, ApprovalStatus::TimedOut);
             completed.insert(id.clone(), status.clone());
 
             if is_timeout && let Some((_, pending_approval)) = pending.remove(&id) {
@@ -222,15 +265,33 @@ impl Approvals {
                     tracing::debug!("approval '{}' timeout notification receiver dropped", id);
                 }
 
+                if let Some((outcome, reason)) = outcome_of(&status) {
+                    record_decision(
+                        &pool,
+                        CreateApprovalDecision {
+                            approval_id: id.clone(),
+                            execution_process_id: pending_approval.execution_process_id,
+                            tool_name: pending_approval.tool_name.clone(),
+                            tool_input: pending_approval.tool_input.clone(),
+                            outcome,
+                            decided_by: ApprovalDecider::Timeout,
+                            user_name: None,
+                            reason,
+                        },
+                    )
+                    .await;
+                }
+
                 let store = {
                     let map = msg_stores.read().await;
                     map.get(&pending_approval.execution_process_id).cloned()
                 };
 
                 if let Some(store) = store {
-                    if let Some(updated_entry) = pending_approval
-                        .entry
-                        .with_tool_status(ToolStatus::TimedOut)
+                    if let Some(updated_entry) =
+                        ToolStatus::from_approval_status(&status).and_then(|tool_status| {
+                            pending_approval.entry.with_tool_status(tool_status)
+                        })
                     {
                         store.push_patch(ConversationPatch::replace(
                             pending_approval.entry_index,
@@ -270,6 +331,27 @@ pub(crate) async fn ensure_task_in_review(pool: &SqlitePool, execution_process_i
     }
 }
 
+/// The outcome of an answer, with the reason given for a denial
+pub(crate) fn outcome_of(status: &ApprovalStatus) -> Option<(ApprovalOutcome, Option<String>)> {
+    match status {
+        ApprovalStatus::Approved => Some((ApprovalOutcome::Approved, None)),
+        ApprovalStatus::Denied { reason } => Some((ApprovalOutcome::Denied, reason.clone())),
+        ApprovalStatus::TimedOut => Some((ApprovalOutcome::TimedOut, None)),
+        ApprovalStatus::Pending => None,
+    }
+}
+
+/// Add a decision to the audit trail; a failure is only logged
+pub(crate) async fn record_decision(pool: &SqlitePool, decision: CreateApprovalDecision) {
+    if let Err(e) = ApprovalDecision::create(pool, &decision).await {
+        tracing::warn!(
+            "Failed to record decision on approval {}: {}",
+            decision.approval_id,
+            e
+        );
+    }
+}
+
 /// Find a matching tool use entry that hasn't been assigned to an approval yet
 /// Matches by tool call id from tool metadata
 fn find_matching_tool_use(
@@ -1,28 +1,79 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use db::{self, DBService};
+use chrono::Utc;
+use db::{
+    self, DBService,
+    models::{
+        approval_decision::{ApprovalDecider, CreateApprovalDecision},
+        execution_process::ExecutionProcess,
+        project::ApprovalPolicy,
+    },
+};
 use executors::approvals::{ExecutorApprovalError, ExecutorApprovalService};
 use serde_json::Value;
+use tokio::sync::RwLock;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
 
-use crate::services::approvals::Approvals;
+use crate::services::{
+    approval_policy::{self, PolicyDecision},
+    approvals::{self, Approvals},
+    chat_notifier,
+    config::Config,
+    notification_feed::{self, NotificationTrigger},
+    webhook::{self, WebhookNotification},
+};
 
 pub struct ExecutorApprovalBridge {
     approvals: Approvals,
     db: DBService,
+    config: Arc<RwLock<Config>>,
     execution_process_id: Uuid,
 }
 
 impl ExecutorApprovalBridge {
-    pub fn new(approvals: Approvals, db: DBService, execution_process_id: Uuid) -> Arc<Self> {
+    pub fn new(
+        approvals: Approvals,
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        execution_process_id: Uuid,
+    ) -> Arc<Self> {
         Arc::new(Self {
             approvals,
             db,
+            config,
             execution_process_id,
         })
     }
+
+    /// Flag the process so the task list can show that it is waiting on the user
+    async fn set_awaiting_approval(&self, awaiting: bool) {
+        let awaiting_approval_at = awaiting.then(Utc::now);
+        if let Err(e) = ExecutionProcess::update_awaiting_approval_at(
+            &self.db.pool,
+            self.execution_process_id,
+            awaiting_approval_at,
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to update approval state of execution process {}: {}",
+                self.execution_process_id,
+                e
+            );
+        }
+    }
+
+    /// Approval policy of the project the process belongs to
+    async fn approval_policy(&self) -> Option<ApprovalPolicy> {
+        let pool = &self.db.pool;
+        let ctx = ExecutionProcess::load_context(pool, self.execution_process_id)
+            .await
+            .ok()?;
+        let project = ctx.task.parent_project(pool).await.ok()??;
+        project.approval_policy.map(|policy| policy.0)
+    }
 }
 
 #[async_trait]
@@ -33,9 +84,41 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         tool_input: Value,
         tool_call_id: &str,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
+        let policy = self.approval_policy().await;
+        let answered = match approval_policy::decide(policy.as_ref(), tool_name, &tool_input) {
+            PolicyDecision::Ask => None,
+            PolicyDecision::Approve { rule } => Some((ApprovalStatus::Approved, rule)),
+            PolicyDecision::Deny { rule } => Some((
+                ApprovalStatus::Denied {
+                    reason: Some(format!("Denied by the project's approval policy ({rule})")),
+                },
+                rule,
+            )),
+        };
+        if let Some((status, rule)) = answered {
+            if let Some((outcome, _)) = approvals::outcome_of(&status) {
+                approvals::record_decision(
+                    &self.db.pool,
+                    CreateApprovalDecision {
+                        approval_id: Uuid::new_v4().to_string(),
+                        execution_process_id: self.execution_process_id,
+                        tool_name: tool_name.to_string(),
+                        tool_input,
+                        outcome,
+                        decided_by: ApprovalDecider::Policy,
+                        user_name: None,
+                        reason: Some(rule),
+                    },
+                )
+                .await;
+            }
+            return Ok(status);
+        }
+
         super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
+        self.set_awaiting_approval(true).await;
 
-        let request = ApprovalRequest::from_create(
+        let mut request = ApprovalRequest::from_create(
             CreateApprovalRequest {
                 tool_name: tool_name.to_string(),
                 tool_input,
@@ -43,14 +126,36 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             },
             self.execution_process_id,
         );
+        let (timeout, on_timeout) = approval_policy::timeout(policy.as_ref());
+        request.timeout_at = request.created_at + timeout;
 
-        let (_, waiter) = self
+        let created = self
             .approvals
-            .create_with_waiter(request)
-            .await
-            .map_err(ExecutorApprovalError::request_failed)?;
-
-        let status = waiter.clone().await;
+            .create_with_waiter(&self.db.pool, request, on_timeout)
+            .await;
+        let status = match created {
+            Ok((request, waiter)) => {
+                webhook::spawn_dispatch(
+                    self.db.clone(),
+                    WebhookNotification::approval_requested(&request),
+                );
+                chat_notifier::spawn_notify_approval_requested(
+                    self.db.clone(),
+                    self.config.clone(),
+                    request.clone(),
+                );
+                notification_feed::spawn_publish(
+                    self.db.clone(),
+                    NotificationTrigger::ApprovalRequested(request.clone()),
+                );
+                waiter.clone().await
+            }
+            Err(e) => {
+                self.set_awaiting_approval(false).await;
+                return Err(ExecutorApprovalError::request_failed(e));
+            }
+        };
+        self.set_awaiting_approval(false).await;
 
         if matches!(status, ApprovalStatus::Pending) {
             return Err(ExecutorApprovalError::request_failed(
@@ -0,0 +1,215 @@
+//! Side-by-side results of attempts of one task started together with different executor
+//! profiles ("best of N"), so the best one can be kept and the rest discarded.
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    attempt_comparison::AttemptComparison,
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    task_attempt::TaskAttempt,
+    workflow_run::{WORKFLOW_RUN_COMPLETED, WorkflowRun},
+};
+use executors::profile::ExecutorProfileId;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+use utils::diff::Diff;
+
+pub const MIN_ATTEMPTS: usize = 2;
+pub const MAX_ATTEMPTS: usize = 6;
+
+#[derive(Debug, Error)]
+pub enum AttemptComparisonError {
+    #[error("Compare between {MIN_ATTEMPTS} and {MAX_ATTEMPTS} attempts")]
+    AttemptCount,
+    #[error("The attempt is not part of this comparison")]
+    NotAMember,
+}
+
+pub fn validate_profiles(profiles: &[ExecutorProfileId]) -> Result<(), AttemptComparisonError> {
+    if (MIN_ATTEMPTS..=MAX_ATTEMPTS).contains(&profiles.len()) {
+        Ok(())
+    } else {
+        Err(AttemptComparisonError::AttemptCount)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum CheckResult {
+    Pending,
+    Passed,
+    Failed,
+}
+
+impl CheckResult {
+    pub fn of_process(status: &ExecutionProcessStatus) -> Self {
+        match status {
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running => Self::Pending,
+            ExecutionProcessStatus::Completed => Self::Passed,
+            ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed => Self::Failed,
+        }
+    }
+
+    /// Skipped and neutral runs count as passed, like GitHub's checks do
+    pub fn of_workflow_run(run: &WorkflowRun) -> Self {
+        if run.status != WORKFLOW_RUN_COMPLETED {
+            return Self::Pending;
+        }
+        match run.conclusion.as_deref() {
+            Some("success" | "neutral" | "skipped") => Self::Passed,
+            _ => Self::Failed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct DiffStats {
+    pub files_changed: u32,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+impl DiffStats {
+    pub fn from_diffs(diffs: &[Diff]) -> Self {
+        diffs.iter().fold(Self::default(), |mut stats, diff| {
+            stats.files_changed += 1;
+            stats.additions += diff.additions.unwrap_or(0) as u32;
+            stats.deletions += diff.deletions.unwrap_or(0) as u32;
+            stats
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ComparedAttempt {
+    pub attempt: TaskAttempt,
+    /// The profile the attempt was started with
+    pub executor_profile_id: ExecutorProfileId,
+    /// Status of the latest coding agent run
+    pub agent_status: Option<ExecutionProcessStatus>,
+    /// Unset when the changes could not be read, e.g. after the worktree was discarded
+    pub diff: Option<DiffStats>,
+    /// Latest cleanup script run, which is where projects usually run their tests
+    pub cleanup_script: Option<CheckResult>,
+    /// Latest GitHub Actions run dispatched for the attempt's branch
+    pub ci: Option<CheckResult>,
+    /// From the first process starting to the last one finishing; unset while any still runs
+    pub duration_ms: Option<i64>,
+}
+
+impl ComparedAttempt {
+    /// `processes` and `workflow_runs` are the attempt's, in any order
+    pub fn new(
+        attempt: TaskAttempt,
+        executor_profile_id: ExecutorProfileId,
+        processes: &[ExecutionProcess],
+        diff: Option<&[Diff]>,
+        workflow_runs: &[WorkflowRun],
+    ) -> Self {
+        let latest = |run_reason: ExecutionProcessRunReason| {
+            processes
+                .iter()
+                .filter(|process| process.run_reason == run_reason)
+                .max_by_key(|process| process.started_at)
+        };
+        Self {
+            attempt,
+            executor_profile_id,
+            agent_status: latest(ExecutionProcessRunReason::CodingAgent)
+                .map(|process| process.status.clone()),
+            diff: diff.map(DiffStats::from_diffs),
+            cleanup_script: latest(ExecutionProcessRunReason::CleanupScript)
+                .map(|process| CheckResult::of_process(&process.status)),
+            ci: workflow_runs
+                .iter()
+                .max_by_key(|run| run.created_at)
+                .map(CheckResult::of_workflow_run),
+            duration_ms: duration_ms(
+                processes
+                    .iter()
+                    .filter(|process| process.run_reason != ExecutionProcessRunReason::DevServer)
+                    .map(|process| (process.started_at, process.completed_at)),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AttemptComparisonDetails {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub comparison: AttemptComparison,
+    /// In the order they were started
+    pub attempts: Vec<ComparedAttempt>,
+}
+
+/// Time from the earliest start to the latest completion of `spans`, or `None` if there are
+/// none or one has not completed
+fn duration_ms(
+    spans: impl IntoIterator<Item = (DateTime<Utc>, Option<DateTime<Utc>>)>,
+) -> Option<i64> {
+    let mut first: Option<DateTime<Utc>> = None;
+    let mut last: Option<DateTime<Utc>> = None;
+    for (started_at, completed_at) in spans {
+        let completed_at = completed_at?;
+        first = Some(first.map_or(started_at, |first| first.min(started_at)));
+        last = Some(last.map_or(completed_at, |last| last.max(completed_at)));
+    }
+    Some((last? - first?).num_milliseconds())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use executors::executors::BaseCodingAgent;
+    use utils::diff::DiffChangeKind;
+
+    use super::*;
+
+    fn diff(additions: usize, deletions: usize) -> Diff {
+        Diff {
+            change: DiffChangeKind::Modified,
+            old_path: Some("src/lib.rs".to_string()),
+            new_path: Some("src/lib.rs".to_string()),
+            old_content: None,
+            new_content: None,
+            content_omitted: true,
+            lfs: false,
+            additions: Some(additions),
+            deletions: Some(deletions),
+            hunks: None,
+        }
+    }
+
+    #[test]
+    fn sums_diff_stats_over_files() {
+        assert_eq!(
+            DiffStats::from_diffs(&[diff(10, 2), diff(0, 7)]),
+            DiffStats {
+                files_changed: 2,
+                additions: 10,
+                deletions: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn duration_spans_all_processes_once_they_finish() {
+        let at = |minute| Utc.with_ymd_and_hms(2025, 11, 29, 12, minute, 0).unwrap();
+        assert_eq!(
+            duration_ms(vec![(at(0), Some(at(2))), (at(3), Some(at(10)))]),
+            Some(600_000)
+        );
+        assert_eq!(duration_ms(vec![(at(0), Some(at(2))), (at(3), None)]), None);
+        assert_eq!(duration_ms(Vec::new()), None);
+    }
+
+    #[test]
+    fn limits_the_number_of_attempts() {
+        let profile = ExecutorProfileId::new(BaseCodingAgent::ClaudeCode);
+        assert!(validate_profiles(std::slice::from_ref(&profile)).is_err());
+        assert!(validate_profiles(&vec![profile.clone(); MIN_ATTEMPTS]).is_ok());
+        assert!(validate_profiles(&vec![profile; MAX_ATTEMPTS + 1]).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Correlates the agent configuration of a project's attempts (executor and variant) with
+//! how their work ended up: merge rate, follow-ups needed and pull request verdicts over time.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use db::models::{
+    merge::{MergeStatus, MergeType},
+    task_attempt::AttemptOutcome,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::services::project_locale;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ReportPeriod {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl ReportPeriod {
+    /// First day of the period containing `date`; weeks start on Monday
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            ReportPeriod::Day => date,
+            ReportPeriod::Week => date
+                .checked_sub_days(Days::new(date.weekday().num_days_from_monday().into()))
+                .unwrap_or(date),
+            ReportPeriod::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, TS)]
+pub struct OutcomeStats {
+    pub attempts: u32,
+    /// Merged directly or through a merged pull request
+    pub merged: u32,
+    /// Pull request still awaiting review
+    pub pr_open: u32,
+    /// Pull request closed without merging
+    pub pr_closed: u32,
+    /// Neither merged nor submitted as a pull request
+    pub unmerged: u32,
+    /// Share of attempts that were merged, from 0 to 1
+    pub merge_rate: f64,
+    pub avg_follow_ups: f64,
+    #[serde(skip)]
+    #[ts(skip)]
+    total_follow_ups: i64,
+}
+
+impl OutcomeStats {
+    fn record(&mut self, outcome: &AttemptOutcome) {
+        self.attempts += 1;
+        self.total_follow_ups += outcome.follow_ups;
+        match (&outcome.merge_type, &outcome.pr_status) {
+            (Some(MergeType::Direct), _) | (_, Some(MergeStatus::Merged)) => self.merged += 1,
+            (_, Some(MergeStatus::Closed)) => self.pr_closed += 1,
+            (Some(MergeType::Pr), _) => self.pr_open += 1,
+            (None, _) => self.unmerged += 1,
+        }
+        self.merge_rate = f64::from(self.merged) / f64::from(self.attempts);
+        self.avg_follow_ups = self.total_follow_ups as f64 / f64::from(self.attempts);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct PeriodStats {
+    /// First day of the period in the report's time zone
+    pub start: NaiveDate,
+    pub stats: OutcomeStats,
+}
+
+/// Outcomes of the attempts that used one executor and variant
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct AgentConfigurationReport {
+    pub executor: String,
+    pub variant: Option<String>,
+    pub totals: OutcomeStats,
+    /// Only periods with at least one attempt, oldest first
+    pub periods: Vec<PeriodStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct AttemptReport {
+    pub since: DateTime<Utc>,
+    pub period: ReportPeriod,
+    /// IANA time zone the periods follow; unset for the server's local time
+    pub timezone: Option<String>,
+    /// Most used configuration first
+    pub configurations: Vec<AgentConfigurationReport>,
+}
+
+/// Group `outcomes` by agent configuration and by period in `timezone`
+pub fn build_report(
+    outcomes: &[AttemptOutcome],
+    since: DateTime<Utc>,
+    period: ReportPeriod,
+    timezone: Option<&str>,
+) -> AttemptReport {
+    type Key = (String, Option<String>);
+    let mut grouped: BTreeMap<Key, (OutcomeStats, BTreeMap<NaiveDate, OutcomeStats>)> =
+        BTreeMap::new();
+
+    for outcome in outcomes {
+        let (totals, periods) = grouped
+            .entry((outcome.executor.clone(), outcome.variant.clone()))
+            .or_default();
+        totals.record(outcome);
+        periods
+            .entry(
+                period.start_of(project_locale::in_timezone(timezone, outcome.created_at).date()),
+            )
+            .or_default()
+            .record(outcome);
+    }
+
+    let mut configurations: Vec<_> = grouped
+        .into_iter()
+        .map(
+            |((executor, variant), (totals, periods))| AgentConfigurationReport {
+                executor,
+                variant,
+                totals,
+                periods: periods
+                    .into_iter()
+                    .map(|(start, stats)| PeriodStats { start, stats })
+                    .collect(),
+            },
+        )
+        .collect();
+    configurations.sort_by(|a, b| b.totals.attempts.cmp(&a.totals.attempts));
+
+    AttemptReport {
+        since,
+        period,
+        timezone: timezone.map(str::to_string),
+        configurations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn outcome(
+        variant: Option<&str>,
+        day: u32,
+        follow_ups: i64,
+        merge: Option<(MergeType, Option<MergeStatus>)>,
+    ) -> AttemptOutcome {
+        let (merge_type, pr_status) = merge.unzip();
+        AttemptOutcome {
+            task_attempt_id: Uuid::new_v4(),
+            executor: "CLAUDE_CODE".to_string(),
+            variant: variant.map(str::to_string),
+            follow_ups,
+            merge_type,
+            pr_status: pr_status.flatten(),
+            created_at: Utc.with_ymd_and_hms(2025, 11, day, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn groups_outcomes_by_configuration_and_week() {
+        let since = Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap();
+        let outcomes = vec![
+            // Monday 3rd and Sunday 9th share a week
+            outcome(None, 3, 0, Some((MergeType::Direct, None))),
+            outcome(None, 9, 3, Some((MergeType::Pr, Some(MergeStatus::Closed)))),
+            outcome(
+                None,
+                10,
+                1,
+                Some((MergeType::Pr, Some(MergeStatus::Merged))),
+            ),
+            outcome(
+                Some("PLAN"),
+                4,
+                2,
+                Some((MergeType::Pr, Some(MergeStatus::Open))),
+            ),
+            outcome(None, 11, 2, None),
+        ];
+
+        let report = build_report(&outcomes, since, ReportPeriod::Week, Some("UTC"));
+        assert_eq!(report.configurations.len(), 2);
+
+        let default = &report.configurations[0];
+        assert_eq!(default.variant, None);
+        assert_eq!(default.totals.attempts, 4);
+        assert_eq!(default.totals.merged, 2);
+        assert_eq!(default.totals.pr_closed, 1);
+        assert_eq!(default.totals.unmerged, 1);
+        assert_eq!(default.totals.merge_rate, 0.5);
+        assert_eq!(default.totals.avg_follow_ups, 1.5);
+        let starts: Vec<_> = default.periods.iter().map(|p| p.start.day()).collect();
+        assert_eq!(starts, vec![3, 10]);
+        assert_eq!(default.periods[0].stats.attempts, 2);
+
+        let plan = &report.configurations[1];
+        assert_eq!(plan.variant.as_deref(), Some("PLAN"));
+        assert_eq!(plan.totals.pr_open, 1);
+        assert_eq!(plan.totals.merge_rate, 0.0);
+    }
+
+    #[test]
+    fn periods_follow_the_time_zone() {
+        let since = Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap();
+        // Noon on Sunday the 9th in UTC is already Monday the 10th in Auckland
+        let outcomes = vec![outcome(None, 9, 0, None)];
+        let utc = build_report(&outcomes, since, ReportPeriod::Week, Some("UTC"));
+        assert_eq!(utc.configurations[0].periods[0].start.day(), 3);
+        let auckland = build_report(
+            &outcomes,
+            since,
+            ReportPeriod::Week,
+            Some("Pacific/Auckland"),
+        );
+        assert_eq!(auckland.configurations[0].periods[0].start.day(), 10);
+    }
+
+    #[test]
+    fn months_start_on_the_first() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 19).unwrap();
+        assert_eq!(ReportPeriod::Month.start_of(date).day(), 1);
+        assert_eq!(ReportPeriod::Week.start_of(date).day(), 17);
+        assert_eq!(ReportPeriod::Day.start_of(date), date);
+    }
+}
@@ -0,0 +1,56 @@
+//! Which user a request acts for, so tasks, attempts and executions can record who started
+//! them. The server sets the user for the duration of each request; work the server starts on
+//! its own, such as schedules and automation rules, runs without one.
+
+use std::future::Future;
+
+/// Request header naming the acting user, set by whatever authenticates users in front of a
+/// shared deployment
+pub const USER_HEADER: &str = "x-vibe-kanban-user";
+
+/// Longest user name kept; longer names are cut
+const MAX_USER_LEN: usize = 100;
+
+tokio::task_local! {
+    static CURRENT_USER: Option<String>;
+}
+
+/// Clean up a user name from a header or request body. Blank names mean no user.
+pub fn normalize_user(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.chars().take(MAX_USER_LEN).collect())
+}
+
+/// Run `future` on behalf of `user`
+pub async fn with_user<F: Future>(user: Option<String>, future: F) -> F::Output {
+    CURRENT_USER.scope(user, future).await
+}
+
+/// The user the current request acts for, if any. Not carried into spawned tasks.
+pub fn current_user() -> Option<String> {
+    CURRENT_USER.try_with(|user| user.clone()).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_names_are_trimmed_and_bounded() {
+        assert_eq!(normalize_user("  alice "), Some("alice".to_string()));
+        assert_eq!(normalize_user(" \t"), None);
+        let long = "x".repeat(MAX_USER_LEN + 10);
+        assert_eq!(normalize_user(&long).unwrap().len(), MAX_USER_LEN);
+    }
+
+    #[tokio::test]
+    async fn current_user_is_scoped_to_the_request() {
+        assert_eq!(current_user(), None);
+        let inside = with_user(Some("alice".to_string()), async { current_user() }).await;
+        assert_eq!(inside, Some("alice".to_string()));
+        assert_eq!(current_user(), None);
+    }
+}
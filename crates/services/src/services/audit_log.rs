@@ -0,0 +1,103 @@
+//! Recording operations that discard or publish work in the append-only audit log, so what
+//! happened to a worktree or branch can be traced back to who asked for it.
+
+use std::fmt::Display;
+
+use db::models::audit_log::{AuditAction, AuditLogEntry, CreateAuditLogEntry};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::attribution;
+
+/// An audit log entry being put together, acting for the current request's user
+#[derive(Debug, Clone)]
+pub struct AuditEvent(CreateAuditLogEntry);
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, target: impl Into<String>) -> Self {
+        Self(CreateAuditLogEntry {
+            action,
+            actor: attribution::current_user(),
+            project_id: None,
+            task_id: None,
+            task_attempt_id: None,
+            target: target.into(),
+            parameters: Value::Object(Default::default()),
+            error: None,
+        })
+    }
+
+    /// The attempt the operation acted on; its task and project are filled in when recorded
+    pub fn attempt(mut self, task_attempt_id: Uuid) -> Self {
+        self.0.task_attempt_id = Some(task_attempt_id);
+        self
+    }
+
+    pub fn task(mut self, project_id: Uuid, task_id: Uuid) -> Self {
+        self.0.project_id = Some(project_id);
+        self.0.task_id = Some(task_id);
+        self
+    }
+
+    pub fn project(mut self, project_id: Uuid) -> Self {
+        self.0.project_id = Some(project_id);
+        self
+    }
+
+    /// A JSON object of the options the operation ran with
+    pub fn parameters(mut self, parameters: Value) -> Self {
+        self.0.parameters = parameters;
+        self
+    }
+
+    /// Mark the entry as failed when the operation returned an error
+    pub fn outcome<T, E: Display>(mut self, result: &Result<T, E>) -> Self {
+        self.0.error = result.as_ref().err().map(|e| e.to_string());
+        self
+    }
+
+    /// Write the entry. The operation already happened, so failing to record it is logged
+    /// rather than returned.
+    pub async fn record(self, pool: &SqlitePool) {
+        if let Err(e) = AuditLogEntry::create(pool, &self.0).await {
+            tracing::error!(
+                "Failed to record {:?} of {} in the audit log: {}",
+                self.0.action,
+                self.0.target,
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn events_act_for_the_current_user() {
+        let event = attribution::with_user(Some("alice".to_string()), async {
+            AuditEvent::new(AuditAction::Push, "vk/1234-fix")
+        })
+        .await;
+        assert_eq!(event.0.actor.as_deref(), Some("alice"));
+        assert_eq!(event.0.parameters, json!({}));
+
+        let event = AuditEvent::new(AuditAction::WorktreeDelete, "/tmp/worktree");
+        assert_eq!(event.0.actor, None);
+    }
+
+    #[test]
+    fn failed_operations_keep_their_error() {
+        let ok: Result<(), String> = Ok(());
+        let event = AuditEvent::new(AuditAction::Merge, "main").outcome(&ok);
+        assert_eq!(event.0.error, None);
+
+        let failed: Result<(), String> = Err("merge conflicts".to_string());
+        let event = event.outcome(&failed);
+        assert_eq!(event.0.error.as_deref(), Some("merge conflicts"));
+    }
+}
@@ -0,0 +1,374 @@
+//! Runs a project's [`AutomationRule`]s when the events pipeline sees something happen on the
+//! board: an attempt merging, or a coding agent run completing or failing.
+//!
+//! A rule runs its actions when all of its conditions hold for the event's task. Each firing
+//! is logged as an [`AutomationRuleRun`], keyed by the event, so a rule fires at most once per
+//! event even though a row can be updated several times.
+
+use std::sync::{Arc, LazyLock};
+
+use db::{
+    DBService,
+    models::{
+        automation_rule::{
+            AutomationAction, AutomationCondition, AutomationRule, AutomationRuleRun,
+            AutomationRuleRunStatus, AutomationTrigger,
+        },
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+        merge::{Merge, MergeStatus},
+        project::Project,
+        tag::{CreateTag, Tag},
+        task::Task,
+        task_attempt::TaskAttempt,
+    },
+};
+use regex::Regex;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::services::{
+    config::Config,
+    git::GitService,
+    github_service::{GitHubRepoInfo, GitHubService},
+};
+
+/// Issue references in a task: `#123`, or an issue URL of any repository
+static ISSUE_REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[^\w&/])#(?P<number>\d+)\b|github\.com/(?P<owner>[\w.-]+)/(?P<repo>[\w.-]+)/issues/(?P<url_number>\d+)")
+        .unwrap()
+});
+
+#[derive(Debug, Error)]
+pub enum AutomationRuleError {
+    #[error("A rule needs at least one action")]
+    NoActions,
+    #[error("Tag names must not be empty or contain spaces")]
+    InvalidTagName,
+    #[error("The failure count must be at least 1")]
+    InvalidFailureCount,
+}
+
+/// Something that happened to an attempt which rules can fire on
+#[derive(Debug, Clone)]
+pub struct AutomationEvent {
+    pub trigger: AutomationTrigger,
+    /// Identifies the event, so it fires each rule only once
+    pub key: String,
+    pub task_attempt_id: Uuid,
+}
+
+impl AutomationEvent {
+    /// The event for a coding agent run that has just finished, if it has
+    pub fn from_execution_process(process: &ExecutionProcess) -> Option<Self> {
+        if process.run_reason != ExecutionProcessRunReason::CodingAgent {
+            return None;
+        }
+        let trigger = match process.status {
+            ExecutionProcessStatus::Completed => AutomationTrigger::CodingAgentCompleted,
+            ExecutionProcessStatus::Failed => AutomationTrigger::CodingAgentFailed,
+            _ => return None,
+        };
+        Some(Self {
+            trigger,
+            key: format!("execution_process:{}", process.id),
+            task_attempt_id: process.task_attempt_id,
+        })
+    }
+
+    /// The event for a direct merge, or a pull request that has been merged
+    pub fn from_merge(merge: &Merge) -> Option<Self> {
+        let (id, task_attempt_id) = match merge {
+            Merge::Direct(direct) => (direct.id, direct.task_attempt_id),
+            Merge::Pr(pr) if matches!(pr.pr_info.status, MergeStatus::Merged) => {
+                (pr.id, pr.task_attempt_id)
+            }
+            Merge::Pr(_) => return None,
+        };
+        Some(Self {
+            trigger: AutomationTrigger::AttemptMerged,
+            key: format!("merge:{id}"),
+            task_attempt_id,
+        })
+    }
+}
+
+/// Check that a rule's conditions and actions are usable
+pub fn validate(
+    conditions: &[AutomationCondition],
+    actions: &[AutomationAction],
+) -> Result<(), AutomationRuleError> {
+    if actions.is_empty() {
+        return Err(AutomationRuleError::NoActions);
+    }
+    let valid_tag_name = |name: &str| !name.is_empty() && !name.contains(char::is_whitespace);
+    for condition in conditions {
+        match condition {
+            AutomationCondition::HasTag { tag_name } if !valid_tag_name(tag_name) => {
+                return Err(AutomationRuleError::InvalidTagName);
+            }
+            AutomationCondition::FailedRunsAtLeast { count: 0 } => {
+                return Err(AutomationRuleError::InvalidFailureCount);
+            }
+            _ => {}
+        }
+    }
+    for action in actions {
+        if let AutomationAction::AddTag { tag_name } = action
+            && !valid_tag_name(tag_name)
+        {
+            return Err(AutomationRuleError::InvalidTagName);
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate the rules for `event` in the background
+pub fn spawn_evaluate(db: DBService, config: Arc<RwLock<Config>>, event: AutomationEvent) {
+    tokio::spawn(async move {
+        if let Err(e) = evaluate(&db, &config, &event).await {
+            tracing::error!(
+                "Failed to evaluate automation rules for {}: {}",
+                event.key,
+                e
+            );
+        }
+    });
+}
+
+async fn evaluate(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    event: &AutomationEvent,
+) -> Result<(), sqlx::Error> {
+    let pool = &db.pool;
+    let Some(task_attempt) = TaskAttempt::find_by_id(pool, event.task_attempt_id).await? else {
+        return Ok(());
+    };
+    let Some(task) = Task::find_by_id(pool, task_attempt.task_id).await? else {
+        return Ok(());
+    };
+    let rules =
+        AutomationRule::find_enabled_by_trigger(pool, task.project_id, event.trigger).await?;
+
+    for rule in rules {
+        if !conditions_hold(db, &rule.conditions, &task, &task_attempt).await? {
+            continue;
+        }
+        let Some(run) =
+            AutomationRuleRun::start(pool, rule.id, &event.key, task.id, Some(task_attempt.id))
+                .await?
+        else {
+            continue;
+        };
+        tracing::info!(
+            "Automation rule '{}' fired for task {} ({})",
+            rule.name,
+            task.id,
+            event.key
+        );
+
+        let mut done = Vec::new();
+        let mut failure = None;
+        for action in rule.actions.iter() {
+            match run_action(db, config, action, &task).await {
+                Ok(summary) => done.push(summary),
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+        let (status, message) = match failure {
+            None => (AutomationRuleRunStatus::Succeeded, done.join("; ")),
+            Some(error) => {
+                tracing::warn!("Automation rule '{}' failed: {}", rule.name, error);
+                done.push(error);
+                (AutomationRuleRunStatus::Failed, done.join("; "))
+            }
+        };
+        AutomationRuleRun::complete(pool, run.id, status, &message).await?;
+    }
+    Ok(())
+}
+
+async fn conditions_hold(
+    db: &DBService,
+    conditions: &[AutomationCondition],
+    task: &Task,
+    task_attempt: &TaskAttempt,
+) -> Result<bool, sqlx::Error> {
+    for condition in conditions {
+        let holds = match condition {
+            AutomationCondition::TaskStatus { status } => task.status == *status,
+            AutomationCondition::HasTag { tag_name } => Task::find_tags_for_task(&db.pool, task.id)
+                .await?
+                .iter()
+                .any(|tag| tag.tag_name == *tag_name),
+            AutomationCondition::FailedRunsAtLeast { count } => {
+                ExecutionProcess::count_failed_coding_agents(&db.pool, task_attempt.id).await?
+                    >= i64::from(*count)
+            }
+        };
+        if !holds {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Run one action, describing what it did or why it failed
+async fn run_action(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    action: &AutomationAction,
+    task: &Task,
+) -> Result<String, String> {
+    let pool = &db.pool;
+    match action {
+        AutomationAction::SetTaskStatus { status } => {
+            Task::update_status(pool, task.id, status.clone())
+                .await
+                .map_err(|e| format!("Failed to set task status: {e}"))?;
+            Ok(format!("Set task status to {status}"))
+        }
+        AutomationAction::AddTag { tag_name } => {
+            let tag = match Tag::find_by_name(pool, tag_name).await {
+                Ok(Some(tag)) => tag,
+                Ok(None) => Tag::create(
+                    pool,
+                    &CreateTag {
+                        tag_name: tag_name.clone(),
+                        content: tag_name.clone(),
+                        variables: Vec::new(),
+                    },
+                )
+                .await
+                .map_err(|e| format!("Failed to create tag {tag_name}: {e}"))?,
+                Err(e) => return Err(format!("Failed to look up tag {tag_name}: {e}")),
+            };
+            Task::add_tag(pool, task.id, tag.id)
+                .await
+                .map_err(|e| format!("Failed to add tag {tag_name}: {e}"))?;
+            Ok(format!("Added tag {tag_name}"))
+        }
+        AutomationAction::CloseGithubIssue => close_github_issues(db, config, task).await,
+    }
+}
+
+async fn close_github_issues(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    task: &Task,
+) -> Result<String, String> {
+    let project = Project::find_by_id(&db.pool, task.project_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Project not found")?;
+    let repo_info = GitService::new()
+        .get_github_repo_info(&project.git_repo_path, project.base_remote.as_deref())
+        .map_err(|e| format!("No GitHub repository to close issues in: {e}"))?;
+    let text = format!(
+        "{}\n{}",
+        task.title,
+        task.description.as_deref().unwrap_or_default()
+    );
+    let issues = referenced_issues(&text, &repo_info);
+    if issues.is_empty() {
+        return Ok("No linked GitHub issue to close".to_string());
+    }
+
+    let token = config
+        .read()
+        .await
+        .github
+        .token()
+        .ok_or("No GitHub token configured")?;
+    let github = GitHubService::new(&token).map_err(|e| e.to_string())?;
+    for issue in &issues {
+        github
+            .close_issue(&repo_info, *issue)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let closed = issues
+        .iter()
+        .map(|issue| format!("#{issue}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(format!("Closed GitHub issue {closed}"))
+}
+
+/// Numbers of the issues in `repo_info` that `text` refers to, in order of appearance
+fn referenced_issues(text: &str, repo_info: &GitHubRepoInfo) -> Vec<u64> {
+    let mut issues = Vec::new();
+    for caps in ISSUE_REFERENCE.captures_iter(text) {
+        let number = match (caps.name("number"), caps.name("url_number")) {
+            (Some(number), _) => number.as_str(),
+            (None, Some(number))
+                if caps["owner"].eq_ignore_ascii_case(&repo_info.owner)
+                    && caps["repo"].eq_ignore_ascii_case(&repo_info.repo_name) =>
+            {
+                number.as_str()
+            }
+            _ => continue,
+        };
+        if let Ok(number) = number.parse::<u64>()
+            && !issues.contains(&number)
+        {
+            issues.push(number);
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo() -> GitHubRepoInfo {
+        GitHubRepoInfo {
+            owner: "BloopAI".to_string(),
+            repo_name: "vibe-kanban".to_string(),
+        }
+    }
+
+    #[test]
+    fn finds_issue_references() {
+        let text = "Fix crash on startup (#123)\nSee https://github.com/bloopai/vibe-kanban/issues/45 and #123 again";
+        assert_eq!(referenced_issues(text, &repo()), vec![123, 45]);
+    }
+
+    #[test]
+    fn ignores_other_repositories_and_anchors() {
+        let text =
+            "Upstream: https://github.com/other/repo/issues/9, docs at page.html#12, color &#35;";
+        assert!(referenced_issues(text, &repo()).is_empty());
+    }
+
+    #[test]
+    fn validates_rules() {
+        let close = vec![AutomationAction::CloseGithubIssue];
+        assert!(validate(&[], &close).is_ok());
+        assert!(matches!(
+            validate(&[], &[]),
+            Err(AutomationRuleError::NoActions)
+        ));
+        assert!(matches!(
+            validate(
+                &[],
+                &[AutomationAction::AddTag {
+                    tag_name: "needs human".to_string()
+                }]
+            ),
+            Err(AutomationRuleError::InvalidTagName)
+        ));
+        assert!(matches!(
+            validate(
+                &[AutomationCondition::FailedRunsAtLeast { count: 0 }],
+                &close
+            ),
+            Err(AutomationRuleError::InvalidFailureCount)
+        ));
+    }
+}
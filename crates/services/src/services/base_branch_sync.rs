@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        base_branch_sync::BaseBranchSync, project::Project, task::Task, task_attempt::TaskAttempt,
+    },
+};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
+
+use crate::services::{
+    config::Config,
+    git::{GitService, GitServiceError},
+};
+
+#[derive(Debug, Error)]
+enum BaseBranchSyncError {
+    #[error(transparent)]
+    GitServiceError(#[from] GitServiceError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// Service that periodically fetches the base branches of open attempts in projects that
+/// opted in, records how far behind each attempt is and optionally rebases clean attempts
+pub struct BaseBranchSyncService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    git: GitService,
+    poll_interval: Duration,
+}
+
+impl BaseBranchSyncService {
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        git: GitService,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            git,
+            poll_interval: Duration::from_secs(300),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting base branch sync service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.sync_all_attempts().await {
+                error!("Error syncing base branches: {}", e);
+            }
+        }
+    }
+
+    async fn sync_all_attempts(&self) -> Result<(), BaseBranchSyncError> {
+        let attempts = TaskAttempt::find_for_base_branch_sync(&self.db.pool).await?;
+
+        if attempts.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Checking base branches of {} task attempts", attempts.len());
+
+        let github_token = self.config.read().await.github.token();
+        // Attempts often share a base branch, so fetch each one once per pass
+        let mut fetch_errors: HashMap<(PathBuf, String), Option<String>> = HashMap::new();
+
+        for attempt in attempts {
+            let Some(task) = Task::find_by_id(&self.db.pool, attempt.task_id).await? else {
+                continue;
+            };
+            let Some(project) = Project::find_by_id(&self.db.pool, task.project_id).await? else {
+                continue;
+            };
+
+            let fetch_error = fetch_errors
+                .entry((project.git_repo_path.clone(), attempt.target_branch.clone()))
+                .or_insert_with(|| {
+                    self.git
+                        .fetch_base_branch(
+                            &project.git_repo_path,
+                            &attempt.target_branch,
+                            github_token.clone(),
+                        )
+                        .err()
+                        .map(|e| e.to_string())
+                })
+                .clone();
+
+            if let Err(e) = self
+                .sync_attempt(&project, &attempt, fetch_error, github_token.clone())
+                .await
+            {
+                warn!(
+                    "Failed to sync base branch of task attempt {}: {}",
+                    attempt.id, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record how far `attempt` is behind its base branch, rebasing it first if the project
+    /// allows it and the worktree has no uncommitted changes
+    async fn sync_attempt(
+        &self,
+        project: &Project,
+        attempt: &TaskAttempt,
+        fetch_error: Option<String>,
+        github_token: Option<String>,
+    ) -> Result<(), BaseBranchSyncError> {
+        let repo_path = &project.git_repo_path;
+        let (_, mut commits_behind) =
+            self.git
+                .get_branch_status(repo_path, &attempt.branch, &attempt.target_branch)?;
+        let mut rebased = false;
+        let mut sync_error = fetch_error;
+
+        if commits_behind > 0
+            && project.auto_rebase_on_sync
+            && sync_error.is_none()
+            && let Some(worktree_path) = attempt.container_ref.as_deref().map(Path::new)
+            && worktree_path.exists()
+            && self.git.is_worktree_clean(worktree_path)?
+        {
+            match self.git.rebase_branch(
+                repo_path,
+                worktree_path,
+                &attempt.target_branch,
+                &attempt.target_branch,
+                &attempt.branch,
+                github_token,
+            ) {
+                Ok(_) => {
+                    info!(
+                        "Rebased task attempt {} onto {} ({} new commits)",
+                        attempt.id, attempt.target_branch, commits_behind
+                    );
+                    rebased = true;
+                    commits_behind = 0;
+                }
+                Err(e) => {
+                    // Nobody is around to resolve conflicts, so leave the worktree as it was
+                    if matches!(e, GitServiceError::MergeConflicts(_))
+                        && let Err(abort_err) = self.git.abort_rebase(worktree_path)
+                    {
+                        error!(
+                            "Failed to abort automatic rebase of task attempt {}: {}",
+                            attempt.id, abort_err
+                        );
+                    }
+                    sync_error = Some(e.to_string());
+                }
+            }
+        }
+
+        BaseBranchSync::record(
+            &self.db.pool,
+            attempt.id,
+            commits_behind as i64,
+            rebased,
+            sync_error.as_deref(),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+//! Posts to a project's Slack and Discord channels when a coding agent finishes, fails or asks
+//! for approval. Each channel is an incoming webhook; its URL is a credential, so it is stored
+//! encrypted like other secrets.
+//!
+//! Messages are rendered from the channel's templates, or the built-in ones, and link back to
+//! the attempt at the app's public URL. Posting is best effort: a channel that rejects a
+//! message is logged and not retried.
+
+use std::{sync::Arc, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        chat_channel::{
+            ChatChannel, ChatChannelKind, ChatNotificationEvent, CreateChatChannel,
+            UpdateChatChannel,
+        },
+        execution_process::{ExecutionContext, ExecutionProcess, ExecutionProcessStatus},
+        project::Project,
+        task::Task,
+        task_attempt::TaskAttempt,
+    },
+};
+use serde_json::json;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use utils::approvals::ApprovalRequest;
+use uuid::Uuid;
+
+use crate::services::{
+    config::Config,
+    project_env::{ProjectEnvError, SecretCipher},
+    public_url,
+};
+
+/// Discord refuses messages longer than this
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+#[derive(Debug, Error)]
+pub enum ChatNotifierError {
+    #[error("{0}")]
+    InvalidChannel(String),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Secret(#[from] ProjectEnvError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+fn url_context(channel_id: Uuid) -> String {
+    format!("{channel_id}/chat-channel")
+}
+
+/// Check that `url` is an incoming webhook of the kind of channel
+fn validate_webhook_url(kind: ChatChannelKind, url: &str) -> Result<String, ChatNotifierError> {
+    let url = url.trim();
+    let prefixes: &[&str] = match kind {
+        ChatChannelKind::Slack => &["https://hooks.slack.com/"],
+        ChatChannelKind::Discord => &[
+            "https://discord.com/api/webhooks/",
+            "https://discordapp.com/api/webhooks/",
+        ],
+    };
+    if prefixes
+        .iter()
+        .any(|prefix| url.len() > prefix.len() && url.starts_with(prefix))
+    {
+        Ok(url.to_string())
+    } else {
+        Err(ChatNotifierError::InvalidChannel(format!(
+            "The webhook URL must start with {}",
+            prefixes[0]
+        )))
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, ChatNotifierError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ChatNotifierError::InvalidChannel(
+            "Channel name is required".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Add a channel to the project, encrypting its webhook URL
+pub async fn create(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    data: &CreateChatChannel,
+) -> Result<ChatChannel, ChatNotifierError> {
+    let name = validate_name(&data.name)?;
+    let url = validate_webhook_url(data.kind, &data.webhook_url)?;
+    let id = Uuid::new_v4();
+    let webhook_url = SecretCipher::global()?.encrypt(&url, &url_context(id))?;
+    Ok(ChatChannel::create(
+        pool,
+        id,
+        project_id,
+        data.kind,
+        &name,
+        &webhook_url,
+        &data.events,
+        &data.templates,
+        data.enabled,
+    )
+    .await?)
+}
+
+/// Update a channel; settings left unset keep their current value
+pub async fn update(
+    pool: &SqlitePool,
+    channel: &ChatChannel,
+    data: &UpdateChatChannel,
+) -> Result<ChatChannel, ChatNotifierError> {
+    let name = match &data.name {
+        Some(name) => validate_name(name)?,
+        None => channel.name.clone(),
+    };
+    let webhook_url = match &data.webhook_url {
+        Some(url) => {
+            let url = validate_webhook_url(channel.kind, url)?;
+            SecretCipher::global()?.encrypt(&url, &url_context(channel.id))?
+        }
+        None => channel.webhook_url.clone(),
+    };
+    Ok(ChatChannel::update(
+        pool,
+        channel.id,
+        &name,
+        &webhook_url,
+        data.events.as_deref().unwrap_or(&channel.events.0),
+        data.templates.as_ref().unwrap_or(&channel.templates.0),
+        data.enabled.unwrap_or(channel.enabled),
+    )
+    .await?)
+}
+
+/// Values for a message's placeholders
+#[derive(Debug, Clone, Default)]
+struct MessageValues {
+    task: String,
+    project: String,
+    executor: String,
+    branch: String,
+    link: String,
+    tool: String,
+}
+
+fn default_template(event: ChatNotificationEvent) -> &'static str {
+    match event {
+        ChatNotificationEvent::AgentCompleted => {
+            "✅ {executor} finished \"{task}\" in {project} on {branch}\n{link}"
+        }
+        ChatNotificationEvent::AgentFailed => {
+            "❌ {executor} failed on \"{task}\" in {project} ({branch})\n{link}"
+        }
+        ChatNotificationEvent::ApprovalRequested => {
+            "✋ {executor} is waiting for approval to use {tool} on \"{task}\" in {project}\n{link}"
+        }
+    }
+}
+
+fn render(template: &str, values: &MessageValues) -> String {
+    [
+        ("{task}", &values.task),
+        ("{project}", &values.project),
+        ("{executor}", &values.executor),
+        ("{branch}", &values.branch),
+        ("{link}", &values.link),
+        ("{tool}", &values.tool),
+    ]
+    .iter()
+    .fold(template.to_string(), |message, (placeholder, value)| {
+        message.replace(placeholder, value)
+    })
+}
+
+/// Address of the attempt in the app
+pub(crate) async fn attempt_link(
+    config: &Arc<RwLock<Config>>,
+    task: &Task,
+    attempt_id: Uuid,
+) -> String {
+    let Some(base) = public_url::base_url(config, None).await else {
+        return String::new();
+    };
+    format!(
+        "{base}/projects/{}/tasks/{}/attempts/{attempt_id}",
+        task.project_id, task.id
+    )
+}
+
+/// Post to the project's channels about an execution that has finished, if it is one they are
+/// told about. Processes the user stopped are not announced.
+pub fn spawn_notify_execution_halted(
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    ctx: &ExecutionContext,
+) {
+    let event = match ctx.execution_process.status {
+        ExecutionProcessStatus::Completed => ChatNotificationEvent::AgentCompleted,
+        ExecutionProcessStatus::Failed => ChatNotificationEvent::AgentFailed,
+        _ => return,
+    };
+    let task = ctx.task.clone();
+    let attempt_id = ctx.task_attempt.id;
+    let values = MessageValues {
+        executor: ctx.task_attempt.executor.clone(),
+        branch: ctx.task_attempt.branch.clone(),
+        ..Default::default()
+    };
+    tokio::spawn(async move {
+        if let Err(e) = notify(&db, &config, event, &task, attempt_id, values).await {
+            tracing::warn!(
+                "Failed to notify chat channels about attempt {}: {}",
+                attempt_id,
+                e
+            );
+        }
+    });
+}
+
+/// Post to the project's channels that a coding agent is waiting on an approval
+pub fn spawn_notify_approval_requested(
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    request: ApprovalRequest,
+) {
+    tokio::spawn(async move {
+        let result = async {
+            let pool = &db.pool;
+            let Some(process) =
+                ExecutionProcess::find_by_id(pool, request.execution_process_id).await?
+            else {
+                return Ok(());
+            };
+            let Some(attempt) = TaskAttempt::find_by_id(pool, process.task_attempt_id).await?
+            else {
+                return Ok(());
+            };
+            let Some(task) = Task::find_by_id(pool, attempt.task_id).await? else {
+                return Ok(());
+            };
+            let values = MessageValues {
+                executor: attempt.executor.clone(),
+                branch: attempt.branch.clone(),
+                tool: request.tool_name.clone(),
+                ..Default::default()
+            };
+            notify(
+                &db,
+                &config,
+                ChatNotificationEvent::ApprovalRequested,
+                &task,
+                attempt.id,
+                values,
+            )
+            .await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to notify chat channels about approval {}: {}",
+                request.id,
+                e
+            );
+        }
+    });
+}
+
+async fn notify(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    event: ChatNotificationEvent,
+    task: &Task,
+    attempt_id: Uuid,
+    mut values: MessageValues,
+) -> Result<(), ChatNotifierError> {
+    let pool = &db.pool;
+    let channels: Vec<_> = ChatChannel::find_enabled_by_project_id(pool, task.project_id)
+        .await?
+        .into_iter()
+        .filter(|channel| channel.posts_on(event))
+        .collect();
+    if channels.is_empty() {
+        return Ok(());
+    }
+
+    values.task = task.title.clone();
+    values.project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .map(|project| project.name)
+        .unwrap_or_default();
+    values.link = attempt_link(config, task, attempt_id).await;
+
+    for channel in channels {
+        let template = channel
+            .templates
+            .for_event(event)
+            .unwrap_or(default_template(event));
+        if let Err(e) = post(&channel, &render(template, &values)).await {
+            tracing::warn!("Failed to post to chat channel '{}': {}", channel.name, e);
+        }
+    }
+    Ok(())
+}
+
+/// Post a message to the channel
+pub async fn post(channel: &ChatChannel, message: &str) -> Result<(), ChatNotifierError> {
+    let url = SecretCipher::global()?.decrypt(&channel.webhook_url, &url_context(channel.id))?;
+    let body = match channel.kind {
+        ChatChannelKind::Slack => json!({ "text": message }),
+        ChatChannelKind::Discord => {
+            let content: String = message.chars().take(DISCORD_MESSAGE_LIMIT).collect();
+            json!({ "content": content })
+        }
+    };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()?;
+    client
+        .post(&url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders() {
+        let values = MessageValues {
+            task: "Fix login".to_string(),
+            project: "app".to_string(),
+            executor: "CLAUDE_CODE".to_string(),
+            branch: "vk/fix-login".to_string(),
+            link: "http://127.0.0.1:3000/projects/p/tasks/t/attempts/a".to_string(),
+            tool: "Bash".to_string(),
+        };
+        assert_eq!(
+            render(
+                "{executor} asks to run {tool} for {task}: {link} {unknown}",
+                &values
+            ),
+            "CLAUDE_CODE asks to run Bash for Fix login: \
+             http://127.0.0.1:3000/projects/p/tasks/t/attempts/a {unknown}"
+        );
+    }
+
+    #[test]
+    fn accepts_only_incoming_webhooks_of_the_channel_kind() {
+        assert!(
+            validate_webhook_url(
+                ChatChannelKind::Slack,
+                " https://hooks.slack.com/services/T/B/x "
+            )
+            .is_ok()
+        );
+        assert!(
+            validate_webhook_url(
+                ChatChannelKind::Discord,
+                "https://discord.com/api/webhooks/1/x"
+            )
+            .is_ok()
+        );
+        assert!(
+            validate_webhook_url(
+                ChatChannelKind::Slack,
+                "https://discord.com/api/webhooks/1/x"
+            )
+            .is_err()
+        );
+        assert!(validate_webhook_url(ChatChannelKind::Slack, "https://hooks.slack.com/").is_err());
+        assert!(
+            validate_webhook_url(
+                ChatChannelKind::Discord,
+                "http://discord.com/api/webhooks/1/x"
+            )
+            .is_err()
+        );
+    }
+}
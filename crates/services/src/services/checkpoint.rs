@@ -0,0 +1,216 @@
+//! Checkpoints of a task attempt: the worktree's HEAD before each of its execution processes
+//! ran. Rolling back to one resets the worktree there and drops that process and all later
+//! ones, so follow-ups continue the agent session as it was at that point.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    executor_session::ExecutorSession,
+};
+use executors::actions::ExecutorActionType;
+use serde::Serialize;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Characters of a prompt's first line used as a checkpoint name
+const NAME_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct Checkpoint {
+    pub execution_process_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    /// First line of the coding agent's prompt, or the kind of script
+    pub name: String,
+    /// HEAD before the process ran; unset if it was never recorded
+    pub commit: Option<String>,
+    /// Agent session that follow-ups continue after rolling back here; unset starts a new one
+    pub session_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+}
+
+/// One checkpoint per process, oldest first. `processes` are the attempt's processes that
+/// haven't been dropped, oldest first. Dev servers don't change the worktree and are skipped.
+pub fn checkpoints(
+    processes: &[ExecutionProcess],
+    sessions: &[ExecutorSession],
+) -> Vec<Checkpoint> {
+    let session_ids: HashMap<Uuid, &str> = sessions
+        .iter()
+        .filter_map(|session| Some((session.execution_process_id, session.session_id.as_deref()?)))
+        .collect();
+
+    let mut checkpoints = Vec::new();
+    let mut previous_commit: Option<&str> = None;
+    let mut session_id: Option<&str> = None;
+    for process in processes {
+        if process.run_reason != ExecutionProcessRunReason::DevServer {
+            checkpoints.push(Checkpoint {
+                execution_process_id: process.id,
+                run_reason: process.run_reason.clone(),
+                name: name(process),
+                commit: process
+                    .before_head_commit
+                    .as_deref()
+                    .or(previous_commit)
+                    .map(str::to_string),
+                session_id: session_id.map(str::to_string),
+                started_at: process.started_at,
+            });
+        }
+        if let Some(after) = process.after_head_commit.as_deref() {
+            previous_commit = Some(after);
+        }
+        if process.run_reason == ExecutionProcessRunReason::CodingAgent
+            && let Some(id) = session_ids.get(&process.id)
+        {
+            session_id = Some(id);
+        }
+    }
+    checkpoints
+}
+
+fn name(process: &ExecutionProcess) -> String {
+    let prompt = match process.executor_action().map(|action| action.typ()) {
+        Ok(ExecutorActionType::CodingAgentInitialRequest(request)) => Some(&request.prompt),
+        Ok(ExecutorActionType::CodingAgentFollowUpRequest(request)) => Some(&request.prompt),
+        _ => None,
+    };
+    if let Some(line) =
+        prompt.and_then(|prompt| prompt.lines().map(str::trim).find(|line| !line.is_empty()))
+    {
+        return match line.char_indices().nth(NAME_LENGTH) {
+            Some((end, _)) => format!("{}…", &line[..end]),
+            None => line.to_string(),
+        };
+    }
+    match process.run_reason {
+        ExecutionProcessRunReason::SetupScript => "Setup script",
+        ExecutionProcessRunReason::CleanupScript => "Cleanup script",
+        ExecutionProcessRunReason::CodingAgent => "Coding agent",
+        ExecutionProcessRunReason::DevServer => "Dev server",
+        ExecutionProcessRunReason::PostMergeScript => "Post-merge script",
+        ExecutionProcessRunReason::TestScript => "Test run",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::execution_process::{ExecutionProcessStatus, ExecutorActionField};
+    use executors::{
+        actions::{
+            ExecutorAction,
+            coding_agent_follow_up::CodingAgentFollowUpRequest,
+            script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        },
+        executors::BaseCodingAgent,
+        profile::ExecutorProfileId,
+    };
+
+    use super::*;
+
+    fn process(
+        run_reason: ExecutionProcessRunReason,
+        typ: ExecutorActionType,
+        before: Option<&str>,
+        after: Option<&str>,
+    ) -> ExecutionProcess {
+        let now = Utc::now();
+        ExecutionProcess {
+            id: Uuid::new_v4(),
+            task_attempt_id: Uuid::nil(),
+            run_reason,
+            executor_action: sqlx::types::Json(ExecutorActionField::ExecutorAction(
+                ExecutorAction::new(typ, None),
+            )),
+            before_head_commit: before.map(str::to_string),
+            after_head_commit: after.map(str::to_string),
+            status: ExecutionProcessStatus::Completed,
+            exit_code: Some(0),
+            dropped: false,
+            started_at: now,
+            completed_at: Some(now),
+            stalled_at: None,
+            kill_reason: None,
+            queue_position: None,
+            retry_of: None,
+            started_by: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn agent(prompt: &str, before: Option<&str>, after: Option<&str>) -> ExecutionProcess {
+        process(
+            ExecutionProcessRunReason::CodingAgent,
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt: prompt.to_string(),
+                session_id: "earlier".to_string(),
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            }),
+            before,
+            after,
+        )
+    }
+
+    fn session(process: &ExecutionProcess, session_id: &str) -> ExecutorSession {
+        ExecutorSession {
+            id: Uuid::new_v4(),
+            task_attempt_id: Uuid::nil(),
+            execution_process_id: process.id,
+            session_id: Some(session_id.to_string()),
+            prompt: None,
+            summary: None,
+            created_at: process.created_at,
+            updated_at: process.updated_at,
+        }
+    }
+
+    #[test]
+    fn rolling_back_continues_the_session_of_earlier_agents() {
+        let first = agent("Add a parser\n\nwith tests", Some("a"), Some("b"));
+        let cleanup = process(
+            ExecutionProcessRunReason::CleanupScript,
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "cargo fmt".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::CleanupScript,
+                env: Default::default(),
+            }),
+            None,
+            Some("c"),
+        );
+        let second = agent("Fix the failing test", Some("c"), Some("d"));
+        let sessions = [session(&first, "s1"), session(&second, "s2")];
+
+        let checkpoints = checkpoints(&[first, cleanup, second], &sessions);
+        let summary: Vec<_> = checkpoints
+            .iter()
+            .map(|c| {
+                (
+                    c.name.as_str(),
+                    c.commit.as_deref(),
+                    c.session_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Add a parser", Some("a"), None),
+                // Missing HEADs fall back to the previous process's
+                ("Cleanup script", Some("b"), Some("s1")),
+                ("Fix the failing test", Some("c"), Some("s1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_prompts_are_shortened() {
+        let prompt = "x".repeat(NAME_LENGTH + 20);
+        let process = agent(&prompt, None, None);
+        assert_eq!(name(&process), format!("{}…", "x".repeat(NAME_LENGTH)));
+    }
+}
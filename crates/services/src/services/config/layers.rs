@@ -0,0 +1,315 @@
+//! Layered configuration. The effective config starts from the defaults, overlaid in turn by the
+//! config file, `VK_CONFIG__*` environment variables and overrides set at runtime and kept in the
+//! database, later layers winning. Settings are addressed by dotted paths into the config's JSON,
+//! e.g. `worktree_gc.retention_days`, and the merged result must still be a valid [`Config`].
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+use ts_rs::TS;
+
+use super::{Config, ConfigError, validate_config};
+
+/// Prefix of environment variables overriding settings. The rest of the name is the setting's
+/// path in upper case with `__` between segments, e.g. `VK_CONFIG__WORKTREE_GC__RETENTION_DAYS`.
+pub const ENV_PREFIX: &str = "VK_CONFIG__";
+
+/// Settings only changed through their own endpoints
+const FIXED_PATHS: [&str; 2] = ["config_version", "storage"];
+
+/// Layer a setting's effective value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    Override,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct EffectiveConfig {
+    pub config: Config,
+    /// Layer of each setting that isn't at its default, by dotted path
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+/// The layers behind the config in use. The deployment keeps them next to the config, which is
+/// replaced with a new merge whenever they change.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    /// The config as stored in the file
+    file: Value,
+    env: BTreeMap<String, Value>,
+    overrides: BTreeMap<String, Value>,
+}
+
+impl ConfigLayers {
+    pub fn new(file: &Config) -> Result<Self, ConfigError> {
+        Ok(Self {
+            file: serde_json::to_value(file)?,
+            ..Default::default()
+        })
+    }
+
+    /// Set up the layers from the file and the environment, then apply the overrides kept in the
+    /// database
+    pub fn load(
+        file: &Config,
+        overrides: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<Self, ConfigError> {
+        let mut layers = Self::new(file)?.with_env(std::env::vars())?;
+        for (path, value) in overrides {
+            // The setting may have been removed or changed type since it was overridden
+            if let Err(e) = layers.set_override(&path, value) {
+                tracing::warn!("Ignoring config override: {}", e);
+            }
+        }
+        Ok(layers)
+    }
+
+    /// Apply the settings named by `vars` with [`ENV_PREFIX`], ignoring other variables
+    pub fn with_env(
+        mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        for (name, raw) in vars {
+            let Some(path) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let path = path
+                .split("__")
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join(".");
+            // Strings are taken as they are; everything else is parsed as JSON
+            let value = match get_path(&self.file, &path) {
+                Some(Value::String(_)) => Value::String(raw),
+                _ => serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+            };
+            self.env.insert(path, value);
+            self.merge().map_err(|e| at_path(&name, e))?;
+        }
+        Ok(self)
+    }
+
+    /// The defaults overlaid with every layer
+    pub fn merge(&self) -> Result<Config, ConfigError> {
+        let mut value = self.file.clone();
+        for (path, layer_value) in self.env.iter().chain(&self.overrides) {
+            set_path(&mut value, path, layer_value.clone())?;
+        }
+        let config: Config = serde_json::from_value(value)
+            .map_err(|e| ConfigError::ValidationError(e.to_string()))?;
+        validate_config(&config)?;
+        Ok(config)
+    }
+
+    /// Override a setting, returning the resulting config. Nothing changes if it is invalid.
+    pub fn set_override(&mut self, path: &str, value: Value) -> Result<Config, ConfigError> {
+        let previous = self.overrides.insert(path.to_string(), value);
+        match self.merge() {
+            Ok(config) => Ok(config),
+            Err(e) => {
+                match previous {
+                    Some(previous) => self.overrides.insert(path.to_string(), previous),
+                    None => self.overrides.remove(path),
+                };
+                Err(at_path(path, e))
+            }
+        }
+    }
+
+    /// Check that a setting can be overridden with `value`, without changing anything
+    pub fn check_override(&self, path: &str, value: &Value) -> Result<(), ConfigError> {
+        self.clone().set_override(path, value.clone()).map(|_| ())
+    }
+
+    pub fn remove_override(&mut self, path: &str) -> Result<Config, ConfigError> {
+        self.overrides.remove(path);
+        self.merge()
+    }
+
+    /// `config` with the settings coming from the environment or overrides put back to their
+    /// values in the file, which becomes the new file layer
+    pub fn to_file(&mut self, config: &Config) -> Result<Value, ConfigError> {
+        let mut value = serde_json::to_value(config)?;
+        for path in self.env.keys().chain(self.overrides.keys()) {
+            if let Some(file_value) = get_path(&self.file, path) {
+                set_path(&mut value, path, file_value.clone())?;
+            }
+        }
+        self.file = value.clone();
+        Ok(value)
+    }
+
+    /// Where the settings of `config` that aren't at their default come from
+    pub fn sources(&self, config: &Config) -> Result<BTreeMap<String, ConfigSource>, ConfigError> {
+        let defaults = serde_json::to_value(Config::default())?;
+        let mut leaves = Vec::new();
+        collect_leaves(&serde_json::to_value(config)?, String::new(), &mut leaves);
+
+        let covers = |layer: &BTreeMap<String, Value>, path: &str| {
+            layer.keys().any(|prefix| {
+                path == prefix
+                    || path
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        };
+        let mut sources = BTreeMap::new();
+        for (path, value) in leaves {
+            let source = if covers(&self.overrides, &path) {
+                ConfigSource::Override
+            } else if covers(&self.env, &path) {
+                ConfigSource::Env
+            } else if get_path(&defaults, &path) != Some(&value) {
+                ConfigSource::File
+            } else {
+                continue;
+            };
+            sources.insert(path, source);
+        }
+        Ok(sources)
+    }
+
+    pub fn describe(&self, config: &Config) -> Result<EffectiveConfig, ConfigError> {
+        Ok(EffectiveConfig {
+            config: config.clone(),
+            sources: self.sources(config)?,
+        })
+    }
+}
+
+fn check_path(path: &str) -> Result<(), ConfigError> {
+    let first = path.split('.').next().unwrap_or_default();
+    if FIXED_PATHS.contains(&first) {
+        return Err(ConfigError::ValidationError(
+            "cannot be overridden".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn at_path(path: &str, e: ConfigError) -> ConfigError {
+    match e {
+        ConfigError::ValidationError(message) => {
+            ConfigError::ValidationError(format!("{path}: {message}"))
+        }
+        e => e,
+    }
+}
+
+fn get_path<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(root, |value, segment| value.as_object()?.get(segment))
+}
+
+/// Replace the existing setting at `path`; unknown settings are rejected
+fn set_path(root: &mut Value, path: &str, value: Value) -> Result<(), ConfigError> {
+    check_path(path)?;
+    let target = path
+        .split('.')
+        .try_fold(root, |value, segment| {
+            value.as_object_mut()?.get_mut(segment)
+        })
+        .ok_or_else(|| ConfigError::ValidationError("unknown setting".to_string()))?;
+    *target = value;
+    Ok(())
+}
+
+fn collect_leaves(value: &Value, path: String, leaves: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, field) in fields {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_leaves(field, path, leaves);
+            }
+        }
+        _ => leaves.push((path, value.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let mut file = Config::default();
+        file.git_branch_prefix = "team".to_string();
+        let mut layers = ConfigLayers::new(&file)
+            .unwrap()
+            .with_env(env(&[
+                ("VK_CONFIG__WORKTREE_GC__RETENTION_DAYS", "14"),
+                ("VK_CONFIG__GIT_BRANCH_PREFIX", "123"),
+                ("UNRELATED", "1"),
+            ]))
+            .unwrap();
+        let config = layers
+            .set_override("worktree_gc.retention_days", json!(30))
+            .unwrap();
+        assert_eq!(config.worktree_gc.retention_days, 30);
+        // Strings stay strings even if they look like JSON
+        assert_eq!(config.git_branch_prefix, "123");
+
+        let sources = layers.sources(&config).unwrap();
+        assert_eq!(
+            sources.get("worktree_gc.retention_days"),
+            Some(&ConfigSource::Override)
+        );
+        assert_eq!(sources.get("git_branch_prefix"), Some(&ConfigSource::Env));
+        assert_eq!(sources.get("theme"), None);
+
+        let config = layers
+            .remove_override("worktree_gc.retention_days")
+            .unwrap();
+        assert_eq!(config.worktree_gc.retention_days, 14);
+
+        // Saving keeps the environment's values out of the file
+        let saved = layers.to_file(&config).unwrap();
+        assert_eq!(saved["git_branch_prefix"], json!("team"));
+        assert_eq!(saved["worktree_gc"]["retention_days"], json!(7));
+    }
+
+    #[test]
+    fn invalid_overrides_are_rejected() {
+        let mut layers = ConfigLayers::new(&Config::default()).unwrap();
+        let cases = [
+            ("worktree_gc.retention_days", json!("soon"), "invalid type"),
+            ("worktree_gc.no_such_setting", json!(1), "unknown setting"),
+            ("git_branch_prefix", json!("a/b"), "branch prefix"),
+            (
+                "storage.worktree_dir",
+                json!("/tmp"),
+                "cannot be overridden",
+            ),
+        ];
+        for (path, value, expected) in cases {
+            let error = layers.set_override(path, value).unwrap_err().to_string();
+            assert!(error.contains(path), "{error}");
+            assert!(error.contains(expected), "{error}");
+        }
+        assert!(layers.overrides.is_empty());
+        assert!(
+            ConfigLayers::new(&Config::default())
+                .unwrap()
+                .with_env(env(&[("VK_CONFIG__STALL_DETECTION__ENABLED", "maybe")]))
+                .is_err()
+        );
+    }
+}
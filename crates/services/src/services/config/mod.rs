@@ -1,7 +1,11 @@
 use std::path::PathBuf;
 
 use thiserror::Error;
+use tokio::sync::RwLock;
 
+use crate::services::config::layers::ConfigLayers;
+
+pub mod layers;
 mod versions;
 
 #[derive(Debug, Error)]
@@ -23,6 +27,28 @@ pub type EditorType = versions::v7::EditorType;
 pub type GitHubConfig = versions::v7::GitHubConfig;
 pub type UiLanguage = versions::v7::UiLanguage;
 pub type ShowcaseState = versions::v7::ShowcaseState;
+pub type TelemetryConfig = versions::v7::TelemetryConfig;
+pub type CommitSigningConfig = versions::v7::CommitSigningConfig;
+pub type CommitSigningFormat = versions::v7::CommitSigningFormat;
+pub type VulnerabilityScanConfig = versions::v7::VulnerabilityScanConfig;
+pub type VulnerabilityScanner = versions::v7::VulnerabilityScanner;
+pub type StallDetectionConfig = versions::v7::StallDetectionConfig;
+pub type StallAction = versions::v7::StallAction;
+pub type WorktreeGcConfig = versions::v7::WorktreeGcConfig;
+pub type GitCloneConfig = versions::v7::GitCloneConfig;
+pub type CloneFilter = versions::v7::CloneFilter;
+pub type StorageConfig = versions::v7::StorageConfig;
+pub type StorageRelocation = versions::v7::StorageRelocation;
+pub type ResourceLimits = versions::v7::ResourceLimits;
+pub type ResourceLimitsConfig = versions::v7::ResourceLimitsConfig;
+pub type DevServerConfig = versions::v7::DevServerConfig;
+pub type LogRetentionConfig = versions::v7::LogRetentionConfig;
+pub type EventJournalConfig = versions::v7::EventJournalConfig;
+pub type RateLimit = versions::v7::RateLimit;
+pub type RateLimitConfig = versions::v7::RateLimitConfig;
+pub type ChatNotificationConfig = versions::v7::ChatNotificationConfig;
+pub type EmailNotificationConfig = versions::v7::EmailNotificationConfig;
+pub type SmtpSecurity = versions::v7::SmtpSecurity;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
@@ -35,12 +61,25 @@ pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
     }
 }
 
-/// Saves the config to the given path
+/// Checks what the schema alone doesn't enforce
+pub fn validate_config(config: &Config) -> Result<(), ConfigError> {
+    if !utils::git::is_valid_branch_prefix(&config.git_branch_prefix) {
+        return Err(ConfigError::ValidationError(
+            "Invalid git branch prefix. Must be a valid git branch name component without slashes."
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Saves the config to the given path. Settings coming from environment variables or runtime
+/// overrides keep their value from the file.
 pub async fn save_config_to_file(
     config: &Config,
+    layers: &RwLock<ConfigLayers>,
     config_path: &PathBuf,
 ) -> Result<(), ConfigError> {
-    let raw_config = serde_json::to_string_pretty(config)?;
+    let raw_config = serde_json::to_string_pretty(&layers.write().await.to_file(config)?)?;
     std::fs::write(config_path, raw_config)?;
     Ok(())
 }
@@ -1,4 +1,5 @@
 use anyhow::Error;
+use db::models::task::StalenessThresholds;
 use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
@@ -11,12 +12,444 @@ fn default_git_branch_prefix() -> String {
     "vk".to_string()
 }
 
+fn default_stall_detection_enabled() -> bool {
+    true
+}
+
+fn default_stall_timeout_secs() -> u32 {
+    600
+}
+
+fn default_worktree_gc_enabled() -> bool {
+    true
+}
+
+fn default_worktree_retention_days() -> u32 {
+    7
+}
+
+fn default_dev_server_max_restarts() -> u32 {
+    3
+}
+
+fn default_log_retention_enabled() -> bool {
+    true
+}
+
+fn default_log_compress_after_days() -> Option<u32> {
+    Some(7)
+}
+
+fn default_email_min_duration_mins() -> u32 {
+    10
+}
+
+fn default_rate_limits_enabled() -> bool {
+    true
+}
+
+fn default_attempt_start_limit() -> Option<RateLimit> {
+    Some(RateLimit {
+        per_minute: 20,
+        burst: 10,
+    })
+}
+
+fn default_diff_limit() -> Option<RateLimit> {
+    Some(RateLimit {
+        per_minute: 120,
+        burst: 40,
+    })
+}
+
+fn default_log_stream_limit() -> Option<RateLimit> {
+    Some(RateLimit {
+        per_minute: 120,
+        burst: 60,
+    })
+}
+
+fn default_event_journal_enabled() -> bool {
+    true
+}
+
+fn default_event_journal_retention_hours() -> Option<u32> {
+    Some(24)
+}
+
+fn default_event_journal_max_entries() -> Option<u32> {
+    Some(50_000)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
 pub struct ShowcaseState {
     #[serde(default)]
     pub seen_features: Vec<String>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct TelemetryConfig {
+    /// Opt in to aggregating anonymous usage statistics locally
+    #[serde(default)]
+    pub enabled: bool,
+    /// Optional endpoint the aggregated statistics are periodically sent to
+    #[serde(default)]
+    pub collector_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CommitSigningFormat {
+    #[default]
+    Gpg,
+    Ssh,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct CommitSigningConfig {
+    /// Sign commits created by Vibe Kanban (agent commits, squash merges, initial commits)
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub format: CommitSigningFormat,
+    /// GPG key id, or path to the SSH key used for signing
+    #[serde(default)]
+    pub signing_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VulnerabilityScanner {
+    #[default]
+    Osv,
+    Command,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct VulnerabilityScanConfig {
+    /// Scan dependencies added by an attempt once its coding agent finishes
+    #[serde(default)]
+    pub enabled: bool,
+    /// Query the public OSV database (https://osv.dev) or run a local command
+    #[serde(default)]
+    pub scanner: VulnerabilityScanner,
+    /// Command that reads `{"packages": [...]}` on stdin and prints `{"findings": [...]}`
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StallAction {
+    /// Only flag the process as stalled and log a warning
+    #[default]
+    Warn,
+    /// Flag the process and stop it
+    Cancel,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct StallDetectionConfig {
+    /// Watch running coding agents for periods without any output
+    #[serde(default = "default_stall_detection_enabled")]
+    pub enabled: bool,
+    /// Seconds without output before a coding agent is considered stalled
+    #[serde(default = "default_stall_timeout_secs")]
+    pub timeout_secs: u32,
+    #[serde(default)]
+    pub action: StallAction,
+}
+
+impl Default for StallDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_stall_detection_enabled(),
+            timeout_secs: default_stall_timeout_secs(),
+            action: StallAction::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct WorktreeGcConfig {
+    /// Periodically remove worktrees of attempts whose tasks are done or cancelled
+    #[serde(default = "default_worktree_gc_enabled")]
+    pub enabled: bool,
+    /// Days a task must have been done or cancelled before its worktrees are removed
+    #[serde(default = "default_worktree_retention_days")]
+    pub retention_days: u32,
+}
+
+impl Default for WorktreeGcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_worktree_gc_enabled(),
+            retention_days: default_worktree_retention_days(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CloneFilter {
+    /// Download every object up front
+    #[default]
+    None,
+    /// Download file contents when they are first needed (`--filter=blob:none`)
+    Blobless,
+    /// Download directories and file contents when they are first needed (`--filter=tree:0`)
+    Treeless,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct GitCloneConfig {
+    /// Partial clone filter used when cloning a project from a URL
+    #[serde(default)]
+    pub filter: CloneFilter,
+    /// Only fetch this many commits of history; older history is fetched when a merge or
+    /// rebase needs it
+    #[serde(default)]
+    pub depth: Option<u32>,
+}
+
+/// Directories holding vibe-kanban's data. Unset directories use the platform default. Changed
+/// only through the storage relocation endpoint, which moves the existing data along.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct StorageConfig {
+    /// Parent directory of task attempt worktrees
+    #[serde(default)]
+    pub worktree_dir: Option<String>,
+    /// Directory of images attached to tasks
+    #[serde(default)]
+    pub image_dir: Option<String>,
+    /// Directory of the SQLite database, which also holds execution logs
+    #[serde(default)]
+    pub database_dir: Option<String>,
+    /// Move requested through the relocation endpoint, carried out on the next start
+    #[serde(default)]
+    pub relocation: Option<StorageRelocation>,
+}
+
+/// New locations for storage directories; unset directories stay where they are
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+pub struct StorageRelocation {
+    #[serde(default)]
+    pub worktree_dir: Option<String>,
+    #[serde(default)]
+    pub image_dir: Option<String>,
+    #[serde(default)]
+    pub database_dir: Option<String>,
+}
+
+/// Limits for one kind of execution process. Unset limits are not enforced.
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Stop the process once it has run this many seconds
+    #[serde(default)]
+    pub timeout_secs: Option<u32>,
+    /// Stop the process once it and its children use more memory than this, in megabytes
+    #[serde(default)]
+    pub memory_max_mb: Option<u32>,
+    /// CPU niceness from -20 to 19; higher values leave more CPU time to other programs
+    #[serde(default)]
+    pub nice: Option<i8>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct ResourceLimitsConfig {
+    #[serde(default)]
+    pub coding_agent: ResourceLimits,
+    /// Setup and cleanup scripts
+    #[serde(default)]
+    pub scripts: ResourceLimits,
+    #[serde(default)]
+    pub dev_server: ResourceLimits,
+    /// Coding agents allowed to run at once; further ones wait in a queue
+    #[serde(default)]
+    pub max_concurrent_coding_agents: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct DevServerConfig {
+    /// Start the dev server again when it exits with an error
+    #[serde(default)]
+    pub restart_on_crash: bool,
+    /// Restarts allowed before giving up, counted from the last manual start
+    #[serde(default = "default_dev_server_max_restarts")]
+    pub max_restarts: u32,
+    /// When the server starts, start the dev servers that were running when it last stopped
+    #[serde(default)]
+    pub resume_after_restart: bool,
+}
+
+impl Default for DevServerConfig {
+    fn default() -> Self {
+        Self {
+            restart_on_crash: false,
+            max_restarts: default_dev_server_max_restarts(),
+            resume_after_restart: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct LogRetentionConfig {
+    /// Periodically apply the policy below to the logs of finished execution processes
+    #[serde(default = "default_log_retention_enabled")]
+    pub enabled: bool,
+    /// Compress logs that have not been written to for this many days
+    #[serde(default = "default_log_compress_after_days")]
+    pub compress_after_days: Option<u32>,
+    /// Delete logs that have not been written to for this many days
+    #[serde(default)]
+    pub delete_after_days: Option<u32>,
+    /// Delete the oldest logs once all logs together take up more than this, in megabytes
+    #[serde(default)]
+    pub max_total_mb: Option<u32>,
+    /// Save logs to this directory as compressed JSONL before deleting them
+    #[serde(default)]
+    pub archive_dir: Option<String>,
+}
+
+impl Default for LogRetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_log_retention_enabled(),
+            compress_after_days: default_log_compress_after_days(),
+            delete_after_days: None,
+            max_total_mb: None,
+            archive_dir: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct EventJournalConfig {
+    /// Keep the patches sent on the events stream, so recent board changes can be replayed
+    #[serde(default = "default_event_journal_enabled")]
+    pub enabled: bool,
+    /// Delete patches older than this many hours
+    #[serde(default = "default_event_journal_retention_hours")]
+    pub retention_hours: Option<u32>,
+    /// Delete the oldest patches once there are more than this many
+    #[serde(default = "default_event_journal_max_entries")]
+    pub max_entries: Option<u32>,
+}
+
+impl Default for EventJournalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_event_journal_enabled(),
+            retention_hours: default_event_journal_retention_hours(),
+            max_entries: default_event_journal_max_entries(),
+        }
+    }
+}
+
+/// A token bucket: `burst` requests at once, refilled at `per_minute`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct RateLimit {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct RateLimitConfig {
+    /// Limit how often each client can call the routes below
+    #[serde(default = "default_rate_limits_enabled")]
+    pub enabled: bool,
+    /// Starting attempts, follow-ups and reruns; unlimited when unset
+    #[serde(default = "default_attempt_start_limit")]
+    pub attempt_starts: Option<RateLimit>,
+    /// Computing an attempt's diff; unlimited when unset
+    #[serde(default = "default_diff_limit")]
+    pub diffs: Option<RateLimit>,
+    /// Opening log streams and downloading logs; unlimited when unset
+    #[serde(default = "default_log_stream_limit")]
+    pub log_streams: Option<RateLimit>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_rate_limits_enabled(),
+            attempt_starts: default_attempt_start_limit(),
+            diffs: default_diff_limit(),
+            log_streams: default_log_stream_limit(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS, Default)]
+pub struct ChatNotificationConfig {
+    /// Address the app is reached at, used for links in Slack, Discord and email messages,
+    /// e.g. `https://kanban.example.com`. When unset, links use the address the app was last
+    /// reached at from another machine, as a reverse proxy forwards it, or else this machine.
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, Default, PartialEq, Eq)]
+#[ts(use_ts_enum)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SmtpSecurity {
+    /// Upgrade the connection with STARTTLS, usually on port 587
+    #[default]
+    StartTls,
+    /// Connect over TLS, usually on port 465
+    Tls,
+    /// Send unencrypted; only for relays on the local machine or network
+    None,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct EmailNotificationConfig {
+    /// Email when the coding agent of an attempt finishes or fails
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub smtp_host: Option<String>,
+    /// Defaults to the usual port of the security mode
+    #[serde(default)]
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender, e.g. `Vibe Kanban <kanban@example.com>`
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Recipients, separated by commas
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Only email about runs that took at least this many minutes; shorter ones usually
+    /// finish while the user is still watching
+    #[serde(default = "default_email_min_duration_mins")]
+    pub min_duration_mins: u32,
+}
+
+impl Default for EmailNotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: None,
+            smtp_port: None,
+            security: SmtpSecurity::default(),
+            username: None,
+            password: None,
+            from: None,
+            to: None,
+            min_duration_mins: default_email_min_duration_mins(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS, EnumString)]
 #[ts(use_ts_enum)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -49,6 +482,39 @@ pub struct Config {
     pub git_branch_prefix: String,
     #[serde(default)]
     pub showcases: ShowcaseState,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub commit_signing: CommitSigningConfig,
+    #[serde(default)]
+    pub vulnerability_scan: VulnerabilityScanConfig,
+    #[serde(default)]
+    pub stall_detection: StallDetectionConfig,
+    #[serde(default)]
+    pub staleness: StalenessThresholds,
+    #[serde(default)]
+    pub worktree_gc: WorktreeGcConfig,
+    #[serde(default)]
+    pub git_clone: GitCloneConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub resource_limits: ResourceLimitsConfig,
+    #[serde(default)]
+    pub dev_server: DevServerConfig,
+    #[serde(default)]
+    pub log_retention: LogRetentionConfig,
+    #[serde(default)]
+    pub event_journal: EventJournalConfig,
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+    #[serde(default)]
+    pub chat_notifications: ChatNotificationConfig,
+    #[serde(default)]
+    pub email_notifications: EmailNotificationConfig,
+    /// Addresses or CIDR ranges of the reverse proxies whose forwarding headers are trusted
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
 }
 
 impl Config {
@@ -99,6 +565,22 @@ impl Config {
             language: old_config.language,
             git_branch_prefix: default_git_branch_prefix(),
             showcases: ShowcaseState::default(),
+            telemetry: TelemetryConfig::default(),
+            commit_signing: CommitSigningConfig::default(),
+            vulnerability_scan: VulnerabilityScanConfig::default(),
+            stall_detection: StallDetectionConfig::default(),
+            staleness: StalenessThresholds::default(),
+            worktree_gc: WorktreeGcConfig::default(),
+            git_clone: GitCloneConfig::default(),
+            storage: StorageConfig::default(),
+            resource_limits: ResourceLimitsConfig::default(),
+            dev_server: DevServerConfig::default(),
+            log_retention: LogRetentionConfig::default(),
+            event_journal: EventJournalConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            chat_notifications: ChatNotificationConfig::default(),
+            email_notifications: EmailNotificationConfig::default(),
+            trusted_proxies: Vec::new(),
         })
     }
 }
@@ -144,6 +626,22 @@ impl Default for Config {
             language: UiLanguage::default(),
             git_branch_prefix: default_git_branch_prefix(),
             showcases: ShowcaseState::default(),
+            telemetry: TelemetryConfig::default(),
+            commit_signing: CommitSigningConfig::default(),
+            vulnerability_scan: VulnerabilityScanConfig::default(),
+            stall_detection: StallDetectionConfig::default(),
+            staleness: StalenessThresholds::default(),
+            worktree_gc: WorktreeGcConfig::default(),
+            git_clone: GitCloneConfig::default(),
+            storage: StorageConfig::default(),
+            resource_limits: ResourceLimitsConfig::default(),
+            dev_server: DevServerConfig::default(),
+            log_retention: LogRetentionConfig::default(),
+            event_journal: EventJournalConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            chat_notifications: ChatNotificationConfig::default(),
+            email_notifications: EmailNotificationConfig::default(),
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -6,17 +6,26 @@ use std::{
 
 use anyhow::{Error as AnyhowError, anyhow};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use db::{
     DBService,
     models::{
+        audit_log::AuditAction,
+        dev_server::DevServer,
         execution_process::{
             CreateExecutionProcess, ExecutionContext, ExecutionProcess, ExecutionProcessRunReason,
-            ExecutionProcessStatus,
+            ExecutionProcessStatus, KillReason,
         },
         execution_process_logs::ExecutionProcessLogs,
         executor_session::{CreateExecutorSession, ExecutorSession},
-        task::{Task, TaskStatus},
-        task_attempt::{TaskAttempt, TaskAttemptError},
+        project::{Project, TestReportFormat},
+        tag::Tag,
+        task::{CreateTask, Task, TaskStatus},
+        task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+        task_dependency::TaskDependency,
+        task_schedule::TaskSchedule,
+        test_run_result::TestRunResult,
+        wip_limit::WipLimitEnforcement,
     },
 };
 use executors::{
@@ -26,27 +35,46 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
-    executors::{ExecutorError, StandardCodingAgentExecutor},
+    executors::ExecutorError,
     profile::{ExecutorConfigs, ExecutorProfileId, to_default_variant},
 };
 use futures::{StreamExt, future};
-use sqlx::Error as SqlxError;
+use serde::Serialize;
+use sqlx::{Error as SqlxError, SqlitePool};
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::Instrument;
+use ts_rs::TS;
 use utils::{
-    log_msg::LogMsg,
+    diff::Diff,
+    log_msg::{LogMsg, TokenDelta},
     msg_store::MsgStore,
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
 
 use crate::services::{
-    git::{GitService, GitServiceError},
+    attribution,
+    audit_log::AuditEvent,
+    dev_server, failure_action,
+    git::{Commit, GitService, GitServiceError},
     image::ImageService,
+    log_export,
+    process_tree::ProcessTreeNode,
+    resource_usage::ResourceMonitor,
+    setup_cache::{self, SetupCache, SetupCacheError},
+    shutdown,
+    terminal::{Terminal, TerminalRegistry, TerminalSize},
+    test_report,
+    wip_limits::{self, WipLimitExceeded},
     worktree_manager::{WorktreeError, WorktreeManager},
 };
 pub type ContainerRef = String;
 
+/// Serializes the concurrency limit check with queueing or starting a process, so two callers
+/// cannot both take the last free slot
+static SCHEDULER_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 /// Data needed for background worktree cleanup (doesn't require DB access)
 #[derive(Debug, Clone)]
 pub struct WorktreeCleanupData {
@@ -55,9 +83,14 @@ pub struct WorktreeCleanupData {
     pub git_repo_path: Option<PathBuf>,
 }
 
-/// Cleanup worktrees without requiring database access
-pub async fn cleanup_worktrees_direct(data: &[WorktreeCleanupData]) -> Result<(), ContainerError> {
+/// Cleanup worktrees without requiring database access. Terminals open in them are closed
+/// first.
+pub async fn cleanup_worktrees_direct(
+    data: &[WorktreeCleanupData],
+    terminals: &TerminalRegistry,
+) -> Result<(), ContainerError> {
     for cleanup_data in data {
+        terminals.close_attempt(cleanup_data.attempt_id);
         tracing::debug!(
             "Cleaning up worktree for attempt {}: {:?}",
             cleanup_data.attempt_id,
@@ -81,6 +114,24 @@ pub async fn cleanup_worktrees_direct(data: &[WorktreeCleanupData]) -> Result<()
     Ok(())
 }
 
+/// A process stopped by a bulk stop
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct StoppedProcess {
+    pub execution_process_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub run_reason: ExecutionProcessRunReason,
+    /// Whether it was running or still waiting in the queue
+    pub previous_status: ExecutionProcessStatus,
+}
+
+/// What stopping several execution processes at once did
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct StopSummary {
+    pub stopped: Vec<StoppedProcess>,
+    /// Processes that could not be stopped, usually because they exited in the meantime
+    pub failed: Vec<Uuid>,
+}
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -95,12 +146,30 @@ pub enum ContainerError {
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
     KillFailed(std::io::Error),
+    #[error("Blocked by tasks that aren't done: {}", .0.join(", "))]
+    TaskBlocked(Vec<String>),
+    #[error("At most {} tasks may be {}", .0.max_tasks, .0.status)]
+    WipLimitReached(WipLimitExceeded),
     #[error(transparent)]
     TaskAttemptError(#[from] TaskAttemptError),
+    #[error("The server is shutting down")]
+    ShuttingDown,
     #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
 
+/// Fails with the titles of the tasks `task_id` depends on that aren't done yet
+async fn check_task_unblocked(pool: &SqlitePool, task_id: Uuid) -> Result<(), ContainerError> {
+    let blockers = TaskDependency::find_blockers(pool, task_id).await?;
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(ContainerError::TaskBlocked(
+            blockers.into_iter().map(|task| task.title).collect(),
+        ))
+    }
+}
+
 #[async_trait]
 pub trait ContainerService {
     fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>;
@@ -109,13 +178,30 @@ pub trait ContainerService {
 
     fn git(&self) -> &GitService;
 
+    /// Live CPU and memory use of the running execution processes
+    fn resource_monitor(&self) -> &ResourceMonitor;
+
+    /// Interactive shells open in attempts' worktrees
+    fn terminals(&self) -> &TerminalRegistry;
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf;
 
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError>;
 
     async fn delete(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
+        self.terminals().close_attempt(task_attempt.id);
         self.try_stop(task_attempt).await;
-        self.delete_inner(task_attempt).await
+        let result = self.delete_inner(task_attempt).await;
+        AuditEvent::new(
+            AuditAction::WorktreeDelete,
+            task_attempt.container_ref.clone().unwrap_or_default(),
+        )
+        .attempt(task_attempt.id)
+        .parameters(serde_json::json!({ "branch": task_attempt.branch }))
+        .outcome(&result)
+        .record(&self.db().pool)
+        .await;
+        result
     }
 
     /// Check if a task has any running execution processes
@@ -127,7 +213,10 @@ pub trait ContainerService {
                 ExecutionProcess::find_by_task_attempt_id(&self.db().pool, attempt.id, false).await
             {
                 for process in processes {
-                    if process.status == ExecutionProcessStatus::Running {
+                    if matches!(
+                        process.status,
+                        ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                    ) {
                         return Ok(true);
                     }
                 }
@@ -141,24 +230,154 @@ pub trait ContainerService {
     async fn stop_task_processes(
         &self,
         task_attempts: &[TaskAttempt],
-    ) -> Result<(), ContainerError> {
+    ) -> Result<StopSummary, ContainerError> {
+        let mut processes = Vec::new();
         for attempt in task_attempts {
-            self.try_stop(attempt).await;
+            processes.extend(
+                ExecutionProcess::find_by_task_attempt_id(&self.db().pool, attempt.id, false)
+                    .await?,
+            );
         }
-        Ok(())
+        Ok(self.stop_processes(processes).await)
     }
 
-    fn cleanup_action(&self, cleanup_script: Option<String>) -> Option<Box<ExecutorAction>> {
-        cleanup_script.map(|script| {
-            Box::new(ExecutorAction::new(
-                ExecutorActionType::ScriptRequest(ScriptRequest {
-                    script,
-                    language: ScriptRequestLanguage::Bash,
-                    context: ScriptContext::CleanupScript,
+    /// Stop every running or queued execution process of the project
+    async fn stop_project_processes(
+        &self,
+        project_id: Uuid,
+    ) -> Result<StopSummary, ContainerError> {
+        let processes = ExecutionProcess::find_active(&self.db().pool, Some(project_id)).await?;
+        Ok(self.stop_processes(processes).await)
+    }
+
+    /// Stop every running or queued execution process on the server
+    async fn stop_all_processes(&self) -> Result<StopSummary, ContainerError> {
+        let processes = ExecutionProcess::find_active(&self.db().pool, None).await?;
+        Ok(self.stop_processes(processes).await)
+    }
+
+    /// Stop those of `processes` that are running or queued. Queued ones are cancelled first,
+    /// so the slots freed by stopping the running ones do not start them.
+    async fn stop_processes(&self, processes: Vec<ExecutionProcess>) -> StopSummary {
+        let mut processes: Vec<ExecutionProcess> = processes
+            .into_iter()
+            .filter(|process| {
+                matches!(
+                    process.status,
+                    ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                )
+            })
+            .collect();
+        processes.sort_by_key(|process| process.status != ExecutionProcessStatus::Queued);
+
+        let mut summary = StopSummary::default();
+        for process in processes {
+            match self
+                .stop_execution(&process, ExecutionProcessStatus::Killed)
+                .await
+            {
+                Ok(()) => summary.stopped.push(StoppedProcess {
+                    execution_process_id: process.id,
+                    task_attempt_id: process.task_attempt_id,
+                    run_reason: process.run_reason,
+                    previous_status: process.status,
+                }),
+                Err(e) => {
+                    tracing::warn!("Failed to stop execution process {}: {}", process.id, e);
+                    summary.failed.push(process.id);
+                }
+            }
+        }
+        summary
+    }
+
+    /// Stop every running execution process as the server shuts down, all at once so it doesn't
+    /// take longer with more of them. Nothing starts afterwards; queued processes stay queued
+    /// and start with the server next time.
+    async fn stop_for_shutdown(&self) -> Result<StopSummary, ContainerError> {
+        shutdown::begin();
+        let pool = &self.db().pool;
+        let processes = ExecutionProcess::find_running(pool).await?;
+        let results = future::join_all(processes.iter().map(|process| async move {
+            if let Err(e) =
+                ExecutionProcess::update_kill_reason(pool, process.id, &KillReason::Shutdown).await
+            {
+                tracing::warn!("Failed to record kill reason: {}", e);
+            }
+            self.stop_execution(process, ExecutionProcessStatus::Killed)
+                .await
+        }))
+        .await;
+
+        let mut summary = StopSummary::default();
+        for (process, result) in processes.into_iter().zip(results) {
+            match result {
+                Ok(()) => summary.stopped.push(StoppedProcess {
+                    execution_process_id: process.id,
+                    task_attempt_id: process.task_attempt_id,
+                    run_reason: process.run_reason,
+                    previous_status: process.status,
                 }),
-                None,
-            ))
-        })
+                Err(e) => {
+                    tracing::warn!("Failed to stop execution process {}: {}", process.id, e);
+                    summary.failed.push(process.id);
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// The actions run after a coding agent: the project's cleanup script followed by its test
+    /// run, or whichever of them it has
+    fn cleanup_action(&self, project: &Project) -> Option<Box<ExecutorAction>> {
+        let test_run =
+            test_report::executor_action(project.test_run.as_ref().map(|config| &config.0))
+                .map(Box::new);
+        let Some(script) = project.cleanup_script.clone() else {
+            return test_run;
+        };
+        Some(Box::new(ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::CleanupScript,
+                env: HashMap::new(),
+            }),
+            test_run,
+        )))
+    }
+
+    /// Run the project's post-merge script in its repository after the attempt has been
+    /// merged. Returns None if the project has no post-merge script.
+    async fn start_post_merge_script(
+        &self,
+        task_attempt: &TaskAttempt,
+        project: &Project,
+    ) -> Result<Option<ExecutionProcess>, ContainerError> {
+        let Some(script) = project
+            .post_merge_script
+            .clone()
+            .filter(|script| !script.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::PostMergeScript,
+                env: HashMap::new(),
+            }),
+            None,
+        );
+        let execution_process = self
+            .start_execution(
+                task_attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::PostMergeScript,
+            )
+            .await?;
+        Ok(Some(execution_process))
     }
 
     async fn try_stop(&self, task_attempt: &TaskAttempt) {
@@ -167,7 +386,10 @@ pub trait ContainerService {
             ExecutionProcess::find_by_task_attempt_id(&self.db().pool, task_attempt.id, false).await
         {
             for process in processes {
-                if process.status == ExecutionProcessStatus::Running {
+                if matches!(
+                    process.status,
+                    ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                ) {
                     self.stop_execution(&process, ExecutionProcessStatus::Killed)
                         .await
                         .unwrap_or_else(|e| {
@@ -191,6 +413,9 @@ pub trait ContainerService {
     ) -> Result<ContainerRef, ContainerError>;
     async fn is_container_clean(&self, task_attempt: &TaskAttempt) -> Result<bool, ContainerError>;
 
+    /// Maximum number of coding agents to run at once, if limited
+    async fn max_concurrent_coding_agents(&self) -> Option<u32>;
+
     async fn start_execution_inner(
         &self,
         task_attempt: &TaskAttempt,
@@ -204,6 +429,35 @@ pub trait ContainerService {
         status: ExecutionProcessStatus,
     ) -> Result<(), ContainerError>;
 
+    /// Live OS processes started by the execution; empty once it has exited
+    async fn process_tree(
+        &self,
+        execution_process: &ExecutionProcess,
+    ) -> Result<Vec<ProcessTreeNode>, ContainerError>;
+
+    /// Kill one process started by the execution, with its descendants, leaving the rest of the
+    /// run alone. Returns false if `pid` is not a child process of the execution.
+    async fn kill_child_process(
+        &self,
+        execution_process: &ExecutionProcess,
+        pid: u32,
+    ) -> Result<bool, ContainerError>;
+
+    /// Kill what is left of an execution that was running when the server died, given the id
+    /// of the process it spawned. Returns false if nothing of it is running anymore.
+    async fn kill_orphaned_process(
+        &self,
+        execution_process: &ExecutionProcess,
+        pid: u32,
+    ) -> Result<bool, ContainerError>;
+
+    /// Start an interactive shell in the attempt's worktree
+    async fn open_terminal(
+        &self,
+        task_attempt: &TaskAttempt,
+        size: TerminalSize,
+    ) -> Result<Terminal, ContainerError>;
+
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError>;
 
     async fn copy_project_files(
@@ -220,6 +474,33 @@ pub trait ContainerService {
         stats_only: bool,
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>;
 
+    /// Stream only the changes made between two commits of an attempt's branch,
+    /// e.g. the slice of work produced by a single execution process.
+    async fn stream_commit_range_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        from_commit: &Commit,
+        to_commit: &Commit,
+        stats_only: bool,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>;
+
+    /// List the files changed by an attempt (or by a commit range of it) with line stats
+    /// but no contents, sorted by path. Contents are loaded per file with `get_diff_file`.
+    async fn list_diff_files(
+        &self,
+        task_attempt: &TaskAttempt,
+        range: Option<(&Commit, &Commit)>,
+    ) -> Result<Vec<Diff>, ContainerError>;
+
+    /// Load a single changed file with its contents, including files too large to be
+    /// inlined in the diff stream.
+    async fn get_diff_file(
+        &self,
+        task_attempt: &TaskAttempt,
+        range: Option<(&Commit, &Commit)>,
+        path: &str,
+    ) -> Result<Option<Diff>, ContainerError>;
+
     /// Fetch the MsgStore for a given execution ID, panicking if missing.
     async fn get_msg_store_by_id(&self, uuid: &Uuid) -> Option<Arc<MsgStore>> {
         let map = self.msg_stores().read().await;
@@ -290,6 +571,16 @@ pub trait ContainerService {
         }
     }
 
+    /// Live token deltas for a running process; `None` once its logs are no longer in memory.
+    async fn stream_tokens(
+        &self,
+        id: &Uuid,
+    ) -> Option<futures::stream::BoxStream<'static, TokenDelta>> {
+        self.get_msg_store_by_id(id)
+            .await
+            .map(|store| store.token_stream())
+    }
+
     async fn stream_normalized_logs(
         &self,
         id: &Uuid,
@@ -388,12 +679,12 @@ pub trait ContainerService {
                 ExecutorActionType::CodingAgentInitialRequest(request) => {
                     let executor = ExecutorConfigs::get_cached()
                         .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
+                    executor.normalize_output(temp_store.clone(), &current_dir);
                 }
                 ExecutorActionType::CodingAgentFollowUpRequest(request) => {
                     let executor = ExecutorConfigs::get_cached()
                         .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
+                    executor.normalize_output(temp_store.clone(), &current_dir);
                 }
                 _ => {
                     tracing::debug!(
@@ -420,7 +711,7 @@ pub trait ContainerService {
         let msg_stores = self.msg_stores().clone();
         let db = self.db().clone();
 
-        tokio::spawn(async move {
+        let stream_logs = async move {
             // Get the message store for this execution
             let store = {
                 let map = msg_stores.read().await;
@@ -486,16 +777,46 @@ pub trait ContainerService {
                     }
                 }
             }
-        })
+        };
+        tokio::spawn(stream_logs.instrument(tracing::info_span!(
+            "logs.stream",
+            execution_process_id = %execution_id
+        )))
     }
 
+    /// Refuse to start work on a task while a task it depends on isn't done
+    async fn ensure_task_unblocked(&self, task_id: Uuid) -> Result<(), ContainerError> {
+        check_task_unblocked(&self.db().pool, task_id).await
+    }
+
+    /// Check the work-in-progress limit that starting `task` moves it into. A refusing limit
+    /// fails; a warning one is returned for the caller to pass on.
+    async fn check_wip_limit(
+        &self,
+        task: &Task,
+    ) -> Result<Option<WipLimitExceeded>, ContainerError> {
+        let pool = &self.db().pool;
+        match wip_limits::check_move(pool, task.project_id, &task.status, TaskStatus::InProgress)
+            .await?
+        {
+            Some(exceeded) if exceeded.enforcement == WipLimitEnforcement::Refuse => {
+                Err(ContainerError::WipLimitReached(exceeded))
+            }
+            exceeded => Ok(exceeded),
+        }
+    }
+
+    #[tracing::instrument(
+        name = "attempt.start",
+        skip_all,
+        fields(task_attempt_id = %task_attempt.id, executor = %executor_profile_id)
+    )]
     async fn start_attempt(
         &self,
         task_attempt: &TaskAttempt,
         executor_profile_id: ExecutorProfileId,
     ) -> Result<ExecutionProcess, ContainerError> {
-        // Create container
-        self.create(task_attempt).await?;
+        self.ensure_task_unblocked(task_attempt.task_id).await?;
 
         // Get parent task
         let task = task_attempt
@@ -503,6 +824,18 @@ pub trait ContainerService {
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        if let Some(exceeded) = self.check_wip_limit(&task).await? {
+            tracing::warn!(
+                "Starting task {} exceeds the limit of {} {} tasks",
+                task.id,
+                exceeded.max_tasks,
+                exceeded.status
+            );
+        }
+
+        // Create container
+        self.create(task_attempt).await?;
+
         // Get parent project
         let project = task
             .parent_project(&self.db().pool)
@@ -523,15 +856,22 @@ pub trait ContainerService {
         );
         let prompt = ImageService::canonicalise_image_paths(&task.to_prompt(), &worktree_path);
 
-        let cleanup_action = self.cleanup_action(project.cleanup_script);
+        let cleanup_action = self.cleanup_action(&project);
+        // Output saved from an earlier run of the same setup script makes running it unnecessary
+        let setup_script = if self.restore_setup_cache(&project, &worktree_path).await {
+            None
+        } else {
+            project.setup_script
+        };
 
         // Choose whether to execute the setup_script or coding agent first
-        let execution_process = if let Some(setup_script) = project.setup_script {
+        let execution_process = if let Some(setup_script) = setup_script {
             let executor_action = ExecutorAction::new(
                 ExecutorActionType::ScriptRequest(ScriptRequest {
                     script: setup_script,
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
+                    env: HashMap::new(),
                 }),
                 // once the setup script is done, run the initial coding agent request
                 Some(Box::new(ExecutorAction::new(
@@ -568,19 +908,256 @@ pub trait ContainerService {
         Ok(execution_process)
     }
 
+    /// Copy saved setup script output into a new attempt's worktree if the project caches it
+    /// and the script and lockfiles are unchanged. Returns true if the setup script can be
+    /// skipped.
+    async fn restore_setup_cache(&self, project: &Project, worktree_path: &Path) -> bool {
+        let (Some(script), Some(config)) = (
+            &project.setup_script,
+            project
+                .setup_cache
+                .as_deref()
+                .filter(|config| config.enabled),
+        ) else {
+            return false;
+        };
+        let restored = async {
+            let head = self.git().get_head_info(worktree_path)?;
+            let key = setup_cache::cache_key(self.git(), worktree_path, &head.oid, script, config)?;
+            SetupCache::default()
+                .restore(
+                    &self.db().pool,
+                    project.id,
+                    &key,
+                    worktree_path,
+                    &config.paths,
+                )
+                .await
+        }
+        .await;
+        match restored {
+            Ok(restored) => {
+                if restored {
+                    tracing::info!(
+                        "Restored cached setup script output into {:?}",
+                        worktree_path
+                    );
+                }
+                restored
+            }
+            Err(e) => {
+                tracing::warn!("Failed to restore cached setup script output: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Save the output of a setup script that succeeded, if its project caches it, for later
+    /// attempts to reuse
+    async fn save_setup_cache(&self, ctx: &ExecutionContext) -> Result<(), SetupCacheError> {
+        if ctx.execution_process.run_reason != ExecutionProcessRunReason::SetupScript {
+            return Ok(());
+        }
+        let Some(project) = Project::find_by_id(&self.db().pool, ctx.task.project_id).await? else {
+            return Ok(());
+        };
+        let (Some(config), Some(commit)) = (
+            project.setup_cache.filter(|config| config.enabled),
+            &ctx.execution_process.before_head_commit,
+        ) else {
+            return Ok(());
+        };
+        let Ok(action) = ctx.execution_process.executor_action() else {
+            return Ok(());
+        };
+        let ExecutorActionType::ScriptRequest(request) = action.typ() else {
+            return Ok(());
+        };
+
+        let worktree_path = self.task_attempt_to_current_dir(&ctx.task_attempt);
+        let key =
+            setup_cache::cache_key(self.git(), &worktree_path, commit, &request.script, &config)?;
+        if let Some(entry) = SetupCache::default()
+            .save(
+                &self.db().pool,
+                project.id,
+                &key,
+                &worktree_path,
+                &config.paths,
+            )
+            .await?
+        {
+            tracing::info!(
+                "Cached {} bytes of setup script output for project {}",
+                entry.size_bytes,
+                project.id
+            );
+        }
+        Ok(())
+    }
+
+    /// Store the pass/fail counts of a finished test run, read from its output or from the
+    /// report it wrote. Runs without results, e.g. because the tests failed to build, are not
+    /// stored.
+    async fn record_test_results(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
+        if ctx.execution_process.run_reason != ExecutionProcessRunReason::TestScript {
+            return Ok(());
+        }
+        let Some(project) = Project::find_by_id(&self.db().pool, ctx.task.project_id).await? else {
+            return Ok(());
+        };
+        let Some(config) = project.test_run.map(|config| config.0) else {
+            return Ok(());
+        };
+
+        let output = match self.get_msg_store_by_id(&ctx.execution_process.id).await {
+            Some(store) => log_export::raw_text(&store.get_history(), None),
+            None => String::new(),
+        };
+        // A report left behind by an earlier run is ignored
+        let report = match config.report_path.as_deref() {
+            Some(report_path) if config.format == TestReportFormat::Junit => {
+                let path = self
+                    .task_attempt_to_current_dir(&ctx.task_attempt)
+                    .join(report_path);
+                let written_by_this_run = tokio::fs::metadata(&path)
+                    .await
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| {
+                        DateTime::<Utc>::from(modified) >= ctx.execution_process.started_at
+                    });
+                if written_by_this_run {
+                    Some(tokio::fs::read_to_string(&path).await?)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let Some(counts) = test_report::parse(config.format, &output, report.as_deref()) else {
+            tracing::info!(
+                "No test results found for execution process {}",
+                ctx.execution_process.id
+            );
+            return Ok(());
+        };
+        TestRunResult::upsert(
+            &self.db().pool,
+            ctx.execution_process.id,
+            ctx.task_attempt.id,
+            config.format,
+            &counts,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Start one run of a schedule: a new task from the schedule's tag, or a new attempt on
+    /// its task, started with the schedule's executor profile
+    async fn start_scheduled_run(
+        &self,
+        schedule: &TaskSchedule,
+    ) -> Result<TaskAttempt, ContainerError> {
+        let pool = &self.db().pool;
+        let task = match (schedule.task_id, schedule.tag_id) {
+            (Some(task_id), _) => Task::find_by_id(pool, task_id)
+                .await?
+                .ok_or(SqlxError::RowNotFound)?,
+            (None, Some(tag_id)) => {
+                let tag = Tag::find_by_id(pool, tag_id)
+                    .await?
+                    .ok_or(SqlxError::RowNotFound)?;
+                let task = Task::create(
+                    pool,
+                    &CreateTask::from_title_description(
+                        schedule.project_id,
+                        schedule.name.clone(),
+                        Some(tag.content),
+                    ),
+                    Uuid::new_v4(),
+                )
+                .await?;
+                Task::set_tags(pool, task.id, vec![tag.id]).await?;
+                task
+            }
+            (None, None) => {
+                return Err(ContainerError::Other(anyhow!(
+                    "Schedule has nothing to run"
+                )));
+            }
+        };
+
+        self.ensure_task_unblocked(task.id).await?;
+
+        let attempt_id = Uuid::new_v4();
+        let branch = self
+            .git_branch_from_task_attempt(&attempt_id, &task.title)
+            .await;
+        let task_attempt = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: schedule.executor_profile_id.executor,
+                base_branch: schedule.base_branch.clone(),
+                branch,
+                // Started by the schedule, not a user
+                started_by: None,
+            },
+            attempt_id,
+            task.id,
+        )
+        .await?;
+        self.start_attempt(&task_attempt, schedule.executor_profile_id.0.clone())
+            .await?;
+        Ok(task_attempt)
+    }
+
     async fn start_execution(
         &self,
         task_attempt: &TaskAttempt,
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
     ) -> Result<ExecutionProcess, ContainerError> {
+        self.start_execution_process(task_attempt, executor_action, run_reason, None)
+            .await
+    }
+
+    /// Run a failed process's action again as a new execution process linked to it
+    async fn retry_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        failed: &ExecutionProcess,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let executor_action = failed.executor_action()?;
+        self.start_execution_process(
+            task_attempt,
+            executor_action,
+            &failed.run_reason,
+            Some(failed.id),
+        )
+        .await
+    }
+
+    async fn start_execution_process(
+        &self,
+        task_attempt: &TaskAttempt,
+        executor_action: &ExecutorAction,
+        run_reason: &ExecutionProcessRunReason,
+        retry_of: Option<Uuid>,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        if shutdown::in_progress() {
+            return Err(ContainerError::ShuttingDown);
+        }
         // Update task status to InProgress when starting an attempt
         let task = task_attempt
             .parent_task(&self.db().pool)
             .await?
             .ok_or(SqlxError::RowNotFound)?;
         if task.status != TaskStatus::InProgress
-            && run_reason != &ExecutionProcessRunReason::DevServer
+            && !matches!(
+                run_reason,
+                ExecutionProcessRunReason::DevServer | ExecutionProcessRunReason::PostMergeScript
+            )
         {
             Task::update_status(&self.db().pool, task.id, TaskStatus::InProgress).await?;
         }
@@ -598,15 +1175,30 @@ pub trait ContainerService {
             task_attempt_id: task_attempt.id,
             executor_action: executor_action.clone(),
             run_reason: run_reason.clone(),
+            retry_of,
+            // Processes the server chains on, like cleanup scripts, count as the attempt's
+            started_by: attribution::current_user().or_else(|| task_attempt.started_by.clone()),
         };
 
-        let execution_process = ExecutionProcess::create(
-            &self.db().pool,
-            &create_execution_process,
-            Uuid::new_v4(),
-            before_head_commit.as_deref(),
-        )
-        .await?;
+        // Coding agents over the concurrency limit are queued instead of started
+        let execution_process = {
+            let _scheduler = SCHEDULER_LOCK.lock().await;
+            let status = if run_reason == &ExecutionProcessRunReason::CodingAgent
+                && self.coding_agent_limit_reached().await?
+            {
+                ExecutionProcessStatus::Queued
+            } else {
+                ExecutionProcessStatus::Running
+            };
+            ExecutionProcess::create(
+                &self.db().pool,
+                &create_execution_process,
+                Uuid::new_v4(),
+                before_head_commit.as_deref(),
+                status,
+            )
+            .await?
+        };
 
         if let Some(prompt) = match executor_action.typ() {
             ExecutorActionType::CodingAgentInitialRequest(coding_agent_request) => {
@@ -625,26 +1217,52 @@ pub trait ContainerService {
 
             let executor_session_record_id = Uuid::new_v4();
 
-            ExecutorSession::create(
+            if let Err(e) = ExecutorSession::create(
                 &self.db().pool,
                 &create_executor_data,
                 executor_session_record_id,
             )
-            .await?;
+            .await
+            {
+                let error = ContainerError::from(e);
+                self.record_start_failure(execution_process.id, &error)
+                    .await?;
+                return Err(error);
+            }
+        }
+
+        if execution_process.status == ExecutionProcessStatus::Queued {
+            ExecutionProcess::update_queue_positions(&self.db().pool).await?;
+            tracing::info!(
+                "Execution process {} queued: concurrent coding agent limit reached",
+                execution_process.id
+            );
+            return Ok(
+                ExecutionProcess::find_by_id(&self.db().pool, execution_process.id)
+                    .await?
+                    .unwrap_or(execution_process),
+            );
         }
 
+        self.launch_execution(task_attempt, &execution_process, executor_action)
+            .await?;
+        Ok(execution_process)
+    }
+
+    /// Spawn a created execution process and start persisting and normalizing its logs
+    async fn launch_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        execution_process: &ExecutionProcess,
+        executor_action: &ExecutorAction,
+    ) -> Result<(), ContainerError> {
         if let Err(start_error) = self
-            .start_execution_inner(task_attempt, &execution_process, executor_action)
+            .start_execution_inner(task_attempt, execution_process, executor_action)
             .await
         {
-            // Mark process as failed
-            if let Err(update_error) = ExecutionProcess::update_completion(
-                &self.db().pool,
-                execution_process.id,
-                ExecutionProcessStatus::Failed,
-                None,
-            )
-            .await
+            if let Err(update_error) = self
+                .record_start_failure(execution_process.id, &start_error)
+                .await
             {
                 tracing::error!(
                     "Failed to mark execution process {} as failed after start error: {}",
@@ -653,23 +1271,6 @@ pub trait ContainerService {
                 );
             }
 
-            // Persist the error to process stderr logs
-            let log_message = LogMsg::Stderr(format!("Failed to start execution: {start_error}"));
-            if let Ok(json_line) = serde_json::to_string(&log_message)
-                && let Err(err) = ExecutionProcessLogs::append_log_line(
-                    &self.db().pool,
-                    execution_process.id,
-                    &format!("{json_line}\n"),
-                )
-                .await
-            {
-                tracing::error!(
-                    "Failed to write to process log table {}: {}",
-                    execution_process.id,
-                    err
-                );
-            }
-
             return Err(start_error);
         }
 
@@ -680,7 +1281,7 @@ pub trait ContainerService {
                     if let Some(executor) =
                         ExecutorConfigs::get_cached().get_coding_agent(&request.executor_profile_id)
                     {
-                        executor.normalize_logs(
+                        executor.normalize_output(
                             msg_store,
                             &self.task_attempt_to_current_dir(task_attempt),
                         );
@@ -697,7 +1298,7 @@ pub trait ContainerService {
                     if let Some(executor) =
                         ExecutorConfigs::get_cached().get_coding_agent(&request.executor_profile_id)
                     {
-                        executor.normalize_logs(
+                        executor.normalize_output(
                             msg_store,
                             &self.task_attempt_to_current_dir(task_attempt),
                         );
@@ -713,9 +1314,207 @@ pub trait ContainerService {
         };
 
         self.spawn_stream_raw_logs_to_db(&execution_process.id);
+        Ok(())
+    }
+
+    /// Mark a process that could not be started as failed, with the reason in its stderr log
+    async fn record_start_failure(
+        &self,
+        execution_process_id: Uuid,
+        error: &ContainerError,
+    ) -> Result<(), SqlxError> {
+        ExecutionProcess::update_completion(
+            &self.db().pool,
+            execution_process_id,
+            ExecutionProcessStatus::Failed,
+            None,
+        )
+        .await?;
+
+        // Persist the error to process stderr logs
+        let log_message = LogMsg::Stderr(format!("Failed to start execution: {error}"));
+        if let Ok(json_line) = serde_json::to_string(&log_message)
+            && let Err(err) = ExecutionProcessLogs::append_log_line(
+                &self.db().pool,
+                execution_process_id,
+                &format!("{json_line}\n"),
+            )
+            .await
+        {
+            tracing::error!(
+                "Failed to write to process log table {}: {}",
+                execution_process_id,
+                err
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the configured number of coding agents is already running
+    async fn coding_agent_limit_reached(&self) -> Result<bool, ContainerError> {
+        let Some(limit) = self
+            .max_concurrent_coding_agents()
+            .await
+            .filter(|limit| *limit > 0)
+        else {
+            return Ok(false);
+        };
+        let running = ExecutionProcess::count_running_coding_agents(&self.db().pool).await?;
+        Ok(running >= i64::from(limit))
+    }
+
+    /// Start queued coding agents, highest task priority then oldest first, while the
+    /// concurrency limit allows
+    async fn start_queued_executions(&self) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        loop {
+            if shutdown::in_progress() {
+                return Ok(());
+            }
+            let process = {
+                let _scheduler = SCHEDULER_LOCK.lock().await;
+                if self.coding_agent_limit_reached().await? {
+                    return Ok(());
+                }
+                let Some(process) = ExecutionProcess::find_next_queued(pool).await? else {
+                    return Ok(());
+                };
+                if !ExecutionProcess::start_queued(pool, process.id).await? {
+                    continue;
+                }
+                process
+            };
+
+            // The process already counts as running, so it must not stay that way if it can't
+            // be launched
+            let ctx = match ExecutionProcess::load_context(pool, process.id).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    self.record_start_failure(process.id, &e.into()).await?;
+                    continue;
+                }
+            };
+            let executor_action = match ctx.execution_process.executor_action() {
+                Ok(executor_action) => executor_action,
+                Err(e) => {
+                    self.record_start_failure(process.id, &e.into()).await?;
+                    continue;
+                }
+            };
+            if let Err(e) = self
+                .launch_execution(&ctx.task_attempt, &ctx.execution_process, executor_action)
+                .await
+            {
+                tracing::error!(
+                    "Failed to start queued execution process {}: {}",
+                    process.id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Run the project's dev script for the attempt on a port of its own. The attempt keeps its
+    /// previous port while that is free, so the dev server URL stays the same.
+    async fn start_dev_server(
+        &self,
+        task_attempt: &TaskAttempt,
+        script: String,
+        restart_count: u32,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let pool = &self.db().pool;
+        let previous_port = DevServer::find_by_task_attempt_id(pool, task_attempt.id)
+            .await?
+            .map(|dev_server| dev_server.port)
+            .filter(|port| dev_server::is_port_free(*port));
+        let port = match previous_port {
+            Some(port) => port,
+            None => dev_server::allocate_port(&DevServer::ports_in_use(pool).await?)?,
+        };
+
+        let execution_process = self
+            .start_execution(
+                task_attempt,
+                &dev_server::action(script, port),
+                &ExecutionProcessRunReason::DevServer,
+            )
+            .await?;
+        DevServer::record_start(
+            pool,
+            task_attempt.id,
+            execution_process.id,
+            port,
+            restart_count,
+        )
+        .await?;
         Ok(execution_process)
     }
 
+    /// The project's action for when the cleanup script started after `ctx`'s coding agent
+    /// fails, if it has one
+    async fn cleanup_failure_action(
+        &self,
+        ctx: &ExecutionContext,
+    ) -> Result<Option<ExecutorAction>, ContainerError> {
+        let pool = &self.db().pool;
+        let Some(project) = Project::find_by_id(pool, ctx.task.project_id).await? else {
+            return Ok(None);
+        };
+        let Some(config) = project
+            .on_cleanup_failure
+            .as_deref()
+            .filter(|config| config.enabled)
+        else {
+            return Ok(None);
+        };
+        let action = ctx.execution_process.executor_action()?;
+        if failure_action::is_automatic_follow_up(&config.action, action) {
+            return Ok(None);
+        }
+
+        let executor_profile_id = match action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Some(request.executor_profile_id.clone())
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Some(request.executor_profile_id.clone())
+            }
+            ExecutorActionType::ScriptRequest(_) => None,
+        };
+        let session_id =
+            ExecutionProcess::find_latest_session_id_by_task_attempt(pool, ctx.task_attempt.id)
+                .await?;
+        Ok(failure_action::executor_action(
+            &config.action,
+            session_id.zip(executor_profile_id),
+            self.cleanup_action(&project),
+        ))
+    }
+
+    /// Start the action chained to run if `ctx`'s process failed. Returns false if it has none.
+    async fn try_start_failure_action(
+        &self,
+        ctx: &ExecutionContext,
+    ) -> Result<bool, ContainerError> {
+        let Some(on_failure) = ctx.execution_process.executor_action()?.on_failure() else {
+            return Ok(false);
+        };
+        let history = self
+            .get_msg_store_by_id(&ctx.execution_process.id)
+            .await
+            .map(|store| store.get_history())
+            .unwrap_or_default();
+        let action = failure_action::with_output(on_failure.clone(), &history);
+        let run_reason = if action.base_executor().is_some() {
+            ExecutionProcessRunReason::CodingAgent
+        } else {
+            ctx.execution_process.run_reason.clone()
+        };
+        self.start_execution(&ctx.task_attempt, &action, &run_reason)
+            .await?;
+        Ok(true)
+    }
+
     async fn try_start_next_action(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
         let action = ctx.execution_process.executor_action()?;
         let next_action = if let Some(next_action) = action.next_action() {
@@ -732,8 +1531,23 @@ pub trait ContainerService {
             return Ok(());
         };
 
+        // A failed process's failure action runs instead of the rest of the chain
+        if ctx.execution_process.status == ExecutionProcessStatus::Failed
+            && action.on_failure().is_some()
+        {
+            return Ok(());
+        }
+
         // Determine the run reason of the next action
         let next_run_reason = match ctx.execution_process.run_reason {
+            _ if matches!(
+                next_action.typ(),
+                ExecutorActionType::ScriptRequest(request)
+                    if request.context == ScriptContext::TestScript
+            ) =>
+            {
+                ExecutionProcessRunReason::TestScript
+            }
             ExecutionProcessRunReason::SetupScript => ExecutionProcessRunReason::CodingAgent,
             ExecutionProcessRunReason::CodingAgent => ExecutionProcessRunReason::CleanupScript,
             _ => {
@@ -745,7 +1559,15 @@ pub trait ContainerService {
             }
         };
 
-        self.start_execution(&ctx.task_attempt, next_action, &next_run_reason)
+        // The cleanup script's failure action is attached now that the agent has a session
+        let mut next_action = next_action.clone();
+        if next_run_reason == ExecutionProcessRunReason::CleanupScript
+            && next_action.on_failure().is_none()
+        {
+            next_action = next_action.with_on_failure(self.cleanup_failure_action(ctx).await?);
+        }
+
+        self.start_execution(&ctx.task_attempt, &next_action, &next_run_reason)
             .await?;
 
         tracing::debug!("Started next action: {:?}", next_action);
@@ -811,3 +1633,58 @@ pub trait ContainerService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use db::models::project::CreateProject;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn tasks_are_blocked_until_their_dependencies_are_done() {
+        let tmp = TempDir::new().unwrap();
+        let db = DBService::new(&tmp.path().join("db.sqlite")).await.unwrap();
+        let pool = &db.pool;
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            git_repo_path: "/repo".to_string(),
+            use_existing_repo: true,
+            git_url: None,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        };
+        Project::create(pool, &project, project_id).await.unwrap();
+        let mut tasks = Vec::new();
+        for title in ["design", "build"] {
+            let data = CreateTask::from_title_description(project_id, title.to_string(), None);
+            tasks.push(Task::create(pool, &data, Uuid::new_v4()).await.unwrap());
+        }
+        let (design, build) = (&tasks[0], &tasks[1]);
+        TaskDependency::create(pool, build.id, design.id)
+            .await
+            .unwrap();
+
+        assert!(check_task_unblocked(pool, design.id).await.is_ok());
+        match check_task_unblocked(pool, build.id).await {
+            Err(ContainerError::TaskBlocked(titles)) => assert_eq!(titles, ["design"]),
+            other => panic!("expected the task to be blocked, got {other:?}"),
+        }
+
+        Task::update_status(pool, design.id, TaskStatus::InProgress)
+            .await
+            .unwrap();
+        assert!(matches!(
+            check_task_unblocked(pool, build.id).await,
+            Err(ContainerError::TaskBlocked(_))
+        ));
+
+        Task::update_status(pool, design.id, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert!(check_task_unblocked(pool, build.id).await.is_ok());
+    }
+}
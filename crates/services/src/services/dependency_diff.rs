@@ -0,0 +1,229 @@
+//! Summarizes how an attempt changed its lockfiles as dependencies added, removed,
+//! upgraded or downgraded, since raw lockfile diffs are too noisy to review.
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use serde::Serialize;
+use ts_rs::TS;
+use utils::diff::Diff;
+
+use crate::services::vulnerability_scan::{PackageSet, manifest_parser};
+
+const LOCKFILES: &[&str] = &["Cargo.lock", "package-lock.json", "poetry.lock"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum DependencyChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct DependencyChange {
+    pub ecosystem: String,
+    pub name: String,
+    pub kind: DependencyChangeKind,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct LockfileDependencyDiff {
+    pub path: String,
+    pub changes: Vec<DependencyChange>,
+    /// True when the lockfile was too large to load, so its changes are unknown
+    pub content_omitted: bool,
+}
+
+/// Whether `path` names a lockfile whose changes can be summarized
+pub fn is_lockfile(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| LOCKFILES.contains(&name))
+}
+
+/// Summarize a changed file loaded with its contents, if it is a lockfile
+pub fn lockfile_diff(diff: &Diff) -> Option<LockfileDependencyDiff> {
+    let path = diff.new_path.as_deref().or(diff.old_path.as_deref())?;
+    if !is_lockfile(path) {
+        return None;
+    }
+    let parse = manifest_parser(path)?;
+    if diff.content_omitted || diff.lfs {
+        return Some(LockfileDependencyDiff {
+            path: path.to_string(),
+            changes: Vec::new(),
+            content_omitted: true,
+        });
+    }
+
+    let old = diff.old_content.as_deref().map(parse).unwrap_or_default();
+    let new = diff.new_content.as_deref().map(parse).unwrap_or_default();
+    Some(LockfileDependencyDiff {
+        path: path.to_string(),
+        changes: dependency_changes(&old, &new),
+        content_omitted: false,
+    })
+}
+
+/// Compare the packages pinned before and after, pairing the versions of a package that
+/// changed as upgrades or downgrades. Lockfiles may pin several versions of one package;
+/// versions that can't be paired are reported as added or removed.
+fn dependency_changes(old: &PackageSet, new: &PackageSet) -> Vec<DependencyChange> {
+    let (old, new) = (versions_by_package(old), versions_by_package(new));
+    let packages: BTreeSet<_> = old.keys().chain(new.keys()).copied().collect();
+    let empty = BTreeSet::new();
+
+    let mut changes = Vec::new();
+    for package in packages {
+        let (ecosystem, name) = package;
+        let old_versions = old.get(&package).unwrap_or(&empty);
+        let new_versions = new.get(&package).unwrap_or(&empty);
+        let mut removed: Vec<&str> = old_versions.difference(new_versions).copied().collect();
+        let mut added: Vec<&str> = new_versions.difference(old_versions).copied().collect();
+        removed.sort_by(|a, b| compare_versions(a, b));
+        added.sort_by(|a, b| compare_versions(a, b));
+
+        let change =
+            |kind, old_version: Option<&str>, new_version: Option<&str>| DependencyChange {
+                ecosystem: ecosystem.to_string(),
+                name: name.to_string(),
+                kind,
+                old_version: old_version.map(str::to_string),
+                new_version: new_version.map(str::to_string),
+            };
+        let paired = removed.len().min(added.len());
+        for (&from, &to) in removed.iter().zip(&added) {
+            let kind = if compare_versions(from, to) == Ordering::Greater {
+                DependencyChangeKind::Downgraded
+            } else {
+                DependencyChangeKind::Upgraded
+            };
+            changes.push(change(kind, Some(from), Some(to)));
+        }
+        for &version in &removed[paired..] {
+            changes.push(change(DependencyChangeKind::Removed, Some(version), None));
+        }
+        for &version in &added[paired..] {
+            changes.push(change(DependencyChangeKind::Added, None, Some(version)));
+        }
+    }
+    changes
+}
+
+/// Versions pinned for each (ecosystem, name)
+fn versions_by_package(packages: &PackageSet) -> BTreeMap<(&'static str, &str), BTreeSet<&str>> {
+    let mut versions: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for (ecosystem, name, version) in packages {
+        versions
+            .entry((*ecosystem, name.as_str()))
+            .or_default()
+            .insert(version.as_str());
+    }
+    versions
+}
+
+/// Compare dotted versions segment by segment, numerically where both segments are numbers
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn segments(version: &str) -> Vec<&str> {
+        version
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .collect()
+    }
+    let (a_segments, b_segments) = (segments(a), segments(b));
+    for (x, y) in a_segments.iter().zip(&b_segments) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a_segments.len().cmp(&b_segments.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packages(entries: &[(&'static str, &str, &str)]) -> PackageSet {
+        entries
+            .iter()
+            .map(|(ecosystem, name, version)| (*ecosystem, name.to_string(), version.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_lockfile_changes() {
+        let old = packages(&[
+            ("crates.io", "serde", "1.0.190"),
+            ("crates.io", "rand", "0.8.5"),
+            ("crates.io", "syn", "1.0.109"),
+            ("crates.io", "syn", "2.0.10"),
+            ("crates.io", "log", "0.4.20"),
+        ]);
+        let new = packages(&[
+            ("crates.io", "serde", "1.0.200"),
+            ("crates.io", "syn", "2.0.10"),
+            ("crates.io", "log", "0.4.9"),
+            ("crates.io", "tokio", "1.40.0"),
+        ]);
+
+        let changes: Vec<_> = dependency_changes(&old, &new)
+            .into_iter()
+            .map(|c| (c.name, c.kind, c.old_version, c.new_version))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "log".to_string(),
+                    DependencyChangeKind::Downgraded,
+                    Some("0.4.20".to_string()),
+                    Some("0.4.9".to_string())
+                ),
+                (
+                    "rand".to_string(),
+                    DependencyChangeKind::Removed,
+                    Some("0.8.5".to_string()),
+                    None
+                ),
+                (
+                    "serde".to_string(),
+                    DependencyChangeKind::Upgraded,
+                    Some("1.0.190".to_string()),
+                    Some("1.0.200".to_string())
+                ),
+                (
+                    "syn".to_string(),
+                    DependencyChangeKind::Removed,
+                    Some("1.0.109".to_string()),
+                    None
+                ),
+                (
+                    "tokio".to_string(),
+                    DependencyChangeKind::Added,
+                    None,
+                    Some("1.40.0".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_summarizes_lockfiles() {
+        assert!(is_lockfile("Cargo.lock"));
+        assert!(is_lockfile("frontend/package-lock.json"));
+        assert!(is_lockfile("backend/poetry.lock"));
+        assert!(!is_lockfile("package.json"));
+        assert!(!is_lockfile("src/Cargo.lock.rs"));
+    }
+}
@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use db::{
+    DBService,
+    models::{deploy_hook::DeployHook, task_attempt::TaskAttempt},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum DeployHookError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+/// What caused a deploy hook to be called
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployHookEvent {
+    /// A coding agent run for the attempt finished successfully
+    AttemptCompleted,
+    /// The attempt was merged into its target branch
+    Merged,
+}
+
+impl DeployHookEvent {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DeployHookEvent::AttemptCompleted => "attempt_completed",
+            DeployHookEvent::Merged => "merged",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DeployHookPayload<'a> {
+    event: DeployHookEvent,
+    project_id: Uuid,
+    task_id: Uuid,
+    attempt_id: Uuid,
+    branch: &'a str,
+    target_branch: &'a str,
+    commit: Option<&'a str>,
+}
+
+/// Accepted response shapes: `{"preview_url": ...}` or `{"url": ...}`
+#[derive(Debug, Deserialize)]
+struct DeployHookResponse {
+    #[serde(alias = "url")]
+    preview_url: Option<String>,
+}
+
+/// Calls per-project deploy hooks and records the preview URL they return on the attempt
+pub struct DeployHookService;
+
+impl DeployHookService {
+    /// Fire the project's deploy hook in the background, if one is configured
+    pub fn spawn_trigger(
+        db: DBService,
+        project_id: Uuid,
+        task_attempt: TaskAttempt,
+        event: DeployHookEvent,
+        commit: Option<String>,
+    ) {
+        tokio::spawn(async move {
+            if let Err(e) =
+                Self::trigger(&db, project_id, &task_attempt, event, commit.as_deref()).await
+            {
+                tracing::warn!(
+                    "Deploy hook failed for attempt {} ({}): {}",
+                    task_attempt.id,
+                    event.as_str(),
+                    e
+                );
+            }
+        });
+    }
+
+    /// Call the deploy hook and store the returned preview URL. Returns the preview URL, if any.
+    pub async fn trigger(
+        db: &DBService,
+        project_id: Uuid,
+        task_attempt: &TaskAttempt,
+        event: DeployHookEvent,
+        commit: Option<&str>,
+    ) -> Result<Option<String>, DeployHookError> {
+        let Some(hook) = DeployHook::find_by_project_id(&db.pool, project_id).await? else {
+            return Ok(None);
+        };
+
+        let url = render_url_template(&hook.url_template, project_id, task_attempt, event, commit);
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let mut request = client.post(&url).json(&DeployHookPayload {
+            event,
+            project_id,
+            task_id: task_attempt.task_id,
+            attempt_id: task_attempt.id,
+            branch: &task_attempt.branch,
+            target_branch: &task_attempt.target_branch,
+            commit,
+        });
+        if let Some(token) = hook.token.as_deref().filter(|t| !t.is_empty()) {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        // Hooks are not required to return anything useful
+        let preview_url = response
+            .json::<DeployHookResponse>()
+            .await
+            .ok()
+            .and_then(|body| body.preview_url)
+            .filter(|url| !url.trim().is_empty());
+
+        if let Some(preview_url) = &preview_url {
+            TaskAttempt::update_preview_url(&db.pool, task_attempt.id, preview_url).await?;
+            tracing::info!(
+                "Deploy hook returned preview {} for attempt {}",
+                preview_url,
+                task_attempt.id
+            );
+        }
+        Ok(preview_url)
+    }
+}
+
+fn render_url_template(
+    template: &str,
+    project_id: Uuid,
+    task_attempt: &TaskAttempt,
+    event: DeployHookEvent,
+    commit: Option<&str>,
+) -> String {
+    template
+        .replace("{project_id}", &project_id.to_string())
+        .replace("{task_id}", &task_attempt.task_id.to_string())
+        .replace("{attempt_id}", &task_attempt.id.to_string())
+        .replace("{branch}", &encode_component(&task_attempt.branch))
+        .replace("{commit}", commit.unwrap_or_default())
+        .replace("{event}", event.as_str())
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn attempt(branch: &str) -> TaskAttempt {
+        TaskAttempt {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            container_ref: None,
+            branch: branch.to_string(),
+            target_branch: "main".to_string(),
+            executor: "CLAUDE_CODE".to_string(),
+            worktree_deleted: false,
+            setup_completed_at: None,
+            preview_url: None,
+            started_by: None,
+            review_state: None,
+            review_note: None,
+            reviewed_by: None,
+            reviewed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn encodes_all_but_unreserved_characters() {
+        assert_eq!(
+            encode_component("vk/1a2b-fix_it.v2~"),
+            "vk%2F1a2b-fix_it.v2~"
+        );
+        assert_eq!(encode_component("a b&c=d?e#f%"), "a%20b%26c%3Dd%3Fe%23f%25");
+        assert_eq!(encode_component("ünï"), "%C3%BCn%C3%AF");
+        assert_eq!(encode_component(""), "");
+    }
+
+    #[test]
+    fn fills_in_known_placeholders_only() {
+        let project_id = Uuid::new_v4();
+        let attempt = attempt("vk/feature x");
+        let url = render_url_template(
+            "https://deploy.example.com/{project_id}/{task_id}/{attempt_id}?branch={branch}&commit={commit}&event={event}&other={unknown}",
+            project_id,
+            &attempt,
+            DeployHookEvent::Merged,
+            Some("abc123"),
+        );
+        assert_eq!(
+            url,
+            format!(
+                "https://deploy.example.com/{project_id}/{}/{}?branch=vk%2Ffeature%20x&commit=abc123&event=merged&other={{unknown}}",
+                attempt.task_id, attempt.id
+            )
+        );
+
+        assert_eq!(
+            render_url_template(
+                "https://deploy.example.com/{event}?commit={commit}",
+                project_id,
+                &attempt,
+                DeployHookEvent::AttemptCompleted,
+                None,
+            ),
+            "https://deploy.example.com/attempt_completed?commit="
+        );
+    }
+}
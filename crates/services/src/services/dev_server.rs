@@ -0,0 +1,118 @@
+//! Port allocation and status for attempts' dev servers. Each dev server gets its own port,
+//! passed in the `PORT` environment variable and kept across restarts while it is free.
+
+use std::{collections::HashMap, net::TcpListener};
+
+use db::models::{
+    dev_server::DevServer,
+    execution_process::{ExecutionProcess, ExecutionProcessStatus},
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Environment variable holding the allocated port
+pub const PORT_ENV_VAR: &str = "PORT";
+
+/// Attempts to find a port not already handed to another dev server
+const ALLOCATION_ATTEMPTS: usize = 20;
+
+/// Dev server of an attempt as reported by the API
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DevServerStatus {
+    pub execution_process_id: Option<Uuid>,
+    pub status: Option<ExecutionProcessStatus>,
+    pub port: Option<u16>,
+    /// Set while the dev server is running
+    pub url: Option<String>,
+    pub restart_count: u32,
+}
+
+impl DevServerStatus {
+    pub async fn for_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let Some(dev_server) = DevServer::find_by_task_attempt_id(pool, task_attempt_id).await?
+        else {
+            return Ok(Self {
+                execution_process_id: None,
+                status: None,
+                port: None,
+                url: None,
+                restart_count: 0,
+            });
+        };
+        let status = match dev_server.execution_process_id {
+            Some(id) => ExecutionProcess::find_by_id(pool, id)
+                .await?
+                .map(|process| process.status),
+            None => None,
+        };
+        let url = (status == Some(ExecutionProcessStatus::Running)).then(|| url(dev_server.port));
+        Ok(Self {
+            execution_process_id: dev_server.execution_process_id,
+            status,
+            port: Some(dev_server.port),
+            url,
+            restart_count: dev_server.restart_count,
+        })
+    }
+}
+
+/// Whether nothing is listening on `port` on the loopback interface
+pub fn is_port_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// Ask the OS for a free port, skipping ports held by other dev servers
+pub fn allocate_port(in_use: &[u16]) -> std::io::Result<u16> {
+    for _ in 0..ALLOCATION_ATTEMPTS {
+        let port = TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port();
+        if !in_use.contains(&port) {
+            return Ok(port);
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AddrInUse,
+        "no free port for the dev server",
+    ))
+}
+
+pub fn url(port: u16) -> String {
+    format!("http://localhost:{port}")
+}
+
+/// Action running the project's dev script with the allocated port in its environment
+pub fn action(script: String, port: u16) -> ExecutorAction {
+    ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script,
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::DevServer,
+            env: HashMap::from([(PORT_ENV_VAR.to_string(), port.to_string())]),
+        }),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_ports_that_are_free_and_not_in_use() {
+        let first = allocate_port(&[]).unwrap();
+        assert!(is_port_free(first));
+        let second = allocate_port(&[first]).unwrap();
+        assert_ne!(first, second);
+
+        let _listener = TcpListener::bind(("127.0.0.1", first)).unwrap();
+        assert!(!is_port_free(first));
+    }
+}
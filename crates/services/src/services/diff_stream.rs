@@ -315,6 +315,7 @@ fn omit_diff_contents(diff: &mut Diff) {
 
     diff.old_content = None;
     diff.new_content = None;
+    diff.hunks = None;
     diff.content_omitted = true;
 }
 
@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use db::{
     DBService,
@@ -16,7 +19,7 @@ use executors::{
     actions::{
         ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
     },
-    profile::ExecutorProfileId,
+    profile::{ExecutorConfigs, ExecutorProfileId, ProfileError},
 };
 use serde::{Deserialize, Serialize};
 use sqlx::Error as SqlxError;
@@ -27,6 +30,7 @@ use uuid::Uuid;
 use super::{
     container::{ContainerError, ContainerService},
     image::{ImageError, ImageService},
+    prompt_snippets,
 };
 
 #[derive(Debug, Error)]
@@ -39,6 +43,8 @@ pub enum DraftsServiceError {
     Image(#[from] ImageError),
     #[error(transparent)]
     ExecutionProcess(#[from] ExecutionProcessError),
+    #[error(transparent)]
+    Profile(#[from] ProfileError),
     #[error("Conflict: {0}")]
     Conflict(String),
 }
@@ -52,6 +58,7 @@ pub struct DraftResponse {
     pub queued: bool,
     pub variant: Option<String>,
     pub image_ids: Option<Vec<Uuid>>,
+    pub image_captions: Option<HashMap<Uuid, String>>,
     pub version: i64,
 }
 
@@ -60,6 +67,7 @@ pub struct UpdateFollowUpDraftRequest {
     pub prompt: Option<String>,
     pub variant: Option<Option<String>>,
     pub image_ids: Option<Vec<Uuid>>,
+    pub image_captions: Option<HashMap<Uuid, String>>,
     pub version: Option<i64>,
 }
 
@@ -69,6 +77,7 @@ pub struct UpdateRetryFollowUpDraftRequest {
     pub prompt: Option<String>,
     pub variant: Option<Option<String>>,
     pub image_ids: Option<Vec<Uuid>>,
+    pub image_captions: Option<HashMap<Uuid, String>>,
     pub version: Option<i64>,
 }
 
@@ -103,10 +112,30 @@ impl DraftsService {
             queued: d.queued,
             variant: d.variant,
             image_ids: d.image_ids,
+            image_captions: d.image_captions,
             version: d.version,
         }
     }
 
+    /// Reject variants the attempt's executor doesn't have before they are saved
+    async fn validate_variant(
+        &self,
+        task_attempt: &TaskAttempt,
+        variant: &Option<Option<String>>,
+    ) -> Result<(), DraftsServiceError> {
+        let Some(Some(variant)) = variant else {
+            return Ok(());
+        };
+        let base_profile =
+            ExecutionProcess::latest_executor_profile_for_attempt(self.pool(), task_attempt.id)
+                .await?;
+        ExecutorConfigs::get_cached().validate_profile_id(&ExecutorProfileId::with_variant(
+            base_profile.executor,
+            variant.clone(),
+        ))?;
+        Ok(())
+    }
+
     async fn ensure_follow_up_draft_row(
         &self,
         attempt_id: Uuid,
@@ -128,6 +157,7 @@ impl DraftsService {
                 queued: false,
                 variant: None,
                 image_ids: None,
+                image_captions: None,
             },
         )
         .await?;
@@ -158,8 +188,10 @@ impl DraftsService {
         let processes =
             ExecutionProcess::find_by_task_attempt_id(self.pool(), attempt_id, false).await?;
         Ok(processes.into_iter().any(|p| {
-            matches!(p.status, ExecutionProcessStatus::Running)
-                && !matches!(p.run_reason, ExecutionProcessRunReason::DevServer)
+            matches!(
+                p.status,
+                ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+            ) && !matches!(p.run_reason, ExecutionProcessRunReason::DevServer)
         }))
     }
 
@@ -181,6 +213,7 @@ impl DraftsService {
                 queued: false,
                 variant: None,
                 image_ids: None,
+                image_captions: None,
                 version: 0,
             }
         };
@@ -191,6 +224,7 @@ impl DraftsService {
         &self,
         task_id: Uuid,
         image_ids: &[Uuid],
+        image_captions: &HashMap<Uuid, String>,
         prompt: &str,
         worktree_path: &Path,
     ) -> Result<String, DraftsServiceError> {
@@ -199,13 +233,10 @@ impl DraftsService {
         }
 
         TaskImage::associate_many_dedup(self.pool(), task_id, image_ids).await?;
-        self.image
-            .copy_images_by_ids_to_worktree(worktree_path, image_ids)
-            .await?;
-        Ok(ImageService::canonicalise_image_paths(
-            prompt,
-            worktree_path,
-        ))
+        Ok(self
+            .image
+            .prepare_prompt_images(prompt, image_ids, image_captions, worktree_path)
+            .await?)
     }
 
     async fn start_follow_up_from_draft(
@@ -235,12 +266,19 @@ impl DraftsService {
             .ok_or(SqlxError::RowNotFound)
             .map_err(DraftsServiceError::from)?;
 
-        let cleanup_action = container.cleanup_action(project.cleanup_script);
+        let cleanup_action = container.cleanup_action(&project);
 
-        let mut prompt = draft.prompt.clone();
+        let mut prompt =
+            prompt_snippets::expand_for_project(self.pool(), project.id, &draft.prompt).await?;
         if let Some(image_ids) = &draft.image_ids {
             prompt = self
-                .handle_images_for_prompt(task_attempt.task_id, image_ids, &prompt, &worktree_path)
+                .handle_images_for_prompt(
+                    task_attempt.task_id,
+                    image_ids,
+                    &draft.image_captions.clone().unwrap_or_default(),
+                    &prompt,
+                    &worktree_path,
+                )
                 .await?;
         }
 
@@ -298,8 +336,14 @@ impl DraftsService {
                 "Draft changed, please retry with latest".to_string(),
             ));
         }
+        self.validate_variant(task_attempt, &payload.variant)
+            .await?;
 
-        if payload.prompt.is_none() && payload.variant.is_none() && payload.image_ids.is_none() {
+        if payload.prompt.is_none()
+            && payload.variant.is_none()
+            && payload.image_ids.is_none()
+            && payload.image_captions.is_none()
+        {
         } else {
             Draft::update_partial(
                 pool,
@@ -308,6 +352,7 @@ impl DraftsService {
                 payload.prompt.clone(),
                 payload.variant.clone(),
                 payload.image_ids.clone(),
+                payload.image_captions.clone(),
                 None,
             )
             .await?;
@@ -330,6 +375,7 @@ impl DraftsService {
                     queued: false,
                     variant: None,
                     image_ids: None,
+                    image_captions: None,
                     version: 0,
                 });
 
@@ -359,6 +405,8 @@ impl DraftsService {
                 ));
             }
         }
+        self.validate_variant(task_attempt, &payload.variant)
+            .await?;
 
         if existing.is_none() {
             let draft = Draft::upsert(
@@ -371,6 +419,7 @@ impl DraftsService {
                     queued: false,
                     variant: payload.variant.clone().unwrap_or(None),
                     image_ids: payload.image_ids.clone(),
+                    image_captions: payload.image_captions.clone(),
                 },
             )
             .await?;
@@ -378,7 +427,11 @@ impl DraftsService {
             return Ok(Self::draft_to_response(draft));
         }
 
-        if payload.prompt.is_none() && payload.variant.is_none() && payload.image_ids.is_none() {
+        if payload.prompt.is_none()
+            && payload.variant.is_none()
+            && payload.image_ids.is_none()
+            && payload.image_captions.is_none()
+        {
         } else {
             Draft::update_partial(
                 pool,
@@ -387,6 +440,7 @@ impl DraftsService {
                 payload.prompt.clone(),
                 payload.variant.clone(),
                 payload.image_ids.clone(),
+                payload.image_captions.clone(),
                 Some(payload.retry_process_id),
             )
             .await?;
@@ -422,6 +476,22 @@ impl DraftsService {
     ) -> Result<DraftResponse, DraftsServiceError> {
         let pool = self.pool();
 
+        // A queued draft is sent without anyone watching, so refuse to queue one that would fail
+        if payload.queued
+            && let Some(draft) =
+                Draft::find_by_task_attempt_and_type(pool, task_attempt.id, DraftType::FollowUp)
+                    .await?
+            && let Some(image_ids) = &draft.image_ids
+        {
+            self.image
+                .check_images_placed(
+                    &draft.prompt,
+                    image_ids,
+                    &draft.image_captions.unwrap_or_default(),
+                )
+                .await?;
+        }
+
         let rows_updated = Draft::set_queued(
             pool,
             task_attempt.id,
@@ -0,0 +1,228 @@
+//! Filtering HTTP proxy that enforces a sandbox's egress allowlist.
+//!
+//! Supports `CONNECT` tunnels (HTTPS and other TLS traffic) and absolute-form plain HTTP
+//! requests. Connections to hosts outside the allowlist are refused with `403 Forbidden`
+//! and reported on the channel returned by [`EgressProxy::start`].
+
+use std::{net::SocketAddr, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+
+/// Largest request head accepted before the request line and headers are complete
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// A running proxy; it stops accepting connections when dropped.
+pub struct EgressProxy {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl EgressProxy {
+    /// Start a proxy on an ephemeral loopback port. Hosts that are refused are sent on the
+    /// returned receiver.
+    pub async fn start(
+        allowed_hosts: Vec<String>,
+    ) -> std::io::Result<(Self, mpsc::UnboundedReceiver<String>)> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let (denied_tx, denied_rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!("Egress proxy failed to accept a connection: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let allowed_hosts = allowed_hosts.clone();
+                let denied_tx = denied_tx.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, &allowed_hosts, &denied_tx).await {
+                        tracing::debug!("Egress proxy connection failed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok((Self { addr, task }, denied_rx))
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for EgressProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// True if `host` matches an entry of `allowed_hosts`. `*.example.com` matches any
+/// subdomain of example.com; other entries must match exactly.
+pub fn host_allowed(allowed_hosts: &[String], host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowed_hosts.iter().any(|allowed| {
+        let allowed = allowed.trim().to_ascii_lowercase();
+        match allowed.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.')),
+            None => host == allowed,
+        }
+    })
+}
+
+async fn handle_connection(
+    mut client: TcpStream,
+    allowed_hosts: &[String],
+    denied_tx: &mpsc::UnboundedSender<String>,
+) -> std::io::Result<()> {
+    let head = read_head(&mut client).await?;
+    let Some((is_connect, host, port)) = parse_target(&head) else {
+        return respond(&mut client, "400 Bad Request").await;
+    };
+
+    if !host_allowed(allowed_hosts, &host) {
+        let _ = denied_tx.send(host);
+        return respond(&mut client, "403 Forbidden").await;
+    }
+
+    let mut upstream = match TcpStream::connect((host.as_str(), port)).await {
+        Ok(upstream) => upstream,
+        Err(_) => return respond(&mut client, "502 Bad Gateway").await,
+    };
+    if is_connect {
+        client
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await?;
+    } else {
+        // Origin servers accept absolute-form request targets, so forward the head as is
+        upstream.write_all(&head).await?;
+    }
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Read until the end of the request head. Any body bytes read along with it are kept.
+async fn read_head(client: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_HEAD_BYTES {
+            return Err(std::io::Error::other("request head too large"));
+        }
+        let n = client.read(&mut buf).await?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(head)
+}
+
+/// Parse the request line into (is CONNECT, host, port)
+fn parse_target(head: &[u8]) -> Option<(bool, String, u16)> {
+    let line = head.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?.trim_end();
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target, 443)?;
+        return Some((true, host, port));
+    }
+
+    let (default_port, rest) = if let Some(rest) = target.strip_prefix("http://") {
+        (80, rest)
+    } else if let Some(rest) = target.strip_prefix("https://") {
+        (443, rest)
+    } else {
+        return None;
+    };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let (host, port) = split_host_port(authority, default_port)?;
+    Some((false, host, port))
+}
+
+fn split_host_port(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        // IPv6 literal, e.g. [::1]:443
+        let (host, rest) = rest.split_once(']')?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().ok()?,
+            None => default_port,
+        };
+        (host, port)
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, default_port),
+        }
+    };
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+async fn respond(client: &mut TcpStream, status: &str) -> std::io::Result<()> {
+    let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    client.write_all(response.as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exact_hosts_and_wildcard_subdomains() {
+        let allowed = vec!["github.com".to_string(), "*.npmjs.org".to_string()];
+        assert!(host_allowed(&allowed, "github.com"));
+        assert!(host_allowed(&allowed, "GitHub.com."));
+        assert!(!host_allowed(&allowed, "api.github.com"));
+        assert!(host_allowed(&allowed, "registry.npmjs.org"));
+        assert!(!host_allowed(&allowed, "npmjs.org"));
+        assert!(!host_allowed(&allowed, "evilnpmjs.org"));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_targets() {
+        assert_eq!(
+            parse_target(b"CONNECT example.com:8443 HTTP/1.1\r\n\r\n"),
+            Some((true, "example.com".to_string(), 8443))
+        );
+        assert_eq!(
+            parse_target(b"GET http://user@example.com/path?q=1 HTTP/1.1\r\n\r\n"),
+            Some((false, "example.com".to_string(), 80))
+        );
+        assert_eq!(
+            parse_target(b"CONNECT [::1]:443 HTTP/1.1\r\n\r\n"),
+            Some((true, "::1".to_string(), 443))
+        );
+        assert_eq!(parse_target(b"GET /path HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[tokio::test]
+    async fn refuses_and_reports_hosts_outside_the_allowlist() {
+        let (proxy, mut denied) = EgressProxy::start(vec!["example.com".to_string()])
+            .await
+            .unwrap();
+        let mut client = TcpStream::connect(proxy.addr).await.unwrap();
+        client
+            .write_all(b"CONNECT attacker.test:443 HTTP/1.1\r\nHost: attacker.test\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"));
+        assert_eq!(denied.recv().await.as_deref(), Some("attacker.test"));
+    }
+}
@@ -0,0 +1,341 @@
+//! Emails the user when the coding agent of an attempt finishes or fails after a long run, with
+//! the exit status, how long it took and a summary of the changes.
+//!
+//! Mail is handed to the configured SMTP server by `curl`, which speaks SMTP with STARTTLS or
+//! implicit TLS. Credentials are passed in a private config file rather than on the command
+//! line, where other users could read them.
+
+use std::{io::Write, sync::Arc};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        execution_process::{
+            ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+        },
+        project::Project,
+    },
+};
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command, sync::RwLock};
+use utils::diff::Diff;
+
+use crate::services::{
+    attempt_comparison::DiffStats,
+    chat_notifier,
+    config::{Config, EmailNotificationConfig, SmtpSecurity},
+    git::GitService,
+};
+
+/// Changed files listed in the email; the totals cover all of them
+const MAX_LISTED_FILES: usize = 20;
+const SEND_TIMEOUT_SECS: &str = "30";
+
+#[derive(Debug, Error)]
+pub enum EmailError {
+    #[error("{0}")]
+    NotConfigured(&'static str),
+    #[error("Failed to run curl: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("The SMTP server did not accept the email: {0}")]
+    Send(String),
+}
+
+/// Email the user about the attempt's coding agent finishing, if email notifications are on and
+/// the run took long enough. `diffs` are the attempt's changes, when they could be read.
+pub fn spawn_notify_execution_halted(
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    ctx: &ExecutionContext,
+    diffs: Option<Vec<Diff>>,
+) {
+    let succeeded = match ctx.execution_process.status {
+        ExecutionProcessStatus::Completed => true,
+        ExecutionProcessStatus::Failed => false,
+        _ => return,
+    };
+    let task = ctx.task.clone();
+    let attempt = ctx.task_attempt.clone();
+    let process = ctx.execution_process.clone();
+    tokio::spawn(async move {
+        let email_config = config.read().await.email_notifications.clone();
+        if !email_config.enabled {
+            return;
+        }
+
+        // The run started with the latest coding agent, even when a cleanup script ended it
+        let started_at = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
+            &db.pool,
+            attempt.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await
+        .ok()
+        .flatten()
+        .map_or(process.started_at, |agent| agent.started_at);
+        let completed_at = process.completed_at.unwrap_or_else(Utc::now);
+        let duration_secs = (completed_at - started_at).num_seconds().max(0);
+        if duration_secs < i64::from(email_config.min_duration_mins) * 60 {
+            return;
+        }
+
+        let project = Project::find_by_id(&db.pool, task.project_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|project| project.name)
+            .unwrap_or_default();
+        let outcome = if succeeded { "finished" } else { "failed" };
+        let subject = format!("[{project}] {} {outcome}: {}", attempt.executor, task.title);
+
+        let mut body = vec![
+            format!("Task: {}", task.title),
+            format!("Project: {project}"),
+            format!("Executor: {}", attempt.executor),
+            format!("Branch: {}", attempt.branch),
+            match process.exit_code {
+                Some(code) => format!("Status: {outcome} (exit code {code})"),
+                None => format!("Status: {outcome}"),
+            },
+            format!("Duration: {}", format_duration(duration_secs)),
+            String::new(),
+        ];
+        body.extend(diff_summary(diffs.as_deref()));
+        let link = chat_notifier::attempt_link(&config, &task, attempt.id).await;
+        if !link.is_empty() {
+            body.push(String::new());
+            body.push(link);
+        }
+
+        if let Err(e) = send(&email_config, &subject, &body.join("\n"), completed_at).await {
+            tracing::warn!("Failed to email about task attempt {}: {}", attempt.id, e);
+        }
+    });
+}
+
+/// Send a test email so the user can check the SMTP settings
+pub async fn send_test(config: &EmailNotificationConfig) -> Result<(), EmailError> {
+    send(
+        config,
+        "Vibe Kanban test email",
+        "Email notifications are set up. You'll get an email when a coding agent finishes \
+         or fails after a long run.",
+        Utc::now(),
+    )
+    .await
+}
+
+fn format_duration(secs: i64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+fn diff_summary(diffs: Option<&[Diff]>) -> Vec<String> {
+    let Some(diffs) = diffs else {
+        return vec!["Changes: unavailable".to_string()];
+    };
+    if diffs.is_empty() {
+        return vec!["Changes: none".to_string()];
+    }
+    let stats = DiffStats::from_diffs(diffs);
+    let mut lines = vec![format!(
+        "Changes: {} files changed, {} insertions(+), {} deletions(-)",
+        stats.files_changed, stats.additions, stats.deletions
+    )];
+    lines.extend(diffs.iter().take(MAX_LISTED_FILES).map(|diff| {
+        format!(
+            "  {} (+{} -{})",
+            GitService::diff_path(diff),
+            diff.additions.unwrap_or(0),
+            diff.deletions.unwrap_or(0)
+        )
+    }));
+    if diffs.len() > MAX_LISTED_FILES {
+        lines.push(format!("  and {} more", diffs.len() - MAX_LISTED_FILES));
+    }
+    lines
+}
+
+fn required<'a>(value: &'a Option<String>, missing: &'static str) -> Result<&'a str, EmailError> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or(EmailError::NotConfigured(missing))
+}
+
+fn smtp_url(config: &EmailNotificationConfig) -> Result<String, EmailError> {
+    let host = required(&config.smtp_host, "Set the SMTP server to send emails")?;
+    let (scheme, default_port) = match config.security {
+        SmtpSecurity::StartTls => ("smtp", 587),
+        SmtpSecurity::Tls => ("smtps", 465),
+        SmtpSecurity::None => ("smtp", 25),
+    };
+    Ok(format!(
+        "{scheme}://{host}:{}",
+        config.smtp_port.unwrap_or(default_port)
+    ))
+}
+
+/// The address in `Name <address>`, or the whole value when it is a bare address
+fn bare_address(mailbox: &str) -> &str {
+    match (mailbox.find('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => mailbox[start + 1..end].trim(),
+        _ => mailbox.trim(),
+    }
+}
+
+/// Header value without line breaks, encoded when it isn't plain ASCII
+fn header_value(value: &str) -> String {
+    let value = value.replace(['\r', '\n'], " ");
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(value))
+    }
+}
+
+fn compose(from: &str, to: &str, subject: &str, body: &str, date: DateTime<Utc>) -> String {
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n\
+         {body}\r\n",
+        from.replace(['\r', '\n'], " "),
+        to.replace(['\r', '\n'], " "),
+        header_value(subject),
+        date.to_rfc2822(),
+    )
+}
+
+/// Quote a value for a curl config file
+fn curl_config_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+async fn send(
+    config: &EmailNotificationConfig,
+    subject: &str,
+    body: &str,
+    date: DateTime<Utc>,
+) -> Result<(), EmailError> {
+    let url = smtp_url(config)?;
+    let from = required(&config.from, "Set the sender address to send emails")?;
+    let to = required(&config.to, "Set a recipient address to send emails")?;
+    let recipients: Vec<&str> = to
+        .split(',')
+        .map(bare_address)
+        .filter(|address| !address.is_empty())
+        .collect();
+
+    let mut command = Command::new("curl");
+    command
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--max-time")
+        .arg(SEND_TIMEOUT_SECS)
+        .arg("--url")
+        .arg(&url)
+        .arg("--mail-from")
+        .arg(bare_address(from))
+        .arg("--upload-file")
+        .arg("-");
+    for recipient in &recipients {
+        command.arg("--mail-rcpt").arg(recipient);
+    }
+    if config.security == SmtpSecurity::StartTls {
+        command.arg("--ssl-reqd");
+    }
+
+    // Kept until curl exits; the file is only readable by the current user
+    let mut credentials = None;
+    if let Some(username) = config.username.as_deref().filter(|u| !u.is_empty()) {
+        let mut file = tempfile::NamedTempFile::new()?;
+        let user = format!("{username}:{}", config.password.as_deref().unwrap_or(""));
+        writeln!(file, "user = {}", curl_config_string(&user))?;
+        command.arg("--config").arg(file.path());
+        credentials = Some(file);
+    }
+
+    let mut child = command
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(compose(from, to, subject, body, date).as_bytes())
+            .await?;
+    }
+    let output = child.wait_with_output().await?;
+    drop(credentials);
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(EmailError::Send(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(12 * 60 + 5), "12m 5s");
+        assert_eq!(format_duration(2 * 3600 + 7 * 60 + 30), "2h 7m");
+    }
+
+    #[test]
+    fn composes_headers_without_injected_lines() {
+        let date = Utc.with_ymd_and_hms(2025, 12, 18, 9, 30, 0).unwrap();
+        let message = compose(
+            "Vibe Kanban <kanban@example.com>",
+            "me@example.com",
+            "Fix login\r\nBcc: someone@example.com",
+            "line one\nline two",
+            date,
+        );
+        assert!(message.starts_with("From: Vibe Kanban <kanban@example.com>\r\n"));
+        assert!(message.contains("Subject: Fix login  Bcc: someone@example.com\r\n"));
+        assert!(!message.contains("\r\nBcc:"));
+        assert!(message.ends_with("\r\n\r\nline one\r\nline two\r\n"));
+
+        let encoded = header_value("Überprüfung");
+        assert!(encoded.starts_with("=?UTF-8?B?") && encoded.ends_with("?="));
+    }
+
+    #[test]
+    fn uses_the_usual_port_of_the_security_mode() {
+        let mut config = EmailNotificationConfig {
+            smtp_host: Some("smtp.example.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(smtp_url(&config).unwrap(), "smtp://smtp.example.com:587");
+        config.security = SmtpSecurity::Tls;
+        assert_eq!(smtp_url(&config).unwrap(), "smtps://smtp.example.com:465");
+        config.smtp_port = Some(2525);
+        assert_eq!(smtp_url(&config).unwrap(), "smtps://smtp.example.com:2525");
+        config.smtp_host = Some(" ".to_string());
+        assert!(smtp_url(&config).is_err());
+        assert_eq!(
+            bare_address("Vibe Kanban <kanban@example.com>"),
+            "kanban@example.com"
+        );
+    }
+}
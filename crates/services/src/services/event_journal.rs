@@ -0,0 +1,192 @@
+//! Keeps a journal of the patches sent on the events stream, so a client that connects later,
+//! or someone debugging, can see how the board changed recently rather than only its current
+//! state.
+//!
+//! Patches are written in batches as they are sent, and entries older than the configured age,
+//! or beyond the configured count, are pruned regularly.
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use db::{DBService, models::event_journal::EventJournalEntry};
+use serde_json::Value;
+use sqlx::error::Error as SqlxError;
+use tokio::{
+    sync::{RwLock, broadcast::error::RecvError},
+    time::interval,
+};
+use tracing::{error, info, warn};
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+use crate::services::config::Config;
+
+/// Patches written at once; fewer are written when the flush interval comes first
+const BATCH_SIZE: usize = 200;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+/// Entries returned per request when the client doesn't ask for fewer
+const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Where to start reading the journal from: after the entry with an id, or at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalCursor {
+    AfterId(i64),
+    Since(DateTime<Utc>),
+}
+
+impl FromStr for JournalCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(id) = s.parse() {
+            return Ok(Self::AfterId(id));
+        }
+        DateTime::parse_from_rfc3339(s)
+            .map(|time| Self::Since(time.with_timezone(&Utc)))
+            .map_err(|_| format!("'{s}' is neither an entry id nor an RFC 3339 timestamp"))
+    }
+}
+
+/// Service that writes the events stream to the journal and prunes it as configured
+#[derive(Clone)]
+pub struct EventJournalService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    msg_store: Arc<MsgStore>,
+}
+
+impl EventJournalService {
+    pub fn new(db: DBService, config: Arc<RwLock<Config>>, msg_store: Arc<MsgStore>) -> Self {
+        Self {
+            db,
+            config,
+            msg_store,
+        }
+    }
+
+    pub async fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            self.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!("Starting event journal");
+        let mut receiver = self.msg_store.get_receiver();
+        let mut pending = Vec::new();
+        let mut flush = interval(FLUSH_INTERVAL);
+        let mut prune = interval(PRUNE_INTERVAL);
+
+        loop {
+            tokio::select! {
+                msg = receiver.recv() => match msg {
+                    Ok(LogMsg::JsonPatch(patch)) => match serde_json::to_value(&patch) {
+                        Ok(patch) => {
+                            pending.push(patch);
+                            if pending.len() >= BATCH_SIZE {
+                                self.flush(&mut pending).await;
+                            }
+                        }
+                        Err(e) => warn!("Failed to serialize patch for the event journal: {}", e),
+                    },
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Event journal fell behind and skipped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => {
+                        self.flush(&mut pending).await;
+                        return;
+                    }
+                },
+                _ = flush.tick() => self.flush(&mut pending).await,
+                _ = prune.tick() => {
+                    if let Err(e) = self.prune().await {
+                        error!("Error pruning the event journal: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn flush(&self, pending: &mut Vec<Value>) {
+        if pending.is_empty() {
+            return;
+        }
+        let patches = std::mem::take(pending);
+        if !self.config.read().await.event_journal.enabled {
+            return;
+        }
+        if let Err(e) = EventJournalEntry::append(&self.db.pool, &patches).await {
+            error!(
+                "Failed to write {} events to the journal: {}",
+                patches.len(),
+                e
+            );
+        }
+    }
+
+    /// Delete the entries the configured retention no longer keeps
+    pub async fn prune(&self) -> Result<u64, SqlxError> {
+        let policy = self.config.read().await.event_journal.clone();
+        let mut deleted = 0;
+        if let Some(hours) = policy.retention_hours {
+            let before = Utc::now() - chrono::Duration::hours(hours.into());
+            deleted += EventJournalEntry::delete_before(&self.db.pool, before).await?;
+        }
+        if let Some(max_entries) = policy.max_entries {
+            deleted +=
+                EventJournalEntry::delete_all_but_latest(&self.db.pool, max_entries.into()).await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Entries from the cursor on, or the oldest ones kept without one, oldest first
+    pub async fn history(
+        &self,
+        cursor: Option<JournalCursor>,
+        limit: Option<i64>,
+    ) -> Result<Vec<EventJournalEntry>, SqlxError> {
+        let limit = page_size(limit);
+        match cursor {
+            Some(JournalCursor::Since(since)) => {
+                EventJournalEntry::find_since(&self.db.pool, since, limit).await
+            }
+            Some(JournalCursor::AfterId(id)) => {
+                EventJournalEntry::find_after_id(&self.db.pool, id, limit).await
+            }
+            None => EventJournalEntry::find_after_id(&self.db.pool, 0, limit).await,
+        }
+    }
+}
+
+fn page_size(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn parses_ids_and_timestamps_as_cursors() {
+        assert_eq!("42".parse(), Ok(JournalCursor::AfterId(42)));
+        assert_eq!(
+            "2025-12-18T09:30:00+01:00".parse(),
+            Ok(JournalCursor::Since(
+                Utc.with_ymd_and_hms(2025, 12, 18, 8, 30, 0).unwrap()
+            ))
+        );
+        assert!("yesterday".parse::<JournalCursor>().is_err());
+    }
+
+    #[test]
+    fn keeps_pages_within_bounds() {
+        assert_eq!(page_size(None), MAX_PAGE_SIZE);
+        assert_eq!(page_size(Some(0)), 1);
+        assert_eq!(page_size(Some(50)), 50);
+        assert_eq!(page_size(Some(1_000_000)), MAX_PAGE_SIZE);
+    }
+}
@@ -5,8 +5,12 @@ use db::{
     models::{
         draft::{Draft, DraftType},
         execution_process::ExecutionProcess,
-        task::Task,
+        merge::Merge,
+        task::{StalenessThresholds, Task, TaskStatus},
         task_attempt::TaskAttempt,
+        task_attempt_diff_stats::TaskAttemptDiffStats,
+        task_comment::TaskComment,
+        task_dependency::TaskDependency,
     },
 };
 use serde_json::json;
@@ -15,6 +19,14 @@ use tokio::sync::RwLock;
 use utils::msg_store::MsgStore;
 use uuid::Uuid;
 
+use crate::services::{
+    automation::{self, AutomationEvent},
+    config::Config,
+    github_issue_sync,
+    notification_feed::{self, NotificationTrigger},
+    webhook::{self, WebhookNotification},
+};
+
 #[path = "events/patches.rs"]
 pub mod patches;
 #[path = "events/streams.rs"]
@@ -22,8 +34,13 @@ mod streams;
 #[path = "events/types.rs"]
 pub mod types;
 
-pub use patches::{draft_patch, execution_process_patch, task_attempt_patch, task_patch};
-pub use types::{EventError, EventPatch, EventPatchInner, HookTables, RecordTypes};
+pub use patches::{
+    draft_patch, execution_process_patch, task_attempt_patch, task_comment_patch, task_patch,
+};
+pub use types::{
+    EventError, EventPatch, EventPatchInner, EventRecordType, EventStreamFilter, HookTables,
+    RecordTypes,
+};
 
 #[derive(Clone)]
 pub struct EventService {
@@ -31,15 +48,22 @@ pub struct EventService {
     db: DBService,
     #[allow(dead_code)]
     entry_count: Arc<RwLock<usize>>,
+    config: Arc<RwLock<Config>>,
 }
 
 impl EventService {
     /// Creates a new EventService that will work with a DBService configured with hooks
-    pub fn new(db: DBService, msg_store: Arc<MsgStore>, entry_count: Arc<RwLock<usize>>) -> Self {
+    pub fn new(
+        db: DBService,
+        msg_store: Arc<MsgStore>,
+        entry_count: Arc<RwLock<usize>>,
+        config: Arc<RwLock<Config>>,
+    ) -> Self {
         Self {
             msg_store,
             db,
             entry_count,
+            config,
         }
     }
 
@@ -47,9 +71,15 @@ impl EventService {
         pool: &SqlitePool,
         msg_store: Arc<MsgStore>,
         task_id: Uuid,
+        staleness_thresholds: &StalenessThresholds,
     ) -> Result<(), SqlxError> {
         if let Some(task) = Task::find_by_id(pool, task_id).await? {
-            let tasks = Task::find_by_project_id_with_attempt_status(pool, task.project_id).await?;
+            let tasks = Task::find_by_project_id_with_attempt_status(
+                pool,
+                task.project_id,
+                staleness_thresholds,
+            )
+            .await?;
 
             if let Some(task_with_status) = tasks
                 .into_iter()
@@ -66,19 +96,107 @@ impl EventService {
         pool: &SqlitePool,
         msg_store: Arc<MsgStore>,
         attempt_id: Uuid,
+        staleness_thresholds: &StalenessThresholds,
     ) -> Result<(), SqlxError> {
         if let Some(attempt) = TaskAttempt::find_by_id(pool, attempt_id).await? {
-            Self::push_task_update_for_task(pool, msg_store, attempt.task_id).await?;
+            Self::push_task_update_for_task(pool, msg_store, attempt.task_id, staleness_thresholds)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Push an `/entries` record, the format used for events without a direct patch
+    async fn push_entry(
+        msg_store: &MsgStore,
+        entry_count: &RwLock<usize>,
+        db_op: &str,
+        record: RecordTypes,
+    ) {
+        let next_entry_count = {
+            let mut entry_count = entry_count.write().await;
+            *entry_count += 1;
+            *entry_count
+        };
+
+        let event_patch: EventPatch = EventPatch {
+            op: "add".to_string(),
+            path: format!("/entries/{next_entry_count}"),
+            value: EventPatchInner {
+                db_op: db_op.to_string(),
+                record,
+            },
+        };
+
+        let patch =
+            serde_json::from_value(json!([serde_json::to_value(event_patch).unwrap()])).unwrap();
+
+        msg_store.push_patch(patch);
+    }
+
+    /// Resolve or reopen the dependencies on a task after its status changed, refreshing the
+    /// dependent tasks and announcing the ones it unblocked
+    async fn sync_task_dependencies(
+        pool: &SqlitePool,
+        msg_store: Arc<MsgStore>,
+        entry_count: &RwLock<usize>,
+        task: &Task,
+        staleness_thresholds: &StalenessThresholds,
+    ) -> Result<(), SqlxError> {
+        if task.status == TaskStatus::Done {
+            for dependent in TaskDependency::resolve(pool, task.id).await? {
+                Self::push_task_update_for_task(
+                    pool,
+                    msg_store.clone(),
+                    dependent,
+                    staleness_thresholds,
+                )
+                .await?;
+                Self::push_entry(
+                    &msg_store,
+                    entry_count,
+                    "update",
+                    RecordTypes::TaskUnblocked {
+                        task_id: dependent,
+                        project_id: task.project_id,
+                        unblocked_by: task.id,
+                    },
+                )
+                .await;
+            }
+        } else {
+            for dependent in TaskDependency::unresolve(pool, task.id).await? {
+                Self::push_task_update_for_task(
+                    pool,
+                    msg_store.clone(),
+                    dependent,
+                    staleness_thresholds,
+                )
+                .await?;
+            }
         }
 
         Ok(())
     }
 
+    /// Push a fresh copy of a task to task streams, for changes the hooks don't see
+    pub async fn refresh_task(&self, task_id: Uuid) -> Result<(), SqlxError> {
+        let staleness_thresholds = self.config.read().await.staleness.clone();
+        Self::push_task_update_for_task(
+            &self.db.pool,
+            self.msg_store.clone(),
+            task_id,
+            &staleness_thresholds,
+        )
+        .await
+    }
+
     /// Creates the hook function that should be used with DBService::new_with_after_connect
     pub fn create_hook(
         msg_store: Arc<MsgStore>,
         entry_count: Arc<RwLock<usize>>,
         db_service: DBService,
+        config: Arc<RwLock<Config>>,
     ) -> impl for<'a> Fn(
         &'a mut sqlx::sqlite::SqliteConnection,
     ) -> std::pin::Pin<
@@ -90,12 +208,40 @@ impl EventService {
             let msg_store_for_hook = msg_store.clone();
             let entry_count_for_hook = entry_count.clone();
             let db_for_hook = db_service.clone();
+            let config_for_hook = config.clone();
             Box::pin(async move {
                 let mut handle = conn.lock_handle().await?;
                 let runtime_handle = tokio::runtime::Handle::current();
                 handle.set_preupdate_hook({
                     let msg_store_for_preupdate = msg_store_for_hook.clone();
+                    let db_for_preupdate = db_for_hook.clone();
+                    let runtime_for_preupdate = runtime_handle.clone();
                     move |preupdate: sqlx::sqlite::PreupdateHookResult<'_>| {
+                        // Status changes are caught here, while the previous status is known
+                        if preupdate.operation == SqliteOperation::Update {
+                            if preupdate.table == "tasks" {
+                                let task_id = preupdate
+                                    .get_old_column_value(0)
+                                    .ok()
+                                    .and_then(|val| <Uuid as Decode<Sqlite>>::decode(val).ok());
+                                let from = preupdate.get_old_column_value(4).ok().and_then(|val| {
+                                    <TaskStatus as Decode<Sqlite>>::decode(val).ok()
+                                });
+                                let to = preupdate.get_new_column_value(4).ok().and_then(|val| {
+                                    <TaskStatus as Decode<Sqlite>>::decode(val).ok()
+                                });
+                                if let (Some(task_id), Some(from), Some(to)) = (task_id, from, to)
+                                    && from != to
+                                {
+                                    let _runtime = runtime_for_preupdate.enter();
+                                    webhook::spawn_dispatch(
+                                        db_for_preupdate.clone(),
+                                        WebhookNotification::task_status_changed(task_id, from, to),
+                                    );
+                                }
+                            }
+                            return;
+                        }
                         if preupdate.operation != SqliteOperation::Delete {
                             return;
                         }
@@ -125,6 +271,14 @@ impl EventService {
                                     msg_store_for_preupdate.push_patch(patch);
                                 }
                             }
+                            "task_comments" => {
+                                if let Ok(value) = preupdate.get_old_column_value(0)
+                                    && let Ok(comment_id) = <Uuid as Decode<Sqlite>>::decode(value)
+                                {
+                                    let patch = task_comment_patch::remove(comment_id);
+                                    msg_store_for_preupdate.push_patch(patch);
+                                }
+                            }
                             "drafts" => {
                                 let draft_type = preupdate
                                     .get_old_column_value(2)
@@ -160,6 +314,7 @@ impl EventService {
                     let entry_count_for_hook = entry_count_for_hook.clone();
                     let msg_store_for_hook = msg_store_for_hook.clone();
                     let db = db_for_hook.clone();
+                    let config = config_for_hook.clone();
 
                     if let Ok(table) = HookTables::from_str(hook.table) {
                         let rowid = hook.rowid;
@@ -168,13 +323,79 @@ impl EventService {
                                 (HookTables::Tasks, SqliteOperation::Delete)
                                 | (HookTables::TaskAttempts, SqliteOperation::Delete)
                                 | (HookTables::ExecutionProcesses, SqliteOperation::Delete)
-                                | (HookTables::Drafts, SqliteOperation::Delete) => {
+                                | (HookTables::Drafts, SqliteOperation::Delete)
+                                | (HookTables::TaskComments, SqliteOperation::Delete)
+                                | (HookTables::Merges, SqliteOperation::Delete) => {
                                     // Deletions handled in preupdate hook for reliable data capture
                                     return;
                                 }
+                                (HookTables::Merges, _) => {
+                                    match Merge::find_by_rowid(&db.pool, rowid).await {
+                                        Ok(Some(merge)) => {
+                                            if let Some(event) =
+                                                AutomationEvent::from_merge(&merge)
+                                            {
+                                                automation::spawn_evaluate(
+                                                    db.clone(),
+                                                    config.clone(),
+                                                    event,
+                                                );
+                                            }
+                                            if let Some(notification) =
+                                                WebhookNotification::from_merge(&merge)
+                                            {
+                                                webhook::spawn_dispatch(db.clone(), notification);
+                                            }
+                                            notification_feed::spawn_publish(
+                                                db.clone(),
+                                                NotificationTrigger::Merge(merge),
+                                            );
+                                        }
+                                        Ok(None) => {}
+                                        Err(e) => {
+                                            tracing::error!("Failed to fetch merge: {:?}", e);
+                                        }
+                                    }
+                                    return;
+                                }
+                                (HookTables::TaskAttemptDiffStats, _) => {
+                                    let staleness_thresholds =
+                                        config.read().await.staleness.clone();
+                                    let result = match TaskAttemptDiffStats::find_by_rowid(
+                                        &db.pool, rowid,
+                                    )
+                                    .await
+                                    {
+                                        Ok(Some(stats)) => {
+                                            EventService::push_task_update_for_attempt(
+                                                &db.pool,
+                                                msg_store_for_hook.clone(),
+                                                stats.task_attempt_id,
+                                                &staleness_thresholds,
+                                            )
+                                            .await
+                                        }
+                                        Ok(None) => Ok(()),
+                                        Err(e) => Err(e),
+                                    };
+                                    if let Err(e) = result {
+                                        tracing::error!(
+                                            "Failed to push task update after diff stats change: {:?}",
+                                            e
+                                        );
+                                    }
+                                    return;
+                                }
                                 (HookTables::Tasks, _) => {
                                     match Task::find_by_rowid(&db.pool, rowid).await {
-                                        Ok(Some(task)) => RecordTypes::Task(task),
+                                        Ok(Some(task)) => {
+                                            github_issue_sync::spawn_push_status(
+                                                db.clone(),
+                                                config.clone(),
+                                                task.clone(),
+                                            );
+                                            RecordTypes::Task(task)
+                                        }
                                         Ok(None) => RecordTypes::DeletedTask {
                                             rowid,
                                             project_id: None,
@@ -219,6 +440,17 @@ impl EventService {
                                         }
                                     }
                                 }
+                                (HookTables::TaskComments, _) => {
+                                    match TaskComment::find_by_rowid(&db.pool, rowid).await {
+                                        Ok(Some(comment)) => RecordTypes::TaskComment(comment),
+                                        // Removed again before we got to it
+                                        Ok(None) => return,
+                                        Err(e) => {
+                                            tracing::error!("Failed to fetch task comment: {:?}", e);
+                                            return;
+                                        }
+                                    }
+                                }
                                 (HookTables::Drafts, _) => {
                                     match Draft::find_by_rowid(&db.pool, rowid).await {
                                         Ok(Some(draft)) => match draft.draft_type {
@@ -245,14 +477,50 @@ impl EventService {
                                 SqliteOperation::Unknown(_) => "unknown",
                             };
 
+                            let staleness_thresholds = config.read().await.staleness.clone();
+
                             // Handle task-related operations with direct patches
                             match &record_type {
                                 RecordTypes::Task(task) => {
+                                    if let Err(err) = EventService::sync_task_dependencies(
+                                        &db.pool,
+                                        msg_store_for_hook.clone(),
+                                        &entry_count_for_hook,
+                                        task,
+                                        &staleness_thresholds,
+                                    )
+                                    .await
+                                    {
+                                        tracing::error!(
+                                            "Failed to sync dependencies of task {}: {:?}",
+                                            task.id,
+                                            err
+                                        );
+                                    }
+
+                                    // Keep the parent's subtask progress current
+                                    if let Some(parent_task_id) = task.parent_task_id
+                                        && let Err(err) = EventService::push_task_update_for_task(
+                                            &db.pool,
+                                            msg_store_for_hook.clone(),
+                                            parent_task_id,
+                                            &staleness_thresholds,
+                                        )
+                                        .await
+                                    {
+                                        tracing::error!(
+                                            "Failed to push update for parent task {}: {:?}",
+                                            parent_task_id,
+                                            err
+                                        );
+                                    }
+
                                     // Convert Task to TaskWithAttemptStatus
                                     if let Ok(task_list) =
                                         Task::find_by_project_id_with_attempt_status(
                                             &db.pool,
                                             task.project_id,
+                                            &staleness_thresholds,
                                         )
                                         .await
                                         && let Some(task_with_status) =
@@ -282,6 +550,14 @@ impl EventService {
                                     msg_store_for_hook.push_patch(patch);
                                     return;
                                 }
+                                RecordTypes::TaskComment(comment) => {
+                                    let patch = match hook.operation {
+                                        SqliteOperation::Insert => task_comment_patch::add(comment),
+                                        _ => task_comment_patch::replace(comment),
+                                    };
+                                    msg_store_for_hook.push_patch(patch);
+                                    return;
+                                }
                                 RecordTypes::DeletedDraft { draft_type, task_attempt_id: Some(id), .. } => {
                                     let patch = match draft_type {
                                         DraftType::FollowUp => draft_patch::follow_up_clear(*id),
@@ -306,6 +582,7 @@ impl EventService {
                                             Task::find_by_project_id_with_attempt_status(
                                                 &db.pool,
                                                 task.project_id,
+                                                &staleness_thresholds,
                                             )
                                             .await
                                         && let Some(task_with_status) =
@@ -327,6 +604,7 @@ impl EventService {
                                             Task::find_by_project_id_with_attempt_status(
                                                 &db.pool,
                                                 task.project_id,
+                                                &staleness_thresholds,
                                             )
                                             .await
                                         && let Some(task_with_status) =
@@ -349,10 +627,34 @@ impl EventService {
                                     };
                                     msg_store_for_hook.push_patch(patch);
 
+                                    if matches!(hook.operation, SqliteOperation::Update)
+                                        && let Some(event) =
+                                            AutomationEvent::from_execution_process(process)
+                                    {
+                                        automation::spawn_evaluate(
+                                            db.clone(),
+                                            config.clone(),
+                                            event,
+                                        );
+                                    }
+                                    if matches!(hook.operation, SqliteOperation::Update)
+                                        && let Some(notification) =
+                                            WebhookNotification::from_execution_process(process)
+                                    {
+                                        webhook::spawn_dispatch(db.clone(), notification);
+                                    }
+                                    if matches!(hook.operation, SqliteOperation::Update) {
+                                        notification_feed::spawn_publish(
+                                            db.clone(),
+                                            NotificationTrigger::ExecutionProcess(process.clone()),
+                                        );
+                                    }
+
                                     if let Err(err) = EventService::push_task_update_for_attempt(
                                         &db.pool,
                                         msg_store_for_hook.clone(),
                                         process.task_attempt_id,
+                                        &staleness_thresholds,
                                     )
                                     .await
                                     {
@@ -378,6 +680,7 @@ impl EventService {
                                                 &db.pool,
                                                 msg_store_for_hook.clone(),
                                                 *task_attempt_id,
+                                                &staleness_thresholds,
                                             )
                                             .await
                                         {
@@ -393,28 +696,13 @@ impl EventService {
                             }
 
                             // Fallback: use the old entries format for other record types
-                            let next_entry_count = {
-                                let mut entry_count = entry_count_for_hook.write().await;
-                                *entry_count += 1;
-                                *entry_count
-                            };
-
-                            let event_patch: EventPatch = EventPatch {
-                                op: "add".to_string(),
-                                path: format!("/entries/{next_entry_count}"),
-                                value: EventPatchInner {
-                                    db_op: db_op.to_string(),
-                                    record: record_type,
-                                },
-                            };
-
-                            let patch =
-                                serde_json::from_value(json!([
-                                    serde_json::to_value(event_patch).unwrap()
-                                ]))
-                                .unwrap();
-
-                            msg_store_for_hook.push_patch(patch);
+                            EventService::push_entry(
+                                &msg_store_for_hook,
+                                &entry_count_for_hook,
+                                db_op,
+                                record_type,
+                            )
+                            .await;
                         });
                     }
                 });
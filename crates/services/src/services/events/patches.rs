@@ -3,10 +3,13 @@ use db::models::{
     execution_process::ExecutionProcess,
     task::TaskWithAttemptStatus,
     task_attempt::TaskAttempt,
+    task_comment::TaskComment,
 };
 use json_patch::{AddOperation, Patch, PatchOperation, RemoveOperation, ReplaceOperation};
 use uuid::Uuid;
 
+use crate::services::project_clone::ProjectClone;
+
 // Shared helper to escape JSON Pointer segments
 fn escape_pointer_segment(s: &str) -> String {
     s.replace('~', "~0").replace('/', "~1")
@@ -137,6 +140,7 @@ pub mod draft_patch {
             sending: false,
             variant: None,
             image_ids: None,
+            image_captions: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             version: 0,
@@ -202,3 +206,78 @@ pub mod task_attempt_patch {
         })])
     }
 }
+
+/// Helper functions for creating task comment-specific patches
+pub mod task_comment_patch {
+    use super::*;
+
+    fn comment_path(comment_id: Uuid) -> String {
+        format!(
+            "/comments/{}",
+            escape_pointer_segment(&comment_id.to_string())
+        )
+    }
+
+    /// Create patch for adding a new comment
+    pub fn add(comment: &TaskComment) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: comment_path(comment.id)
+                .try_into()
+                .expect("Comment path should be valid"),
+            value: serde_json::to_value(comment).expect("Comment serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for updating an existing comment
+    pub fn replace(comment: &TaskComment) -> Patch {
+        Patch(vec![PatchOperation::Replace(ReplaceOperation {
+            path: comment_path(comment.id)
+                .try_into()
+                .expect("Comment path should be valid"),
+            value: serde_json::to_value(comment).expect("Comment serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for removing a comment
+    pub fn remove(comment_id: Uuid) -> Patch {
+        Patch(vec![PatchOperation::Remove(RemoveOperation {
+            path: comment_path(comment_id)
+                .try_into()
+                .expect("Comment path should be valid"),
+        })])
+    }
+}
+
+/// Helper functions for creating patches that report a project clone's progress
+pub mod project_clone_patch {
+    use super::*;
+
+    fn project_clone_path(clone_id: Uuid) -> String {
+        format!(
+            "/project_clones/{}",
+            escape_pointer_segment(&clone_id.to_string())
+        )
+    }
+
+    /// Create patch for adding a new clone
+    pub fn add(clone: &ProjectClone) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: project_clone_path(clone.id)
+                .try_into()
+                .expect("Project clone path should be valid"),
+            value: serde_json::to_value(clone)
+                .expect("Project clone serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for updating a clone's progress
+    pub fn replace(clone: &ProjectClone) -> Patch {
+        Patch(vec![PatchOperation::Replace(ReplaceOperation {
+            path: project_clone_path(clone.id)
+                .try_into()
+                .expect("Project clone path should be valid"),
+            value: serde_json::to_value(clone)
+                .expect("Project clone serialization should not fail"),
+        })])
+    }
+}
@@ -1,29 +1,52 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
 use db::models::{
     draft::{Draft, DraftType},
     execution_process::ExecutionProcess,
     task::{Task, TaskWithAttemptStatus},
+    task_attempt::TaskAttempt,
+    task_comment::TaskComment,
 };
 use futures::StreamExt;
-use serde_json::json;
+use json_patch::PatchOperation;
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
 use tokio_stream::wrappers::BroadcastStream;
-use utils::log_msg::LogMsg;
+use utils::{log_msg::LogMsg, msg_store::MsgCursor};
 use uuid::Uuid;
 
 use super::{
     EventService,
     patches::execution_process_patch,
-    types::{EventError, EventPatch, RecordTypes},
+    types::{
+        EventError, EventPatch, EventPatchInner, EventRecordType, EventStreamFilter, RecordTypes,
+    },
 };
 
 impl EventService {
-    /// Stream raw task messages for a specific project with initial snapshot
-    pub async fn stream_tasks_raw(
+    /// Stream raw task messages for one or more projects (e.g. the projects of a workspace)
+    /// with initial snapshot. The set of projects is fixed when the stream starts.
+    pub async fn stream_tasks_for_projects_raw(
         &self,
-        project_id: Uuid,
+        project_ids: Vec<Uuid>,
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, EventError>
     {
         // Get initial snapshot of tasks
-        let tasks = Task::find_by_project_id_with_attempt_status(&self.db.pool, project_id).await?;
+        let staleness_thresholds = self.config.read().await.staleness.clone();
+        let mut tasks = Vec::new();
+        for &project_id in &project_ids {
+            tasks.extend(
+                Task::find_by_project_id_with_attempt_status(
+                    &self.db.pool,
+                    project_id,
+                    &staleness_thresholds,
+                )
+                .await?,
+            );
+        }
 
         // Convert task array to object keyed by task ID
         let tasks_map: serde_json::Map<String, serde_json::Value> = tasks
@@ -40,15 +63,17 @@ impl EventService {
 
         // Clone necessary data for the async filter
         let db_pool = self.db.pool.clone();
+        let project_ids = Arc::new(project_ids);
 
         // Get filtered event stream
         let filtered_stream =
             BroadcastStream::new(self.msg_store.get_receiver()).filter_map(move |msg_result| {
                 let db_pool = db_pool.clone();
+                let project_ids = project_ids.clone();
                 async move {
                     match msg_result {
                         Ok(LogMsg::JsonPatch(patch)) => {
-                            // Filter events based on project_id
+                            // Filter events based on project_ids
                             if let Some(patch_op) = patch.0.first() {
                                 // Check if this is a direct task patch (new format)
                                 if patch_op.path().starts_with("/tasks/") {
@@ -59,7 +84,7 @@ impl EventService {
                                                 serde_json::from_value::<TaskWithAttemptStatus>(
                                                     op.value.clone(),
                                                 )
-                                                && task.project_id == project_id
+                                                && project_ids.contains(&task.project_id)
                                             {
                                                 return Some(Ok(LogMsg::JsonPatch(patch)));
                                             }
@@ -70,7 +95,7 @@ impl EventService {
                                                 serde_json::from_value::<TaskWithAttemptStatus>(
                                                     op.value.clone(),
                                                 )
-                                                && task.project_id == project_id
+                                                && project_ids.contains(&task.project_id)
                                             {
                                                 return Some(Ok(LogMsg::JsonPatch(patch)));
                                             }
@@ -91,7 +116,7 @@ impl EventService {
                                     // Handle old EventPatch format for non-task records
                                     match &event_patch.value.record {
                                         RecordTypes::Task(task) => {
-                                            if task.project_id == project_id {
+                                            if project_ids.contains(&task.project_id) {
                                                 return Some(Ok(LogMsg::JsonPatch(patch)));
                                             }
                                         }
@@ -99,7 +124,15 @@ impl EventService {
                                             project_id: Some(deleted_project_id),
                                             ..
                                         } => {
-                                            if *deleted_project_id == project_id {
+                                            if project_ids.contains(deleted_project_id) {
+                                                return Some(Ok(LogMsg::JsonPatch(patch)));
+                                            }
+                                        }
+                                        RecordTypes::TaskUnblocked {
+                                            project_id: unblocked_project_id,
+                                            ..
+                                        } => {
+                                            if project_ids.contains(unblocked_project_id) {
                                                 return Some(Ok(LogMsg::JsonPatch(patch)));
                                             }
                                         }
@@ -107,7 +140,7 @@ impl EventService {
                                             // Check if this task_attempt belongs to a task in our project
                                             if let Ok(Some(task)) =
                                                 Task::find_by_id(&db_pool, attempt.task_id).await
-                                                && task.project_id == project_id
+                                                && project_ids.contains(&task.project_id)
                                             {
                                                 return Some(Ok(LogMsg::JsonPatch(patch)));
                                             }
@@ -119,7 +152,7 @@ impl EventService {
                                             // Check if deleted attempt belonged to a task in our project
                                             if let Ok(Some(task)) =
                                                 Task::find_by_id(&db_pool, *deleted_task_id).await
-                                                && task.project_id == project_id
+                                                && project_ids.contains(&task.project_id)
                                             {
                                                 return Some(Ok(LogMsg::JsonPatch(patch)));
                                             }
@@ -269,6 +302,71 @@ impl EventService {
         Ok(combined_stream)
     }
 
+    /// Stream a task's comments with initial snapshot (raw LogMsg format for WebSocket)
+    pub async fn stream_task_comments_raw(
+        &self,
+        task_id: Uuid,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, EventError>
+    {
+        let comments = TaskComment::find_by_task_id(&self.db.pool, task_id).await?;
+
+        // Convert comments array to object keyed by comment ID
+        let comments_map: serde_json::Map<String, serde_json::Value> = comments
+            .into_iter()
+            .map(|comment| {
+                (
+                    comment.id.to_string(),
+                    serde_json::to_value(comment).unwrap(),
+                )
+            })
+            .collect();
+
+        let initial_patch = json!([{
+            "op": "replace",
+            "path": "/comments",
+            "value": comments_map
+        }]);
+        let initial_msg = LogMsg::JsonPatch(serde_json::from_value(initial_patch).unwrap());
+
+        let filtered_stream = BroadcastStream::new(self.msg_store.get_receiver()).filter_map(
+            move |msg_result| async move {
+                match msg_result {
+                    Ok(LogMsg::JsonPatch(patch)) => {
+                        let patch_op = patch.0.first()?;
+                        if !patch_op.path().starts_with("/comments/") {
+                            return None;
+                        }
+                        match patch_op {
+                            json_patch::PatchOperation::Add(json_patch::AddOperation {
+                                value,
+                                ..
+                            })
+                            | json_patch::PatchOperation::Replace(json_patch::ReplaceOperation {
+                                value,
+                                ..
+                            }) => serde_json::from_value::<TaskComment>(value.clone())
+                                .ok()
+                                .filter(|comment| comment.task_id == task_id)
+                                .map(|_| Ok(LogMsg::JsonPatch(patch))),
+                            // Removals can't be matched to a task; clients ignore unknown ids
+                            json_patch::PatchOperation::Remove(_) => {
+                                Some(Ok(LogMsg::JsonPatch(patch)))
+                            }
+                            _ => None,
+                        }
+                    }
+                    Ok(other) => Some(Ok(other)), // Pass through non-patch messages
+                    Err(_) => None,               // Filter out broadcast errors
+                }
+            },
+        );
+
+        let initial_stream = futures::stream::once(async move { Ok(initial_msg) });
+        let combined_stream = initial_stream.chain(filtered_stream).boxed();
+
+        Ok(combined_stream)
+    }
+
     /// Stream drafts for all task attempts in a project with initial snapshot (raw LogMsg)
     pub async fn stream_drafts_for_project_raw(
         &self,
@@ -305,6 +403,7 @@ impl EventService {
                 sending: false,
                 variant: None,
                 image_ids: None,
+                image_captions: None,
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
                 version: 0,
@@ -371,4 +470,365 @@ impl EventService {
         let combined_stream = initial_stream.chain(filtered_stream).boxed();
         Ok(combined_stream)
     }
+
+    /// The history of the events stream, then live events, narrowed down by `filter`, with the
+    /// cursor of each event. Given the cursor of the last event a client got, only the events
+    /// after it are replayed when the history still has them. `None` when the filter's attempt
+    /// or task doesn't exist, or doesn't belong to its task or project.
+    pub async fn stream_events_filtered(
+        &self,
+        filter: EventStreamFilter,
+        last: Option<MsgCursor>,
+    ) -> Result<
+        Option<futures::stream::BoxStream<'static, Result<(MsgCursor, LogMsg), std::io::Error>>>,
+        EventError,
+    > {
+        if filter.is_empty() {
+            return Ok(Some(self.msg_store.history_plus_stream_after(last)));
+        }
+        let Some(filter) = self.resolve_filter(filter).await? else {
+            return Ok(None);
+        };
+
+        let filter = Arc::new(filter);
+        let resolver = ScopeResolver::new(self.db.pool.clone());
+        let stream = self.msg_store.history_plus_stream_after(last);
+        let filtered_stream = stream.filter_map(move |msg_result| {
+            let filter = filter.clone();
+            let resolver = resolver.clone();
+            async move {
+                match msg_result {
+                    Ok((cursor, LogMsg::JsonPatch(patch))) => {
+                        let allowed = match patch.0.first().and_then(record_of_patch) {
+                            Some((record_type, scope)) => {
+                                let scope = if has_scope(&filter) {
+                                    resolver.complete(scope).await
+                                } else {
+                                    scope
+                                };
+                                allows(&filter, record_type, &scope)
+                            }
+                            None => filter.record_types.is_none(),
+                        };
+                        allowed.then_some(Ok((cursor, LogMsg::JsonPatch(patch))))
+                    }
+                    other => Some(other),
+                }
+            }
+        });
+        Ok(Some(filtered_stream.boxed()))
+    }
+
+    /// Fill in the task and project of the filter's attempt, and the project of its task
+    async fn resolve_filter(
+        &self,
+        mut filter: EventStreamFilter,
+    ) -> Result<Option<EventStreamFilter>, EventError> {
+        if let Some(attempt_id) = filter.task_attempt_id {
+            let Some(attempt) = TaskAttempt::find_by_id(&self.db.pool, attempt_id).await? else {
+                return Ok(None);
+            };
+            if filter
+                .task_id
+                .is_some_and(|task_id| task_id != attempt.task_id)
+            {
+                return Ok(None);
+            }
+            filter.task_id = Some(attempt.task_id);
+        }
+        if let Some(task_id) = filter.task_id {
+            let Some(task) = Task::find_by_id(&self.db.pool, task_id).await? else {
+                return Ok(None);
+            };
+            if filter
+                .project_id
+                .is_some_and(|project_id| project_id != task.project_id)
+            {
+                return Ok(None);
+            }
+            filter.project_id = Some(task.project_id);
+        }
+        Ok(Some(filter))
+    }
+}
+
+/// What a streamed record belongs to, as far as is known
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct RecordScope {
+    project_id: Option<Uuid>,
+    task_id: Option<Uuid>,
+    task_attempt_id: Option<Uuid>,
+}
+
+fn has_scope(filter: &EventStreamFilter) -> bool {
+    filter.project_id.is_some() || filter.task_id.is_some() || filter.task_attempt_id.is_some()
+}
+
+/// Whether a record passes the filter. What isn't known about the record, such as the attempt
+/// of a removed execution process, doesn't count against it.
+fn allows(filter: &EventStreamFilter, record_type: EventRecordType, scope: &RecordScope) -> bool {
+    if let Some(record_types) = &filter.record_types
+        && !record_types.contains(&record_type)
+    {
+        return false;
+    }
+    let differs = |wanted: Option<Uuid>, actual: Option<Uuid>| matches!((wanted, actual), (Some(wanted), Some(actual)) if wanted != actual);
+    !(differs(filter.project_id, scope.project_id)
+        || differs(filter.task_id, scope.task_id)
+        || differs(filter.task_attempt_id, scope.task_attempt_id))
+}
+
+fn uuid_field(value: &Value, field: &str) -> Option<Uuid> {
+    value.get(field)?.as_str()?.parse().ok()
+}
+
+/// The kind of record a patch is about and what the patch tells of its scope; `None` for
+/// patches of other kinds
+fn record_of_patch(op: &PatchOperation) -> Option<(EventRecordType, RecordScope)> {
+    let value = match op {
+        PatchOperation::Add(op) => Some(&op.value),
+        PatchOperation::Replace(op) => Some(&op.value),
+        _ => None,
+    };
+    let field = |name: &str| value.and_then(|value| uuid_field(value, name));
+    let path: &str = op.path();
+    let (collection, rest) = path.strip_prefix('/')?.split_once('/')?;
+    // The first segment after the collection is the record's id, or the attempt's for drafts
+    let id = rest.split('/').next().and_then(|id| id.parse().ok());
+
+    let record = match collection {
+        "tasks" => (
+            EventRecordType::Task,
+            RecordScope {
+                project_id: field("project_id"),
+                task_id: id,
+                ..Default::default()
+            },
+        ),
+        "task_attempts" => (
+            EventRecordType::TaskAttempt,
+            RecordScope {
+                task_id: field("task_id"),
+                task_attempt_id: id,
+                ..Default::default()
+            },
+        ),
+        "execution_processes" => (
+            EventRecordType::ExecutionProcess,
+            RecordScope {
+                task_attempt_id: field("task_attempt_id"),
+                ..Default::default()
+            },
+        ),
+        "drafts" => (
+            EventRecordType::Draft,
+            RecordScope {
+                task_attempt_id: id,
+                ..Default::default()
+            },
+        ),
+        "comments" => (
+            EventRecordType::TaskComment,
+            RecordScope {
+                task_id: field("task_id"),
+                ..Default::default()
+            },
+        ),
+        "project_clones" => (
+            EventRecordType::ProjectClone,
+            RecordScope {
+                project_id: field("project_id"),
+                ..Default::default()
+            },
+        ),
+        "entries" => {
+            let inner = serde_json::from_value::<EventPatchInner>(value?.clone()).ok()?;
+            record_of_entry(&inner.record)
+        }
+        _ => return None,
+    };
+    Some(record)
+}
+
+fn record_of_entry(record: &RecordTypes) -> (EventRecordType, RecordScope) {
+    let scope = |project_id, task_id, task_attempt_id| RecordScope {
+        project_id,
+        task_id,
+        task_attempt_id,
+    };
+    match record {
+        RecordTypes::Task(task) => (
+            EventRecordType::Task,
+            scope(Some(task.project_id), Some(task.id), None),
+        ),
+        RecordTypes::DeletedTask {
+            project_id,
+            task_id,
+            ..
+        } => (EventRecordType::Task, scope(*project_id, *task_id, None)),
+        RecordTypes::TaskUnblocked {
+            task_id,
+            project_id,
+            ..
+        } => (
+            EventRecordType::Task,
+            scope(Some(*project_id), Some(*task_id), None),
+        ),
+        RecordTypes::TaskAttempt(attempt) => (
+            EventRecordType::TaskAttempt,
+            scope(None, Some(attempt.task_id), Some(attempt.id)),
+        ),
+        RecordTypes::DeletedTaskAttempt { task_id, .. } => {
+            (EventRecordType::TaskAttempt, scope(None, *task_id, None))
+        }
+        RecordTypes::ExecutionProcess(process) => (
+            EventRecordType::ExecutionProcess,
+            scope(None, None, Some(process.task_attempt_id)),
+        ),
+        RecordTypes::DeletedExecutionProcess {
+            task_attempt_id, ..
+        } => (
+            EventRecordType::ExecutionProcess,
+            scope(None, None, *task_attempt_id),
+        ),
+        RecordTypes::Draft(draft) | RecordTypes::RetryDraft(draft) => (
+            EventRecordType::Draft,
+            scope(None, None, Some(draft.task_attempt_id)),
+        ),
+        RecordTypes::DeletedDraft {
+            task_attempt_id, ..
+        } => (EventRecordType::Draft, scope(None, None, *task_attempt_id)),
+        RecordTypes::TaskComment(comment) => (
+            EventRecordType::TaskComment,
+            scope(None, Some(comment.task_id), None),
+        ),
+    }
+}
+
+/// Looks up the task and project records belong to, remembering them for the life of the
+/// stream since they never change
+#[derive(Clone)]
+struct ScopeResolver {
+    pool: SqlitePool,
+    attempt_tasks: Arc<Mutex<HashMap<Uuid, Uuid>>>,
+    task_projects: Arc<Mutex<HashMap<Uuid, Uuid>>>,
+}
+
+impl ScopeResolver {
+    fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            attempt_tasks: Arc::default(),
+            task_projects: Arc::default(),
+        }
+    }
+
+    async fn attempt_task(&self, attempt_id: Uuid) -> Option<Uuid> {
+        if let Some(task_id) = self.attempt_tasks.lock().unwrap().get(&attempt_id) {
+            return Some(*task_id);
+        }
+        let task_id = TaskAttempt::find_by_id(&self.pool, attempt_id)
+            .await
+            .ok()??
+            .task_id;
+        self.attempt_tasks
+            .lock()
+            .unwrap()
+            .insert(attempt_id, task_id);
+        Some(task_id)
+    }
+
+    async fn task_project(&self, task_id: Uuid) -> Option<Uuid> {
+        if let Some(project_id) = self.task_projects.lock().unwrap().get(&task_id) {
+            return Some(*project_id);
+        }
+        let project_id = Task::find_by_id(&self.pool, task_id)
+            .await
+            .ok()??
+            .project_id;
+        self.task_projects
+            .lock()
+            .unwrap()
+            .insert(task_id, project_id);
+        Some(project_id)
+    }
+
+    /// Fill in the task and project of the record, where they can be looked up
+    async fn complete(&self, mut scope: RecordScope) -> RecordScope {
+        if scope.task_id.is_none()
+            && let Some(attempt_id) = scope.task_attempt_id
+        {
+            scope.task_id = self.attempt_task(attempt_id).await;
+        }
+        if scope.project_id.is_none()
+            && let Some(task_id) = scope.task_id
+        {
+            scope.project_id = self.task_project(task_id).await;
+        }
+        scope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::events::{draft_patch, task_patch};
+
+    #[test]
+    fn reads_the_scope_of_patches() {
+        let task_id = Uuid::new_v4();
+        let patch = task_patch::remove(task_id);
+        assert_eq!(
+            record_of_patch(&patch.0[0]),
+            Some((
+                EventRecordType::Task,
+                RecordScope {
+                    task_id: Some(task_id),
+                    ..Default::default()
+                }
+            ))
+        );
+
+        let attempt_id = Uuid::new_v4();
+        let patch = draft_patch::retry_clear(attempt_id);
+        assert_eq!(
+            record_of_patch(&patch.0[0]).map(|(_, scope)| scope.task_attempt_id),
+            Some(Some(attempt_id))
+        );
+    }
+
+    #[test]
+    fn only_known_scope_counts_against_a_record() {
+        let project_id = Uuid::new_v4();
+        let filter = EventStreamFilter {
+            project_id: Some(project_id),
+            record_types: Some(vec![EventRecordType::ExecutionProcess]),
+            ..Default::default()
+        };
+        let in_project = RecordScope {
+            project_id: Some(project_id),
+            ..Default::default()
+        };
+        let elsewhere = RecordScope {
+            project_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+
+        assert!(allows(
+            &filter,
+            EventRecordType::ExecutionProcess,
+            &in_project
+        ));
+        assert!(allows(
+            &filter,
+            EventRecordType::ExecutionProcess,
+            &RecordScope::default()
+        ));
+        assert!(!allows(
+            &filter,
+            EventRecordType::ExecutionProcess,
+            &elsewhere
+        ));
+        assert!(!allows(&filter, EventRecordType::Task, &in_project));
+    }
 }
@@ -4,6 +4,7 @@ use db::models::{
     execution_process::ExecutionProcess,
     task::Task,
     task_attempt::TaskAttempt,
+    task_comment::TaskComment,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::Error as SqlxError;
@@ -32,6 +33,14 @@ pub enum HookTables {
     ExecutionProcesses,
     #[strum(to_string = "drafts")]
     Drafts,
+    #[strum(to_string = "task_comments")]
+    TaskComments,
+    /// Only watched for automation rules; merges are not streamed
+    #[strum(to_string = "merges")]
+    Merges,
+    /// Only watched to refresh the task the attempt belongs to
+    #[strum(to_string = "task_attempt_diff_stats")]
+    TaskAttemptDiffStats,
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -42,6 +51,7 @@ pub enum RecordTypes {
     ExecutionProcess(ExecutionProcess),
     Draft(Draft),
     RetryDraft(Draft),
+    TaskComment(TaskComment),
     DeletedTask {
         rowid: i64,
         project_id: Option<Uuid>,
@@ -61,6 +71,40 @@ pub enum RecordTypes {
         draft_type: DraftType,
         task_attempt_id: Option<Uuid>,
     },
+    /// The last unfinished task `task_id` depended on was just done
+    TaskUnblocked {
+        task_id: Uuid,
+        project_id: Uuid,
+        unblocked_by: Uuid,
+    },
+}
+
+/// Kinds of records the events stream carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum EventRecordType {
+    Task,
+    TaskAttempt,
+    ExecutionProcess,
+    Draft,
+    TaskComment,
+    ProjectClone,
+}
+
+/// Narrows the events stream to the records of a project, task or attempt, and of some kinds.
+/// Fields left unset don't narrow it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventStreamFilter {
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    pub record_types: Option<Vec<EventRecordType>>,
+}
+
+impl EventStreamFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -0,0 +1,181 @@
+//! Measures execution processes when they finish.
+//!
+//! Wall time and exit code come from the process record and output sizes from its log
+//! history. Token usage and cost are read from the final usage reports that coding agents
+//! print: the `result` message of Claude Code and Amp, and Codex's last `token_count` event.
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::ExecutionProcess,
+    execution_process_metrics::{
+        CreateExecutionProcessMetrics, ExecutionMetricsAggregate, ExitCodeCount,
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+
+/// Project-wide metrics over the processes that started since `since`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionMetricsReport {
+    pub since: DateTime<Utc>,
+    pub aggregates: Vec<ExecutionMetricsAggregate>,
+    pub exit_codes: Vec<ExitCodeCount>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TokenUsage {
+    input_tokens: Option<i64>,
+    cached_input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+    cost_usd: Option<f64>,
+}
+
+/// Metrics of a finished `process` whose output is `history`
+pub fn measure(process: &ExecutionProcess, history: &[LogMsg]) -> CreateExecutionProcessMetrics {
+    let wall_time_ms = process
+        .completed_at
+        .map(|completed_at| {
+            (completed_at - process.started_at)
+                .num_milliseconds()
+                .max(0)
+        })
+        .unwrap_or(0);
+
+    let mut stdout = String::new();
+    let mut stdout_bytes = 0i64;
+    let mut stderr_bytes = 0i64;
+    for msg in history {
+        match msg {
+            LogMsg::Stdout(text) => {
+                stdout_bytes += text.len() as i64;
+                stdout.push_str(text);
+            }
+            LogMsg::Stderr(text) => stderr_bytes += text.len() as i64,
+            _ => {}
+        }
+    }
+    let usage = token_usage(&stdout);
+
+    CreateExecutionProcessMetrics {
+        wall_time_ms,
+        exit_code: process.exit_code,
+        stdout_bytes,
+        stderr_bytes,
+        input_tokens: usage.input_tokens,
+        cached_input_tokens: usage.cached_input_tokens,
+        output_tokens: usage.output_tokens,
+        cost_usd: usage.cost_usd,
+    }
+}
+
+/// The last usage report in a coding agent's output. Reports are cumulative, so the last one
+/// covers the whole session.
+fn token_usage(stdout: &str) -> TokenUsage {
+    stdout
+        .lines()
+        .rev()
+        .filter(|line| line.trim_start().starts_with('{'))
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .find_map(|json| claude_usage(&json).or_else(|| codex_usage(&json)))
+        .unwrap_or_default()
+}
+
+/// `{"type":"result","total_cost_usd":..,"usage":{..}}`, where input tokens exclude the ones
+/// read from or written to the prompt cache
+fn claude_usage(json: &Value) -> Option<TokenUsage> {
+    if json.get("type")?.as_str()? != "result" {
+        return None;
+    }
+    let usage = json.get("usage");
+    let count = |field: &str| usage.and_then(|usage| usage.get(field)?.as_i64());
+    let cached = count("cache_read_input_tokens");
+    let input = [
+        count("input_tokens"),
+        count("cache_creation_input_tokens"),
+        cached,
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(|sum, count| sum + count);
+    Some(TokenUsage {
+        input_tokens: input,
+        cached_input_tokens: cached,
+        output_tokens: count("output_tokens"),
+        cost_usd: json.get("total_cost_usd").and_then(Value::as_f64),
+    })
+}
+
+/// A `codex/event` notification carrying `{"type":"token_count","info":{"total_token_usage":..}}`
+fn codex_usage(json: &Value) -> Option<TokenUsage> {
+    if !json.get("method")?.as_str()?.starts_with("codex/event") {
+        return None;
+    }
+    let msg = json.get("params")?.get("msg")?;
+    if msg.get("type")?.as_str()? != "token_count" {
+        return None;
+    }
+    let usage = msg.get("info")?.get("total_token_usage")?;
+    let count = |field: &str| usage.get(field).and_then(Value::as_i64);
+    Some(TokenUsage {
+        input_tokens: count("input_tokens"),
+        cached_input_tokens: count("cached_input_tokens"),
+        output_tokens: count("output_tokens"),
+        cost_usd: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdout(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    #[test]
+    fn reads_the_claude_result_message() {
+        let output = stdout(&[
+            r#"{"type":"system","subtype":"init","session_id":"abc"}"#,
+            r#"{"type":"assistant","message":{"usage":{"input_tokens":3,"output_tokens":1}}}"#,
+            r#"{"type":"result","subtype":"success","total_cost_usd":0.0421,"usage":{"input_tokens":12,"cache_creation_input_tokens":300,"cache_read_input_tokens":4000,"output_tokens":250}}"#,
+        ]);
+        assert_eq!(
+            token_usage(&output),
+            TokenUsage {
+                input_tokens: Some(4312),
+                cached_input_tokens: Some(4000),
+                output_tokens: Some(250),
+                cost_usd: Some(0.0421),
+            }
+        );
+    }
+
+    #[test]
+    fn reads_the_last_codex_token_count() {
+        let event = |input: i64, output: i64| {
+            format!(
+                r#"{{"jsonrpc":"2.0","method":"codex/event/token_count","params":{{"msg":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":{input},"cached_input_tokens":100,"output_tokens":{output},"total_tokens":0}}}}}}}}}}"#
+            )
+        };
+        let output = stdout(&[&event(500, 20), "plain text", &event(900, 60)]);
+        assert_eq!(
+            token_usage(&output),
+            TokenUsage {
+                input_tokens: Some(900),
+                cached_input_tokens: Some(100),
+                output_tokens: Some(60),
+                cost_usd: None,
+            }
+        );
+    }
+
+    #[test]
+    fn output_without_usage_has_no_tokens() {
+        assert_eq!(
+            token_usage(&stdout(&["Compiling vibe-kanban", "{not json"])),
+            TokenUsage::default()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Decides whether a failed execution process is re-run under its project's [`RetryPolicy`].
+//!
+//! Only setup scripts and coding agents whose output ends with a recognizable transient
+//! error, such as a dropped connection or a rate limit, are retried; other failures are
+//! left for the user to look at.
+
+use std::time::Duration;
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    project::RetryPolicy,
+};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use utils::log_msg::LogMsg;
+
+/// Output messages at the end of a process that are searched for transient errors
+const TAIL_MESSAGES: usize = 200;
+
+/// Lowercase fragments of error messages that usually go away when the process is re-run
+const TRANSIENT_ERRORS: &[&str] = &[
+    "rate limit",
+    "rate_limit",
+    "too many requests",
+    "overloaded",
+    "econnreset",
+    "econnrefused",
+    "etimedout",
+    "enotfound",
+    "eai_again",
+    "connection reset",
+    "connection refused",
+    "connection timed out",
+    "network is unreachable",
+    "temporary failure in name resolution",
+    "could not resolve host",
+    "502 bad gateway",
+    "503 service unavailable",
+    "504 gateway timeout",
+];
+
+/// Longest accepted retry limit, to keep a broken setup from looping for hours
+const MAX_RETRIES: u32 = 10;
+
+#[derive(Debug, Error)]
+pub enum RetryPolicyError {
+    #[error("At most {MAX_RETRIES} retries are allowed")]
+    TooManyRetries,
+    #[error("The maximum backoff must not be shorter than the initial backoff")]
+    InvalidBackoff,
+}
+
+/// Check that the policy's limits are usable
+pub fn validate(policy: &RetryPolicy) -> Result<(), RetryPolicyError> {
+    if policy.max_retries > MAX_RETRIES {
+        return Err(RetryPolicyError::TooManyRetries);
+    }
+    if policy.max_backoff_secs < policy.initial_backoff_secs {
+        return Err(RetryPolicyError::InvalidBackoff);
+    }
+    Ok(())
+}
+
+/// Whether failures of processes run for `run_reason` may be retried
+pub fn is_retryable(run_reason: &ExecutionProcessRunReason) -> bool {
+    matches!(
+        run_reason,
+        ExecutionProcessRunReason::SetupScript | ExecutionProcessRunReason::CodingAgent
+    )
+}
+
+/// The transient error reported at the end of a process's output, if any
+pub fn transient_error(history: &[LogMsg]) -> Option<&'static str> {
+    let tail = history
+        .iter()
+        .rev()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(text) | LogMsg::Stderr(text) => Some(text.to_lowercase()),
+            _ => None,
+        })
+        .take(TAIL_MESSAGES)
+        .collect::<Vec<_>>();
+    TRANSIENT_ERRORS
+        .iter()
+        .find(|pattern| tail.iter().any(|text| text.contains(*pattern)))
+        .copied()
+}
+
+/// Delay before the `retry`-th retry (1-based): the initial backoff, doubled for every
+/// earlier retry and capped at the maximum backoff
+pub fn backoff(policy: &RetryPolicy, retry: u32) -> Duration {
+    let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+    Duration::from_secs(
+        policy
+            .initial_backoff_secs
+            .saturating_mul(factor)
+            .min(policy.max_backoff_secs),
+    )
+}
+
+/// How many times the original process has already been retried before `process`
+pub async fn previous_retries(
+    pool: &SqlitePool,
+    process: &ExecutionProcess,
+) -> Result<u32, sqlx::Error> {
+    let mut retries = 0;
+    let mut retry_of = process.retry_of;
+    while let Some(id) = retry_of {
+        retries += 1;
+        retry_of = ExecutionProcess::find_by_id(pool, id)
+            .await?
+            .and_then(|process| process.retry_of);
+    }
+    Ok(retries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            enabled: true,
+            max_retries: 5,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 60,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let delays: Vec<_> = (1..=5)
+            .map(|retry| backoff(&policy(), retry).as_secs())
+            .collect();
+        assert_eq!(delays, vec![10, 20, 40, 60, 60]);
+        assert_eq!(backoff(&policy(), 200).as_secs(), 60);
+    }
+
+    #[test]
+    fn detects_transient_errors_in_output() {
+        let history = vec![
+            LogMsg::Stdout("Installing dependencies".to_string()),
+            LogMsg::Stderr("npm ERR! code ECONNRESET".to_string()),
+            LogMsg::Finished,
+        ];
+        assert_eq!(transient_error(&history), Some("econnreset"));
+
+        let history = vec![LogMsg::Stderr(
+            r#"{"type":"error","error":{"type":"rate_limit_error"}}"#.to_string(),
+        )];
+        assert_eq!(transient_error(&history), Some("rate_limit"));
+
+        let history = vec![LogMsg::Stderr("error[E0308]: mismatched types".to_string())];
+        assert_eq!(transient_error(&history), None);
+    }
+
+    #[test]
+    fn validates_limits() {
+        assert!(validate(&policy()).is_ok());
+        assert!(
+            validate(&RetryPolicy {
+                max_retries: 50,
+                ..policy()
+            })
+            .is_err()
+        );
+        assert!(
+            validate(&RetryPolicy {
+                max_backoff_secs: 5,
+                ..policy()
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Actions chained to run when an attempt's cleanup script fails, configured per project.
+//!
+//! The action is attached to the cleanup script when it starts, so a follow-up continues the
+//! coding agent session that just finished. Follow-ups get the end of the failed script's
+//! output appended to their prompt.
+
+use db::models::project::FailureAction;
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        coding_agent_follow_up::CodingAgentFollowUpRequest,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    profile::ExecutorProfileId,
+};
+use thiserror::Error;
+use utils::log_msg::LogMsg;
+
+use crate::services::log_export;
+
+/// Lines of the failed script's output added to follow-up prompts
+const OUTPUT_LINES: usize = 40;
+
+#[derive(Debug, Error)]
+pub enum FailureActionError {
+    #[error("The failure script must not be empty")]
+    EmptyScript,
+    #[error("The failure follow-up prompt must not be empty")]
+    EmptyPrompt,
+}
+
+pub fn validate(action: &FailureAction) -> Result<(), FailureActionError> {
+    match action {
+        FailureAction::Script { script } if script.trim().is_empty() => {
+            Err(FailureActionError::EmptyScript)
+        }
+        FailureAction::FollowUp { prompt } if prompt.trim().is_empty() => {
+            Err(FailureActionError::EmptyPrompt)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The executor action for `action`. A follow-up continues `session` and is followed by
+/// `cleanup_action`; without a session there is nothing to follow up on.
+pub fn executor_action(
+    action: &FailureAction,
+    session: Option<(String, ExecutorProfileId)>,
+    cleanup_action: Option<Box<ExecutorAction>>,
+) -> Option<ExecutorAction> {
+    match action {
+        FailureAction::Script { script } => Some(ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: script.clone(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::DiagnosticScript,
+                env: Default::default(),
+            }),
+            None,
+        )),
+        FailureAction::FollowUp { prompt } => {
+            let (session_id, executor_profile_id) = session?;
+            Some(ExecutorAction::new(
+                ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                    prompt: prompt.clone(),
+                    session_id,
+                    executor_profile_id,
+                }),
+                cleanup_action,
+            ))
+        }
+    }
+}
+
+/// Whether `previous` is a follow-up started by `action`. Its cleanup script doesn't get
+/// another one, so a script that keeps failing can't keep the agent running.
+pub fn is_automatic_follow_up(action: &FailureAction, previous: &ExecutorAction) -> bool {
+    match (action, previous.typ()) {
+        (
+            FailureAction::FollowUp { prompt },
+            ExecutorActionType::CodingAgentFollowUpRequest(request),
+        ) => request.prompt.starts_with(prompt.as_str()),
+        _ => false,
+    }
+}
+
+/// `action` with the end of the failed process's output appended if it is a follow-up
+pub fn with_output(mut action: ExecutorAction, history: &[LogMsg]) -> ExecutorAction {
+    if let ExecutorActionType::CodingAgentFollowUpRequest(request) = &mut action.typ {
+        let output = log_export::raw_text(history, None);
+        let lines: Vec<&str> = output.lines().collect();
+        let tail = lines[lines.len().saturating_sub(OUTPUT_LINES)..].join("\n");
+        request.prompt = format!(
+            "{}\n\nEnd of the failed script's output:\n```\n{}\n```",
+            request.prompt, tail
+        );
+    }
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::executors::BaseCodingAgent;
+
+    use super::*;
+
+    fn follow_up(prompt: &str) -> ExecutorAction {
+        executor_action(
+            &FailureAction::FollowUp {
+                prompt: prompt.to_string(),
+            },
+            Some((
+                "session".to_string(),
+                ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            )),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn follow_ups_need_a_session_and_get_the_output() {
+        let action = FailureAction::FollowUp {
+            prompt: "The cleanup script failed, please fix it".to_string(),
+        };
+        assert!(executor_action(&action, None, None).is_none());
+
+        let history = vec![
+            LogMsg::Stdout("lint: 2 errors\n".to_string()),
+            LogMsg::Stderr("exit 1\n".to_string()),
+        ];
+        let ExecutorActionType::CodingAgentFollowUpRequest(request) =
+            with_output(follow_up("Fix it"), &history).typ
+        else {
+            panic!("expected a follow-up");
+        };
+        assert_eq!(
+            request.prompt,
+            "Fix it\n\nEnd of the failed script's output:\n```\nlint: 2 errors\nexit 1\n```"
+        );
+    }
+
+    #[test]
+    fn recognises_its_own_follow_ups() {
+        let action = FailureAction::FollowUp {
+            prompt: "Fix it".to_string(),
+        };
+        assert!(is_automatic_follow_up(
+            &action,
+            &with_output(follow_up("Fix it"), &[])
+        ));
+        assert!(!is_automatic_follow_up(&action, &follow_up("Add tests")));
+        assert!(!is_automatic_follow_up(
+            &FailureAction::Script {
+                script: "env".to_string()
+            },
+            &follow_up("Fix it")
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     collections::HashSet,
     fs,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 use ignore::WalkBuilder;
@@ -9,6 +9,7 @@ use serde::Serialize;
 use thiserror::Error;
 use tokio_util::sync::CancellationToken;
 use ts_rs::TS;
+use utils::path::is_within_root;
 
 #[derive(Clone)]
 pub struct FilesystemService {}
@@ -19,9 +20,78 @@ pub enum FilesystemError {
     DirectoryDoesNotExist,
     #[error("Path is not a directory")]
     PathIsNotDirectory,
+    #[error("Path must be relative and stay inside the worktree")]
+    PathOutsideRoot,
+    #[error("Path is not a file")]
+    PathIsNotFile,
+    #[error("File is larger than {MAX_EDITABLE_FILE_BYTES} bytes")]
+    FileTooLarge,
+    #[error("File is not valid UTF-8 text")]
+    NotText,
     #[error("Failed to read directory: {0}")]
     Io(#[from] std::io::Error),
 }
+
+/// Largest file that can be read or written through `read_text_file` and `write_text_file`
+pub const MAX_EDITABLE_FILE_BYTES: u64 = 1024 * 1024;
+
+/// `relative` without `.` components, refusing absolute paths, `..` and anything inside `.git`
+pub fn normalize_relative(relative: &str) -> Result<PathBuf, FilesystemError> {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(part) => normalized.push(part),
+            _ => return Err(FilesystemError::PathOutsideRoot),
+        }
+    }
+    match normalized.components().next() {
+        None => Err(FilesystemError::PathOutsideRoot),
+        Some(first) if first.as_os_str() == ".git" => Err(FilesystemError::PathOutsideRoot),
+        Some(_) => Ok(normalized),
+    }
+}
+
+/// Resolve `relative` against `root` like `normalize_relative`, also refusing symlinks that lead
+/// outside `root`. The file itself doesn't have to exist.
+pub fn resolve_within(root: &Path, relative: &str) -> Result<PathBuf, FilesystemError> {
+    let relative = normalize_relative(relative)?;
+    let root = root.canonicalize()?;
+    let path = root.join(relative);
+    if !is_within_root(&root, &path)? {
+        return Err(FilesystemError::PathOutsideRoot);
+    }
+    Ok(path)
+}
+
+/// Read a UTF-8 file at `relative` inside `root`
+pub fn read_text_file(root: &Path, relative: &str) -> Result<String, FilesystemError> {
+    let path = resolve_within(root, relative)?;
+    let metadata = fs::metadata(&path)?;
+    if !metadata.is_file() {
+        return Err(FilesystemError::PathIsNotFile);
+    }
+    if metadata.len() > MAX_EDITABLE_FILE_BYTES {
+        return Err(FilesystemError::FileTooLarge);
+    }
+    String::from_utf8(fs::read(&path)?).map_err(|_| FilesystemError::NotText)
+}
+
+/// Create or overwrite the file at `relative` inside `root`, creating missing parent directories
+pub fn write_text_file(root: &Path, relative: &str, content: &str) -> Result<(), FilesystemError> {
+    if content.len() as u64 > MAX_EDITABLE_FILE_BYTES {
+        return Err(FilesystemError::FileTooLarge);
+    }
+    let path = resolve_within(root, relative)?;
+    if path.exists() && !path.is_file() {
+        return Err(FilesystemError::PathIsNotFile);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+    Ok(())
+}
 #[derive(Debug, Serialize, TS)]
 pub struct DirectoryListResponse {
     pub entries: Vec<DirectoryEntry>,
@@ -321,3 +391,68 @@ impl FilesystemService {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn paths_must_stay_inside_the_root() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join(".git")).unwrap();
+
+        for path in [
+            "",
+            ".",
+            "/etc/passwd",
+            "../outside",
+            "src/../../outside",
+            ".git/config",
+        ] {
+            assert!(
+                matches!(
+                    resolve_within(root.path(), path),
+                    Err(FilesystemError::PathOutsideRoot)
+                ),
+                "{path} was accepted"
+            );
+        }
+        assert!(resolve_within(root.path(), "./src/new.rs").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_root_are_refused() {
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link")).unwrap();
+
+        assert!(matches!(
+            write_text_file(root.path(), "link/file.txt", "x"),
+            Err(FilesystemError::PathOutsideRoot)
+        ));
+        assert!(!outside.path().join("file.txt").exists());
+    }
+
+    #[test]
+    fn text_files_round_trip_within_the_size_limit() {
+        let root = TempDir::new().unwrap();
+        write_text_file(root.path(), "src/lib.rs", "fn main() {}\n").unwrap();
+        assert_eq!(
+            read_text_file(root.path(), "src/lib.rs").unwrap(),
+            "fn main() {}\n"
+        );
+
+        let too_large = "x".repeat(MAX_EDITABLE_FILE_BYTES as usize + 1);
+        assert!(matches!(
+            write_text_file(root.path(), "big.txt", &too_large),
+            Err(FilesystemError::FileTooLarge)
+        ));
+        assert!(matches!(
+            read_text_file(root.path(), "src"),
+            Err(FilesystemError::PathIsNotFile)
+        ));
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
 use chrono::{DateTime, Utc};
 use git2::{
@@ -8,12 +12,20 @@ use git2::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ts_rs::TS;
-use utils::diff::{Diff, DiffChangeKind, FileDiffDetails};
+use utils::diff::{
+    Diff, DiffChangeKind, DiffHunk, FileDiffDetails, compute_diff_hunks, compute_line_change_counts,
+};
 
 // Import for file ranking functionality
 use super::file_ranker::FileStat;
-use super::git_cli::{ChangeType, GitCli, GitCliError, StatusDiffEntry, StatusDiffOptions};
-use crate::services::github_service::GitHubRepoInfo;
+use super::git_cli::{
+    ChangeType, CloneProgress, CommitSigning, GitCli, GitCliError, StatusDiffEntry,
+    StatusDiffOptions,
+};
+use crate::services::{
+    config::{CommitSigningConfig, GitCloneConfig},
+    github_service::GitHubRepoInfo,
+};
 
 #[derive(Debug, Error)]
 pub enum GitServiceError {
@@ -37,15 +49,58 @@ pub enum GitServiceError {
     TokenUnavailable,
     #[error("Rebase in progress; resolve or abort it before retrying")]
     RebaseInProgress,
+    #[error("Stash conflict: {0}")]
+    StashConflict(String),
+    #[error("Branch '{0}' is protected")]
+    ProtectedBranch(String),
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+    #[error("Invalid commit range: {0}")]
+    InvalidCommitRange(String),
 }
 /// Service for managing Git operations in task execution workflows
 #[derive(Clone)]
-pub struct GitService {}
+pub struct GitService {
+    commit_signing: Arc<RwLock<Option<CommitSigning>>>,
+}
 
 // Max inline diff size for UI (in bytes). Files larger than this will have
 // their contents omitted from the diff stream to avoid UI crashes.
 const MAX_INLINE_DIFF_BYTES: usize = 2 * 1024 * 1024; // ~2MB
 
+// Max size of a single file whose contents are loaded on demand, bypassing the inline limit
+const MAX_ON_DEMAND_DIFF_BYTES: usize = 20 * 1024 * 1024; // ~20MB
+
+/// How much of each changed file to load when computing diffs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffContentMode {
+    /// Contents and hunks for files up to the inline size limit
+    Inline,
+    /// No contents, only addition/deletion counts
+    StatsOnly,
+    /// Contents and hunks for files up to the on-demand size limit
+    Full,
+}
+
+impl DiffContentMode {
+    fn max_bytes(self) -> usize {
+        match self {
+            DiffContentMode::Inline | DiffContentMode::StatsOnly => MAX_INLINE_DIFF_BYTES,
+            DiffContentMode::Full => MAX_ON_DEMAND_DIFF_BYTES,
+        }
+    }
+}
+
+/// Objects requested per fetch when downloading missing contents of a partial clone
+const PARTIAL_CLONE_FETCH_BATCH: usize = 1000;
+
+/// Commits fetched at each step when a shallow clone lacks the history for a merge base,
+/// before falling back to the full history
+const SHALLOW_DEEPEN_STEPS: [u32; 3] = [100, 1_000, 10_000];
+
+/// First line of every Git LFS pointer file
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/";
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[ts(rename_all = "snake_case")]
@@ -137,6 +192,12 @@ pub enum DiffTarget<'p> {
         repo_path: &'p Path,
         commit_sha: &'p str,
     },
+    /// Changes introduced between two commits (e.g. by a single execution process)
+    Range {
+        repo_path: &'p Path,
+        from_commit: &'p Commit,
+        to_commit: &'p Commit,
+    },
 }
 
 impl Default for GitService {
@@ -148,7 +209,57 @@ impl Default for GitService {
 impl GitService {
     /// Create a new GitService for the given repository path
     pub fn new() -> Self {
-        Self {}
+        Self {
+            commit_signing: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Update how commits created by this service are signed. Shared by all clones.
+    pub fn set_commit_signing(&self, config: &CommitSigningConfig) {
+        *self.commit_signing.write().unwrap() = CommitSigning::from_config(config);
+    }
+
+    /// Git CLI wrapper that honors the configured commit signing
+    fn cli(&self) -> GitCli {
+        GitCli::new().with_signing(self.commit_signing.read().unwrap().clone())
+    }
+
+    /// Create a commit through libgit2, signing it when commit signing is configured.
+    /// `update_ref` behaves like the first argument of `Repository::commit`.
+    fn create_commit(
+        &self,
+        repo: &Repository,
+        update_ref: Option<&str>,
+        signature: &git2::Signature,
+        message: &str,
+        tree: &git2::Tree,
+        parents: &[&git2::Commit],
+    ) -> Result<git2::Oid, GitServiceError> {
+        let cli = self.cli();
+        if !cli.signs_commits() {
+            return Ok(repo.commit(update_ref, signature, signature, message, tree, parents)?);
+        }
+
+        let buffer = repo.commit_create_buffer(signature, signature, message, tree, parents)?;
+        let buffer = std::str::from_utf8(&buffer).map_err(|e| {
+            GitServiceError::InvalidRepository(format!("Commit buffer is not valid UTF-8: {e}"))
+        })?;
+        let gpg_signature = cli.sign_commit_buffer(buffer)?;
+        let commit_id = repo.commit_signed(buffer, &gpg_signature, None)?;
+        if let Some(update_ref) = update_ref {
+            // Resolve symbolic refs such as HEAD to the branch they point at
+            let refname = match repo.find_reference(update_ref) {
+                Ok(reference) => reference
+                    .resolve()
+                    .ok()
+                    .and_then(|r| r.name().map(|n| n.to_string()))
+                    .unwrap_or_else(|| update_ref.to_string()),
+                Err(_) => update_ref.to_string(),
+            };
+            let log_message = message.lines().next().unwrap_or_default();
+            repo.reference(&refname, commit_id, true, log_message)?;
+        }
+        Ok(commit_id)
     }
 
     /// Open the repository
@@ -196,6 +307,66 @@ impl GitService {
         }
     }
 
+    /// Name of the remote to use: `preferred` if the project configures one, otherwise the
+    /// repository's first remote
+    pub fn resolve_remote_name(
+        &self,
+        repo: &Repository,
+        preferred: Option<&str>,
+    ) -> Result<String, GitServiceError> {
+        match preferred.map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) => {
+                repo.find_remote(name).map_err(|_| {
+                    GitServiceError::InvalidRepository(format!("No '{name}' remote found"))
+                })?;
+                Ok(name.to_string())
+            }
+            None => Ok(self.default_remote_name(repo)),
+        }
+    }
+
+    pub fn list_remote_names(&self, repo_path: &Path) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        Ok(repo.remotes()?.iter().flatten().map(String::from).collect())
+    }
+
+    /// Refuse to merge or push into `branch_name` when it matches one of the project's
+    /// comma-separated protected branch `patterns`, unless `allow_protected` is set. A leading
+    /// remote name (`origin/main`) is ignored when matching.
+    pub fn ensure_branch_writable(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        patterns: Option<&str>,
+        allow_protected: bool,
+    ) -> Result<(), GitServiceError> {
+        let Some(patterns) = patterns.filter(|_| !allow_protected) else {
+            return Ok(());
+        };
+        let branch_name = branch_name
+            .strip_prefix("refs/heads/")
+            .unwrap_or(branch_name);
+        let local_name = branch_name
+            .split_once('/')
+            .filter(|(remote, _)| {
+                self.list_remote_names(repo_path)
+                    .is_ok_and(|remotes| remotes.iter().any(|name| name == remote))
+            })
+            .map(|(_, name)| name);
+        let is_protected = patterns
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .any(|pattern| {
+                branch_pattern_matches(pattern, branch_name)
+                    || local_name.is_some_and(|name| branch_pattern_matches(pattern, name))
+            });
+        if is_protected {
+            return Err(GitServiceError::ProtectedBranch(branch_name.to_string()));
+        }
+        Ok(())
+    }
+
     /// Initialize a new git repository with a main branch and initial commit
     pub fn initialize_repo_with_main_branch(
         &self,
@@ -250,10 +421,10 @@ impl GitService {
         let tree = repo.find_tree(tree_id)?;
 
         // Create initial commit on main branch
-        let _commit_id = repo.commit(
+        let _commit_id = self.create_commit(
+            repo,
             Some("refs/heads/main"),
             &signature,
-            &signature,
             "Initial commit",
             &tree,
             &[],
@@ -267,7 +438,7 @@ impl GitService {
 
     pub fn commit(&self, path: &Path, message: &str) -> Result<bool, GitServiceError> {
         // Use Git CLI to respect sparse-checkout semantics for staging and commit
-        let git = GitCli::new();
+        let git = self.cli();
         let has_changes = git
             .has_changes(path)
             .map_err(|e| GitServiceError::InvalidRepository(format!("git status failed: {e}")))?;
@@ -290,6 +461,16 @@ impl GitService {
         &self,
         target: DiffTarget,
         path_filter: Option<&[&str]>,
+    ) -> Result<Vec<Diff>, GitServiceError> {
+        self.get_diffs_with_mode(target, path_filter, DiffContentMode::Inline)
+    }
+
+    /// Like `get_diffs`, but controls how much of each file is loaded
+    pub fn get_diffs_with_mode(
+        &self,
+        target: DiffTarget,
+        path_filter: Option<&[&str]>,
+        mode: DiffContentMode,
     ) -> Result<Vec<Diff>, GitServiceError> {
         match target {
             DiffTarget::Worktree {
@@ -297,6 +478,7 @@ impl GitService {
                 base_commit,
             } => {
                 // Use Git CLI to compute diff vs base to avoid sparse false deletions
+                self.prefetch_diff_objects(worktree_path, &base_commit.as_oid().to_string(), None)?;
                 let repo = Repository::open(worktree_path)?;
                 let base_tree = repo
                     .find_commit(base_commit.as_oid())?
@@ -318,7 +500,7 @@ impl GitService {
                     })?;
                 Ok(entries
                     .into_iter()
-                    .map(|e| Self::status_entry_to_diff(&repo, &base_tree, e))
+                    .map(|e| Self::status_entry_to_diff(&repo, &base_tree, e, mode))
                     .collect())
             }
             DiffTarget::Branch {
@@ -326,6 +508,7 @@ impl GitService {
                 branch_name,
                 base_branch,
             } => {
+                self.prefetch_diff_objects(repo_path, base_branch, Some(branch_name))?;
                 let repo = self.open_repo(repo_path)?;
                 let base_tree = Self::find_branch(&repo, base_branch)?
                     .get()
@@ -356,7 +539,7 @@ impl GitService {
                 let mut find_opts = DiffFindOptions::new();
                 diff.find_similar(Some(&mut find_opts))?;
 
-                self.convert_diff_to_file_diffs(diff, &repo)
+                self.convert_diff_to_file_diffs(diff, &repo, mode)
             }
             DiffTarget::Commit {
                 repo_path,
@@ -376,6 +559,7 @@ impl GitService {
                     )
                 })?;
 
+                self.prefetch_diff_objects(repo_path, &parent.id().to_string(), Some(commit_sha))?;
                 let parent_tree = parent.tree()?;
                 let commit_tree = commit.tree()?;
 
@@ -401,7 +585,35 @@ impl GitService {
                 let mut find_opts = git2::DiffFindOptions::new();
                 diff.find_similar(Some(&mut find_opts))?;
 
-                self.convert_diff_to_file_diffs(diff, &repo)
+                self.convert_diff_to_file_diffs(diff, &repo, mode)
+            }
+            DiffTarget::Range {
+                repo_path,
+                from_commit,
+                to_commit,
+            } => {
+                let repo = self.open_repo(repo_path)?;
+                let from_tree = repo.find_commit(from_commit.as_oid())?.tree()?;
+                let to_tree = repo.find_commit(to_commit.as_oid())?.tree()?;
+
+                let mut diff_opts = DiffOptions::new();
+                diff_opts.include_typechange(true);
+
+                if let Some(paths) = path_filter {
+                    for path in paths {
+                        diff_opts.pathspec(*path);
+                    }
+                }
+
+                let mut diff =
+                    repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), Some(&mut diff_opts))?;
+
+                // Enable rename detection
+                let mut find_opts = DiffFindOptions::new();
+                find_opts.renames(true);
+                diff.find_similar(Some(&mut find_opts))?;
+
+                self.convert_diff_to_file_diffs(diff, &repo, mode)
             }
         }
     }
@@ -411,8 +623,10 @@ impl GitService {
         &self,
         diff: git2::Diff,
         repo: &Repository,
+        mode: DiffContentMode,
     ) -> Result<Vec<Diff>, GitServiceError> {
         let mut file_diffs = Vec::new();
+        let max_bytes = mode.max_bytes();
 
         let mut delta_index: usize = 0;
         diff.foreach(
@@ -424,14 +638,14 @@ impl GitService {
                 let status = delta.status();
 
                 // Decide if we should omit content due to size
-                let mut content_omitted = false;
+                let mut content_omitted = mode == DiffContentMode::StatsOnly;
                 // Check old blob size when applicable
                 if !matches!(status, Delta::Added) {
                     let oid = delta.old_file().id();
                     if !oid.is_zero()
                         && let Ok(blob) = repo.find_blob(oid)
                         && !blob.is_binary()
-                        && blob.size() > MAX_INLINE_DIFF_BYTES
+                        && blob.size() > max_bytes
                     {
                         content_omitted = true;
                     }
@@ -442,12 +656,27 @@ impl GitService {
                     if !oid.is_zero()
                         && let Ok(blob) = repo.find_blob(oid)
                         && !blob.is_binary()
-                        && blob.size() > MAX_INLINE_DIFF_BYTES
+                        && blob.size() > max_bytes
                     {
                         content_omitted = true;
                     }
                 }
 
+                // LFS-tracked files only have pointer files in git; never show those as content
+                let lfs = [
+                    (!matches!(status, Delta::Added)).then(|| delta.old_file()),
+                    (!matches!(status, Delta::Deleted)).then(|| delta.new_file()),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|file| {
+                    file.path()
+                        .is_some_and(|p| Self::is_lfs_tracked(repo, p, Some(file.id())))
+                });
+                if lfs {
+                    content_omitted = true;
+                }
+
                 // Only build old/new content if not omitted
                 let (old_path, old_content) = if matches!(status, Delta::Added) {
                     (None, None)
@@ -459,10 +688,9 @@ impl GitService {
                     if content_omitted {
                         (path_opt, None)
                     } else {
-                        let details = delta
-                            .old_file()
-                            .path()
-                            .map(|p| self.create_file_details(p, &delta.old_file().id(), repo));
+                        let details = delta.old_file().path().map(|p| {
+                            self.create_file_details(p, &delta.old_file().id(), repo, max_bytes)
+                        });
                         (
                             details.as_ref().and_then(|f| f.file_name.clone()),
                             details.and_then(|f| f.content),
@@ -480,10 +708,9 @@ impl GitService {
                     if content_omitted {
                         (path_opt, None)
                     } else {
-                        let details = delta
-                            .new_file()
-                            .path()
-                            .map(|p| self.create_file_details(p, &delta.new_file().id(), repo));
+                        let details = delta.new_file().path().map(|p| {
+                            self.create_file_details(p, &delta.new_file().id(), repo, max_bytes)
+                        });
                         (
                             details.as_ref().and_then(|f| f.file_name.clone()),
                             details.and_then(|f| f.content),
@@ -516,6 +743,7 @@ impl GitService {
                 let mut additions: Option<usize> = None;
                 let mut deletions: Option<usize> = None;
                 if content_omitted
+                    && !lfs
                     && let Ok(Some(patch)) = git2::Patch::from_diff(&diff, delta_index)
                     && let Ok((_ctx, adds, dels)) = patch.line_stats()
                 {
@@ -523,6 +751,7 @@ impl GitService {
                     deletions = Some(dels);
                 }
 
+                let hunks = Self::inline_hunks(&old_content, &new_content);
                 file_diffs.push(Diff {
                     change,
                     old_path,
@@ -530,8 +759,10 @@ impl GitService {
                     old_content,
                     new_content,
                     content_omitted,
+                    lfs,
                     additions,
                     deletions,
+                    hunks,
                 });
 
                 delta_index += 1;
@@ -553,6 +784,17 @@ impl GitService {
             .unwrap_or_default()
     }
 
+    /// Precompute hunks so clients don't have to diff the full contents themselves
+    fn inline_hunks(old: &Option<String>, new: &Option<String>) -> Option<Vec<DiffHunk>> {
+        if old.is_none() && new.is_none() {
+            return None;
+        }
+        Some(compute_diff_hunks(
+            old.as_deref().unwrap_or(""),
+            new.as_deref().unwrap_or(""),
+        ))
+    }
+
     /// Helper function to convert blob to string content
     fn blob_to_string(blob: &git2::Blob) -> Option<String> {
         if blob.is_binary() {
@@ -564,8 +806,22 @@ impl GitService {
         }
     }
 
+    /// True if the path is routed through the LFS filter by `.gitattributes`, or the
+    /// blob (when known) is an LFS pointer file
+    fn is_lfs_tracked(repo: &Repository, path: &Path, blob_id: Option<git2::Oid>) -> bool {
+        if let Ok(Some(filter)) = repo.get_attr(path, "filter", git2::AttrCheckFlags::default())
+            && filter == "lfs"
+        {
+            return true;
+        }
+        blob_id
+            .filter(|oid| !oid.is_zero())
+            .and_then(|oid| repo.find_blob(oid).ok())
+            .is_some_and(|blob| blob.content().starts_with(LFS_POINTER_PREFIX))
+    }
+
     /// Helper function to read file content from filesystem with safety guards
-    fn read_file_to_string(repo: &Repository, rel_path: &Path) -> Option<String> {
+    fn read_file_to_string(repo: &Repository, rel_path: &Path, max_bytes: usize) -> Option<String> {
         let workdir = repo.workdir()?;
         let abs_path = workdir.join(rel_path);
 
@@ -578,8 +834,8 @@ impl GitService {
             }
         };
 
-        // Size guard - skip files larger than UI inline threshold
-        if bytes.len() > MAX_INLINE_DIFF_BYTES {
+        // Size guard - skip files larger than the caller's threshold
+        if bytes.len() > max_bytes {
             tracing::debug!(
                 "Skipping large file ({}KB): {:?}",
                 bytes.len() / 1024,
@@ -610,6 +866,7 @@ impl GitService {
         path: &Path,
         blob_id: &git2::Oid,
         repo: &Repository,
+        max_bytes: usize,
     ) -> FileDiffDetails {
         let file_name = path.to_string_lossy().to_string();
 
@@ -624,11 +881,11 @@ impl GitService {
                         "Blob not found for non-zero OID, reading from filesystem: {}",
                         file_name
                     );
-                    Self::read_file_to_string(repo, path)
+                    Self::read_file_to_string(repo, path, max_bytes)
                 })
         } else {
             // For zero OIDs, check filesystem directly (covers new/untracked files)
-            Self::read_file_to_string(repo, path)
+            Self::read_file_to_string(repo, path, max_bytes)
         };
 
         FileDiffDetails {
@@ -639,7 +896,13 @@ impl GitService {
 
     /// Create Diff entries from git_cli::StatusDiffEntry
     /// New Diff format is flattened with change kind, paths, and optional contents.
-    fn status_entry_to_diff(repo: &Repository, base_tree: &git2::Tree, e: StatusDiffEntry) -> Diff {
+    fn status_entry_to_diff(
+        repo: &Repository,
+        base_tree: &git2::Tree,
+        e: StatusDiffEntry,
+        mode: DiffContentMode,
+    ) -> Diff {
+        let max_bytes = mode.max_bytes();
         // Map ChangeType to DiffChangeKind
         let mut change = match e.change {
             ChangeType::Added => DiffChangeKind::Added,
@@ -664,8 +927,17 @@ impl GitService {
             ChangeType::Unknown(_) => (e.old_path.clone(), Some(e.path.clone())),
         };
 
+        // LFS-tracked files only have pointer files in git; never show those as content
+        let lfs = old_path_opt.iter().any(|oldp| {
+            let rel = std::path::Path::new(oldp);
+            let blob_id = base_tree.get_path(rel).ok().map(|entry| entry.id());
+            Self::is_lfs_tracked(repo, rel, blob_id)
+        }) || new_path_opt
+            .iter()
+            .any(|newp| Self::is_lfs_tracked(repo, std::path::Path::new(newp), None));
+
         // Decide if we should omit content by size (either side)
-        let mut content_omitted = false;
+        let mut content_omitted = lfs;
         // Old side (from base tree)
         if let Some(ref oldp) = old_path_opt {
             let rel = std::path::Path::new(oldp);
@@ -673,7 +945,7 @@ impl GitService {
                 && entry.kind() == Some(git2::ObjectType::Blob)
                 && let Ok(blob) = repo.find_blob(entry.id())
                 && !blob.is_binary()
-                && blob.size() > MAX_INLINE_DIFF_BYTES
+                && blob.size() > max_bytes
             {
                 content_omitted = true;
             }
@@ -684,7 +956,7 @@ impl GitService {
         {
             let abs = workdir.join(newp);
             if let Ok(md) = std::fs::metadata(&abs)
-                && (md.len() as usize) > MAX_INLINE_DIFF_BYTES
+                && (md.len() as usize) > max_bytes
             {
                 content_omitted = true;
             }
@@ -711,7 +983,7 @@ impl GitService {
             // Load new content from filesystem (worktree) when available
             let new_content = if let Some(ref newp) = new_path_opt {
                 let rel = std::path::Path::new(newp);
-                Self::read_file_to_string(repo, rel)
+                Self::read_file_to_string(repo, rel, max_bytes)
             } else {
                 None
             };
@@ -727,6 +999,31 @@ impl GitService {
             change = DiffChangeKind::PermissionChange;
         }
 
+        // The CLI status has no line stats, so count them from the contents before dropping them
+        if mode == DiffContentMode::StatsOnly {
+            let (additions, deletions) = (old_content.is_some() || new_content.is_some())
+                .then(|| {
+                    compute_line_change_counts(
+                        old_content.as_deref().unwrap_or(""),
+                        new_content.as_deref().unwrap_or(""),
+                    )
+                })
+                .unzip();
+            return Diff {
+                change,
+                old_path: old_path_opt,
+                new_path: new_path_opt,
+                old_content: None,
+                new_content: None,
+                content_omitted: true,
+                lfs,
+                additions,
+                deletions,
+                hunks: None,
+            };
+        }
+
+        let hunks = Self::inline_hunks(&old_content, &new_content);
         Diff {
             change,
             old_path: old_path_opt,
@@ -734,8 +1031,10 @@ impl GitService {
             old_content,
             new_content,
             content_omitted,
+            lfs,
             additions: None,
             deletions: None,
+            hunks,
         }
     }
 
@@ -761,6 +1060,11 @@ impl GitService {
     }
 
     /// Merge changes from a task branch into the base branch.
+    #[tracing::instrument(
+        name = "git.merge",
+        skip_all,
+        fields(task_branch = task_branch_name, base_branch = base_branch_name)
+    )]
     pub fn merge_changes(
         &self,
         base_worktree_path: &Path,
@@ -772,6 +1076,7 @@ impl GitService {
         // Open the repositories
         let task_repo = self.open_repo(task_worktree_path)?;
         let base_repo = self.open_repo(base_worktree_path)?;
+        self.ensure_merge_base_available(base_worktree_path, task_branch_name, base_branch_name)?;
 
         // Check if base branch is ahead of task branch - this indicates the base has moved
         // ahead since the task was created, which should block the merge
@@ -788,7 +1093,7 @@ impl GitService {
         match self.find_checkout_path_for_branch(base_worktree_path, base_branch_name)? {
             Some(base_checkout_path) => {
                 // base branch is checked out somewhere - use CLI merge
-                let git_cli = GitCli::new();
+                let git_cli = self.cli();
 
                 // Safety check: base branch has no staged changes
                 if git_cli
@@ -836,6 +1141,11 @@ impl GitService {
                 let task_commit = task_branch.get().peel_to_commit()?;
 
                 // Create the squash commit in-memory (no checkout) and update the base branch ref
+                self.prefetch_diff_objects(
+                    task_worktree_path,
+                    base_branch_name,
+                    Some(task_branch_name),
+                )?;
                 let signature = self.signature_with_fallback(&task_repo)?;
                 let squash_commit_id = self.perform_squash_merge(
                     &task_repo,
@@ -901,6 +1211,7 @@ impl GitService {
         branch_name: &str,
         base_branch_name: &str,
     ) -> Result<Commit, GitServiceError> {
+        self.ensure_merge_base_available(repo_path, branch_name, base_branch_name)?;
         let repo = Repository::open(repo_path)?;
         let branch = Self::find_branch(&repo, branch_name)?;
         let base_branch = Self::find_branch(&repo, base_branch_name)?;
@@ -914,6 +1225,45 @@ impl GitService {
         Ok(Commit::new(oid))
     }
 
+    /// Check that `from_commit..to_commit` runs forward along `branch_name`: `to_commit` is the
+    /// branch's tip or one of its ancestors, and `from_commit` is `to_commit` or one of its
+    /// ancestors
+    pub fn check_commit_range(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        from_commit: &Commit,
+        to_commit: &Commit,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        for commit in [from_commit, to_commit] {
+            if repo.find_commit(commit.as_oid()).is_err() {
+                return Err(GitServiceError::InvalidCommitRange(format!(
+                    "commit {commit} not found"
+                )));
+            }
+        }
+        let (from, to) = (from_commit.as_oid(), to_commit.as_oid());
+        let tip = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        if to != tip && !repo.graph_descendant_of(tip, to)? {
+            return Err(GitServiceError::InvalidCommitRange(format!(
+                "{to} is not on branch {branch_name}"
+            )));
+        }
+        if from != to && !repo.graph_descendant_of(to, from)? {
+            let reason = if repo.graph_descendant_of(from, to)? {
+                format!("{from} comes after {to}")
+            } else {
+                format!("{from} is not an ancestor of {to}")
+            };
+            return Err(GitServiceError::InvalidCommitRange(reason));
+        }
+        Ok(())
+    }
+
     pub fn get_remote_branch_status(
         &self,
         repo_path: &Path,
@@ -936,6 +1286,23 @@ impl GitService {
         self.get_branch_status_inner(&repo, &branch_ref, &base_branch_ref)
     }
 
+    /// Update `branch_name` from its remote if it is a remote-tracking branch. Local branches
+    /// are left as they are.
+    pub fn fetch_base_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        github_token: Option<String>,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_ref = Self::find_branch(&repo, branch_name)?.into_reference();
+        if branch_ref.is_remote() {
+            let github_token = github_token.ok_or(GitServiceError::TokenUnavailable)?;
+            self.fetch_branch_from_remote(&repo, &github_token, &branch_ref)?;
+        }
+        Ok(())
+    }
+
     pub fn is_worktree_clean(&self, worktree_path: &Path) -> Result<bool, GitServiceError> {
         let repo = self.open_repo(worktree_path)?;
         match self.check_worktree_clean(&repo) {
@@ -1065,6 +1432,26 @@ impl GitService {
         Ok(commit.summary().unwrap_or("(no subject)").to_string())
     }
 
+    /// Read a file as it was at `commit`. Returns None if the path did not exist there.
+    pub fn get_file_at_commit(
+        &self,
+        repo_path: &Path,
+        commit: &Commit,
+        file_path: &Path,
+    ) -> Result<Option<Vec<u8>>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tree = repo.find_commit(commit.as_oid())?.tree()?;
+        let entry = match tree.get_path(file_path) {
+            Ok(entry) => entry,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if entry.kind() != Some(git2::ObjectType::Blob) {
+            return Ok(None);
+        }
+        Ok(Some(repo.find_blob(entry.id())?.content().to_vec()))
+    }
+
     /// Compare two OIDs and return (ahead, behind) counts: how many commits
     /// `from_oid` is ahead of and behind `to_oid`.
     pub fn ahead_behind_commits_by_oid(
@@ -1361,10 +1748,10 @@ impl GitService {
         let tree = repo.find_tree(tree_id)?;
 
         // Create a squash commit: use merged tree with base_commit as sole parent
-        let squash_commit_id = repo.commit(
+        let squash_commit_id = self.create_commit(
+            repo,
             None,           // Don't update any reference yet
-            signature,      // Author
-            signature,      // Committer
+            signature,      // Author and committer
             commit_message, // Custom message
             &tree,          // Merged tree content
             &[base_commit], // Single parent: base branch commit
@@ -1397,7 +1784,7 @@ impl GitService {
 
         // If a rebase is already in progress, refuse to proceed instead of
         // aborting (which might destroy user changes mid-rebase).
-        let git = GitCli::new();
+        let git = self.cli();
         if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
             return Err(GitServiceError::RebaseInProgress);
         }
@@ -1409,6 +1796,7 @@ impl GitService {
             let github_token = github_token.ok_or(GitServiceError::TokenUnavailable)?;
             self.fetch_branch_from_remote(&main_repo, &github_token, &nbr)?;
         }
+        self.ensure_merge_base_available(worktree_path, new_base_branch, task_branch)?;
 
         // Ensure identity for any commits produced by rebase
         self.ensure_cli_commit_identity(worktree_path)?;
@@ -1472,6 +1860,147 @@ impl GitService {
         Ok(final_commit.id().to_string())
     }
 
+    /// List the commits reachable from `head` but not from `base`, oldest first.
+    pub fn list_commits_between(
+        &self,
+        repo_path: &Path,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let head_oid = repo.revparse_single(head)?.peel_to_commit()?.id();
+        let base_oid = repo.revparse_single(base)?.peel_to_commit()?.id();
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        revwalk.push(head_oid)?;
+        revwalk.hide(base_oid)?;
+        revwalk
+            .map(|oid| Ok(oid?.to_string()))
+            .collect::<Result<Vec<_>, GitServiceError>>()
+    }
+
+    /// Cherry-pick `commits` (oldest first) onto the branch checked out in `worktree_path`
+    /// and return the resulting HEAD commit. If a commit does not apply cleanly the
+    /// cherry-pick is left in progress so it can be resolved or aborted.
+    pub fn cherry_pick_commits(
+        &self,
+        worktree_path: &Path,
+        commits: &[String],
+    ) -> Result<String, GitServiceError> {
+        let worktree_repo = Repository::open(worktree_path)?;
+        self.check_worktree_clean(&worktree_repo)?;
+
+        let git = self.cli();
+        if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+            return Err(GitServiceError::RebaseInProgress);
+        }
+
+        self.ensure_cli_commit_identity(worktree_path)?;
+        match git.cherry_pick(worktree_path, commits) {
+            Ok(()) => {}
+            Err(GitCliError::CommandFailed(stderr)) => {
+                let looks_like_conflict = stderr.contains("could not apply")
+                    || stderr.contains("CONFLICT")
+                    || git
+                        .is_cherry_pick_in_progress(worktree_path)
+                        .unwrap_or(false);
+                if looks_like_conflict {
+                    let conflicts = git.get_conflicted_files(worktree_path).unwrap_or_default();
+                    let files_part = if conflicts.is_empty() {
+                        "".to_string()
+                    } else {
+                        format!(" Conflicted files: {}.", conflicts.join(", "))
+                    };
+                    let msg = format!(
+                        "Cherry-pick stopped on a commit that does not apply cleanly.{files_part} Resolve conflicts and then continue or abort."
+                    );
+                    return Err(GitServiceError::MergeConflicts(msg));
+                }
+                return Err(GitServiceError::InvalidRepository(format!(
+                    "Cherry-pick failed: {}",
+                    stderr.lines().next().unwrap_or("")
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let head = worktree_repo.head()?.peel_to_commit()?;
+        Ok(head.id().to_string())
+    }
+
+    /// Export the commits on `branch_name` since it diverged from `base_branch_name` as a
+    /// `git format-patch` series that can be applied elsewhere with `git am`.
+    pub fn export_patch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<Vec<u8>, GitServiceError> {
+        let range = self.export_range(repo_path, branch_name, base_branch_name)?;
+        Ok(self.cli().format_patch(repo_path, &range)?)
+    }
+
+    /// Export the commits on `branch_name` since it diverged from `base_branch_name` as a
+    /// git bundle. The merge base is a prerequisite, so the receiving repository must
+    /// already contain it.
+    pub fn export_bundle(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<Vec<u8>, GitServiceError> {
+        let range = self.export_range(repo_path, branch_name, base_branch_name)?;
+        let dir = tempfile::tempdir()?;
+        let bundle_path = dir.path().join("export.bundle");
+        self.cli()
+            .bundle_create(repo_path, &bundle_path, &[range])?;
+        Ok(std::fs::read(&bundle_path)?)
+    }
+
+    fn export_range(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<String, GitServiceError> {
+        let base = self.get_base_commit(repo_path, branch_name, base_branch_name)?;
+        Ok(format!("{base}..refs/heads/{branch_name}"))
+    }
+
+    /// Stash uncommitted changes to tracked files in the worktree.
+    /// Returns the stash commit to reapply, or None if the worktree had nothing to stash.
+    pub fn stash_push(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let git = GitCli::new();
+        Ok(git.stash_push(worktree_path, message)?)
+    }
+
+    /// Reapply the changes stashed as `sha`. If they no longer apply cleanly the
+    /// stash entry is kept and `StashConflict` is returned.
+    pub fn stash_pop(&self, worktree_path: &Path, sha: &str) -> Result<(), GitServiceError> {
+        let git = GitCli::new();
+        match git.stash_pop(worktree_path, sha) {
+            Ok(()) => Ok(()),
+            Err(GitCliError::CommandFailed(stderr)) => {
+                let conflicts = git.get_conflicted_files(worktree_path).unwrap_or_default();
+                let files_part = if conflicts.is_empty() {
+                    "".to_string()
+                } else {
+                    format!(" Conflicted files: {}.", conflicts.join(", "))
+                };
+                let detail = stderr.lines().next().unwrap_or("").to_string();
+                Err(GitServiceError::StashConflict(format!(
+                    "Stashed changes could not be reapplied ({detail}).{files_part} They are kept in the stash as {sha}; resolve the conflicts and drop its entry with `git stash drop`."
+                )))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn find_branch_type(
         &self,
         repo_path: &Path,
@@ -1645,10 +2174,10 @@ impl GitService {
         let parent_commit = head.peel_to_commit()?;
 
         let commit_message = format!("Delete file: {file_path}");
-        let commit_id = repo.commit(
+        let commit_id = self.create_commit(
+            &repo,
             Some("HEAD"),
             &signature,
-            &signature,
             &commit_message,
             &tree,
             &[&parent_commit],
@@ -1657,6 +2186,47 @@ impl GitService {
         Ok(commit_id.to_string())
     }
 
+    /// Stage a single file and commit it on HEAD, leaving other changes in the worktree and the
+    /// index alone
+    pub fn commit_file(
+        &self,
+        worktree_path: &Path,
+        file_path: &str,
+        message: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = Repository::open(worktree_path)?;
+        let path = Path::new(file_path);
+        let parent_commit = repo.head()?.peel_to_commit()?;
+
+        // Stage only this file in the on-disk index
+        let mut index = repo.index()?;
+        index.add_path(path)?;
+        index.write()?;
+        let entry = index.get_path(path, 0).ok_or_else(|| {
+            GitServiceError::InvalidRepository(format!("{file_path} is not in the index"))
+        })?;
+
+        // Build the tree from HEAD's with just this file replaced, so changes staged for other
+        // files stay out of the commit
+        let mut head_index = git2::Index::new()?;
+        head_index.read_tree(&parent_commit.tree()?)?;
+        head_index.add(&entry)?;
+        let tree_id = head_index.write_tree_to(&repo)?;
+        let tree = repo.find_tree(tree_id)?;
+        let signature = self.signature_with_fallback(&repo)?;
+
+        let commit_id = self.create_commit(
+            &repo,
+            Some("HEAD"),
+            &signature,
+            message,
+            &tree,
+            &[&parent_commit],
+        )?;
+
+        Ok(commit_id.to_string())
+    }
+
     /// Get the default branch name for the repository
     pub fn get_default_branch_name(&self, repo_path: &Path) -> Result<String, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
@@ -1673,13 +2243,15 @@ impl GitService {
         }
     }
 
-    /// Extract GitHub owner and repo name from git repo path
+    /// Extract GitHub owner and repo name from a remote of the repository, by default its
+    /// first remote
     pub fn get_github_repo_info(
         &self,
         repo_path: &Path,
+        remote_name: Option<&str>,
     ) -> Result<GitHubRepoInfo, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
-        let remote_name = self.default_remote_name(&repo);
+        let remote_name = self.resolve_remote_name(&repo, remote_name)?;
         let remote = repo.find_remote(&remote_name).map_err(|_| {
             GitServiceError::InvalidRepository(format!("No '{remote_name}' remote found"))
         })?;
@@ -1729,17 +2301,19 @@ impl GitService {
         })
     }
 
+    /// Push `branch_name` to `remote_name` (by default the first remote) and track it there
     pub fn push_to_github(
         &self,
         worktree_path: &Path,
         branch_name: &str,
         github_token: &str,
+        remote_name: Option<&str>,
     ) -> Result<(), GitServiceError> {
         let repo = Repository::open(worktree_path)?;
         self.check_worktree_clean(&repo)?;
 
         // Get the remote
-        let remote_name = self.default_remote_name(&repo);
+        let remote_name = self.resolve_remote_name(&repo, remote_name)?;
         let remote = repo.find_remote(&remote_name)?;
 
         let remote_url = remote
@@ -1771,6 +2345,152 @@ impl GitService {
         Ok(())
     }
 
+    /// Create an annotated tag named `tag_name` on `commit_sha`, signed with the commit signing
+    /// key when `sign` is set
+    pub fn create_tag(
+        &self,
+        repo_path: &Path,
+        tag_name: &str,
+        commit_sha: &str,
+        message: &str,
+        sign: bool,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let tag_ref = format!("refs/tags/{tag_name}");
+        if !git2::Reference::is_valid_name(&tag_ref) {
+            return Err(GitServiceError::InvalidTag(format!(
+                "'{tag_name}' is not a valid tag name"
+            )));
+        }
+        if repo.find_reference(&tag_ref).is_ok() {
+            return Err(GitServiceError::InvalidTag(format!(
+                "'{tag_name}' already exists"
+            )));
+        }
+        let commit = repo.find_commit(git2::Oid::from_str(commit_sha)?)?;
+        self.cli()
+            .create_tag(repo_path, tag_name, &commit.id().to_string(), message, sign)?;
+        Ok(())
+    }
+
+    /// Fetch the branches of `remote_name` (by default the first remote) unless `commit_sha` is
+    /// already present locally, e.g. for a pull request merged on GitHub
+    pub fn ensure_commit_fetched(
+        &self,
+        repo_path: &Path,
+        commit_sha: &str,
+        github_token: Option<String>,
+        remote_name: Option<&str>,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        if repo.find_commit(git2::Oid::from_str(commit_sha)?).is_ok() {
+            return Ok(());
+        }
+        let github_token = github_token.ok_or(GitServiceError::TokenUnavailable)?;
+        let remote_name = self.resolve_remote_name(&repo, remote_name)?;
+        let remote = repo.find_remote(&remote_name)?;
+        self.fetch_all_from_remote(&repo, &github_token, &remote)
+    }
+
+    /// In a partial clone, download the file contents on both sides of `git diff <from> [<to>]`
+    /// in batches, so libgit2 can read them. Complete clones are left alone.
+    pub fn prefetch_diff_objects(
+        &self,
+        repo_path: &Path,
+        from: &str,
+        to: Option<&str>,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let Some(remote) = Self::promisor_remote(&repo) else {
+            return Ok(());
+        };
+        let git = self.cli();
+        let odb = repo.odb()?;
+        let missing: Vec<String> = git
+            .diff_blob_ids(repo_path, from, to)?
+            .into_iter()
+            .filter(|id| git2::Oid::from_str(id).is_ok_and(|oid| !odb.exists(oid)))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        tracing::debug!(
+            "Fetching {} missing objects from promisor remote '{}'",
+            missing.len(),
+            remote
+        );
+        for batch in missing.chunks(PARTIAL_CLONE_FETCH_BATCH) {
+            git.fetch_objects(repo_path, &remote, batch)?;
+        }
+        Ok(())
+    }
+
+    /// Remote that a partial clone fetches missing objects from, if this is one
+    fn promisor_remote(repo: &Repository) -> Option<String> {
+        let config = repo.config().ok()?;
+        if let Ok(remote) = config.get_string("extensions.partialclone") {
+            return Some(remote);
+        }
+        repo.remotes()
+            .ok()?
+            .iter()
+            .flatten()
+            .find(|name| {
+                config
+                    .get_bool(&format!("remote.{name}.promisor"))
+                    .unwrap_or(false)
+            })
+            .map(str::to_string)
+    }
+
+    /// In a shallow clone, fetch older history until `a` and `b` share an ancestor, so merges,
+    /// rebases and merge bases see the real fork point. Complete clones are left alone.
+    pub fn ensure_merge_base_available(
+        &self,
+        repo_path: &Path,
+        a: &str,
+        b: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let git = self.cli();
+        if !repo.is_shallow() || git.has_merge_base(repo_path, a, b) {
+            return Ok(());
+        }
+        let remote = self.default_remote_name(&repo);
+        for depth in SHALLOW_DEEPEN_STEPS {
+            tracing::debug!("Deepening shallow clone by {} commits", depth);
+            git.deepen(repo_path, &remote, Some(depth))?;
+            if git.has_merge_base(repo_path, a, b) {
+                return Ok(());
+            }
+        }
+        tracing::info!(
+            "Fetching the full history of shallow clone at {:?}",
+            repo_path
+        );
+        git.deepen(repo_path, &remote, None)?;
+        Ok(())
+    }
+
+    /// Push the tag `tag_name` to `remote_name` (by default the first remote)
+    pub fn push_tag(
+        &self,
+        repo_path: &Path,
+        tag_name: &str,
+        github_token: &str,
+        remote_name: Option<&str>,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let remote_name = self.resolve_remote_name(&repo, remote_name)?;
+        let remote = repo.find_remote(&remote_name)?;
+        let remote_url = remote
+            .url()
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
+        let https_url = self.convert_to_https_url(remote_url);
+        GitCli::new().push_tag_with_token(repo_path, &https_url, tag_name, github_token)?;
+        Ok(())
+    }
+
     pub fn convert_to_https_url(&self, url: &str) -> String {
         // Convert SSH URL to HTTPS URL if necessary
         let new_url = if url.starts_with("git@github.com:") {
@@ -1852,9 +2572,24 @@ impl GitService {
         clone_url: &str,
         target_path: &Path,
         token: Option<&str>,
+        options: &GitCloneConfig,
     ) -> Result<(), GitServiceError> {
         let git_cli = GitCli::new();
-        git_cli.clone(clone_url, target_path, token)?;
+        git_cli.clone(clone_url, target_path, token, options)?;
+        Ok(())
+    }
+
+    /// Clone a repository using git CLI, reporting git's progress to `on_progress`
+    pub fn clone_repository_with_progress(
+        &self,
+        clone_url: &str,
+        target_path: &Path,
+        token: Option<&str>,
+        options: &GitCloneConfig,
+        on_progress: impl FnMut(CloneProgress),
+    ) -> Result<(), GitServiceError> {
+        let git_cli = GitCli::new();
+        git_cli.clone_with_progress(clone_url, target_path, token, options, on_progress)?;
         Ok(())
     }
 
@@ -1989,6 +2724,31 @@ impl GitService {
     }
 }
 
+/// Match a branch name against a pattern where `*` stands for any run of characters
+fn branch_pattern_matches(pattern: &str, branch_name: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let name = branch_name.as_bytes();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` seen and the name position it is currently matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == name[n] {
+            p += 1;
+            n += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use tempfile::TempDir;
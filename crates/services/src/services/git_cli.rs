@@ -17,15 +17,19 @@
 //! network operations when useful.
 use std::{
     ffi::{OsStr, OsString},
-    path::Path,
-    process::Command,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
 };
 
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use thiserror::Error;
 use utils::shell::resolve_executable_path;
 
-use crate::services::git::Commit;
+use crate::services::{
+    config::{CloneFilter, CommitSigningConfig, CommitSigningFormat, GitCloneConfig},
+    git::Commit,
+};
 
 #[derive(Debug, Error)]
 pub enum GitCliError {
@@ -42,7 +46,58 @@ pub enum GitCliError {
 }
 
 #[derive(Clone, Default)]
-pub struct GitCli;
+pub struct GitCli {
+    signing: Option<CommitSigning>,
+}
+
+/// Key and format used to sign the commits git creates on our behalf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSigning {
+    pub format: CommitSigningFormat,
+    pub signing_key: String,
+}
+
+impl CommitSigning {
+    /// Signing settings from the user config, if signing is enabled and a key is set
+    pub fn from_config(config: &CommitSigningConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let signing_key = config.signing_key.as_deref()?.trim();
+        if signing_key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            format: config.format,
+            signing_key: signing_key.to_string(),
+        })
+    }
+
+    fn key_arg(&self) -> String {
+        match self.format {
+            CommitSigningFormat::Gpg => self.signing_key.clone(),
+            CommitSigningFormat::Ssh => utils::path::expand_tilde(&self.signing_key)
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    /// `-c` overrides that make git sign every commit it creates (commit, merge, rebase)
+    fn config_args(&self) -> Vec<OsString> {
+        let format = match self.format {
+            CommitSigningFormat::Gpg => "openpgp",
+            CommitSigningFormat::Ssh => "ssh",
+        };
+        [
+            "commit.gpgsign=true".to_string(),
+            format!("gpg.format={format}"),
+            format!("user.signingkey={}", self.key_arg()),
+        ]
+        .into_iter()
+        .flat_map(|kv| [OsString::from("-c"), OsString::from(kv)])
+        .collect()
+    }
+}
 
 /// Parsed change type from `git diff --name-status` output
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -78,9 +133,91 @@ pub struct StatusDiffOptions {
     pub path_filter: Option<Vec<String>>, // pathspecs to limit diff
 }
 
+/// One progress report of `git clone --progress`, e.g. `Receiving objects:  45% (450/1000)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloneProgress {
+    pub phase: String,
+    pub percent: u8,
+}
+
+impl CloneProgress {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let line = line.strip_prefix("remote:").unwrap_or(line).trim_start();
+        let (phase, rest) = line.split_once(':')?;
+        let (percent, _) = rest.trim_start().split_once('%')?;
+        let percent = percent.parse::<u8>().ok()?.min(100);
+        Some(Self {
+            phase: phase.trim().to_string(),
+            percent,
+        })
+    }
+}
+
 impl GitCli {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Sign commits created by this instance with the given key
+    pub fn with_signing(mut self, signing: Option<CommitSigning>) -> Self {
+        self.signing = signing;
+        self
+    }
+
+    pub fn signs_commits(&self) -> bool {
+        self.signing.is_some()
+    }
+
+    /// Produce a detached signature over a raw commit buffer, the same way git
+    /// does for `commit -S`. Used for commits created through libgit2.
+    pub fn sign_commit_buffer(&self, buffer: &str) -> Result<String, GitCliError> {
+        let Some(signing) = &self.signing else {
+            return Err(GitCliError::CommandFailed(
+                "commit signing is not configured".to_string(),
+            ));
+        };
+        let key = signing.key_arg();
+        let mut cmd = match signing.format {
+            CommitSigningFormat::Gpg => {
+                let gpg = resolve_executable_path("gpg").ok_or_else(|| {
+                    GitCliError::CommandFailed("gpg executable not found".to_string())
+                })?;
+                let mut cmd = Command::new(gpg);
+                cmd.args(["--status-fd=2", "-bsau", &key]);
+                cmd
+            }
+            CommitSigningFormat::Ssh => {
+                let ssh_keygen = resolve_executable_path("ssh-keygen").ok_or_else(|| {
+                    GitCliError::CommandFailed("ssh-keygen executable not found".to_string())
+                })?;
+                let mut cmd = Command::new(ssh_keygen);
+                cmd.args(["-Y", "sign", "-n", "git", "-f", &key]);
+                cmd
+            }
+        };
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| GitCliError::CommandFailed("failed to open signer stdin".to_string()))?
+            .write_all(buffer.as_bytes())
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        let out = child
+            .wait_with_output()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+            return Err(GitCliError::CommandFailed(format!(
+                "commit signing failed: {stderr}"
+            )));
+        }
+        Ok(String::from_utf8_lossy(&out.stdout).to_string())
     }
 
     /// Run `git -C <repo> worktree add <path> <branch>` (optionally creating the branch with -b)
@@ -109,6 +246,38 @@ impl GitCli {
         Ok(())
     }
 
+    /// True if the checkout routes any paths through the Git LFS filter
+    /// (via `.gitattributes` at the root or `.git/info/attributes`).
+    pub fn uses_lfs(&self, worktree_path: &Path) -> bool {
+        let mut attribute_files = vec![worktree_path.join(".gitattributes")];
+        if let Ok(common_dir) = self.git(worktree_path, ["rev-parse", "--git-common-dir"]) {
+            let common_dir = Path::new(common_dir.trim());
+            attribute_files.push(worktree_path.join(common_dir).join("info/attributes"));
+        }
+        attribute_files.iter().any(|path| {
+            std::fs::read_to_string(path).is_ok_and(|content| {
+                content.lines().any(|line| {
+                    !line.trim_start().starts_with('#')
+                        && line.split_whitespace().any(|attr| attr == "filter=lfs")
+                })
+            })
+        })
+    }
+
+    /// True if the `git lfs` extension is installed
+    pub fn lfs_available(&self) -> bool {
+        self.git(Path::new("."), ["lfs", "version"]).is_ok()
+    }
+
+    /// Install the LFS hooks for the repository and replace pointer files in the
+    /// worktree with their real contents (`git lfs install/fetch/checkout`).
+    pub fn lfs_pull(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["lfs", "install", "--local"])?;
+        self.git(worktree_path, ["lfs", "fetch"])?;
+        self.git(worktree_path, ["lfs", "checkout"])?;
+        Ok(())
+    }
+
     /// Run `git -C <repo> worktree remove <path>`
     pub fn worktree_remove(
         &self,
@@ -132,6 +301,72 @@ impl GitCli {
         Ok(())
     }
 
+    /// Point the repository's metadata for a worktree at the directory it was moved to
+    pub fn worktree_repair(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> Result<(), GitCliError> {
+        let args: Vec<OsString> = vec![
+            "worktree".into(),
+            "repair".into(),
+            worktree_path.as_os_str().into(),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
+    /// The repository's common git directory, shared by all of its worktrees
+    pub fn common_dir(&self, repo_path: &Path) -> Result<PathBuf, GitCliError> {
+        let out = self.git(repo_path, ["rev-parse", "--git-common-dir"])?;
+        Ok(repo_path.join(out.trim()))
+    }
+
+    /// Worktrees whose metadata `worktree_prune` would remove, as `(name, reason)` pairs
+    pub fn prunable_worktrees(
+        &self,
+        repo_path: &Path,
+    ) -> Result<Vec<(String, String)>, GitCliError> {
+        let out = self.git_output(repo_path, ["worktree", "prune", "--dry-run", "--verbose"])?;
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+            return Err(GitCliError::CommandFailed(stderr));
+        }
+        // e.g. `Removing worktrees/foo: gitdir file points to non-existent location`,
+        // which git prints on stderr
+        let text = [out.stdout, out.stderr].concat();
+        Ok(String::from_utf8_lossy(&text)
+            .lines()
+            .filter_map(|line| line.strip_prefix("Removing ")?.split_once(": "))
+            .map(|(name, reason)| {
+                let name = name.strip_prefix("worktrees/").unwrap_or(name);
+                (name.to_string(), reason.trim().to_string())
+            })
+            .collect())
+    }
+
+    /// Run `git fsck` and return the problems it reports, or nothing when the object database
+    /// and refs are intact. Warnings that do not make fsck fail are ignored.
+    pub fn fsck(&self, repo_path: &Path) -> Result<Vec<String>, GitCliError> {
+        let out = self.git_output(repo_path, ["fsck", "--no-progress", "--no-dangling"])?;
+        if out.status.success() {
+            return Ok(Vec::new());
+        }
+        let text = [out.stdout, out.stderr].concat();
+        let mut problems: Vec<String> = String::from_utf8_lossy(&text)
+            .lines()
+            .map(str::trim)
+            .filter(|line| {
+                !line.is_empty() && !line.starts_with("notice:") && !line.starts_with("warning")
+            })
+            .map(str::to_string)
+            .collect();
+        if problems.is_empty() {
+            problems.push(format!("git fsck failed ({})", out.status));
+        }
+        Ok(problems)
+    }
+
     /// Return true if there are any changes in the working tree (staged or unstaged).
     pub fn has_changes(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
         let out = self.git(worktree_path, ["status", "--porcelain"])?;
@@ -298,6 +533,26 @@ impl GitCli {
         self.git(worktree_path, ["commit", "-m", message])?;
         Ok(())
     }
+
+    /// Create an annotated tag on `target`, signed with the configured key when `sign` is set.
+    pub fn create_tag(
+        &self,
+        repo_path: &Path,
+        tag: &str,
+        target: &str,
+        message: &str,
+        sign: bool,
+    ) -> Result<(), GitCliError> {
+        if sign && !self.signs_commits() {
+            return Err(GitCliError::CommandFailed(
+                "tag signing requested but commit signing is not configured".to_string(),
+            ));
+        }
+        let kind = if sign { "-s" } else { "-a" };
+        self.git(repo_path, ["tag", kind, tag, "-m", message, target])?;
+        Ok(())
+    }
+
     /// Fetch a branch to the given remote using an HTTPS token for authentication.
     pub fn fetch_with_token_and_refspec(
         &self,
@@ -335,6 +590,28 @@ impl GitCli {
         token: &str,
     ) -> Result<(), GitCliError> {
         let refspec = format!("refs/heads/{branch}:refs/heads/{branch}");
+        self.push_refspec_with_token(repo_path, remote_url, &refspec, token)
+    }
+
+    /// Push a tag to the given remote using an HTTPS token for authentication.
+    pub fn push_tag_with_token(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        tag: &str,
+        token: &str,
+    ) -> Result<(), GitCliError> {
+        let refspec = format!("refs/tags/{tag}:refs/tags/{tag}");
+        self.push_refspec_with_token(repo_path, remote_url, &refspec, token)
+    }
+
+    fn push_refspec_with_token(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        refspec: &str,
+        token: &str,
+    ) -> Result<(), GitCliError> {
         let auth_header = self.build_auth_header(token);
         let envs = self.build_token_env(&auth_header);
 
@@ -440,11 +717,20 @@ impl GitCli {
     /// - rebase-merge (interactive rebase)
     /// - rebase-apply (am-based rebase)
     pub fn is_rebase_in_progress(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
-        let rebase_merge = self.git(worktree_path, ["rev-parse", "--git-path", "rebase-merge"])?;
-        let rebase_apply = self.git(worktree_path, ["rev-parse", "--git-path", "rebase-apply"])?;
-        let rm_exists = std::path::Path::new(rebase_merge.trim()).exists();
-        let ra_exists = std::path::Path::new(rebase_apply.trim()).exists();
-        Ok(rm_exists || ra_exists)
+        Ok(self.rebase_state_dir(worktree_path)?.is_some())
+    }
+
+    /// The rebase state directory of this worktree, if a rebase is in progress
+    pub fn rebase_state_dir(&self, worktree_path: &Path) -> Result<Option<PathBuf>, GitCliError> {
+        for name in ["rebase-merge", "rebase-apply"] {
+            let path = self.git(worktree_path, ["rev-parse", "--git-path", name])?;
+            // Relative to the worktree for the main checkout, absolute for linked worktrees
+            let path = worktree_path.join(path.trim());
+            if path.exists() {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
     }
 
     /// Return true if a merge is in progress (MERGE_HEAD exists).
@@ -494,6 +780,40 @@ impl GitCli {
         self.git(worktree_path, ["rebase", "--quit"]).map(|_| ())
     }
 
+    /// Stash uncommitted changes to tracked files. Untracked files are left in place.
+    /// Returns the stash commit, or None if there was nothing to stash. The entry is
+    /// created and stored by its SHA because `refs/stash` is shared by all worktrees of
+    /// the repository, so "the latest stash" may belong to another one.
+    pub fn stash_push(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+    ) -> Result<Option<String>, GitCliError> {
+        let sha = self.git(worktree_path, ["stash", "create", message])?;
+        let sha = sha.trim();
+        if sha.is_empty() {
+            return Ok(None);
+        }
+        self.git(worktree_path, ["stash", "store", "-m", message, sha])?;
+        self.git(worktree_path, ["reset", "--hard", "--quiet", "HEAD"])?;
+        Ok(Some(sha.to_string()))
+    }
+
+    /// Reapply the stash commit `sha` and drop its entry. If it does not apply cleanly,
+    /// git keeps the stash entry and leaves conflict markers in the worktree.
+    pub fn stash_pop(&self, worktree_path: &Path, sha: &str) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["stash", "apply", sha])?;
+        // Look the entry up only now, as other worktrees may have stashed since
+        let entries = self.git(worktree_path, ["stash", "list", "--format=%H"])?;
+        if let Some(index) = entries.lines().position(|entry| entry.trim() == sha) {
+            self.git(
+                worktree_path,
+                ["stash", "drop", &format!("stash@{{{index}}}")],
+            )?;
+        }
+        Ok(())
+    }
+
     /// Return true if there are staged changes (index differs from HEAD)
     pub fn has_staged_changes(&self, repo_path: &Path) -> Result<bool, GitCliError> {
         // `git diff --cached --quiet` returns exit code 1 if there are differences
@@ -556,6 +876,14 @@ impl GitCli {
         self.git(worktree_path, ["merge", "--abort"]).map(|_| ())
     }
 
+    /// Apply `commits` in order onto the branch checked out in `worktree_path`.
+    /// On conflicts git stops with the cherry-pick in progress.
+    pub fn cherry_pick(&self, worktree_path: &Path, commits: &[String]) -> Result<(), GitCliError> {
+        let mut args = vec!["cherry-pick".to_string()];
+        args.extend(commits.iter().cloned());
+        self.git(worktree_path, args).map(|_| ())
+    }
+
     pub fn abort_cherry_pick(&self, worktree_path: &Path) -> Result<(), GitCliError> {
         if !self.is_cherry_pick_in_progress(worktree_path)? {
             return Ok(());
@@ -571,6 +899,25 @@ impl GitCli {
         self.git(worktree_path, ["revert", "--abort"]).map(|_| ())
     }
 
+    /// Render the commits in `range` as a `git format-patch` mailbox series.
+    pub fn format_patch(&self, repo_path: &Path, range: &str) -> Result<Vec<u8>, GitCliError> {
+        self.git_bytes(repo_path, ["format-patch", "--stdout", "--binary", range])
+    }
+
+    /// Write a bundle containing `refs` (rev-list arguments, e.g. `base..refs/heads/x`)
+    /// to `bundle_path`.
+    pub fn bundle_create(
+        &self,
+        repo_path: &Path,
+        bundle_path: &Path,
+        refs: &[String],
+    ) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = vec!["bundle".into(), "create".into()];
+        args.push(bundle_path.as_os_str().to_os_string());
+        args.extend(refs.iter().map(OsString::from));
+        self.git(repo_path, args).map(|_| ())
+    }
+
     /// List files currently in a conflicted (unmerged) state in the worktree.
     pub fn get_conflicted_files(&self, worktree_path: &Path) -> Result<Vec<String>, GitCliError> {
         // `--diff-filter=U` lists paths with unresolved conflicts
@@ -591,7 +938,106 @@ impl GitCli {
         clone_url: &str,
         target_path: &Path,
         token: Option<&str>,
+        options: &GitCloneConfig,
+    ) -> Result<(), GitCliError> {
+        let mut cmd = self.clone_command(clone_url, target_path, token, options, false)?;
+
+        let out = cmd
+            .output()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+            return Err(self.classify_cli_error(stderr));
+        }
+
+        tracing::info!(
+            "Successfully cloned repository from {} to {}",
+            clone_url,
+            target_path.display()
+        );
+
+        Ok(())
+    }
+
+    /// Like [`GitCli::clone`], calling `on_progress` as git reports each phase's progress
+    pub fn clone_with_progress(
+        &self,
+        clone_url: &str,
+        target_path: &Path,
+        token: Option<&str>,
+        options: &GitCloneConfig,
+        mut on_progress: impl FnMut(CloneProgress),
     ) -> Result<(), GitCliError> {
+        let mut cmd = self.clone_command(clone_url, target_path, token, options, true)?;
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        let mut stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| GitCliError::CommandFailed("failed to open git stderr".to_string()))?;
+
+        // Progress lines are rewritten in place with `\r`; everything else is kept for errors
+        let mut messages = String::new();
+        let mut line = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = stderr
+                .read(&mut buf)
+                .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+            if n == 0 {
+                break;
+            }
+            for &byte in &buf[..n] {
+                if byte != b'\r' && byte != b'\n' {
+                    line.push(byte);
+                    continue;
+                }
+                let text = String::from_utf8_lossy(&line);
+                match CloneProgress::parse(&text) {
+                    Some(progress) => on_progress(progress),
+                    None if !text.trim().is_empty() => {
+                        messages.push_str(text.trim());
+                        messages.push('\n');
+                    }
+                    None => {}
+                }
+                line.clear();
+            }
+        }
+        messages.push_str(String::from_utf8_lossy(&line).trim());
+
+        let status = child
+            .wait()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        if !status.success() {
+            return Err(self.classify_cli_error(messages.trim().to_string()));
+        }
+
+        tracing::info!(
+            "Successfully cloned repository from {} to {}",
+            clone_url,
+            target_path.display()
+        );
+
+        Ok(())
+    }
+
+    /// `git clone` of `clone_url` into `target_path`, creating its parent directory. With
+    /// `progress`, git reports progress on stderr even though it isn't a terminal.
+    fn clone_command(
+        &self,
+        clone_url: &str,
+        target_path: &Path,
+        token: Option<&str>,
+        options: &GitCloneConfig,
+        progress: bool,
+    ) -> Result<Command, GitCliError> {
         self.ensure_available()?;
 
         // Create parent directory if it doesn't exist
@@ -619,31 +1065,113 @@ impl GitCli {
                 .arg("http.extraHeader=GIT_HTTP_EXTRAHEADER");
         }
 
-        cmd.arg("clone")
-            .arg(clone_url)
-            .arg(target_path);
+        cmd.arg("clone").args(Self::partial_clone_args(options));
+        if progress {
+            cmd.arg("--progress");
+        }
+        cmd.arg(clone_url).arg(target_path);
 
-        let out = cmd
-            .output()
-            .map_err(|e| GitCliError::CommandFailed(e.to_string()))?;
+        Ok(cmd)
+    }
 
-        if !out.status.success() {
-            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-            return Err(self.classify_cli_error(stderr));
-        }
+    /// True if `a` and `b` have a common ancestor in the local history
+    pub fn has_merge_base(&self, repo_path: &Path, a: &str, b: &str) -> bool {
+        self.git(repo_path, ["merge-base", a, b]).is_ok()
+    }
 
-        tracing::info!(
-            "Successfully cloned repository from {} to {}",
-            clone_url,
-            target_path.display()
-        );
+    /// Fetch `depth` more commits of history from `remote`, or all of it when `depth` is None
+    pub fn deepen(
+        &self,
+        repo_path: &Path,
+        remote: &str,
+        depth: Option<u32>,
+    ) -> Result<(), GitCliError> {
+        let deepen = match depth {
+            Some(depth) => format!("--deepen={depth}"),
+            None => "--unshallow".to_string(),
+        };
+        self.git(repo_path, ["fetch", "--no-tags", deepen.as_str(), remote])?;
+        Ok(())
+    }
 
+    /// Ids of the blobs on both sides of `git diff <from> [<to>]`. Only trees are compared,
+    /// so this works before the file contents have been downloaded.
+    pub fn diff_blob_ids(
+        &self,
+        repo_path: &Path,
+        from: &str,
+        to: Option<&str>,
+    ) -> Result<Vec<String>, GitCliError> {
+        let mut args = vec!["diff", "--raw", "--no-abbrev", "--no-renames", from];
+        args.extend(to);
+        let out = self.git(repo_path, args)?;
+        Ok(Self::parse_raw_blob_ids(&out))
+    }
+
+    /// Download `object_ids` from the promisor `remote` of a partial clone in one request,
+    /// the same way git lazily fetches a single missing object
+    pub fn fetch_objects(
+        &self,
+        repo_path: &Path,
+        remote: &str,
+        object_ids: &[String],
+    ) -> Result<(), GitCliError> {
+        let mut args: Vec<OsString> = [
+            "-c",
+            "fetch.negotiationAlgorithm=noop",
+            "fetch",
+            remote,
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--recurse-submodules=no",
+            "--filter=blob:none",
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect();
+        args.extend(object_ids.iter().map(OsString::from));
+        self.git(repo_path, args)?;
         Ok(())
     }
 }
 
 // Private methods
 impl GitCli {
+    fn partial_clone_args(options: &GitCloneConfig) -> Vec<OsString> {
+        let mut args = Vec::new();
+        match options.filter {
+            CloneFilter::None => {}
+            CloneFilter::Blobless => args.push(OsString::from("--filter=blob:none")),
+            CloneFilter::Treeless => args.push(OsString::from("--filter=tree:0")),
+        }
+        if let Some(depth) = options.depth {
+            // Keep every branch so attempts can start from any base branch
+            args.push(OsString::from(format!("--depth={depth}")));
+            args.push(OsString::from("--no-single-branch"));
+        }
+        args
+    }
+
+    /// Parse `git diff --raw --no-abbrev` lines (`:<mode> <mode> <sha> <sha> <status>\t<path>`),
+    /// skipping submodules and the all-zero ids of added, deleted or unstaged files
+    fn parse_raw_blob_ids(output: &str) -> Vec<String> {
+        let mut ids = Vec::new();
+        for line in output.lines().filter(|line| line.starts_with(':')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [old_mode, new_mode, old_id, new_id, ..] = fields[..] else {
+                continue;
+            };
+            let sides = [(&old_mode[1..], old_id), (new_mode, new_id)];
+            for (mode, id) in sides {
+                let is_blob = mode != "160000" && mode != "000000";
+                if is_blob && id.bytes().any(|b| b != b'0') && !ids.iter().any(|i| i == id) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids
+    }
+
     fn classify_cli_error(&self, msg: String) -> GitCliError {
         let lower = msg.to_ascii_lowercase();
         if lower.contains("authentication failed")
@@ -707,6 +1235,16 @@ impl GitCli {
     ///   `AsRef<OsStr>` so typical call sites can still pass `&str` literals or
     ///   owned `String`s without friction.
     pub fn git<I, S>(&self, repo_path: &Path, args: I) -> Result<String, GitCliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let out = self.git_bytes(repo_path, args)?;
+        Ok(String::from_utf8_lossy(&out).to_string())
+    }
+
+    /// Like `git`, but returns stdout unmodified for output that may not be UTF-8.
+    fn git_bytes<I, S>(&self, repo_path: &Path, args: I) -> Result<Vec<u8>, GitCliError>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
@@ -715,6 +1253,9 @@ impl GitCli {
         let git = resolve_executable_path("git").ok_or(GitCliError::NotAvailable)?;
         let mut cmd = Command::new(&git);
         cmd.arg("-C").arg(repo_path);
+        if let Some(signing) = &self.signing {
+            cmd.args(signing.config_args());
+        }
         for a in args {
             cmd.arg(a);
         }
@@ -725,7 +1266,24 @@ impl GitCli {
             let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
             return Err(GitCliError::CommandFailed(stderr));
         }
-        Ok(String::from_utf8_lossy(&out.stdout).to_string())
+        Ok(out.stdout)
+    }
+
+    /// Run `git -C <repo_path> <args...>` and return its output without treating a non-zero
+    /// exit as an error, for commands that report their findings through the exit status.
+    fn git_output<I, S>(&self, repo_path: &Path, args: I) -> Result<Output, GitCliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.ensure_available()?;
+        let git = resolve_executable_path("git").ok_or(GitCliError::NotAvailable)?;
+        Command::new(&git)
+            .arg("-C")
+            .arg(repo_path)
+            .args(args)
+            .output()
+            .map_err(|e| GitCliError::CommandFailed(e.to_string()))
     }
 
     /// Like `git`, but allows passing additional environment variables.
@@ -743,6 +1301,9 @@ impl GitCli {
         let git = resolve_executable_path("git").ok_or(GitCliError::NotAvailable)?;
         let mut cmd = Command::new(&git);
         cmd.arg("-C").arg(repo_path);
+        if let Some(signing) = &self.signing {
+            cmd.args(signing.config_args());
+        }
         for (k, v) in envs {
             cmd.env(k, v);
         }
@@ -0,0 +1,381 @@
+//! Two-way sync between a project's tasks and the issues of its GitHub repository.
+//!
+//! Every few minutes, each project with a [`GitHubIssueSync`] imports its open issues as tasks
+//! and settles issues that were closed or reopened on GitHub: a closed issue marks its task
+//! done, a reopened one moves a done task back to to do. The other way round, a linked task
+//! that becomes done closes its issue, and one that leaves done reopens it, as soon as the
+//! task changes. Pull requests created from an attempt of a linked task reference the issue,
+//! so GitHub shows them on it and closes it when they merge.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{
+        github_issue::{GitHubIssueLink, GitHubIssueSync},
+        project::Project,
+        task::{CreateTask, Task, TaskStatus},
+    },
+};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::{
+    config::Config,
+    git::{GitService, GitServiceError},
+    github_service::{GitHubRepoInfo, GitHubService, GitHubServiceError, IssueInfo},
+};
+
+/// Issues updated this long before the last sync are listed again, in case GitHub's clock and
+/// ours disagree
+const SINCE_OVERLAP: chrono::Duration = chrono::Duration::minutes(5);
+
+#[derive(Debug, Error)]
+pub enum GitHubIssueSyncError {
+    #[error("No GitHub token configured")]
+    NoGitHubToken,
+    #[error("Project not found")]
+    ProjectNotFound,
+    #[error(transparent)]
+    GitHub(#[from] GitHubServiceError),
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// What one sync of a project changed
+#[derive(Debug, Default, Serialize, TS)]
+pub struct GitHubIssueSyncSummary {
+    /// Open issues imported as new tasks
+    pub imported: u32,
+    /// Tasks marked done because their issue was closed
+    pub closed: u32,
+    /// Done tasks moved back to to do because their issue was reopened
+    pub reopened: u32,
+}
+
+/// Service that periodically syncs every project with a [`GitHubIssueSync`]
+pub struct GitHubIssueSyncService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl GitHubIssueSyncService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(300),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting GitHub issue sync service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.sync_all().await {
+                error!("Error syncing GitHub issues: {}", e);
+            }
+        }
+    }
+
+    async fn sync_all(&self) -> Result<(), sqlx::Error> {
+        let syncs = GitHubIssueSync::find_all(&self.db.pool).await?;
+        if syncs.is_empty() {
+            return Ok(());
+        }
+        if self.config.read().await.github.token().is_none() {
+            warn!("No GitHub token configured, cannot sync GitHub issues");
+            return Ok(());
+        }
+
+        debug!("Syncing GitHub issues of {} projects", syncs.len());
+        for sync in syncs {
+            if let Err(e) = sync_project(&self.db, &self.config, &sync).await {
+                warn!(
+                    "Failed to sync GitHub issues of project {}: {}",
+                    sync.project_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn github_service(
+    config: &Arc<RwLock<Config>>,
+) -> Result<GitHubService, GitHubIssueSyncError> {
+    let token = config
+        .read()
+        .await
+        .github
+        .token()
+        .ok_or(GitHubIssueSyncError::NoGitHubToken)?;
+    Ok(GitHubService::new(&token)?)
+}
+
+/// Import new issues of the project and settle ones closed or reopened since the last sync,
+/// recording the outcome on `sync`
+pub async fn sync_project(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    sync: &GitHubIssueSync,
+) -> Result<GitHubIssueSyncSummary, GitHubIssueSyncError> {
+    let started_at = Utc::now();
+    let result = sync_issues(db, config, sync).await;
+    let error = result.as_ref().err().map(ToString::to_string);
+    GitHubIssueSync::record_sync(
+        &db.pool,
+        sync.project_id,
+        result.is_ok().then_some(started_at),
+        error.as_deref(),
+    )
+    .await?;
+    result
+}
+
+async fn sync_issues(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    sync: &GitHubIssueSync,
+) -> Result<GitHubIssueSyncSummary, GitHubIssueSyncError> {
+    let pool = &db.pool;
+    let project = Project::find_by_id(pool, sync.project_id)
+        .await?
+        .ok_or(GitHubIssueSyncError::ProjectNotFound)?;
+    let repo_info = GitService::new()
+        .get_github_repo_info(&project.git_repo_path, project.base_remote.as_deref())?;
+    let github = github_service(config).await?;
+
+    let since = sync
+        .last_synced_at
+        .map(|synced_at| synced_at - SINCE_OVERLAP);
+    let issues = github
+        .list_issues(&repo_info, sync.label.as_deref(), since)
+        .await?;
+
+    let mut summary = GitHubIssueSyncSummary::default();
+    for issue in issues {
+        let number = issue.number as i64;
+        let Some(link) = GitHubIssueLink::find_by_issue(pool, project.id, number).await? else {
+            if !issue.closed {
+                import_issue(db, project.id, &issue).await?;
+                summary.imported += 1;
+            }
+            continue;
+        };
+        if link.closed == issue.closed {
+            continue;
+        }
+        // Recorded before the task changes, so the change isn't pushed back to GitHub
+        GitHubIssueLink::set_closed(pool, link.task_id, issue.closed).await?;
+        let Some(task) = Task::find_by_id(pool, link.task_id).await? else {
+            continue;
+        };
+        if issue.closed && task.status != TaskStatus::Done {
+            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            summary.closed += 1;
+        } else if !issue.closed && task.status == TaskStatus::Done {
+            Task::update_status(pool, task.id, TaskStatus::Todo).await?;
+            summary.reopened += 1;
+        }
+    }
+
+    if summary.imported + summary.closed + summary.reopened > 0 {
+        info!(
+            "Synced GitHub issues of {}/{} into project {}: {} imported, {} closed, {} reopened",
+            repo_info.owner,
+            repo_info.repo_name,
+            project.id,
+            summary.imported,
+            summary.closed,
+            summary.reopened
+        );
+    }
+    Ok(summary)
+}
+
+async fn import_issue(
+    db: &DBService,
+    project_id: Uuid,
+    issue: &IssueInfo,
+) -> Result<(), sqlx::Error> {
+    let task = Task::create(
+        &db.pool,
+        &CreateTask::from_title_description(
+            project_id,
+            issue.title.clone(),
+            Some(issue_description(issue.body.as_deref(), &issue.url)),
+        ),
+        Uuid::new_v4(),
+    )
+    .await?;
+    GitHubIssueLink::create(
+        &db.pool,
+        task.id,
+        project_id,
+        issue.number as i64,
+        &issue.url,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Description of a task imported from an issue: the issue's body followed by its URL
+fn issue_description(body: Option<&str>, url: &str) -> String {
+    match body.map(str::trim).filter(|body| !body.is_empty()) {
+        Some(body) => format!("{body}\n\nGitHub issue: {url}"),
+        None => format!("GitHub issue: {url}"),
+    }
+}
+
+/// Close or reopen the issue of `task` in the background if the task moved into or out of
+/// done
+pub fn spawn_push_status(db: DBService, config: Arc<RwLock<Config>>, task: Task) {
+    tokio::spawn(async move {
+        if let Err(e) = push_status(&db, &config, &task).await {
+            warn!(
+                "Failed to update the GitHub issue of task {}: {}",
+                task.id, e
+            );
+        }
+    });
+}
+
+async fn push_status(
+    db: &DBService,
+    config: &Arc<RwLock<Config>>,
+    task: &Task,
+) -> Result<(), GitHubIssueSyncError> {
+    let pool = &db.pool;
+    let Some(link) = GitHubIssueLink::find_by_task_id(pool, task.id).await? else {
+        return Ok(());
+    };
+    let done = task.status == TaskStatus::Done;
+    if link.closed == done
+        || GitHubIssueSync::find_by_project_id(pool, link.project_id)
+            .await?
+            .is_none()
+    {
+        return Ok(());
+    }
+
+    // Claimed first so that further updates of the task don't push the same change again
+    GitHubIssueLink::set_closed(pool, task.id, done).await?;
+    let result = async {
+        let repo_info = GitHubRepoInfo::from_remote_url(&link.issue_url)?;
+        let github = github_service(config).await?;
+        let number = link.issue_number as u64;
+        if done {
+            github.close_issue(&repo_info, number).await?;
+        } else {
+            github.reopen_issue(&repo_info, number).await?;
+        }
+        Ok::<_, GitHubIssueSyncError>(())
+    }
+    .await;
+    if result.is_err() {
+        GitHubIssueLink::set_closed(pool, task.id, link.closed).await?;
+    }
+    result
+}
+
+/// A pull request body that references the linked issue, so GitHub shows the pull request on
+/// the issue and closes the issue when it merges
+pub fn reference_issue(
+    body: Option<&str>,
+    link: &GitHubIssueLink,
+    pr_repo: &GitHubRepoInfo,
+) -> String {
+    let issue_repo = GitHubRepoInfo::from_remote_url(&link.issue_url).ok();
+    let reference = match issue_repo {
+        Some(issue_repo) if issue_repo != *pr_repo => format!(
+            "{}/{}#{}",
+            issue_repo.owner, issue_repo.repo_name, link.issue_number
+        ),
+        _ => format!("#{}", link.issue_number),
+    };
+    let body = body.map(str::trim_end).unwrap_or_default();
+    if body.contains(&reference) || body.contains(&link.issue_url) {
+        return body.to_string();
+    }
+    if body.is_empty() {
+        format!("Closes {reference}")
+    } else {
+        format!("{body}\n\nCloses {reference}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(issue_url: &str, issue_number: i64) -> GitHubIssueLink {
+        GitHubIssueLink {
+            task_id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            issue_number,
+            issue_url: issue_url.to_string(),
+            closed: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn repo(owner: &str, repo_name: &str) -> GitHubRepoInfo {
+        GitHubRepoInfo {
+            owner: owner.to_string(),
+            repo_name: repo_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn references_the_issue_once() {
+        let link = link("https://github.com/acme/app/issues/42", 42);
+        assert_eq!(
+            reference_issue(Some("Fixes the login loop.\n"), &link, &repo("acme", "app")),
+            "Fixes the login loop.\n\nCloses #42"
+        );
+        assert_eq!(
+            reference_issue(None, &link, &repo("acme", "app")),
+            "Closes #42"
+        );
+        assert_eq!(
+            reference_issue(Some("Closes #42"), &link, &repo("acme", "app")),
+            "Closes #42"
+        );
+        assert_eq!(
+            reference_issue(None, &link, &repo("me", "app-fork")),
+            "Closes acme/app#42"
+        );
+    }
+
+    #[test]
+    fn describes_imported_issues_with_their_url() {
+        let url = "https://github.com/acme/app/issues/7";
+        assert_eq!(
+            issue_description(Some("  Steps to reproduce\n"), url),
+            "Steps to reproduce\n\nGitHub issue: https://github.com/acme/app/issues/7"
+        );
+        assert_eq!(
+            issue_description(Some(" "), url),
+            "GitHub issue: https://github.com/acme/app/issues/7"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
 use db::models::merge::{MergeStatus, PullRequestInfo};
 use octocrab::{Octocrab, OctocrabBuilder, models::IssueState};
 use regex::Regex;
@@ -28,6 +29,15 @@ pub enum GitHubServiceError {
     #[ts(skip)]
     #[error("Branch error: {0}")]
     Branch(String),
+    #[ts(skip)]
+    #[error("Workflow error: {0}")]
+    Workflow(String),
+    #[ts(skip)]
+    #[error("Release error: {0}")]
+    Release(String),
+    #[ts(skip)]
+    #[error("Issue error: {0}")]
+    Issue(String),
     #[error("GitHub token is invalid or expired.")]
     TokenInvalid,
     #[error("Insufficient permissions")]
@@ -115,7 +125,7 @@ impl GitHubServiceError {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GitHubRepoInfo {
     pub owner: String,
     pub repo_name: String,
@@ -145,6 +155,39 @@ pub struct CreatePrRequest {
     pub body: Option<String>,
     pub head_branch: String,
     pub base_branch: String,
+    /// Repository the head branch was pushed to, when it is not the base repository (e.g. a
+    /// fork)
+    pub head_repo: Option<GitHubRepoInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateReleaseRequest {
+    /// Existing tag the release is published for
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+}
+
+/// Status of a GitHub Actions workflow run
+#[derive(Debug, Clone)]
+pub struct WorkflowRunInfo {
+    pub run_id: i64,
+    pub url: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An issue, without the pull requests GitHub lists alongside issues
+#[derive(Debug, Clone)]
+pub struct IssueInfo {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub url: String,
+    pub closed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -250,8 +293,9 @@ impl GitHubService {
             })?;
 
         // Check if the head branch exists
+        let head_repo = request.head_repo.as_ref().unwrap_or(repo_info);
         self.client
-            .repos(&repo_info.owner, &repo_info.repo_name)
+            .repos(&head_repo.owner, &head_repo.repo_name)
             .get_ref(&octocrab::params::repos::Reference::Branch(
                 request.head_branch.to_string(),
             ))
@@ -265,11 +309,17 @@ impl GitHubService {
                 other => other,
             })?;
 
+        // Branches in another repository are referred to as `owner:branch`
+        let head = match &request.head_repo {
+            Some(head_repo) => format!("{}:{}", head_repo.owner, request.head_branch),
+            None => request.head_branch.clone(),
+        };
+
         // Create the pull request
         let pr_info = self
             .client
             .pulls(&repo_info.owner, &repo_info.repo_name)
-            .create(&request.title, &request.head_branch, &request.base_branch)
+            .create(&request.title, &head, &request.base_branch)
             .body(request.body.as_deref().unwrap_or(""))
             .send()
             .await
@@ -354,13 +404,15 @@ impl GitHubService {
     }
 
     /// List all pull requests for a branch (including closed/merged)
+    /// List PRs in `repo_info` whose head is `branch_name` in `head_owner`'s repository
     pub async fn list_all_prs_for_branch(
         &self,
         repo_info: &GitHubRepoInfo,
+        head_owner: &str,
         branch_name: &str,
     ) -> Result<Vec<PullRequestInfo>, GitHubServiceError> {
         (|| async {
-            self.list_all_prs_for_branch_internal(repo_info, branch_name)
+            self.list_all_prs_for_branch_internal(repo_info, head_owner, branch_name)
                 .await
         })
         .retry(
@@ -384,6 +436,7 @@ impl GitHubService {
     async fn list_all_prs_for_branch_internal(
         &self,
         repo_info: &GitHubRepoInfo,
+        head_owner: &str,
         branch_name: &str,
     ) -> Result<Vec<PullRequestInfo>, GitHubServiceError> {
         let prs = self
@@ -391,7 +444,7 @@ impl GitHubService {
             .pulls(&repo_info.owner, &repo_info.repo_name)
             .list()
             .state(octocrab::params::State::All)
-            .head(format!("{}:{}", repo_info.owner, branch_name))
+            .head(format!("{head_owner}:{branch_name}"))
             .per_page(100)
             .send()
             .await
@@ -408,6 +461,311 @@ impl GitHubService {
         Ok(pr_infos)
     }
 
+    /// Trigger a workflow_dispatch event for a workflow on the given ref
+    pub async fn dispatch_workflow(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        workflow_file: &str,
+        git_ref: &str,
+        inputs: &serde_json::Value,
+    ) -> Result<(), GitHubServiceError> {
+        // Not retried: a dispatch that timed out may still have started a run
+        self.client
+            .actions()
+            .create_workflow_dispatch(
+                &repo_info.owner,
+                &repo_info.repo_name,
+                workflow_file,
+                git_ref,
+            )
+            .inputs(inputs.clone())
+            .send()
+            .await
+            .map_err(|err| match GitHubServiceError::from(err) {
+                GitHubServiceError::Client(source) => GitHubServiceError::Workflow(format!(
+                    "Failed to dispatch workflow '{workflow_file}' on '{git_ref}': {source}",
+                    source = format_octocrab_error(&source),
+                )),
+                other => other,
+            })?;
+
+        info!(
+            "Dispatched workflow {} on {} in {}/{}",
+            workflow_file, git_ref, repo_info.owner, repo_info.repo_name
+        );
+        Ok(())
+    }
+
+    /// Publish a release for an already pushed tag and return its URL
+    pub async fn create_release(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        request: &CreateReleaseRequest,
+    ) -> Result<String, GitHubServiceError> {
+        // Not retried: a request that timed out may still have created the release
+        let repos = self.client.repos(&repo_info.owner, &repo_info.repo_name);
+        let releases = repos.releases();
+        let mut builder = releases
+            .create(&request.tag_name)
+            .draft(request.draft)
+            .prerelease(request.prerelease);
+        if let Some(name) = &request.name {
+            builder = builder.name(name);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body);
+        }
+        let release = builder
+            .send()
+            .await
+            .map_err(|err| match GitHubServiceError::from(err) {
+                GitHubServiceError::Client(source) => GitHubServiceError::Release(format!(
+                    "Failed to create release for tag '{}': {source}",
+                    request.tag_name,
+                    source = format_octocrab_error(&source),
+                )),
+                other => other,
+            })?;
+
+        info!(
+            "Created release {} in {}/{}",
+            request.tag_name, repo_info.owner, repo_info.repo_name
+        );
+        Ok(release.html_url.to_string())
+    }
+
+    /// Close an issue; closing an issue that is already closed succeeds
+    pub async fn close_issue(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        issue_number: u64,
+    ) -> Result<(), GitHubServiceError> {
+        self.client
+            .issues(&repo_info.owner, &repo_info.repo_name)
+            .update(issue_number)
+            .state(IssueState::Closed)
+            .send()
+            .await
+            .map_err(|err| match GitHubServiceError::from(err) {
+                GitHubServiceError::Client(source) => GitHubServiceError::Issue(format!(
+                    "Failed to close issue #{issue_number}: {source}",
+                    source = format_octocrab_error(&source),
+                )),
+                other => other,
+            })?;
+
+        info!(
+            "Closed issue #{} in {}/{}",
+            issue_number, repo_info.owner, repo_info.repo_name
+        );
+        Ok(())
+    }
+
+    /// Reopen a closed issue; reopening an open issue succeeds
+    pub async fn reopen_issue(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        issue_number: u64,
+    ) -> Result<(), GitHubServiceError> {
+        self.client
+            .issues(&repo_info.owner, &repo_info.repo_name)
+            .update(issue_number)
+            .state(IssueState::Open)
+            .send()
+            .await
+            .map_err(|err| match GitHubServiceError::from(err) {
+                GitHubServiceError::Client(source) => GitHubServiceError::Issue(format!(
+                    "Failed to reopen issue #{issue_number}: {source}",
+                    source = format_octocrab_error(&source),
+                )),
+                other => other,
+            })?;
+
+        info!(
+            "Reopened issue #{} in {}/{}",
+            issue_number, repo_info.owner, repo_info.repo_name
+        );
+        Ok(())
+    }
+
+    /// List issues with `label`, if given. Without `since` only open issues are listed;
+    /// with it, issues in any state that were updated at or after `since`.
+    pub async fn list_issues(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        label: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<IssueInfo>, GitHubServiceError> {
+        const PER_PAGE: u8 = 100;
+        const MAX_PAGES: u32 = 10;
+
+        let labels: Vec<String> = label.map(str::to_string).into_iter().collect();
+        let state = if since.is_some() {
+            octocrab::params::State::All
+        } else {
+            octocrab::params::State::Open
+        };
+        let mut issues = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let items = (|| async {
+                let handler = self.client.issues(&repo_info.owner, &repo_info.repo_name);
+                let mut request = handler.list().state(state).per_page(PER_PAGE).page(page);
+                if !labels.is_empty() {
+                    request = request.labels(&labels);
+                }
+                if let Some(since) = since {
+                    request = request.since(since);
+                }
+                request.send().await.map_err(GitHubServiceError::from)
+            })
+            .retry(
+                &ExponentialBuilder::default()
+                    .with_min_delay(Duration::from_secs(1))
+                    .with_max_delay(Duration::from_secs(30))
+                    .with_max_times(3)
+                    .with_jitter(),
+            )
+            .when(|err| err.should_retry())
+            .notify(|err: &GitHubServiceError, dur: Duration| {
+                tracing::warn!(
+                    "GitHub API call failed, retrying after {:.2}s: {}",
+                    dur.as_secs_f64(),
+                    err
+                );
+            })
+            .await
+            .map_err(|err| match err {
+                GitHubServiceError::Client(source) => GitHubServiceError::Issue(format!(
+                    "Failed to list issues of {}/{}: {source}",
+                    repo_info.owner,
+                    repo_info.repo_name,
+                    source = format_octocrab_error(&source),
+                )),
+                other => other,
+            })?
+            .items;
+            let last_page = items.len() < usize::from(PER_PAGE);
+            issues.extend(
+                items
+                    .into_iter()
+                    .filter(|issue| issue.pull_request.is_none())
+                    .map(|issue| IssueInfo {
+                        number: issue.number,
+                        title: issue.title,
+                        body: issue.body,
+                        url: issue.html_url.to_string(),
+                        closed: matches!(issue.state, IssueState::Closed),
+                    }),
+            );
+            if last_page {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    /// The recent workflow_dispatch runs of a workflow on a branch created at or after `since`,
+    /// oldest first
+    pub async fn list_dispatched_runs(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        workflow_file: &str,
+        branch_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<WorkflowRunInfo>, GitHubServiceError> {
+        (|| async {
+            self.client
+                .workflows(&repo_info.owner, &repo_info.repo_name)
+                .list_runs(workflow_file)
+                .branch(branch_name)
+                .event("workflow_dispatch")
+                .per_page(20u8)
+                .send()
+                .await
+                .map(|page| {
+                    let mut runs: Vec<WorkflowRunInfo> = page
+                        .items
+                        .into_iter()
+                        .filter(|run| run.created_at >= since)
+                        .map(Self::map_workflow_run)
+                        .collect();
+                    runs.sort_by_key(|run| run.created_at);
+                    runs
+                })
+                .map_err(|err| match GitHubServiceError::from(err) {
+                    GitHubServiceError::Client(source) => GitHubServiceError::Workflow(format!(
+                        "Failed to list runs of workflow '{workflow_file}': {source}",
+                        source = format_octocrab_error(&source),
+                    )),
+                    other => other,
+                })
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|err| err.should_retry())
+        .notify(|err: &GitHubServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    /// Get the current status of a workflow run
+    pub async fn get_workflow_run(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        run_id: i64,
+    ) -> Result<WorkflowRunInfo, GitHubServiceError> {
+        (|| async {
+            self.client
+                .workflows(&repo_info.owner, &repo_info.repo_name)
+                .get((run_id as u64).into())
+                .await
+                .map(Self::map_workflow_run)
+                .map_err(|err| match GitHubServiceError::from(err) {
+                    GitHubServiceError::Client(source) => GitHubServiceError::Workflow(format!(
+                        "Failed to get workflow run {run_id}: {source}",
+                        source = format_octocrab_error(&source),
+                    )),
+                    other => other,
+                })
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|err| err.should_retry())
+        .notify(|err: &GitHubServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    fn map_workflow_run(run: octocrab::models::workflows::Run) -> WorkflowRunInfo {
+        WorkflowRunInfo {
+            run_id: run.id.into_inner() as i64,
+            url: run.html_url.to_string(),
+            status: run.status,
+            conclusion: run.conclusion,
+            created_at: run.created_at,
+        }
+    }
+
     /// List repositories for the authenticated user with pagination
     #[cfg(feature = "cloud")]
     pub async fn list_repositories(
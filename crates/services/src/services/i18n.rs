@@ -0,0 +1,354 @@
+//! Catalog of the user-facing strings the server generates itself (notifications, commit
+//! messages and error messages), in the languages the frontend supports.
+//!
+//! Requests use the configured [`UiLanguage`], or the browser's `Accept-Language` when the
+//! language is left to the browser. Messages produced outside of a request fall back to the
+//! system locale instead.
+
+use crate::services::config::UiLanguage;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Ja,
+    Ko,
+}
+
+impl Locale {
+    /// Match the primary subtag of a language tag (`es-MX`, `ja_JP.UTF-8`)
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_', '.']).next()?;
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            "ja" => Some(Locale::Ja),
+            "ko" => Some(Locale::Ko),
+            _ => None,
+        }
+    }
+
+    /// The supported language the client prefers most in an `Accept-Language` header
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut best: Option<(f32, Locale)> = None;
+        for range in header.split(',') {
+            let mut params = range.split(';');
+            let Some(locale) = params.next().and_then(Locale::from_tag) else {
+                continue;
+            };
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            // Ties keep the earlier range
+            if quality > 0.0 && best.is_none_or(|(best_quality, _)| quality > best_quality) {
+                best = Some((quality, locale));
+            }
+        }
+        best.map(|(_, locale)| locale)
+    }
+
+    /// Locale for a request: the configured language unless it is left to the browser
+    pub fn negotiate(language: &UiLanguage, accept_language: Option<&str>) -> Self {
+        Self::from_ui_language(language)
+            .or_else(|| accept_language.and_then(Self::from_accept_language))
+            .unwrap_or_default()
+    }
+
+    /// Locale for messages produced outside of a request, such as notifications and commits:
+    /// the configured language, or the system's when it is left to the browser
+    pub fn from_config(language: &UiLanguage) -> Self {
+        Self::from_ui_language(language)
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .into_iter()
+                    .filter_map(|var| std::env::var(var).ok())
+                    .find(|value| !value.is_empty())
+                    .and_then(|value| Self::from_tag(&value))
+            })
+            .unwrap_or_default()
+    }
+
+    fn from_ui_language(language: &UiLanguage) -> Option<Self> {
+        match language {
+            UiLanguage::Browser => None,
+            UiLanguage::En => Some(Locale::En),
+            UiLanguage::Es => Some(Locale::Es),
+            UiLanguage::Ja => Some(Locale::Ja),
+            UiLanguage::Ko => Some(Locale::Ko),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Message<'a> {
+    TaskCompleteTitle {
+        task: &'a str,
+    },
+    TaskCompleted {
+        task: &'a str,
+        branch: &'a str,
+        executor: &'a str,
+    },
+    TaskFailed {
+        task: &'a str,
+        branch: &'a str,
+        executor: &'a str,
+    },
+    TaskCancelled {
+        task: &'a str,
+        branch: &'a str,
+        executor: &'a str,
+    },
+    AgentCommit {
+        attempt_id: &'a str,
+    },
+    CleanupScriptCommit {
+        attempt_id: &'a str,
+    },
+    ImageInvalidFormat,
+    /// Sizes in megabytes
+    ImageTooLarge {
+        size: f64,
+        max: f64,
+    },
+    ImageNotFound,
+    ImagesNotPlaced {
+        names: &'a str,
+    },
+    ImageProcessingFailed,
+    UploadFailed,
+    ProtectedBranch {
+        branch: &'a str,
+    },
+    RebaseInProgress,
+    TaskBlocked {
+        tasks: &'a str,
+    },
+    /// A work-in-progress limit is reached
+    WipLimitReached {
+        status: &'a str,
+        count: i64,
+        max: i64,
+    },
+    /// A client called a rate-limited route too often
+    RateLimited {
+        retry_after_secs: u64,
+    },
+}
+
+impl Message<'_> {
+    pub fn localize(&self, locale: Locale) -> String {
+        use Locale::*;
+        match *self {
+            Message::TaskCompleteTitle { task } => match locale {
+                En => format!("Task Complete: {task}"),
+                Es => format!("Tarea completada: {task}"),
+                Ja => format!("タスク完了: {task}"),
+                Ko => format!("작업 완료: {task}"),
+            },
+            Message::TaskCompleted {
+                task,
+                branch,
+                executor,
+            } => match locale {
+                En => format!(
+                    "✅ '{task}' completed successfully\nBranch: {branch:?}\nExecutor: {executor}"
+                ),
+                Es => format!(
+                    "✅ '{task}' se completó correctamente\nRama: {branch:?}\nEjecutor: {executor}"
+                ),
+                Ja => format!(
+                    "✅ '{task}' が正常に完了しました\nブランチ: {branch:?}\nエグゼキューター: {executor}"
+                ),
+                Ko => format!(
+                    "✅ '{task}' 작업이 성공적으로 완료되었습니다\n브랜치: {branch:?}\n실행기: {executor}"
+                ),
+            },
+            Message::TaskFailed {
+                task,
+                branch,
+                executor,
+            } => match locale {
+                En => {
+                    format!("❌ '{task}' execution failed\nBranch: {branch:?}\nExecutor: {executor}")
+                }
+                Es => format!(
+                    "❌ La ejecución de '{task}' falló\nRama: {branch:?}\nEjecutor: {executor}"
+                ),
+                Ja => format!(
+                    "❌ '{task}' の実行に失敗しました\nブランチ: {branch:?}\nエグゼキューター: {executor}"
+                ),
+                Ko => format!(
+                    "❌ '{task}' 실행에 실패했습니다\n브랜치: {branch:?}\n실행기: {executor}"
+                ),
+            },
+            Message::TaskCancelled {
+                task,
+                branch,
+                executor,
+            } => match locale {
+                En => format!(
+                    "🛑 '{task}' execution cancelled by user\nBranch: {branch:?}\nExecutor: {executor}"
+                ),
+                Es => format!(
+                    "🛑 El usuario canceló la ejecución de '{task}'\nRama: {branch:?}\nEjecutor: {executor}"
+                ),
+                Ja => format!(
+                    "🛑 '{task}' の実行はユーザーによってキャンセルされました\nブランチ: {branch:?}\nエグゼキューター: {executor}"
+                ),
+                Ko => format!(
+                    "🛑 사용자가 '{task}' 실행을 취소했습니다\n브랜치: {branch:?}\n실행기: {executor}"
+                ),
+            },
+            Message::AgentCommit { attempt_id } => match locale {
+                En => format!("Commit changes from coding agent for task attempt {attempt_id}"),
+                Es => format!(
+                    "Confirmar cambios del agente de código para el intento de tarea {attempt_id}"
+                ),
+                Ja => format!("タスク試行 {attempt_id} のコーディングエージェントによる変更をコミット"),
+                Ko => format!("작업 시도 {attempt_id}의 코딩 에이전트 변경 사항 커밋"),
+            },
+            Message::CleanupScriptCommit { attempt_id } => match locale {
+                En => format!("Cleanup script changes for task attempt {attempt_id}"),
+                Es => format!(
+                    "Cambios del script de limpieza para el intento de tarea {attempt_id}"
+                ),
+                Ja => format!("タスク試行 {attempt_id} のクリーンアップスクリプトによる変更"),
+                Ko => format!("작업 시도 {attempt_id}의 정리 스크립트 변경 사항"),
+            },
+            Message::ImageInvalidFormat => match locale {
+                En => "This file type is not supported. Please upload an image file (PNG, JPG, GIF, WebP, or BMP).",
+                Es => "Este tipo de archivo no es compatible. Sube un archivo de imagen (PNG, JPG, GIF, WebP o BMP).",
+                Ja => "このファイル形式はサポートされていません。画像ファイル（PNG、JPG、GIF、WebP、BMP）をアップロードしてください。",
+                Ko => "지원되지 않는 파일 형식입니다. 이미지 파일(PNG, JPG, GIF, WebP 또는 BMP)을 업로드하세요.",
+            }
+            .to_string(),
+            Message::ImageTooLarge { size, max } => match locale {
+                En => format!(
+                    "This image is too large ({size:.1} MB). Maximum file size is {max:.1} MB."
+                ),
+                Es => format!(
+                    "Esta imagen es demasiado grande ({size:.1} MB). El tamaño máximo es {max:.1} MB."
+                ),
+                Ja => format!(
+                    "画像が大きすぎます（{size:.1} MB）。最大ファイルサイズは {max:.1} MB です。"
+                ),
+                Ko => format!(
+                    "이미지가 너무 큽니다({size:.1} MB). 최대 파일 크기는 {max:.1} MB입니다."
+                ),
+            },
+            Message::ImageNotFound => match locale {
+                En => "Image not found.",
+                Es => "Imagen no encontrada.",
+                Ja => "画像が見つかりません。",
+                Ko => "이미지를 찾을 수 없습니다.",
+            }
+            .to_string(),
+            Message::ImagesNotPlaced { names } => match locale {
+                En => format!(
+                    "Every attached image must be placed in the prompt. Add a reference or an [image:<id>] marker for: {names}"
+                ),
+                Es => format!(
+                    "Cada imagen adjunta debe colocarse en el prompt. Añade una referencia o un marcador [image:<id>] para: {names}"
+                ),
+                Ja => format!(
+                    "添付したすべての画像をプロンプト内に配置する必要があります。次の画像の参照または [image:<id>] マーカーを追加してください: {names}"
+                ),
+                Ko => format!(
+                    "첨부한 모든 이미지를 프롬프트에 배치해야 합니다. 다음 이미지에 대한 참조 또는 [image:<id>] 마커를 추가하세요: {names}"
+                ),
+            },
+            Message::ImageProcessingFailed => match locale {
+                En => "Failed to process image. Please try again.",
+                Es => "No se pudo procesar la imagen. Inténtalo de nuevo.",
+                Ja => "画像を処理できませんでした。もう一度お試しください。",
+                Ko => "이미지를 처리하지 못했습니다. 다시 시도하세요.",
+            }
+            .to_string(),
+            Message::UploadFailed => match locale {
+                En => "Failed to upload file. Please ensure the file is valid and try again.",
+                Es => "No se pudo subir el archivo. Comprueba que el archivo sea válido e inténtalo de nuevo.",
+                Ja => "ファイルをアップロードできませんでした。ファイルが有効であることを確認して、もう一度お試しください。",
+                Ko => "파일을 업로드하지 못했습니다. 파일이 올바른지 확인한 후 다시 시도하세요.",
+            }
+            .to_string(),
+            Message::ProtectedBranch { branch } => match locale {
+                En => format!(
+                    "'{branch}' is a protected branch. Confirm the override to write to it anyway."
+                ),
+                Es => format!(
+                    "'{branch}' es una rama protegida. Confirma la anulación para escribir en ella de todos modos."
+                ),
+                Ja => format!(
+                    "'{branch}' は保護されたブランチです。それでも書き込むには上書きを確認してください。"
+                ),
+                Ko => format!(
+                    "'{branch}'은(는) 보호된 브랜치입니다. 그래도 쓰려면 재정의를 확인하세요."
+                ),
+            },
+            Message::RebaseInProgress => match locale {
+                En => "A rebase is already in progress. Resolve conflicts or abort the rebase, then retry.",
+                Es => "Ya hay un rebase en curso. Resuelve los conflictos o cancela el rebase y vuelve a intentarlo.",
+                Ja => "リベースがすでに進行中です。競合を解決するかリベースを中止してから、もう一度お試しください。",
+                Ko => "이미 리베이스가 진행 중입니다. 충돌을 해결하거나 리베이스를 중단한 후 다시 시도하세요.",
+            }
+            .to_string(),
+            Message::TaskBlocked { tasks } => match locale {
+                En => format!("This task is blocked until these tasks are done: {tasks}"),
+                Es => format!(
+                    "Esta tarea está bloqueada hasta que se completen estas tareas: {tasks}"
+                ),
+                Ja => format!("次のタスクが完了するまで、このタスクはブロックされています: {tasks}"),
+                Ko => format!("다음 작업이 완료될 때까지 이 작업은 차단됩니다: {tasks}"),
+            },
+            Message::WipLimitReached { status, count, max } => match locale {
+                En => format!("{count} tasks are already {status}; the limit is {max}"),
+                Es => format!("Ya hay {count} tareas en {status}; el límite es {max}"),
+                Ja => format!("{status} のタスクはすでに {count} 件です（上限 {max} 件）"),
+                Ko => format!("{status} 상태의 작업이 이미 {count}개입니다 (한도 {max}개)"),
+            },
+            Message::RateLimited { retry_after_secs } => match locale {
+                En => format!("Too many requests. Try again in {retry_after_secs}s."),
+                Es => format!(
+                    "Demasiadas solicitudes. Vuelve a intentarlo en {retry_after_secs} s."
+                ),
+                Ja => format!(
+                    "リクエストが多すぎます。{retry_after_secs} 秒後にもう一度お試しください。"
+                ),
+                Ko => format!("요청이 너무 많습니다. {retry_after_secs}초 후에 다시 시도하세요."),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_preferred_supported_language() {
+        assert_eq!(
+            Locale::from_accept_language("fr-FR,fr;q=0.9,ja;q=0.8,en;q=0.7"),
+            Some(Locale::Ja)
+        );
+        assert_eq!(
+            Locale::from_accept_language("en;q=0.5, ko-KR"),
+            Some(Locale::Ko)
+        );
+        assert_eq!(Locale::from_accept_language("es;q=0, de"), None);
+        assert_eq!(Locale::from_tag("ja_JP.UTF-8"), Some(Locale::Ja));
+        assert_eq!(Locale::from_tag("C"), None);
+    }
+
+    #[test]
+    fn configured_language_overrides_the_browser() {
+        assert_eq!(Locale::negotiate(&UiLanguage::Es, Some("ko")), Locale::Es);
+        assert_eq!(
+            Locale::negotiate(&UiLanguage::Browser, Some("ko")),
+            Locale::Ko
+        );
+        assert_eq!(Locale::negotiate(&UiLanguage::Browser, None), Locale::En);
+    }
+}
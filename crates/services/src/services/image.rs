@@ -1,4 +1,5 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
 };
@@ -9,6 +10,20 @@ use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+lazy_static::lazy_static! {
+    /// `[image:<id>]`, placing an attached image in a prompt
+    static ref IMAGE_MARKER: Regex = Regex::new(
+        r"\[image:([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})\]"
+    )
+    .unwrap();
+    /// Markdown image whose path is in the worktree's images directory
+    static ref IMAGE_REFERENCE: Regex = Regex::new(&format!(
+        r#"!\[([^\]]*)\]\(({}/([^)\s]+))\)"#,
+        regex::escape(utils::path::VIBE_IMAGES_DIR)
+    ))
+    .unwrap();
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ImageError {
     #[error("IO error: {0}")]
@@ -28,6 +43,9 @@ pub enum ImageError {
 
     #[error("Failed to build response: {0}")]
     ResponseBuildError(String),
+
+    #[error("Attached images are not placed in the prompt: {}", .0.join(", "))]
+    NotPlaced(Vec<String>),
 }
 
 #[derive(Clone)]
@@ -38,8 +56,9 @@ pub struct ImageService {
 }
 
 impl ImageService {
-    pub fn new(pool: SqlitePool) -> Result<Self, ImageError> {
-        let cache_dir = utils::cache_dir().join("images");
+    /// Store images under `cache_dir`. Image paths in the database are relative to it, so
+    /// the directory can be moved without touching them.
+    pub fn new(pool: SqlitePool, cache_dir: PathBuf) -> Result<Self, ImageError> {
         fs::create_dir_all(&cache_dir)?;
         Ok(Self {
             cache_dir,
@@ -177,12 +196,7 @@ impl ImageService {
         worktree_path: &Path,
         image_ids: &[Uuid],
     ) -> Result<(), ImageError> {
-        let mut images = Vec::new();
-        for id in image_ids {
-            if let Some(image) = Image::find_by_id(&self.pool, *id).await? {
-                images.push(image);
-            }
-        }
+        let images = self.find_images(image_ids).await?;
         self.copy_images(worktree_path, images)
     }
 
@@ -217,20 +231,170 @@ impl ImageService {
         Ok(())
     }
 
+    /// Check that every attached image is placed in the prompt, without copying anything
+    pub async fn check_images_placed(
+        &self,
+        prompt: &str,
+        image_ids: &[Uuid],
+        captions: &HashMap<Uuid, String>,
+    ) -> Result<(), ImageError> {
+        let images = self.find_images(image_ids).await?;
+        Self::place_images(prompt, &images, captions).map(|_| ())
+    }
+
+    /// Place the attached images in the prompt, copy them into the worktree and point the
+    /// prompt's references at the copies
+    pub async fn prepare_prompt_images(
+        &self,
+        prompt: &str,
+        image_ids: &[Uuid],
+        captions: &HashMap<Uuid, String>,
+        worktree_path: &Path,
+    ) -> Result<String, ImageError> {
+        let images = self.find_images(image_ids).await?;
+        let prompt = Self::place_images(prompt, &images, captions)?;
+        self.copy_images(worktree_path, images)?;
+        Ok(Self::canonicalise_image_paths(&prompt, worktree_path))
+    }
+
+    async fn find_images(&self, image_ids: &[Uuid]) -> Result<Vec<Image>, ImageError> {
+        let mut images = Vec::new();
+        for id in image_ids {
+            if let Some(image) = Image::find_by_id(&self.pool, *id).await? {
+                images.push(image);
+            }
+        }
+        Ok(images)
+    }
+
+    /// Replace `[image:<id>]` markers with markdown references to the images, and give each
+    /// image's references its caption as alt text, falling back to the file name. Fails if an
+    /// image is neither marked nor referenced, so the agent never gets an image without
+    /// knowing where it belongs.
+    pub fn place_images(
+        prompt: &str,
+        images: &[Image],
+        captions: &HashMap<Uuid, String>,
+    ) -> Result<String, ImageError> {
+        let mut placed = HashSet::new();
+        let alt_text = |image: &Image| {
+            captions
+                .get(&image.id)
+                .map(|caption| caption_alt_text(caption))
+                .filter(|caption| !caption.is_empty())
+        };
+
+        let prompt = IMAGE_MARKER.replace_all(prompt, |caps: &Captures| {
+            let image = Uuid::parse_str(&caps[1])
+                .ok()
+                .and_then(|id| images.iter().find(|image| image.id == id));
+            match image {
+                Some(image) => {
+                    placed.insert(image.id);
+                    let alt = alt_text(image).unwrap_or_else(|| image.original_name.clone());
+                    format!(
+                        "![{alt}]({}/{})",
+                        utils::path::VIBE_IMAGES_DIR,
+                        image.file_path
+                    )
+                }
+                None => caps[0].to_string(),
+            }
+        });
+        let prompt = IMAGE_REFERENCE.replace_all(&prompt, |caps: &Captures| {
+            let Some(image) = images.iter().find(|image| image.file_path == caps[3]) else {
+                return caps[0].to_string();
+            };
+            placed.insert(image.id);
+            match alt_text(image) {
+                Some(alt) => format!("![{alt}]({})", &caps[2]),
+                None => caps[0].to_string(),
+            }
+        });
+
+        let unplaced: Vec<String> = images
+            .iter()
+            .filter(|image| !placed.contains(&image.id))
+            .map(|image| image.original_name.clone())
+            .collect();
+        if !unplaced.is_empty() {
+            return Err(ImageError::NotPlaced(unplaced));
+        }
+        Ok(prompt.into_owned())
+    }
+
     pub fn canonicalise_image_paths(prompt: &str, worktree_path: &Path) -> String {
-        let pattern = format!(
-            r#"!\[([^\]]*)\]\(({}/[^)\s]+)\)"#,
-            regex::escape(utils::path::VIBE_IMAGES_DIR)
+        IMAGE_REFERENCE
+            .replace_all(prompt, |caps: &Captures| {
+                let alt = &caps[1];
+                let rel = &caps[2];
+                let abs = worktree_path.join(rel);
+                let abs = abs.to_string_lossy().replace('\\', "/");
+                format!("![{alt}]({abs})")
+            })
+            .into_owned()
+    }
+}
+
+/// A caption on one line without brackets, so it can't end the markdown alt text early
+fn caption_alt_text(caption: &str) -> String {
+    caption
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(['[', ']'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn image(original_name: &str) -> Image {
+        let id = Uuid::new_v4();
+        Image {
+            id,
+            file_path: format!("{id}.png"),
+            original_name: original_name.to_string(),
+            mime_type: Some("image/png".to_string()),
+            size_bytes: 1,
+            hash: id.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn places_marked_images_with_their_captions() {
+        let login = image("login.png");
+        let error = image("error.png");
+        let captions = HashMap::from([(login.id, "The login\nform [broken]".to_string())]);
+        let prompt = format!(
+            "Before:\n[image:{}]\nAfter:\n![error.png](.vibe-images/{})",
+            login.id, error.file_path
         );
-        let re = Regex::new(&pattern).unwrap();
-
-        re.replace_all(prompt, |caps: &Captures| {
-            let alt = &caps[1];
-            let rel = &caps[2];
-            let abs = worktree_path.join(rel);
-            let abs = abs.to_string_lossy().replace('\\', "/");
-            format!("![{alt}]({abs})")
-        })
-        .into_owned()
+
+        let placed =
+            ImageService::place_images(&prompt, &[login.clone(), error.clone()], &captions)
+                .unwrap();
+        assert_eq!(
+            placed,
+            format!(
+                "Before:\n![The login form broken](.vibe-images/{})\nAfter:\n![error.png](.vibe-images/{})",
+                login.file_path, error.file_path
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_attached_images_missing_from_the_prompt() {
+        let login = image("login.png");
+        let error = image("error.png");
+        let prompt = format!("See [image:{}] and [image:{}]", login.id, Uuid::new_v4());
+
+        let err =
+            ImageService::place_images(&prompt, &[login, error], &HashMap::new()).unwrap_err();
+        assert!(matches!(err, ImageError::NotPlaced(names) if names == ["error.png"]));
     }
 }
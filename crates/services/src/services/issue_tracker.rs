@@ -0,0 +1,504 @@
+//! Syncs a project's tasks with the issues of an external tracker.
+//!
+//! A project connects to one tracker — a Linear team or a Jira project — through an
+//! [`IssueTracker`] implementation. Every few minutes its issues that aren't finished are imported
+//! as tasks that record the issue's key, and statuses are settled both ways: a status changed on
+//! the tracker moves the task, and one changed on the board moves the issue. When both changed
+//! since the last sync, the tracker wins.
+
+pub mod jira;
+pub mod linear;
+
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        issue_tracker::{
+            IssueTrackerConnection, IssueTrackerKind, IssueTrackerLink,
+            UpsertIssueTrackerConnection,
+        },
+        task::{CreateTask, Task, TaskStatus},
+    },
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use self::{jira::JiraTracker, linear::LinearTracker};
+use crate::services::project_env::{ProjectEnvError, SecretCipher};
+
+/// Issues updated this long before the last sync are listed again, in case the tracker's clock
+/// and ours disagree
+const SINCE_OVERLAP: chrono::Duration = chrono::Duration::minutes(5);
+
+#[derive(Debug, Error)]
+pub enum IssueTrackerError {
+    #[error("{0}")]
+    InvalidConnection(String),
+    #[error("{tracker} API error: {message}")]
+    Api {
+        tracker: &'static str,
+        message: String,
+    },
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Secret(#[from] ProjectEnvError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Where an issue is in its workflow, as far as the board is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueState {
+    Todo,
+    InProgress,
+    Done,
+    Cancelled,
+}
+
+impl IssueState {
+    pub fn of(status: &TaskStatus) -> Self {
+        match status {
+            TaskStatus::Todo => IssueState::Todo,
+            TaskStatus::InProgress | TaskStatus::InReview => IssueState::InProgress,
+            TaskStatus::Done => IssueState::Done,
+            TaskStatus::Cancelled => IssueState::Cancelled,
+        }
+    }
+
+    fn task_status(self) -> TaskStatus {
+        match self {
+            IssueState::Todo => TaskStatus::Todo,
+            IssueState::InProgress => TaskStatus::InProgress,
+            IssueState::Done => TaskStatus::Done,
+            IssueState::Cancelled => TaskStatus::Cancelled,
+        }
+    }
+
+    fn is_finished(self) -> bool {
+        matches!(self, IssueState::Done | IssueState::Cancelled)
+    }
+}
+
+/// An issue as listed by a tracker
+#[derive(Debug, Clone)]
+pub struct ExternalIssue {
+    /// Human-readable key, e.g. `ENG-42`
+    pub key: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub state: IssueState,
+}
+
+#[async_trait]
+pub trait IssueTracker: Send + Sync {
+    /// Name shown in errors and imported task descriptions
+    fn name(&self) -> &'static str;
+
+    /// Issues in scope updated since `since`, or every unfinished one without it
+    async fn list_issues(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ExternalIssue>, IssueTrackerError>;
+
+    /// Move the issue with `key` into a workflow state of `state`
+    async fn set_state(&self, key: &str, state: IssueState) -> Result<(), IssueTrackerError>;
+
+    /// The state listed for an issue after it was moved to `state`, for trackers that can't
+    /// tell some states apart
+    fn listed_state(&self, state: IssueState) -> IssueState {
+        state
+    }
+}
+
+/// What one sync of a project changed
+#[derive(Debug, Default, Serialize, TS)]
+pub struct IssueTrackerSyncSummary {
+    /// Issues imported as new tasks
+    pub imported: u32,
+    /// Tasks moved because their issue changed status
+    pub updated: u32,
+    /// Issues moved because their task changed status
+    pub pushed: u32,
+}
+
+/// Service that periodically syncs every project with an [`IssueTrackerConnection`]
+pub struct IssueTrackerSyncService {
+    db: DBService,
+    poll_interval: Duration,
+}
+
+impl IssueTrackerSyncService {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            poll_interval: Duration::from_secs(300),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting issue tracker sync service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.sync_all().await {
+                error!("Error syncing issue trackers: {}", e);
+            }
+        }
+    }
+
+    async fn sync_all(&self) -> Result<(), sqlx::Error> {
+        let connections = IssueTrackerConnection::find_all(&self.db.pool).await?;
+        if connections.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Syncing issue trackers of {} projects", connections.len());
+        for connection in connections {
+            if let Err(e) = sync_project(&self.db, &connection).await {
+                warn!(
+                    "Failed to sync the issue tracker of project {}: {}",
+                    connection.project_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn token_context(project_id: Uuid) -> String {
+    format!("{project_id}/issue-tracker")
+}
+
+/// The tracker a connection points at, with its API token decrypted
+pub fn connect(
+    connection: &IssueTrackerConnection,
+) -> Result<Box<dyn IssueTracker>, IssueTrackerError> {
+    let token = SecretCipher::global()?
+        .decrypt(&connection.api_token, &token_context(connection.project_id))?;
+    let tracker: Box<dyn IssueTracker> = match connection.kind {
+        IssueTrackerKind::Linear => Box::new(LinearTracker::new(token, connection.scope.clone())?),
+        IssueTrackerKind::Jira => {
+            let (Some(base_url), Some(email)) = (&connection.base_url, &connection.email) else {
+                return Err(IssueTrackerError::InvalidConnection(
+                    "Jira connections need a site URL and an email".to_string(),
+                ));
+            };
+            Box::new(JiraTracker::new(
+                base_url.clone(),
+                email.clone(),
+                token,
+                connection.scope.clone(),
+            )?)
+        }
+    };
+    Ok(tracker)
+}
+
+/// Trim the settings and check the ones the tracker needs are there. The settings another
+/// tracker uses are dropped.
+fn normalize(
+    data: &UpsertIssueTrackerConnection,
+) -> Result<UpsertIssueTrackerConnection, IssueTrackerError> {
+    let trimmed = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let scope = data.scope.trim().to_string();
+    if scope.is_empty() {
+        return Err(IssueTrackerError::InvalidConnection(
+            match data.kind {
+                IssueTrackerKind::Linear => "A Linear team key is required",
+                IssueTrackerKind::Jira => "A Jira project key is required",
+            }
+            .to_string(),
+        ));
+    }
+
+    let (base_url, email) = match data.kind {
+        IssueTrackerKind::Linear => (None, None),
+        IssueTrackerKind::Jira => {
+            let base_url = trimmed(&data.base_url)
+                .map(|url| url.trim_end_matches('/').to_string())
+                .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+                .ok_or_else(|| {
+                    IssueTrackerError::InvalidConnection(
+                        "A Jira site URL starting with https:// is required".to_string(),
+                    )
+                })?;
+            let email = trimmed(&data.email).ok_or_else(|| {
+                IssueTrackerError::InvalidConnection(
+                    "The email of the Jira account is required".to_string(),
+                )
+            })?;
+            (Some(base_url), Some(email))
+        }
+    };
+
+    Ok(UpsertIssueTrackerConnection {
+        kind: data.kind,
+        base_url,
+        scope,
+        email,
+        api_token: trimmed(&data.api_token),
+    })
+}
+
+/// Connect the project to a tracker, encrypting the API token. Without a token the current
+/// one is kept, as long as the project stays on the same kind of tracker.
+pub async fn save_connection(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    data: &UpsertIssueTrackerConnection,
+) -> Result<IssueTrackerConnection, IssueTrackerError> {
+    let data = normalize(data)?;
+    let api_token = match &data.api_token {
+        Some(token) => SecretCipher::global()?.encrypt(token, &token_context(project_id))?,
+        None => IssueTrackerConnection::find_by_project_id(pool, project_id)
+            .await?
+            .filter(|current| current.kind == data.kind)
+            .map(|current| current.api_token)
+            .ok_or_else(|| {
+                IssueTrackerError::InvalidConnection("An API token is required".to_string())
+            })?,
+    };
+    Ok(IssueTrackerConnection::upsert(
+        pool,
+        project_id,
+        data.kind,
+        data.base_url.as_deref(),
+        &data.scope,
+        data.email.as_deref(),
+        &api_token,
+    )
+    .await?)
+}
+
+/// Import new issues and settle status changes on either side since the last sync, recording
+/// the outcome on `connection`
+pub async fn sync_project(
+    db: &DBService,
+    connection: &IssueTrackerConnection,
+) -> Result<IssueTrackerSyncSummary, IssueTrackerError> {
+    let started_at = Utc::now();
+    let result = sync_issues(db, connection).await;
+    let error = result.as_ref().err().map(ToString::to_string);
+    IssueTrackerConnection::record_sync(
+        &db.pool,
+        connection.project_id,
+        result.is_ok().then_some(started_at),
+        error.as_deref(),
+    )
+    .await?;
+    result
+}
+
+async fn sync_issues(
+    db: &DBService,
+    connection: &IssueTrackerConnection,
+) -> Result<IssueTrackerSyncSummary, IssueTrackerError> {
+    let pool = &db.pool;
+    let project_id = connection.project_id;
+    let tracker = connect(connection)?;
+    let since = connection
+        .last_synced_at
+        .map(|synced_at| synced_at - SINCE_OVERLAP);
+    let issues = tracker.list_issues(since).await?;
+
+    let mut summary = IssueTrackerSyncSummary::default();
+    let mut links: HashMap<Uuid, IssueTrackerLink> =
+        IssueTrackerLink::find_by_project_id(pool, project_id)
+            .await?
+            .into_iter()
+            .map(|link| (link.task_id, link))
+            .collect();
+
+    // Issues changed on the tracker
+    for issue in issues {
+        let Some(task) = Task::find_by_external_key(pool, project_id, &issue.key).await? else {
+            if !issue.state.is_finished() {
+                let link = import_issue(pool, project_id, tracker.name(), &issue).await?;
+                links.insert(link.task_id, link);
+                summary.imported += 1;
+            }
+            continue;
+        };
+        let synced_status = match links.get(&task.id) {
+            Some(link) => link.synced_status.clone(),
+            // The key was recorded on the task by hand
+            None => {
+                let link = IssueTrackerLink::create(
+                    pool,
+                    task.id,
+                    project_id,
+                    &issue.url,
+                    task.status.clone(),
+                )
+                .await?;
+                links.insert(task.id, link);
+                task.status.clone()
+            }
+        };
+        let listed = |status: &TaskStatus| tracker.listed_state(IssueState::of(status));
+        if issue.state == listed(&synced_status) {
+            continue;
+        }
+        let status = if issue.state == listed(&task.status) {
+            task.status.clone()
+        } else {
+            let status = issue.state.task_status();
+            Task::update_status(pool, task.id, status.clone()).await?;
+            summary.updated += 1;
+            status
+        };
+        IssueTrackerLink::set_synced_status(pool, task.id, status.clone()).await?;
+        if let Some(link) = links.get_mut(&task.id) {
+            link.synced_status = status;
+        }
+    }
+
+    // Tasks changed on the board
+    for task in Task::find_by_project_id(pool, project_id).await? {
+        let (Some(link), Some(key)) = (links.get(&task.id), task.external_key.as_deref()) else {
+            continue;
+        };
+        let state = IssueState::of(&task.status);
+        if tracker.listed_state(state) == tracker.listed_state(IssueState::of(&link.synced_status))
+        {
+            continue;
+        }
+        tracker.set_state(key, state).await?;
+        IssueTrackerLink::set_synced_status(pool, task.id, task.status.clone()).await?;
+        summary.pushed += 1;
+    }
+
+    if summary.imported + summary.updated + summary.pushed > 0 {
+        info!(
+            "Synced {} issues of {} with project {}: {} imported, {} updated, {} pushed",
+            tracker.name(),
+            connection.scope,
+            project_id,
+            summary.imported,
+            summary.updated,
+            summary.pushed
+        );
+    }
+    Ok(summary)
+}
+
+async fn import_issue(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    tracker: &str,
+    issue: &ExternalIssue,
+) -> Result<IssueTrackerLink, sqlx::Error> {
+    let task = Task::create(
+        pool,
+        &CreateTask::from_title_description(
+            project_id,
+            issue.title.clone(),
+            Some(issue_description(
+                tracker,
+                issue.description.as_deref(),
+                &issue.url,
+            )),
+        ),
+        Uuid::new_v4(),
+    )
+    .await?;
+    let status = issue.state.task_status();
+    if status != task.status {
+        Task::update_status(pool, task.id, status.clone()).await?;
+    }
+    Task::set_external_key(pool, task.id, Some(&issue.key)).await?;
+    IssueTrackerLink::create(pool, task.id, project_id, &issue.url, status).await
+}
+
+/// Description of a task imported from an issue: the issue's description followed by its URL
+fn issue_description(tracker: &str, description: Option<&str>, url: &str) -> String {
+    match description.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(description) => format!("{description}\n\n{tracker} issue: {url}"),
+        None => format!("{tracker} issue: {url}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(kind: IssueTrackerKind) -> UpsertIssueTrackerConnection {
+        UpsertIssueTrackerConnection {
+            kind,
+            base_url: Some(" https://acme.atlassian.net/ ".to_string()),
+            scope: " ENG ".to_string(),
+            email: Some("dev@acme.test".to_string()),
+            api_token: Some(" ".to_string()),
+        }
+    }
+
+    #[test]
+    fn normalizes_connections_per_tracker() {
+        let jira = normalize(&connection(IssueTrackerKind::Jira)).unwrap();
+        assert_eq!(jira.base_url.as_deref(), Some("https://acme.atlassian.net"));
+        assert_eq!(jira.scope, "ENG");
+        assert_eq!(jira.email.as_deref(), Some("dev@acme.test"));
+        assert_eq!(jira.api_token, None);
+
+        let linear = normalize(&connection(IssueTrackerKind::Linear)).unwrap();
+        assert_eq!(linear.base_url, None);
+        assert_eq!(linear.email, None);
+
+        let no_site = UpsertIssueTrackerConnection {
+            base_url: Some("acme.atlassian.net".to_string()),
+            ..connection(IssueTrackerKind::Jira)
+        };
+        assert!(matches!(
+            normalize(&no_site),
+            Err(IssueTrackerError::InvalidConnection(_))
+        ));
+        let no_scope = UpsertIssueTrackerConnection {
+            scope: "  ".to_string(),
+            ..connection(IssueTrackerKind::Linear)
+        };
+        assert!(matches!(
+            normalize(&no_scope),
+            Err(IssueTrackerError::InvalidConnection(_))
+        ));
+    }
+
+    #[test]
+    fn review_counts_as_in_progress() {
+        assert_eq!(
+            IssueState::of(&TaskStatus::InReview),
+            IssueState::InProgress
+        );
+        for status in [
+            TaskStatus::Todo,
+            TaskStatus::InProgress,
+            TaskStatus::Done,
+            TaskStatus::Cancelled,
+        ] {
+            assert_eq!(IssueState::of(&status).task_status(), status);
+        }
+    }
+}
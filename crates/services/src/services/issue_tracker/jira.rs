@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::{ExternalIssue, IssueState, IssueTracker, IssueTrackerError};
+
+/// Issues of one Jira project, through the Jira Cloud REST API
+pub struct JiraTracker {
+    client: reqwest::Client,
+    base_url: String,
+    email: String,
+    token: String,
+    project_key: String,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    issues: Vec<JiraIssue>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JiraIssue {
+    key: String,
+    fields: JiraFields,
+}
+
+#[derive(Deserialize)]
+struct JiraFields {
+    summary: String,
+    /// Atlassian Document Format
+    description: Option<Value>,
+    status: JiraStatus,
+}
+
+#[derive(Deserialize)]
+struct JiraStatus {
+    #[serde(rename = "statusCategory")]
+    category: StatusCategory,
+}
+
+#[derive(Deserialize)]
+struct StatusCategory {
+    key: String,
+}
+
+#[derive(Deserialize)]
+struct TransitionsResponse {
+    transitions: Vec<Transition>,
+}
+
+#[derive(Deserialize)]
+struct Transition {
+    id: String,
+    to: JiraStatus,
+}
+
+/// The board state of a Jira status category
+fn issue_state(category: &str) -> IssueState {
+    match category {
+        "indeterminate" => IssueState::InProgress,
+        "done" => IssueState::Done,
+        // new and undefined
+        _ => IssueState::Todo,
+    }
+}
+
+/// The Jira status category issues in `state` are moved to. Jira has no category for
+/// cancelled work, so cancelled issues are done.
+fn status_category(state: IssueState) -> &'static str {
+    match state {
+        IssueState::Todo => "new",
+        IssueState::InProgress => "indeterminate",
+        IssueState::Done | IssueState::Cancelled => "done",
+    }
+}
+
+/// Plain text of an Atlassian Document Format node, one line per block
+fn document_text(node: &Value) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text.trim().to_string()
+}
+
+fn collect_text(node: &Value, text: &mut String) {
+    match node["type"].as_str() {
+        Some("text") => text.push_str(node["text"].as_str().unwrap_or_default()),
+        Some("hardBreak") => text.push('\n'),
+        _ => {}
+    }
+    if let Some(children) = node["content"].as_array() {
+        for child in children {
+            collect_text(child, text);
+        }
+    }
+    if matches!(
+        node["type"].as_str(),
+        Some("paragraph" | "heading" | "codeBlock" | "listItem")
+    ) && !text.ends_with('\n')
+    {
+        text.push('\n');
+    }
+}
+
+impl JiraTracker {
+    pub fn new(
+        base_url: String,
+        email: String,
+        token: String,
+        project_key: String,
+    ) -> Result<Self, IssueTrackerError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            base_url,
+            email,
+            token,
+            project_key,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/rest/api/3/{path}", self.base_url)
+    }
+
+    /// JQL selecting the project's issues updated since `since`, or its unfinished ones
+    fn jql(&self, since: Option<DateTime<Utc>>) -> String {
+        let project = format!("project = \"{}\"", self.project_key.replace('"', "\\\""));
+        match since {
+            // Relative, so the user's time zone on the Jira site doesn't matter
+            Some(since) => {
+                let minutes = (Utc::now() - since).num_minutes().max(0) + 1;
+                format!("{project} AND updated >= \"-{minutes}m\" ORDER BY updated ASC")
+            }
+            None => format!("{project} AND statusCategory != Done ORDER BY created ASC"),
+        }
+    }
+}
+
+#[async_trait]
+impl IssueTracker for JiraTracker {
+    fn name(&self) -> &'static str {
+        "Jira"
+    }
+
+    async fn list_issues(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ExternalIssue>, IssueTrackerError> {
+        let jql = self.jql(since);
+        let mut issues = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("jql", jql.clone()),
+                ("fields", "summary,description,status".to_string()),
+                ("maxResults", "100".to_string()),
+            ];
+            if let Some(token) = page_token.take() {
+                query.push(("nextPageToken", token));
+            }
+            let response: SearchResponse = self
+                .client
+                .get(self.url("search/jql"))
+                .basic_auth(&self.email, Some(&self.token))
+                .query(&query)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            issues.extend(response.issues.into_iter().map(|issue| {
+                ExternalIssue {
+                    url: format!("{}/browse/{}", self.base_url, issue.key),
+                    key: issue.key,
+                    title: issue.fields.summary,
+                    description: issue
+                        .fields
+                        .description
+                        .as_ref()
+                        .map(document_text)
+                        .filter(|text| !text.is_empty()),
+                    state: issue_state(&issue.fields.status.category.key),
+                }
+            }));
+            match response.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn set_state(&self, key: &str, state: IssueState) -> Result<(), IssueTrackerError> {
+        let category = status_category(state);
+        let url = self.url(&format!("issue/{key}/transitions"));
+        let response: TransitionsResponse = self
+            .client
+            .get(&url)
+            .basic_auth(&self.email, Some(&self.token))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let transition = response
+            .transitions
+            .into_iter()
+            .find(|transition| transition.to.category.key == category)
+            .ok_or_else(|| IssueTrackerError::Api {
+                tracker: "Jira",
+                message: format!("No transition moves {key} into the {category} category"),
+            })?;
+
+        self.client
+            .post(&url)
+            .basic_auth(&self.email, Some(&self.token))
+            .json(&json!({ "transition": { "id": transition.id } }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn listed_state(&self, state: IssueState) -> IssueState {
+        issue_state(status_category(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_issues_are_listed_as_done() {
+        let tracker = JiraTracker::new(
+            "https://acme.atlassian.net".to_string(),
+            "dev@acme.test".to_string(),
+            "token".to_string(),
+            "ENG".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            tracker.listed_state(IssueState::Cancelled),
+            IssueState::Done
+        );
+        assert_eq!(
+            tracker.listed_state(IssueState::InProgress),
+            IssueState::InProgress
+        );
+        assert_eq!(
+            tracker.jql(None),
+            "project = \"ENG\" AND statusCategory != Done ORDER BY created ASC"
+        );
+    }
+
+    #[test]
+    fn flattens_document_descriptions() {
+        let description = json!({
+            "type": "doc",
+            "content": [
+                {"type": "paragraph", "content": [
+                    {"type": "text", "text": "Login fails"},
+                    {"type": "hardBreak"},
+                    {"type": "text", "text": "on Safari"}
+                ]},
+                {"type": "paragraph", "content": [{"type": "text", "text": "Since 2.3"}]}
+            ]
+        });
+        assert_eq!(
+            document_text(&description),
+            "Login fails\non Safari\nSince 2.3"
+        );
+    }
+}
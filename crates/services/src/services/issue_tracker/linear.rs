@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::AUTHORIZATION;
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+use super::{ExternalIssue, IssueState, IssueTracker, IssueTrackerError};
+
+const API_URL: &str = "https://api.linear.app/graphql";
+
+const ISSUES_QUERY: &str = r#"
+query Issues($filter: IssueFilter, $after: String) {
+  issues(filter: $filter, first: 100, after: $after) {
+    nodes { identifier title description url state { type } }
+    pageInfo { hasNextPage endCursor }
+  }
+}"#;
+
+const STATES_QUERY: &str = r#"
+query States($team: String!, $type: String!) {
+  workflowStates(filter: { team: { key: { eq: $team } }, type: { eq: $type } }) {
+    nodes { id position }
+  }
+}"#;
+
+const MOVE_ISSUE_MUTATION: &str = r#"
+mutation MoveIssue($id: String!, $stateId: String!) {
+  issueUpdate(id: $id, input: { stateId: $stateId }) { success }
+}"#;
+
+/// Issues of one Linear team, through Linear's GraphQL API
+pub struct LinearTracker {
+    client: reqwest::Client,
+    token: String,
+    team_key: String,
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Connection<T> {
+    nodes: Vec<T>,
+    #[serde(rename = "pageInfo")]
+    page_info: Option<PageInfo>,
+}
+
+#[derive(Deserialize)]
+struct PageInfo {
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
+    #[serde(rename = "endCursor")]
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IssuesData {
+    issues: Connection<LinearIssue>,
+}
+
+#[derive(Deserialize)]
+struct LinearIssue {
+    identifier: String,
+    title: String,
+    description: Option<String>,
+    url: String,
+    state: LinearState,
+}
+
+#[derive(Deserialize)]
+struct LinearState {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct StatesData {
+    #[serde(rename = "workflowStates")]
+    workflow_states: Connection<WorkflowState>,
+}
+
+#[derive(Deserialize)]
+struct WorkflowState {
+    id: String,
+    position: f64,
+}
+
+#[derive(Deserialize)]
+struct MoveIssueData {
+    #[serde(rename = "issueUpdate")]
+    issue_update: MoveIssueResult,
+}
+
+#[derive(Deserialize)]
+struct MoveIssueResult {
+    success: bool,
+}
+
+/// The board state of a Linear workflow state type
+fn issue_state(state_type: &str) -> IssueState {
+    match state_type {
+        "started" => IssueState::InProgress,
+        "completed" => IssueState::Done,
+        "canceled" => IssueState::Cancelled,
+        // triage, backlog and unstarted
+        _ => IssueState::Todo,
+    }
+}
+
+/// The Linear workflow state type issues in `state` are moved to
+fn state_type(state: IssueState) -> &'static str {
+    match state {
+        IssueState::Todo => "unstarted",
+        IssueState::InProgress => "started",
+        IssueState::Done => "completed",
+        IssueState::Cancelled => "canceled",
+    }
+}
+
+impl LinearTracker {
+    pub fn new(token: String, team_key: String) -> Result<Self, IssueTrackerError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            token,
+            team_key,
+        })
+    }
+
+    fn error(message: impl Into<String>) -> IssueTrackerError {
+        IssueTrackerError::Api {
+            tracker: "Linear",
+            message: message.into(),
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, IssueTrackerError> {
+        let response: GraphQlResponse<T> = self
+            .client
+            .post(API_URL)
+            .header(AUTHORIZATION, &self.token)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if !response.errors.is_empty() {
+            let messages: Vec<_> = response.errors.into_iter().map(|e| e.message).collect();
+            return Err(Self::error(messages.join("; ")));
+        }
+        response
+            .data
+            .ok_or_else(|| Self::error("The response has no data"))
+    }
+}
+
+#[async_trait]
+impl IssueTracker for LinearTracker {
+    fn name(&self) -> &'static str {
+        "Linear"
+    }
+
+    async fn list_issues(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ExternalIssue>, IssueTrackerError> {
+        let mut filter = json!({ "team": { "key": { "eq": self.team_key } } });
+        match since {
+            Some(since) => filter["updatedAt"] = json!({ "gte": since.to_rfc3339() }),
+            None => filter["state"] = json!({ "type": { "nin": ["completed", "canceled"] } }),
+        }
+
+        let mut issues = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let data: IssuesData = self
+                .query(ISSUES_QUERY, json!({ "filter": filter, "after": after }))
+                .await?;
+            issues.extend(data.issues.nodes.into_iter().map(|issue| ExternalIssue {
+                key: issue.identifier,
+                title: issue.title,
+                description: issue.description,
+                url: issue.url,
+                state: issue_state(&issue.state.kind),
+            }));
+            match data.issues.page_info {
+                Some(PageInfo {
+                    has_next_page: true,
+                    end_cursor: Some(cursor),
+                }) => after = Some(cursor),
+                _ => break,
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn set_state(&self, key: &str, state: IssueState) -> Result<(), IssueTrackerError> {
+        let kind = state_type(state);
+        let data: StatesData = self
+            .query(STATES_QUERY, json!({ "team": self.team_key, "type": kind }))
+            .await?;
+        let target = data
+            .workflow_states
+            .nodes
+            .into_iter()
+            .min_by(|a, b| a.position.total_cmp(&b.position))
+            .ok_or_else(|| {
+                Self::error(format!(
+                    "Team {} has no workflow state of type {kind}",
+                    self.team_key
+                ))
+            })?;
+
+        let data: MoveIssueData = self
+            .query(
+                MOVE_ISSUE_MUTATION,
+                json!({ "id": key, "stateId": target.id }),
+            )
+            .await?;
+        if !data.issue_update.success {
+            return Err(Self::error(format!("Failed to move {key}")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_workflow_state_types() {
+        assert_eq!(issue_state("backlog"), IssueState::Todo);
+        assert_eq!(issue_state("triage"), IssueState::Todo);
+        for state in [
+            IssueState::Todo,
+            IssueState::InProgress,
+            IssueState::Done,
+            IssueState::Cancelled,
+        ] {
+            assert_eq!(issue_state(state_type(state)), state);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Plain-text copies of execution process output, for sharing outside the UI.
+//!
+//! A process's output is read from its in-memory store while it runs and from its stored logs
+//! afterwards. An attempt's processes are bundled into one zip file, one text file each.
+
+use std::io::{Cursor, Write};
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    execution_process_log_search::LogStream,
+    execution_process_logs::ExecutionProcessLogs,
+};
+use sqlx::SqlitePool;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::services::log_search::split_lines;
+
+/// The messages a process has logged: `in_memory` if it is still running, otherwise its stored
+/// logs. Logs that cannot be parsed are treated as empty.
+pub async fn load_history(
+    pool: &SqlitePool,
+    execution_process_id: Uuid,
+    in_memory: Option<Vec<LogMsg>>,
+) -> Result<Vec<LogMsg>, sqlx::Error> {
+    if let Some(history) = in_memory {
+        return Ok(history);
+    }
+    let Some(logs) = ExecutionProcessLogs::find_by_execution_id(pool, execution_process_id).await?
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(logs.parse_logs().unwrap_or_else(|e| {
+        tracing::warn!(
+            "Failed to parse logs of execution process {}: {}",
+            execution_process_id,
+            e
+        );
+        Vec::new()
+    }))
+}
+
+/// A process's output as text: one stream exactly as printed, or both interleaved line by line
+/// in the order the lines were completed
+pub fn raw_text(history: &[LogMsg], stream: Option<LogStream>) -> String {
+    match stream {
+        Some(stream) => history
+            .iter()
+            .filter_map(|msg| match (msg, stream) {
+                (LogMsg::Stdout(text), LogStream::Stdout)
+                | (LogMsg::Stderr(text), LogStream::Stderr) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect(),
+        None => split_lines(history)
+            .into_iter()
+            .map(|line| line.content + "\n")
+            .collect(),
+    }
+}
+
+/// Name of a process's file in an attempt's zip: its position in the attempt, why it ran and
+/// the start of its id, e.g. `02-coding-agent-1f0c2a9b.log`
+pub fn file_name(position: usize, process: &ExecutionProcess) -> String {
+    let run_reason = match process.run_reason {
+        ExecutionProcessRunReason::SetupScript => "setup-script",
+        ExecutionProcessRunReason::CleanupScript => "cleanup-script",
+        ExecutionProcessRunReason::CodingAgent => "coding-agent",
+        ExecutionProcessRunReason::DevServer => "dev-server",
+        ExecutionProcessRunReason::PostMergeScript => "post-merge-script",
+        ExecutionProcessRunReason::TestScript => "test-script",
+    };
+    let id = process.id.simple().to_string();
+    format!("{:02}-{}-{}.log", position + 1, run_reason, &id[..8])
+}
+
+/// Zip `(file name, contents)` pairs
+pub fn zip_files(files: impl IntoIterator<Item = (String, String)>) -> std::io::Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(name, options)
+            .map_err(std::io::Error::other)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    let cursor = zip.finish().map_err(std::io::Error::other)?;
+    Ok(cursor.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+
+    #[test]
+    fn renders_one_stream_or_both_by_line() {
+        let history = vec![
+            LogMsg::Stdout("Compiling a\nCompil".to_string()),
+            LogMsg::Stderr("warning: unused\n".to_string()),
+            LogMsg::Stdout("ing b\n".to_string()),
+            LogMsg::Finished,
+        ];
+        assert_eq!(
+            raw_text(&history, Some(LogStream::Stdout)),
+            "Compiling a\nCompiling b\n"
+        );
+        assert_eq!(
+            raw_text(&history, Some(LogStream::Stderr)),
+            "warning: unused\n"
+        );
+        assert_eq!(
+            raw_text(&history, None),
+            "Compiling a\nwarning: unused\nCompiling b\n"
+        );
+    }
+
+    #[test]
+    fn zips_one_file_per_process() {
+        let bytes = zip_files(vec![
+            (
+                "01-setup-script-aaaaaaaa.log".to_string(),
+                "npm i\n".to_string(),
+            ),
+            ("02-coding-agent-bbbbbbbb.log".to_string(), String::new()),
+        ])
+        .unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut contents = String::new();
+        archive
+            .by_name("01-setup-script-aaaaaaaa.log")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "npm i\n");
+    }
+}
@@ -0,0 +1,274 @@
+//! Keeps execution logs from filling up the database.
+//!
+//! Logs of finished processes are compressed once they have not been written to for a while,
+//! and deleted once they are older than the configured age or the oldest ones no longer fit in
+//! the size cap. Deleted logs are saved to the archive directory first if one is configured.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{
+        execution_process_log_search::ExecutionProcessLogSearch,
+        execution_process_logs::ExecutionProcessLogs,
+    },
+};
+use serde::Serialize;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::config::{Config, LogRetentionConfig};
+
+/// Processes handled per query while compressing or pruning
+const BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Error)]
+pub enum LogRetentionError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error("Failed to archive logs to {path}: {source}")]
+    Archive {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Outcome of one retention pass
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct LogRetentionReport {
+    /// Processes whose logs were compressed
+    pub compressed: u32,
+    /// Bytes saved by compressing them
+    pub compressed_bytes_saved: i64,
+    /// Processes whose logs were saved to the archive directory before deletion
+    pub archived: u32,
+    /// Processes whose logs were deleted for being too old
+    pub deleted_expired: u32,
+    /// Processes whose logs were deleted to stay under the size cap
+    pub deleted_over_cap: u32,
+}
+
+/// Service that compresses, archives and prunes execution logs as configured
+#[derive(Clone)]
+pub struct LogRetentionService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+}
+
+impl LogRetentionService {
+    pub fn new(db: DBService, config: Arc<RwLock<Config>>) -> Self {
+        Self { db, config }
+    }
+
+    pub async fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            self.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        let poll_interval = Duration::from_secs(3600);
+        info!(
+            "Starting execution log retention with interval {:?}",
+            poll_interval
+        );
+
+        let mut interval = interval(poll_interval);
+
+        loop {
+            interval.tick().await;
+            if !self.config.read().await.log_retention.enabled {
+                continue;
+            }
+            if let Err(e) = self.run().await {
+                error!("Error applying execution log retention: {}", e);
+            }
+        }
+    }
+
+    /// Apply the configured policy now
+    pub async fn run(&self) -> Result<LogRetentionReport, LogRetentionError> {
+        let policy = self.config.read().await.log_retention.clone();
+        let mut report = LogRetentionReport::default();
+        let now = Utc::now();
+
+        if let Some(days) = policy.delete_after_days {
+            let before = now - chrono::Duration::days(days.into());
+            loop {
+                let expired =
+                    ExecutionProcessLogs::find_expired(&self.db.pool, before, BATCH_SIZE).await?;
+                if expired.is_empty() {
+                    break;
+                }
+                for execution_id in expired {
+                    self.delete(&policy, execution_id, &mut report).await?;
+                    report.deleted_expired += 1;
+                }
+            }
+        }
+
+        if let Some(max_total_mb) = policy.max_total_mb {
+            let over_cap = over_cap(
+                ExecutionProcessLogs::stored_sizes(&self.db.pool)
+                    .await?
+                    .into_iter()
+                    .map(|size| (size.execution_id, size.stored_bytes, size.finished)),
+                i64::from(max_total_mb) * 1024 * 1024,
+            );
+            for execution_id in over_cap {
+                self.delete(&policy, execution_id, &mut report).await?;
+                report.deleted_over_cap += 1;
+            }
+        }
+
+        if let Some(days) = policy.compress_after_days {
+            let before = now - chrono::Duration::days(days.into());
+            loop {
+                let compressible =
+                    ExecutionProcessLogs::find_compressible(&self.db.pool, before, BATCH_SIZE)
+                        .await?;
+                if compressible.is_empty() {
+                    break;
+                }
+                let mut progressed = false;
+                for execution_id in compressible {
+                    match ExecutionProcessLogs::compress(&self.db.pool, execution_id).await {
+                        Ok(Some(saved)) => {
+                            report.compressed += 1;
+                            report.compressed_bytes_saved += saved;
+                            progressed = true;
+                        }
+                        Ok(None) => {}
+                        Err(e) => warn!(
+                            "Failed to compress logs of execution process {}: {}",
+                            execution_id, e
+                        ),
+                    }
+                }
+                // Logs that keep failing would otherwise be picked up forever
+                if !progressed {
+                    break;
+                }
+            }
+        }
+
+        if report.compressed + report.deleted_expired + report.deleted_over_cap > 0 {
+            info!(
+                "Execution log retention compressed {} and deleted {} logs",
+                report.compressed,
+                report.deleted_expired + report.deleted_over_cap
+            );
+        } else {
+            debug!("No execution logs to compress or delete");
+        }
+        Ok(report)
+    }
+
+    /// Delete a process's logs and their search index entries, archiving them first if
+    /// configured. Nothing is deleted if archiving fails.
+    async fn delete(
+        &self,
+        policy: &LogRetentionConfig,
+        execution_id: Uuid,
+        report: &mut LogRetentionReport,
+    ) -> Result<(), LogRetentionError> {
+        if let Some(dir) = &policy.archive_dir
+            && let Some(logs) =
+                ExecutionProcessLogs::find_by_execution_id(&self.db.pool, execution_id).await?
+        {
+            let path = archive(Path::new(dir), execution_id, &logs.logs)
+                .await
+                .map_err(|source| LogRetentionError::Archive {
+                    path: dir.clone(),
+                    source,
+                })?;
+            debug!(
+                "Archived logs of execution process {} to {:?}",
+                execution_id, path
+            );
+            report.archived += 1;
+        }
+        ExecutionProcessLogs::delete(&self.db.pool, execution_id).await?;
+        ExecutionProcessLogSearch::remove_lines(&self.db.pool, execution_id).await?;
+        Ok(())
+    }
+}
+
+/// Write logs to `<dir>/<execution id>.jsonl.zst`
+async fn archive(dir: &Path, execution_id: Uuid, logs: &str) -> std::io::Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+    let path = dir.join(format!("{execution_id}.jsonl.zst"));
+    let compressed = ExecutionProcessLogs::compress_jsonl(logs)?;
+    tokio::fs::write(&path, compressed).await?;
+    Ok(path)
+}
+
+/// Logs of finished processes to delete so the rest fit in `max_bytes`: every log from the
+/// first one that no longer fits on. `sizes` is ordered newest first; logs of running
+/// processes are kept but count towards the cap.
+fn over_cap(sizes: impl IntoIterator<Item = (Uuid, i64, bool)>, max_bytes: i64) -> Vec<Uuid> {
+    let mut total = 0;
+    let mut full = false;
+    let mut delete = Vec::new();
+    for (execution_id, stored_bytes, finished) in sizes {
+        if finished && (full || total + stored_bytes > max_bytes) {
+            full = true;
+            delete.push(execution_id);
+        } else {
+            total += stored_bytes;
+        }
+    }
+    delete
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletes_the_oldest_finished_logs_over_the_cap() {
+        let ids: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+        let sizes = vec![
+            (ids[0], 400, false),
+            (ids[1], 300, true),
+            (ids[2], 500, true),
+            (ids[3], 200, true),
+            (ids[4], 100, true),
+        ];
+        // 400 + 300 fit and 500 would not, so it and everything older goes
+        assert_eq!(over_cap(sizes, 1_000), vec![ids[2], ids[3], ids[4]]);
+    }
+
+    #[test]
+    fn never_deletes_logs_of_running_processes() {
+        let running = Uuid::new_v4();
+        let finished = Uuid::new_v4();
+        assert_eq!(
+            over_cap(vec![(running, 5_000, false), (finished, 10, true)], 1_000),
+            vec![finished]
+        );
+    }
+
+    #[tokio::test]
+    async fn archives_compressed_jsonl() {
+        let dir = std::env::temp_dir().join(format!("vk-log-archive-{}", Uuid::new_v4()));
+        let execution_id = Uuid::new_v4();
+        let logs = "{\"Stdout\":\"hello\\n\"}\n{\"Finished\":null}\n";
+        let path = archive(&dir, execution_id, logs).await.unwrap();
+        let compressed = std::fs::read(&path).unwrap();
+        assert_eq!(
+            ExecutionProcessLogs::decompress_jsonl(&compressed).unwrap(),
+            logs
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,294 @@
+//! Searches the output of execution processes.
+//!
+//! When a process finishes, its output is split into lines and added to a full-text index;
+//! processes that finished before the index existed are indexed in the background at startup.
+//! Grepping a single process reads its logs directly, so it also works while the process runs.
+
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessSearchResult},
+        execution_process_log_search::{ExecutionProcessLogSearch, LogLine, LogStream},
+    },
+};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use crate::services::log_export;
+
+/// Matched lines returned per process by a search
+const MATCHES_PER_PROCESS: usize = 20;
+
+/// Longer lines are cut off in results; the whole line is still searched
+const MAX_LINE_CHARS: usize = 1_000;
+
+/// Processes indexed per step of the startup backfill
+const BACKFILL_BATCH: i64 = 50;
+
+#[derive(Debug, Error)]
+pub enum LogSearchError {
+    #[error("The search query is empty")]
+    EmptyQuery,
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+}
+
+/// A process whose output matched a search, with the matching lines
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutionProcessLogMatches {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub process: ExecutionProcessSearchResult,
+    /// Lines that matched, in output order; at most 20 are returned
+    pub matches: Vec<LogLine>,
+    /// Number of matching lines found, which may exceed `matches`
+    pub match_count: u32,
+}
+
+/// Split a process's output into lines. A line can arrive in several chunks, and stdout and
+/// stderr lines are numbered together in the order they are completed.
+pub fn split_lines(history: &[LogMsg]) -> Vec<LogLine> {
+    let mut lines = Vec::new();
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    fn push(lines: &mut Vec<LogLine>, stream: LogStream, content: &str) {
+        lines.push(LogLine {
+            line_number: lines.len() as i64 + 1,
+            stream,
+            content: content.strip_suffix('\r').unwrap_or(content).to_string(),
+        });
+    }
+
+    for msg in history {
+        let (buffer, stream) = match msg {
+            LogMsg::Stdout(text) => {
+                stdout.push_str(text);
+                (&mut stdout, LogStream::Stdout)
+            }
+            LogMsg::Stderr(text) => {
+                stderr.push_str(text);
+                (&mut stderr, LogStream::Stderr)
+            }
+            _ => continue,
+        };
+        while let Some(end) = buffer.find('\n') {
+            push(&mut lines, stream, &buffer[..end]);
+            buffer.drain(..=end);
+        }
+    }
+    for (rest, stream) in [(stdout, LogStream::Stdout), (stderr, LogStream::Stderr)] {
+        if !rest.is_empty() {
+            push(&mut lines, stream, &rest);
+        }
+    }
+    lines
+}
+
+/// FTS5 query matching lines that contain every word of `query`, taken literally
+pub fn fts_query(query: &str) -> Result<String, LogSearchError> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return Err(LogSearchError::EmptyQuery);
+    }
+    Ok(words.join(" "))
+}
+
+/// Matches lines of one process's output against a plain-text or regex pattern
+pub struct LogGrep {
+    pattern: Regex,
+}
+
+impl LogGrep {
+    pub fn new(pattern: &str, regex: bool, ignore_case: bool) -> Result<Self, LogSearchError> {
+        if pattern.is_empty() {
+            return Err(LogSearchError::EmptyQuery);
+        }
+        let pattern = if regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        let pattern = RegexBuilder::new(&pattern)
+            .case_insensitive(ignore_case)
+            .build()?;
+        Ok(Self { pattern })
+    }
+
+    /// The first `limit` matching lines
+    pub fn matches(&self, lines: Vec<LogLine>, limit: usize) -> Vec<LogLine> {
+        lines
+            .into_iter()
+            .filter(|line| self.pattern.is_match(&line.content))
+            .take(limit)
+            .map(truncate)
+            .collect()
+    }
+}
+
+/// The lines of a process's output: from memory while it runs, otherwise from its stored logs
+pub async fn process_lines(
+    pool: &SqlitePool,
+    execution_process_id: Uuid,
+    history: Option<Vec<LogMsg>>,
+) -> Result<Vec<LogLine>, sqlx::Error> {
+    let history = log_export::load_history(pool, execution_process_id, history).await?;
+    Ok(split_lines(&history))
+}
+
+/// Add the output of a finished process to the search index, read from `history` if it is
+/// still in memory
+pub async fn index(
+    pool: &SqlitePool,
+    execution_process_id: Uuid,
+    history: Option<Vec<LogMsg>>,
+) -> Result<(), sqlx::Error> {
+    let lines = process_lines(pool, execution_process_id, history).await?;
+    ExecutionProcessLogSearch::index(pool, execution_process_id, &lines).await
+}
+
+/// Processes whose output matches the query built by [`fts_query`], most recently started
+/// first. At most `line_limit` matching lines are read.
+pub async fn search(
+    pool: &SqlitePool,
+    fts_query: &str,
+    project_id: Option<Uuid>,
+    line_limit: i64,
+) -> Result<Vec<ExecutionProcessLogMatches>, sqlx::Error> {
+    let hits = ExecutionProcessLogSearch::search(pool, fts_query, project_id, line_limit).await?;
+
+    let mut grouped: Vec<(Uuid, Vec<LogLine>, u32)> = Vec::new();
+    for hit in hits {
+        let line = LogLine {
+            line_number: hit.line_number,
+            stream: hit.stream,
+            content: hit.content,
+        };
+        match grouped.last_mut() {
+            Some((id, matches, count)) if *id == hit.execution_process_id => {
+                if matches.len() < MATCHES_PER_PROCESS {
+                    matches.push(truncate(line));
+                }
+                *count += 1;
+            }
+            _ => grouped.push((hit.execution_process_id, vec![truncate(line)], 1)),
+        }
+    }
+
+    let mut results = Vec::with_capacity(grouped.len());
+    for (id, matches, match_count) in grouped {
+        if let Some(process) = ExecutionProcess::find_search_result_by_id(pool, id).await? {
+            results.push(ExecutionProcessLogMatches {
+                process,
+                matches,
+                match_count,
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Index the output of processes that finished before the index existed
+pub fn spawn_backfill(db: DBService) {
+    tokio::spawn(async move {
+        let mut indexed = 0;
+        loop {
+            let ids =
+                match ExecutionProcessLogSearch::find_unindexed(&db.pool, BACKFILL_BATCH).await {
+                    Ok(ids) => ids,
+                    Err(e) => {
+                        tracing::error!("Failed to find execution processes to index: {}", e);
+                        return;
+                    }
+                };
+            if ids.is_empty() {
+                break;
+            }
+            for id in ids {
+                if let Err(e) = index(&db.pool, id, None).await {
+                    tracing::error!("Failed to index logs of execution process {}: {}", id, e);
+                    return;
+                }
+                indexed += 1;
+            }
+        }
+        if indexed > 0 {
+            tracing::info!("Indexed the logs of {} execution processes", indexed);
+        }
+    });
+}
+
+fn truncate(mut line: LogLine) -> LogLine {
+    if let Some((end, _)) = line.content.char_indices().nth(MAX_LINE_CHARS) {
+        line.content.truncate(end);
+        line.content.push('…');
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_number: i64, stream: LogStream, content: &str) -> LogLine {
+        LogLine {
+            line_number,
+            stream,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn splits_chunks_into_numbered_lines() {
+        let history = vec![
+            LogMsg::Stdout("Compiling a\nCompil".to_string()),
+            LogMsg::Stderr("warning: unused\r\n".to_string()),
+            LogMsg::Stdout("ing b\n".to_string()),
+            LogMsg::Finished,
+            LogMsg::Stdout("no newline".to_string()),
+        ];
+        assert_eq!(
+            split_lines(&history),
+            vec![
+                line(1, LogStream::Stdout, "Compiling a"),
+                line(2, LogStream::Stderr, "warning: unused"),
+                line(3, LogStream::Stdout, "Compiling b"),
+                line(4, LogStream::Stdout, "no newline"),
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_every_word_of_the_query() {
+        assert_eq!(
+            fts_query(r#"  panicked at "src/main.rs" OR "#).unwrap(),
+            r#""panicked" "at" """src/main.rs""" "OR""#
+        );
+        assert!(matches!(fts_query("   "), Err(LogSearchError::EmptyQuery)));
+    }
+
+    #[test]
+    fn greps_plain_text_and_regexes() {
+        let lines = vec![
+            line(1, LogStream::Stdout, "test a ... ok"),
+            line(2, LogStream::Stdout, "test b ... FAILED"),
+            line(3, LogStream::Stderr, "error[E0308]: mismatched types"),
+        ];
+        let plain = LogGrep::new("failed", false, true).unwrap();
+        assert_eq!(plain.matches(lines.clone(), 10), vec![lines[1].clone()]);
+
+        let regex = LogGrep::new(r"error\[E\d+\]", true, false).unwrap();
+        assert_eq!(regex.matches(lines.clone(), 10), vec![lines[2].clone()]);
+
+        let literal = LogGrep::new("[E0308]", false, false).unwrap();
+        assert_eq!(literal.matches(lines, 10).len(), 1);
+        assert!(LogGrep::new("(", true, false).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        audit_log::AuditAction,
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+        merge::{Merge, MergeStrategy},
+        merge_queue::{MergeQueueEntry, MergeQueueStatus},
+        project::Project,
+        task::{Task, TaskStatus},
+        task_attempt::TaskAttempt,
+        vulnerability_finding::VulnerabilitySeverity,
+    },
+};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{
+    sync::{Notify, RwLock, broadcast},
+    time::timeout,
+};
+use tracing::{error, info, warn};
+use utils::shell::get_shell_command;
+use uuid::Uuid;
+
+use crate::services::{
+    audit_log::AuditEvent,
+    config::{Config, VulnerabilityScanConfig},
+    deploy_hook::{DeployHookEvent, DeployHookService},
+    git::{GitService, GitServiceError},
+    vulnerability_scan::{VulnerabilityScanError, VulnerabilityScanService},
+};
+
+/// Wakes the worker as soon as an attempt is queued instead of at the next poll
+static WAKE: Notify = Notify::const_new();
+
+/// Ids of the task attempts the queue merges, for running their projects' post-merge scripts
+static LANDED: LazyLock<broadcast::Sender<Uuid>> = LazyLock::new(|| broadcast::channel(64).0);
+
+/// How long a project's verify script may run before the entry fails
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Lines of verify script output kept in the entry's error
+const VERIFY_OUTPUT_LINES: usize = 20;
+
+#[derive(Debug, Error)]
+pub enum MergeQueueError {
+    #[error(transparent)]
+    GitServiceError(#[from] GitServiceError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// Commit message for squash merges of an attempt: the task title, with its description
+/// below if it has one
+pub fn merge_commit_message(task: &Task) -> String {
+    let task_uuid_str = task.id.to_string();
+    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
+    let mut commit_message = format!("{} (vibe-kanban {})", task.title, first_uuid_section);
+    if let Some(description) = &task.description
+        && !description.trim().is_empty()
+    {
+        commit_message.push_str("\n\n");
+        commit_message.push_str(description);
+    }
+    commit_message
+}
+
+/// Why the project refuses to merge the attempt because of vulnerable dependencies, if it does.
+/// Projects that block on them have the attempt's branch scanned as it is now, so the result
+/// is for exactly the commits being merged; a scan that can't complete blocks the merge too.
+pub async fn vulnerability_block(
+    db: &DBService,
+    git: &GitService,
+    config: &VulnerabilityScanConfig,
+    project: &Project,
+    task_attempt: &TaskAttempt,
+    worktree_path: &Path,
+) -> Result<Option<String>, SqlxError> {
+    if !project.block_merge_on_vulnerabilities {
+        return Ok(None);
+    }
+    let findings =
+        match VulnerabilityScanService::scan_branch(db, git, config, task_attempt, worktree_path)
+            .await
+        {
+            Ok(findings) => findings,
+            Err(VulnerabilityScanError::Database(e)) => return Err(e),
+            Err(e) => {
+                return Ok(Some(format!(
+                    "Merge blocked: the dependency vulnerability scan failed: {e}"
+                )));
+            }
+        };
+    let critical = findings
+        .iter()
+        .filter(|finding| finding.severity == VulnerabilitySeverity::Critical)
+        .count();
+    Ok((critical > 0).then(|| {
+        format!(
+            "Merge blocked: {critical} critical dependency vulnerabilities found in this attempt"
+        )
+    }))
+}
+
+/// Service that lands queued attempts one at a time per project: each is rebased onto the
+/// current base branch, checked with the project's verify script and merged, so no attempt is
+/// merged after passing checks against a base that has since moved
+pub struct MergeQueueService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    git: GitService,
+    poll_interval: Duration,
+}
+
+impl MergeQueueService {
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        git: GitService,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            git,
+            poll_interval: Duration::from_secs(30),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    /// Process the queues now rather than at the next poll
+    pub fn wake() {
+        WAKE.notify_one();
+    }
+
+    /// Receive the id of every task attempt the queue merges from now on
+    pub fn subscribe_landed() -> broadcast::Receiver<Uuid> {
+        LANDED.subscribe()
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting merge queue service with interval {:?}",
+            self.poll_interval
+        );
+
+        match MergeQueueEntry::requeue_interrupted(&self.db.pool).await {
+            Ok(0) => {}
+            Ok(count) => info!("Re-queued {} interrupted merge queue entries", count),
+            Err(e) => error!("Failed to re-queue interrupted merge queue entries: {}", e),
+        }
+
+        loop {
+            if let Err(e) = self.process_queues().await {
+                error!("Error processing merge queues: {}", e);
+            }
+            let _ = timeout(self.poll_interval, WAKE.notified()).await;
+        }
+    }
+
+    async fn process_queues(&self) -> Result<(), MergeQueueError> {
+        for project_id in MergeQueueEntry::find_queued_project_ids(&self.db.pool).await? {
+            while let Some(entry) =
+                MergeQueueEntry::find_next_queued(&self.db.pool, project_id).await?
+            {
+                if !MergeQueueEntry::start(&self.db.pool, entry.id).await? {
+                    continue;
+                }
+                match self.land(&entry).await {
+                    Ok(merge_commit) => {
+                        info!(
+                            "Merge queue landed task attempt {} as {}",
+                            entry.task_attempt_id, merge_commit
+                        );
+                        MergeQueueEntry::update_status(
+                            &self.db.pool,
+                            entry.id,
+                            MergeQueueStatus::Merged,
+                            None,
+                            Some(&merge_commit),
+                        )
+                        .await?;
+                        // Nobody may be listening
+                        let _ = LANDED.send(entry.task_attempt_id);
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Merge queue failed to land task attempt {}: {}",
+                            entry.task_attempt_id, e
+                        );
+                        MergeQueueEntry::update_status(
+                            &self.db.pool,
+                            entry.id,
+                            MergeQueueStatus::Failed,
+                            Some(&e.to_string()),
+                            None,
+                        )
+                        .await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebase, verify and merge one attempt, returning the merge commit
+    async fn land(&self, entry: &MergeQueueEntry) -> Result<String, MergeQueueError> {
+        let pool = &self.db.pool;
+        let rejected = |message: &str| MergeQueueError::Rejected(message.to_string());
+
+        let attempt = TaskAttempt::find_by_id(pool, entry.task_attempt_id)
+            .await?
+            .ok_or_else(|| rejected("The task attempt no longer exists"))?;
+        let task = attempt
+            .parent_task(pool)
+            .await?
+            .ok_or_else(|| rejected("The task no longer exists"))?;
+        let project = Project::find_by_id(pool, entry.project_id)
+            .await?
+            .ok_or_else(|| rejected("The project no longer exists"))?;
+
+        let busy = ExecutionProcess::find_by_task_attempt_id(pool, attempt.id, false)
+            .await?
+            .iter()
+            .any(|process| {
+                process.run_reason != ExecutionProcessRunReason::DevServer
+                    && matches!(
+                        process.status,
+                        ExecutionProcessStatus::Running | ExecutionProcessStatus::Queued
+                    )
+            });
+        if busy {
+            return Err(rejected("The attempt has running processes"));
+        }
+
+        let worktree_path = attempt
+            .container_ref
+            .as_deref()
+            .map(PathBuf::from)
+            .filter(|path| path.exists())
+            .ok_or_else(|| {
+                rejected("The attempt's worktree no longer exists; open the attempt to recreate it")
+            })?;
+        if !self.git.is_worktree_clean(&worktree_path)? {
+            return Err(rejected("The attempt's worktree has uncommitted changes"));
+        }
+
+        let repo_path = &project.git_repo_path;
+        let github_token = self.config.read().await.github.token();
+        self.git
+            .fetch_base_branch(repo_path, &attempt.target_branch, github_token.clone())?;
+        if let Err(e) = self.git.rebase_branch(
+            repo_path,
+            &worktree_path,
+            &attempt.target_branch,
+            &attempt.target_branch,
+            &attempt.branch,
+            github_token,
+        ) {
+            if matches!(e, GitServiceError::MergeConflicts(_))
+                && let Err(abort_err) = self.git.abort_rebase(&worktree_path)
+            {
+                error!(
+                    "Failed to abort merge queue rebase of task attempt {}: {}",
+                    attempt.id, abort_err
+                );
+            }
+            return Err(e.into());
+        }
+
+        if let Some(script) = project
+            .verify_script
+            .as_deref()
+            .filter(|script| !script.trim().is_empty())
+        {
+            MergeQueueEntry::update_status(pool, entry.id, MergeQueueStatus::Verifying, None, None)
+                .await?;
+            verify(&worktree_path, script).await?;
+        }
+
+        MergeQueueEntry::update_status(pool, entry.id, MergeQueueStatus::Merging, None, None)
+            .await?;
+        let scan_config = self.config.read().await.vulnerability_scan.clone();
+        if let Some(reason) = vulnerability_block(
+            &self.db,
+            &self.git,
+            &scan_config,
+            &project,
+            &attempt,
+            &worktree_path,
+        )
+        .await?
+        {
+            return Err(MergeQueueError::Rejected(reason));
+        }
+
+        let merged = self.git.merge_changes(
+            repo_path,
+            &worktree_path,
+            &attempt.branch,
+            &attempt.target_branch,
+            &merge_commit_message(&task),
+        );
+        AuditEvent::new(AuditAction::Merge, &attempt.target_branch)
+            .task(project.id, task.id)
+            .attempt(attempt.id)
+            .parameters(serde_json::json!({
+                "branch": attempt.branch,
+                "strategy": MergeStrategy::Squash,
+                "merge_queue_entry_id": entry.id,
+                "merge_commit": merged.as_ref().ok(),
+            }))
+            .outcome(&merged)
+            .record(pool)
+            .await;
+        let merge_commit = merged?;
+        Merge::create_direct(
+            pool,
+            attempt.id,
+            &attempt.target_branch,
+            &merge_commit,
+            MergeStrategy::Squash,
+        )
+        .await?;
+        Task::update_status(pool, task.id, TaskStatus::Done).await?;
+
+        DeployHookService::spawn_trigger(
+            self.db.clone(),
+            project.id,
+            attempt,
+            DeployHookEvent::Merged,
+            Some(merge_commit.clone()),
+        );
+
+        Ok(merge_commit)
+    }
+}
+
+/// Run the verify script in the worktree, failing with the end of its output if it does
+async fn verify(worktree_path: &Path, script: &str) -> Result<(), MergeQueueError> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let child = tokio::process::Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg(script)
+        .current_dir(worktree_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let output = timeout(VERIFY_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            MergeQueueError::Rejected(format!(
+                "Verify script did not finish within {} minutes",
+                VERIFY_TIMEOUT.as_secs() / 60
+            ))
+        })??;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lines: Vec<&str> = combined.lines().collect();
+    let tail = lines[lines.len().saturating_sub(VERIFY_OUTPUT_LINES)..].join("\n");
+    Err(MergeQueueError::Rejected(format!(
+        "Verify script failed ({}):\n{}",
+        output.status, tail
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn verify_fails_with_the_end_of_the_output() {
+        let dir = std::env::temp_dir();
+        verify(&dir, "exit 0").await.unwrap();
+
+        let err = verify(&dir, "for i in $(seq 1 30); do echo line$i; done; exit 3")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Verify script failed"));
+        assert!(err.ends_with("line30"));
+        assert!(err.contains("line11"));
+        assert!(!err.contains("line10\n"));
+    }
+}
@@ -1,19 +1,72 @@
 pub mod analytics;
+pub mod approval_policy;
 pub mod approvals;
+pub mod attempt_comparison;
+pub mod attempt_report;
+pub mod attribution;
+pub mod audit_log;
 pub mod auth;
+pub mod automation;
+pub mod base_branch_sync;
+pub mod chat_notifier;
+pub mod checkpoint;
 pub mod config;
 pub mod container;
+pub mod dependency_diff;
+pub mod deploy_hook;
+pub mod dev_server;
 pub mod diff_stream;
 pub mod drafts;
+pub mod egress_proxy;
+pub mod email_notification;
+pub mod event_journal;
 pub mod events;
+pub mod execution_metrics;
+pub mod execution_retry;
+pub mod failure_action;
 pub mod file_ranker;
 pub mod file_search_cache;
 pub mod filesystem;
 pub mod filesystem_watcher;
 pub mod git;
 pub mod git_cli;
+pub mod github_issue_sync;
 pub mod github_service;
+pub mod i18n;
 pub mod image;
+pub mod issue_tracker;
+pub mod log_export;
+pub mod log_retention;
+pub mod log_search;
+pub mod merge_queue;
 pub mod notification;
+pub mod notification_feed;
 pub mod pr_monitor;
+pub mod process_tree;
+pub mod project_archive;
+pub mod project_clone;
+pub mod project_env;
+pub mod project_locale;
+pub mod prompt_snippets;
+pub mod public_url;
+pub mod quiet_hours;
+pub mod rate_limit;
+pub mod repo_health;
+pub mod resource_usage;
+pub mod setup_cache;
+pub mod share_link;
+pub mod shutdown;
+pub mod storage;
+pub mod task_import;
+pub mod task_inbox;
+pub mod task_schedule;
+pub mod task_template;
+pub mod telemetry;
+pub mod terminal;
+pub mod test_report;
+pub mod vulnerability_scan;
+pub mod webhook;
+pub mod wip_limits;
+pub mod workflow_monitor;
+pub mod worktree_gc;
 pub mod worktree_manager;
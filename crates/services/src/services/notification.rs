@@ -3,7 +3,10 @@ use std::sync::OnceLock;
 use db::models::execution_process::{ExecutionContext, ExecutionProcessStatus};
 use utils;
 
-use crate::services::config::SoundFile;
+use crate::services::{
+    config::SoundFile,
+    i18n::{Locale, Message},
+};
 
 /// Service for handling cross-platform notifications including sound alerts and push notifications
 #[derive(Debug, Clone)]
@@ -14,26 +17,36 @@ use crate::services::config::NotificationConfig;
 static WSL_ROOT_PATH_CACHE: OnceLock<Option<String>> = OnceLock::new();
 
 impl NotificationService {
-    pub async fn notify_execution_halted(mut config: NotificationConfig, ctx: &ExecutionContext) {
+    pub async fn notify_execution_halted(
+        mut config: NotificationConfig,
+        locale: Locale,
+        ctx: &ExecutionContext,
+    ) {
         // If the process was intentionally killed by user, suppress sound
         if matches!(ctx.execution_process.status, ExecutionProcessStatus::Killed) {
             config.sound_enabled = false;
         }
 
-        let title = format!("Task Complete: {}", ctx.task.title);
+        let task = ctx.task.title.as_str();
+        let branch = ctx.task_attempt.branch.as_str();
+        let executor = ctx.task_attempt.executor.as_str();
+        let title = Message::TaskCompleteTitle { task }.localize(locale);
         let message = match ctx.execution_process.status {
-            ExecutionProcessStatus::Completed => format!(
-                "✅ '{}' completed successfully\nBranch: {:?}\nExecutor: {}",
-                ctx.task.title, ctx.task_attempt.branch, ctx.task_attempt.executor
-            ),
-            ExecutionProcessStatus::Failed => format!(
-                "❌ '{}' execution failed\nBranch: {:?}\nExecutor: {}",
-                ctx.task.title, ctx.task_attempt.branch, ctx.task_attempt.executor
-            ),
-            ExecutionProcessStatus::Killed => format!(
-                "🛑 '{}' execution cancelled by user\nBranch: {:?}\nExecutor: {}",
-                ctx.task.title, ctx.task_attempt.branch, ctx.task_attempt.executor
-            ),
+            ExecutionProcessStatus::Completed => Message::TaskCompleted {
+                task,
+                branch,
+                executor,
+            },
+            ExecutionProcessStatus::Failed => Message::TaskFailed {
+                task,
+                branch,
+                executor,
+            },
+            ExecutionProcessStatus::Killed => Message::TaskCancelled {
+                task,
+                branch,
+                executor,
+            },
             _ => {
                 tracing::warn!(
                     "Tried to notify attempt completion for {} but process is still running!",
@@ -41,7 +54,8 @@ impl NotificationService {
                 );
                 return;
             }
-        };
+        }
+        .localize(locale);
         Self::notify(config, &title, &message).await;
     }
 
@@ -0,0 +1,328 @@
+//! Notifications for the user, published on one stream so the frontend and OS integrations
+//! don't each have to work out from raw database events what is worth telling the user.
+//!
+//! The events pipeline hands occurrences to [`spawn_publish`], and the rules in
+//! [`NotificationTrigger::notification`] decide which of them become a notification: a coding
+//! agent finishing or failing, a setup or cleanup script failing, a coding agent asking for
+//! approval and an attempt being merged. The feed keeps the latest notifications, so clients
+//! that reconnect can catch up on what they missed.
+
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, OnceLock},
+};
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+        merge::{Merge, MergeStatus},
+        task::Task,
+        task_attempt::TaskAttempt,
+    },
+};
+use futures::{StreamExt, stream::BoxStream};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use ts_rs::TS;
+use utils::approvals::ApprovalRequest;
+use uuid::Uuid;
+
+/// Notifications kept for clients that connect later
+const RECENT_LIMIT: usize = 100;
+
+static FEED: OnceLock<NotificationFeed> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationSeverity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    AgentCompleted,
+    AgentFailed,
+    ScriptFailed,
+    ApprovalRequested,
+    MergeCompleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct AppNotification {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    pub severity: NotificationSeverity,
+    pub title: String,
+    pub body: String,
+    /// Path of the page in the app the notification is about
+    pub link: String,
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Something that happened which may be worth a notification
+#[derive(Debug, Clone)]
+pub enum NotificationTrigger {
+    ExecutionProcess(ExecutionProcess),
+    ApprovalRequested(ApprovalRequest),
+    Merge(Merge),
+}
+
+impl NotificationTrigger {
+    /// Identifies the occurrence, so the same one is announced only once
+    fn key(&self) -> String {
+        match self {
+            Self::ExecutionProcess(process) => format!("execution_process:{}", process.id),
+            Self::ApprovalRequested(request) => format!("approval:{}", request.id),
+            Self::Merge(Merge::Direct(merge)) => format!("merge:{}", merge.id),
+            Self::Merge(Merge::Pr(merge)) => format!("merge:{}", merge.id),
+        }
+    }
+
+    /// The notification the rules make of the trigger, for the attempt it happened on
+    fn notification(&self, task: &Task, attempt: &TaskAttempt) -> Option<AppNotification> {
+        let (kind, severity, title, body) = match self {
+            Self::ExecutionProcess(process) => match (&process.run_reason, &process.status) {
+                (ExecutionProcessRunReason::CodingAgent, ExecutionProcessStatus::Completed) => (
+                    NotificationKind::AgentCompleted,
+                    NotificationSeverity::Success,
+                    format!("{} finished", attempt.executor),
+                    format!("\"{}\" is ready for review", task.title),
+                ),
+                (ExecutionProcessRunReason::CodingAgent, ExecutionProcessStatus::Failed) => (
+                    NotificationKind::AgentFailed,
+                    NotificationSeverity::Error,
+                    format!("{} failed", attempt.executor),
+                    match process.exit_code {
+                        Some(code) => format!("\"{}\" stopped with exit code {code}", task.title),
+                        None => format!("\"{}\" stopped unexpectedly", task.title),
+                    },
+                ),
+                (
+                    ExecutionProcessRunReason::SetupScript
+                    | ExecutionProcessRunReason::CleanupScript,
+                    ExecutionProcessStatus::Failed,
+                ) => {
+                    let script = if process.run_reason == ExecutionProcessRunReason::SetupScript {
+                        "Setup script"
+                    } else {
+                        "Cleanup script"
+                    };
+                    (
+                        NotificationKind::ScriptFailed,
+                        NotificationSeverity::Warning,
+                        format!("{script} failed"),
+                        format!("On \"{}\" ({})", task.title, attempt.branch),
+                    )
+                }
+                _ => return None,
+            },
+            Self::ApprovalRequested(request) => (
+                NotificationKind::ApprovalRequested,
+                NotificationSeverity::Warning,
+                "Approval needed".to_string(),
+                format!(
+                    "{} wants to use {} on \"{}\"",
+                    attempt.executor, request.tool_name, task.title
+                ),
+            ),
+            Self::Merge(merge) => {
+                let target_branch = match merge {
+                    Merge::Direct(merge) => &merge.target_branch_name,
+                    Merge::Pr(merge) if matches!(merge.pr_info.status, MergeStatus::Merged) => {
+                        &merge.target_branch_name
+                    }
+                    Merge::Pr(_) => return None,
+                };
+                (
+                    NotificationKind::MergeCompleted,
+                    NotificationSeverity::Success,
+                    "Merged".to_string(),
+                    format!("\"{}\" was merged into {target_branch}", task.title),
+                )
+            }
+        };
+
+        Some(AppNotification {
+            id: Uuid::new_v4(),
+            kind,
+            severity,
+            title,
+            body,
+            link: format!(
+                "/projects/{}/tasks/{}/attempts/{}",
+                task.project_id, task.id, attempt.id
+            ),
+            project_id: task.project_id,
+            task_id: task.id,
+            task_attempt_id: attempt.id,
+            created_at: Utc::now(),
+        })
+    }
+
+    async fn task_attempt_id(&self, db: &DBService) -> Result<Option<Uuid>, sqlx::Error> {
+        Ok(match self {
+            Self::ExecutionProcess(process) => Some(process.task_attempt_id),
+            Self::Merge(Merge::Direct(merge)) => Some(merge.task_attempt_id),
+            Self::Merge(Merge::Pr(merge)) => Some(merge.task_attempt_id),
+            Self::ApprovalRequested(request) => {
+                ExecutionProcess::find_by_id(&db.pool, request.execution_process_id)
+                    .await?
+                    .map(|process| process.task_attempt_id)
+            }
+        })
+    }
+}
+
+/// The process-wide notification stream
+pub struct NotificationFeed {
+    sender: broadcast::Sender<AppNotification>,
+    /// Latest notifications, oldest first, with the key of the occurrence they announce
+    recent: Mutex<VecDeque<(String, AppNotification)>>,
+}
+
+impl NotificationFeed {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(64);
+        Self {
+            sender,
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_LIMIT)),
+        }
+    }
+
+    pub fn global() -> &'static Self {
+        FEED.get_or_init(Self::new)
+    }
+
+    /// Publish the notification, unless the occurrence `key` was announced already
+    fn publish(&self, key: String, notification: AppNotification) -> bool {
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.iter().any(|(published, _)| *published == key) {
+                return false;
+            }
+            if recent.len() == RECENT_LIMIT {
+                recent.pop_front();
+            }
+            recent.push_back((key, notification.clone()));
+        }
+        let _ = self.sender.send(notification);
+        true
+    }
+
+    /// Latest notifications, newest first
+    pub fn recent(&self) -> Vec<AppNotification> {
+        let recent = self.recent.lock().unwrap();
+        recent
+            .iter()
+            .rev()
+            .map(|(_, notification)| notification.clone())
+            .collect()
+    }
+
+    /// The notifications published after `last_id`, when it is still among the recent ones,
+    /// then every new one
+    pub fn stream(&self, last_id: Option<Uuid>) -> BoxStream<'static, AppNotification> {
+        let receiver = self.sender.subscribe();
+        let missed: Vec<_> = {
+            let recent = self.recent.lock().unwrap();
+            match last_id.and_then(|id| recent.iter().position(|(_, n)| n.id == id)) {
+                Some(position) => recent
+                    .iter()
+                    .skip(position + 1)
+                    .map(|(_, notification)| notification.clone())
+                    .collect(),
+                None => Vec::new(),
+            }
+        };
+        futures::stream::iter(missed)
+            .chain(
+                BroadcastStream::new(receiver)
+                    .filter_map(|notification| async move { notification.ok() }),
+            )
+            .boxed()
+    }
+}
+
+/// Apply the notification rules to the trigger, publishing the result, in the background
+pub fn spawn_publish(db: DBService, trigger: NotificationTrigger) {
+    tokio::spawn(async move {
+        if let Err(e) = publish(&db, &trigger).await {
+            tracing::warn!(
+                "Failed to publish notification for {}: {}",
+                trigger.key(),
+                e
+            );
+        }
+    });
+}
+
+async fn publish(db: &DBService, trigger: &NotificationTrigger) -> Result<(), sqlx::Error> {
+    let Some(attempt_id) = trigger.task_attempt_id(db).await? else {
+        return Ok(());
+    };
+    let Some(attempt) = TaskAttempt::find_by_id(&db.pool, attempt_id).await? else {
+        return Ok(());
+    };
+    let Some(task) = Task::find_by_id(&db.pool, attempt.task_id).await? else {
+        return Ok(());
+    };
+    if let Some(notification) = trigger.notification(&task, &attempt) {
+        NotificationFeed::global().publish(trigger.key(), notification);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(title: &str) -> AppNotification {
+        AppNotification {
+            id: Uuid::new_v4(),
+            kind: NotificationKind::AgentCompleted,
+            severity: NotificationSeverity::Success,
+            title: title.to_string(),
+            body: String::new(),
+            link: String::new(),
+            project_id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            task_attempt_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn announces_each_occurrence_once() {
+        let feed = NotificationFeed::new();
+        assert!(feed.publish("execution_process:1".to_string(), notification("first")));
+        assert!(!feed.publish("execution_process:1".to_string(), notification("again")));
+        assert!(feed.publish("execution_process:2".to_string(), notification("second")));
+
+        let titles: Vec<_> = feed.recent().into_iter().map(|n| n.title).collect();
+        assert_eq!(titles, ["second", "first"]);
+    }
+
+    #[tokio::test]
+    async fn replays_notifications_missed_since_the_last_one_seen() {
+        let feed = NotificationFeed::new();
+        let first = notification("first");
+        feed.publish("a".to_string(), first.clone());
+        feed.publish("b".to_string(), notification("second"));
+
+        let mut stream = feed.stream(Some(first.id));
+        feed.publish("c".to_string(), notification("third"));
+
+        assert_eq!(stream.next().await.unwrap().title, "second");
+        assert_eq!(stream.next().await.unwrap().title, "third");
+    }
+}
@@ -0,0 +1,283 @@
+//! Lists the OS processes an execution has started, including ones that moved to their own
+//! process group and would survive a kill of the execution's group.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// One row of `ps` output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgid: u32,
+    pub rss_kb: u64,
+    pub elapsed_secs: u64,
+    /// CPU time used over the process's lifetime
+    pub cpu_time_ms: u64,
+    pub command: String,
+}
+
+/// A live process started by an execution, with the processes it started
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProcessTreeNode {
+    pub pid: u32,
+    pub pgid: u32,
+    pub command: String,
+    pub memory_kb: u32,
+    pub elapsed_secs: u32,
+    /// False for processes that left the execution's process group
+    pub in_process_group: bool,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+/// How far a process's start may be from its execution's recorded start for it to still be
+/// taken for the process the execution spawned
+const START_TOLERANCE_SECS: u64 = 60;
+
+/// Snapshot of every process on the machine. `None` when it cannot be taken, including on
+/// Windows.
+pub async fn snapshot() -> Option<Vec<ProcessEntry>> {
+    #[cfg(unix)]
+    {
+        let output = tokio::process::Command::new("ps")
+            .args(["-A", "-o", "pid=,ppid=,pgid=,rss=,etime=,time=,args="])
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// Parse `ps -o pid=,ppid=,pgid=,rss=,etime=,time=,args=` output, skipping malformed lines
+pub fn parse_ps(output: &str) -> Vec<ProcessEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let pgid = fields.next()?.parse().ok()?;
+            let rss_kb = fields.next()?.parse().ok()?;
+            let elapsed_secs = parse_elapsed(fields.next()?)?;
+            let cpu_time_ms = parse_cpu_time(fields.next()?)?;
+            let command = fields.collect::<Vec<_>>().join(" ");
+            Some(ProcessEntry {
+                pid,
+                ppid,
+                pgid,
+                rss_kb,
+                elapsed_secs,
+                cpu_time_ms,
+                command,
+            })
+        })
+        .collect()
+}
+
+/// Parse the `[[dd-]hh:]mm:ss` format of `ps -o etime`
+fn parse_elapsed(value: &str) -> Option<u64> {
+    let (days, clock) = match value.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, value),
+    };
+    let mut secs = 0;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86_400 + secs)
+}
+
+/// Parse `ps -o time`: `[dd-]hh:mm:ss` on Linux, `mm:ss.cc` with hundredths on macOS
+fn parse_cpu_time(value: &str) -> Option<u64> {
+    let (clock, fraction) = match value.split_once('.') {
+        Some((clock, fraction)) => (clock, fraction),
+        None => (value, ""),
+    };
+    let mut fraction_ms = 0;
+    for (digit, scale) in fraction.chars().zip([100, 10, 1]) {
+        fraction_ms += u64::from(digit.to_digit(10)?) * scale;
+    }
+    Some(parse_elapsed(clock)? * 1_000 + fraction_ms)
+}
+
+/// Processes belonging to the execution led by `root_pid`: its process group plus every
+/// descendant, wherever it moved
+pub fn members(entries: &[ProcessEntry], root_pid: u32) -> HashSet<u32> {
+    let Some(root) = entries.iter().find(|entry| entry.pid == root_pid) else {
+        return HashSet::new();
+    };
+    let mut members: HashSet<u32> = entries
+        .iter()
+        .filter(|entry| entry.pgid == root.pgid)
+        .map(|entry| entry.pid)
+        .collect();
+    members.insert(root_pid);
+    loop {
+        let before = members.len();
+        for entry in entries {
+            if members.contains(&entry.ppid) {
+                members.insert(entry.pid);
+            }
+        }
+        if members.len() == before {
+            return members;
+        }
+    }
+}
+
+/// Processes still running for an execution that spawned `pid` `age_secs` ago, for cleaning up
+/// after a server that died without stopping it. Empty when `pid` is gone, no longer leads its
+/// process group or started at another time, as then the id belongs to an unrelated process.
+pub fn orphaned_members(entries: &[ProcessEntry], pid: u32, age_secs: u64) -> Vec<u32> {
+    let is_leader = entries.iter().any(|entry| {
+        entry.pid == pid
+            && entry.pgid == pid
+            && entry.elapsed_secs.abs_diff(age_secs) <= START_TOLERANCE_SECS
+    });
+    if !is_leader {
+        return Vec::new();
+    }
+    let mut members: Vec<u32> = members(entries, pid).into_iter().collect();
+    members.sort_unstable();
+    members
+}
+
+/// `pid` and all of its descendants, children first so they are signalled before their parents
+pub fn descendants(entries: &[ProcessEntry], pid: u32) -> Vec<u32> {
+    let mut found = vec![pid];
+    let mut index = 0;
+    while index < found.len() {
+        let parent = found[index];
+        found.extend(
+            entries
+                .iter()
+                .filter(|entry| entry.ppid == parent && !found.contains(&entry.pid))
+                .map(|entry| entry.pid)
+                .collect::<Vec<_>>(),
+        );
+        index += 1;
+    }
+    found.reverse();
+    found
+}
+
+/// The execution's processes as trees. The first root is the process it spawned; processes
+/// whose parent already exited become further roots.
+pub fn build_tree(entries: &[ProcessEntry], root_pid: u32) -> Vec<ProcessTreeNode> {
+    let members = members(entries, root_pid);
+    let Some(group) = entries
+        .iter()
+        .find(|entry| entry.pid == root_pid)
+        .map(|entry| entry.pgid)
+    else {
+        return Vec::new();
+    };
+
+    let mut children: HashMap<u32, Vec<&ProcessEntry>> = HashMap::new();
+    let mut roots = Vec::new();
+    for entry in entries.iter().filter(|entry| members.contains(&entry.pid)) {
+        if entry.pid != root_pid && members.contains(&entry.ppid) {
+            children.entry(entry.ppid).or_default().push(entry);
+        } else {
+            roots.push(entry);
+        }
+    }
+    roots.sort_by_key(|entry| (entry.pid != root_pid, entry.pid));
+
+    fn node(
+        entry: &ProcessEntry,
+        group: u32,
+        children: &HashMap<u32, Vec<&ProcessEntry>>,
+    ) -> ProcessTreeNode {
+        let mut kids: Vec<_> = children.get(&entry.pid).cloned().unwrap_or_default();
+        kids.sort_by_key(|child| child.pid);
+        ProcessTreeNode {
+            pid: entry.pid,
+            pgid: entry.pgid,
+            command: entry.command.clone(),
+            memory_kb: u32::try_from(entry.rss_kb).unwrap_or(u32::MAX),
+            elapsed_secs: u32::try_from(entry.elapsed_secs).unwrap_or(u32::MAX),
+            in_process_group: entry.pgid == group,
+            children: kids
+                .into_iter()
+                .map(|child| node(child, group, children))
+                .collect(),
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|entry| node(entry, group, &children))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS_OUTPUT: &str = "\
+    1     0     1  1200 10-02:00:00 00:01:10 /sbin/init
+  100     1   100  5000    01:05 00:00:12 claude --print
+  101   100   100  9000    00:40 00:00:01 npm test
+  102   101   100 20000    00:39 00:00:30 node jest
+  103   100   103 30000    00:30 00:00:08 vite --port 5173
+  104   103   103  1000    00:29 00:00:02 esbuild --service
+  200     1   200   800    03:00 1-00:00:00 unrelated
+";
+
+    #[test]
+    fn parses_ps_output() {
+        let entries = parse_ps(PS_OUTPUT);
+        assert_eq!(entries.len(), 7);
+        assert_eq!(entries[0].elapsed_secs, 10 * 86_400 + 2 * 3_600);
+        assert_eq!(entries[1].elapsed_secs, 65);
+        assert_eq!(entries[1].cpu_time_ms, 12_000);
+        assert_eq!(entries[6].cpu_time_ms, 86_400_000);
+        assert_eq!(entries[4].command, "vite --port 5173");
+    }
+
+    #[test]
+    fn follows_children_that_left_the_process_group() {
+        let entries = parse_ps(PS_OUTPUT);
+        let tree = build_tree(&entries, 100);
+        assert_eq!(tree.len(), 1);
+        let root = &tree[0];
+        assert_eq!(root.pid, 100);
+        let child_pids: Vec<u32> = root.children.iter().map(|child| child.pid).collect();
+        assert_eq!(child_pids, vec![101, 103]);
+        assert!(!root.children[1].in_process_group);
+        assert_eq!(root.children[1].children[0].pid, 104);
+        assert!(!members(&entries, 100).contains(&200));
+        assert_eq!(descendants(&entries, 103), vec![104, 103]);
+    }
+
+    #[test]
+    fn orphans_are_matched_by_start_time() {
+        let entries = parse_ps(PS_OUTPUT);
+        assert_eq!(
+            orphaned_members(&entries, 100, 70),
+            vec![100, 101, 102, 103, 104]
+        );
+        // The id was reused by a process started long after the execution
+        assert!(orphaned_members(&entries, 100, 3_600).is_empty());
+        // Not the leader of its process group
+        assert!(orphaned_members(&entries, 101, 40).is_empty());
+        assert!(orphaned_members(&entries, 999, 40).is_empty());
+    }
+
+    #[test]
+    fn parses_macos_cpu_time() {
+        assert_eq!(parse_cpu_time("1:02.35"), Some(62_350));
+        assert_eq!(parse_cpu_time("0:00.5"), Some(500));
+        assert_eq!(parse_cpu_time("0:0x.00"), None);
+    }
+}
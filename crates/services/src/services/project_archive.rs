@@ -0,0 +1,543 @@
+//! Portable copies of a project's history, for backups and for moving a project between
+//! machines.
+//!
+//! An archive is a zip holding `manifest.json` (format version and project settings),
+//! `tasks.jsonl`, `task_attempts.jsonl`, `execution_processes.jsonl` and, if asked for,
+//! `logs/<execution process id>.jsonl`. Importing creates a new project around a repository on
+//! this machine and gives every row a fresh id, so an archive can be imported more than once.
+//! Worktrees and coding agent sessions aren't part of an archive: imported attempts are marked
+//! as having no worktree, and processes that were still queued or running are marked killed.
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+};
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessStatus},
+    execution_process_logs::ExecutionProcessLogs,
+    project::{CreateProject, Project},
+    tag::Tag,
+    task::Task,
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+use zip::{ZipArchive, result::ZipError};
+
+use crate::services::log_export;
+
+/// Bumped whenever a change to the archive layout would stop older servers reading it
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const TASKS: &str = "tasks.jsonl";
+const TASK_ATTEMPTS: &str = "task_attempts.jsonl";
+const EXECUTION_PROCESSES: &str = "execution_processes.jsonl";
+/// Largest uncompressed size of a file in an archive, so a small upload can't inflate into
+/// more than the server can hold
+const MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ProjectArchiveError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    TaskAttempt(#[from] TaskAttemptError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Not a project archive: {0}")]
+    Invalid(String),
+    #[error(
+        "Archive format version {0} isn't supported; this server reads version {FORMAT_VERSION}"
+    )]
+    UnsupportedVersion(u32),
+}
+
+impl From<ZipError> for ProjectArchiveError {
+    fn from(e: ZipError) -> Self {
+        ProjectArchiveError::Invalid(e.to_string())
+    }
+}
+
+/// Read on its own first, so a newer archive is refused before its layout is relied on
+#[derive(Debug, Deserialize)]
+struct ManifestVersion {
+    format_version: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    exported_at: DateTime<Utc>,
+    project: Project,
+    includes_logs: bool,
+}
+
+/// A task with the names of its tags, which are shared between projects and matched by name
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedTask {
+    #[serde(flatten)]
+    task: Task,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// The contents of an archive
+#[derive(Debug)]
+struct Archive {
+    manifest: Manifest,
+    tasks: Vec<ArchivedTask>,
+    attempts: Vec<TaskAttempt>,
+    processes: Vec<ExecutionProcess>,
+    logs: HashMap<Uuid, String>,
+}
+
+/// What an import created
+#[derive(Debug, Serialize, TS)]
+pub struct ProjectImportSummary {
+    pub project: Project,
+    pub tasks: u32,
+    pub task_attempts: u32,
+    pub execution_processes: u32,
+    /// Processes whose logs were in the archive
+    pub logs: u32,
+    /// Tags the archive's tasks had that don't exist here, so were left off
+    pub missing_tags: Vec<String>,
+}
+
+fn to_jsonl<T: Serialize>(rows: &[T]) -> serde_json::Result<String> {
+    let mut jsonl = String::new();
+    for row in rows {
+        jsonl.push_str(&serde_json::to_string(row)?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+fn from_jsonl<T: DeserializeOwned>(file: &str, jsonl: &str) -> Result<Vec<T>, ProjectArchiveError> {
+    jsonl
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                ProjectArchiveError::Invalid(format!("{file} line {}: {e}", index + 1))
+            })
+        })
+        .collect()
+}
+
+fn read_file(
+    zip: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<String>, ProjectArchiveError> {
+    read_file_up_to(zip, name, MAX_FILE_SIZE)
+}
+
+fn read_file_up_to(
+    zip: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+    limit: u64,
+) -> Result<Option<String>, ProjectArchiveError> {
+    let file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let too_large = || ProjectArchiveError::Invalid(format!("{name} is larger than {limit} bytes"));
+    if file.size() > limit {
+        return Err(too_large());
+    }
+    // The size in the archive's directory is only what it claims, so stop reading past the limit
+    let mut contents = String::new();
+    file.take(limit + 1).read_to_string(&mut contents)?;
+    if contents.len() as u64 > limit {
+        return Err(too_large());
+    }
+    Ok(Some(contents))
+}
+
+fn read_archive(bytes: &[u8]) -> Result<Archive, ProjectArchiveError> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+    let manifest = read_file(&mut zip, MANIFEST)?
+        .ok_or_else(|| ProjectArchiveError::Invalid(format!("{MANIFEST} is missing")))?;
+    let invalid_manifest =
+        |e: serde_json::Error| ProjectArchiveError::Invalid(format!("{MANIFEST}: {e}"));
+    let version: ManifestVersion = serde_json::from_str(&manifest).map_err(invalid_manifest)?;
+    if version.format_version != FORMAT_VERSION {
+        return Err(ProjectArchiveError::UnsupportedVersion(
+            version.format_version,
+        ));
+    }
+    let manifest: Manifest = serde_json::from_str(&manifest).map_err(invalid_manifest)?;
+
+    let mut jsonl = |name: &str| read_file(&mut zip, name).map(Option::unwrap_or_default);
+    let tasks = from_jsonl(TASKS, &jsonl(TASKS)?)?;
+    let attempts = from_jsonl(TASK_ATTEMPTS, &jsonl(TASK_ATTEMPTS)?)?;
+    let processes: Vec<ExecutionProcess> =
+        from_jsonl(EXECUTION_PROCESSES, &jsonl(EXECUTION_PROCESSES)?)?;
+
+    let mut logs = HashMap::new();
+    if manifest.includes_logs {
+        for process in &processes {
+            if let Some(log) = read_file(&mut zip, &log_file_name(process.id))? {
+                logs.insert(process.id, log);
+            }
+        }
+    }
+
+    Ok(Archive {
+        manifest,
+        tasks,
+        attempts,
+        processes,
+        logs,
+    })
+}
+
+fn log_file_name(execution_process_id: Uuid) -> String {
+    format!("logs/{execution_process_id}.jsonl")
+}
+
+/// Archive file name for a project, e.g. `my-app-2025-12-11.zip`
+pub fn file_name(project: &Project) -> String {
+    let name: String = project
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!(
+        "{}-{}.zip",
+        name.trim_matches('-'),
+        Utc::now().format("%Y-%m-%d")
+    )
+}
+
+/// Zip the project's tasks, attempts and execution processes, and their stored logs if
+/// `include_logs` is set
+pub async fn export(
+    pool: &SqlitePool,
+    project: &Project,
+    include_logs: bool,
+) -> Result<Vec<u8>, ProjectArchiveError> {
+    let mut tasks = Vec::new();
+    let mut attempts = Vec::new();
+    let mut processes = Vec::new();
+    for task in Task::find_by_project_id(pool, project.id).await? {
+        let tags = Task::find_tags_for_task(pool, task.id)
+            .await?
+            .into_iter()
+            .map(|tag| tag.tag_name)
+            .collect();
+        for attempt in TaskAttempt::fetch_all(pool, Some(task.id)).await? {
+            processes
+                .extend(ExecutionProcess::find_by_task_attempt_id(pool, attempt.id, true).await?);
+            attempts.push(attempt);
+        }
+        tasks.push(ArchivedTask { task, tags });
+    }
+
+    let mut logs = Vec::new();
+    if include_logs {
+        for process in &processes {
+            if let Some(stored) =
+                ExecutionProcessLogs::find_by_execution_id(pool, process.id).await?
+            {
+                logs.push((log_file_name(process.id), stored.logs));
+            }
+        }
+    }
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        exported_at: Utc::now(),
+        project: project.clone(),
+        includes_logs: include_logs,
+    };
+    let mut files = vec![
+        (
+            MANIFEST.to_string(),
+            serde_json::to_string_pretty(&manifest).map_err(std::io::Error::other)?,
+        ),
+        (
+            TASKS.to_string(),
+            to_jsonl(&tasks).map_err(std::io::Error::other)?,
+        ),
+        (
+            TASK_ATTEMPTS.to_string(),
+            to_jsonl(&attempts).map_err(std::io::Error::other)?,
+        ),
+        (
+            EXECUTION_PROCESSES.to_string(),
+            to_jsonl(&processes).map_err(std::io::Error::other)?,
+        ),
+    ];
+    files.extend(logs);
+    Ok(log_export::zip_files(files)?)
+}
+
+/// Create a project for the repository at `git_repo_path` from an archive, named `name` or
+/// else as the exported project was. Nothing is created unless the whole archive imports.
+pub async fn import(
+    pool: &SqlitePool,
+    bytes: &[u8],
+    git_repo_path: String,
+    name: Option<String>,
+) -> Result<ProjectImportSummary, ProjectArchiveError> {
+    let Archive {
+        manifest,
+        tasks,
+        attempts,
+        processes,
+        logs,
+    } = read_archive(bytes)?;
+    let source = manifest.project;
+
+    let task_ids: HashMap<Uuid, Uuid> = tasks
+        .iter()
+        .map(|archived| (archived.task.id, Uuid::new_v4()))
+        .collect();
+    let attempt_ids: HashMap<Uuid, Uuid> = attempts
+        .iter()
+        .filter(|attempt| task_ids.contains_key(&attempt.task_id))
+        .map(|attempt| (attempt.id, Uuid::new_v4()))
+        .collect();
+    let process_ids: HashMap<Uuid, Uuid> = processes
+        .iter()
+        .filter(|process| attempt_ids.contains_key(&process.task_attempt_id))
+        .map(|process| (process.id, Uuid::new_v4()))
+        .collect();
+
+    let mut tag_ids = HashMap::new();
+    let mut missing_tags = Vec::new();
+    for name in tasks.iter().flat_map(|archived| &archived.tags) {
+        if tag_ids.contains_key(name) || missing_tags.contains(name) {
+            continue;
+        }
+        match Tag::find_by_name(pool, name).await? {
+            Some(tag) => {
+                tag_ids.insert(name.clone(), tag.id);
+            }
+            None => missing_tags.push(name.clone()),
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let project_id = Uuid::new_v4();
+    let created = Project::create(
+        &mut *tx,
+        &CreateProject {
+            name: name.unwrap_or_else(|| source.name.clone()),
+            git_repo_path,
+            use_existing_repo: true,
+            git_url: None,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        },
+        project_id,
+    )
+    .await?;
+    let project = Project::update(
+        &mut *tx,
+        project_id,
+        created.name,
+        created.git_repo_path.to_string_lossy().to_string(),
+        source.setup_script,
+        source.dev_script,
+        source.cleanup_script,
+        source.copy_files,
+        source.block_merge_on_vulnerabilities,
+        source.sync_base_branch,
+        source.auto_rebase_on_sync,
+        source.push_remote,
+        source.base_remote,
+        source.protected_branches,
+        source.timezone,
+        source.locale,
+        source.quiet_hours.map(|json| json.0),
+        source.sandbox.map(|json| json.0),
+        source.verify_script,
+        source.retry_policy.map(|json| json.0),
+        source.setup_cache.map(|json| json.0),
+        source.post_merge_script,
+        source.on_cleanup_failure.map(|json| json.0),
+        source.test_run.map(|json| json.0),
+        source.approval_policy.map(|json| json.0),
+    )
+    .await?;
+
+    // Parents may come later in the archive, so they're linked once every task exists
+    for ArchivedTask { task, tags } in &tasks {
+        let id = task_ids[&task.id];
+        Task::restore(
+            &mut *tx,
+            &Task {
+                id,
+                project_id,
+                parent_task_attempt: None,
+                parent_task_id: None,
+                column_id: None,
+                ..task.clone()
+            },
+        )
+        .await?;
+        for tag in tags {
+            if let Some(tag_id) = tag_ids.get(tag) {
+                Task::add_tag(&mut *tx, id, *tag_id).await?;
+            }
+        }
+    }
+
+    for attempt in &attempts {
+        let (Some(&id), Some(&task_id)) =
+            (attempt_ids.get(&attempt.id), task_ids.get(&attempt.task_id))
+        else {
+            continue;
+        };
+        TaskAttempt::restore(
+            &mut *tx,
+            &TaskAttempt {
+                id,
+                task_id,
+                ..attempt.clone()
+            },
+        )
+        .await?;
+    }
+
+    for ArchivedTask { task, .. } in &tasks {
+        let parent_task_attempt = task
+            .parent_task_attempt
+            .and_then(|id| attempt_ids.get(&id).copied());
+        let parent_task_id = task
+            .parent_task_id
+            .and_then(|id| task_ids.get(&id).copied());
+        if parent_task_attempt.is_some() || parent_task_id.is_some() {
+            Task::set_parents(
+                &mut *tx,
+                task_ids[&task.id],
+                parent_task_attempt,
+                parent_task_id,
+            )
+            .await?;
+        }
+    }
+
+    // Retries point at earlier processes, so insert in the order they were created
+    let mut processes: Vec<&ExecutionProcess> = processes
+        .iter()
+        .filter(|process| process_ids.contains_key(&process.id))
+        .collect();
+    processes.sort_by_key(|process| process.created_at);
+    for process in &processes {
+        let unfinished = matches!(
+            process.status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        );
+        let id = process_ids[&process.id];
+        ExecutionProcess::restore(
+            &mut *tx,
+            &ExecutionProcess {
+                id,
+                task_attempt_id: attempt_ids[&process.task_attempt_id],
+                status: if unfinished {
+                    ExecutionProcessStatus::Killed
+                } else {
+                    process.status.clone()
+                },
+                completed_at: match process.completed_at {
+                    None if unfinished => Some(process.updated_at),
+                    completed_at => completed_at,
+                },
+                queue_position: None,
+                retry_of: process
+                    .retry_of
+                    .and_then(|id| process_ids.get(&id).copied()),
+                ..(*process).clone()
+            },
+        )
+        .await?;
+        if let Some(log) = logs.get(&process.id) {
+            ExecutionProcessLogs::restore(&mut *tx, id, log, process.updated_at).await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(ProjectImportSummary {
+        project,
+        tasks: task_ids.len() as u32,
+        task_attempts: attempt_ids.len() as u32,
+        execution_processes: process_ids.len() as u32,
+        logs: processes
+            .iter()
+            .filter(|process| logs.contains_key(&process.id))
+            .count() as u32,
+        missing_tags,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zip_archive(manifest: serde_json::Value, tasks: &str) -> Vec<u8> {
+        log_export::zip_files(vec![
+            (MANIFEST.to_string(), manifest.to_string()),
+            (TASKS.to_string(), tasks.to_string()),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn refuses_other_format_versions() {
+        let bytes = zip_archive(serde_json::json!({ "format_version": 2 }), "");
+        assert!(matches!(
+            read_archive(&bytes),
+            Err(ProjectArchiveError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            read_archive(b"not a zip"),
+            Err(ProjectArchiveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn refuses_files_over_the_size_limit() {
+        let bytes = zip_archive(serde_json::json!({ "format_version": 1 }), "0123456789");
+        let mut zip = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        assert_eq!(
+            read_file_up_to(&mut zip, TASKS, 10).unwrap().as_deref(),
+            Some("0123456789")
+        );
+        assert!(matches!(
+            read_file_up_to(&mut zip, TASKS, 9),
+            Err(ProjectArchiveError::Invalid(_))
+        ));
+        assert!(
+            read_file_up_to(&mut zip, TASK_ATTEMPTS, 9)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_a_bad_row() {
+        let rows: Result<Vec<serde_json::Value>, _> =
+            from_jsonl(TASKS, "{\"id\": 1}\n\n{\"id\": \n");
+        match rows {
+            Err(ProjectArchiveError::Invalid(message)) => {
+                assert!(message.starts_with("tasks.jsonl line 3:"), "{message}")
+            }
+            other => panic!("expected an invalid archive, got {other:?}"),
+        }
+        let rows: Vec<serde_json::Value> = from_jsonl(TASKS, &to_jsonl(&[1, 2]).unwrap()).unwrap();
+        assert_eq!(rows, vec![1, 2]);
+    }
+}
@@ -0,0 +1,330 @@
+//! Creating a project from a git URL in one call. The repository is cloned into a directory
+//! the app manages and registered as a project. Cloning runs in the background and reports
+//! its progress as JSON patches on the event stream, at `/project_clones/<clone id>`.
+//!
+//! GitHub HTTPS URLs are cloned with the configured GitHub token; everything else relies on
+//! git's own credentials, i.e. SSH keys and credential helpers.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use db::{
+    DBService,
+    models::project::{CreateProject, Project},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use utils::{assets::asset_dir, msg_store::MsgStore};
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, GitCloneConfig},
+    events::patches::project_clone_patch,
+    git::{GitService, GitServiceError},
+};
+
+#[derive(Debug, Error)]
+pub enum ProjectCloneError {
+    #[error("Not a remote git repository URL: {0}")]
+    InvalidUrl(String),
+    #[error("Project name cannot be empty")]
+    EmptyName,
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Clone task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+    #[error("Failed to create the clone's directory: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CloneProjectRequest {
+    /// HTTPS, SSH (`ssh://` or `git@host:owner/repo`) or `git://` URL of the repository
+    pub git_url: String,
+    /// Defaults to the repository's name
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ProjectCloneStatus {
+    Cloning,
+    Registering,
+    Completed,
+    Failed,
+}
+
+/// A clone started by [`start`], as last pushed to the event stream
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProjectClone {
+    pub id: Uuid,
+    pub git_url: String,
+    pub name: String,
+    /// Where the repository is cloned to
+    pub path: String,
+    pub status: ProjectCloneStatus,
+    /// The phase git is in, e.g. "Receiving objects"
+    pub phase: Option<String>,
+    /// Progress of the current phase
+    pub percent: Option<u8>,
+    /// The registered project, once completed
+    pub project_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+/// Directory cloned repositories are kept in
+pub fn clones_dir() -> PathBuf {
+    asset_dir().join("repos")
+}
+
+/// Whether `url` names a remote repository. Local paths and `file://` URLs are refused, as is
+/// anything git could mistake for an option.
+fn is_remote_url(url: &str) -> bool {
+    if url.starts_with('-') || url.chars().any(char::is_whitespace) {
+        return false;
+    }
+    if let Some((scheme, rest)) = url.split_once("://") {
+        return matches!(scheme, "https" | "http" | "ssh" | "git") && !rest.is_empty();
+    }
+    // scp-like syntax, `[user@]host:path`. A one-letter host is a Windows drive.
+    match url.split_once(':') {
+        Some((host, path)) => host.len() > 1 && !host.contains('/') && !path.is_empty(),
+        None => false,
+    }
+}
+
+/// The repository's name, the last segment of its URL without `.git`
+fn repo_name(url: &str) -> Option<String> {
+    let (_, path) = url.split_once("://").unwrap_or(("", url));
+    let (_, path) = path.split_once(['/', ':'])?;
+    let last = path.trim_end_matches('/').rsplit(['/', ':']).next()?;
+    let name = last.strip_suffix(".git").unwrap_or(last);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Create a new directory in `base` named after `name`, numbered if the name is taken. Creating
+/// it is what reserves it, so concurrent clones of one repository each get their own.
+fn reserve_dir(base: &Path, name: &str) -> std::io::Result<PathBuf> {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    let name = if name.is_empty() { "repo" } else { name };
+
+    std::fs::create_dir_all(base)?;
+    let mut path = base.join(name);
+    let mut suffix = 2;
+    loop {
+        match std::fs::create_dir(&path) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                path = base.join(format!("{name}-{suffix}"));
+                suffix += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The GitHub token is only sent to GitHub
+fn token_for(url: &str, config: &Config) -> Option<String> {
+    url.starts_with("https://github.com/")
+        .then(|| config.github.token())
+        .flatten()
+}
+
+/// Validate the request, pick the clone's directory and start cloning in the background
+pub async fn start(
+    db: DBService,
+    git: GitService,
+    config: Arc<RwLock<Config>>,
+    msg_store: Arc<MsgStore>,
+    request: CloneProjectRequest,
+) -> Result<ProjectClone, ProjectCloneError> {
+    let git_url = request.git_url.trim().to_string();
+    let repo = match repo_name(&git_url) {
+        Some(repo) if is_remote_url(&git_url) => repo,
+        _ => return Err(ProjectCloneError::InvalidUrl(git_url)),
+    };
+    let name = match request.name.map(|name| name.trim().to_string()) {
+        Some(name) if name.is_empty() => return Err(ProjectCloneError::EmptyName),
+        Some(name) => name,
+        None => repo.clone(),
+    };
+    let (token, options) = {
+        let config = config.read().await;
+        (token_for(&git_url, &config), config.git_clone.clone())
+    };
+    let path = reserve_dir(&clones_dir(), &repo)?;
+
+    let clone = ProjectClone {
+        id: Uuid::new_v4(),
+        git_url,
+        name,
+        path: path.to_string_lossy().to_string(),
+        status: ProjectCloneStatus::Cloning,
+        phase: None,
+        percent: None,
+        project_id: None,
+        error: None,
+    };
+    msg_store.push_patch(project_clone_patch::add(&clone));
+
+    tokio::spawn(run(db, git, msg_store, clone.clone(), token, options));
+    Ok(clone)
+}
+
+async fn run(
+    db: DBService,
+    git: GitService,
+    msg_store: Arc<MsgStore>,
+    mut clone: ProjectClone,
+    token: Option<String>,
+    options: GitCloneConfig,
+) {
+    let path = PathBuf::from(&clone.path);
+    match clone_and_register(&db, git, &msg_store, &clone, &path, token, options).await {
+        Ok(project) => {
+            tracing::info!("Cloned {} as project {}", clone.git_url, project.id);
+            clone.status = ProjectCloneStatus::Completed;
+            clone.project_id = Some(project.id);
+        }
+        Err(e) => {
+            tracing::error!("Failed to clone {}: {}", clone.git_url, e);
+            // The directory was reserved for this clone in `start`, so nothing else is in it
+            if let Err(cleanup_err) = std::fs::remove_dir_all(&path) {
+                tracing::error!("Failed to clean up {}: {}", path.display(), cleanup_err);
+            }
+            clone.status = ProjectCloneStatus::Failed;
+            clone.error = Some(e.to_string());
+        }
+    }
+    msg_store.push_patch(project_clone_patch::replace(&clone));
+}
+
+async fn clone_and_register(
+    db: &DBService,
+    git: GitService,
+    msg_store: &Arc<MsgStore>,
+    clone: &ProjectClone,
+    path: &Path,
+    token: Option<String>,
+    options: GitCloneConfig,
+) -> Result<Project, ProjectCloneError> {
+    let url = clone.git_url.clone();
+    let mut progress = clone.clone();
+    let store = msg_store.clone();
+    let target = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        git.clone_repository_with_progress(&url, &target, token.as_deref(), &options, |update| {
+            // git repeats a line for every object; only push when the numbers move
+            if progress.phase.as_deref() == Some(update.phase.as_str())
+                && progress.percent == Some(update.percent)
+            {
+                return;
+            }
+            progress.phase = Some(update.phase);
+            progress.percent = Some(update.percent);
+            store.push_patch(project_clone_patch::replace(&progress));
+        })?;
+        git.ensure_main_branch_exists(&target)
+    })
+    .await??;
+
+    let mut registering = clone.clone();
+    registering.status = ProjectCloneStatus::Registering;
+    msg_store.push_patch(project_clone_patch::replace(&registering));
+
+    let project = Project::create(
+        &db.pool,
+        &CreateProject {
+            name: clone.name.clone(),
+            git_repo_path: clone.path.clone(),
+            use_existing_repo: true,
+            git_url: None,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        },
+        Uuid::new_v4(),
+    )
+    .await?;
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_remote_urls() {
+        for url in [
+            "https://github.com/acme/app.git",
+            "ssh://git@gitlab.com/acme/app",
+            "git@github.com:acme/app.git",
+            "git://example.com/app",
+        ] {
+            assert!(is_remote_url(url), "{url}");
+        }
+        for url in [
+            "/home/me/app",
+            "file:///home/me/app",
+            "C:\\code\\app",
+            "--upload-pack=touch /tmp/x",
+            "https://",
+        ] {
+            assert!(!is_remote_url(url), "{url}");
+        }
+    }
+
+    #[test]
+    fn names_repositories_after_the_last_url_segment() {
+        assert_eq!(
+            repo_name("https://github.com/acme/app.git").as_deref(),
+            Some("app")
+        );
+        assert_eq!(
+            repo_name("git@gitlab.com:acme/tools/cli.git").as_deref(),
+            Some("cli")
+        );
+        assert_eq!(
+            repo_name("ssh://git@host:2222/app/").as_deref(),
+            Some("app")
+        );
+        assert_eq!(repo_name("https://github.com"), None);
+    }
+
+    #[test]
+    fn reserves_an_unused_directory() {
+        let base = tempfile::tempdir().unwrap();
+        std::fs::create_dir(base.path().join("app")).unwrap();
+        assert_eq!(
+            reserve_dir(base.path(), "app").unwrap(),
+            base.path().join("app-2")
+        );
+        assert_eq!(
+            reserve_dir(base.path(), "app").unwrap(),
+            base.path().join("app-3")
+        );
+        assert!(base.path().join("app-3").is_dir());
+        assert_eq!(
+            reserve_dir(base.path(), "../x y").unwrap(),
+            base.path().join("-x-y")
+        );
+    }
+}
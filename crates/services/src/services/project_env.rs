@@ -0,0 +1,255 @@
+//! Environment variables configured per project and given to its setup and cleanup scripts,
+//! dev servers and coding agents.
+//!
+//! Values of secrets are encrypted with AES-256-GCM before they are stored, using a key kept
+//! next to the config file, and are never returned by the API.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{ErrorKind, Write},
+    path::Path,
+    sync::OnceLock,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use chrono::{DateTime, Utc};
+use db::models::project_env_var::ProjectEnvVar;
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+static CIPHER: OnceLock<SecretCipher> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum ProjectEnvError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(
+        "Invalid environment variable name `{0}`: use letters, digits and `_`, not starting with a digit"
+    )]
+    InvalidKey(String),
+    #[error("Failed to encrypt or decrypt a secret")]
+    Crypto,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ProjectEnvVarResponse {
+    pub key: String,
+    /// Not returned for secrets
+    pub value: Option<String>,
+    pub secret: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ProjectEnvVar> for ProjectEnvVarResponse {
+    fn from(var: ProjectEnvVar) -> Self {
+        Self {
+            value: (!var.secret).then_some(var.value),
+            key: var.key,
+            secret: var.secret,
+            updated_at: var.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpsertProjectEnvVar {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// Encrypts secrets for storage. Each value is bound to its project and name, so an encrypted
+/// value copied to another variable fails to decrypt.
+pub struct SecretCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            key: LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("32-byte key")),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// The cipher using the key in the asset directory, created on first use
+    pub fn global() -> Result<&'static Self, ProjectEnvError> {
+        if let Some(cipher) = CIPHER.get() {
+            return Ok(cipher);
+        }
+        let cipher = Self::load_or_create(&utils::assets::secret_key_path())?;
+        Ok(CIPHER.get_or_init(|| cipher))
+    }
+
+    /// Read the key at `path`, writing a new random key readable only by the current user if
+    /// there is none
+    pub fn load_or_create(path: &Path) -> Result<Self, ProjectEnvError> {
+        let mut key = [0u8; 32];
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(path) {
+            Ok(mut file) => {
+                SystemRandom::new()
+                    .fill(&mut key)
+                    .map_err(|_| ProjectEnvError::Crypto)?;
+                file.write_all(&key)?;
+                tracing::info!("Created secret key at {}", path.display());
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                key = fs::read(path)?.try_into().map_err(|_| {
+                    std::io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{} is not a 32-byte key", path.display()),
+                    )
+                })?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        Ok(Self::new(&key))
+    }
+
+    pub fn encrypt(&self, plaintext: &str, context: &str) -> Result<String, ProjectEnvError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| ProjectEnvError::Crypto)?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(context.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| ProjectEnvError::Crypto)?;
+        Ok(BASE64_STANDARD.encode([nonce.as_slice(), &sealed].concat()))
+    }
+
+    pub fn decrypt(&self, encrypted: &str, context: &str) -> Result<String, ProjectEnvError> {
+        let data = BASE64_STANDARD
+            .decode(encrypted)
+            .map_err(|_| ProjectEnvError::Crypto)?;
+        if data.len() < NONCE_LEN {
+            return Err(ProjectEnvError::Crypto);
+        }
+        let (nonce, sealed) = data.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| ProjectEnvError::Crypto)?;
+        let mut sealed = sealed.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut sealed)
+            .map_err(|_| ProjectEnvError::Crypto)?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| ProjectEnvError::Crypto)
+    }
+}
+
+/// Whether `key` can be used as an environment variable name
+pub fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn secret_context(project_id: Uuid, key: &str) -> String {
+    format!("{project_id}/{key}")
+}
+
+pub async fn list(
+    pool: &SqlitePool,
+    project_id: Uuid,
+) -> Result<Vec<ProjectEnvVarResponse>, ProjectEnvError> {
+    Ok(ProjectEnvVar::find_by_project_id(pool, project_id)
+        .await?
+        .into_iter()
+        .map(ProjectEnvVarResponse::from)
+        .collect())
+}
+
+/// Create or replace a variable, encrypting its value if it is a secret
+pub async fn set(
+    pool: &SqlitePool,
+    cipher: &SecretCipher,
+    project_id: Uuid,
+    data: &UpsertProjectEnvVar,
+) -> Result<ProjectEnvVarResponse, ProjectEnvError> {
+    let key = data.key.trim();
+    if !is_valid_key(key) {
+        return Err(ProjectEnvError::InvalidKey(key.to_string()));
+    }
+    let value = if data.secret {
+        cipher.encrypt(&data.value, &secret_context(project_id, key))?
+    } else {
+        data.value.clone()
+    };
+    let var = ProjectEnvVar::upsert(pool, project_id, key, &value, data.secret).await?;
+    Ok(var.into())
+}
+
+/// The project's variables with secrets decrypted, as given to its processes
+pub async fn resolve(
+    pool: &SqlitePool,
+    cipher: &SecretCipher,
+    project_id: Uuid,
+) -> Result<HashMap<String, String>, ProjectEnvError> {
+    ProjectEnvVar::find_by_project_id(pool, project_id)
+        .await?
+        .into_iter()
+        .map(|var| {
+            let value = if var.secret {
+                cipher.decrypt(&var.value, &secret_context(project_id, &var.key))?
+            } else {
+                var.value
+            };
+            Ok((var.key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_only_decrypt_for_the_variable_they_were_encrypted_for() {
+        let cipher = SecretCipher::new(&[7; 32]);
+        let project_id = Uuid::new_v4();
+        let context = secret_context(project_id, "API_KEY");
+
+        let encrypted = cipher.encrypt("sk-123", &context).unwrap();
+        assert!(!encrypted.contains("sk-123"));
+        assert_ne!(encrypted, cipher.encrypt("sk-123", &context).unwrap());
+        assert_eq!(cipher.decrypt(&encrypted, &context).unwrap(), "sk-123");
+
+        let other = secret_context(project_id, "OTHER_KEY");
+        assert!(cipher.decrypt(&encrypted, &other).is_err());
+        assert!(
+            SecretCipher::new(&[8; 32])
+                .decrypt(&encrypted, &context)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn validates_variable_names() {
+        assert!(is_valid_key("API_KEY"));
+        assert!(is_valid_key("_private2"));
+        assert!(!is_valid_key("2FA"));
+        assert!(!is_valid_key("MY-KEY"));
+        assert!(!is_valid_key(""));
+    }
+}
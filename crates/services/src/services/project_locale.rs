@@ -0,0 +1,89 @@
+//! Resolves a [`Project`]'s time zone and language, so schedules, reports and generated
+//! messages use the project's settings instead of the server's.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use db::models::project::Project;
+use thiserror::Error;
+
+use crate::services::{config::UiLanguage, i18n::Locale};
+
+#[derive(Debug, Error)]
+pub enum ProjectLocaleError {
+    #[error("Unknown time zone: {0}")]
+    InvalidTimezone(String),
+    #[error("Unsupported language '{0}', expected one of en, es, ja, ko")]
+    UnsupportedLocale(String),
+}
+
+/// Parse an IANA time zone name such as `America/New_York`
+pub fn parse_timezone(name: &str) -> Result<Tz, ProjectLocaleError> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| ProjectLocaleError::InvalidTimezone(name.to_string()))
+}
+
+/// Parse a language tag such as `es` or `ja-JP` into a supported locale
+pub fn parse_locale(tag: &str) -> Result<Locale, ProjectLocaleError> {
+    Locale::from_tag(tag).ok_or_else(|| ProjectLocaleError::UnsupportedLocale(tag.to_string()))
+}
+
+/// Wall-clock time of `instant` in the project's time zone. Projects without a valid time zone
+/// use the server's local time.
+pub fn local_time(project: &Project, instant: DateTime<Utc>) -> NaiveDateTime {
+    in_timezone(project.timezone.as_deref(), instant)
+}
+
+/// Like [`local_time`], for callers that only have the project's time zone setting
+pub fn in_timezone(timezone: Option<&str>, instant: DateTime<Utc>) -> NaiveDateTime {
+    match timezone.and_then(|name| parse_timezone(name).ok()) {
+        Some(tz) => instant.with_timezone(&tz).naive_local(),
+        None => instant.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// The instant at which the wall clock in `timezone` shows `local`; the earlier one if the
+/// clocks go back over it, and `None` if they skip it
+pub fn from_local(timezone: Option<&str>, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    match timezone.and_then(|name| parse_timezone(name).ok()) {
+        Some(tz) => tz
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|instant| instant.with_timezone(&Utc)),
+        None => Local
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|instant| instant.with_timezone(&Utc)),
+    }
+}
+
+/// Language for messages generated for the project: its own setting, or the configured one
+pub fn locale(project: &Project, language: &UiLanguage) -> Locale {
+    project
+        .locale
+        .as_deref()
+        .and_then(Locale::from_tag)
+        .unwrap_or_else(|| Locale::from_config(language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_the_project_time_zone() {
+        let instant = Utc.with_ymd_and_hms(2025, 11, 3, 23, 30, 0).unwrap();
+        let tokyo = in_timezone(Some("Asia/Tokyo"), instant);
+        assert_eq!(tokyo.to_string(), "2025-11-04 08:30:00");
+        let new_york = in_timezone(Some("America/New_York"), instant);
+        assert_eq!(new_york.to_string(), "2025-11-03 18:30:00");
+    }
+
+    #[test]
+    fn rejects_unknown_zones_and_languages() {
+        assert!(parse_timezone("Europe/Berlin").is_ok());
+        assert!(parse_timezone("Mars/Olympus").is_err());
+        assert_eq!(parse_locale("ja-JP").unwrap(), Locale::Ja);
+        assert!(parse_locale("fr").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Expands `/name` references to [`PromptSnippet`]s in prompts sent to coding agents.
+//!
+//! A reference must stand on its own: it starts the prompt or follows whitespace, and ends
+//! the prompt or is followed by whitespace, so paths like `/usr/bin` are left alone. Unknown
+//! names are kept as typed, and snippet content is not expanded again.
+
+use std::collections::HashMap;
+
+use db::models::prompt_snippet::PromptSnippet;
+use regex::{Captures, Regex};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+lazy_static::lazy_static! {
+    static ref SNIPPET_REFERENCE: Regex = Regex::new(r"/([a-z0-9][a-z0-9_\-]*)").unwrap();
+    static ref SNIPPET_NAME: Regex = Regex::new(r"^[a-z0-9][a-z0-9_\-]*$").unwrap();
+}
+
+/// Whether `name` can be referenced as `/name`: lowercase letters, digits, `-` and `_`
+pub fn is_valid_name(name: &str) -> bool {
+    SNIPPET_NAME.is_match(name)
+}
+
+/// Replace snippet references in `prompt`. Later snippets of the same name take precedence,
+/// so pass global snippets before project ones.
+pub fn expand(prompt: &str, snippets: &[PromptSnippet]) -> String {
+    if snippets.is_empty() {
+        return prompt.to_string();
+    }
+    let by_name: HashMap<&str, &str> = snippets
+        .iter()
+        .map(|snippet| (snippet.name.as_str(), snippet.content.as_str()))
+        .collect();
+
+    SNIPPET_REFERENCE
+        .replace_all(prompt, |caps: &Captures| {
+            let reference = caps.get(0).unwrap();
+            let standalone = prompt[..reference.start()]
+                .chars()
+                .next_back()
+                .is_none_or(char::is_whitespace)
+                && prompt[reference.end()..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace);
+            match by_name.get(&caps[1]) {
+                Some(content) if standalone => content.trim().to_string(),
+                _ => reference.as_str().to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Expand `prompt` with the global snippets and those of `project_id`
+pub async fn expand_for_project(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    prompt: &str,
+) -> Result<String, sqlx::Error> {
+    if !prompt.contains('/') {
+        return Ok(prompt.to_string());
+    }
+    let mut snippets = PromptSnippet::find_all(pool, Some(project_id)).await?;
+    // Project snippets override global ones with the same name
+    snippets.sort_by_key(|snippet| snippet.project_id.is_some());
+    Ok(expand(prompt, &snippets))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn snippet(project_id: Option<Uuid>, name: &str, content: &str) -> PromptSnippet {
+        PromptSnippet {
+            id: Uuid::new_v4(),
+            project_id,
+            name: name.to_string(),
+            content: content.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn expands_standalone_references_only() {
+        let snippets = vec![
+            snippet(None, "tests", "Run the full test suite before finishing.\n"),
+            snippet(None, "usr", "never used"),
+        ];
+        assert_eq!(
+            expand("Fix the bug.\n/tests\nThen /tests again", &snippets),
+            "Fix the bug.\nRun the full test suite before finishing.\nThen Run the full test suite before finishing. again"
+        );
+        assert_eq!(
+            expand("Look in /usr/bin and a/usr and /unknown", &snippets),
+            "Look in /usr/bin and a/usr and /unknown"
+        );
+    }
+
+    #[test]
+    fn later_snippets_override_earlier_ones() {
+        let snippets = vec![
+            snippet(None, "style", "global"),
+            snippet(Some(Uuid::new_v4()), "style", "project"),
+        ];
+        assert_eq!(expand("/style", &snippets), "project");
+    }
+
+    #[test]
+    fn validates_names() {
+        assert!(is_valid_name("review-checklist"));
+        assert!(is_valid_name("tests_2"));
+        assert!(!is_valid_name("Tests"));
+        assert!(!is_valid_name("-tests"));
+        assert!(!is_valid_name("run tests"));
+    }
+}
@@ -0,0 +1,229 @@
+//! The address the app is reached at, for the absolute links it hands out.
+//!
+//! The configured public URL wins. Share links otherwise use the address the request creating
+//! them was made to, as a trusted reverse proxy reports it in `Forwarded` or
+//! `X-Forwarded-Proto` and `X-Forwarded-Host`, and notifications the address the server
+//! listens on.
+
+use std::{
+    net::IpAddr,
+    sync::{Arc, OnceLock},
+};
+
+use axum::http::{HeaderMap, header};
+use tokio::sync::RwLock;
+
+use crate::services::config::Config;
+
+static LOCAL_ORIGIN: OnceLock<String> = OnceLock::new();
+
+/// Set the origin of the server on this machine, e.g. `https://127.0.0.1:3000`, once it listens
+pub fn set_local_origin(origin: String) {
+    let _ = LOCAL_ORIGIN.set(origin);
+}
+
+/// The scheme the server itself is reached with
+pub fn local_scheme() -> &'static str {
+    match LOCAL_ORIGIN.get() {
+        Some(origin) if origin.starts_with("https://") => "https",
+        _ => "http",
+    }
+}
+
+/// Whether `peer` is one of the `trusted_proxies`, given as addresses or CIDR ranges such as
+/// `10.0.0.0/8`. Only their forwarding headers are believed.
+pub fn is_trusted_proxy(peer: IpAddr, trusted_proxies: &[String]) -> bool {
+    let peer = peer.to_canonical();
+    trusted_proxies.iter().any(|entry| {
+        let entry = entry.trim();
+        let (address, prefix) = match entry.split_once('/') {
+            Some((address, prefix)) => (address, prefix.parse::<u32>().ok()),
+            None => (entry, None),
+        };
+        let Ok(address) = address.parse::<IpAddr>() else {
+            return false;
+        };
+        match (peer, address.to_canonical()) {
+            (IpAddr::V4(peer), IpAddr::V4(address)) => {
+                in_range(peer.to_bits().into(), address.to_bits().into(), prefix, 32)
+            }
+            (IpAddr::V6(peer), IpAddr::V6(address)) => {
+                in_range(peer.to_bits(), address.to_bits(), prefix, 128)
+            }
+            _ => false,
+        }
+    })
+}
+
+fn in_range(peer: u128, address: u128, prefix: Option<u32>, bits: u32) -> bool {
+    let prefix = prefix.unwrap_or(bits);
+    if prefix > bits {
+        return false;
+    }
+    let shift = bits - prefix;
+    shift >= bits || peer >> shift == address >> shift
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        // Proxies in a chain each append theirs; the first is the client's
+        .and_then(|value| value.split(',').next())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// The `proto` and `host` of the first element of a `Forwarded` header
+fn forwarded(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let Some(element) = header_value(headers, "forwarded") else {
+        return (None, None);
+    };
+    let mut proto = None;
+    let mut host = None;
+    for pair in element.split(';') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "proto" => proto = Some(value),
+            "host" => host = Some(value),
+            _ => {}
+        }
+    }
+    (proto, host)
+}
+
+fn is_valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+}
+
+/// The origin a request was made to, e.g. `https://kanban.example.com`. `scheme` is the one the
+/// server itself was reached with; the headers a reverse proxy adds are only taken into account
+/// when `from_trusted_proxy`.
+pub fn request_origin(
+    headers: &HeaderMap,
+    scheme: &str,
+    from_trusted_proxy: bool,
+) -> Option<String> {
+    let (forwarded_proto, forwarded_host) = if from_trusted_proxy {
+        let (proto, host) = forwarded(headers);
+        (
+            proto.or_else(|| header_value(headers, "x-forwarded-proto").map(str::to_string)),
+            host.or_else(|| header_value(headers, "x-forwarded-host").map(str::to_string)),
+        )
+    } else {
+        (None, None)
+    };
+    let proto = forwarded_proto
+        .map(|proto| proto.to_ascii_lowercase())
+        .filter(|proto| proto == "http" || proto == "https")
+        .unwrap_or_else(|| scheme.to_string());
+    let host = forwarded_host
+        .or_else(|| header_value(headers, header::HOST.as_str()).map(str::to_string))
+        .filter(|host| is_valid_host(host))?;
+    Some(format!("{proto}://{host}"))
+}
+
+/// Base of the absolute links to the app, without a trailing slash. `request_origin` is the
+/// origin of the request the link is made for, if any.
+pub async fn base_url(
+    config: &Arc<RwLock<Config>>,
+    request_origin: Option<&str>,
+) -> Option<String> {
+    let public_url = config.read().await.chat_notifications.public_url.clone();
+    if let Some(url) = public_url.filter(|url| !url.trim().is_empty()) {
+        return Some(url.trim().trim_end_matches('/').to_string());
+    }
+    if let Some(origin) = request_origin {
+        return Some(origin.to_string());
+    }
+    LOCAL_ORIGIN.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn prefers_what_a_trusted_proxy_forwarded() {
+        assert_eq!(
+            request_origin(&headers(&[("host", "192.168.1.20:3000")]), "http", false).as_deref(),
+            Some("http://192.168.1.20:3000")
+        );
+        let proxied = headers(&[
+            ("host", "127.0.0.1:3000"),
+            ("x-forwarded-proto", "https"),
+            ("x-forwarded-host", "kanban.example.com, proxy.internal"),
+        ]);
+        assert_eq!(
+            request_origin(&proxied, "http", true).as_deref(),
+            Some("https://kanban.example.com")
+        );
+        assert_eq!(
+            request_origin(&proxied, "http", false).as_deref(),
+            Some("http://127.0.0.1:3000")
+        );
+        assert_eq!(
+            request_origin(
+                &headers(&[
+                    ("host", "127.0.0.1:3000"),
+                    ("forwarded", "for=10.0.0.2;proto=https;host=\"kanban.lan\""),
+                ]),
+                "http",
+                true
+            )
+            .as_deref(),
+            Some("https://kanban.lan")
+        );
+        assert_eq!(
+            request_origin(
+                &headers(&[
+                    ("host", "kanban.lan:8443"),
+                    ("x-forwarded-proto", "gopher"),
+                    ("x-forwarded-host", "evil.com/path?"),
+                ]),
+                "https",
+                true
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn matches_trusted_proxies() {
+        let trusted = vec![
+            "10.0.0.0/8".to_string(),
+            " 192.168.1.5 ".to_string(),
+            "fd00::/16".to_string(),
+            "not an address".to_string(),
+        ];
+        let is_trusted = |peer: &str| is_trusted_proxy(peer.parse().unwrap(), &trusted);
+        assert!(is_trusted("10.20.30.40"));
+        assert!(is_trusted("192.168.1.5"));
+        assert!(is_trusted("::ffff:10.0.0.1"));
+        assert!(is_trusted("fd00:1::1"));
+        assert!(!is_trusted("192.168.1.6"));
+        assert!(!is_trusted("11.0.0.1"));
+        assert!(!is_trusted("fe80::1"));
+        assert!(!is_trusted_proxy("127.0.0.1".parse().unwrap(), &[]));
+        assert!(is_trusted_proxy(
+            "203.0.113.9".parse().unwrap(),
+            &["0.0.0.0/0".to_string()]
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! Evaluates a project's [`QuietHours`] window. While a project is quiet, queued follow-ups
+//! and scheduled runs wait instead of starting on their own; manual actions are unaffected.
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use db::models::project::{Project, QuietHours};
+use thiserror::Error;
+
+use crate::services::project_locale;
+
+#[derive(Debug, Error)]
+pub enum QuietHoursError {
+    #[error("Invalid time '{0}', expected HH:MM")]
+    InvalidTime(String),
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, QuietHoursError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| QuietHoursError::InvalidTime(value.to_string()))
+}
+
+/// Check that both ends of the window are valid `HH:MM` times
+pub fn validate(quiet_hours: &QuietHours) -> Result<(), QuietHoursError> {
+    parse_time(&quiet_hours.start)?;
+    parse_time(&quiet_hours.end)?;
+    Ok(())
+}
+
+/// Whether the project's quiet hours cover `now`, in the project's time zone. Windows that
+/// fail to parse never apply.
+pub fn is_quiet(project: &Project, now: DateTime<Utc>) -> bool {
+    let Some(quiet_hours) = project.quiet_hours.as_deref() else {
+        return false;
+    };
+    if !quiet_hours.enabled {
+        return false;
+    }
+    let local = project_locale::local_time(project, now);
+    covers(quiet_hours, local.weekday(), local.time())
+}
+
+fn covers(quiet_hours: &QuietHours, weekday: Weekday, time: NaiveTime) -> bool {
+    if quiet_hours.weekends && matches!(weekday, Weekday::Sat | Weekday::Sun) {
+        return true;
+    }
+    let (Ok(start), Ok(end)) = (parse_time(&quiet_hours.start), parse_time(&quiet_hours.end))
+    else {
+        return false;
+    };
+    if start <= end {
+        start <= time && time < end
+    } else {
+        // The window spans midnight
+        time >= start || time < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str, weekends: bool) -> QuietHours {
+        QuietHours {
+            enabled: true,
+            start: start.to_string(),
+            end: end.to_string(),
+            weekends,
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn windows_can_span_midnight() {
+        let night = window("22:00", "07:30", false);
+        assert!(covers(&night, Weekday::Tue, at(23, 15)));
+        assert!(covers(&night, Weekday::Tue, at(3, 0)));
+        assert!(!covers(&night, Weekday::Tue, at(7, 30)));
+        assert!(!covers(&night, Weekday::Tue, at(12, 0)));
+
+        let lunch = window("12:00", "13:00", false);
+        assert!(covers(&lunch, Weekday::Wed, at(12, 30)));
+        assert!(!covers(&lunch, Weekday::Wed, at(13, 0)));
+
+        let empty = window("09:00", "09:00", false);
+        assert!(!covers(&empty, Weekday::Wed, at(9, 0)));
+    }
+
+    #[test]
+    fn weekends_are_quiet_all_day_when_enabled() {
+        let night = window("22:00", "07:00", false);
+        assert!(!covers(&night, Weekday::Sun, at(12, 0)));
+
+        let night_and_weekends = window("22:00", "07:00", true);
+        assert!(covers(&night_and_weekends, Weekday::Sat, at(12, 0)));
+        assert!(!covers(&night_and_weekends, Weekday::Mon, at(12, 0)));
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        assert!(validate(&window("9:00", "17:00", false)).is_ok());
+        assert!(validate(&window("25:00", "07:00", false)).is_err());
+        assert!(validate(&window("late", "07:00", false)).is_err());
+    }
+}
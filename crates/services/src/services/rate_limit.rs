@@ -0,0 +1,260 @@
+//! Per-client rate limits on the routes that are expensive to serve: starting attempts, which
+//! spawns coding agents, computing diffs and streaming logs. A misbehaving script gets 429s
+//! instead of starting hundreds of agent processes.
+//!
+//! Each client gets a token bucket per kind of route, sized by the `rate_limits` config.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+
+use crate::services::config::{Config, RateLimit, RateLimitConfig};
+
+/// Buckets kept before full ones, whose clients are back to their burst, are dropped
+const MAX_TRACKED_BUCKETS: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitedRoute {
+    AttemptStart,
+    Diff,
+    LogStream,
+}
+
+impl LimitedRoute {
+    const ALL: [Self; 3] = [Self::AttemptStart, Self::Diff, Self::LogStream];
+
+    fn limit(self, config: &RateLimitConfig) -> Option<RateLimit> {
+        match self {
+            Self::AttemptStart => config.attempt_starts,
+            Self::Diff => config.diffs,
+            Self::LogStream => config.log_streams,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::AttemptStart => 0,
+            Self::Diff => 1,
+            Self::LogStream => 2,
+        }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: capacity(limit),
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second(limit)).min(capacity(limit));
+        self.updated_at = now;
+    }
+
+    /// Take a token, or say how long until there is one
+    fn take(&mut self, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        self.refill(limit, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / per_second(limit),
+            ))
+        }
+    }
+
+    fn is_full(&mut self, limit: RateLimit, now: Instant) -> bool {
+        self.refill(limit, now);
+        self.tokens >= capacity(limit)
+    }
+}
+
+fn capacity(limit: RateLimit) -> f64 {
+    limit.burst.max(1) as f64
+}
+
+fn per_second(limit: RateLimit) -> f64 {
+    limit.per_minute.max(1) as f64 / 60.0
+}
+
+#[derive(Default)]
+struct RouteCounters {
+    allowed: AtomicU64,
+    limited: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RouteRateLimitStats {
+    pub route: LimitedRoute,
+    /// Unset when the route isn't limited
+    pub limit: Option<RateLimit>,
+    pub allowed: u64,
+    /// Requests answered with 429 Too Many Requests
+    pub limited: u64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RateLimitSnapshot {
+    pub enabled: bool,
+    pub routes: Vec<RouteRateLimitStats>,
+    /// Clients currently tracked, per kind of route
+    pub tracked_buckets: usize,
+}
+
+/// Service that decides whether a client may call a limited route, and counts the answers
+#[derive(Clone)]
+pub struct RateLimitService {
+    config: Arc<RwLock<Config>>,
+    buckets: Arc<Mutex<HashMap<(LimitedRoute, String), TokenBucket>>>,
+    counters: Arc<[RouteCounters; 3]>,
+}
+
+impl RateLimitService {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            counters: Arc::new(Default::default()),
+        }
+    }
+
+    /// Take one of the client's requests to the route, or say how long it has to wait
+    pub async fn check(&self, route: LimitedRoute, client: &str) -> Result<(), Duration> {
+        let config = self.config.read().await.rate_limits.clone();
+        let result = match route.limit(&config).filter(|_| config.enabled) {
+            Some(limit) => self.take(&config, route, limit, client, Instant::now()),
+            None => Ok(()),
+        };
+        let counters = &self.counters[route.index()];
+        match result {
+            Ok(()) => counters.allowed.fetch_add(1, Ordering::Relaxed),
+            Err(_) => counters.limited.fetch_add(1, Ordering::Relaxed),
+        };
+        result
+    }
+
+    fn take(
+        &self,
+        config: &RateLimitConfig,
+        route: LimitedRoute,
+        limit: RateLimit,
+        client: &str,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            buckets.retain(
+                |(bucket_route, _), bucket| match bucket_route.limit(config) {
+                    Some(limit) => !bucket.is_full(limit, now),
+                    None => false,
+                },
+            );
+        }
+        buckets
+            .entry((route, client.to_string()))
+            .or_insert_with(|| TokenBucket::full(limit, now))
+            .take(limit, now)
+    }
+
+    pub async fn snapshot(&self) -> RateLimitSnapshot {
+        let config = self.config.read().await.rate_limits.clone();
+        let routes = LimitedRoute::ALL
+            .into_iter()
+            .map(|route| {
+                let counters = &self.counters[route.index()];
+                RouteRateLimitStats {
+                    route,
+                    limit: route.limit(&config),
+                    allowed: counters.allowed.load(Ordering::Relaxed),
+                    limited: counters.limited.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        RateLimitSnapshot {
+            enabled: config.enabled,
+            routes,
+            tracked_buckets: self.buckets.lock().unwrap().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: RateLimit = RateLimit {
+        per_minute: 60,
+        burst: 2,
+    };
+
+    #[test]
+    fn buckets_allow_a_burst_then_refill_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(LIMIT, start);
+        assert!(bucket.take(LIMIT, start).is_ok());
+        assert!(bucket.take(LIMIT, start).is_ok());
+        assert_eq!(bucket.take(LIMIT, start), Err(Duration::from_secs(1)));
+
+        let later = start + Duration::from_millis(1500);
+        assert!(bucket.take(LIMIT, later).is_ok());
+        assert!(bucket.take(LIMIT, later).is_err());
+        assert!(bucket.is_full(LIMIT, later + Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn limits_each_client_and_route_separately() {
+        let mut config = Config::default();
+        config.rate_limits.attempt_starts = Some(RateLimit {
+            per_minute: 1,
+            burst: 1,
+        });
+        config.rate_limits.diffs = None;
+        let service = RateLimitService::new(Arc::new(RwLock::new(config)));
+
+        assert!(
+            service
+                .check(LimitedRoute::AttemptStart, "alice")
+                .await
+                .is_ok()
+        );
+        assert!(
+            service
+                .check(LimitedRoute::AttemptStart, "alice")
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .check(LimitedRoute::AttemptStart, "bob")
+                .await
+                .is_ok()
+        );
+        for _ in 0..10 {
+            assert!(service.check(LimitedRoute::Diff, "alice").await.is_ok());
+        }
+
+        let snapshot = service.snapshot().await;
+        let attempt_starts = &snapshot.routes[LimitedRoute::AttemptStart.index()];
+        assert_eq!((attempt_starts.allowed, attempt_starts.limited), (2, 1));
+        assert_eq!(snapshot.tracked_buckets, 2);
+    }
+}
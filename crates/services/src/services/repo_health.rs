@@ -0,0 +1,297 @@
+//! Checks a project repository for the ways it commonly breaks and repairs the ones that
+//! have a safe fix: lock files left behind by a git process that died, rebases that were
+//! never finished, metadata of worktrees whose directory is gone, and remote-tracking refs
+//! pointing at missing objects. Corrupt objects are only reported.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use git2::Repository;
+use serde::Serialize;
+use tracing::{info, warn};
+use ts_rs::TS;
+
+use crate::services::{git::GitServiceError, git_cli::GitCli};
+
+/// Lock files are held for the duration of a single git command
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Rebases stopped on conflicts are resolved from the UI, so give them time before
+/// treating them as abandoned
+const STALE_REBASE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Lock files git takes in the common directory, next to those under `refs/`
+const COMMON_LOCK_FILES: [&str; 5] = [
+    "index.lock",
+    "HEAD.lock",
+    "config.lock",
+    "packed-refs.lock",
+    "shallow.lock",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum RepoHealthIssueKind {
+    ObjectCorruption,
+    StaleLock,
+    StaleRebase,
+    OrphanedWorktree,
+    BrokenRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct RepoHealthIssue {
+    pub kind: RepoHealthIssueKind,
+    /// Ref name, worktree or file the issue is about
+    pub target: Option<String>,
+    pub detail: String,
+    /// Whether the repair action fixes this issue
+    pub repairable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoHealthReport {
+    pub healthy: bool,
+    pub issues: Vec<RepoHealthIssue>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RepoRepairReport {
+    pub repaired: Vec<RepoHealthIssue>,
+    /// State of the repository after the repair; repairs that failed show up here again
+    pub health: RepoHealthReport,
+}
+
+/// Run every check against the repository at `repo_path`, including a full `git fsck`
+pub async fn check(repo_path: &Path) -> Result<RepoHealthReport, GitServiceError> {
+    let repo_path = repo_path.to_path_buf();
+    tokio::task::spawn_blocking(move || check_blocking(&repo_path))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+/// Fix the repairable issues of the repository at `repo_path`, then check it again
+pub async fn repair(repo_path: &Path) -> Result<RepoRepairReport, GitServiceError> {
+    let repo_path = repo_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let git = GitCli::new();
+        // Corruption is never repairable, so skip the slow fsck until the final check
+        let issues = find_issues(&git, &repo_path, false)?;
+        let mut repaired = Vec::new();
+        for issue in issues.into_iter().filter(|issue| issue.repairable) {
+            match repair_issue(&git, &repo_path, &issue) {
+                Ok(()) => repaired.push(issue),
+                Err(e) => warn!(
+                    "Failed to repair {:?} in {}: {}",
+                    issue.kind,
+                    repo_path.display(),
+                    e
+                ),
+            }
+        }
+        info!(
+            "Repaired {} issues in repository {}",
+            repaired.len(),
+            repo_path.display()
+        );
+        Ok(RepoRepairReport {
+            repaired,
+            health: check_blocking(&repo_path)?,
+        })
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+fn check_blocking(repo_path: &Path) -> Result<RepoHealthReport, GitServiceError> {
+    let issues = find_issues(&GitCli::new(), repo_path, true)?;
+    Ok(RepoHealthReport {
+        healthy: issues.is_empty(),
+        issues,
+        checked_at: Utc::now(),
+    })
+}
+
+/// Issues in the order they should be repaired: locks first, since they block the git
+/// commands the other repairs run
+fn find_issues(
+    git: &GitCli,
+    repo_path: &Path,
+    fsck: bool,
+) -> Result<Vec<RepoHealthIssue>, GitServiceError> {
+    let repo = Repository::open(repo_path)?;
+    let broken_refs = broken_refs(&repo)?;
+
+    let mut issues = Vec::new();
+    if fsck {
+        for problem in git.fsck(repo_path)? {
+            // fsck complains about the refs found below as well
+            let about_broken_ref = broken_refs
+                .iter()
+                .filter_map(|issue| issue.target.as_deref())
+                .any(|name| problem.contains(name));
+            if !about_broken_ref {
+                issues.push(RepoHealthIssue {
+                    kind: RepoHealthIssueKind::ObjectCorruption,
+                    target: None,
+                    detail: problem,
+                    repairable: false,
+                });
+            }
+        }
+    }
+    issues.extend(stale_locks(&git.common_dir(repo_path)?));
+    issues.extend(stale_rebases(git, repo_path)?);
+    issues.extend(
+        git.prunable_worktrees(repo_path)?
+            .into_iter()
+            .map(|(name, reason)| RepoHealthIssue {
+                kind: RepoHealthIssueKind::OrphanedWorktree,
+                target: Some(name),
+                detail: reason,
+                repairable: true,
+            }),
+    );
+    issues.extend(broken_refs);
+    Ok(issues)
+}
+
+fn broken_refs(repo: &Repository) -> Result<Vec<RepoHealthIssue>, GitServiceError> {
+    let odb = repo.odb()?;
+    let mut issues = Vec::new();
+    for reference in repo.references()? {
+        let reference = match reference {
+            Ok(reference) => reference,
+            // Ref files that cannot even be parsed have to be looked at by hand
+            Err(e) => {
+                issues.push(RepoHealthIssue {
+                    kind: RepoHealthIssueKind::BrokenRef,
+                    target: None,
+                    detail: e.message().to_string(),
+                    repairable: false,
+                });
+                continue;
+            }
+        };
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        let detail = match reference.target() {
+            Some(oid) if !odb.exists(oid) => format!("Points to missing object {oid}"),
+            None if reference.resolve().is_err() => format!(
+                "Points to missing ref {}",
+                reference.symbolic_target().unwrap_or_default()
+            ),
+            _ => continue,
+        };
+        issues.push(RepoHealthIssue {
+            kind: RepoHealthIssueKind::BrokenRef,
+            target: Some(name.to_string()),
+            detail,
+            // Remote-tracking refs come back on the next fetch; local branches may hold
+            // work that has to be recovered first
+            repairable: name.starts_with("refs/remotes/"),
+        });
+    }
+    Ok(issues)
+}
+
+fn stale_locks(common_dir: &Path) -> Vec<RepoHealthIssue> {
+    let mut candidates: Vec<PathBuf> = COMMON_LOCK_FILES
+        .iter()
+        .map(|name| common_dir.join(name))
+        .collect();
+    if let Ok(entries) = fs::read_dir(common_dir.join("worktrees")) {
+        for entry in entries.flatten() {
+            candidates.push(entry.path().join("index.lock"));
+            candidates.push(entry.path().join("HEAD.lock"));
+        }
+    }
+    collect_ref_locks(&common_dir.join("refs"), &mut candidates);
+
+    candidates
+        .into_iter()
+        .filter(|path| is_older_than(path, STALE_LOCK_AGE))
+        .map(|path| RepoHealthIssue {
+            kind: RepoHealthIssueKind::StaleLock,
+            target: Some(path.to_string_lossy().to_string()),
+            detail: "Lock file left behind by a git process that did not exit cleanly".to_string(),
+            repairable: true,
+        })
+        .collect()
+}
+
+fn collect_ref_locks(dir: &Path, locks: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_ref_locks(&path, locks);
+        } else if path.extension().is_some_and(|ext| ext == "lock") {
+            locks.push(path);
+        }
+    }
+}
+
+fn stale_rebases(git: &GitCli, repo_path: &Path) -> Result<Vec<RepoHealthIssue>, GitServiceError> {
+    let mut issues = Vec::new();
+    for worktree in git.list_worktrees(repo_path)? {
+        let path = Path::new(&worktree.path);
+        // Worktrees whose directory is gone are reported as orphaned
+        if !path.exists() {
+            continue;
+        }
+        if let Some(state_dir) = git.rebase_state_dir(path)?
+            && is_older_than(&state_dir, STALE_REBASE_AGE)
+        {
+            issues.push(RepoHealthIssue {
+                kind: RepoHealthIssueKind::StaleRebase,
+                target: Some(worktree.path),
+                detail: "Rebase started more than an hour ago was never finished; repairing \
+                         aborts it"
+                    .to_string(),
+                repairable: true,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+fn repair_issue(
+    git: &GitCli,
+    repo_path: &Path,
+    issue: &RepoHealthIssue,
+) -> Result<(), GitServiceError> {
+    let target = issue.target.as_deref().unwrap_or_default();
+    match issue.kind {
+        RepoHealthIssueKind::StaleLock => fs::remove_file(target)?,
+        RepoHealthIssueKind::StaleRebase => {
+            let worktree_path = Path::new(target);
+            // Quitting leaves the commits alone when the rebase cannot be rolled back
+            if let Err(e) = git.abort_rebase(worktree_path) {
+                warn!("Failed to abort rebase in {}: {}", target, e);
+                git.quit_rebase(worktree_path)?;
+            }
+        }
+        RepoHealthIssueKind::OrphanedWorktree => git.worktree_prune(repo_path)?,
+        RepoHealthIssueKind::BrokenRef => Repository::open(repo_path)?
+            .find_reference(target)?
+            .delete()?,
+        RepoHealthIssueKind::ObjectCorruption => {}
+    }
+    Ok(())
+}
+
+fn is_older_than(path: &Path, age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|elapsed| elapsed >= age))
+}
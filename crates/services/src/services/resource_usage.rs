@@ -0,0 +1,222 @@
+//! Samples the CPU and memory use of running execution processes, so a busy machine can be
+//! traced back to the run responsible for it.
+//!
+//! An execution's usage covers every process it started (see [`process_tree::members`]). CPU use
+//! is the CPU time those processes spent between two samples, as a share of one core.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream::BoxStream};
+use serde::Serialize;
+use tokio::sync::{RwLock, broadcast};
+use tokio_stream::wrappers::BroadcastStream;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::process_tree::{self, ProcessEntry};
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct ExecutionResourceUsage {
+    pub execution_process_id: Uuid,
+    /// Share of one CPU core used since the previous sample; above 100 when the run keeps
+    /// several cores busy
+    pub cpu_percent: f64,
+    /// Resident memory of all of the run's processes
+    pub memory_kb: u32,
+    pub process_count: u32,
+    pub sampled_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Samples {
+    usage: HashMap<Uuid, ExecutionResourceUsage>,
+    /// Lifetime CPU time of each sampled process at the previous sample
+    cpu_time_ms: HashMap<u32, u64>,
+    taken_at: Option<Instant>,
+}
+
+/// Latest usage of every running execution process, fed by the container's sampler
+#[derive(Clone)]
+pub struct ResourceMonitor {
+    samples: Arc<RwLock<Samples>>,
+    sender: broadcast::Sender<Vec<ExecutionResourceUsage>>,
+}
+
+impl Default for ResourceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(16);
+        Self {
+            samples: Arc::new(RwLock::new(Samples::default())),
+            sender,
+        }
+    }
+
+    /// Record a sample of the running executions, given as their id and the pid of the process
+    /// they spawned. Executions missing from `roots` have finished and are dropped.
+    pub async fn record(&self, entries: &[ProcessEntry], roots: &[(Uuid, u32)]) {
+        let now = Instant::now();
+        let sampled_at = Utc::now();
+        let mut samples = self.samples.write().await;
+        let elapsed = samples.taken_at.map(|taken_at| now - taken_at);
+
+        let mut usage = HashMap::new();
+        let mut cpu_time_ms = HashMap::new();
+        for (id, root_pid) in roots {
+            let members = process_tree::members(entries, *root_pid);
+            if members.is_empty() {
+                continue;
+            }
+            let processes: Vec<_> = entries
+                .iter()
+                .filter(|entry| members.contains(&entry.pid))
+                .collect();
+            usage.insert(
+                *id,
+                measure(*id, &processes, &samples.cpu_time_ms, elapsed, sampled_at),
+            );
+            cpu_time_ms.extend(processes.iter().map(|entry| (entry.pid, entry.cpu_time_ms)));
+        }
+
+        *samples = Samples {
+            usage,
+            cpu_time_ms,
+            taken_at: Some(now),
+        };
+        let _ = self.sender.send(sorted(&samples.usage));
+    }
+
+    /// Forget all samples once nothing is running
+    pub async fn clear(&self) {
+        let mut samples = self.samples.write().await;
+        let had_usage = !samples.usage.is_empty();
+        *samples = Samples::default();
+        if had_usage {
+            let _ = self.sender.send(Vec::new());
+        }
+    }
+
+    pub async fn get(&self, execution_process_id: &Uuid) -> Option<ExecutionResourceUsage> {
+        self.samples
+            .read()
+            .await
+            .usage
+            .get(execution_process_id)
+            .cloned()
+    }
+
+    /// Usage of every running execution, busiest first
+    pub async fn current(&self) -> Vec<ExecutionResourceUsage> {
+        sorted(&self.samples.read().await.usage)
+    }
+
+    /// The current usage, then every new sample
+    pub async fn stream(&self) -> BoxStream<'static, Vec<ExecutionResourceUsage>> {
+        let receiver = self.sender.subscribe();
+        let current = self.current().await;
+        futures::stream::once(async move { current })
+            .chain(BroadcastStream::new(receiver).filter_map(|sample| async move { sample.ok() }))
+            .boxed()
+    }
+}
+
+/// Usage of one execution made up of `processes`. Processes without an earlier sample started
+/// since then, so all of their CPU time counts.
+fn measure(
+    execution_process_id: Uuid,
+    processes: &[&ProcessEntry],
+    previous_cpu_time_ms: &HashMap<u32, u64>,
+    elapsed: Option<Duration>,
+    sampled_at: DateTime<Utc>,
+) -> ExecutionResourceUsage {
+    let cpu_percent = match elapsed {
+        Some(elapsed) if !elapsed.is_zero() => {
+            let used_ms: u64 = processes
+                .iter()
+                .map(|entry| {
+                    let previous = previous_cpu_time_ms.get(&entry.pid).copied().unwrap_or(0);
+                    entry.cpu_time_ms.saturating_sub(previous)
+                })
+                .sum();
+            used_ms as f64 / elapsed.as_millis() as f64 * 100.0
+        }
+        // The first sample has nothing to compare against
+        _ => 0.0,
+    };
+    let memory_kb: u64 = processes.iter().map(|entry| entry.rss_kb).sum();
+    ExecutionResourceUsage {
+        execution_process_id,
+        cpu_percent: (cpu_percent * 10.0).round() / 10.0,
+        memory_kb: u32::try_from(memory_kb).unwrap_or(u32::MAX),
+        process_count: u32::try_from(processes.len()).unwrap_or(u32::MAX),
+        sampled_at,
+    }
+}
+
+fn sorted(usage: &HashMap<Uuid, ExecutionResourceUsage>) -> Vec<ExecutionResourceUsage> {
+    let mut usage: Vec<_> = usage.values().cloned().collect();
+    usage.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, rss_kb: u64, cpu_time_ms: u64) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            ppid: 1,
+            pgid: 100,
+            rss_kb,
+            elapsed_secs: 60,
+            cpu_time_ms,
+            command: "node".to_string(),
+        }
+    }
+
+    #[test]
+    fn measures_cpu_time_since_the_previous_sample() {
+        let first = entry(100, 5_000, 10_000);
+        let second = entry(101, 20_000, 3_000);
+        let previous = HashMap::from([(100, 9_000), (101, 1_000)]);
+        let usage = measure(
+            Uuid::nil(),
+            &[&first, &second],
+            &previous,
+            Some(Duration::from_secs(2)),
+            Utc::now(),
+        );
+        assert_eq!(usage.cpu_percent, 150.0);
+        assert_eq!(usage.memory_kb, 25_000);
+        assert_eq!(usage.process_count, 2);
+    }
+
+    #[test]
+    fn counts_new_processes_and_never_goes_negative() {
+        let started = entry(102, 1_000, 500);
+        let reused = entry(103, 1_000, 100);
+        let previous = HashMap::from([(103, 40_000)]);
+        let usage = measure(
+            Uuid::nil(),
+            &[&started, &reused],
+            &previous,
+            Some(Duration::from_secs(1)),
+            Utc::now(),
+        );
+        assert_eq!(usage.cpu_percent, 50.0);
+
+        let first_sample = measure(Uuid::nil(), &[&started], &previous, None, Utc::now());
+        assert_eq!(first_sample.cpu_percent, 0.0);
+    }
+}
@@ -0,0 +1,394 @@
+//! Reuses the output of a project's setup script between attempts.
+//!
+//! After a setup script succeeds, the paths in the project's [`SetupCacheConfig`] (e.g.
+//! `node_modules`) are copied out of the worktree, keyed by a hash of the script and the
+//! project's lockfiles at the commit the attempt started from. A later attempt with the same
+//! key gets the saved copy instead of running the setup script again.
+
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use db::models::{project::SetupCacheConfig, setup_cache_entry::SetupCacheEntry};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::services::{
+    git::{Commit, GitService, GitServiceError},
+    storage,
+};
+
+/// Files hashed with the setup script when the project does not list its own
+pub const DEFAULT_KEY_FILES: &[&str] = &[
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "bun.lockb",
+    "Cargo.lock",
+    "poetry.lock",
+    "uv.lock",
+    "Pipfile.lock",
+    "requirements.txt",
+    "Gemfile.lock",
+    "go.sum",
+    "composer.lock",
+];
+
+#[derive(Debug, Error)]
+pub enum SetupCacheError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error("Invalid path '{0}': paths must be relative and stay inside the repository")]
+    InvalidPath(String),
+    #[error("Choose at least one path to cache")]
+    NoPaths,
+}
+
+/// Check that every path is inside the repository and caching has something to save
+pub fn validate(config: &SetupCacheConfig) -> Result<(), SetupCacheError> {
+    if config.enabled && config.paths.iter().all(|path| path.trim().is_empty()) {
+        return Err(SetupCacheError::NoPaths);
+    }
+    for path in config.paths.iter().chain(&config.key_files) {
+        if !path.trim().is_empty() && relative_path(path).is_none() {
+            return Err(SetupCacheError::InvalidPath(path.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Key of the setup script's output for a worktree at `commit`
+pub fn cache_key(
+    git: &GitService,
+    repo_path: &Path,
+    commit: &str,
+    script: &str,
+    config: &SetupCacheConfig,
+) -> Result<String, SetupCacheError> {
+    let commit = Commit::new(git2::Oid::from_str(commit).map_err(GitServiceError::from)?);
+    let names: Vec<&str> = if config.key_files.is_empty() {
+        DEFAULT_KEY_FILES.to_vec()
+    } else {
+        config.key_files.iter().map(|file| file.trim()).collect()
+    };
+    let mut key_files = Vec::with_capacity(names.len());
+    for name in names.into_iter().filter(|name| !name.is_empty()) {
+        let contents = git.get_file_at_commit(repo_path, &commit, Path::new(name))?;
+        key_files.push((name, contents));
+    }
+    Ok(hash_inputs(script, &config.paths, &key_files))
+}
+
+fn hash_inputs(script: &str, paths: &[String], key_files: &[(&str, Option<Vec<u8>>)]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(script.as_bytes());
+    for path in paths {
+        hasher.update(b"\0path\0");
+        hasher.update(path.trim().as_bytes());
+    }
+    for (name, contents) in key_files {
+        hasher.update(b"\0file\0");
+        hasher.update(name.as_bytes());
+        match contents {
+            Some(contents) => {
+                hasher.update((contents.len() as u64).to_le_bytes());
+                hasher.update(contents);
+            }
+            None => hasher.update(b"\0missing"),
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Saved setup script output on disk, one directory per project and key
+#[derive(Debug, Clone)]
+pub struct SetupCache {
+    root: PathBuf,
+}
+
+impl Default for SetupCache {
+    fn default() -> Self {
+        Self::new(utils::cache_dir().join("setup-cache"))
+    }
+}
+
+impl SetupCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn project_dir(&self, project_id: Uuid) -> PathBuf {
+        self.root.join(project_id.to_string())
+    }
+
+    fn entry_dir(&self, project_id: Uuid, cache_key: &str) -> PathBuf {
+        self.project_dir(project_id).join(cache_key)
+    }
+
+    /// Copy the saved `paths` into the worktree. Returns false if nothing is saved for the key.
+    pub async fn restore(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+        worktree_path: &Path,
+        paths: &[String],
+    ) -> Result<bool, SetupCacheError> {
+        if SetupCacheEntry::find(pool, project_id, cache_key)
+            .await?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        let entry_dir = self.entry_dir(project_id, cache_key);
+        if !entry_dir.is_dir() {
+            // Removed from disk behind our back
+            SetupCacheEntry::delete(pool, project_id, cache_key).await?;
+            return Ok(false);
+        }
+
+        let worktree_path = worktree_path.to_path_buf();
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|path| relative_path(path))
+            .collect();
+        tokio::task::spawn_blocking(move || -> io::Result<()> {
+            for path in paths {
+                let from = entry_dir.join(&path);
+                if fs::symlink_metadata(&from).is_err() {
+                    continue;
+                }
+                let to = worktree_path.join(&path);
+                remove_path(&to)?;
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                copy_path(&from, &to)?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(io::Error::other)??;
+
+        SetupCacheEntry::record_hit(pool, project_id, cache_key).await?;
+        Ok(true)
+    }
+
+    /// Save `paths` from the worktree under the key. Returns None if none of them exist.
+    pub async fn save(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+        worktree_path: &Path,
+        paths: &[String],
+    ) -> Result<Option<SetupCacheEntry>, SetupCacheError> {
+        let entry_dir = self.entry_dir(project_id, cache_key);
+        // Copied next to the entry first, so a half-written copy is never restored
+        let staging_dir = self
+            .project_dir(project_id)
+            .join(format!(".{cache_key}.{}", Uuid::new_v4()));
+        let worktree_path = worktree_path.to_path_buf();
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|path| relative_path(path))
+            .collect();
+
+        let size_bytes = tokio::task::spawn_blocking(move || {
+            let result = save_paths(&worktree_path, &paths, &staging_dir, &entry_dir);
+            let _ = remove_path(&staging_dir);
+            result
+        })
+        .await
+        .map_err(io::Error::other)??;
+
+        let Some(size_bytes) = size_bytes else {
+            return Ok(None);
+        };
+        let entry = SetupCacheEntry::upsert(
+            pool,
+            project_id,
+            cache_key,
+            i64::try_from(size_bytes).unwrap_or(i64::MAX),
+        )
+        .await?;
+        Ok(Some(entry))
+    }
+
+    /// Delete one saved entry. Returns false if it did not exist.
+    pub async fn remove(
+        &self,
+        pool: &SqlitePool,
+        project_id: Uuid,
+        cache_key: &str,
+    ) -> Result<bool, SetupCacheError> {
+        let deleted = SetupCacheEntry::delete(pool, project_id, cache_key).await? > 0;
+        // Keys come from the request, so only ever touch a direct child of the project dir
+        if relative_path(cache_key).is_some_and(|path| path.components().count() == 1) {
+            let entry_dir = self.entry_dir(project_id, cache_key);
+            tokio::task::spawn_blocking(move || remove_path(&entry_dir))
+                .await
+                .map_err(io::Error::other)??;
+        }
+        Ok(deleted)
+    }
+
+    /// Delete everything saved for the project. Returns the number of entries deleted.
+    pub async fn clear(&self, pool: &SqlitePool, project_id: Uuid) -> Result<u64, SetupCacheError> {
+        let deleted = SetupCacheEntry::delete_by_project_id(pool, project_id).await?;
+        let project_dir = self.project_dir(project_id);
+        tokio::task::spawn_blocking(move || remove_path(&project_dir))
+            .await
+            .map_err(io::Error::other)??;
+        Ok(deleted)
+    }
+}
+
+/// Copy the existing `paths` to `staging_dir`, then move it to `entry_dir`. Returns the size
+/// of the copy, or None if none of the paths exist.
+fn save_paths(
+    worktree_path: &Path,
+    paths: &[PathBuf],
+    staging_dir: &Path,
+    entry_dir: &Path,
+) -> io::Result<Option<u64>> {
+    let mut copied = false;
+    for path in paths {
+        let from = worktree_path.join(path);
+        if fs::symlink_metadata(&from).is_err() {
+            continue;
+        }
+        let to = staging_dir.join(path);
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        copy_path(&from, &to)?;
+        copied = true;
+    }
+    if !copied {
+        return Ok(None);
+    }
+    let size = path_size(staging_dir)?;
+    remove_path(entry_dir)?;
+    fs::rename(staging_dir, entry_dir)?;
+    Ok(Some(size))
+}
+
+/// `path` as a path inside the repository, or None if it is absolute or leaves it
+fn relative_path(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path.trim());
+    let mut components = path.components().peekable();
+    components.peek()?;
+    components
+        .all(|component| matches!(component, Component::Normal(name) if name != ".git"))
+        .then(|| path.to_path_buf())
+}
+
+fn copy_path(from: &Path, to: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_symlink() {
+        storage::copy_symlink(from, to)
+    } else if file_type.is_dir() {
+        storage::copy_dir(from, to)
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    let result = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) => Err(e),
+    };
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn path_size(path: &Path) -> io::Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += path_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(paths: &[&str], key_files: &[&str]) -> SetupCacheConfig {
+        SetupCacheConfig {
+            enabled: true,
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            key_files: key_files.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_repository() {
+        assert!(validate(&config(&["node_modules", "packages/a/node_modules"], &[])).is_ok());
+        assert!(matches!(
+            validate(&config(&[], &[])),
+            Err(SetupCacheError::NoPaths)
+        ));
+        for path in ["../node_modules", "/tmp/cache", ".git/hooks", "a/../../b"] {
+            assert!(
+                matches!(
+                    validate(&config(&["node_modules"], &[path])),
+                    Err(SetupCacheError::InvalidPath(_))
+                ),
+                "{path} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn key_changes_with_script_and_lockfiles() {
+        let paths = vec!["node_modules".to_string()];
+        let lock = |contents: &str| vec![("package-lock.json", Some(contents.as_bytes().to_vec()))];
+        let key = hash_inputs("npm ci", &paths, &lock("v1"));
+
+        assert_eq!(key, hash_inputs("npm ci", &paths, &lock("v1")));
+        assert_ne!(key, hash_inputs("npm ci", &paths, &lock("v2")));
+        assert_ne!(key, hash_inputs("npm install", &paths, &lock("v1")));
+        assert_ne!(
+            key,
+            hash_inputs("npm ci", &paths, &[("package-lock.json", None)])
+        );
+    }
+
+    #[tokio::test]
+    async fn copies_and_removes_trees() {
+        let dir = std::env::temp_dir().join(format!("vk-setup-cache-{}", Uuid::new_v4()));
+        let worktree = dir.join("worktree");
+        fs::create_dir_all(worktree.join("node_modules/left-pad")).unwrap();
+        fs::write(worktree.join("node_modules/left-pad/index.js"), "pad").unwrap();
+
+        let staging = dir.join("copy");
+        copy_path(&worktree.join("node_modules"), &staging).unwrap();
+        assert_eq!(path_size(&staging).unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(staging.join("left-pad/index.js")).unwrap(),
+            "pad"
+        );
+
+        remove_path(&staging).unwrap();
+        remove_path(&staging).unwrap();
+        assert!(!staging.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+//! Expiring links that let someone without access to the app read one attempt's diff and logs,
+//! e.g. a teammate asked to review it.
+//!
+//! A link's token names the attempt and when the link expires, signed with HMAC-SHA256 using a
+//! key kept next to the config file, so links need no storage. Replacing the key revokes every
+//! link handed out so far.
+
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::{OnceLock, RwLock},
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Lifetime of links created without one
+pub const DEFAULT_EXPIRY_HOURS: u32 = 24;
+/// Longest lifetime a link can be given
+pub const MAX_EXPIRY_HOURS: u32 = 24 * 30;
+
+static SIGNER: OnceLock<ShareLinkSigner> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum ShareLinkError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Failed to create a signing key")]
+    Crypto,
+    #[error("This share link is invalid or has been revoked")]
+    Invalid,
+    #[error("This share link expired at {0}")]
+    Expired(DateTime<Utc>),
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct CreateShareLink {
+    /// Hours until the link expires, 24 when unset and at most 720
+    pub expires_in_hours: Option<u32>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ShareLink {
+    pub token: String,
+    pub task_attempt_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    /// Link to the read-only view of the attempt; unset when the address of the app is unknown
+    pub url: Option<String>,
+}
+
+/// Signs and checks share link tokens
+pub struct ShareLinkSigner {
+    path: PathBuf,
+    key: RwLock<hmac::Key>,
+}
+
+impl ShareLinkSigner {
+    /// The signer using the key in the asset directory, created on first use
+    pub fn global() -> Result<&'static Self, ShareLinkError> {
+        if let Some(signer) = SIGNER.get() {
+            return Ok(signer);
+        }
+        let signer = Self::load_or_create(utils::assets::share_link_key_path())?;
+        Ok(SIGNER.get_or_init(|| signer))
+    }
+
+    /// Read the key at `path`, writing a new random key readable only by the current user if
+    /// there is none
+    pub fn load_or_create(path: PathBuf) -> Result<Self, ShareLinkError> {
+        let key = match fs::read(&path) {
+            Ok(key) => key.try_into().map_err(|_| {
+                std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{} is not a 32-byte key", path.display()),
+                )
+            })?,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let key = random_key()?;
+                write_key(&path, &key)?;
+                tracing::info!("Created share link key at {}", path.display());
+                key
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            key: RwLock::new(hmac::Key::new(hmac::HMAC_SHA256, &key)),
+        })
+    }
+
+    /// A token granting access to the attempt until `expires_at`
+    pub fn token(&self, task_attempt_id: Uuid, expires_at: DateTime<Utc>) -> String {
+        let payload = format!("{}.{}", task_attempt_id.simple(), expires_at.timestamp());
+        let tag = hmac::sign(&self.key.read().unwrap(), payload.as_bytes());
+        format!("{payload}.{}", URL_SAFE_NO_PAD.encode(tag.as_ref()))
+    }
+
+    /// The attempt the token grants access to, if it was signed with the current key and hasn't
+    /// expired
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<Uuid, ShareLinkError> {
+        let (payload, signature) = token.rsplit_once('.').ok_or(ShareLinkError::Invalid)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| ShareLinkError::Invalid)?;
+        hmac::verify(&self.key.read().unwrap(), payload.as_bytes(), &signature)
+            .map_err(|_| ShareLinkError::Invalid)?;
+
+        let (task_attempt_id, expires_at) =
+            payload.split_once('.').ok_or(ShareLinkError::Invalid)?;
+        let task_attempt_id =
+            Uuid::parse_str(task_attempt_id).map_err(|_| ShareLinkError::Invalid)?;
+        let expires_at = expires_at
+            .parse()
+            .ok()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .ok_or(ShareLinkError::Invalid)?;
+        if expires_at <= now {
+            return Err(ShareLinkError::Expired(expires_at));
+        }
+        Ok(task_attempt_id)
+    }
+
+    /// Replace the key with a new one, revoking every link created so far
+    pub fn rotate(&self) -> Result<(), ShareLinkError> {
+        let key = random_key()?;
+        write_key(&self.path, &key)?;
+        *self.key.write().unwrap() = hmac::Key::new(hmac::HMAC_SHA256, &key);
+        tracing::info!("Replaced the share link key, revoking all share links");
+        Ok(())
+    }
+
+    /// Create a link to the attempt. `base_url` is where the app is reached, for the link's URL.
+    pub fn create(
+        &self,
+        task_attempt_id: Uuid,
+        request: &CreateShareLink,
+        base_url: Option<String>,
+    ) -> ShareLink {
+        let hours = request
+            .expires_in_hours
+            .unwrap_or(DEFAULT_EXPIRY_HOURS)
+            .clamp(1, MAX_EXPIRY_HOURS);
+        let expires_at = Utc::now() + Duration::hours(hours.into());
+        let token = self.token(task_attempt_id, expires_at);
+        ShareLink {
+            url: base_url.map(|base| format!("{base}/shared/{token}")),
+            token,
+            task_attempt_id,
+            expires_at,
+        }
+    }
+}
+
+fn random_key() -> Result<[u8; 32], ShareLinkError> {
+    let mut key = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| ShareLinkError::Crypto)?;
+    Ok(key)
+}
+
+fn write_key(path: &Path, key: &[u8; 32]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_tokens_until_they_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let signer = ShareLinkSigner::load_or_create(dir.path().join("share.key")).unwrap();
+        let attempt_id = Uuid::new_v4();
+        let now = Utc::now();
+        let token = signer.token(attempt_id, now + Duration::hours(1));
+
+        assert_eq!(signer.verify(&token, now).unwrap(), attempt_id);
+        assert!(matches!(
+            signer.verify(&token, now + Duration::hours(2)),
+            Err(ShareLinkError::Expired(_))
+        ));
+
+        // Extending the expiry invalidates the signature
+        let (payload, signature) = token.rsplit_once('.').unwrap();
+        let (id, _) = payload.split_once('.').unwrap();
+        let forged = format!(
+            "{id}.{}.{signature}",
+            (now + Duration::days(365)).timestamp()
+        );
+        assert!(matches!(
+            signer.verify(&forged, now),
+            Err(ShareLinkError::Invalid)
+        ));
+        assert!(matches!(
+            signer.verify("not-a-token", now),
+            Err(ShareLinkError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn rotating_the_key_revokes_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("share.key");
+        let signer = ShareLinkSigner::load_or_create(path.clone()).unwrap();
+        let now = Utc::now();
+        let link = signer.create(
+            Uuid::new_v4(),
+            &CreateShareLink::default(),
+            Some("https://kanban.lan".to_string()),
+        );
+        assert_eq!(
+            link.url.as_deref(),
+            Some(format!("https://kanban.lan/shared/{}", link.token).as_str())
+        );
+
+        // The key survives a restart
+        let reloaded = ShareLinkSigner::load_or_create(path.clone()).unwrap();
+        assert!(reloaded.verify(&link.token, now).is_ok());
+
+        signer.rotate().unwrap();
+        assert!(signer.verify(&link.token, now).is_err());
+        let reloaded = ShareLinkSigner::load_or_create(path).unwrap();
+        assert!(reloaded.verify(&link.token, now).is_err());
+    }
+}
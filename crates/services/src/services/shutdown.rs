@@ -0,0 +1,14 @@
+//! Whether the server is shutting down. From then on no execution processes are started, so
+//! the running ones can be stopped without queued ones or follow-ups taking their place.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+pub fn begin() {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+}
+
+pub fn in_progress() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
@@ -0,0 +1,453 @@
+//! Where vibe-kanban keeps its data (task attempt worktrees, task images and the SQLite
+//! database, which also holds execution logs) and moving it to other directories.
+//!
+//! Moves are requested while the app runs and carried out on the next start, before anything
+//! has the files open.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use db::models::task_attempt::TaskAttempt;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use ts_rs::TS;
+
+use crate::services::{
+    config::{StorageConfig, StorageRelocation},
+    git_cli::GitCli,
+    worktree_manager::WorktreeManager,
+};
+
+pub const DATABASE_FILE: &str = "db.sqlite";
+
+/// The database and the files SQLite keeps next to it. The database directory is shared with
+/// the config, so only these are moved.
+const DATABASE_FILES: [&str; 3] = ["db.sqlite", "db.sqlite-wal", "db.sqlite-shm"];
+
+const WRITE_PROBE: &str = ".vibe-kanban-write-test";
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Invalid {root} directory '{path}': {reason}")]
+    InvalidTarget {
+        root: &'static str,
+        path: String,
+        reason: String,
+    },
+    #[error("No new directory given")]
+    NothingToRelocate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Root {
+    Worktrees,
+    Images,
+    Database,
+}
+
+impl Root {
+    fn name(self) -> &'static str {
+        match self {
+            Root::Worktrees => "worktree",
+            Root::Images => "image",
+            Root::Database => "database",
+        }
+    }
+}
+
+/// Resolved storage directories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageRoots {
+    pub worktrees: PathBuf,
+    pub images: PathBuf,
+    pub database: PathBuf,
+}
+
+impl StorageRoots {
+    pub fn resolve(config: &StorageConfig) -> Self {
+        Self {
+            worktrees: config
+                .worktree_dir
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(WorktreeManager::default_worktree_base_dir),
+            images: config
+                .image_dir
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| utils::cache_dir().join("images")),
+            database: config
+                .database_dir
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(utils::assets::asset_dir),
+        }
+    }
+
+    pub fn database_file(&self) -> PathBuf {
+        self.database.join(DATABASE_FILE)
+    }
+
+    fn get(&self, root: Root) -> &Path {
+        match root {
+            Root::Worktrees => &self.worktrees,
+            Root::Images => &self.images,
+            Root::Database => &self.database,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct StorageInfo {
+    pub worktree_dir: String,
+    pub image_dir: String,
+    pub database_dir: String,
+    /// Move that will be carried out on the next start
+    pub pending_relocation: Option<StorageRelocation>,
+}
+
+impl StorageInfo {
+    pub fn new(config: &StorageConfig) -> Self {
+        let roots = StorageRoots::resolve(config);
+        Self {
+            worktree_dir: roots.worktrees.to_string_lossy().to_string(),
+            image_dir: roots.images.to_string_lossy().to_string(),
+            database_dir: roots.database.to_string_lossy().to_string(),
+            pending_relocation: config.relocation.clone(),
+        }
+    }
+}
+
+/// Worktree directory that was moved. Container refs still point at `from` until
+/// [`update_worktree_refs`] runs.
+#[derive(Debug, Clone)]
+pub struct WorktreeMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+fn targets(relocation: &StorageRelocation) -> impl Iterator<Item = (Root, &str)> {
+    [
+        (Root::Worktrees, relocation.worktree_dir.as_deref()),
+        (Root::Images, relocation.image_dir.as_deref()),
+        (Root::Database, relocation.database_dir.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(root, target)| target.map(|target| (root, target)))
+}
+
+/// Check a requested move and return it with normalized paths, ready to be saved to the config
+pub fn validate_relocation(
+    config: &StorageConfig,
+    relocation: &StorageRelocation,
+) -> Result<StorageRelocation, StorageError> {
+    let roots = StorageRoots::resolve(config);
+    let mut normalized = StorageRelocation::default();
+    for (root, target) in targets(relocation) {
+        // Drops trailing separators, so container refs can be matched by prefix
+        let target: PathBuf = Path::new(target.trim()).components().collect();
+        validate_target(root, roots.get(root), &target)?;
+        let target = Some(target.to_string_lossy().to_string());
+        match root {
+            Root::Worktrees => normalized.worktree_dir = target,
+            Root::Images => normalized.image_dir = target,
+            Root::Database => normalized.database_dir = target,
+        }
+    }
+    if normalized == StorageRelocation::default() {
+        return Err(StorageError::NothingToRelocate);
+    }
+    Ok(normalized)
+}
+
+fn validate_target(root: Root, current: &Path, target: &Path) -> Result<(), StorageError> {
+    let invalid = |reason: &str| StorageError::InvalidTarget {
+        root: root.name(),
+        path: target.to_string_lossy().to_string(),
+        reason: reason.to_string(),
+    };
+    if !target.is_absolute() {
+        return Err(invalid("must be an absolute path"));
+    }
+    if target == current {
+        return Err(invalid("is the current directory"));
+    }
+    match fs::read_dir(target) {
+        Ok(mut entries) => match root {
+            Root::Database => {
+                if DATABASE_FILES.iter().any(|name| target.join(name).exists()) {
+                    return Err(invalid("already contains a database"));
+                }
+            }
+            Root::Worktrees | Root::Images => {
+                if entries.next().is_some() {
+                    return Err(invalid("must be empty"));
+                }
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(invalid(&e.to_string())),
+    }
+    if root != Root::Database && (target.starts_with(current) || current.starts_with(target)) {
+        return Err(invalid(
+            "cannot be inside the current directory or contain it",
+        ));
+    }
+    // Find out about permissions now rather than halfway through the move
+    fs::create_dir_all(target)
+        .and_then(|()| fs::write(target.join(WRITE_PROBE), b""))
+        .and_then(|()| fs::remove_file(target.join(WRITE_PROBE)))
+        .map_err(|e| invalid(&format!("is not writable: {e}")))
+}
+
+/// Carry out the move saved in `config`, and point `config` at the new directories. Must run
+/// before the database is opened. Directories that fail to move stay where they were.
+pub fn apply_relocation(config: &mut StorageConfig) -> Option<WorktreeMove> {
+    let relocation = config.relocation.take()?;
+    let roots = StorageRoots::resolve(config);
+    let mut worktree_move = None;
+    for (root, target) in targets(&relocation) {
+        let from = roots.get(root);
+        let to = Path::new(target);
+        let moved = validate_target(root, from, to).and_then(|()| {
+            match root {
+                Root::Database => move_database(from, to),
+                Root::Worktrees | Root::Images => move_dir(from, to),
+            }
+            .map_err(StorageError::from)
+        });
+        if let Err(e) = moved {
+            error!(
+                "Failed to move {} data from {} to {}: {}",
+                root.name(),
+                from.display(),
+                to.display(),
+                e
+            );
+            continue;
+        }
+        info!(
+            "Moved {} data from {} to {}",
+            root.name(),
+            from.display(),
+            to.display()
+        );
+        let target = Some(target.to_string());
+        match root {
+            Root::Worktrees => {
+                config.worktree_dir = target;
+                worktree_move = Some(WorktreeMove {
+                    from: from.to_path_buf(),
+                    to: to.to_path_buf(),
+                });
+            }
+            Root::Images => config.image_dir = target,
+            Root::Database => config.database_dir = target,
+        }
+    }
+    worktree_move
+}
+
+/// Point the attempts' container refs and their repositories' worktree metadata at the
+/// directory the worktrees were moved to. Returns the number of attempts updated.
+pub async fn update_worktree_refs(
+    pool: &SqlitePool,
+    worktree_move: &WorktreeMove,
+) -> Result<u64, StorageError> {
+    let updated = TaskAttempt::relocate_container_refs(
+        pool,
+        &worktree_move.from.to_string_lossy(),
+        &worktree_move.to.to_string_lossy(),
+    )
+    .await?;
+
+    let worktrees = TaskAttempt::find_active_worktrees(pool).await?;
+    let base_dir = worktree_move.to.clone();
+    tokio::task::spawn_blocking(move || {
+        let git = GitCli::new();
+        for (attempt_id, container_ref, git_repo_path) in worktrees {
+            let worktree_path = Path::new(&container_ref);
+            if !worktree_path.starts_with(&base_dir) || !worktree_path.exists() {
+                continue;
+            }
+            if let Err(e) = git.worktree_repair(Path::new(&git_repo_path), worktree_path) {
+                warn!(
+                    "Failed to repair moved worktree of attempt {}: {}",
+                    attempt_id, e
+                );
+            }
+        }
+    })
+    .await
+    .map_err(io::Error::other)?;
+
+    info!("Updated the worktree paths of {} task attempts", updated);
+    Ok(updated)
+}
+
+fn move_dir(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.exists() {
+        return fs::create_dir_all(to);
+    }
+    // Renaming onto an existing directory, even an empty one, fails on some platforms
+    match fs::remove_dir(to) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // Different file systems: copy, and only remove the original once everything arrived
+    if let Err(e) = copy_dir(from, to) {
+        let _ = fs::remove_dir_all(to);
+        return Err(e);
+    }
+    fs::remove_dir_all(from)
+}
+
+pub(crate) fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    // Last, in case the directory is read-only
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+#[cfg(unix)]
+pub(crate) fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(windows)]
+pub(crate) fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let link = fs::read_link(from)?;
+    if fs::metadata(from).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(link, to)
+    } else {
+        std::os::windows::fs::symlink_file(link, to)
+    }
+}
+
+/// Copy the database files before removing any, so a failure never splits the database from
+/// its write-ahead log
+fn move_database(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    let files: Vec<&str> = DATABASE_FILES
+        .into_iter()
+        .filter(|name| from.join(name).exists())
+        .collect();
+    for name in &files {
+        if let Err(e) = fs::copy(from.join(name), to.join(name)) {
+            for name in &files {
+                let _ = fs::remove_file(to.join(name));
+            }
+            return Err(e);
+        }
+    }
+    for name in &files {
+        fs::remove_file(from.join(name))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn config_in(dir: &Path) -> StorageConfig {
+        StorageConfig {
+            worktree_dir: Some(dir.join("worktrees").to_string_lossy().to_string()),
+            image_dir: Some(dir.join("images").to_string_lossy().to_string()),
+            database_dir: Some(dir.join("data").to_string_lossy().to_string()),
+            relocation: None,
+        }
+    }
+
+    #[test]
+    fn rejects_unusable_targets() {
+        let tmp = TempDir::new().unwrap();
+        let config = config_in(tmp.path());
+        let relocate_images = |target: &str| {
+            validate_relocation(
+                &config,
+                &StorageRelocation {
+                    image_dir: Some(target.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(matches!(
+            validate_relocation(&config, &StorageRelocation::default()),
+            Err(StorageError::NothingToRelocate)
+        ));
+        assert!(relocate_images("relative/images").is_err());
+        assert!(relocate_images(&tmp.path().join("images/nested").to_string_lossy()).is_err());
+        fs::create_dir_all(tmp.path().join("busy")).unwrap();
+        fs::write(tmp.path().join("busy/file"), "x").unwrap();
+        assert!(relocate_images(&tmp.path().join("busy").to_string_lossy()).is_err());
+
+        let target = tmp.path().join("elsewhere/images/");
+        let relocation = relocate_images(&target.to_string_lossy()).unwrap();
+        assert_eq!(
+            relocation.image_dir.as_deref(),
+            Some(&*tmp.path().join("elsewhere/images").to_string_lossy())
+        );
+    }
+
+    #[test]
+    fn moves_data_and_updates_config() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = config_in(tmp.path());
+        fs::create_dir_all(tmp.path().join("images")).unwrap();
+        fs::write(tmp.path().join("images/a.png"), "png").unwrap();
+        fs::create_dir_all(tmp.path().join("worktrees/attempt/src")).unwrap();
+        fs::write(tmp.path().join("worktrees/attempt/src/lib.rs"), "code").unwrap();
+        fs::create_dir_all(tmp.path().join("data")).unwrap();
+        fs::write(tmp.path().join("data/db.sqlite"), "db").unwrap();
+        fs::write(tmp.path().join("data/config.json"), "{}").unwrap();
+
+        let big_disk = tmp.path().join("big");
+        let relocation = StorageRelocation {
+            worktree_dir: Some(big_disk.join("worktrees").to_string_lossy().to_string()),
+            image_dir: Some(big_disk.join("images").to_string_lossy().to_string()),
+            database_dir: Some(big_disk.join("data").to_string_lossy().to_string()),
+        };
+        config.relocation = Some(validate_relocation(&config, &relocation).unwrap());
+
+        let worktree_move = apply_relocation(&mut config).unwrap();
+        assert_eq!(worktree_move.from, tmp.path().join("worktrees"));
+        assert_eq!(worktree_move.to, big_disk.join("worktrees"));
+        assert!(config.relocation.is_none());
+
+        let roots = StorageRoots::resolve(&config);
+        assert_eq!(roots.images, big_disk.join("images"));
+        assert!(roots.images.join("a.png").is_file());
+        assert!(roots.worktrees.join("attempt/src/lib.rs").is_file());
+        assert!(roots.database_file().is_file());
+        assert!(!tmp.path().join("images").exists());
+        assert!(!tmp.path().join("data/db.sqlite").exists());
+        // The config next to the database stays put
+        assert!(tmp.path().join("data/config.json").is_file());
+    }
+}
@@ -0,0 +1,424 @@
+//! Imports a backlog of tasks into a project from CSV or JSON.
+//!
+//! CSV needs a header row; JSON is an array of objects. The mapping names the column or field
+//! that holds each task field, compared case-insensitively. Tags are separated by commas or
+//! semicolons, and tags that don't exist yet are created. Rows without a title, with an
+//! unknown status, or with the title of a task the project already has are skipped.
+
+use std::collections::{HashMap, HashSet};
+
+use db::models::{
+    tag::{CreateTag, Tag},
+    task::{CreateTask, Task, TaskStatus},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Most rows imported by one request
+pub const MAX_IMPORT_ROWS: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskImportFormat {
+    Csv,
+    Json,
+}
+
+/// Columns or fields holding each task field; unset ones use the field's own name
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct TaskImportMapping {
+    #[serde(default)]
+    #[ts(optional)]
+    pub title: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub status: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub tags: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TaskImportRequest {
+    pub format: TaskImportFormat,
+    /// CSV with a header row, or a JSON array of objects
+    pub content: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub mapping: TaskImportMapping,
+    /// Check the rows and report what would be imported without creating anything
+    #[serde(default)]
+    #[ts(optional)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SkippedImportRow {
+    /// 1-based, not counting the CSV header
+    pub row: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TaskImportSummary {
+    pub dry_run: bool,
+    pub total_rows: u32,
+    /// Tasks created, or that would be created in a dry run
+    pub created: u32,
+    pub skipped: Vec<SkippedImportRow>,
+    /// Tags created, or that would be created, because no tag had their name
+    pub new_tags: Vec<String>,
+    /// The created tasks; empty in a dry run
+    pub task_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Error)]
+pub enum TaskImportError {
+    #[error("Invalid CSV: {0}")]
+    Csv(&'static str),
+    #[error("Invalid JSON: {0}")]
+    Json(String),
+    #[error("There is no '{0}' column for task titles")]
+    MissingTitleColumn(String),
+    #[error("At most {MAX_IMPORT_ROWS} rows can be imported at once")]
+    TooManyRows,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// A row of the file, by lowercased column or field name
+type Record = HashMap<String, String>;
+
+#[derive(Debug, PartialEq)]
+struct ImportRow {
+    title: String,
+    description: Option<String>,
+    status: TaskStatus,
+    tags: Vec<String>,
+}
+
+fn column(name: &Option<String>, default: &str) -> String {
+    name.as_deref().unwrap_or(default).trim().to_lowercase()
+}
+
+/// Rows of RFC 4180 CSV: quoted fields may contain commas, newlines and doubled quotes
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, TaskImportError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(TaskImportError::Csv("a quoted field is never closed"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| !(row.len() == 1 && row[0].trim().is_empty()));
+    Ok(rows)
+}
+
+fn csv_records(content: &str, title_column: &str) -> Result<Vec<Record>, TaskImportError> {
+    let mut rows = parse_csv(content)?.into_iter();
+    let Some(header) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    if !header.iter().any(|name| name == title_column) {
+        return Err(TaskImportError::MissingTitleColumn(
+            title_column.to_string(),
+        ));
+    }
+    Ok(rows
+        .map(|row| header.iter().cloned().zip(row).collect())
+        .collect())
+}
+
+fn json_records(content: &str) -> Result<Vec<Record>, TaskImportError> {
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_str(content).map_err(|e| TaskImportError::Json(e.to_string()))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .filter_map(|(name, value)| {
+                    let value = match value {
+                        serde_json::Value::Null => return None,
+                        serde_json::Value::String(value) => value,
+                        serde_json::Value::Array(values) => values
+                            .iter()
+                            .map(|value| match value {
+                                serde_json::Value::String(value) => value.clone(),
+                                other => other.to_string(),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(","),
+                        other => other.to_string(),
+                    };
+                    Some((name.to_lowercase(), value))
+                })
+                .collect()
+        })
+        .collect())
+}
+
+fn parse_status(value: &str) -> Option<TaskStatus> {
+    let normalized: String = value
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .to_lowercase();
+    match normalized.as_str() {
+        "" | "todo" => Some(TaskStatus::Todo),
+        "inprogress" => Some(TaskStatus::InProgress),
+        "inreview" => Some(TaskStatus::InReview),
+        "done" => Some(TaskStatus::Done),
+        "cancelled" | "canceled" => Some(TaskStatus::Cancelled),
+        _ => None,
+    }
+}
+
+/// Tag names can't contain spaces, so spaces inside a name become `_`
+fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for name in value.split([',', ';']) {
+        let name = name.split_whitespace().collect::<Vec<_>>().join("_");
+        if !name.is_empty() && !tags.contains(&name) {
+            tags.push(name);
+        }
+    }
+    tags
+}
+
+fn parse_row(mapping: &TaskImportMapping, record: &Record) -> Result<ImportRow, String> {
+    let field = |name: &Option<String>, default: &str| {
+        record
+            .get(&column(name, default))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let title = field(&mapping.title, "title").ok_or("The title is empty")?;
+    let status = match field(&mapping.status, "status") {
+        Some(value) => parse_status(value).ok_or(format!("Unknown status '{value}'"))?,
+        None => TaskStatus::Todo,
+    };
+    Ok(ImportRow {
+        title: title.to_string(),
+        description: field(&mapping.description, "description").map(str::to_string),
+        status,
+        tags: field(&mapping.tags, "tags").map_or_else(Vec::new, parse_tags),
+    })
+}
+
+/// Import the rows of `request` as tasks of the project, or only check them in a dry run
+pub async fn import(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    request: &TaskImportRequest,
+) -> Result<TaskImportSummary, TaskImportError> {
+    let records = match request.format {
+        TaskImportFormat::Csv => {
+            csv_records(&request.content, &column(&request.mapping.title, "title"))?
+        }
+        TaskImportFormat::Json => json_records(&request.content)?,
+    };
+    if records.len() > MAX_IMPORT_ROWS {
+        return Err(TaskImportError::TooManyRows);
+    }
+
+    let mut titles: HashSet<String> = Task::find_titles_by_project_id(pool, project_id)
+        .await?
+        .into_iter()
+        .map(|title| title.to_lowercase())
+        .collect();
+    let mut tag_ids: HashMap<String, Option<Uuid>> = HashMap::new();
+    let mut summary = TaskImportSummary {
+        dry_run: request.dry_run,
+        total_rows: records.len() as u32,
+        created: 0,
+        skipped: Vec::new(),
+        new_tags: Vec::new(),
+        task_ids: Vec::new(),
+    };
+
+    for (index, record) in records.iter().enumerate() {
+        let row_number = index as u32 + 1;
+        let row = match parse_row(&request.mapping, record) {
+            Ok(row) => row,
+            Err(reason) => {
+                summary.skipped.push(SkippedImportRow {
+                    row: row_number,
+                    reason,
+                });
+                continue;
+            }
+        };
+        if !titles.insert(row.title.to_lowercase()) {
+            summary.skipped.push(SkippedImportRow {
+                row: row_number,
+                reason: format!("A task titled '{}' already exists", row.title),
+            });
+            continue;
+        }
+
+        let mut row_tag_ids = Vec::with_capacity(row.tags.len());
+        for name in &row.tags {
+            let id = match tag_ids.get(name) {
+                Some(id) => *id,
+                None => {
+                    let id = match Tag::find_by_name(pool, name).await? {
+                        Some(tag) => Some(tag.id),
+                        None => {
+                            summary.new_tags.push(name.clone());
+                            if request.dry_run {
+                                None
+                            } else {
+                                let tag = Tag::create(
+                                    pool,
+                                    &CreateTag {
+                                        tag_name: name.clone(),
+                                        content: name.clone(),
+                                        variables: Vec::new(),
+                                    },
+                                )
+                                .await?;
+                                Some(tag.id)
+                            }
+                        }
+                    };
+                    tag_ids.insert(name.clone(), id);
+                    id
+                }
+            };
+            row_tag_ids.extend(id);
+        }
+
+        summary.created += 1;
+        if request.dry_run {
+            continue;
+        }
+        let task = Task::create(
+            pool,
+            &CreateTask::from_title_description(project_id, row.title, row.description),
+            Uuid::new_v4(),
+        )
+        .await?;
+        if row.status != TaskStatus::Todo {
+            Task::update_status(pool, task.id, row.status).await?;
+        }
+        if !row_tag_ids.is_empty() {
+            Task::set_tags(pool, task.id, row_tag_ids).await?;
+        }
+        summary.task_ids.push(task.id);
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_csv_fields() {
+        let csv =
+            "title,description\r\n\"Fix \"\"flaky\"\" test\",\"line one\nline two\"\n\nPlain,\n";
+        assert_eq!(
+            parse_csv(csv).unwrap(),
+            vec![
+                vec!["title", "description"],
+                vec!["Fix \"flaky\" test", "line one\nline two"],
+                vec!["Plain", ""],
+            ]
+        );
+        assert!(matches!(
+            parse_csv("title\n\"never closed"),
+            Err(TaskImportError::Csv(_))
+        ));
+    }
+
+    #[test]
+    fn maps_csv_columns_to_task_fields() {
+        let mapping = TaskImportMapping {
+            title: Some("Summary".to_string()),
+            status: Some("State".to_string()),
+            ..Default::default()
+        };
+        let records = csv_records(
+            "Summary,State,Tags\nShip it,In Progress,\"backend; needs review\"\n,done,\nOld,wontfix,",
+            "summary",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_row(&mapping, &records[0]).unwrap(),
+            ImportRow {
+                title: "Ship it".to_string(),
+                description: None,
+                status: TaskStatus::InProgress,
+                tags: vec!["backend".to_string(), "needs_review".to_string()],
+            }
+        );
+        assert_eq!(
+            parse_row(&mapping, &records[1]).unwrap_err(),
+            "The title is empty"
+        );
+        assert_eq!(
+            parse_row(&mapping, &records[2]).unwrap_err(),
+            "Unknown status 'wontfix'"
+        );
+        assert!(matches!(
+            csv_records("name\nx", "title"),
+            Err(TaskImportError::MissingTitleColumn(_))
+        ));
+    }
+
+    #[test]
+    fn reads_json_objects() {
+        let records = json_records(
+            r#"[{"Title": "Bump deps", "tags": ["chore", "deps"], "status": "todo", "points": 3, "description": null}]"#,
+        )
+        .unwrap();
+        let row = parse_row(&TaskImportMapping::default(), &records[0]).unwrap();
+        assert_eq!(row.title, "Bump deps");
+        assert_eq!(row.tags, vec!["chore", "deps"]);
+        assert_eq!(row.description, None);
+        assert_eq!(records[0]["points"], "3");
+        assert!(matches!(
+            json_records(r#"{"title": "not an array"}"#),
+            Err(TaskImportError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,409 @@
+//! Turns payloads posted to a project's [`TaskInbox`] into tasks.
+//!
+//! JSON bodies are used as they are. Raw emails (`message/rfc822`, as piped by most mail
+//! forwarders) become an object of their lowercased headers plus `body`, the text of the
+//! message's first `text/plain` part (or, failing that, of its HTML part with the markup
+//! removed) with base64 and quoted-printable encodings undone. Other text bodies are exposed as
+//! `body`.
+//!
+//! Mail has to be posted to the inbox URL; polling a mailbox over IMAP is not supported yet.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use db::models::task_inbox::TaskInbox;
+use regex::{Captures, Regex};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Longest title kept from a rendered template
+const MAX_TITLE_CHARS: usize = 200;
+
+const FALLBACK_TITLE: &str = "Inbound task";
+
+lazy_static::lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{([A-Za-z0-9_.\-]+)\}").unwrap();
+    static ref ENCODED_WORD: Regex = Regex::new(r"=\?([^?\s]+)\?([BbQq])\?([^?\s]*)\?=").unwrap();
+    /// Whitespace between two encoded words, which is not part of the text
+    static ref ENCODED_WORD_GAP: Regex = Regex::new(r"\?=\s+=\?").unwrap();
+    static ref HTML_TAG: Regex = Regex::new(r"(?is)<(script|style)\b.*?</(script|style)>|<[^>]*>").unwrap();
+}
+
+#[derive(Debug, Error)]
+pub enum TaskInboxError {
+    #[error("No inbox found for this URL")]
+    NotFound,
+    #[error("Invalid JSON payload: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Payload is not valid UTF-8")]
+    InvalidEncoding,
+}
+
+/// Title and description rendered from an inbound payload
+#[derive(Debug, PartialEq)]
+pub struct InboxTask {
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// Parse a request body according to its `Content-Type`
+pub fn parse_payload(content_type: Option<&str>, body: &[u8]) -> Result<Value, TaskInboxError> {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    if mime == "application/json" || mime.ends_with("+json") {
+        return Ok(serde_json::from_slice(body)?);
+    }
+    let text = std::str::from_utf8(body).map_err(|_| TaskInboxError::InvalidEncoding)?;
+    if mime == "message/rfc822" {
+        return Ok(parse_email(text));
+    }
+    let mut fields = Map::new();
+    fields.insert("body".to_string(), Value::String(text.to_string()));
+    Ok(Value::Object(fields))
+}
+
+/// Split a raw email into its headers and the text of its body. Folded header lines are
+/// joined, encoded words in them decoded, and repeated headers keep their first value.
+pub fn parse_email(raw: &str) -> Value {
+    let raw = raw.replace("\r\n", "\n");
+    let (mut fields, body) = split_entity(&raw);
+    let text = find_text(&fields, body, "text/plain")
+        .or_else(|| find_text(&fields, body, "text/html").map(|html| strip_html(&html)))
+        .unwrap_or_default();
+    fields.insert("body".to_string(), Value::String(text.trim().to_string()));
+    Value::Object(fields)
+}
+
+/// The headers and body of a MIME entity: a whole message or one part of a multipart body
+fn split_entity(raw: &str) -> (Map<String, Value>, &str) {
+    let (head, body) = raw.split_once("\n\n").unwrap_or((raw, ""));
+
+    let mut fields = Map::new();
+    let mut current: Option<(String, String)> = None;
+    let insert = |fields: &mut Map<String, Value>, (name, value): (String, String)| {
+        fields
+            .entry(name)
+            .or_insert(Value::String(decode_encoded_words(&value)));
+    };
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = current.as_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some(header) = current.take() {
+            insert(&mut fields, header);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            current = Some((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    if let Some(header) = current {
+        insert(&mut fields, header);
+    }
+    (fields, body)
+}
+
+fn header<'a>(headers: &'a Map<String, Value>, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(Value::as_str)
+}
+
+/// The lowercased media type of an entity and its parameters, e.g. `charset` or `boundary`
+fn content_type(headers: &Map<String, Value>) -> (String, Vec<(String, String)>) {
+    let value = header(headers, "content-type").unwrap_or("text/plain");
+    let mut items = value.split(';');
+    let mime = items.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let params = items
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| {
+            (
+                name.trim().to_ascii_lowercase(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    (mime, params)
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(param, _)| param == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// The decoded text of the first part of type `mime` that isn't an attachment, looking inside
+/// nested multipart bodies
+fn find_text(headers: &Map<String, Value>, body: &str, mime: &str) -> Option<String> {
+    let (entity_mime, params) = content_type(headers);
+    if entity_mime.starts_with("multipart/") {
+        let boundary = param(&params, "boundary")?;
+        return multipart_parts(body, boundary)
+            .into_iter()
+            .find_map(|part| {
+                let (part_headers, part_body) = split_entity(part);
+                find_text(&part_headers, part_body, mime)
+            });
+    }
+    let attachment = header(headers, "content-disposition").is_some_and(|disposition| {
+        disposition
+            .trim()
+            .to_ascii_lowercase()
+            .starts_with("attachment")
+    });
+    if entity_mime != mime || attachment {
+        return None;
+    }
+    let bytes = match header(headers, "content-transfer-encoding")
+        .map(|encoding| encoding.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("base64") => {
+            let encoded: String = body.split_whitespace().collect();
+            STANDARD
+                .decode(encoded)
+                .unwrap_or_else(|_| body.as_bytes().to_vec())
+        }
+        Some("quoted-printable") => decode_quoted_printable(body),
+        _ => body.as_bytes().to_vec(),
+    };
+    Some(decode_charset(&bytes, param(&params, "charset")))
+}
+
+/// The parts of a multipart body, without the preamble and epilogue around them
+fn multipart_parts<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    for section in body.split(delimiter.as_str()).skip(1) {
+        // The closing delimiter has a trailing `--`
+        if section.starts_with("--") {
+            break;
+        }
+        // Skip the rest of the delimiter line; the line break before the next one belongs to it
+        let part = section.split_once('\n').map_or("", |(_, part)| part);
+        parts.push(part.strip_suffix('\n').unwrap_or(part));
+    }
+    parts
+}
+
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // A soft line break, where a long line was wrapped
+            let rest = &bytes[i + 1..];
+            let padding = rest
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t'))
+                .count();
+            if rest.get(padding) == Some(&b'\n') {
+                i += padding + 2;
+                continue;
+            }
+            if let Some(byte) = rest
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    decoded
+}
+
+/// Text in `charset`, read as UTF-8 unless it is Latin-1
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    match charset.map(str::to_ascii_lowercase).as_deref() {
+        Some("iso-8859-1" | "latin1" | "l1") => bytes.iter().map(|&b| char::from(b)).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Decode RFC 2047 encoded words, e.g. `=?UTF-8?B?...?=`, as used for non-ASCII subjects
+fn decode_encoded_words(value: &str) -> String {
+    if !value.contains("=?") {
+        return value.to_string();
+    }
+    let value = ENCODED_WORD_GAP.replace_all(value, "?==?");
+    ENCODED_WORD
+        .replace_all(&value, |caps: &Captures| {
+            // A language may follow the charset, as in `UTF-8*en`
+            let charset = caps[1].split('*').next().unwrap_or_default();
+            let bytes = if caps[2].eq_ignore_ascii_case("b") {
+                match STANDARD.decode(&caps[3]) {
+                    Ok(bytes) => bytes,
+                    Err(_) => return caps[0].to_string(),
+                }
+            } else {
+                decode_quoted_printable(&caps[3].replace('_', " "))
+            };
+            decode_charset(&bytes, Some(charset))
+        })
+        .into_owned()
+}
+
+/// Text of an HTML body: tags, scripts and styles removed and common entities decoded
+fn strip_html(html: &str) -> String {
+    let text = HTML_TAG.replace_all(html, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Replace `{path}` placeholders with values from `payload`; missing values render as
+/// empty strings
+pub fn render_template(template: &str, payload: &Value) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &Captures| {
+            lookup(payload, &caps[1]).unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Render the inbox's templates for `payload`
+pub fn task_from_payload(inbox: &TaskInbox, payload: &Value) -> InboxTask {
+    let rendered = render_template(&inbox.title_template, payload);
+    let title: String = rendered
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(FALLBACK_TITLE)
+        .chars()
+        .take(MAX_TITLE_CHARS)
+        .collect();
+    let description = inbox
+        .description_template
+        .as_deref()
+        .map(|template| render_template(template, payload).trim().to_string())
+        .filter(|description| !description.is_empty());
+    InboxTask { title, description }
+}
+
+fn lookup(payload: &Value, path: &str) -> Option<String> {
+    let value = path
+        .split('.')
+        .try_fold(payload, |value, key| match value {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })?;
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn renders_nested_fields_and_array_items() {
+        let payload = json!({
+            "alerts": [{ "labels": { "severity": "critical" }, "count": 3 }],
+            "service": null,
+        });
+        assert_eq!(
+            render_template(
+                "[{alerts.0.labels.severity}] {alerts.0.count}x {service}{missing}",
+                &payload
+            ),
+            "[critical] 3x "
+        );
+    }
+
+    #[test]
+    fn parses_raw_emails() {
+        let email = "From: Ana <ana@example.com>\r\nSubject: Checkout page\r\n  crashes on submit\r\n\r\nSteps to reproduce:\r\n1. Submit\r\n";
+        let payload = parse_payload(Some("message/rfc822"), email.as_bytes()).unwrap();
+        assert_eq!(payload["subject"], "Checkout page crashes on submit");
+        assert_eq!(payload["from"], "Ana <ana@example.com>");
+        assert_eq!(payload["body"], "Steps to reproduce:\n1. Submit");
+    }
+
+    #[test]
+    fn decodes_multipart_emails() {
+        let email = concat!(
+            "From: =?UTF-8?Q?Jos=C3=A9?= <jose@example.com>\r\n",
+            "Subject: =?UTF-8?B?Q2Fmw6k=?= =?UTF-8?Q?_menu_broken?=\r\n",
+            "Content-Type: multipart/mixed; boundary=\"outer\"\r\n",
+            "\r\n",
+            "This is a multi-part message in MIME format.\r\n",
+            "--outer\r\n",
+            "Content-Type: multipart/alternative; boundary=inner\r\n",
+            "\r\n",
+            "--inner\r\n",
+            "Content-Type: text/html; charset=utf-8\r\n",
+            "\r\n",
+            "<p>HTML version</p>\r\n",
+            "--inner\r\n",
+            "Content-Type: text/plain; charset=\"utf-8\"\r\n",
+            "Content-Transfer-Encoding: quoted-printable\r\n",
+            "\r\n",
+            "The caf=C3=A9 page crashes when the list is long=\r\n",
+            "er than a page.\r\n",
+            "--inner--\r\n",
+            "--outer\r\n",
+            "Content-Type: text/plain\r\n",
+            "Content-Disposition: attachment; filename=log.txt\r\n",
+            "\r\n",
+            "attached log\r\n",
+            "--outer--\r\n",
+        );
+        let payload = parse_payload(Some("message/rfc822"), email.as_bytes()).unwrap();
+        assert_eq!(payload["subject"], "Café menu broken");
+        assert_eq!(payload["from"], "José <jose@example.com>");
+        assert_eq!(
+            payload["body"],
+            "The café page crashes when the list is longer than a page."
+        );
+    }
+
+    #[test]
+    fn decodes_single_part_encodings() {
+        let email = "Subject: Base64\nContent-Transfer-Encoding: base64\nContent-Type: text/plain; charset=iso-8859-1\n\nQ2Fm6SBidWc=\n";
+        let payload = parse_payload(Some("message/rfc822"), email.as_bytes()).unwrap();
+        assert_eq!(payload["body"], "Café bug");
+
+        let email = "Subject: HTML only\nContent-Type: text/html\n\n<html><style>p {}</style><p>Tom &amp; Jerry</p></html>\n";
+        let payload = parse_payload(Some("message/rfc822"), email.as_bytes()).unwrap();
+        assert_eq!(payload["body"], "Tom & Jerry");
+    }
+
+    #[test]
+    fn falls_back_to_a_single_line_title() {
+        let inbox = TaskInbox {
+            project_id: Uuid::new_v4(),
+            secret: "secret".to_string(),
+            title_template: "{subject}".to_string(),
+            description_template: Some("{body}".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let task = task_from_payload(&inbox, &json!({ "body": "details" }));
+        assert_eq!(task.title, FALLBACK_TITLE);
+        assert_eq!(task.description.as_deref(), Some("details"));
+
+        let task = task_from_payload(&inbox, &json!({ "subject": "\nFirst\nSecond" }));
+        assert_eq!(task.title, "First");
+        assert_eq!(task.description, None);
+    }
+}
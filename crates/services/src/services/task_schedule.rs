@@ -0,0 +1,305 @@
+//! Cron expressions for [`TaskSchedule`]s. Expressions are evaluated in the project's time
+//! zone, and runs that fall into the project's quiet hours wait until the window ends.
+//!
+//! The usual five fields are supported (minute, hour, day of month, month, day of week) with
+//! `*`, lists, ranges, steps, month and weekday names, and the `@hourly`, `@daily`, `@weekly`,
+//! `@monthly` and `@yearly` shorthands. As in cron, a day matches when either the day of month
+//! or the day of week matches if both are restricted.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use db::models::task_schedule::TaskSchedule;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::services::project_locale;
+
+/// How far ahead to look for the next matching day, so expressions such as `0 0 31 2 *`
+/// that never match don't loop forever
+const MAX_LOOKAHEAD_DAYS: u32 = 366 * 5;
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+#[derive(Debug, Error)]
+pub enum TaskScheduleError {
+    #[error("Invalid cron expression '{0}': {1}")]
+    InvalidCron(String, String),
+    #[error("Cron expression '{0}' never matches")]
+    NeverRuns(String),
+    #[error("A schedule runs either a tag or an existing task; set exactly one of them")]
+    InvalidSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpression {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Neither day field is `*`, so a day matches when either of them does
+    either_day: bool,
+}
+
+impl FromStr for CronExpression {
+    type Err = TaskScheduleError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            TaskScheduleError::InvalidCron(expression.to_string(), reason.to_string())
+        };
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(invalid("expected 5 fields"));
+        };
+
+        let days_of_week =
+            parse_field(day_of_week, 0, 7, WEEKDAY_NAMES, 0).map_err(|e| invalid(&e))?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0).map_err(|e| invalid(&e))?,
+            hours: parse_field(hour, 0, 23, &[], 0).map_err(|e| invalid(&e))?,
+            days_of_month: parse_field(day_of_month, 1, 31, &[], 0).map_err(|e| invalid(&e))?,
+            months: parse_field(month, 1, 12, MONTH_NAMES, 1).map_err(|e| invalid(&e))?,
+            // Both 0 and 7 are Sunday
+            days_of_week: (days_of_week | (days_of_week >> 7)) & 0x7f,
+            either_day: !day_of_month.starts_with('*') && !day_of_week.starts_with('*'),
+        })
+    }
+}
+
+/// Parse one field into a bit set of the values it matches. `names` are accepted in place of
+/// numbers, the first one standing for `first_name_value`.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name_value: u32,
+) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let lower = text.to_ascii_lowercase();
+        let value = match names.iter().position(|name| *name == lower) {
+            Some(index) => index as u32 + first_name_value,
+            None => text
+                .parse::<u32>()
+                .map_err(|_| format!("'{text}' is not a number"))?,
+        };
+        if value < min || value > max {
+            return Err(format!("{value} is outside {min}-{max}"));
+        }
+        Ok(value)
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("invalid step '{step}'")),
+            },
+            None => (part, None),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/15` runs from 5 to the end of the range
+                None if step.is_some() => (value(range)?, max),
+                None => {
+                    let value = value(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range '{range}' is backwards"));
+        }
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn contains(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+impl CronExpression {
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !contains(self.months, date.month()) {
+            return false;
+        }
+        let day_of_month = contains(self.days_of_month, date.day());
+        let day_of_week = contains(self.days_of_week, date.weekday().num_days_from_sunday());
+        if self.either_day {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+
+    /// The first matching minute strictly after `after`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        for _ in 0..MAX_LOOKAHEAD_DAYS {
+            if self.matches_day(date) {
+                let first_day = date == start.date();
+                for hour in (if first_day { start.hour() } else { 0 })..24 {
+                    if !contains(self.hours, hour) {
+                        continue;
+                    }
+                    let first_hour = first_day && hour == start.hour();
+                    for minute in (if first_hour { start.minute() } else { 0 })..60 {
+                        if contains(self.minutes, minute) {
+                            return Some(date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?));
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+/// Parse `expression`, rejecting expressions that never match
+pub fn parse(expression: &str) -> Result<CronExpression, TaskScheduleError> {
+    let cron = expression.parse::<CronExpression>()?;
+    if cron.next_after(Utc::now().naive_utc()).is_none() {
+        return Err(TaskScheduleError::NeverRuns(expression.to_string()));
+    }
+    Ok(cron)
+}
+
+/// Check that a schedule runs exactly one of a tag and a task
+pub fn validate_source(
+    tag_id: Option<Uuid>,
+    task_id: Option<Uuid>,
+) -> Result<(), TaskScheduleError> {
+    if tag_id.is_some() == task_id.is_some() {
+        return Err(TaskScheduleError::InvalidSource);
+    }
+    Ok(())
+}
+
+/// The first time after `after` that `cron` matches in the given time zone. Local times that
+/// are skipped by a daylight saving change are skipped by the schedule too.
+pub fn next_run(
+    cron: &CronExpression,
+    timezone: Option<&str>,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut local = project_locale::in_timezone(timezone, after);
+    loop {
+        local = cron.next_after(local)?;
+        if let Some(instant) = project_locale::from_local(timezone, local)
+            && instant > after
+        {
+            return Some(instant);
+        }
+    }
+}
+
+/// When an enabled schedule should next run after `after`; `None` while it is disabled or if
+/// its expression doesn't parse
+pub fn next_run_for(
+    schedule: &TaskSchedule,
+    timezone: Option<&str>,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if !schedule.enabled {
+        return None;
+    }
+    let cron = schedule.cron_expression.parse::<CronExpression>().ok()?;
+    next_run(&cron, timezone, after)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn next(expression: &str, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        expression
+            .parse::<CronExpression>()
+            .unwrap()
+            .next_after(after)
+    }
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        // 2025-11-05 is a Wednesday
+        let now = at(2025, 11, 5, 14, 30);
+        assert_eq!(next("0 2 * * *", now), Some(at(2025, 11, 6, 2, 0)));
+        assert_eq!(next("*/15 * * * *", now), Some(at(2025, 11, 5, 14, 45)));
+        assert_eq!(next("30 14 * * *", now), Some(at(2025, 11, 6, 14, 30)));
+        assert_eq!(next("0 9 * * mon-fri", now), Some(at(2025, 11, 6, 9, 0)));
+        assert_eq!(next("0 9 * * 7", now), Some(at(2025, 11, 9, 9, 0)));
+        assert_eq!(next("@monthly", now), Some(at(2025, 12, 1, 0, 0)));
+        assert_eq!(next("0 0 29 feb *", now), Some(at(2028, 2, 29, 0, 0)));
+    }
+
+    #[test]
+    fn either_day_field_matches_when_both_are_restricted() {
+        // The 13th, or any Friday
+        let now = at(2025, 11, 5, 0, 0);
+        assert_eq!(next("0 0 13 * 5", now), Some(at(2025, 11, 7, 0, 0)));
+        assert_eq!(
+            next("0 0 13 * 5", at(2025, 11, 10, 0, 0)),
+            Some(at(2025, 11, 13, 0, 0))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert!("0 2 * *".parse::<CronExpression>().is_err());
+        assert!("60 * * * *".parse::<CronExpression>().is_err());
+        assert!("*/0 * * * *".parse::<CronExpression>().is_err());
+        assert!("0 5-2 * * *".parse::<CronExpression>().is_err());
+        assert!("0 0 * smarch *".parse::<CronExpression>().is_err());
+        assert!(matches!(
+            parse("0 0 31 2 *"),
+            Err(TaskScheduleError::NeverRuns(_))
+        ));
+    }
+
+    #[test]
+    fn runs_in_the_project_time_zone() {
+        let cron = "0 2 * * *".parse::<CronExpression>().unwrap();
+        let after = Utc.with_ymd_and_hms(2025, 11, 5, 12, 0, 0).unwrap();
+        assert_eq!(
+            next_run(&cron, Some("Asia/Tokyo"), after),
+            Some(Utc.with_ymd_and_hms(2025, 11, 5, 17, 0, 0).unwrap())
+        );
+
+        // 02:30 doesn't exist in New York on the day clocks go forward
+        let cron = "30 2 * * *".parse::<CronExpression>().unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 3, 8, 0, 0, 0).unwrap();
+        assert_eq!(
+            next_run(&cron, Some("America/New_York"), after),
+            Some(Utc.with_ymd_and_hms(2026, 3, 9, 6, 30, 0).unwrap())
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Fills in the `{{name}}` placeholders of a tag used as a task template.
+//!
+//! A tag declares its variables with a kind and an optional default. Creating a task from it
+//! takes a value for each variable, falling back to the default, and checks the value against
+//! the kind before the title and description are rendered. Placeholders that don't name a
+//! declared variable are left as typed.
+
+use std::collections::{HashMap, HashSet};
+
+use db::models::tag::{TemplateVariable, TemplateVariableKind};
+use regex::{Captures, Regex};
+use thiserror::Error;
+
+lazy_static::lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([a-z][a-z0-9_]*)\s*\}\}").unwrap();
+    static ref VARIABLE_NAME: Regex = Regex::new(r"^[a-z][a-z0-9_]*$").unwrap();
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error(
+        "Invalid variable name '{0}': use lowercase letters, digits and _, starting with a letter"
+    )]
+    InvalidName(String),
+    #[error("Variable '{0}' is declared more than once")]
+    DuplicateName(String),
+    #[error("'{0}' is not a variable of this template")]
+    Undeclared(String),
+    #[error("A value for '{0}' is required")]
+    MissingValue(String),
+    #[error("'{value}' is not a valid {kind} for '{name}'")]
+    InvalidValue {
+        name: String,
+        kind: &'static str,
+        value: String,
+    },
+}
+
+fn kind_name(kind: TemplateVariableKind) -> &'static str {
+    match kind {
+        TemplateVariableKind::Text => "text",
+        TemplateVariableKind::Url => "URL",
+        TemplateVariableKind::Number => "number",
+        TemplateVariableKind::Boolean => "boolean",
+    }
+}
+
+fn is_valid_value(kind: TemplateVariableKind, value: &str) -> bool {
+    match kind {
+        TemplateVariableKind::Text => true,
+        TemplateVariableKind::Url => {
+            (value.starts_with("http://") || value.starts_with("https://"))
+                && !value.contains(char::is_whitespace)
+        }
+        TemplateVariableKind::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+        TemplateVariableKind::Boolean => matches!(value, "true" | "false"),
+    }
+}
+
+fn check_value(variable: &TemplateVariable, value: &str) -> Result<(), TemplateError> {
+    if is_valid_value(variable.kind, value) {
+        Ok(())
+    } else {
+        Err(TemplateError::InvalidValue {
+            name: variable.name.clone(),
+            kind: kind_name(variable.kind),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Check the variables a tag declares: valid, unique names and defaults of the right kind
+pub fn validate_declarations(variables: &[TemplateVariable]) -> Result<(), TemplateError> {
+    let mut seen = HashSet::new();
+    for variable in variables {
+        if !VARIABLE_NAME.is_match(&variable.name) {
+            return Err(TemplateError::InvalidName(variable.name.clone()));
+        }
+        if !seen.insert(variable.name.as_str()) {
+            return Err(TemplateError::DuplicateName(variable.name.clone()));
+        }
+        if let Some(default) = &variable.default {
+            check_value(variable, default)?;
+        }
+    }
+    Ok(())
+}
+
+/// The value of every declared variable: the given one, or else its default when none or an
+/// empty one is given. Values for variables the template doesn't declare are refused.
+pub fn resolve(
+    variables: &[TemplateVariable],
+    values: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, TemplateError> {
+    if let Some(name) = values
+        .keys()
+        .find(|name| !variables.iter().any(|variable| &variable.name == *name))
+    {
+        return Err(TemplateError::Undeclared(name.clone()));
+    }
+
+    let mut resolved = HashMap::with_capacity(variables.len());
+    for variable in variables {
+        let value = match values.get(&variable.name) {
+            Some(value) if !value.trim().is_empty() => value.trim().to_string(),
+            _ => variable
+                .default
+                .clone()
+                .ok_or_else(|| TemplateError::MissingValue(variable.name.clone()))?,
+        };
+        check_value(variable, &value)?;
+        resolved.insert(variable.name.clone(), value);
+    }
+    Ok(resolved)
+}
+
+/// Replace the placeholders of `text` that have a value in `values`
+pub fn render(text: &str, values: &HashMap<String, String>) -> String {
+    PLACEHOLDER
+        .replace_all(text, |caps: &Captures| match values.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, kind: TemplateVariableKind, default: Option<&str>) -> TemplateVariable {
+        TemplateVariable {
+            name: name.to_string(),
+            kind,
+            default: default.map(str::to_string),
+            description: None,
+        }
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_given_values_and_defaults() {
+        let variables = [
+            variable("service_name", TemplateVariableKind::Text, None),
+            variable("retries", TemplateVariableKind::Number, Some("3")),
+        ];
+        let resolved = resolve(&variables, &values(&[("service_name", " billing ")])).unwrap();
+        assert_eq!(
+            render(
+                "Fix {{service_name}}, retry {{ retries }} times, see {{ticket_url}}",
+                &resolved
+            ),
+            "Fix billing, retry 3 times, see {{ticket_url}}"
+        );
+    }
+
+    #[test]
+    fn refuses_missing_undeclared_and_mistyped_values() {
+        let variables = [
+            variable("service_name", TemplateVariableKind::Text, None),
+            variable("ticket_url", TemplateVariableKind::Url, None),
+        ];
+        assert_eq!(
+            resolve(&variables, &values(&[("ticket_url", "https://x.test/1")])),
+            Err(TemplateError::MissingValue("service_name".to_string()))
+        );
+        assert_eq!(
+            resolve(
+                &variables,
+                &values(&[("service_name", "a"), ("ticket_url", "ticket 1")])
+            ),
+            Err(TemplateError::InvalidValue {
+                name: "ticket_url".to_string(),
+                kind: "URL",
+                value: "ticket 1".to_string(),
+            })
+        );
+        assert_eq!(
+            resolve(&[], &values(&[("other", "x")])),
+            Err(TemplateError::Undeclared("other".to_string()))
+        );
+    }
+
+    #[test]
+    fn validates_declarations() {
+        let flag = variable("dry_run", TemplateVariableKind::Boolean, Some("true"));
+        assert!(validate_declarations(std::slice::from_ref(&flag)).is_ok());
+        assert_eq!(
+            validate_declarations(&[flag.clone(), flag]),
+            Err(TemplateError::DuplicateName("dry_run".to_string()))
+        );
+        assert_eq!(
+            validate_declarations(&[variable("Service", TemplateVariableKind::Text, None)]),
+            Err(TemplateError::InvalidName("Service".to_string()))
+        );
+        assert!(matches!(
+            validate_declarations(&[variable("n", TemplateVariableKind::Number, Some("many"))]),
+            Err(TemplateError::InvalidValue { .. })
+        ));
+    }
+}
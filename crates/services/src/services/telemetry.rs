@@ -0,0 +1,246 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use tokio::{sync::RwLock, time::interval};
+use ts_rs::TS;
+
+use crate::services::config::Config;
+
+/// Setting this environment variable disables telemetry regardless of the config
+const DISABLE_TELEMETRY_ENV: &str = "DISABLE_TELEMETRY";
+
+const FORWARD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct RouteTelemetry {
+    pub requests: usize,
+    pub client_errors: usize,
+    pub server_errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TelemetrySnapshot {
+    pub enabled: bool,
+    pub disabled_by_env: bool,
+    pub collecting_since: DateTime<Utc>,
+    pub version: String,
+    pub features: BTreeMap<String, usize>,
+    pub routes: BTreeMap<String, RouteTelemetry>,
+    pub total_requests: usize,
+    pub total_errors: usize,
+    pub error_rate: f64,
+}
+
+/// Opt-in, anonymous usage statistics aggregated in memory.
+///
+/// Only counters keyed by feature name or route template are kept: no ids, paths,
+/// prompts or user details. Nothing is recorded unless `telemetry.enabled` is set
+/// in the config, and nothing leaves the machine unless a collector URL is also set.
+#[derive(Clone)]
+pub struct TelemetryService {
+    config: Arc<RwLock<Config>>,
+    features: Arc<DashMap<String, usize>>,
+    routes: Arc<DashMap<String, RouteTelemetry>>,
+    collecting_since: Arc<RwLock<DateTime<Utc>>>,
+    client: reqwest::Client,
+}
+
+impl TelemetryService {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            config,
+            features: Arc::new(DashMap::new()),
+            routes: Arc::new(DashMap::new()),
+            collecting_since: Arc::new(RwLock::new(Utc::now())),
+            client,
+        }
+    }
+
+    pub fn disabled_by_env() -> bool {
+        std::env::var(DISABLE_TELEMETRY_ENV).is_ok()
+    }
+
+    pub async fn is_enabled(&self) -> bool {
+        !Self::disabled_by_env() && self.config.read().await.telemetry.enabled
+    }
+
+    pub async fn record_feature(&self, feature: &str) {
+        if !self.is_enabled().await {
+            return;
+        }
+        *self.features.entry(feature.to_string()).or_default() += 1;
+    }
+
+    /// Record a handled request against its route template (e.g. `/api/tasks/{id}`)
+    pub async fn record_request(&self, route: &str, status: u16) {
+        if !self.is_enabled().await {
+            return;
+        }
+        let mut stats = self.routes.entry(route.to_string()).or_default();
+        stats.requests += 1;
+        match status {
+            400..=499 => stats.client_errors += 1,
+            500..=599 => stats.server_errors += 1,
+            _ => {}
+        }
+    }
+
+    pub async fn snapshot(&self) -> TelemetrySnapshot {
+        let features: BTreeMap<String, usize> = self
+            .features
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        let routes: BTreeMap<String, RouteTelemetry> = self
+            .routes
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let total_requests: usize = routes.values().map(|r| r.requests).sum();
+        let total_errors: usize = routes
+            .values()
+            .map(|r| r.client_errors + r.server_errors)
+            .sum();
+        let error_rate = if total_requests == 0 {
+            0.0
+        } else {
+            total_errors as f64 / total_requests as f64
+        };
+
+        TelemetrySnapshot {
+            enabled: self.is_enabled().await,
+            disabled_by_env: Self::disabled_by_env(),
+            collecting_since: *self.collecting_since.read().await,
+            version: utils::version::APP_VERSION.to_string(),
+            features,
+            routes,
+            total_requests,
+            total_errors,
+            error_rate,
+        }
+    }
+
+    /// Drop all aggregated counters
+    pub async fn reset(&self) {
+        self.features.clear();
+        self.routes.clear();
+        *self.collecting_since.write().await = Utc::now();
+    }
+
+    /// Periodically forward the aggregated snapshot to the configured collector, if any
+    pub fn spawn_forwarder(&self) -> tokio::task::JoinHandle<()> {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval = interval(FORWARD_INTERVAL);
+            // The first tick completes immediately; skip it so we never send an empty snapshot
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.forward().await {
+                    tracing::warn!("Failed to forward telemetry: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn forward(&self) -> Result<(), reqwest::Error> {
+        if !self.is_enabled().await {
+            return Ok(());
+        }
+        let Some(collector_url) = self.config.read().await.telemetry.collector_url.clone() else {
+            return Ok(());
+        };
+
+        let snapshot = self.snapshot().await;
+        self.client
+            .post(&collector_url)
+            .json(&snapshot)
+            .send()
+            .await?
+            .error_for_status()?;
+        tracing::debug!("Forwarded telemetry snapshot to {}", collector_url);
+        self.reset().await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that read or set `DISABLE_TELEMETRY` hold this, since the environment is shared
+    static ENV: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    fn service(enabled: bool) -> TelemetryService {
+        let mut config = Config::default();
+        config.telemetry.enabled = enabled;
+        TelemetryService::new(Arc::new(RwLock::new(config)))
+    }
+
+    #[tokio::test]
+    async fn snapshot_totals_requests_and_errors() {
+        let _env = ENV.lock().await;
+        let telemetry = service(true);
+        telemetry.record_feature("task_created").await;
+        telemetry.record_feature("task_created").await;
+        for status in [200, 201, 404] {
+            telemetry.record_request("/api/tasks", status).await;
+        }
+        telemetry.record_request("/api/tasks/{id}", 500).await;
+
+        let snapshot = telemetry.snapshot().await;
+        assert!(snapshot.enabled);
+        assert_eq!(snapshot.features["task_created"], 2);
+        let tasks = &snapshot.routes["/api/tasks"];
+        assert_eq!(
+            (tasks.requests, tasks.client_errors, tasks.server_errors),
+            (3, 1, 0)
+        );
+        assert_eq!(snapshot.routes["/api/tasks/{id}"].server_errors, 1);
+        assert_eq!(snapshot.total_requests, 4);
+        assert_eq!(snapshot.total_errors, 2);
+        assert_eq!(snapshot.error_rate, 0.5);
+
+        telemetry.reset().await;
+        let snapshot = telemetry.snapshot().await;
+        assert_eq!(snapshot.total_requests, 0);
+        assert_eq!(snapshot.error_rate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn records_nothing_when_disabled() {
+        let _env = ENV.lock().await;
+        let telemetry = service(false);
+        telemetry.record_feature("task_created").await;
+        telemetry.record_request("/api/tasks", 500).await;
+
+        let snapshot = telemetry.snapshot().await;
+        assert!(!snapshot.enabled);
+        assert!(snapshot.features.is_empty());
+        assert!(snapshot.routes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn environment_variable_overrides_the_config() {
+        let _env = ENV.lock().await;
+        let telemetry = service(true);
+        // SAFETY: every test touching this variable holds ENV, so none reads it concurrently
+        unsafe { std::env::set_var(DISABLE_TELEMETRY_ENV, "1") };
+        telemetry.record_feature("task_created").await;
+        telemetry.record_request("/api/tasks", 200).await;
+        let snapshot = telemetry.snapshot().await;
+        unsafe { std::env::remove_var(DISABLE_TELEMETRY_ENV) };
+
+        assert!(!snapshot.enabled);
+        assert!(snapshot.disabled_by_env);
+        assert!(snapshot.features.is_empty());
+        assert!(snapshot.routes.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Interactive shells in an attempt's worktree. A shell lives as long as the client that
+//! opened it stays connected and the attempt's worktree exists; what it printed is kept as the
+//! session's transcript.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use db::models::terminal_session::TerminalSession;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Most of a shell's output kept in its transcript; older output is dropped first
+pub const TRANSCRIPT_LIMIT: usize = 1024 * 1024;
+
+const TRUNCATED_MARKER: &str = "[earlier output truncated]\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, TS)]
+pub struct TerminalSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl Default for TerminalSize {
+    fn default() -> Self {
+        Self { cols: 80, rows: 24 }
+    }
+}
+
+/// Message sent by the client over the terminal's WebSocket
+#[derive(Debug, Clone, PartialEq, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TerminalClientMessage {
+    /// Keystrokes or pasted text for the shell
+    Input {
+        data: String,
+    },
+    Resize {
+        cols: u16,
+        rows: u16,
+    },
+}
+
+#[derive(Debug)]
+pub enum TerminalInput {
+    Data(Vec<u8>),
+    Resize(TerminalSize),
+}
+
+impl From<TerminalClientMessage> for TerminalInput {
+    fn from(message: TerminalClientMessage) -> Self {
+        match message {
+            TerminalClientMessage::Input { data } => TerminalInput::Data(data.into_bytes()),
+            TerminalClientMessage::Resize { cols, rows } => {
+                TerminalInput::Resize(TerminalSize { cols, rows })
+            }
+        }
+    }
+}
+
+/// A running shell. Dropping `input` ends it; `output` closes once it has ended.
+pub struct Terminal {
+    pub session: TerminalSession,
+    pub input: mpsc::Sender<TerminalInput>,
+    pub output: mpsc::Receiver<Vec<u8>>,
+}
+
+/// The shell started for terminals: the user's login shell, or `/bin/sh`
+pub fn default_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string())
+}
+
+/// The last `TRANSCRIPT_LIMIT` bytes of a shell's output
+#[derive(Debug, Default)]
+pub struct Transcript {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl Transcript {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+        if self.bytes.len() > TRANSCRIPT_LIMIT {
+            let excess = self.bytes.len() - TRANSCRIPT_LIMIT;
+            self.bytes.drain(..excess);
+            self.truncated = true;
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        let text = String::from_utf8_lossy(&self.bytes);
+        if self.truncated {
+            format!("{TRUNCATED_MARKER}{text}")
+        } else {
+            text.into_owned()
+        }
+    }
+}
+
+/// Open terminals by attempt, so they can be ended along with it
+#[derive(Clone, Default)]
+pub struct TerminalRegistry {
+    sessions: Arc<Mutex<HashMap<Uuid, HashMap<Uuid, CancellationToken>>>>,
+}
+
+impl TerminalRegistry {
+    /// Track a session; the returned token is cancelled when the attempt's terminals are closed
+    pub fn register(&self, task_attempt_id: Uuid, session_id: Uuid) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.sessions
+            .lock()
+            .unwrap()
+            .entry(task_attempt_id)
+            .or_default()
+            .insert(session_id, token.clone());
+        token
+    }
+
+    pub fn unregister(&self, task_attempt_id: Uuid, session_id: Uuid) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(attempt_sessions) = sessions.get_mut(&task_attempt_id) {
+            attempt_sessions.remove(&session_id);
+            if attempt_sessions.is_empty() {
+                sessions.remove(&task_attempt_id);
+            }
+        }
+    }
+
+    /// End every open terminal of the attempt
+    pub fn close_attempt(&self, task_attempt_id: Uuid) {
+        if let Some(attempt_sessions) = self.sessions.lock().unwrap().remove(&task_attempt_id) {
+            for token in attempt_sessions.into_values() {
+                token.cancel();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcripts_keep_the_latest_output() {
+        let mut transcript = Transcript::default();
+        transcript.push(b"$ ls\n");
+        transcript.push(&vec![b'x'; TRANSCRIPT_LIMIT]);
+        transcript.push(b"\n$ ");
+
+        let text = transcript.into_string();
+        assert!(text.starts_with(TRUNCATED_MARKER));
+        assert!(text.ends_with("x\n$ "));
+        assert!(!text.contains("ls"));
+        assert_eq!(text.len(), TRUNCATED_MARKER.len() + TRANSCRIPT_LIMIT);
+    }
+
+    #[test]
+    fn closing_an_attempt_cancels_only_its_terminals() {
+        let registry = TerminalRegistry::default();
+        let (attempt, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let first = registry.register(attempt, Uuid::new_v4());
+        let second = registry.register(attempt, Uuid::new_v4());
+        let unrelated = registry.register(other, Uuid::new_v4());
+
+        registry.close_attempt(attempt);
+        assert!(first.is_cancelled() && second.is_cancelled());
+        assert!(!unrelated.is_cancelled());
+    }
+
+    #[test]
+    fn client_messages_are_tagged_by_type() {
+        let input: TerminalClientMessage =
+            serde_json::from_str(r#"{"type":"input","data":"ls\r"}"#).unwrap();
+        assert_eq!(
+            input,
+            TerminalClientMessage::Input {
+                data: "ls\r".to_string()
+            }
+        );
+        let resize: TerminalClientMessage =
+            serde_json::from_str(r#"{"type":"resize","cols":120,"rows":40}"#).unwrap();
+        assert!(matches!(
+            TerminalInput::from(resize),
+            TerminalInput::Resize(TerminalSize {
+                cols: 120,
+                rows: 40
+            })
+        ));
+    }
+}
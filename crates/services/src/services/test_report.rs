@@ -0,0 +1,232 @@
+//! Test runs started after the coding agent, and the parsers that read pass/fail counts from
+//! their output.
+//!
+//! `cargo test` and Jest print a summary the counts are read from. For JUnit the command writes
+//! an XML report into the worktree, which is read once the run has finished.
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path},
+    sync::LazyLock,
+};
+
+use db::models::{
+    project::{TestReportFormat, TestRunConfig},
+    test_run_result::TestCounts,
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use regex::Regex;
+use thiserror::Error;
+
+static ANSI_ESCAPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap());
+static CARGO_RESULT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored").unwrap()
+});
+static JEST_TESTS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*Tests:\s+(.+)$").unwrap());
+static JEST_COUNT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+) (passed|failed|skipped|todo)").unwrap());
+static JUNIT_SUITE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<testsuite\b([^>]*)>").unwrap());
+static JUNIT_SUITES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<testsuites\b([^>]*)>").unwrap());
+static XML_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([\w:-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+#[derive(Debug, Error)]
+pub enum TestRunError {
+    #[error("The test command must not be empty")]
+    EmptyCommand,
+    #[error("JUnit results need the path of the report the test command writes")]
+    MissingReportPath,
+    #[error("The report path must be relative to the worktree and stay inside it")]
+    InvalidReportPath,
+}
+
+pub fn validate(config: &TestRunConfig) -> Result<(), TestRunError> {
+    if config.command.trim().is_empty() {
+        return Err(TestRunError::EmptyCommand);
+    }
+    match config.report_path.as_deref().map(str::trim) {
+        None | Some("") if config.format == TestReportFormat::Junit => {
+            Err(TestRunError::MissingReportPath)
+        }
+        Some(path)
+            if !path.is_empty()
+                && !Path::new(path).components().all(|component| {
+                    matches!(component, Component::Normal(_) | Component::CurDir)
+                }) =>
+        {
+            Err(TestRunError::InvalidReportPath)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The script action running the project's test command, if it has one enabled
+pub fn executor_action(config: Option<&TestRunConfig>) -> Option<ExecutorAction> {
+    let config = config.filter(|config| config.enabled && !config.command.trim().is_empty())?;
+    Some(ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: config.command.clone(),
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::TestScript,
+            env: Default::default(),
+        }),
+        None,
+    ))
+}
+
+/// Counts from a finished test run: read from `report` for JUnit, otherwise from `output`.
+/// `None` if no results were found, e.g. because the command failed before running tests.
+pub fn parse(format: TestReportFormat, output: &str, report: Option<&str>) -> Option<TestCounts> {
+    match format {
+        TestReportFormat::Cargo => parse_cargo(&strip_ansi(output)),
+        TestReportFormat::Jest => parse_jest(&strip_ansi(output)),
+        TestReportFormat::Junit => report.and_then(parse_junit),
+    }
+}
+
+fn strip_ansi(output: &str) -> String {
+    ANSI_ESCAPE.replace_all(output, "").into_owned()
+}
+
+/// Sum of the `test result:` lines, one per test binary and one for doc tests
+fn parse_cargo(output: &str) -> Option<TestCounts> {
+    let mut counts: Option<TestCounts> = None;
+    for captures in CARGO_RESULT.captures_iter(output) {
+        let number = |i: usize| captures[i].parse::<i64>().unwrap_or(0);
+        let counts = counts.get_or_insert_with(TestCounts::default);
+        counts.passed += number(1);
+        counts.failed += number(2);
+        counts.skipped += number(3);
+    }
+    counts
+}
+
+/// The last `Tests:` summary, e.g. `Tests:       1 failed, 2 skipped, 9 passed, 12 total`
+fn parse_jest(output: &str) -> Option<TestCounts> {
+    let summary = JEST_TESTS.captures_iter(output).last()?;
+    let mut counts = TestCounts::default();
+    for captures in JEST_COUNT.captures_iter(&summary[1]) {
+        let number = captures[1].parse::<i64>().unwrap_or(0);
+        match &captures[2] {
+            "passed" => counts.passed += number,
+            "failed" => counts.failed += number,
+            _ => counts.skipped += number,
+        }
+    }
+    Some(counts)
+}
+
+/// Sum of the `<testsuite>` elements' counts, or of `<testsuites>` if there are none. Errors
+/// count as failures.
+fn parse_junit(xml: &str) -> Option<TestCounts> {
+    let suites: Vec<_> = JUNIT_SUITE.captures_iter(xml).collect();
+    let suites = if suites.is_empty() {
+        JUNIT_SUITES.captures_iter(xml).collect()
+    } else {
+        suites
+    };
+    if suites.is_empty() {
+        return None;
+    }
+
+    let mut counts = TestCounts::default();
+    for suite in suites {
+        let attributes: HashMap<&str, i64> = XML_ATTRIBUTE
+            .captures_iter(suite.get(1).map_or("", |m| m.as_str()))
+            .filter_map(|captures| {
+                let name = captures.get(1)?.as_str();
+                let value = captures.get(2)?.as_str().parse().ok()?;
+                Some((name, value))
+            })
+            .collect();
+        let count = |name: &str| attributes.get(name).copied().unwrap_or(0);
+        let failed = count("failures") + count("errors");
+        let skipped = count("skipped") + count("disabled");
+        counts.failed += failed;
+        counts.skipped += skipped;
+        counts.passed += (count("tests") - failed - skipped).max(0);
+    }
+    Some(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(passed: i64, failed: i64, skipped: i64) -> Option<TestCounts> {
+        Some(TestCounts {
+            passed,
+            failed,
+            skipped,
+        })
+    }
+
+    #[test]
+    fn sums_cargo_test_binaries() {
+        let output = "running 3 tests\n\
+            test a ... ok\n\
+            \x1b[32mtest result: ok\x1b[0m. 3 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s\n\
+            running 2 tests\n\
+            test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\n";
+        assert_eq!(
+            parse(TestReportFormat::Cargo, output, None),
+            counts(4, 1, 1)
+        );
+        assert_eq!(
+            parse(
+                TestReportFormat::Cargo,
+                "error[E0425]: cannot find value",
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_the_jest_summary() {
+        let output = "PASS src/a.test.ts\nFAIL src/b.test.ts\n\
+            Test Suites: 1 failed, 1 passed, 2 total\n\
+            Tests:       1 failed, 2 skipped, 1 todo, 9 passed, 13 total\n\
+            Snapshots:   0 total\n";
+        assert_eq!(parse(TestReportFormat::Jest, output, None), counts(9, 1, 3));
+    }
+
+    #[test]
+    fn reads_pytest_junit_reports() {
+        let report = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites><testsuite name="pytest" errors="1" failures="2" skipped="1" tests="10" time="0.5">
+<testcase classname="test_a" name="test_one" time="0.01"/>
+</testsuite></testsuites>"#;
+        assert_eq!(
+            parse(TestReportFormat::Junit, "", Some(report)),
+            counts(6, 3, 1)
+        );
+        assert_eq!(parse(TestReportFormat::Junit, "10 passed", None), None);
+    }
+
+    #[test]
+    fn junit_needs_a_report_inside_the_worktree() {
+        let config = |report_path: Option<&str>| TestRunConfig {
+            enabled: true,
+            command: "pytest --junitxml=report.xml".to_string(),
+            format: TestReportFormat::Junit,
+            report_path: report_path.map(str::to_string),
+        };
+        assert!(validate(&config(Some("report.xml"))).is_ok());
+        assert!(matches!(
+            validate(&config(None)),
+            Err(TestRunError::MissingReportPath)
+        ));
+        assert!(matches!(
+            validate(&config(Some("../report.xml"))),
+            Err(TestRunError::InvalidReportPath)
+        ));
+    }
+}
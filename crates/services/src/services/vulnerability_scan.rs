@@ -0,0 +1,627 @@
+//! Checks dependencies added by a task attempt against a vulnerability database.
+//!
+//! Only packages that appear in a changed manifest or lockfile and were not
+//! already present on the attempt's base commit are scanned, so findings are
+//! attributable to the attempt itself.
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        task_attempt::TaskAttempt,
+        vulnerability_finding::{
+            CreateVulnerabilityFinding, VulnerabilityFinding, VulnerabilitySeverity,
+        },
+    },
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use utils::shell::get_shell_command;
+
+use crate::services::{
+    config::{VulnerabilityScanConfig, VulnerabilityScanner},
+    git::{Commit, DiffTarget, GitService, GitServiceError},
+};
+
+const OSV_API_URL: &str = "https://api.osv.dev/v1";
+
+/// OSV accepts at most this many queries per batch request
+const OSV_BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum VulnerabilityScanError {
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("Scanner command failed: {0}")]
+    Command(String),
+    #[error("No scanner command configured")]
+    NoCommand,
+}
+
+/// A package version pinned by a manifest or lockfile
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ManifestPackage {
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    pub manifest_path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandInput<'a> {
+    packages: &'a [ManifestPackage],
+}
+
+#[derive(Debug, Deserialize)]
+struct CommandOutput {
+    #[serde(default)]
+    findings: Vec<CreateVulnerabilityFinding>,
+}
+
+pub struct VulnerabilityScanService;
+
+impl VulnerabilityScanService {
+    /// Scan the attempt in the background if scanning is enabled
+    pub fn spawn_scan(
+        db: DBService,
+        git: GitService,
+        config: VulnerabilityScanConfig,
+        task_attempt: TaskAttempt,
+        worktree_path: PathBuf,
+    ) {
+        if !config.enabled {
+            return;
+        }
+        tokio::spawn(async move {
+            match Self::scan_attempt(&db, &git, &config, &task_attempt, &worktree_path).await {
+                Ok(findings) if !findings.is_empty() => tracing::info!(
+                    "Found {} dependency vulnerabilities in attempt {}",
+                    findings.len(),
+                    task_attempt.id
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!(
+                    "Dependency vulnerability scan failed for attempt {}: {}",
+                    task_attempt.id,
+                    e
+                ),
+            }
+        });
+    }
+
+    /// Scan packages added by the attempt and replace its stored findings with the result
+    pub async fn scan_attempt(
+        db: &DBService,
+        git: &GitService,
+        config: &VulnerabilityScanConfig,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+    ) -> Result<Vec<VulnerabilityFinding>, VulnerabilityScanError> {
+        let packages = Self::added_packages(git, task_attempt, worktree_path, None)?;
+        Self::store_findings(db, config, task_attempt, worktree_path, &packages).await
+    }
+
+    /// Like [`Self::scan_attempt`], but only scan what is committed on the attempt's branch, as
+    /// that is what a merge lands
+    pub async fn scan_branch(
+        db: &DBService,
+        git: &GitService,
+        config: &VulnerabilityScanConfig,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+    ) -> Result<Vec<VulnerabilityFinding>, VulnerabilityScanError> {
+        let head = git.get_branch_oid(worktree_path, &task_attempt.branch)?;
+        let head = Commit::new(git2::Oid::from_str(&head).map_err(GitServiceError::from)?);
+        let packages = Self::added_packages(git, task_attempt, worktree_path, Some(&head))?;
+        Self::store_findings(db, config, task_attempt, worktree_path, &packages).await
+    }
+
+    async fn store_findings(
+        db: &DBService,
+        config: &VulnerabilityScanConfig,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+        packages: &[ManifestPackage],
+    ) -> Result<Vec<VulnerabilityFinding>, VulnerabilityScanError> {
+        let findings = if packages.is_empty() {
+            Vec::new()
+        } else {
+            match config.scanner {
+                VulnerabilityScanner::Osv => Self::query_osv(packages).await?,
+                VulnerabilityScanner::Command => {
+                    let command = config
+                        .command
+                        .as_deref()
+                        .filter(|c| !c.trim().is_empty())
+                        .ok_or(VulnerabilityScanError::NoCommand)?;
+                    Self::run_command(command, worktree_path, packages).await?
+                }
+            }
+        };
+
+        VulnerabilityFinding::replace_for_task_attempt(&db.pool, task_attempt.id, &findings)
+            .await?;
+        Ok(VulnerabilityFinding::find_by_task_attempt_id(&db.pool, task_attempt.id).await?)
+    }
+
+    /// Packages in changed manifests that were not present on the attempt's base commit. With
+    /// `head`, the manifests as committed there; otherwise as they are in the worktree.
+    pub fn added_packages(
+        git: &GitService,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+        head: Option<&Commit>,
+    ) -> Result<Vec<ManifestPackage>, VulnerabilityScanError> {
+        let base_commit = git.get_base_commit(
+            worktree_path,
+            &task_attempt.branch,
+            &task_attempt.target_branch,
+        )?;
+        let target = match head {
+            Some(head) => DiffTarget::Range {
+                repo_path: worktree_path,
+                from_commit: &base_commit,
+                to_commit: head,
+            },
+            None => DiffTarget::Worktree {
+                worktree_path,
+                base_commit: &base_commit,
+            },
+        };
+        let diffs = git.get_diffs(target, None)?;
+
+        let mut added = Vec::new();
+        for path in diffs.iter().filter_map(|d| d.new_path.as_deref()) {
+            let Some(parse) = manifest_parser(path) else {
+                continue;
+            };
+            let contents = match head {
+                Some(head) => git.get_file_at_commit(worktree_path, head, Path::new(path))?,
+                None => match std::fs::read(worktree_path.join(path)) {
+                    Ok(bytes) => Some(bytes),
+                    Err(e) => {
+                        tracing::debug!("Failed to read manifest {}: {}", path, e);
+                        None
+                    }
+                },
+            };
+            let Some(contents) = contents else {
+                continue;
+            };
+            let new_packages = parse(&String::from_utf8_lossy(&contents));
+            let old_packages = git
+                .get_file_at_commit(worktree_path, &base_commit, Path::new(path))?
+                .map(|bytes| parse(&String::from_utf8_lossy(&bytes)))
+                .unwrap_or_default();
+
+            added.extend(new_packages.difference(&old_packages).map(
+                |(ecosystem, name, version)| ManifestPackage {
+                    ecosystem: ecosystem.to_string(),
+                    name: name.clone(),
+                    version: version.clone(),
+                    manifest_path: path.to_string(),
+                },
+            ));
+        }
+        Ok(added)
+    }
+
+    async fn query_osv(
+        packages: &[ManifestPackage],
+    ) -> Result<Vec<CreateVulnerabilityFinding>, VulnerabilityScanError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?;
+
+        let mut hits: Vec<(&ManifestPackage, String)> = Vec::new();
+        for chunk in packages.chunks(OSV_BATCH_SIZE) {
+            let queries: Vec<_> = chunk
+                .iter()
+                .map(|p| {
+                    serde_json::json!({
+                        "package": { "name": p.name, "ecosystem": p.ecosystem },
+                        "version": p.version,
+                    })
+                })
+                .collect();
+            let response: OsvBatchResponse = client
+                .post(format!("{OSV_API_URL}/querybatch"))
+                .json(&serde_json::json!({ "queries": queries }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            for (package, result) in chunk.iter().zip(response.results) {
+                hits.extend(result.vulns.into_iter().map(|v| (package, v.id)));
+            }
+        }
+
+        // The batch endpoint only returns ids; fetch each advisory once for its details
+        let mut details: HashMap<String, OsvVulnerability> = HashMap::new();
+        for (_, id) in &hits {
+            if details.contains_key(id) {
+                continue;
+            }
+            let vuln: OsvVulnerability = client
+                .get(format!("{OSV_API_URL}/vulns/{id}"))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            details.insert(id.clone(), vuln);
+        }
+
+        Ok(hits
+            .into_iter()
+            .map(|(package, id)| {
+                let vuln = details.get(&id);
+                CreateVulnerabilityFinding {
+                    ecosystem: package.ecosystem.clone(),
+                    package_name: package.name.clone(),
+                    package_version: package.version.clone(),
+                    manifest_path: package.manifest_path.clone(),
+                    summary: vuln.and_then(|v| v.summary.clone().or_else(|| v.details.clone())),
+                    severity: vuln.map(OsvVulnerability::severity).unwrap_or_default(),
+                    vulnerability_id: id,
+                }
+            })
+            .collect())
+    }
+
+    async fn run_command(
+        command: &str,
+        worktree_path: &Path,
+        packages: &[ManifestPackage],
+    ) -> Result<Vec<CreateVulnerabilityFinding>, VulnerabilityScanError> {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let mut child = tokio::process::Command::new(shell_cmd)
+            .arg(shell_arg)
+            .arg(command)
+            .current_dir(worktree_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let input = serde_json::to_vec(&CommandInput { packages })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&input).await?;
+        }
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(VulnerabilityScanError::Command(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        let output: CommandOutput = serde_json::from_slice(&output.stdout)?;
+        Ok(output.findings)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvBatchResponse {
+    #[serde(default)]
+    results: Vec<OsvBatchResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OsvBatchResult {
+    #[serde(default)]
+    vulns: Vec<OsvVulnerabilityId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvVulnerabilityId {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvVulnerability {
+    summary: Option<String>,
+    details: Option<String>,
+    #[serde(default)]
+    database_specific: Option<serde_json::Value>,
+    #[serde(default)]
+    affected: Vec<serde_json::Value>,
+}
+
+impl OsvVulnerability {
+    /// Severity label as published by the advisory database (GHSA uses `MODERATE` for medium)
+    fn severity(&self) -> VulnerabilitySeverity {
+        let labels = std::iter::once(self.database_specific.as_ref())
+            .chain(
+                self.affected
+                    .iter()
+                    .flat_map(|a| [a.get("database_specific"), a.get("ecosystem_specific")]),
+            )
+            .flatten()
+            .filter_map(|v| v.get("severity").and_then(|s| s.as_str()));
+
+        labels
+            .map(|label| match label.to_ascii_uppercase().as_str() {
+                "CRITICAL" => VulnerabilitySeverity::Critical,
+                "HIGH" => VulnerabilitySeverity::High,
+                "MODERATE" | "MEDIUM" => VulnerabilitySeverity::Medium,
+                "LOW" => VulnerabilitySeverity::Low,
+                _ => VulnerabilitySeverity::Unknown,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// (ecosystem, name, version)
+pub(crate) type PackageSet = BTreeSet<(&'static str, String, String)>;
+
+pub(crate) fn manifest_parser(path: &str) -> Option<fn(&str) -> PackageSet> {
+    let file_name = Path::new(path).file_name()?.to_str()?;
+    match file_name {
+        "package.json" => Some(parse_package_json),
+        "package-lock.json" => Some(parse_package_lock),
+        "Cargo.lock" => Some(parse_cargo_lock),
+        "poetry.lock" => Some(parse_poetry_lock),
+        "go.mod" => Some(parse_go_mod),
+        name if name.starts_with("requirements") && name.ends_with(".txt") => {
+            Some(parse_requirements_txt)
+        }
+        _ => None,
+    }
+}
+
+/// Strip range operators from simple npm specs; ranges that don't pin a version are skipped
+fn exact_npm_version(spec: &str) -> Option<String> {
+    let version = spec.trim().trim_start_matches(['^', '~', '=', 'v']);
+    let is_plain = !version.is_empty()
+        && version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    is_plain.then(|| version.to_string())
+}
+
+fn parse_package_json(content: &str) -> PackageSet {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return PackageSet::new();
+    };
+    [
+        "dependencies",
+        "devDependencies",
+        "optionalDependencies",
+        "peerDependencies",
+    ]
+    .iter()
+    .filter_map(|key| json.get(key).and_then(|v| v.as_object()))
+    .flatten()
+    .filter_map(|(name, spec)| {
+        let version = exact_npm_version(spec.as_str()?)?;
+        Some(("npm", name.clone(), version))
+    })
+    .collect()
+}
+
+fn parse_package_lock(content: &str) -> PackageSet {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return PackageSet::new();
+    };
+    // lockfileVersion 2+ lists every installed package under `packages`
+    if let Some(packages) = json.get("packages").and_then(|v| v.as_object()) {
+        return packages
+            .iter()
+            .filter_map(|(key, entry)| {
+                let (_, name) = key.rsplit_once("node_modules/")?;
+                let version = entry.get("version")?.as_str()?;
+                Some(("npm", name.to_string(), version.to_string()))
+            })
+            .collect();
+    }
+    json.get("dependencies")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, entry)| {
+            let version = entry.get("version")?.as_str()?;
+            Some(("npm", name.clone(), version.to_string()))
+        })
+        .collect()
+}
+
+fn parse_cargo_lock(content: &str) -> PackageSet {
+    #[derive(Deserialize)]
+    struct CargoLock {
+        #[serde(default)]
+        package: Vec<CargoLockPackage>,
+    }
+    #[derive(Deserialize)]
+    struct CargoLockPackage {
+        name: String,
+        version: String,
+        source: Option<String>,
+    }
+
+    let Ok(lock) = toml::from_str::<CargoLock>(content) else {
+        return PackageSet::new();
+    };
+    lock.package
+        .into_iter()
+        // Path and git dependencies aren't published to crates.io
+        .filter(|p| {
+            p.source
+                .as_deref()
+                .is_some_and(|s| s.starts_with("registry+"))
+        })
+        .map(|p| ("crates.io", p.name, p.version))
+        .collect()
+}
+
+fn parse_poetry_lock(content: &str) -> PackageSet {
+    #[derive(Deserialize)]
+    struct PoetryLock {
+        #[serde(default)]
+        package: Vec<PoetryLockPackage>,
+    }
+    #[derive(Deserialize)]
+    struct PoetryLockPackage {
+        name: String,
+        version: String,
+    }
+
+    let Ok(lock) = toml::from_str::<PoetryLock>(content) else {
+        return PackageSet::new();
+    };
+    lock.package
+        .into_iter()
+        .map(|p| ("PyPI", p.name, p.version))
+        .collect()
+}
+
+fn parse_requirements_txt(content: &str) -> PackageSet {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.split('#').next()?.split(';').next()?.trim();
+            let (name, version) = line.split_once("==")?;
+            let name = name.split('[').next()?.trim();
+            let version = version.split(',').next()?.trim();
+            (!name.is_empty() && !version.is_empty() && !name.starts_with('-'))
+                .then(|| ("PyPI", name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+fn parse_go_mod(content: &str) -> PackageSet {
+    let mut packages = PackageSet::new();
+    let mut in_require_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let requirement = if in_require_block {
+            if line == ")" {
+                in_require_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_require_block = true;
+            continue;
+        } else if let Some(rest) = line.strip_prefix("require ") {
+            rest
+        } else {
+            continue;
+        };
+
+        let mut parts = requirement.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            packages.insert((
+                "Go",
+                module.to_string(),
+                version.trim_start_matches('v').to_string(),
+            ));
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pinned_versions_from_manifests() {
+        let package_json = r#"{"dependencies": {"lodash": "^4.17.20", "left-pad": "latest"}, "devDependencies": {"vite": "5.0.0"}}"#;
+        assert_eq!(
+            parse_package_json(package_json),
+            PackageSet::from([
+                ("npm", "lodash".to_string(), "4.17.20".to_string()),
+                ("npm", "vite".to_string(), "5.0.0".to_string()),
+            ])
+        );
+
+        let package_lock = r#"{"lockfileVersion": 3, "packages": {"": {"name": "app"}, "node_modules/@scope/a": {"version": "1.0.0"}, "node_modules/a/node_modules/b": {"version": "2.0.0"}}}"#;
+        assert_eq!(
+            parse_package_lock(package_lock),
+            PackageSet::from([
+                ("npm", "@scope/a".to_string(), "1.0.0".to_string()),
+                ("npm", "b".to_string(), "2.0.0".to_string()),
+            ])
+        );
+
+        let cargo_lock = r#"
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "serde"
+version = "1.0.200"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+        assert_eq!(
+            parse_cargo_lock(cargo_lock),
+            PackageSet::from([("crates.io", "serde".to_string(), "1.0.200".to_string())])
+        );
+
+        let poetry_lock = r#"
+[[package]]
+name = "requests"
+version = "2.31.0"
+description = "Python HTTP for Humans."
+optional = false
+
+[metadata]
+lock-version = "2.0"
+"#;
+        assert_eq!(
+            parse_poetry_lock(poetry_lock),
+            PackageSet::from([("PyPI", "requests".to_string(), "2.31.0".to_string())])
+        );
+
+        let requirements =
+            "# pinned\nrequests[socks]==2.31.0 ; python_version > '3.8'\nflask>=2.0\n-e .\n";
+        assert_eq!(
+            parse_requirements_txt(requirements),
+            PackageSet::from([("PyPI", "requests".to_string(), "2.31.0".to_string())])
+        );
+
+        let go_mod = "module example.com/app\n\nrequire golang.org/x/net v0.17.0\n\nrequire (\n\tgithub.com/pkg/errors v0.9.1 // indirect\n)\n";
+        assert_eq!(
+            parse_go_mod(go_mod),
+            PackageSet::from([
+                ("Go", "golang.org/x/net".to_string(), "0.17.0".to_string()),
+                (
+                    "Go",
+                    "github.com/pkg/errors".to_string(),
+                    "0.9.1".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn osv_severity_uses_highest_published_label() {
+        let vuln: OsvVulnerability = serde_json::from_str(
+            r#"{"id": "GHSA-1", "database_specific": {"severity": "MODERATE"}, "affected": [{"ecosystem_specific": {"severity": "CRITICAL"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(vuln.severity(), VulnerabilitySeverity::Critical);
+
+        let vuln: OsvVulnerability = serde_json::from_str(r#"{"id": "OSV-1"}"#).unwrap();
+        assert_eq!(vuln.severity(), VulnerabilitySeverity::Unknown);
+    }
+}
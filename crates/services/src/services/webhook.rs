@@ -0,0 +1,527 @@
+//! Outbound webhooks, called when something happens on the board: a task changing status, an
+//! execution process finishing, an attempt merging, or a coding agent asking for approval.
+//!
+//! The events pipeline hands each occurrence to [`spawn_dispatch`], which queues a
+//! [`WebhookDelivery`] for every enabled webhook of the task's project, and every global one,
+//! that is subscribed to the event. The [`WebhookDeliveryService`] sends queued deliveries as a
+//! JSON POST signed with the webhook's secret, and retries the ones that fail with exponential
+//! backoff until the endpoint accepts them or the retries run out.
+//!
+//! Receivers check the `X-Vibe-Kanban-Signature` header, `sha256=` followed by the hex
+//! HMAC-SHA256 of the raw body keyed with the secret.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessStatus},
+        merge::{Merge, MergeStatus},
+        task::{Task, TaskStatus},
+        task_attempt::TaskAttempt,
+        webhook::{
+            CreateWebhook, UpdateWebhook, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+            WebhookEvent,
+        },
+    },
+};
+use ring::hmac;
+use serde::Serialize;
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use utils::approvals::ApprovalRequest;
+use uuid::Uuid;
+
+use crate::services::project_env::{ProjectEnvError, SecretCipher};
+
+pub const SIGNATURE_HEADER: &str = "X-Vibe-Kanban-Signature";
+pub const EVENT_HEADER: &str = "X-Vibe-Kanban-Event";
+pub const DELIVERY_HEADER: &str = "X-Vibe-Kanban-Delivery";
+
+/// Attempts made before a delivery is given up on
+const MAX_ATTEMPTS: i64 = 8;
+/// Wait after the first failed attempt, doubled after every further one
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+/// Deliveries sent per poll
+const DELIVERY_BATCH: i64 = 50;
+/// Length of the response body kept on a failed delivery
+const ERROR_BODY_LIMIT: usize = 500;
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("{0}")]
+    InvalidWebhook(String),
+    #[error(transparent)]
+    Secret(#[from] ProjectEnvError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+fn secret_context(webhook_id: Uuid) -> String {
+    format!("{webhook_id}/webhook")
+}
+
+fn validate_url(url: &str) -> Result<String, WebhookError> {
+    let url = url.trim();
+    let valid = ["https://", "http://"]
+        .iter()
+        .any(|scheme| url.len() > scheme.len() && url.starts_with(scheme))
+        && !url.chars().any(char::is_whitespace);
+    if valid {
+        Ok(url.to_string())
+    } else {
+        Err(WebhookError::InvalidWebhook(
+            "The webhook URL must start with https:// or http://".to_string(),
+        ))
+    }
+}
+
+fn validate_secret(secret: &str) -> Result<(), WebhookError> {
+    if secret.trim().is_empty() {
+        return Err(WebhookError::InvalidWebhook(
+            "A secret to sign the payloads with is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Create a webhook, encrypting its secret
+pub async fn create(pool: &SqlitePool, data: &CreateWebhook) -> Result<Webhook, WebhookError> {
+    let url = validate_url(&data.url)?;
+    validate_secret(&data.secret)?;
+    let id = Uuid::new_v4();
+    let secret = SecretCipher::global()?.encrypt(&data.secret, &secret_context(id))?;
+    Ok(Webhook::create(
+        pool,
+        id,
+        data.project_id,
+        &url,
+        &secret,
+        &data.events,
+        data.enabled,
+    )
+    .await?)
+}
+
+/// Update a webhook; settings left unset keep their current value
+pub async fn update(
+    pool: &SqlitePool,
+    webhook: &Webhook,
+    data: &UpdateWebhook,
+) -> Result<Webhook, WebhookError> {
+    let url = match &data.url {
+        Some(url) => validate_url(url)?,
+        None => webhook.url.clone(),
+    };
+    let secret = match &data.secret {
+        Some(secret) => {
+            validate_secret(secret)?;
+            SecretCipher::global()?.encrypt(secret, &secret_context(webhook.id))?
+        }
+        None => webhook.secret.clone(),
+    };
+    let events = data.events.as_deref().unwrap_or(&webhook.events.0);
+    Ok(Webhook::update(
+        pool,
+        webhook.id,
+        &url,
+        &secret,
+        events,
+        data.enabled.unwrap_or(webhook.enabled),
+    )
+    .await?)
+}
+
+/// What an event happened to, which decides the project whose webhooks are called
+#[derive(Debug, Clone, Copy)]
+pub enum WebhookSubject {
+    Task(Uuid),
+    TaskAttempt(Uuid),
+    ExecutionProcess(Uuid),
+}
+
+/// An occurrence of an event that webhooks can be called for
+#[derive(Debug, Clone)]
+pub struct WebhookNotification {
+    pub event: WebhookEvent,
+    /// Identifies the occurrence, so each webhook is sent it only once
+    pub key: String,
+    pub subject: WebhookSubject,
+    /// Details of the event, sent as the payload's `data`
+    pub data: Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl WebhookNotification {
+    pub fn task_status_changed(task_id: Uuid, from: TaskStatus, to: TaskStatus) -> Self {
+        Self {
+            event: WebhookEvent::TaskStatusChanged,
+            // A task can go back and forth, so every change is its own occurrence
+            key: format!("task_status:{task_id}:{}", Uuid::new_v4()),
+            subject: WebhookSubject::Task(task_id),
+            data: json!({ "from": from, "to": to }),
+            occurred_at: Utc::now(),
+        }
+    }
+
+    /// The notification for an execution process that has just finished, if it has
+    pub fn from_execution_process(process: &ExecutionProcess) -> Option<Self> {
+        if !matches!(
+            process.status,
+            ExecutionProcessStatus::Completed
+                | ExecutionProcessStatus::Failed
+                | ExecutionProcessStatus::Killed
+        ) {
+            return None;
+        }
+        Some(Self {
+            event: WebhookEvent::ExecutionFinished,
+            key: format!("execution_process:{}", process.id),
+            subject: WebhookSubject::TaskAttempt(process.task_attempt_id),
+            data: json!({
+                "execution_process_id": process.id,
+                "run_reason": process.run_reason,
+                "status": process.status,
+                "exit_code": process.exit_code,
+                "started_at": process.started_at,
+                "completed_at": process.completed_at,
+            }),
+            occurred_at: process.completed_at.unwrap_or_else(Utc::now),
+        })
+    }
+
+    /// The notification for a direct merge, or a pull request that has been merged
+    pub fn from_merge(merge: &Merge) -> Option<Self> {
+        let (id, task_attempt_id) = match merge {
+            Merge::Direct(direct) => (direct.id, direct.task_attempt_id),
+            Merge::Pr(pr) if matches!(pr.pr_info.status, MergeStatus::Merged) => {
+                (pr.id, pr.task_attempt_id)
+            }
+            Merge::Pr(_) => return None,
+        };
+        Some(Self {
+            event: WebhookEvent::MergeCompleted,
+            key: format!("merge:{id}"),
+            subject: WebhookSubject::TaskAttempt(task_attempt_id),
+            data: serde_json::to_value(merge).unwrap_or_default(),
+            occurred_at: Utc::now(),
+        })
+    }
+
+    pub fn approval_requested(request: &ApprovalRequest) -> Self {
+        Self {
+            event: WebhookEvent::ApprovalRequested,
+            key: format!("approval:{}", request.id),
+            subject: WebhookSubject::ExecutionProcess(request.execution_process_id),
+            data: json!({
+                "approval_id": request.id,
+                "execution_process_id": request.execution_process_id,
+                "tool_name": request.tool_name,
+                "tool_input": request.tool_input,
+                "timeout_at": request.timeout_at,
+            }),
+            occurred_at: request.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookPayloadTask<'a> {
+    id: Uuid,
+    title: &'a str,
+}
+
+/// Body of every webhook call
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    event: WebhookEvent,
+    occurred_at: DateTime<Utc>,
+    project_id: Uuid,
+    task: WebhookPayloadTask<'a>,
+    task_attempt_id: Option<Uuid>,
+    data: &'a Value,
+}
+
+/// Queue the notification for the webhooks subscribed to it, in the background
+pub fn spawn_dispatch(db: DBService, notification: WebhookNotification) {
+    tokio::spawn(async move {
+        if let Err(e) = dispatch(&db.pool, &notification).await {
+            error!(
+                "Failed to queue webhook deliveries for {}: {}",
+                notification.key, e
+            );
+        }
+    });
+}
+
+async fn dispatch(
+    pool: &SqlitePool,
+    notification: &WebhookNotification,
+) -> Result<(), sqlx::Error> {
+    let task_attempt_id = match notification.subject {
+        WebhookSubject::Task(_) => None,
+        WebhookSubject::TaskAttempt(id) => Some(id),
+        WebhookSubject::ExecutionProcess(id) => match ExecutionProcess::find_by_id(pool, id).await?
+        {
+            Some(process) => Some(process.task_attempt_id),
+            None => return Ok(()),
+        },
+    };
+    let task_id = match (notification.subject, task_attempt_id) {
+        (WebhookSubject::Task(id), _) => id,
+        (_, Some(attempt_id)) => match TaskAttempt::find_by_id(pool, attempt_id).await? {
+            Some(attempt) => attempt.task_id,
+            None => return Ok(()),
+        },
+        (_, None) => return Ok(()),
+    };
+    let Some(task) = Task::find_by_id(pool, task_id).await? else {
+        return Ok(());
+    };
+
+    let webhooks: Vec<_> = Webhook::find_enabled_for_project(pool, task.project_id)
+        .await?
+        .into_iter()
+        .filter(|webhook| webhook.fires_on(notification.event))
+        .collect();
+    if webhooks.is_empty() {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_string(&WebhookPayload {
+        event: notification.event,
+        occurred_at: notification.occurred_at,
+        project_id: task.project_id,
+        task: WebhookPayloadTask {
+            id: task.id,
+            title: &task.title,
+        },
+        task_attempt_id,
+        data: &notification.data,
+    })
+    .unwrap_or_default();
+    let now = Utc::now();
+    for webhook in webhooks {
+        if WebhookDelivery::create(
+            pool,
+            webhook.id,
+            notification.event,
+            &notification.key,
+            &payload,
+            now,
+        )
+        .await?
+        .is_some()
+        {
+            debug!(
+                "Queued webhook delivery of {} to {}",
+                notification.key, webhook.url
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Value of the signature header for `body`
+fn sign(secret: &str, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, body.as_bytes());
+    let hex: String = tag
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+/// How long to wait before attempting again after `attempts` failed attempts, or `None` once
+/// the delivery should be given up on
+fn retry_delay(attempts: i64) -> Option<Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    let doublings = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Some(
+        INITIAL_RETRY_DELAY
+            .saturating_mul(2u32.pow(doublings))
+            .min(MAX_RETRY_DELAY),
+    )
+}
+
+/// Service that sends queued webhook deliveries and retries the failed ones
+pub struct WebhookDeliveryService {
+    db: DBService,
+    client: reqwest::Client,
+    poll_interval: Duration,
+}
+
+impl WebhookDeliveryService {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent("vibe-kanban-webhooks")
+            .build()
+            .unwrap_or_default();
+        let service = Self {
+            db,
+            client,
+            poll_interval: Duration::from_secs(5),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting webhook delivery service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.deliver_due().await {
+                error!("Error sending webhook deliveries: {}", e);
+            }
+        }
+    }
+
+    async fn deliver_due(&self) -> Result<(), sqlx::Error> {
+        let due = WebhookDelivery::find_due(&self.db.pool, Utc::now(), DELIVERY_BATCH).await?;
+        for delivery in due {
+            self.deliver(&delivery).await?;
+        }
+        Ok(())
+    }
+
+    /// Attempt the delivery once and record how it went
+    async fn deliver(&self, delivery: &WebhookDelivery) -> Result<(), sqlx::Error> {
+        let pool = &self.db.pool;
+        let (response_status, error) = match Webhook::find_by_id(pool, delivery.webhook_id).await? {
+            Some(webhook) if webhook.enabled => self.send(&webhook, delivery).await,
+            Some(_) => (None, Some("The webhook is disabled".to_string())),
+            // Deleted with its deliveries in the meantime
+            None => return Ok(()),
+        };
+
+        let attempts = delivery.attempts + 1;
+        let (status, next_attempt_at) = match (&error, retry_delay(attempts)) {
+            (None, _) => (WebhookDeliveryStatus::Delivered, None),
+            (Some(_), Some(delay)) => (
+                WebhookDeliveryStatus::Pending,
+                chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|delay| Utc::now() + delay),
+            ),
+            (Some(_), None) => (WebhookDeliveryStatus::Failed, None),
+        };
+        if let Some(error) = &error {
+            warn!(
+                "Webhook delivery {} (attempt {}) failed: {}",
+                delivery.id, attempts, error
+            );
+        }
+        WebhookDelivery::record_attempt(
+            pool,
+            delivery.id,
+            status,
+            response_status,
+            error.as_deref(),
+            next_attempt_at,
+        )
+        .await
+    }
+
+    /// POST the payload, returning the response's status and why the attempt failed, if it did
+    async fn send(
+        &self,
+        webhook: &Webhook,
+        delivery: &WebhookDelivery,
+    ) -> (Option<i64>, Option<String>) {
+        let secret = match SecretCipher::global()
+            .and_then(|cipher| cipher.decrypt(&webhook.secret, &secret_context(webhook.id)))
+        {
+            Ok(secret) => secret,
+            Err(e) => return (None, Some(e.to_string())),
+        };
+        let event = serde_json::to_value(delivery.event)
+            .ok()
+            .and_then(|event| event.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let response = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(SIGNATURE_HEADER, sign(&secret, &delivery.payload))
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => {
+                (Some(i64::from(response.status().as_u16())), None)
+            }
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                let body: String = body.chars().take(ERROR_BODY_LIMIT).collect();
+                let error = if body.trim().is_empty() {
+                    format!("HTTP {status}")
+                } else {
+                    format!("HTTP {status}: {}", body.trim())
+                };
+                (Some(i64::from(status.as_u16())), Some(error))
+            }
+            Err(e) => (None, Some(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_the_body_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially_until_giving_up() {
+        assert_eq!(retry_delay(1), Some(Duration::from_secs(30)));
+        assert_eq!(retry_delay(2), Some(Duration::from_secs(60)));
+        assert_eq!(retry_delay(4), Some(Duration::from_secs(240)));
+        assert_eq!(retry_delay(7), Some(Duration::from_secs(1920)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn accepts_only_http_urls() {
+        assert_eq!(
+            validate_url(" https://example.com/hook ").unwrap(),
+            "https://example.com/hook"
+        );
+        assert!(validate_url("http://localhost:8080/hook").is_ok());
+        for url in [
+            "ftp://example.com",
+            "https://",
+            "example.com/hook",
+            "https://a b",
+        ] {
+            assert!(validate_url(url).is_err(), "{url}");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Checks a project's work-in-progress limits. A limit caps how many of the project's tasks
+//! may have a status; moving one more task into it either warns or is refused.
+
+use db::models::{
+    task::TaskStatus,
+    wip_limit::{WipLimit, WipLimitEnforcement},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::i18n::{Locale, Message};
+
+const STATUSES: [TaskStatus; 5] = [
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::InReview,
+    TaskStatus::Done,
+    TaskStatus::Cancelled,
+];
+
+/// How many of a project's tasks have a status, against the status's limit
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WipUsage {
+    pub status: TaskStatus,
+    pub count: i64,
+    /// Unset when the status has no limit
+    pub max_tasks: Option<i64>,
+    pub enforcement: Option<WipLimitEnforcement>,
+}
+
+/// Moving a task into `status` would take it past its limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct WipLimitExceeded {
+    pub status: TaskStatus,
+    /// Tasks that already have the status
+    pub count: i64,
+    pub max_tasks: i64,
+    pub enforcement: WipLimitEnforcement,
+}
+
+/// Task counts and limits of every status of the project
+pub async fn usage(pool: &SqlitePool, project_id: Uuid) -> Result<Vec<WipUsage>, sqlx::Error> {
+    let counts = WipLimit::count_tasks_by_status(pool, project_id).await?;
+    let limits = WipLimit::find_by_project_id(pool, project_id).await?;
+    Ok(STATUSES
+        .into_iter()
+        .map(|status| {
+            let count = counts
+                .iter()
+                .find(|(counted, _)| *counted == status)
+                .map_or(0, |(_, count)| *count);
+            let limit = limits.iter().find(|limit| limit.status == status);
+            WipUsage {
+                status,
+                count,
+                max_tasks: limit.map(|limit| limit.max_tasks),
+                enforcement: limit.map(|limit| limit.enforcement),
+            }
+        })
+        .collect())
+}
+
+/// Whether moving a task of the project from `current` into `status` would take the status
+/// past its limit. Tasks that already have the status never count against it again.
+pub async fn check_move(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    current: &TaskStatus,
+    status: TaskStatus,
+) -> Result<Option<WipLimitExceeded>, sqlx::Error> {
+    if *current == status {
+        return Ok(None);
+    }
+    let Some(limit) = WipLimit::find(pool, project_id, status.clone()).await? else {
+        return Ok(None);
+    };
+    let count = WipLimit::count_tasks_by_status(pool, project_id)
+        .await?
+        .into_iter()
+        .find(|(counted, _)| *counted == status)
+        .map_or(0, |(_, count)| count);
+    Ok(exceeds(&limit, count))
+}
+
+impl WipLimitExceeded {
+    pub fn message(&self, locale: Locale) -> String {
+        Message::WipLimitReached {
+            status: &self.status.to_string(),
+            count: self.count,
+            max: self.max_tasks,
+        }
+        .localize(locale)
+    }
+}
+
+fn exceeds(limit: &WipLimit, count: i64) -> Option<WipLimitExceeded> {
+    (count >= limit.max_tasks).then(|| WipLimitExceeded {
+        status: limit.status.clone(),
+        count,
+        max_tasks: limit.max_tasks,
+        enforcement: limit.enforcement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn limit(max_tasks: i64) -> WipLimit {
+        WipLimit {
+            project_id: Uuid::new_v4(),
+            status: TaskStatus::InProgress,
+            max_tasks,
+            enforcement: WipLimitEnforcement::Refuse,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn one_more_task_fits_below_the_limit() {
+        assert_eq!(exceeds(&limit(3), 0), None);
+        assert_eq!(exceeds(&limit(3), 2), None);
+    }
+
+    #[test]
+    fn a_full_status_is_exceeded() {
+        let exceeded = exceeds(&limit(3), 3).unwrap();
+        assert_eq!(exceeded.count, 3);
+        assert_eq!(exceeded.max_tasks, 3);
+        assert_eq!(exceeded.enforcement, WipLimitEnforcement::Refuse);
+        assert!(exceeds(&limit(3), 5).is_some());
+    }
+}
@@ -0,0 +1,222 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use db::{DBService, models::workflow_run::WorkflowRun};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
+
+use crate::services::{
+    config::Config,
+    github_service::{GitHubRepoInfo, GitHubService, GitHubServiceError, WorkflowRunInfo},
+};
+
+/// Give up looking for a dispatched run after this long
+const DISPATCH_LOOKUP_TIMEOUT: chrono::Duration = chrono::Duration::minutes(15);
+
+/// Allow for clock skew between us and GitHub when matching a dispatch to its run
+const DISPATCH_CLOCK_SKEW: chrono::Duration = chrono::Duration::minutes(1);
+
+#[derive(Debug, Error)]
+enum WorkflowMonitorError {
+    #[error("No GitHub token configured")]
+    NoGitHubToken,
+    #[error(transparent)]
+    GitHubServiceError(#[from] GitHubServiceError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// Service to track the status of workflow runs dispatched from task attempts
+pub struct WorkflowMonitorService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl WorkflowMonitorService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(30),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting workflow run monitoring service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check_active_runs().await {
+                error!("Error checking workflow runs: {}", e);
+            }
+        }
+    }
+
+    async fn check_active_runs(&self) -> Result<(), WorkflowMonitorError> {
+        let active_runs = WorkflowRun::find_active(&self.db.pool).await?;
+
+        if active_runs.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Checking {} active workflow runs", active_runs.len());
+
+        for run in active_runs {
+            match self.check_run_status(&run).await {
+                Err(WorkflowMonitorError::NoGitHubToken) => {
+                    warn!("No GitHub token configured, cannot check workflow runs");
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!(
+                        "Error checking workflow {} for attempt {}: {}",
+                        run.workflow_file, run.task_attempt_id, e
+                    );
+                }
+                Ok(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_run_status(&self, run: &WorkflowRun) -> Result<(), WorkflowMonitorError> {
+        let github_token = self
+            .config
+            .read()
+            .await
+            .github
+            .token()
+            .ok_or(WorkflowMonitorError::NoGitHubToken)?;
+        let github_service = GitHubService::new(&github_token)?;
+        let repo_info = GitHubRepoInfo {
+            owner: run.repo_owner.clone(),
+            repo_name: run.repo_name.clone(),
+        };
+
+        let info = match run.run_id {
+            Some(run_id) => Some(github_service.get_workflow_run(&repo_info, run_id).await?),
+            // GitHub doesn't return the run created by a dispatch, so look it up by branch and time
+            None => {
+                let runs = github_service
+                    .list_dispatched_runs(
+                        &repo_info,
+                        &run.workflow_file,
+                        &run.git_ref,
+                        run.created_at - DISPATCH_CLOCK_SKEW,
+                    )
+                    .await?;
+                let matched = WorkflowRun::find_matched_run_ids(
+                    &self.db.pool,
+                    &run.repo_owner,
+                    &run.repo_name,
+                    &run.workflow_file,
+                )
+                .await?;
+                match_dispatched_run(runs, &matched)
+            }
+        };
+
+        match info {
+            Some(info) => {
+                if run.run_id == Some(info.run_id)
+                    && run.status == info.status
+                    && run.conclusion == info.conclusion
+                {
+                    return Ok(());
+                }
+                debug!(
+                    "Workflow run {} for attempt {} is {} ({:?})",
+                    info.run_id, run.task_attempt_id, info.status, info.conclusion
+                );
+                WorkflowRun::update_run(
+                    &self.db.pool,
+                    run.id,
+                    info.run_id,
+                    &info.url,
+                    &info.status,
+                    info.conclusion.as_deref(),
+                )
+                .await?;
+            }
+            None if Utc::now() - run.created_at > DISPATCH_LOOKUP_TIMEOUT => {
+                warn!(
+                    "No run found for workflow {} dispatched on {}, giving up",
+                    run.workflow_file, run.git_ref
+                );
+                WorkflowRun::mark_not_found(&self.db.pool, run.id).await?;
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// The earliest of a dispatch's candidate `runs` that isn't already matched to another dispatch.
+/// Active dispatches are checked oldest first, so back-to-back dispatches of a workflow on one
+/// branch get its runs in order.
+fn match_dispatched_run(runs: Vec<WorkflowRunInfo>, matched: &[i64]) -> Option<WorkflowRunInfo> {
+    runs.into_iter()
+        .filter(|run| !matched.contains(&run.run_id))
+        .min_by_key(|run| run.created_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone};
+
+    use super::*;
+
+    fn run(run_id: i64, created_at: DateTime<Utc>) -> WorkflowRunInfo {
+        WorkflowRunInfo {
+            run_id,
+            url: format!("https://github.com/acme/app/actions/runs/{run_id}"),
+            status: "queued".to_string(),
+            conclusion: None,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn matches_the_earliest_run() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+        let runs = vec![
+            run(3, start + chrono::Duration::seconds(20)),
+            run(1, start),
+            run(2, start + chrono::Duration::seconds(10)),
+        ];
+        assert_eq!(
+            match_dispatched_run(runs, &[]).map(|run| run.run_id),
+            Some(1)
+        );
+        assert!(match_dispatched_run(Vec::new(), &[]).is_none());
+    }
+
+    #[test]
+    fn skips_runs_matched_to_other_dispatches() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+        let runs = || vec![run(1, start), run(2, start + chrono::Duration::seconds(10))];
+
+        // Two dispatches in a row: the second gets the run the first didn't take
+        let mut matched = Vec::new();
+        let first = match_dispatched_run(runs(), &matched).unwrap();
+        matched.push(first.run_id);
+        let second = match_dispatched_run(runs(), &matched).unwrap();
+        matched.push(second.run_id);
+        assert_eq!((first.run_id, second.run_id), (1, 2));
+
+        // A third dispatch waits for its own run to show up
+        assert!(match_dispatched_run(runs(), &matched).is_none());
+    }
+}
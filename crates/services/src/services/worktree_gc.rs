@@ -0,0 +1,183 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        audit_log::AuditAction,
+        task_attempt::{FinishedWorktree, TaskAttempt},
+    },
+};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::{
+    analytics::AnalyticsContext,
+    audit_log::AuditEvent,
+    config::Config,
+    container::{ContainerError, WorktreeCleanupData, cleanup_worktrees_direct},
+    terminal::TerminalRegistry,
+};
+
+#[derive(Debug, Error)]
+pub enum WorktreeGcError {
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CollectedWorktree {
+    pub task_attempt_id: Uuid,
+    pub task_id: Uuid,
+    pub worktree_path: String,
+}
+
+/// Outcome of one garbage collection pass
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct WorktreeGcReport {
+    pub retention_days: u32,
+    pub removed: Vec<CollectedWorktree>,
+    /// Worktrees that could not be removed and will be retried on the next pass
+    pub failed: usize,
+}
+
+/// Service that removes the worktrees of attempts whose tasks have been done or cancelled
+/// for longer than the configured retention period
+#[derive(Clone)]
+pub struct WorktreeGcService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    terminals: TerminalRegistry,
+    analytics: Option<AnalyticsContext>,
+}
+
+impl WorktreeGcService {
+    pub fn new(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        terminals: TerminalRegistry,
+        analytics: Option<AnalyticsContext>,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            terminals,
+            analytics,
+        }
+    }
+
+    pub async fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            self.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        let poll_interval = Duration::from_secs(3600);
+        info!(
+            "Starting worktree garbage collection with interval {:?}",
+            poll_interval
+        );
+
+        let mut interval = interval(poll_interval);
+
+        loop {
+            interval.tick().await;
+            if !self.config.read().await.worktree_gc.enabled {
+                continue;
+            }
+            if let Err(e) = self.collect(None).await {
+                error!("Error collecting stale worktrees: {}", e);
+            }
+        }
+    }
+
+    /// Remove stale worktrees now. `retention_days` overrides the configured retention.
+    pub async fn collect(
+        &self,
+        retention_days: Option<u32>,
+    ) -> Result<WorktreeGcReport, WorktreeGcError> {
+        let retention_days = match retention_days {
+            Some(days) => days,
+            None => self.config.read().await.worktree_gc.retention_days,
+        };
+        let candidates =
+            TaskAttempt::find_finished_worktrees(&self.db.pool, retention_days).await?;
+        let mut report = WorktreeGcReport {
+            retention_days,
+            ..Default::default()
+        };
+        if candidates.is_empty() {
+            debug!("No stale worktrees found");
+            return Ok(report);
+        }
+
+        info!(
+            "Found {} worktrees of finished tasks older than {} days",
+            candidates.len(),
+            retention_days
+        );
+        let cleanup_data: Vec<WorktreeCleanupData> = candidates
+            .iter()
+            .map(|candidate| WorktreeCleanupData {
+                attempt_id: candidate.attempt_id,
+                worktree_path: PathBuf::from(&candidate.container_ref),
+                git_repo_path: Some(PathBuf::from(&candidate.git_repo_path)),
+            })
+            .collect();
+        cleanup_worktrees_direct(&cleanup_data, &self.terminals).await?;
+
+        for candidate in candidates {
+            // cleanup_worktrees_direct only logs failures, so check what is actually gone
+            if PathBuf::from(&candidate.container_ref).exists() {
+                report.failed += 1;
+                continue;
+            }
+            // Marking the attempt streams the change to clients through the events hook
+            TaskAttempt::mark_worktree_deleted(&self.db.pool, candidate.attempt_id).await?;
+            AuditEvent::new(AuditAction::WorktreeDelete, &candidate.container_ref)
+                .attempt(candidate.attempt_id)
+                .parameters(json!({
+                    "reason": "worktree_gc",
+                    "retention_days": retention_days,
+                }))
+                .record(&self.db.pool)
+                .await;
+            self.track_removed(&candidate);
+            report.removed.push(CollectedWorktree {
+                task_attempt_id: candidate.attempt_id,
+                task_id: candidate.task_id,
+                worktree_path: candidate.container_ref,
+            });
+        }
+
+        if report.failed > 0 {
+            warn!(
+                "Failed to remove {} stale worktrees; they will be retried",
+                report.failed
+            );
+        }
+        info!("Removed {} stale worktrees", report.removed.len());
+        Ok(report)
+    }
+
+    fn track_removed(&self, worktree: &FinishedWorktree) {
+        if let Some(analytics) = &self.analytics {
+            analytics.analytics_service.track_event(
+                &analytics.user_id,
+                "worktree_collected",
+                Some(json!({
+                    "task_id": worktree.task_id.to_string(),
+                    "task_attempt_id": worktree.attempt_id.to_string(),
+                })),
+            );
+        }
+    }
+}
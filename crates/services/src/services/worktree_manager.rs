@@ -1,12 +1,12 @@
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use git2::{Error as GitError, Repository};
 use thiserror::Error;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use utils::shell::get_shell_command;
 
 use super::{
@@ -20,6 +20,9 @@ lazy_static::lazy_static! {
         Arc::new(Mutex::new(HashMap::new()));
 }
 
+/// Worktree base directory chosen at startup, see [`WorktreeManager::set_worktree_base_dir`]
+static WORKTREE_BASE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
 #[derive(Debug, Error)]
 pub enum WorktreeError {
     #[error(transparent)]
@@ -44,6 +47,11 @@ pub struct WorktreeManager;
 
 impl WorktreeManager {
     /// Create a worktree with a new branch
+    #[tracing::instrument(
+        name = "worktree.add",
+        skip_all,
+        fields(branch = branch_name, worktree_path = %worktree_path.display())
+    )]
     pub async fn create_worktree(
         repo_path: &Path,
         branch_name: &str,
@@ -306,6 +314,7 @@ impl WorktreeManager {
                         "Successfully created worktree {} at {} (git CLI)",
                         branch_name, path_str
                     );
+                    Self::checkout_lfs_objects(&git, &worktree_path);
                     Ok(())
                 }
                 Err(e) => {
@@ -331,6 +340,7 @@ impl WorktreeManager {
                         "Successfully created worktree {} at {} after metadata cleanup (git CLI)",
                         branch_name, path_str
                     );
+                    Self::checkout_lfs_objects(&git, &worktree_path);
                     Ok(())
                 }
             }
@@ -339,6 +349,29 @@ impl WorktreeManager {
         .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?
     }
 
+    /// Fetch and check out LFS objects so the worktree has real files instead of pointers.
+    /// Best-effort: a worktree with pointer files is still usable.
+    fn checkout_lfs_objects(git: &GitCli, worktree_path: &Path) {
+        if !git.uses_lfs(worktree_path) {
+            return;
+        }
+        if !git.lfs_available() {
+            warn!(
+                "Repository at {} uses Git LFS but git-lfs is not installed; worktree will contain pointer files",
+                worktree_path.display()
+            );
+            return;
+        }
+        match git.lfs_pull(worktree_path) {
+            Ok(()) => debug!("Checked out LFS objects in {}", worktree_path.display()),
+            Err(e) => warn!(
+                "Failed to check out LFS objects in {}: {}",
+                worktree_path.display(),
+                e
+            ),
+        }
+    }
+
     /// Get the git repository path
     fn get_git_repo_path(repo: &Repository) -> Result<PathBuf, WorktreeError> {
         repo.workdir()
@@ -478,8 +511,22 @@ impl WorktreeManager {
         .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?
     }
 
+    /// Create new worktrees under `dir` instead of the default base directory. Only the first
+    /// call takes effect.
+    pub fn set_worktree_base_dir(dir: PathBuf) {
+        let _ = WORKTREE_BASE_DIR.set(dir);
+    }
+
     /// Get the base directory for vibe-kanban worktrees
-    pub fn get_worktree_base_dir() -> std::path::PathBuf {
+    pub fn get_worktree_base_dir() -> PathBuf {
+        WORKTREE_BASE_DIR
+            .get()
+            .cloned()
+            .unwrap_or_else(Self::default_worktree_base_dir)
+    }
+
+    /// Base directory used when none is configured
+    pub fn default_worktree_base_dir() -> PathBuf {
         utils::path::get_vibe_kanban_temp_dir().join("worktrees")
     }
 }
@@ -6,7 +6,7 @@ use std::{
 
 use git2::{PushOptions, Repository, build::CheckoutBuilder};
 use services::services::{
-    git::GitService,
+    git::{GitService, GitServiceError},
     git_cli::{GitCli, GitCliError},
 };
 use tempfile::TempDir;
@@ -451,6 +451,133 @@ fn rebase_aborts_on_uncommitted_tracked_changes() {
     assert_eq!(edited, "feat change (edited)\n");
 }
 
+#[test]
+fn rebase_with_stash_reapplies_tracked_changes() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+
+    let service = GitService::new();
+    assert!(
+        service
+            .stash_push(&worktree_path, "clean")
+            .unwrap()
+            .is_none(),
+        "nothing to stash on a clean worktree"
+    );
+
+    write_file(&worktree_path, "feat.txt", "feat change (edited)\n");
+    let stash = service
+        .stash_push(&worktree_path, "before rebase")
+        .unwrap()
+        .expect("changes stashed");
+
+    let res = service.rebase_branch(
+        &repo_path,
+        &worktree_path,
+        "new-base",
+        "old-base",
+        "feature",
+        None,
+    );
+    assert!(res.is_ok(), "rebase should succeed once stashed: {res:?}");
+
+    service
+        .stash_pop(&worktree_path, &stash)
+        .expect("stash pop");
+    let edited = fs::read_to_string(worktree_path.join("feat.txt")).unwrap();
+    assert_eq!(edited, "feat change (edited)\n");
+    let base = fs::read_to_string(worktree_path.join("base.txt")).unwrap();
+    assert_eq!(base, "from new-base\n");
+}
+
+#[test]
+fn stash_pop_conflict_keeps_stash() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+
+    // base.txt is rewritten by new-base, so the stashed edit cannot reapply cleanly
+    write_file(&worktree_path, "base.txt", "local edit\n");
+
+    let service = GitService::new();
+    let stash = service
+        .stash_push(&worktree_path, "before rebase")
+        .unwrap()
+        .expect("changes stashed");
+    service
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            "new-base",
+            "old-base",
+            "feature",
+            None,
+        )
+        .expect("rebase");
+
+    let res = service.stash_pop(&worktree_path, &stash);
+    assert!(
+        matches!(res, Err(GitServiceError::StashConflict(_))),
+        "expected stash conflict: {res:?}"
+    );
+    let stashes = GitCli::new()
+        .git(&worktree_path, ["stash", "list"])
+        .unwrap();
+    assert!(!stashes.trim().is_empty(), "stash entry should be kept");
+}
+
+#[test]
+fn stash_pop_only_takes_its_own_worktrees_entry() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let other_path = td.path().join("wt-other");
+    let service = GitService::new();
+    service
+        .add_worktree(&repo_path, &other_path, "new-base", false)
+        .expect("create second worktree");
+
+    // Both worktrees stash into the repository's single refs/stash
+    write_file(&worktree_path, "feat.txt", "feature edit\n");
+    let feature_stash = service
+        .stash_push(&worktree_path, "feature")
+        .unwrap()
+        .expect("feature changes stashed");
+    write_file(&other_path, "base.txt", "other edit\n");
+    let other_stash = service
+        .stash_push(&other_path, "other")
+        .unwrap()
+        .expect("other changes stashed");
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("feat.txt")).unwrap(),
+        "feat change\n"
+    );
+
+    service
+        .stash_pop(&worktree_path, &feature_stash)
+        .expect("pop feature stash");
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("feat.txt")).unwrap(),
+        "feature edit\n"
+    );
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("base.txt")).unwrap(),
+        "from old-base\n"
+    );
+    let stashes = GitCli::new()
+        .git(&worktree_path, ["stash", "list", "--format=%H"])
+        .unwrap();
+    assert_eq!(stashes.trim(), other_stash);
+
+    service
+        .stash_pop(&other_path, &other_stash)
+        .expect("pop other stash");
+    assert_eq!(
+        fs::read_to_string(other_path.join("base.txt")).unwrap(),
+        "other edit\n"
+    );
+    let stashes = GitCli::new().git(&other_path, ["stash", "list"]).unwrap();
+    assert!(stashes.trim().is_empty(), "both entries dropped");
+}
+
 #[test]
 fn rebase_aborts_if_untracked_would_be_overwritten_by_base() {
     let td = TempDir::new().unwrap();
@@ -1410,3 +1537,139 @@ fn merge_base_ahead_of_task_should_error() {
         "Merge should error when base branch is ahead of task branch"
     );
 }
+
+#[test]
+fn cherry_pick_applies_commits_from_another_branch() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+
+    // Another attempt's branch with two commits on top of main
+    checkout_branch(&repo, "main");
+    create_branch_from_head(&repo, "other");
+    checkout_branch(&repo, "other");
+    write_file(&repo_path, "other1.txt", "one\n");
+    commit_all(&repo, "other first");
+    write_file(&repo_path, "other2.txt", "two\n");
+    commit_all(&repo, "other second");
+
+    let service = GitService::new();
+    let commits = service
+        .list_commits_between(&repo_path, "main", "other")
+        .unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(
+        service.get_commit_subject(&repo_path, &commits[0]).unwrap(),
+        "other first"
+    );
+
+    let before = service.get_head_info(&worktree_path).unwrap().oid;
+    let head = service
+        .cherry_pick_commits(&worktree_path, &commits)
+        .expect("cherry-pick should succeed");
+    assert_ne!(before, head);
+    assert_eq!(service.get_head_info(&worktree_path).unwrap().oid, head);
+    assert!(worktree_path.join("other1.txt").exists());
+    assert!(worktree_path.join("other2.txt").exists());
+    assert!(worktree_path.join("feat.txt").exists());
+}
+
+#[test]
+fn cherry_pick_conflict_leaves_operation_in_progress() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+
+    // new-base and feature's base both add base.txt with different content
+    let service = GitService::new();
+    let commits = service
+        .list_commits_between(&repo_path, "main", "new-base")
+        .unwrap();
+    let res = service.cherry_pick_commits(&worktree_path, &commits);
+    assert!(matches!(res, Err(GitServiceError::MergeConflicts(_))));
+    assert_eq!(
+        service.detect_conflict_op(&worktree_path).unwrap(),
+        Some(services::services::git::ConflictOp::CherryPick)
+    );
+
+    service.abort_conflicts(&worktree_path).unwrap();
+    assert_eq!(service.detect_conflict_op(&worktree_path).unwrap(), None);
+}
+
+#[test]
+fn protected_branch_patterns_block_writes_unless_overridden() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    repo.remote("origin", "https://example.com/repo.git")
+        .unwrap();
+
+    let service = GitService::new();
+    let patterns = Some("main, release/*");
+    for branch in [
+        "main",
+        "origin/main",
+        "release/1.2",
+        "refs/heads/release/hotfix",
+    ] {
+        let res = service.ensure_branch_writable(&repo_path, branch, patterns, false);
+        assert!(
+            matches!(res, Err(GitServiceError::ProtectedBranch(_))),
+            "{branch} should be protected, got {res:?}"
+        );
+        assert!(
+            service
+                .ensure_branch_writable(&repo_path, branch, patterns, true)
+                .is_ok()
+        );
+    }
+    for branch in ["feature", "maintenance", "upstream/main", "vk/release/1.2"] {
+        assert!(
+            service
+                .ensure_branch_writable(&repo_path, branch, patterns, false)
+                .is_ok(),
+            "{branch} should not be protected"
+        );
+    }
+    assert!(
+        service
+            .ensure_branch_writable(&repo_path, "main", None, false)
+            .is_ok()
+    );
+}
+
+#[test]
+fn create_tag_annotates_commit_and_rejects_duplicates() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    let head = repo
+        .revparse_single("main")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id()
+        .to_string();
+
+    let service = GitService::new();
+    service
+        .create_tag(&repo_path, "v1.0.0", &head, "First release", false)
+        .unwrap();
+    let tag = repo
+        .find_reference("refs/tags/v1.0.0")
+        .unwrap()
+        .peel_to_tag()
+        .expect("tag should be annotated");
+    assert_eq!(tag.message(), Some("First release\n"));
+    assert_eq!(tag.target_id().to_string(), head);
+
+    let res = service.create_tag(&repo_path, "v1.0.0", &head, "Again", false);
+    assert!(matches!(res, Err(GitServiceError::InvalidTag(_))));
+    let res = service.create_tag(&repo_path, "bad..name", &head, "Bad", false);
+    assert!(matches!(res, Err(GitServiceError::InvalidTag(_))));
+    // Signing needs a configured key
+    assert!(
+        service
+            .create_tag(&repo_path, "v1.0.1", &head, "Signed", true)
+            .is_err()
+    );
+}
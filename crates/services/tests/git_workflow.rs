@@ -4,9 +4,14 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use db::models::task_attempt::TaskAttempt;
 use services::services::{
-    git::{DiffTarget, GitService},
+    config::{CloneFilter, CommitSigningConfig, CommitSigningFormat, GitCloneConfig},
+    git::{Commit, DiffContentMode, DiffTarget, GitService, GitServiceError},
+    git_cli::GitCli,
     github_service::{GitHubRepoInfo, GitHubServiceError},
+    repo_health::{self, RepoHealthIssueKind},
+    vulnerability_scan::VulnerabilityScanService,
 };
 use tempfile::TempDir;
 use utils::diff::DiffChangeKind;
@@ -45,6 +50,37 @@ fn commit_empty_message_behaviour() {
     }
 }
 
+#[test]
+fn commit_file_leaves_other_staged_changes_out() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "edited.txt", "before\n");
+    write_file(&repo_path, "staged.txt", "before\n");
+    let s = GitService::new();
+    s.commit(&repo_path, "add files").unwrap();
+
+    let git = GitCli::new();
+    write_file(&repo_path, "staged.txt", "staged\n");
+    git.git(&repo_path, ["add", "staged.txt"]).unwrap();
+    write_file(&repo_path, "edited.txt", "after\n");
+
+    s.commit_file(&repo_path, "edited.txt", "Edit edited.txt")
+        .unwrap();
+
+    let committed = |file: &str| {
+        git.git(&repo_path, ["show", &format!("HEAD:{file}")])
+            .unwrap()
+    };
+    assert_eq!(committed("edited.txt"), "after\n");
+    assert_eq!(committed("staged.txt"), "before\n");
+    let still_staged = git
+        .git(&repo_path, ["diff", "--cached", "--name-only"])
+        .unwrap();
+    assert_eq!(still_staged.trim(), "staged.txt");
+    let changed = git.git(&repo_path, ["status", "--porcelain"]).unwrap();
+    assert_eq!(changed.trim(), "M  staged.txt");
+}
+
 fn has_global_git_identity() -> bool {
     if let Ok(cfg) = git2::Config::open_default() {
         let has_name = cfg.get_string("user.name").is_ok();
@@ -86,6 +122,52 @@ fn commit_without_user_config_succeeds() {
     assert!(res.is_ok());
 }
 
+#[test]
+fn commits_are_signed_when_signing_configured() {
+    let td = TempDir::new().unwrap();
+    let key_path = td.path().join("signing_key");
+    let keygen = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key_path)
+        .status();
+    if !matches!(keygen, Ok(status) if status.success()) {
+        eprintln!("ssh-keygen unavailable; skipping");
+        return;
+    }
+
+    let s = GitService::new();
+    s.set_commit_signing(&CommitSigningConfig {
+        enabled: true,
+        format: CommitSigningFormat::Ssh,
+        signing_key: Some(key_path.to_string_lossy().to_string()),
+    });
+
+    // Initial commit goes through libgit2
+    let repo_path = td.path().join("repo");
+    s.initialize_repo_with_main_branch(&repo_path).unwrap();
+    s.configure_user(&repo_path, "Test User", "test@example.com")
+        .unwrap();
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let initial = repo.head().unwrap().peel_to_commit().unwrap();
+    let (signature, _) = repo.extract_signature(&initial.id(), None).unwrap();
+    assert!(signature.as_str().unwrap().contains("BEGIN SSH SIGNATURE"));
+
+    // Regular commits go through the git CLI
+    write_file(&repo_path, "a.txt", "a\n");
+    assert!(s.commit(&repo_path, "add a").unwrap());
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_ne!(head.id(), initial.id());
+    assert!(repo.extract_signature(&head.id(), None).is_ok());
+
+    // Clones share the signing settings, and disabling them stops signing
+    let clone = s.clone();
+    clone.set_commit_signing(&CommitSigningConfig::default());
+    write_file(&repo_path, "b.txt", "b\n");
+    assert!(s.commit(&repo_path, "add b").unwrap());
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert!(repo.extract_signature(&head.id(), None).is_err());
+}
+
 #[test]
 fn commit_fails_when_index_locked() {
     use std::fs::File;
@@ -163,6 +245,134 @@ fn worktree_clean_detects_staged_deleted_and_renamed() {
     assert!(!s.is_worktree_clean(&repo_path).unwrap());
 }
 
+#[test]
+fn commit_ranges_must_run_forward_along_the_branch() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "base\n");
+    s.commit(&repo_path, "base").unwrap();
+    let head =
+        |s: &GitService| Commit::new(s.get_head_info(&repo_path).unwrap().oid.parse().unwrap());
+    let base = head(&s);
+
+    s.create_branch(&repo_path, "other").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", "first\n");
+    s.commit(&repo_path, "first").unwrap();
+    let first = head(&s);
+    write_file(&repo_path, "b.txt", "second\n");
+    s.commit(&repo_path, "second").unwrap();
+    let second = head(&s);
+
+    s.checkout_branch(&repo_path, "other").unwrap();
+    write_file(&repo_path, "c.txt", "elsewhere\n");
+    s.commit(&repo_path, "elsewhere").unwrap();
+    let elsewhere = head(&s);
+
+    let check = |from: &Commit, to: &Commit| s.check_commit_range(&repo_path, "feature", from, to);
+    assert!(check(&first, &second).is_ok());
+    assert!(check(&base, &second).is_ok());
+    assert!(check(&second, &second).is_ok());
+    for (from, to) in [
+        (&second, &first),
+        (&first, &elsewhere),
+        (&elsewhere, &second),
+    ] {
+        assert!(
+            matches!(check(from, to), Err(GitServiceError::InvalidCommitRange(_))),
+            "{from}..{to} was accepted"
+        );
+    }
+    let missing = Commit::new(git2::Oid::from_str(&"1".repeat(40)).unwrap());
+    assert!(matches!(
+        check(&first, &missing),
+        Err(GitServiceError::InvalidCommitRange(_))
+    ));
+
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Range {
+                repo_path: Path::new(&repo_path),
+                from_commit: &first,
+                to_commit: &second,
+            },
+            None,
+        )
+        .unwrap();
+    let paths: Vec<_> = diffs.iter().filter_map(|d| d.new_path.as_deref()).collect();
+    assert_eq!(paths, vec!["b.txt"]);
+    assert!(
+        s.get_diffs(
+            DiffTarget::Range {
+                repo_path: Path::new(&repo_path),
+                from_commit: &first,
+                to_commit: &missing,
+            },
+            None,
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn vulnerability_scan_of_the_branch_ignores_uncommitted_manifests() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(
+        &repo_path,
+        "package.json",
+        r#"{"dependencies": {"lodash": "4.17.20"}}"#,
+    );
+    s.commit(&repo_path, "base").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(
+        &repo_path,
+        "package.json",
+        r#"{"dependencies": {"lodash": "4.17.20", "vite": "5.0.0"}}"#,
+    );
+    s.commit(&repo_path, "add vite").unwrap();
+    write_file(
+        &repo_path,
+        "package.json",
+        r#"{"dependencies": {"lodash": "4.17.20", "vite": "5.0.0", "left-pad": "1.3.0"}}"#,
+    );
+
+    let attempt = TaskAttempt {
+        id: uuid::Uuid::new_v4(),
+        task_id: uuid::Uuid::new_v4(),
+        container_ref: Some(repo_path.to_string_lossy().to_string()),
+        branch: "feature".to_string(),
+        target_branch: "main".to_string(),
+        executor: "CLAUDE_CODE".to_string(),
+        worktree_deleted: false,
+        setup_completed_at: None,
+        preview_url: None,
+        started_by: None,
+        review_state: None,
+        review_note: None,
+        reviewed_by: None,
+        reviewed_at: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+    let head = Commit::new(s.get_head_info(&repo_path).unwrap().oid.parse().unwrap());
+    let added = |head: Option<&Commit>| {
+        VulnerabilityScanService::added_packages(&s, &attempt, &repo_path, head)
+            .unwrap()
+            .into_iter()
+            .map(|package| package.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(added(Some(&head)), vec!["vite"]);
+    let mut in_worktree = added(None);
+    in_worktree.sort();
+    assert_eq!(in_worktree, vec!["left-pad", "vite"]);
+}
+
 #[test]
 fn diff_added_binary_file_has_no_content() {
     // ensure binary file content is not loaded (null byte guard)
@@ -197,6 +407,70 @@ fn diff_added_binary_file_has_no_content() {
     assert!(bin.new_content.is_none());
 }
 
+#[test]
+fn diff_lfs_tracked_file_is_labeled_without_pointer_content() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(
+        &repo_path,
+        ".gitattributes",
+        "*.psd filter=lfs diff=lfs merge=lfs -text\n",
+    );
+    write_file(&repo_path, "readme.txt", "hi\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    let pointer = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+    write_file(&repo_path, "art.psd", pointer);
+    write_file(&repo_path, "readme.txt", "hello\n");
+    let _ = s.commit(&repo_path, "add art").unwrap();
+
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Branch {
+                repo_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap();
+    let art = diffs
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some("art.psd"))
+        .expect("lfs diff present");
+    assert!(art.lfs);
+    assert!(art.content_omitted);
+    assert!(art.new_content.is_none());
+    let readme = diffs
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some("readme.txt"))
+        .expect("text diff present");
+    assert!(!readme.lfs);
+    assert_eq!(readme.new_content.as_deref(), Some("hello\n"));
+
+    // Uncommitted worktree changes are labeled too
+    write_file(&repo_path, "art.psd", &pointer.replace("12345", "54321"));
+    let base_commit = s.get_base_commit(&repo_path, "feature", "main").unwrap();
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                base_commit: &base_commit,
+            },
+            None,
+        )
+        .unwrap();
+    let art = diffs
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some("art.psd"))
+        .expect("lfs worktree diff present");
+    assert!(art.lfs);
+    assert!(art.new_content.is_none());
+}
+
 #[test]
 fn initialize_and_default_branch_and_head_info() {
     let td = TempDir::new().unwrap();
@@ -293,6 +567,49 @@ fn branch_status_ahead_and_behind() {
     assert_eq!((ahead2, behind2), (2, 1));
 }
 
+#[test]
+fn export_patch_and_bundle_contain_only_branch_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+    s.create_branch(&repo_path, "vk/feature").unwrap();
+    s.checkout_branch(&repo_path, "vk/feature").unwrap();
+    write_file(&repo_path, "feature.txt", "f1\n");
+    let _ = s.commit(&repo_path, "f1").unwrap();
+    write_file(&repo_path, "feature.txt", "f1\nf2\n");
+    let _ = s.commit(&repo_path, "f2").unwrap();
+    // main moves on after the branch diverged; its commit must not be exported
+    s.checkout_branch(&repo_path, "main").unwrap();
+    write_file(&repo_path, "main.txt", "m1\n");
+    let _ = s.commit(&repo_path, "m1").unwrap();
+
+    let patch = s.export_patch(&repo_path, "vk/feature", "main").unwrap();
+    let patch = String::from_utf8(patch).unwrap();
+    assert!(patch.contains("Subject: [PATCH 1/2] f1"));
+    assert!(patch.contains("Subject: [PATCH 2/2] f2"));
+    assert!(!patch.contains("main.txt"));
+
+    let bundle = s.export_bundle(&repo_path, "vk/feature", "main").unwrap();
+    let bundle_path = td.path().join("feature.bundle");
+    fs::write(&bundle_path, bundle).unwrap();
+    let heads = GitCli::new()
+        .git(
+            &repo_path,
+            ["bundle", "list-heads", bundle_path.to_str().unwrap()],
+        )
+        .unwrap();
+    assert!(heads.contains("refs/heads/vk/feature"));
+    GitCli::new()
+        .git(
+            &repo_path,
+            ["bundle", "verify", bundle_path.to_str().unwrap()],
+        )
+        .unwrap();
+}
+
 #[test]
 fn get_all_branches_lists_current_and_others() {
     let td = TempDir::new().unwrap();
@@ -335,11 +652,30 @@ fn get_github_repo_info_parses_origin() {
     let s = GitService::new();
     s.set_remote(&repo_path, "origin", "https://github.com/foo/bar.git")
         .unwrap();
-    let info = s.get_github_repo_info(&repo_path).unwrap();
+    let info = s.get_github_repo_info(&repo_path, None).unwrap();
     assert_eq!(info.owner, "foo");
     assert_eq!(info.repo_name, "bar");
 }
 
+#[test]
+fn get_github_repo_info_uses_selected_remote() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    s.set_remote(&repo_path, "origin", "https://github.com/fork/bar.git")
+        .unwrap();
+    s.set_remote(&repo_path, "upstream", "https://github.com/main/bar.git")
+        .unwrap();
+
+    let info = s
+        .get_github_repo_info(&repo_path, Some("upstream"))
+        .unwrap();
+    assert_eq!(info.owner, "main");
+    let info = s.get_github_repo_info(&repo_path, None).unwrap();
+    assert_eq!(info.owner, "fork");
+    assert!(s.get_github_repo_info(&repo_path, Some("missing")).is_err());
+}
+
 #[test]
 fn get_branch_diffs_between_branches() {
     let td = TempDir::new().unwrap();
@@ -644,3 +980,185 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[test]
+fn stats_only_diffs_omit_contents_and_full_diffs_load_large_files() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "small.txt", "a\nb\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "small.txt", "a\nc\nd\n");
+    let large = "0123456789abcdef\n".repeat(200_000);
+    write_file(&repo_path, "large.txt", &large);
+    let _ = s.commit(&repo_path, "changes").unwrap();
+
+    let target = || DiffTarget::Branch {
+        repo_path: Path::new(&repo_path),
+        branch_name: "feature",
+        base_branch: "main",
+    };
+
+    let stats = s
+        .get_diffs_with_mode(target(), None, DiffContentMode::StatsOnly)
+        .unwrap();
+    let small = stats
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some("small.txt"))
+        .unwrap();
+    assert!(small.content_omitted);
+    assert!(small.new_content.is_none());
+    assert_eq!((small.additions, small.deletions), (Some(2), Some(1)));
+
+    // Too large to inline by default, but loadable on demand
+    let inline = s.get_diffs(target(), Some(&["large.txt"])).unwrap();
+    assert!(inline[0].content_omitted);
+    let full = s
+        .get_diffs_with_mode(target(), Some(&["large.txt"]), DiffContentMode::Full)
+        .unwrap();
+    assert!(!full[0].content_omitted);
+    assert_eq!(full[0].new_content.as_deref(), Some(large.as_str()));
+}
+
+#[test]
+fn partial_shallow_clone_fetches_history_and_contents_on_demand() {
+    let td = TempDir::new().unwrap();
+    let source = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&source, "a.txt", "one\n");
+    let _ = s.commit(&source, "add a").unwrap();
+    s.create_branch(&source, "feature").unwrap();
+    s.checkout_branch(&source, "feature").unwrap();
+    write_file(&source, "a.txt", "two\n");
+    let _ = s.commit(&source, "change a").unwrap();
+    s.checkout_branch(&source, "main").unwrap();
+    write_file(&source, "b.txt", "b\n");
+    let _ = s.commit(&source, "add b").unwrap();
+    let cli = GitCli::new();
+    cli.git(&source, ["config", "uploadpack.allowFilter", "true"])
+        .unwrap();
+
+    let clone_path = td.path().join("clone");
+    let options = GitCloneConfig {
+        filter: CloneFilter::Blobless,
+        depth: Some(1),
+    };
+    let url = format!("file://{}", source.display());
+    s.clone_repository(&url, &clone_path, None, &options)
+        .unwrap();
+    assert!(git2::Repository::open(&clone_path).unwrap().is_shallow());
+
+    // Neither branch has the commit they forked from until history is deepened
+    assert!(!cli.has_merge_base(&clone_path, "origin/main", "origin/feature"));
+    s.ensure_merge_base_available(&clone_path, "origin/main", "origin/feature")
+        .unwrap();
+    assert!(cli.has_merge_base(&clone_path, "origin/main", "origin/feature"));
+
+    // The feature branch's version of a.txt was never checked out, so it is fetched for the diff
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Branch {
+                repo_path: Path::new(&clone_path),
+                branch_name: "origin/feature",
+                base_branch: "origin/main",
+            },
+            Some(&["a.txt"]),
+        )
+        .unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].old_content.as_deref(), Some("one\n"));
+    assert_eq!(diffs[0].new_content.as_deref(), Some("two\n"));
+}
+
+#[tokio::test]
+async fn repo_health_detects_and_repairs_common_breakage() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "a.txt", "a\n");
+    let s = GitService::new();
+    s.commit(&repo_path, "add a").unwrap();
+
+    let report = repo_health::check(&repo_path).await.unwrap();
+    assert!(report.healthy, "unexpected issues: {:?}", report.issues);
+
+    // Worktree whose directory was deleted by hand
+    let git = GitCli::new();
+    let worktree_path = td.path().join("wt");
+    git.git(&repo_path, ["branch", "feature"]).unwrap();
+    git.worktree_add(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    fs::remove_dir_all(&worktree_path).unwrap();
+    // Lock left behind by a git process that crashed an hour ago
+    let lock_path = repo_path.join(".git/index.lock");
+    let lock = fs::File::create(&lock_path).unwrap();
+    lock.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(3600))
+        .unwrap();
+    // Remote-tracking ref pointing at an object that was never fetched
+    write_file(
+        &repo_path,
+        ".git/refs/remotes/origin/gone",
+        "1111111111111111111111111111111111111111\n",
+    );
+
+    let report = repo_health::check(&repo_path).await.unwrap();
+    assert!(!report.healthy);
+    let kinds: Vec<_> = report.issues.iter().map(|issue| issue.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            RepoHealthIssueKind::StaleLock,
+            RepoHealthIssueKind::OrphanedWorktree,
+            RepoHealthIssueKind::BrokenRef,
+        ]
+    );
+    assert!(report.issues.iter().all(|issue| issue.repairable));
+
+    let repair = repo_health::repair(&repo_path).await.unwrap();
+    assert_eq!(repair.repaired.len(), 3);
+    assert!(
+        repair.health.healthy,
+        "left over: {:?}",
+        repair.health.issues
+    );
+    assert!(!lock_path.exists());
+    assert_eq!(git.list_worktrees(&repo_path).unwrap().len(), 1);
+}
+
+#[test]
+fn repaired_worktree_works_after_moving_it() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    write_file(&repo_path, "a.txt", "a\n");
+    let s = GitService::new();
+    s.commit(&repo_path, "add a").unwrap();
+
+    let git = GitCli::new();
+    let old_path = td.path().join("worktrees/wt");
+    git.git(&repo_path, ["branch", "feature"]).unwrap();
+    git.worktree_add(&repo_path, &old_path, "feature", false)
+        .unwrap();
+
+    // Moved to a bigger disk while the app was stopped
+    let new_path = td.path().join("big/worktrees/wt");
+    fs::create_dir_all(new_path.parent().unwrap()).unwrap();
+    fs::rename(&old_path, &new_path).unwrap();
+    git.worktree_repair(&repo_path, &new_path).unwrap();
+
+    let worktrees = git.list_worktrees(&repo_path).unwrap();
+    let moved = worktrees
+        .iter()
+        .find(|w| w.branch.as_deref() == Some("feature"))
+        .unwrap();
+    assert_eq!(
+        fs::canonicalize(&moved.path).unwrap(),
+        fs::canonicalize(&new_path).unwrap()
+    );
+    write_file(&new_path, "b.txt", "b\n");
+    git.add_all(&new_path).unwrap();
+    git.commit(&new_path, "add b").unwrap();
+    // Nothing is left for prune to remove
+    assert!(git.prunable_worktrees(&repo_path).unwrap().is_empty());
+}
@@ -32,6 +32,21 @@ pub fn profiles_path() -> std::path::PathBuf {
     asset_dir().join("profiles.json")
 }
 
+/// Directory of custom coding agent definitions, one JSON file per agent
+pub fn custom_executors_path() -> std::path::PathBuf {
+    asset_dir().join("executors")
+}
+
+/// Key encrypting secrets stored in the database
+pub fn secret_key_path() -> std::path::PathBuf {
+    asset_dir().join("secret.key")
+}
+
+/// Key signing share links to attempts
+pub fn share_link_key_path() -> std::path::PathBuf {
+    asset_dir().join("share_link.key")
+}
+
 #[derive(RustEmbed)]
 #[folder = "../../assets/sounds"]
 pub struct SoundAssets;
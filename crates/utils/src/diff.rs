@@ -1,4 +1,7 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
 
 use serde::{Deserialize, Serialize};
 use similar::{ChangeTag, TextDiff};
@@ -24,9 +27,53 @@ pub struct Diff {
     pub new_content: Option<String>,
     /// True when file contents are intentionally omitted (e.g., too large)
     pub content_omitted: bool,
+    /// True when the file is tracked by Git LFS; pointer contents are never shown
+    #[serde(default)]
+    pub lfs: bool,
     /// Optional precomputed stats for omitted content
     pub additions: Option<usize>,
     pub deletions: Option<usize>,
+    /// Precomputed hunks with intraline changes, present when contents are inline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub hunks: Option<Vec<DiffHunk>>,
+}
+
+/// A group of changed lines with surrounding context. Line numbers are 1-based and
+/// index into `oldContent`/`newContent`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    /// Changed word ranges within the line, as UTF-16 offsets so they can be used
+    /// directly with JavaScript string slicing
+    pub changes: Vec<DiffRange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffLineKind {
+    Context,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+pub struct DiffRange {
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -111,6 +158,77 @@ pub fn compute_line_change_counts(old: &str, new: &str) -> (usize, usize) {
     (additions, deletions)
 }
 
+/// Number of unchanged lines kept around each hunk
+const HUNK_CONTEXT_LINES: usize = 3;
+
+/// Time budget for diffing a whole file; once spent, the diff falls back to coarser
+/// hunks and changed lines are highlighted as a whole
+const HUNK_DIFF_BUDGET: Duration = Duration::from_millis(500);
+
+/// Compute unified hunks between two text snapshots, with word-level changes for
+/// lines that were modified in place.
+pub fn compute_diff_hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old = ensure_newline(old);
+    let new = ensure_newline(new);
+
+    let deadline = Instant::now() + HUNK_DIFF_BUDGET;
+    let diff = TextDiff::configure()
+        .deadline(deadline)
+        .diff_lines(old.as_ref(), new.as_ref());
+
+    diff.grouped_ops(HUNK_CONTEXT_LINES)
+        .into_iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_inline_changes_deadline(op, Some(deadline)))
+                .map(|change| {
+                    let kind = match change.tag() {
+                        ChangeTag::Equal => DiffLineKind::Context,
+                        ChangeTag::Insert => DiffLineKind::Insert,
+                        ChangeTag::Delete => DiffLineKind::Delete,
+                    };
+                    let mut changes: Vec<DiffRange> = Vec::new();
+                    let mut offset = 0;
+                    for (emphasized, value) in change.values() {
+                        let value = value.strip_suffix('\n').unwrap_or(value);
+                        let len = value.encode_utf16().count();
+                        if *emphasized && len > 0 {
+                            match changes.last_mut() {
+                                Some(range) if range.end == offset => range.end += len,
+                                _ => changes.push(DiffRange {
+                                    start: offset,
+                                    end: offset + len,
+                                }),
+                            }
+                        }
+                        offset += len;
+                    }
+                    DiffLine {
+                        kind,
+                        old_line: change.old_index().map(|i| i + 1),
+                        new_line: change.new_index().map(|i| i + 1),
+                        changes,
+                    }
+                })
+                .collect();
+
+            Some(DiffHunk {
+                old_start: old_range.start + 1,
+                old_lines: old_range.len(),
+                new_start: new_range.start + 1,
+                new_lines: new_range.len(),
+                lines,
+            })
+        })
+        .collect()
+}
+
 // ensure a line ends with a newline character
 fn ensure_newline(line: &str) -> Cow<'_, str> {
     if line.ends_with('\n') {
@@ -260,3 +378,37 @@ pub fn concatenate_diff_hunks(file_path: &str, hunks: &[String]) -> String {
 
     unified_diff
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunks_carry_word_level_changes() {
+        let old = "a\nb\nlet x = 1;\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nlet y = 1;\nc\nd\ne\nf\ng\nh\n";
+
+        let hunks = compute_diff_hunks(old, new);
+        assert_eq!(hunks.len(), 1);
+        let hunk = &hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 8));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 9));
+
+        let deleted = hunk
+            .lines
+            .iter()
+            .find(|l| l.kind == DiffLineKind::Delete)
+            .unwrap();
+        assert_eq!((deleted.old_line, deleted.new_line), (Some(3), None));
+        assert_eq!(deleted.changes, vec![DiffRange { start: 4, end: 5 }]);
+
+        let inserted = hunk
+            .lines
+            .iter()
+            .filter(|l| l.kind == DiffLineKind::Insert)
+            .collect::<Vec<_>>();
+        assert_eq!(inserted.len(), 2);
+        assert_eq!(inserted[0].changes, vec![DiffRange { start: 4, end: 5 }]);
+        assert_eq!(inserted[1].new_line, Some(9));
+    }
+}
@@ -1,6 +1,7 @@
 use axum::{extract::ws::Message, response::sse::Event};
 use json_patch::Patch;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 pub const EV_STDOUT: &str = "stdout";
 pub const EV_STDERR: &str = "stderr";
@@ -8,6 +9,14 @@ pub const EV_JSON_PATCH: &str = "json_patch";
 pub const EV_SESSION_ID: &str = "session_id";
 pub const EV_FINISHED: &str = "finished";
 
+/// Text appended to a streaming conversation entry since the previous delta.
+/// Deltas are not kept in history; the normalized entry is the source of truth.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct TokenDelta {
+    pub entry_index: usize,
+    pub delta: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LogMsg {
     Stdout(String),
@@ -1,32 +1,81 @@
 use std::{
     collections::VecDeque,
+    fmt,
+    str::FromStr,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use axum::response::sse::Event;
 use futures::{StreamExt, TryStreamExt, future};
-use tokio::{sync::broadcast, task::JoinHandle};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+    time::{Instant, timeout_at},
+};
 use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
 
-use crate::{log_msg::LogMsg, stream_lines::LinesStreamExt};
+use crate::{
+    log_msg::{LogMsg, TokenDelta},
+    stream_lines::LinesStreamExt,
+};
 
 // 100 MB Limit
 const HISTORY_BYTES: usize = 100000 * 1024;
 
+// Token deltas are best-effort: slow subscribers skip ahead instead of buffering
+const TOKEN_CHANNEL_CAPACITY: usize = 256;
+
+// Deltas for the same entry arriving within this window are sent as one message
+const TOKEN_COALESCE_WINDOW: Duration = Duration::from_millis(50);
+
+/// Position of a message in a store's stream. It is sent to SSE clients as the event id, so
+/// they can resume after the last message they got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsgCursor {
+    store_id: Uuid,
+    seq: u64,
+}
+
+impl fmt::Display for MsgCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.store_id.simple(), self.seq)
+    }
+}
+
+impl FromStr for MsgCursor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (store_id, seq) = s.trim().split_once('.').ok_or(())?;
+        Ok(Self {
+            store_id: Uuid::parse_str(store_id).map_err(|_| ())?,
+            seq: seq.parse().map_err(|_| ())?,
+        })
+    }
+}
+
 #[derive(Clone)]
 struct StoredMsg {
     msg: LogMsg,
     bytes: usize,
+    seq: u64,
 }
 
 struct Inner {
     history: VecDeque<StoredMsg>,
     total_bytes: usize,
+    /// Sequence number of the next message pushed
+    next_seq: u64,
 }
 
 pub struct MsgStore {
+    /// Tells cursors of this store from those of an earlier run
+    id: Uuid,
     inner: RwLock<Inner>,
     sender: broadcast::Sender<LogMsg>,
+    token_sender: broadcast::Sender<TokenDelta>,
 }
 
 impl Default for MsgStore {
@@ -38,20 +87,27 @@ impl Default for MsgStore {
 impl MsgStore {
     pub fn new() -> Self {
         let (sender, _) = broadcast::channel(10000);
+        let (token_sender, _) = broadcast::channel(TOKEN_CHANNEL_CAPACITY);
         Self {
+            id: Uuid::new_v4(),
             inner: RwLock::new(Inner {
                 history: VecDeque::with_capacity(32),
                 total_bytes: 0,
+                next_seq: 0,
             }),
             sender,
+            token_sender,
         }
     }
 
     pub fn push(&self, msg: LogMsg) {
-        let _ = self.sender.send(msg.clone()); // live listeners
         let bytes = msg.approx_bytes();
 
+        // Sent under the lock, so live listeners get messages in sequence order
         let mut inner = self.inner.write().unwrap();
+        let _ = self.sender.send(msg.clone()); // live listeners
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
         while inner.total_bytes.saturating_add(bytes) > HISTORY_BYTES {
             if let Some(front) = inner.history.pop_front() {
                 inner.total_bytes = inner.total_bytes.saturating_sub(front.bytes);
@@ -59,7 +115,7 @@ impl MsgStore {
                 break;
             }
         }
-        inner.history.push_back(StoredMsg { msg, bytes });
+        inner.history.push_back(StoredMsg { msg, bytes, seq });
         inner.total_bytes = inner.total_bytes.saturating_add(bytes);
     }
 
@@ -83,6 +139,76 @@ impl MsgStore {
         self.push(LogMsg::Finished);
     }
 
+    /// Forward incremental text for a streaming entry to live token subscribers.
+    pub fn push_token<S: Into<String>>(&self, entry_index: usize, delta: S) {
+        let delta = delta.into();
+        if delta.is_empty() || self.token_sender.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.token_sender.send(TokenDelta { entry_index, delta });
+    }
+
+    /// Live token deltas, coalesced per entry, ending once the process finishes.
+    /// Deltas dropped because the subscriber lagged are skipped.
+    pub fn token_stream(&self) -> futures::stream::BoxStream<'static, TokenDelta> {
+        let finished = self
+            .get_history()
+            .iter()
+            .any(|msg| matches!(msg, LogMsg::Finished));
+        if finished {
+            return futures::stream::empty().boxed();
+        }
+
+        struct State {
+            tokens: broadcast::Receiver<TokenDelta>,
+            logs: broadcast::Receiver<LogMsg>,
+            pending: Option<TokenDelta>,
+        }
+
+        let state = State {
+            tokens: self.token_sender.subscribe(),
+            logs: self.get_receiver(),
+            pending: None,
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            let mut current = match state.pending.take() {
+                Some(delta) => delta,
+                None => loop {
+                    tokio::select! {
+                        biased;
+                        res = state.tokens.recv() => match res {
+                            Ok(delta) => break delta,
+                            Err(RecvError::Lagged(_)) => continue,
+                            Err(RecvError::Closed) => return None,
+                        },
+                        res = state.logs.recv() => match res {
+                            Ok(LogMsg::Finished) | Err(RecvError::Closed) => return None,
+                            _ => continue,
+                        },
+                    }
+                },
+            };
+
+            let deadline = Instant::now() + TOKEN_COALESCE_WINDOW;
+            loop {
+                match timeout_at(deadline, state.tokens.recv()).await {
+                    Ok(Ok(next)) if next.entry_index == current.entry_index => {
+                        current.delta.push_str(&next.delta);
+                    }
+                    Ok(Ok(next)) => {
+                        state.pending = Some(next);
+                        break;
+                    }
+                    Ok(Err(RecvError::Lagged(_))) => continue,
+                    Ok(Err(RecvError::Closed)) | Err(_) => break,
+                }
+            }
+            Some((current, state))
+        })
+        .boxed()
+    }
+
     pub fn get_receiver(&self) -> broadcast::Receiver<LogMsg> {
         self.sender.subscribe()
     }
@@ -110,6 +236,42 @@ impl MsgStore {
         Box::pin(hist.chain(live))
     }
 
+    /// History then live, with the cursor of each message. Given the cursor of the last message
+    /// a client got, only the messages after it are replayed, as long as none of them has been
+    /// dropped from the history; otherwise the whole history is. The stream ends if the client
+    /// falls behind the live messages, so it can resume from the history.
+    pub fn history_plus_stream_after(
+        &self,
+        last: Option<MsgCursor>,
+    ) -> futures::stream::BoxStream<'static, Result<(MsgCursor, LogMsg), std::io::Error>> {
+        let store_id = self.id;
+        let (history, rx, next_seq) = {
+            let inner = self.inner.read().unwrap();
+            let first_seq = inner.history.front().map_or(inner.next_seq, |s| s.seq);
+            let resume_after = last
+                .filter(|cursor| cursor.store_id == store_id)
+                .map(|cursor| cursor.seq)
+                .filter(|seq| seq + 1 >= first_seq && *seq < inner.next_seq);
+            let history: Vec<_> = inner
+                .history
+                .iter()
+                .filter(|s| resume_after.is_none_or(|seq| s.seq > seq))
+                .map(|s| (s.seq, s.msg.clone()))
+                .collect();
+            (history, self.sender.subscribe(), inner.next_seq)
+        };
+
+        let hist = futures::stream::iter(history);
+        let live = futures::stream::unfold((rx, next_seq), |(mut rx, seq)| async move {
+            let msg = rx.recv().await.ok()?;
+            Some(((seq, msg), (rx, seq + 1)))
+        });
+
+        hist.chain(live)
+            .map(move |(seq, msg)| Ok((MsgCursor { store_id, seq }, msg)))
+            .boxed()
+    }
+
     pub fn stdout_chunked_stream(
         &self,
     ) -> futures::stream::BoxStream<'static, Result<String, std::io::Error>> {
@@ -175,3 +337,72 @@ impl MsgStore {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn token_stream_coalesces_deltas_per_entry() {
+        let store = MsgStore::new();
+        let mut stream = store.token_stream();
+
+        store.push_token(3, "Hel");
+        store.push_token(3, "lo");
+        store.push_token(4, "next");
+        store.push_finished();
+
+        assert_eq!(
+            stream.next().await,
+            Some(TokenDelta {
+                entry_index: 3,
+                delta: "Hello".to_string(),
+            })
+        );
+        assert_eq!(
+            stream.next().await,
+            Some(TokenDelta {
+                entry_index: 4,
+                delta: "next".to_string(),
+            })
+        );
+        assert_eq!(stream.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn resumes_after_the_last_message_seen() {
+        let store = MsgStore::new();
+        store.push_stdout("one");
+        store.push_stdout("two");
+
+        let mut stream = store.history_plus_stream_after(None);
+        let (first, _) = stream.next().await.unwrap().unwrap();
+        let cursor: MsgCursor = first.to_string().parse().unwrap();
+        assert_eq!(cursor, first);
+
+        let mut resumed = store.history_plus_stream_after(Some(cursor));
+        store.push_stdout("three");
+        let mut replayed = Vec::new();
+        for _ in 0..2 {
+            match resumed.next().await.unwrap().unwrap().1 {
+                LogMsg::Stdout(s) => replayed.push(s),
+                other => panic!("unexpected message {other:?}"),
+            }
+        }
+        assert_eq!(replayed, ["two", "three"]);
+
+        // A cursor of another store replays everything
+        let foreign = MsgCursor {
+            store_id: Uuid::new_v4(),
+            seq: 1,
+        };
+        assert_eq!(
+            store
+                .history_plus_stream_after(Some(foreign))
+                .take(3)
+                .count()
+                .await,
+            3
+        );
+    }
+}
@@ -120,6 +120,20 @@ pub fn get_vibe_kanban_temp_dir() -> std::path::PathBuf {
     }
 }
 
+/// Whether `path` stays inside `root` once symlinks are followed. They can only be followed on
+/// the part of the path that exists, so the file itself doesn't have to; a dangling symlink
+/// fails to canonicalize.
+pub fn is_within_root(root: &Path, path: &Path) -> std::io::Result<bool> {
+    let root = root.canonicalize()?;
+    let Some(existing) = path
+        .ancestors()
+        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+    else {
+        return Ok(false);
+    };
+    Ok(existing.canonicalize()?.starts_with(&root))
+}
+
 /// Expand leading ~ to user's home directory.
 pub fn expand_tilde(path_str: &str) -> std::path::PathBuf {
     shellexpand::tilde(path_str).as_ref().into()
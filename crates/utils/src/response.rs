@@ -20,6 +20,16 @@ impl<T, E> ApiResponse<T, E> {
         }
     }
 
+    /// Creates a successful response, with `data` and a `message` worth showing the user.
+    pub fn success_with_message(data: T, message: &str) -> Self {
+        ApiResponse {
+            success: true,
+            data: Some(data),
+            message: Some(message.to_string()),
+            error_data: None,
+        }
+    }
+
     /// Creates an error response, with `message` and no data.
     pub fn error(message: &str) -> Self {
         ApiResponse {
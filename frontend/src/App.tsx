@@ -5,6 +5,7 @@ import i18n from '@/i18n';
 import { Projects } from '@/pages/projects';
 import { ProjectTasks } from '@/pages/project-tasks';
 import { FullAttemptLogsPage } from '@/pages/full-attempt-logs';
+import { SharedAttemptPage } from '@/pages/shared-attempt';
 import { NormalLayout } from '@/components/layout/NormalLayout';
 import { usePostHog } from 'posthog-js/react';
 
@@ -164,6 +165,9 @@ function AppContent() {
                 element={<FullAttemptLogsPage />}
               />
 
+              {/* Read-only attempt opened from a share link */}
+              <Route path="/shared/:token" element={<SharedAttemptPage />} />
+
               <Route element={<NormalLayout />}>
                 <Route path="/" element={<Projects />} />
                 <Route path="/projects" element={<Projects />} />
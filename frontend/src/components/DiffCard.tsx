@@ -211,6 +211,14 @@ export default function DiffCard({
     >
       <Icon className="h-3 w-3 inline mr-2" aria-hidden />
       {label && <span className="mr-2">{label}</span>}
+      {diff.lfs && (
+        <span
+          className="mr-2 px-1.5 py-0.5 bg-muted rounded"
+          title="Tracked by Git LFS"
+        >
+          LFS
+        </span>
+      )}
       {diff.change === 'renamed' && oldName ? (
         <span className="inline-flex items-center gap-2">
           <span>{oldName}</span>
@@ -315,15 +323,17 @@ export default function DiffCard({
           className="px-4 pb-4 text-xs font-mono"
           style={{ color: 'hsl(var(--muted-foreground) / 0.9)' }}
         >
-          {isOmitted
-            ? 'Content omitted due to file size. Open in editor to view.'
-            : isContentEqual
-              ? diff.change === 'renamed'
-                ? 'File renamed with no content changes.'
-                : diff.change === 'permissionChange'
-                  ? 'File permission changed.'
-                  : 'No content changes to display.'
-              : 'Failed to render diff for this file.'}
+          {diff.lfs
+            ? 'File is stored in Git LFS. Open in editor to view.'
+            : isOmitted
+              ? 'Content omitted due to file size. Open in editor to view.'
+              : isContentEqual
+                ? diff.change === 'renamed'
+                  ? 'File renamed with no content changes.'
+                  : diff.change === 'permissionChange'
+                    ? 'File permission changed.'
+                    : 'No content changes to display.'
+                : 'Failed to render diff for this file.'}
         </div>
       )}
     </div>
@@ -17,6 +17,8 @@ type ExecutorType =
   | 'CODEX'
   | 'CURSOR_AGENT'
   | 'COPILOT'
+  | 'CUSTOM'
+  | 'LOCAL_LLM'
   | 'OPENCODE'
   | 'QWEN_CODE';
 
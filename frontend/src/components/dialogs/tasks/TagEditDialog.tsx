@@ -66,6 +66,7 @@ export const TagEditDialog = NiceModal.create<TagEditDialogProps>(({ tag }) => {
         const updateData: UpdateTag = {
           tag_name: formData.tag_name,
           content: formData.content || null, // null means "don't update"
+          variables: null,
         };
         await tagsApi.update(tag.id, updateData);
       } else {
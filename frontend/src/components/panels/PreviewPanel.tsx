@@ -1,4 +1,4 @@
-import { useState, useEffect, useRef } from 'react';
+import { useState, useEffect, useMemo, useRef } from 'react';
 import { useParams } from 'react-router-dom';
 import { useTranslation } from 'react-i18next';
 import { Loader2, X } from 'lucide-react';
@@ -6,7 +6,10 @@ import { Button } from '@/components/ui/button';
 import { useDevserverPreview } from '@/hooks/useDevserverPreview';
 import { useDevServer } from '@/hooks/useDevServer';
 import { useLogStream } from '@/hooks/useLogStream';
-import { useDevserverUrlFromLogs } from '@/hooks/useDevserverUrl';
+import {
+  detectDevserverUrl,
+  useDevserverUrlFromLogs,
+} from '@/hooks/useDevserverUrl';
 import { ClickToComponentListener } from '@/utils/previewBridge';
 import { useClickedElements } from '@/contexts/ClickedElementsProvider';
 import { Alert } from '@/components/ui/alert';
@@ -40,10 +43,19 @@ export function PreviewPanel() {
     isStopping: isStoppingDevServer,
     runningDevServer,
     latestDevServerProcess,
+    devServerUrl,
   } = useDevServer(attemptId);
 
   const logStream = useLogStream(latestDevServerProcess?.id ?? '');
-  const lastKnownUrl = useDevserverUrlFromLogs(logStream.logs);
+  const urlFromLogs = useDevserverUrlFromLogs(logStream.logs);
+  // Fall back to the allocated port when the dev script does not print its URL
+  const lastKnownUrl = useMemo(
+    () =>
+      urlFromLogs ??
+      (devServerUrl ? detectDevserverUrl(devServerUrl) : null) ??
+      undefined,
+    [urlFromLogs, devServerUrl]
+  );
 
   const previewState = useDevserverPreview(attemptId, {
     projectHasDevScript,
@@ -9,6 +9,7 @@ import { Button } from '../ui/button';
 import { PlusIcon } from 'lucide-react';
 import NiceModal from '@ebay/nice-modal-react';
 import MarkdownRenderer from '@/components/ui/markdown-renderer';
+import TaskComments from '@/components/tasks/TaskComments';
 
 interface TaskPanelProps {
   task: TaskWithAttemptStatus | null;
@@ -160,6 +161,10 @@ const TaskPanel = ({ task }: TaskPanelProps) => {
               </table>
             )}
           </div>
+
+          <div className="mt-6 overflow-y-auto min-h-0">
+            <TaskComments taskId={task.id} />
+          </div>
         </div>
       </NewCardContent>
     </>
@@ -1,6 +1,19 @@
 import { useCallback, useEffect, useRef } from 'react';
+import { useTranslation } from 'react-i18next';
 import { KanbanCard } from '@/components/ui/shadcn-io/kanban';
-import { CheckCircle, Loader2, XCircle } from 'lucide-react';
+import {
+  AlertCircle,
+  CheckCircle,
+  Clock,
+  Eye,
+  FlaskConical,
+  ListChecks,
+  Loader2,
+  Lock,
+  MessageSquareWarning,
+  ShieldCheck,
+  XCircle,
+} from 'lucide-react';
 import type { TaskWithAttemptStatus } from 'shared/types';
 import { ActionsDropdown } from '@/components/ui/ActionsDropdown';
 import { Badge } from '@/components/ui/badge';
@@ -22,6 +35,7 @@ export function TaskCard({
   onViewDetails,
   isOpen,
 }: TaskCardProps) {
+  const { t } = useTranslation('tasks');
   const handleClick = useCallback(() => {
     onViewDetails(task);
   }, [task, onViewDetails]);
@@ -50,6 +64,9 @@ export function TaskCard({
       onClick={handleClick}
       isOpen={isOpen}
       forwardedRef={localRef}
+      className={
+        task.needs_attention ? 'border-l-2 border-l-amber-500' : undefined
+      }
     >
       <div className="flex flex-1 gap-2 items-center min-w-0">
         <h4 className="flex-1 min-w-0 line-clamp-2 font-light text-sm">
@@ -68,6 +85,90 @@ export function TaskCard({
           {task.last_attempt_failed && !task.has_merged_attempt && (
             <XCircle className="h-3 w-3 text-destructive" />
           )}
+          {/* Needs Attention Indicator */}
+          {task.needs_attention && (
+            <span
+              title={t(`taskCard.needsAttention.${task.needs_attention}`)}
+            >
+              <AlertCircle className="h-3 w-3 text-amber-500 animate-pulse" />
+            </span>
+          )}
+          {/* Assignee */}
+          {task.assignee && (
+            <span
+              title={t('taskCard.assignee', { assignee: task.assignee })}
+              className="max-w-[6rem] truncate text-xs text-muted-foreground"
+            >
+              @{task.assignee}
+            </span>
+          )}
+          {/* Priority Indicator */}
+          {(task.priority === 'urgent' || task.priority === 'high') && (
+            <Badge
+              variant={task.priority === 'urgent' ? 'destructive' : 'secondary'}
+              className="px-1 py-0 text-[10px]"
+            >
+              {t(`priority.${task.priority}`)}
+            </Badge>
+          )}
+          {/* Blocked By Dependencies Indicator */}
+          {task.is_blocked && (
+            <span title={t('taskCard.blocked')}>
+              <Lock className="h-3 w-3 text-muted-foreground" />
+            </span>
+          )}
+          {/* Latest Test Run Results */}
+          {task.test_results && (
+            <span
+              title={t('taskCard.testResults', {
+                passed: Number(task.test_results.passed),
+                failed: Number(task.test_results.failed),
+                skipped: Number(task.test_results.skipped),
+              })}
+              className={`flex items-center gap-0.5 text-xs ${
+                Number(task.test_results.failed) > 0
+                  ? 'text-destructive'
+                  : 'text-green-500'
+              }`}
+            >
+              <FlaskConical className="h-3 w-3" />
+              {Number(task.test_results.failed) > 0
+                ? Number(task.test_results.failed)
+                : Number(task.test_results.passed)}
+            </span>
+          )}
+          {/* Subtask Progress */}
+          {task.subtask_progress && (
+            <span
+              title={t('taskCard.subtaskProgress', {
+                done: Number(task.subtask_progress.done),
+                total: Number(task.subtask_progress.total),
+              })}
+              className="flex items-center gap-0.5 text-xs text-muted-foreground"
+            >
+              <ListChecks className="h-3 w-3" />
+              {Number(task.subtask_progress.done)}/
+              {Number(task.subtask_progress.total)}
+            </span>
+          )}
+          {/* Review State of the Latest Attempt */}
+          {task.review_state && (
+            <span title={t(`taskCard.reviewState.${task.review_state}`)}>
+              {task.review_state === 'approved' ? (
+                <ShieldCheck className="h-3 w-3 text-green-500" />
+              ) : task.review_state === 'needs_changes' ? (
+                <MessageSquareWarning className="h-3 w-3 text-amber-500" />
+              ) : (
+                <Eye className="h-3 w-3 text-muted-foreground" />
+              )}
+            </span>
+          )}
+          {/* Stale Indicator */}
+          {task.staleness.is_stale && !task.has_in_progress_attempt && (
+            <span title={t('taskCard.stale')}>
+              <Clock className="h-3 w-3 text-amber-500" />
+            </span>
+          )}
           {/* Actions Menu */}
           <div
             onPointerDown={(e) => e.stopPropagation()}
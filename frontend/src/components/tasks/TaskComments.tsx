@@ -0,0 +1,109 @@
+import { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { Trash2 } from 'lucide-react';
+import { tasksApi } from '@/lib/api';
+import { useTaskComments } from '@/hooks/useTaskComments';
+import { Button } from '../ui/button';
+import { Textarea } from '../ui/textarea';
+import MarkdownRenderer from '@/components/ui/markdown-renderer';
+
+interface TaskCommentsProps {
+  taskId: string;
+}
+
+function TaskComments({ taskId }: TaskCommentsProps) {
+  const { t } = useTranslation('tasks');
+  const { comments, isLoading, error } = useTaskComments(taskId);
+  const [body, setBody] = useState('');
+  const [isPosting, setIsPosting] = useState(false);
+  const [postError, setPostError] = useState<string | null>(null);
+
+  // New comments arrive over the stream, so there's nothing to refetch
+  const handlePost = async () => {
+    if (!body.trim()) return;
+    setIsPosting(true);
+    setPostError(null);
+    try {
+      await tasksApi.createComment(taskId, { body });
+      setBody('');
+    } catch (err) {
+      setPostError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setIsPosting(false);
+    }
+  };
+
+  const handleDelete = async (commentId: string) => {
+    try {
+      await tasksApi.deleteComment(taskId, commentId);
+    } catch (err) {
+      setPostError(err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  return (
+    <div className="space-y-3">
+      <div className="uppercase text-sm text-muted-foreground">
+        {t('taskComments.title', { count: comments.length })}
+      </div>
+      {isLoading && (
+        <div className="text-sm text-muted-foreground">
+          {t('taskComments.loading')}
+        </div>
+      )}
+      {error && <div className="text-sm text-destructive">{error}</div>}
+      {!isLoading && comments.length === 0 && (
+        <div className="text-sm text-muted-foreground">
+          {t('taskComments.empty')}
+        </div>
+      )}
+      {comments.map((comment) => (
+        <div key={comment.id} className="border-t pt-2 text-sm">
+          <div className="flex items-center gap-2 text-muted-foreground">
+            <span className="font-medium">
+              {comment.author
+                ? `@${comment.author}`
+                : t('taskComments.unknownAuthor')}
+            </span>
+            <span>{new Date(comment.created_at).toLocaleString()}</span>
+            <Button
+              variant="icon"
+              className="ml-auto"
+              aria-label={t('taskComments.delete')}
+              onClick={() => handleDelete(comment.id)}
+            >
+              <Trash2 size={14} />
+            </Button>
+          </div>
+          <MarkdownRenderer content={comment.body} />
+        </div>
+      ))}
+      <Textarea
+        value={body}
+        placeholder={t('taskComments.placeholder')}
+        disabled={isPosting}
+        onChange={(e) => setBody(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === 'Enter' && (e.metaKey || e.ctrlKey)) {
+            e.preventDefault();
+            handlePost();
+          }
+        }}
+      />
+      {postError && (
+        <div className="text-sm text-destructive">{postError}</div>
+      )}
+      <div className="flex justify-end">
+        <Button
+          size="sm"
+          onClick={handlePost}
+          disabled={isPosting || !body.trim()}
+        >
+          {t('taskComments.post')}
+        </Button>
+      </div>
+    </div>
+  );
+}
+
+export default TaskComments;
@@ -0,0 +1,93 @@
+import { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { useQuery, useQueryClient } from '@tanstack/react-query';
+import { Square } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { executionProcessesApi } from '@/lib/api';
+import type { ProcessTreeNode } from 'shared/types';
+
+interface ProcessTreeProps {
+  processId: string;
+}
+
+function ProcessTree({ processId }: ProcessTreeProps) {
+  const { t } = useTranslation('tasks');
+  const queryClient = useQueryClient();
+  const [killingPid, setKillingPid] = useState<number | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const { data: roots = [] } = useQuery({
+    queryKey: ['processTree', processId],
+    queryFn: () => executionProcessesApi.getProcessTree(processId),
+    refetchInterval: 3000,
+  });
+
+  const handleKill = async (pid: number) => {
+    setKillingPid(pid);
+    setError(null);
+    try {
+      await executionProcessesApi.killChildProcess(processId, pid);
+      await queryClient.invalidateQueries({
+        queryKey: ['processTree', processId],
+      });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setKillingPid(null);
+    }
+  };
+
+  const renderNode = (node: ProcessTreeNode, depth: number, root: boolean) => (
+    <div key={node.pid}>
+      <div
+        className="flex items-center gap-2 py-0.5 text-xs"
+        style={{ paddingLeft: `${depth}rem` }}
+      >
+        <span className="font-mono text-muted-foreground w-14 shrink-0">
+          {node.pid}
+        </span>
+        <span className="font-mono truncate flex-1" title={node.command}>
+          {node.command}
+        </span>
+        {!node.in_process_group && (
+          <span
+            className="text-[10px] px-1.5 py-0.5 rounded-full bg-amber-100 text-amber-700 border border-amber-200"
+            title={t('processes.tree.leftGroupTooltip')}
+          >
+            {t('processes.tree.leftGroup')}
+          </span>
+        )}
+        <span className="text-muted-foreground shrink-0">
+          {t('processes.tree.memory', {
+            mb: Math.round(node.memory_kb / 1024),
+          })}
+        </span>
+        {!root && (
+          <Button
+            variant="ghost"
+            size="sm"
+            className="h-6 px-2"
+            disabled={killingPid === node.pid}
+            onClick={() => handleKill(node.pid)}
+            title={t('processes.tree.kill')}
+          >
+            <Square className="h-3 w-3" />
+          </Button>
+        )}
+      </div>
+      {node.children.map((child) => renderNode(child, depth + 1, false))}
+    </div>
+  );
+
+  if (roots.length === 0) return null;
+
+  return (
+    <div className="px-4 py-2 border-b max-h-48 overflow-auto flex-shrink-0">
+      <h3 className="text-sm font-medium mb-1">{t('processes.tree.title')}</h3>
+      {error && <p className="text-xs text-destructive mb-1">{error}</p>}
+      {roots.map((node, index) => renderNode(node, 0, index === 0))}
+    </div>
+  );
+}
+
+export default ProcessTree;
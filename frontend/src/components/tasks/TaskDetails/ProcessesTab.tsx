@@ -13,6 +13,7 @@ import { executionProcessesApi } from '@/lib/api.ts';
 import { ProfileVariantBadge } from '@/components/common/ProfileVariantBadge.tsx';
 import { useExecutionProcesses } from '@/hooks/useExecutionProcesses';
 import ProcessLogsViewer from './ProcessLogsViewer';
+import ProcessTree from './ProcessTree';
 import type { ExecutionProcessStatus, ExecutionProcess } from 'shared/types';
 
 import { useProcessSelection } from '@/contexts/ProcessSelectionContext';
@@ -44,6 +45,8 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
 
   const getStatusIcon = (status: ExecutionProcessStatus) => {
     switch (status) {
+      case 'queued':
+        return <Clock className="h-4 w-4 text-amber-500" />;
       case 'running':
         return <Play className="h-4 w-4 text-blue-500" />;
       case 'completed':
@@ -59,6 +62,8 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
 
   const getStatusColor = (status: ExecutionProcessStatus) => {
     switch (status) {
+      case 'queued':
+        return 'bg-amber-50 border-amber-200 text-amber-800';
       case 'running':
         return 'bg-blue-50 border-blue-200 text-blue-800';
       case 'completed':
@@ -199,6 +204,41 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
                             {t('processes.deleted')}
                           </span>
                         )}
+                        {process.status === 'running' &&
+                          process.stalled_at && (
+                            <span
+                              className="inline-block mt-1 text-[10px] px-1.5 py-0.5 rounded-full bg-amber-100 text-amber-700 border border-amber-200"
+                              title={t('processes.stalledTooltip', {
+                                time: formatDate(process.stalled_at),
+                              })}
+                            >
+                              {t('processes.stalled')}
+                            </span>
+                          )}
+                        {process.status === 'queued' &&
+                          process.queue_position !== null && (
+                            <span
+                              className="inline-block mt-1 text-[10px] px-1.5 py-0.5 rounded-full bg-amber-100 text-amber-700 border border-amber-200"
+                              title={t('processes.queuedTooltip')}
+                            >
+                              {t('processes.queued', {
+                                position: process.queue_position,
+                              })}
+                            </span>
+                          )}
+                        {process.kill_reason && (
+                          <span
+                            className="inline-block mt-1 text-[10px] px-1.5 py-0.5 rounded-full bg-red-100 text-red-700 border border-red-200"
+                            title={t(
+                              `processes.killReason.${process.kill_reason.type}.tooltip`,
+                              { ...process.kill_reason }
+                            )}
+                          >
+                            {t(
+                              `processes.killReason.${process.kill_reason.type}.label`
+                            )}
+                          </span>
+                        )}
                         {
                           <p className="text-sm text-muted-foreground mt-1">
                             {t('processes.agent')}{' '}
@@ -271,7 +311,14 @@ function ProcessesTab({ attemptId }: ProcessesTabProps) {
           </div>
           <div className="flex-1">
             {selectedProcess ? (
-              <ProcessLogsViewer processId={selectedProcess.id} />
+              <div className="h-full flex flex-col">
+                {selectedProcess.status === 'running' && (
+                  <ProcessTree processId={selectedProcess.id} />
+                )}
+                <div className="flex-1 min-h-0">
+                  <ProcessLogsViewer processId={selectedProcess.id} />
+                </div>
+              </div>
             ) : loadingProcessId === selectedProcessId ? (
               <div className="text-center text-muted-foreground">
                 <p>{t('processes.loadingDetails')}</p>
@@ -93,6 +93,8 @@ export function NoServerContent({
           setup_script: project.setup_script ?? null,
           dev_script: script,
           cleanup_script: project.cleanup_script ?? null,
+          verify_script: project.verify_script ?? null,
+          post_merge_script: project.post_merge_script ?? null,
           copy_files: project.copy_files ?? null,
         },
       },
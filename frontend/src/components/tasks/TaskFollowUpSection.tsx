@@ -106,6 +106,8 @@ export function TaskFollowUpSection({
     setMessage: setFollowUpMessage,
     images,
     setImages,
+    captions,
+    setCaption,
     newlyUploadedImageIds,
     handleImageUploaded,
     clearImagesAndUploads,
@@ -155,6 +157,7 @@ export function TaskFollowUpSection({
     message: followUpMessage,
     selectedVariant,
     images,
+    captions,
   });
 
   // Presentation-only queue state
@@ -195,6 +198,7 @@ export function TaskFollowUpSection({
       prompt: followUpMessage,
       variant: selectedVariant,
       image_ids: images.map((img) => img.id),
+      image_captions: captions,
     },
     isQueuedUI: displayQueued,
     isDraftSending: !!draft?.sending,
@@ -212,6 +216,7 @@ export function TaskFollowUpSection({
       clickedMarkdown,
       selectedVariant,
       images,
+      captions,
       newlyUploadedImageIds,
       clearComments,
       clearClickedElements,
@@ -443,6 +448,8 @@ export function TaskFollowUpSection({
                     appendImageMarkdown(prev, image)
                   );
                 }}
+                captions={captions}
+                onCaptionChange={setCaption}
                 disabled={!isEditable}
                 collapsible={false}
                 defaultExpanded={true}
@@ -8,14 +8,18 @@ import {
 } from '@/components/ui/shadcn-io/kanban';
 import { TaskCard } from './TaskCard';
 import type { TaskStatus, TaskWithAttemptStatus } from 'shared/types';
+import type { BoardColumn } from '@/hooks/useProjectColumns';
+import type { WipCount } from '@/hooks/useWipLimits';
 // import { useParams } from 'react-router-dom';
 
-import { statusBoardColors, statusLabels } from '@/utils/status-labels';
+import { statusBoardColors } from '@/utils/status-labels';
 
 type Task = TaskWithAttemptStatus;
 
 interface TaskKanbanBoardProps {
-  groupedTasks: Record<TaskStatus, Task[]>;
+  columns: BoardColumn[];
+  groupedTasks: Record<string, Task[]>;
+  wipCounts?: Partial<Record<TaskStatus, WipCount>>;
   onDragEnd: (event: DragEndEvent) => void;
   onViewTaskDetails: (task: Task) => void;
   selectedTask?: Task;
@@ -23,7 +27,9 @@ interface TaskKanbanBoardProps {
 }
 
 function TaskKanbanBoard({
+  columns,
   groupedTasks,
+  wipCounts,
   onDragEnd,
   onViewTaskDetails,
   selectedTask,
@@ -31,20 +37,21 @@ function TaskKanbanBoard({
 }: TaskKanbanBoardProps) {
   return (
     <KanbanProvider onDragEnd={onDragEnd}>
-      {Object.entries(groupedTasks).map(([status, statusTasks]) => (
-        <KanbanBoard key={status} id={status as TaskStatus}>
+      {columns.map((column) => (
+        <KanbanBoard key={column.id} id={column.id}>
           <KanbanHeader
-            name={statusLabels[status as TaskStatus]}
-            color={statusBoardColors[status as TaskStatus]}
+            name={column.name}
+            color={statusBoardColors[column.status]}
             onAddTask={onCreateTask}
+            wip={wipCounts?.[column.status]}
           />
           <KanbanCards>
-            {statusTasks.map((task, index) => (
+            {(groupedTasks[column.id] ?? []).map((task, index) => (
               <TaskCard
                 key={task.id}
                 task={task}
                 index={index}
-                status={status}
+                status={column.id}
                 onViewDetails={onViewTaskDetails}
                 isOpen={selectedTask?.id === task.id}
               />
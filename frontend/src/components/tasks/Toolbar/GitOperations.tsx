@@ -7,6 +7,8 @@ import {
   AlertTriangle,
   CheckCircle,
   ExternalLink,
+  ListOrdered,
+  X,
 } from 'lucide-react';
 import { Button } from '@/components/ui/button.tsx';
 import {
@@ -24,6 +26,7 @@ import type {
 } from 'shared/types';
 import { useRebase } from '@/hooks/useRebase';
 import { useMerge } from '@/hooks/useMerge';
+import { useMergeQueue } from '@/hooks/useMergeQueue';
 import { usePush } from '@/hooks/usePush';
 import { useChangeTargetBranch } from '@/hooks/useChangeTargetBranch';
 import NiceModal from '@ebay/nice-modal-react';
@@ -62,6 +65,8 @@ function GitOperations({
   // Git operation hooks
   const rebaseMutation = useRebase(selectedAttempt.id, projectId);
   const mergeMutation = useMerge(selectedAttempt.id);
+  const mergeQueue = useMergeQueue(projectId, selectedAttempt.id);
+  const queueEntry = mergeQueue.entry;
   const pushMutation = usePush(selectedAttempt.id);
   const changeTargetBranchMutation = useChangeTargetBranch(
     selectedAttempt.id,
@@ -169,8 +174,43 @@ function GitOperations({
   }, [mergeInfo.hasOpenPR, pushSuccess, pushing, t]);
 
   const handleMergeClick = async () => {
-    // Directly perform merge without checking branch status
-    await performMerge();
+    // Protected target branches need an explicit confirmation
+    const allowProtected = !!branchStatus?.target_branch_protected;
+    if (
+      allowProtected &&
+      !window.confirm(
+        t('git.confirmProtectedMerge', {
+          branch: branchStatus?.target_branch_name,
+        })
+      )
+    ) {
+      return;
+    }
+    await performMerge(allowProtected);
+  };
+
+  const handleQueueClick = async () => {
+    try {
+      if (queueEntry?.status === 'queued') {
+        await mergeQueue.cancel.mutateAsync();
+      } else {
+        const allowProtected = !!branchStatus?.target_branch_protected;
+        if (
+          allowProtected &&
+          !window.confirm(
+            t('git.confirmProtectedMerge', {
+              branch: branchStatus?.target_branch_name,
+            })
+          )
+        ) {
+          return;
+        }
+        await mergeQueue.enqueue.mutateAsync(allowProtected);
+      }
+      setError(null);
+    } catch (error: any) {
+      setError(error.message || t('git.errors.mergeQueue'));
+    }
   };
 
   const handlePushClick = async () => {
@@ -187,10 +227,10 @@ function GitOperations({
     }
   };
 
-  const performMerge = async () => {
+  const performMerge = async (allowProtected: boolean) => {
     try {
       setMerging(true);
-      await mergeMutation.mutateAsync();
+      await mergeMutation.mutateAsync(allowProtected);
       setError(null); // Clear any previous errors on success
       setMergeSuccess(true);
       setTimeout(() => setMergeSuccess(false), 2000);
@@ -218,7 +258,9 @@ function GitOperations({
         const isConflict =
           data?.type === 'merge_conflicts' ||
           data?.type === 'rebase_in_progress';
-        if (!isConflict) setError(err.message || t('git.errors.rebaseBranch'));
+        if (data?.type === 'stash_conflict') setError(data.message);
+        else if (!isConflict)
+          setError(err.message || t('git.errors.rebaseBranch'));
       });
     setRebasing(false);
   };
@@ -402,6 +444,36 @@ function GitOperations({
             }
 
             const chips: React.ReactNode[] = [];
+            if (queueEntry?.status === 'failed') {
+              chips.push(
+                <span
+                  key="queue"
+                  title={queueEntry.error ?? undefined}
+                  className="inline-flex items-center gap-1 px-2 py-0.5 rounded-full bg-red-100/60 dark:bg-red-900/30 text-red-700 dark:text-red-300"
+                >
+                  <AlertTriangle className="h-3.5 w-3.5" />
+                  {t('git.queue.failed')}
+                  <button
+                    onClick={() => mergeQueue.cancel.mutate()}
+                    aria-label={t('git.queue.dismiss')}
+                  >
+                    <X className="h-3.5 w-3.5" />
+                  </button>
+                </span>
+              );
+            } else if (queueEntry) {
+              chips.push(
+                <span
+                  key="queue"
+                  className="inline-flex items-center gap-1 px-2 py-0.5 rounded-full bg-sky-100/60 dark:bg-sky-900/30 text-sky-700 dark:text-sky-300"
+                >
+                  <ListOrdered className="h-3.5 w-3.5" />
+                  {t(`git.queue.${queueEntry.status}`, {
+                    position: mergeQueue.position,
+                  })}
+                </span>
+              );
+            }
             if (commitsAhead > 0) {
               chips.push(
                 <span
@@ -426,6 +498,19 @@ function GitOperations({
                 </span>
               );
             }
+            const syncError = branchStatus?.base_branch_sync?.error;
+            if (syncError) {
+              chips.push(
+                <span
+                  key="sync-error"
+                  title={syncError}
+                  className="inline-flex items-center gap-1 px-2 py-0.5 rounded-full bg-amber-100/60 dark:bg-amber-900/30 text-amber-700 dark:text-amber-300"
+                >
+                  <AlertTriangle className="h-3.5 w-3.5" />
+                  {t('git.status.syncFailed')}
+                </span>
+              );
+            }
             if (chips.length > 0)
               return <div className="flex items-center gap-2">{chips}</div>;
 
@@ -460,6 +545,36 @@ function GitOperations({
               <span className="truncate max-w-[10ch]">{mergeButtonLabel}</span>
             </Button>
 
+            <Button
+              onClick={handleQueueClick}
+              disabled={
+                mergeInfo.hasOpenPR ||
+                hasConflictsCalculated ||
+                isAttemptRunning ||
+                mergeQueue.enqueue.isPending ||
+                mergeQueue.cancel.isPending ||
+                (!!queueEntry &&
+                  queueEntry.status !== 'queued' &&
+                  queueEntry.status !== 'failed') ||
+                (!queueEntry && (branchStatus.commits_ahead ?? 0) === 0)
+              }
+              variant="outline"
+              size="xs"
+              className="border-success text-success hover:bg-success gap-1 shrink-0"
+              aria-label={
+                queueEntry?.status === 'queued'
+                  ? t('git.queue.leave')
+                  : t('git.queue.join')
+              }
+            >
+              <ListOrdered className="h-3.5 w-3.5" />
+              <span className="truncate max-w-[10ch]">
+                {queueEntry?.status === 'queued'
+                  ? t('git.queue.leave')
+                  : t('git.queue.join')}
+              </span>
+            </Button>
+
             <Button
               onClick={handlePRButtonClick}
               disabled={
@@ -5,15 +5,27 @@ import {
   DropdownMenuContent,
   DropdownMenuItem,
   DropdownMenuLabel,
+  DropdownMenuRadioGroup,
+  DropdownMenuRadioItem,
   DropdownMenuSeparator,
+  DropdownMenuSub,
+  DropdownMenuSubContent,
+  DropdownMenuSubTrigger,
   DropdownMenuTrigger,
 } from '@/components/ui/dropdown-menu';
 import { MoreHorizontal } from 'lucide-react';
-import type { TaskWithAttemptStatus, TaskAttempt } from 'shared/types';
+import type {
+  TaskPriority,
+  TaskWithAttemptStatus,
+  TaskAttempt,
+} from 'shared/types';
 import { useOpenInEditor } from '@/hooks/useOpenInEditor';
 import NiceModal from '@ebay/nice-modal-react';
 import { useProject } from '@/contexts/project-context';
 import { openTaskForm } from '@/lib/openTaskForm';
+import { attemptsApi, tasksApi } from '@/lib/api';
+
+const TASK_PRIORITIES: TaskPriority[] = ['urgent', 'high', 'normal', 'low'];
 
 interface ActionsDropdownProps {
   task?: TaskWithAttemptStatus | null;
@@ -50,6 +62,23 @@ export function ActionsDropdown({ task, attempt }: ActionsDropdownProps) {
     }
   };
 
+  const handleSetPriority = async (priority: string) => {
+    if (!task || priority === task.priority) return;
+    try {
+      await tasksApi.update(task.id, {
+        title: null,
+        description: null,
+        status: null,
+        parent_task_attempt: null,
+        priority: priority as TaskPriority,
+        image_ids: null,
+        tag_ids: null,
+      });
+    } catch (err) {
+      console.error('Failed to update task priority:', err);
+    }
+  };
+
   const handleOpenInEditor = (e: React.MouseEvent) => {
     e.stopPropagation();
     if (!attempt?.id) return;
@@ -81,6 +110,21 @@ export function ActionsDropdown({ task, attempt }: ActionsDropdownProps) {
     });
   };
 
+  const handleCopyShareLink = async (e: React.MouseEvent) => {
+    e.stopPropagation();
+    if (!attempt?.id) return;
+    try {
+      const link = await attemptsApi.createShareLink(attempt.id, {
+        expires_in_hours: null,
+      });
+      await navigator.clipboard.writeText(
+        link.url ?? `${window.location.origin}/shared/${link.token}`
+      );
+    } catch (err) {
+      console.error('Failed to create share link:', err);
+    }
+  };
+
   const handleGitActions = (e: React.MouseEvent) => {
     e.stopPropagation();
     if (!attempt?.id || !task) return;
@@ -134,6 +178,12 @@ export function ActionsDropdown({ task, attempt }: ActionsDropdownProps) {
               >
                 {t('actionsMenu.gitActions')}
               </DropdownMenuItem>
+              <DropdownMenuItem
+                disabled={!attempt?.id}
+                onClick={handleCopyShareLink}
+              >
+                {t('actionsMenu.copyShareLink')}
+              </DropdownMenuItem>
               <DropdownMenuSeparator />
             </>
           )}
@@ -147,6 +197,23 @@ export function ActionsDropdown({ task, attempt }: ActionsDropdownProps) {
               <DropdownMenuItem disabled={!projectId} onClick={handleDuplicate}>
                 {t('actionsMenu.duplicate')}
               </DropdownMenuItem>
+              <DropdownMenuSub>
+                <DropdownMenuSubTrigger>
+                  {t('actionsMenu.priority')}
+                </DropdownMenuSubTrigger>
+                <DropdownMenuSubContent>
+                  <DropdownMenuRadioGroup
+                    value={task?.priority}
+                    onValueChange={handleSetPriority}
+                  >
+                    {TASK_PRIORITIES.map((priority) => (
+                      <DropdownMenuRadioItem key={priority} value={priority}>
+                        {t(`priority.${priority}`)}
+                      </DropdownMenuRadioItem>
+                    ))}
+                  </DropdownMenuRadioGroup>
+                </DropdownMenuSubContent>
+              </DropdownMenuSub>
               <DropdownMenuItem
                 disabled={!projectId}
                 onClick={handleDelete}
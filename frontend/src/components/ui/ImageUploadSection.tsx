@@ -14,6 +14,7 @@ import {
   AlertCircle,
 } from 'lucide-react';
 import { Button } from './button';
+import { Input } from './input';
 import { Alert, AlertDescription } from './alert';
 import { cn } from '@/lib/utils';
 import { imagesApi } from '@/lib/api';
@@ -25,6 +26,8 @@ interface ImageUploadSectionProps {
   onUpload: (file: File) => Promise<ImageResponse>;
   onDelete?: (imageId: string) => Promise<void>;
   onImageUploaded?: (image: ImageResponse) => void; // Custom callback for upload success
+  captions?: Record<string, string>; // Shown to the agent with each image
+  onCaptionChange?: (imageId: string, caption: string) => void;
   isUploading?: boolean;
   disabled?: boolean;
   readOnly?: boolean;
@@ -59,6 +62,8 @@ export const ImageUploadSection = forwardRef<
       onUpload,
       onDelete,
       onImageUploaded,
+      captions,
+      onCaptionChange,
       isUploading = false,
       disabled = false,
       readOnly = false,
@@ -295,6 +300,15 @@ export const ImageUploadSection = forwardRef<
                     </p>
                   </div>
                 </div>
+                {onCaptionChange && (
+                  <Input
+                    value={captions?.[image.id] ?? ''}
+                    onChange={(e) => onCaptionChange(image.id, e.target.value)}
+                    placeholder="Caption: what does this image show?"
+                    disabled={disabled || readOnly}
+                    className="mt-2 h-7 text-xs"
+                  />
+                )}
                 {!disabled && !readOnly && (
                   <Button
                     variant="ghost"
@@ -150,6 +150,8 @@ export type KanbanHeaderProps =
       color: Status['color'];
       className?: string;
       onAddTask?: () => void;
+      /** Tasks with the column's status against its work-in-progress limit */
+      wip?: { count: number; max: number };
     };
 
 export const KanbanHeader = (props: KanbanHeaderProps) => {
@@ -177,6 +179,19 @@ export const KanbanHeader = (props: KanbanHeaderProps) => {
         />
 
         <p className="m-0 text-sm">{props.name}</p>
+        {props.wip && (
+          <span
+            title={t('wipLimit', props.wip)}
+            className={cn(
+              'text-xs',
+              props.wip.count > props.wip.max
+                ? 'text-destructive'
+                : 'text-muted-foreground'
+            )}
+          >
+            {props.wip.count}/{props.wip.max}
+          </span>
+        )}
       </span>
       <TooltipProvider>
         <Tooltip>
@@ -6,6 +6,8 @@ export const PROCESS_RUN_REASONS = {
   CLEANUP_SCRIPT: 'cleanupscript' as ExecutionProcessRunReason,
   CODING_AGENT: 'codingagent' as ExecutionProcessRunReason,
   DEV_SERVER: 'devserver' as ExecutionProcessRunReason,
+  POST_MERGE_SCRIPT: 'postmergescript' as ExecutionProcessRunReason,
+  TEST_SCRIPT: 'testscript' as ExecutionProcessRunReason,
 } as const;
 
 export const isCodingAgent = (
@@ -50,7 +50,7 @@ export const ExecutionProcessesProvider: React.FC<{
           (process.run_reason === 'codingagent' ||
             process.run_reason === 'setupscript' ||
             process.run_reason === 'cleanupscript') &&
-          process.status === 'running'
+          (process.status === 'running' || process.status === 'queued')
       ),
     [visible]
   );
@@ -13,16 +13,19 @@ type BaseCurrent = {
   prompt: string;
   variant: string | null | undefined;
   image_ids: string[] | null | undefined;
+  image_captions?: Record<string, string> | null;
 };
 type BaseServer = {
   prompt?: string | null;
   variant?: string | null;
   image_ids?: string[] | null;
+  image_captions?: { [key in string]?: string } | null;
 } | null;
 type BasePayload = {
   prompt?: string;
   variant?: string | null;
   image_ids?: string[];
+  image_captions?: Record<string, string>;
 };
 
 function diffBaseDraft(current: BaseCurrent, server: BaseServer): BasePayload {
@@ -41,6 +44,19 @@ function diffBaseDraft(current: BaseCurrent, server: BaseServer): BasePayload {
     currIds.every((id, i) => id === serverIds[i]);
   if (!idsEqual) payload.image_ids = currIds;
 
+  if (current.image_captions !== undefined) {
+    const currCaptions = current.image_captions ?? {};
+    const serverCaptions = server?.image_captions ?? {};
+    const keys = new Set([
+      ...Object.keys(currCaptions),
+      ...Object.keys(serverCaptions),
+    ]);
+    const captionsEqual = [...keys].every(
+      (id) => (currCaptions[id] ?? '') === (serverCaptions[id] ?? '')
+    );
+    if (!captionsEqual) payload.image_captions = currCaptions;
+  }
+
   return payload;
 }
 
@@ -174,7 +190,9 @@ function useDraftAutosaveCore<TServer, TCurrent, TPayload>({
   return { isSaving, saveStatus } as const;
 }
 
-type DraftData = Pick<Draft, 'prompt' | 'variant' | 'image_ids'>;
+type DraftData = Pick<Draft, 'prompt' | 'variant' | 'image_ids'> & {
+  image_captions?: Record<string, string>;
+};
 
 type DraftArgs<TServer, TCurrent> = {
   attemptId?: string;
@@ -3,7 +3,7 @@ import type { Draft, ImageResponse } from 'shared/types';
 import { imagesApi } from '@/lib/api';
 import { useQuery } from '@tanstack/react-query';
 
-type PartialDraft = Pick<Draft, 'prompt' | 'image_ids'>;
+type PartialDraft = Pick<Draft, 'prompt' | 'image_ids' | 'image_captions'>;
 
 type Args = {
   draft: PartialDraft | null;
@@ -16,9 +16,13 @@ export function useDraftEditor({ draft, taskId }: Args) {
   const [newlyUploadedImageIds, setNewlyUploadedImageIds] = useState<string[]>(
     []
   );
+  const [localCaptions, setLocalCaptions] = useState<Record<string, string>>(
+    {}
+  );
 
   const localDirtyRef = useRef<boolean>(false);
   const imagesDirtyRef = useRef<boolean>(false);
+  const captionsDirtyRef = useRef<boolean>(false);
 
   const isMessageLocallyDirty = useCallback(() => localDirtyRef.current, []);
 
@@ -52,6 +56,21 @@ export function useDraftEditor({ draft, taskId }: Args) {
     ? localImages
     : (imagesQuery.data ?? []);
 
+  const captions: Record<string, string> = captionsDirtyRef.current
+    ? localCaptions
+    : ((draft?.image_captions ?? {}) as Record<string, string>);
+
+  const setCaption = useCallback(
+    (imageId: string, caption: string) => {
+      const base = captionsDirtyRef.current
+        ? localCaptions
+        : ((draft?.image_captions ?? {}) as Record<string, string>);
+      captionsDirtyRef.current = true;
+      setLocalCaptions({ ...base, [imageId]: caption });
+    },
+    [draft?.image_captions, localCaptions]
+  );
+
   const setMessage = (v: React.SetStateAction<string>) => {
     localDirtyRef.current = true;
     if (typeof v === 'function') {
@@ -74,8 +93,10 @@ export function useDraftEditor({ draft, taskId }: Args) {
 
   const clearImagesAndUploads = useCallback(() => {
     imagesDirtyRef.current = false;
+    captionsDirtyRef.current = false;
     setLocalImages([]);
     setNewlyUploadedImageIds([]);
+    setLocalCaptions({});
   }, []);
 
   return {
@@ -83,6 +104,8 @@ export function useDraftEditor({ draft, taskId }: Args) {
     setMessage,
     images,
     setImages,
+    captions,
+    setCaption,
     newlyUploadedImageIds,
     handleImageUploaded,
     clearImagesAndUploads,
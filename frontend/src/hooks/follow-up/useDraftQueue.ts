@@ -8,6 +8,7 @@ type Args = {
   message: string;
   selectedVariant: string | null;
   images: ImageResponse[];
+  captions: Record<string, string>;
 };
 
 export function useDraftQueue({
@@ -16,6 +17,7 @@ export function useDraftQueue({
   message,
   selectedVariant,
   images,
+  captions,
 }: Args) {
   const onQueue = useCallback(async (): Promise<boolean> => {
     if (!attemptId) return false;
@@ -33,6 +35,7 @@ export function useDraftQueue({
         currentIds.length === serverIds.length &&
         currentIds.every((id, i) => id === serverIds[i]);
       if (!idsEqual) immediatePayload.image_ids = currentIds;
+      immediatePayload.image_captions = captions;
       await attemptsApi.saveDraft(
         attemptId,
         'follow_up',
@@ -50,6 +53,7 @@ export function useDraftQueue({
     draft?.image_ids,
     draft?.queued,
     images,
+    captions,
     message,
     selectedVariant,
   ]);
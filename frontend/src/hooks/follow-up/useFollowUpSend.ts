@@ -10,6 +10,7 @@ type Args = {
   clickedMarkdown?: string;
   selectedVariant: string | null;
   images: ImageResponse[];
+  captions: Record<string, string>;
   newlyUploadedImageIds: string[];
   clearComments: () => void;
   clearClickedElements?: () => void;
@@ -26,6 +27,7 @@ export function useFollowUpSend({
   clickedMarkdown,
   selectedVariant,
   images,
+  captions,
   newlyUploadedImageIds,
   clearComments,
   clearClickedElements,
@@ -61,6 +63,7 @@ export function useFollowUpSend({
         prompt: finalPrompt,
         variant: selectedVariant,
         image_ids,
+        image_captions: captions,
         retry_process_id: null,
         force_when_dirty: null,
         perform_git_reset: null,
@@ -86,6 +89,7 @@ export function useFollowUpSend({
     clickedMarkdown,
     newlyUploadedImageIds,
     images,
+    captions,
     selectedVariant,
     clearComments,
     clearClickedElements,
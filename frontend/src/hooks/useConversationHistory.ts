@@ -109,7 +109,9 @@ export const useConversationHistory = ({
       (ep) =>
         ep.run_reason === 'setupscript' ||
         ep.run_reason === 'cleanupscript' ||
-        ep.run_reason === 'codingagent'
+        ep.run_reason === 'codingagent' ||
+        ep.run_reason === 'postmergescript' ||
+        ep.run_reason === 'testscript'
     );
   }, [executionProcessesRaw]);
 
@@ -325,7 +327,7 @@ export const useConversationHistory = ({
         } else if (
           p.executionProcess.executor_action.typ.type === 'ScriptRequest'
         ) {
-          // Add setup and cleanup script as a tool call
+          // Add setup, cleanup, test, post-merge and diagnostic scripts as a tool call
           let toolName = '';
           switch (p.executionProcess.executor_action.typ.context) {
             case 'SetupScript':
@@ -334,6 +336,15 @@ export const useConversationHistory = ({
             case 'CleanupScript':
               toolName = 'Cleanup Script';
               break;
+            case 'TestScript':
+              toolName = 'Test Run';
+              break;
+            case 'PostMergeScript':
+              toolName = 'Post-merge Script';
+              break;
+            case 'DiagnosticScript':
+              toolName = 'Diagnostic Script';
+              break;
             default:
               return [];
           }
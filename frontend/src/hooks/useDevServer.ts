@@ -1,6 +1,6 @@
 import { useMemo } from 'react';
-import { useMutation, useQueryClient } from '@tanstack/react-query';
-import { attemptsApi, executionProcessesApi } from '@/lib/api';
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
+import { attemptsApi } from '@/lib/api';
 import { useAttemptExecution } from '@/hooks/useAttemptExecution';
 import type { ExecutionProcess } from 'shared/types';
 
@@ -36,6 +36,18 @@ export function useDevServer(
       )[0];
   }, [attemptData.processes]);
 
+  // Allocated port and URL; refetched whenever the dev server process changes
+  const { data: status } = useQuery({
+    queryKey: [
+      'devServer',
+      attemptId,
+      latestDevServerProcess?.id,
+      latestDevServerProcess?.status,
+    ],
+    queryFn: () => attemptsApi.getDevServer(attemptId!),
+    enabled: !!attemptId,
+  });
+
   // Start mutation
   const startMutation = useMutation({
     mutationKey: ['startDevServer', attemptId],
@@ -57,10 +69,10 @@ export function useDevServer(
 
   // Stop mutation
   const stopMutation = useMutation({
-    mutationKey: ['stopDevServer', runningDevServer?.id],
+    mutationKey: ['stopDevServer', attemptId],
     mutationFn: async () => {
-      if (!runningDevServer) return;
-      await executionProcessesApi.stopExecutionProcess(runningDevServer.id);
+      if (!attemptId || !runningDevServer) return;
+      await attemptsApi.stopDevServer(attemptId);
     },
     onSuccess: async () => {
       await Promise.all([
@@ -88,5 +100,6 @@ export function useDevServer(
     isStopping: stopMutation.isPending,
     runningDevServer,
     latestDevServerProcess,
+    devServerUrl: status?.url ?? undefined,
   };
 }
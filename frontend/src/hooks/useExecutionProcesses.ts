@@ -54,7 +54,7 @@ export const useExecutionProcesses = (
       (process.run_reason === 'codingagent' ||
         process.run_reason === 'setupscript' ||
         process.run_reason === 'cleanupscript') &&
-      process.status === 'running'
+      (process.status === 'running' || process.status === 'queued')
   );
   const isLoading = !!taskAttemptId && !data && !error; // until first snapshot
 
@@ -9,9 +9,9 @@ export function useMerge(
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: () => {
+    mutationFn: (allowProtected: boolean = false) => {
       if (!attemptId) return Promise.resolve();
-      return attemptsApi.merge(attemptId);
+      return attemptsApi.merge(attemptId, allowProtected);
     },
     onSuccess: () => {
       // Refresh attempt-specific branch information
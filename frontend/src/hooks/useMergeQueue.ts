@@ -0,0 +1,45 @@
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
+import { attemptsApi, projectsApi } from '@/lib/api';
+
+/**
+ * The attempt's place in its project's merge queue, with actions to join and leave it
+ */
+export function useMergeQueue(projectId?: string, attemptId?: string) {
+  const queryClient = useQueryClient();
+  const queryKey = ['mergeQueue', projectId];
+
+  const { data: entries = [] } = useQuery({
+    queryKey,
+    queryFn: () => projectsApi.getMergeQueue(projectId!),
+    enabled: !!projectId,
+    // Poll while the queue has work so status changes show up
+    refetchInterval: (query) =>
+      query.state.data?.some((entry) => entry.status !== 'failed')
+        ? 5000
+        : false,
+  });
+
+  const waiting = entries.filter((entry) => entry.status !== 'failed');
+  const entry = entries.find((e) => e.task_attempt_id === attemptId) ?? null;
+  const position = entry ? waiting.indexOf(entry) + 1 : 0;
+
+  const invalidate = async () => {
+    await queryClient.invalidateQueries({ queryKey });
+    await queryClient.invalidateQueries({
+      queryKey: ['branchStatus', attemptId],
+    });
+  };
+
+  const enqueue = useMutation({
+    mutationFn: (allowProtected: boolean = false) =>
+      attemptsApi.enqueueMerge(attemptId!, allowProtected),
+    onSuccess: invalidate,
+  });
+
+  const cancel = useMutation({
+    mutationFn: () => attemptsApi.cancelMerge(attemptId!),
+    onSuccess: invalidate,
+  });
+
+  return { entry, position, enqueue, cancel };
+}
@@ -0,0 +1,37 @@
+import { useMemo } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import { projectsApi } from '@/lib/api';
+import { statusLabels } from '@/utils/status-labels';
+import type { TaskStatus } from 'shared/types';
+
+export interface BoardColumn {
+  id: string;
+  name: string;
+  status: TaskStatus;
+}
+
+const DEFAULT_COLUMNS: BoardColumn[] = (
+  ['todo', 'inprogress', 'inreview', 'done', 'cancelled'] as const
+).map((status) => ({ id: status, name: statusLabels[status], status }));
+
+/**
+ * The project's board columns, left to right. Until they load the board shows
+ * one column per status.
+ */
+export function useProjectColumns(projectId?: string) {
+  const { data } = useQuery({
+    queryKey: ['projectColumns', projectId],
+    queryFn: () => projectsApi.getColumns(projectId!),
+    enabled: !!projectId,
+  });
+
+  const columns = useMemo<BoardColumn[]>(
+    () =>
+      data && data.length > 0
+        ? data.map(({ id, name, status }) => ({ id, name, status }))
+        : DEFAULT_COLUMNS,
+    [data]
+  );
+
+  return { columns, loaded: !!data && data.length > 0 };
+}
@@ -31,10 +31,12 @@ export const useProjectTasks = (projectId: string): UseProjectTasksResult => {
   );
 
   const tasksById = data?.tasks ?? {};
+  // Manual board order first, newest first among equals
   const tasks = Object.values(tasksById).sort(
     (a, b) =>
+      Number(a.sort_order) - Number(b.sort_order) ||
       new Date(b.created_at as unknown as string).getTime() -
-      new Date(a.created_at as unknown as string).getTime()
+        new Date(a.created_at as unknown as string).getTime()
   );
   const isLoading = !data && !error; // until first snapshot
 
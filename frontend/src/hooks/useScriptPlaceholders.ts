@@ -19,5 +19,7 @@ export function useScriptPlaceholders(): ScriptPlaceholders {
     dev: '#!/bin/bash\nnpm run dev\n# Add dev server start command here...',
     cleanup:
       '#!/bin/bash\n# Add cleanup commands here...\n# This runs after coding agent execution',
+    verify:
+      '#!/bin/bash\nnpm test\n# The merge queue only merges attempts for which this succeeds',
   };
 }
@@ -1,6 +1,6 @@
 import { useQuery } from '@tanstack/react-query';
 import { attemptsApi } from '@/lib/api';
-import type { TaskAttempt } from 'shared/types';
+import type { TaskAttemptSummary } from 'shared/types';
 
 export const taskAttemptKeys = {
   all: ['taskAttempts'] as const,
@@ -16,7 +16,7 @@ export function useTaskAttempts(taskId?: string, opts?: Options) {
   const enabled = (opts?.enabled ?? true) && !!taskId;
   const refetchInterval = opts?.refetchInterval ?? 5000;
 
-  return useQuery<TaskAttempt[]>({
+  return useQuery<TaskAttemptSummary[]>({
     queryKey: taskAttemptKeys.byTask(taskId),
     queryFn: () => attemptsApi.getAll(taskId!),
     enabled,
@@ -0,0 +1,46 @@
+import { useCallback } from 'react';
+import { useJsonPatchWsStream } from './useJsonPatchWsStream';
+import type { TaskComment } from 'shared/types';
+
+type CommentsState = {
+  comments: Record<string, TaskComment>;
+};
+
+interface UseTaskCommentsResult {
+  comments: TaskComment[];
+  isLoading: boolean;
+  isConnected: boolean;
+  error: string | null;
+}
+
+/**
+ * Stream a task's comments via WebSocket (JSON Patch), oldest first.
+ * Server sends initial snapshot: replace /comments with an object keyed by id.
+ * Live updates arrive at /comments/<id> via add/replace/remove operations.
+ */
+export const useTaskComments = (
+  taskId: string | undefined
+): UseTaskCommentsResult => {
+  const endpoint = taskId
+    ? `/api/tasks/${taskId}/comments/stream/ws`
+    : undefined;
+
+  const initialData = useCallback(
+    (): CommentsState => ({ comments: {} }),
+    []
+  );
+
+  const { data, isConnected, error } = useJsonPatchWsStream(
+    endpoint,
+    !!taskId,
+    initialData
+  );
+
+  const comments = Object.values(data?.comments ?? {}).sort(
+    (a, b) =>
+      new Date(a.created_at).getTime() - new Date(b.created_at).getTime()
+  );
+  const isLoading = !!taskId && !data && !error; // until first snapshot
+
+  return { comments, isLoading, isConnected, error };
+};
@@ -0,0 +1,27 @@
+import { useEffect, useMemo, useState } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import { projectsApi } from '@/lib/api';
+
+/**
+ * Ids of the project's tasks whose title, description, comments or tags
+ * match the query, searched on the server once typing pauses
+ */
+export function useTaskSearch(projectId: string | undefined, query: string) {
+  const [debounced, setDebounced] = useState(query.trim());
+
+  useEffect(() => {
+    const timer = setTimeout(() => setDebounced(query.trim()), 300);
+    return () => clearTimeout(timer);
+  }, [query]);
+
+  const { data } = useQuery({
+    queryKey: ['taskSearch', projectId, debounced],
+    queryFn: () => projectsApi.searchTasks(projectId!, debounced, 100),
+    enabled: !!projectId && debounced.length > 0,
+  });
+
+  return useMemo(
+    () => new Set((data ?? []).map((result) => result.task_id)),
+    [data]
+  );
+}
@@ -0,0 +1,34 @@
+import { useMemo } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import { projectsApi } from '@/lib/api';
+import type { TaskStatus, TaskWithAttemptStatus } from 'shared/types';
+
+export interface WipCount {
+  count: number;
+  max: number;
+}
+
+/**
+ * Task counts of the project's statuses that have a work-in-progress limit
+ */
+export function useWipLimits(
+  projectId: string | undefined,
+  tasks: TaskWithAttemptStatus[]
+) {
+  const { data: limits } = useQuery({
+    queryKey: ['wipLimits', projectId],
+    queryFn: () => projectsApi.getWipLimits(projectId!),
+    enabled: !!projectId,
+  });
+
+  return useMemo(() => {
+    const counts: Partial<Record<TaskStatus, WipCount>> = {};
+    for (const limit of limits ?? []) {
+      counts[limit.status] = {
+        count: tasks.filter((task) => task.status === limit.status).length,
+        max: Number(limit.max_tasks),
+      };
+    }
+    return counts;
+  }, [limits, tasks]);
+}
@@ -0,0 +1,58 @@
+import { useCallback } from 'react';
+import { useJsonPatchWsStream } from './useJsonPatchWsStream';
+import type { TaskWithAttemptStatus } from 'shared/types';
+
+type TasksState = {
+  tasks: Record<string, TaskWithAttemptStatus>;
+};
+
+interface UseWorkspaceTasksResult {
+  tasks: TaskWithAttemptStatus[];
+  tasksById: Record<string, TaskWithAttemptStatus>;
+  /** Tasks of each project, keyed by project id */
+  tasksByProject: Record<string, TaskWithAttemptStatus[]>;
+  isLoading: boolean;
+  isConnected: boolean;
+  error: string | null;
+}
+
+/**
+ * Stream the tasks of every project in a workspace via WebSocket (JSON Patch),
+ * in the same shape as useProjectTasks. The server fixes the set of projects
+ * when the socket opens, so pass a new `revision` (e.g. the workspace's
+ * updated_at) to reconnect after the workspace's projects change.
+ */
+export const useWorkspaceTasks = (
+  workspaceId: string,
+  revision?: string
+): UseWorkspaceTasksResult => {
+  const params = revision ? `?rev=${encodeURIComponent(revision)}` : '';
+  const endpoint = `/api/workspaces/${encodeURIComponent(workspaceId)}/tasks/stream/ws${params}`;
+
+  const initialData = useCallback((): TasksState => ({ tasks: {} }), []);
+
+  const { data, isConnected, error } = useJsonPatchWsStream(
+    endpoint,
+    !!workspaceId,
+    initialData
+  );
+
+  const tasksById = data?.tasks ?? {};
+  // Manual board order first, newest first among equals
+  const tasks = Object.values(tasksById).sort(
+    (a, b) =>
+      Number(a.sort_order) - Number(b.sort_order) ||
+      new Date(b.created_at as unknown as string).getTime() -
+        new Date(a.created_at as unknown as string).getTime()
+  );
+  const tasksByProject: Record<string, TaskWithAttemptStatus[]> = {};
+  for (const task of tasks) {
+    if (!tasksByProject[task.project_id]) {
+      tasksByProject[task.project_id] = [];
+    }
+    tasksByProject[task.project_id].push(task);
+  }
+  const isLoading = !data && !error; // until first snapshot
+
+  return { tasks, tasksById, tasksByProject, isLoading, isConnected, error };
+};
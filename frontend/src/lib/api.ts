@@ -1,38 +1,71 @@
 // Import all necessary types from shared types
 
 import {
+  AddTaskDependencyRequest,
+  ApprovalDecision,
   ApprovalStatus,
   ApiResponse,
+  AttemptReport,
+  AuditAction,
+  AuditLogEntry,
   BranchStatus,
   CheckTokenResponse,
   Config,
+  ConfigOverride,
+  EffectiveConfig,
+  EmailNotificationConfig,
   CommitInfo,
   CreateFollowUpAttempt,
   CreateGitHubPrRequest,
   CreateTask,
   CreateAndStartTaskRequest,
+  CreateSubtaskRequest,
   CreateTaskAttemptBody,
   CreateTag,
+  CreateTagRequest,
+  CreateTagResponse,
   DeviceFlowStartResponse,
   DevicePollStatus,
   DirectoryListResponse,
   DirectoryEntry,
   EditorType,
+  EventJournalEntry,
   ExecutionProcess,
+  ExecutorVariants,
   GitBranch,
   Project,
   CreateProject,
+  CreateProjectColumn,
+  CreatePromptSnippet,
+  PromptSnippet,
+  RateLimitSnapshot,
+  ReportPeriod,
+  ReorderProjectColumnsRequest,
+  ReorderTasksRequest,
+  RepoHealthReport,
+  RepoRepairReport,
   RepositoryInfo,
   SearchResult,
+  SetConfigOverride,
+  StorageInfo,
+  StorageRelocation,
   Task,
   TaskAttempt,
+  TaskAttemptDetails,
+  TaskAttemptSummary,
+  Merge,
   TaskRelationships,
   Tag,
   TagSearchParams,
   TaskWithAttemptStatus,
   UpdateProject,
+  UpdateProjectColumn,
   UpdateTask,
   UpdateTag,
+  UpdatePromptSnippet,
+  Workspace,
+  CreateWorkspace,
+  UpdateWorkspace,
   UserSystemInfo,
   GitHubServiceError,
   UpdateRetryFollowUpDraftRequest,
@@ -47,6 +80,83 @@ import {
   RebaseTaskAttemptRequest,
   ChangeTargetBranchRequest,
   ChangeTargetBranchResponse,
+  CherryPickRequest,
+  CherryPickResult,
+  ExportFormat,
+  ProcessTreeNode,
+  Diff,
+  DiffFilePage,
+  LockfileDependencyDiff,
+  DevServerStatus,
+  MergeQueueEntry,
+  ProjectColumn,
+  ProjectEnvVarResponse,
+  UpsertProjectEnvVar,
+  BulkRerunRequest,
+  BulkRerunResponse,
+  AttemptComparison,
+  AttemptComparisonDetails,
+  StartAttemptComparison,
+  TestRunResult,
+  UpdateAttemptReview,
+  Checkpoint,
+  RollbackToCheckpointRequest,
+  RollbackResult,
+  WorktreeFile,
+  WriteWorktreeFileRequest,
+  WriteWorktreeFileResult,
+  TerminalSession,
+  TaskSchedule,
+  CreateTaskSchedule,
+  UpdateTaskSchedule,
+  AutomationRule,
+  AutomationRuleRun,
+  CreateAutomationRule,
+  UpdateAutomationRule,
+  Webhook,
+  CreateWebhook,
+  UpdateWebhook,
+  WebhookDelivery,
+  WebhookDeliveryStatus,
+  ChatChannel,
+  CreateChatChannel,
+  UpdateChatChannel,
+  AppNotification,
+  BaseCodingAgent,
+  ExecutionProcessPage,
+  ExecutionMetricsReport,
+  ExecutionProcessMetrics,
+  ExecutionProcessWithUsage,
+  ExecutionProcessLogMatches,
+  LogLine,
+  LogStream,
+  StopSummary,
+  SetupCacheEntry,
+  ExecutionProcessRunReason,
+  ExecutionProcessStatus,
+  TaskStatus,
+  WipLimit,
+  UpsertWipLimit,
+  WipUsage,
+  TaskComment,
+  CreateTaskComment,
+  UpdateTaskComment,
+  TaskSearchResult,
+  CreateTaskFromTemplate,
+  TaskImportRequest,
+  TaskImportSummary,
+  ProjectImportSummary,
+  CloneProjectRequest,
+  ProjectClone,
+  GitHubIssueSync,
+  UpsertGitHubIssueSync,
+  GitHubIssueSyncSummary,
+  IssueTrackerConnection,
+  UpsertIssueTrackerConnection,
+  IssueTrackerSyncSummary,
+  CreateShareLink,
+  ShareLink,
+  SharedAttempt,
 } from 'shared/types';
 
 // Re-export types for convenience
@@ -213,6 +323,259 @@ export const projectsApi = {
     return handleApiResponse<Project>(response);
   },
 
+  getMergeQueue: async (id: string): Promise<MergeQueueEntry[]> => {
+    const response = await makeRequest(`/api/projects/${id}/merge-queue`);
+    return handleApiResponse<MergeQueueEntry[]>(response);
+  },
+
+  getApprovalDecisions: async (
+    id: string,
+    limit?: number
+  ): Promise<ApprovalDecision[]> => {
+    const params = new URLSearchParams();
+    if (limit !== undefined) params.set('limit', String(limit));
+    const response = await makeRequest(
+      `/api/projects/${id}/approval-decisions?${params.toString()}`
+    );
+    return handleApiResponse<ApprovalDecision[]>(response);
+  },
+
+  getEnvVars: async (id: string): Promise<ProjectEnvVarResponse[]> => {
+    const response = await makeRequest(`/api/projects/${id}/env`);
+    return handleApiResponse<ProjectEnvVarResponse[]>(response);
+  },
+
+  setEnvVar: async (
+    id: string,
+    data: UpsertProjectEnvVar
+  ): Promise<ProjectEnvVarResponse> => {
+    const response = await makeRequest(`/api/projects/${id}/env`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ProjectEnvVarResponse>(response);
+  },
+
+  deleteEnvVar: async (id: string, key: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/env/${encodeURIComponent(key)}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  searchTasks: async (
+    id: string,
+    query: string,
+    limit?: number
+  ): Promise<TaskSearchResult[]> => {
+    const params = new URLSearchParams({ q: query });
+    if (limit) params.set('limit', String(limit));
+    const response = await makeRequest(
+      `/api/projects/${id}/search/tasks?${params.toString()}`
+    );
+    return handleApiResponse<TaskSearchResult[]>(response);
+  },
+
+  importTasks: async (
+    id: string,
+    data: TaskImportRequest
+  ): Promise<TaskImportSummary> => {
+    const response = await makeRequest(`/api/projects/${id}/tasks/import`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskImportSummary>(response);
+  },
+
+  getGitHubIssueSync: async (id: string): Promise<GitHubIssueSync | null> => {
+    const response = await makeRequest(`/api/projects/${id}/github-issues`);
+    return handleApiResponse<GitHubIssueSync | null>(response);
+  },
+
+  upsertGitHubIssueSync: async (
+    id: string,
+    data: UpsertGitHubIssueSync
+  ): Promise<GitHubIssueSync> => {
+    const response = await makeRequest(`/api/projects/${id}/github-issues`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<GitHubIssueSync>(response);
+  },
+
+  deleteGitHubIssueSync: async (id: string): Promise<void> => {
+    const response = await makeRequest(`/api/projects/${id}/github-issues`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  syncGitHubIssues: async (id: string): Promise<GitHubIssueSyncSummary> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/github-issues/sync`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<GitHubIssueSyncSummary>(response);
+  },
+
+  getIssueTracker: async (
+    id: string
+  ): Promise<IssueTrackerConnection | null> => {
+    const response = await makeRequest(`/api/projects/${id}/issue-tracker`);
+    return handleApiResponse<IssueTrackerConnection | null>(response);
+  },
+
+  upsertIssueTracker: async (
+    id: string,
+    data: UpsertIssueTrackerConnection
+  ): Promise<IssueTrackerConnection> => {
+    const response = await makeRequest(`/api/projects/${id}/issue-tracker`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<IssueTrackerConnection>(response);
+  },
+
+  deleteIssueTracker: async (id: string): Promise<void> => {
+    const response = await makeRequest(`/api/projects/${id}/issue-tracker`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  syncIssueTracker: async (id: string): Promise<IssueTrackerSyncSummary> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/issue-tracker/sync`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<IssueTrackerSyncSummary>(response);
+  },
+
+  // Zip archive of the project's tasks, attempts and execution processes
+  getExportUrl: (id: string, includeLogs: boolean): string => {
+    return `/api/projects/${id}/export?include_logs=${includeLogs}`;
+  },
+
+  importArchive: async (
+    archive: Blob,
+    gitRepoPath: string,
+    name?: string
+  ): Promise<ProjectImportSummary> => {
+    const params = new URLSearchParams({ git_repo_path: gitRepoPath });
+    if (name) params.set('name', name);
+    const response = await makeRequest(`/api/projects/import?${params}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/zip' },
+      body: archive,
+    });
+    return handleApiResponse<ProjectImportSummary>(response);
+  },
+
+  // Starts cloning in the background; progress arrives on /api/events under
+  // /project_clones/{id}
+  clone: async (data: CloneProjectRequest): Promise<ProjectClone> => {
+    const response = await makeRequest('/api/projects/clone', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ProjectClone>(response);
+  },
+
+  getWipLimits: async (id: string): Promise<WipLimit[]> => {
+    const response = await makeRequest(`/api/projects/${id}/wip-limits`);
+    return handleApiResponse<WipLimit[]>(response);
+  },
+
+  setWipLimit: async (id: string, data: UpsertWipLimit): Promise<WipLimit> => {
+    const response = await makeRequest(`/api/projects/${id}/wip-limits`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<WipLimit>(response);
+  },
+
+  deleteWipLimit: async (id: string, status: TaskStatus): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/wip-limits/${status}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  getColumns: async (id: string): Promise<ProjectColumn[]> => {
+    const response = await makeRequest(`/api/projects/${id}/columns`);
+    return handleApiResponse<ProjectColumn[]>(response);
+  },
+
+  createColumn: async (
+    id: string,
+    data: CreateProjectColumn
+  ): Promise<ProjectColumn> => {
+    const response = await makeRequest(`/api/projects/${id}/columns`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ProjectColumn>(response);
+  },
+
+  updateColumn: async (
+    id: string,
+    columnId: string,
+    data: UpdateProjectColumn
+  ): Promise<ProjectColumn> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/columns/${columnId}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ProjectColumn>(response);
+  },
+
+  deleteColumn: async (id: string, columnId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/columns/${columnId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  reorderColumns: async (
+    id: string,
+    data: ReorderProjectColumnsRequest
+  ): Promise<ProjectColumn[]> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/columns/reorder`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ProjectColumn[]>(response);
+  },
+
+  getSetupCache: async (id: string): Promise<SetupCacheEntry[]> => {
+    const response = await makeRequest(`/api/projects/${id}/setup-cache`);
+    return handleApiResponse<SetupCacheEntry[]>(response);
+  },
+
+  clearSetupCache: async (id: string): Promise<number> => {
+    const response = await makeRequest(`/api/projects/${id}/setup-cache`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<number>(response);
+  },
+
+  deleteSetupCacheEntry: async (id: string, key: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/setup-cache/${encodeURIComponent(key)}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
   create: async (data: CreateProject): Promise<Project> => {
     const response = await makeRequest('/api/projects', {
       method: 'POST',
@@ -252,6 +615,13 @@ export const projectsApi = {
     return handleApiResponse<OpenEditorResponse>(response);
   },
 
+  stopProcesses: async (id: string): Promise<StopSummary> => {
+    const response = await makeRequest(`/api/projects/${id}/stop`, {
+      method: 'POST',
+    });
+    return handleApiResponse<StopSummary>(response);
+  },
+
   getBranches: async (id: string): Promise<GitBranch[]> => {
     const response = await makeRequest(`/api/projects/${id}/branches`);
     return handleApiResponse<GitBranch[]>(response);
@@ -270,6 +640,67 @@ export const projectsApi = {
     );
     return handleApiResponse<SearchResult[]>(response);
   },
+
+  getAttemptReport: async (
+    id: string,
+    days?: number,
+    period?: ReportPeriod
+  ): Promise<AttemptReport> => {
+    const params = new URLSearchParams();
+    if (days !== undefined) params.set('days', String(days));
+    if (period) params.set('period', period);
+    const response = await makeRequest(
+      `/api/projects/${id}/attempt-report?${params.toString()}`
+    );
+    return handleApiResponse<AttemptReport>(response);
+  },
+
+  searchExecutionProcesses: async (
+    id: string,
+    filters: {
+      status?: ExecutionProcessStatus;
+      run_reason?: ExecutionProcessRunReason;
+      started_after?: string;
+      started_before?: string;
+      executor?: BaseCodingAgent;
+      exit_code?: number;
+      offset?: number;
+      limit?: number;
+    } = {}
+  ): Promise<ExecutionProcessPage> => {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(filters)) {
+      if (value !== undefined) params.set(key, String(value));
+    }
+    const response = await makeRequest(
+      `/api/projects/${id}/execution_processes?${params.toString()}`
+    );
+    return handleApiResponse<ExecutionProcessPage>(response);
+  },
+
+  getExecutionMetrics: async (
+    id: string,
+    days?: number
+  ): Promise<ExecutionMetricsReport> => {
+    const params = new URLSearchParams();
+    if (days !== undefined) params.set('days', String(days));
+    const response = await makeRequest(
+      `/api/projects/${id}/execution-metrics?${params.toString()}`
+    );
+    return handleApiResponse<ExecutionMetricsReport>(response);
+  },
+
+  getRepoHealth: async (id: string): Promise<RepoHealthReport> => {
+    const response = await makeRequest(`/api/projects/${id}/repo/health`);
+    return handleApiResponse<RepoHealthReport>(response);
+  },
+
+  repairRepo: async (id: string): Promise<RepoRepairReport> => {
+    const response = await makeRequest(`/api/projects/${id}/repo/health`, {
+      method: 'POST',
+    });
+    return handleApiResponse<RepoRepairReport>(response);
+  },
 };
 
 // Task Management APIs
@@ -316,6 +747,110 @@ export const tasksApi = {
     });
     return handleApiResponse<void>(response);
   },
+
+  reorder: async (data: ReorderTasksRequest): Promise<void> => {
+    const response = await makeRequest(`/api/tasks/reorder`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  getWipUsage: async (projectId: string): Promise<WipUsage[]> => {
+    const response = await makeRequest(
+      `/api/tasks/wip?project_id=${encodeURIComponent(projectId)}`
+    );
+    return handleApiResponse<WipUsage[]>(response);
+  },
+
+  stopProcesses: async (taskId: string): Promise<StopSummary> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/stop`, {
+      method: 'POST',
+    });
+    return handleApiResponse<StopSummary>(response);
+  },
+
+  getDependencies: async (taskId: string): Promise<Task[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`);
+    return handleApiResponse<Task[]>(response);
+  },
+
+  addDependency: async (
+    taskId: string,
+    data: AddTaskDependencyRequest
+  ): Promise<Task[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Task[]>(response);
+  },
+
+  removeDependency: async (
+    taskId: string,
+    dependsOnTaskId: string
+  ): Promise<Task[]> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/dependencies/${dependsOnTaskId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<Task[]>(response);
+  },
+
+  getSubtasks: async (taskId: string): Promise<Task[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/subtasks`);
+    return handleApiResponse<Task[]>(response);
+  },
+
+  createSubtask: async (
+    taskId: string,
+    data: CreateSubtaskRequest
+  ): Promise<Task> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/subtasks`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Task>(response);
+  },
+
+  getComments: async (taskId: string): Promise<TaskComment[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/comments`);
+    return handleApiResponse<TaskComment[]>(response);
+  },
+
+  createComment: async (
+    taskId: string,
+    data: CreateTaskComment
+  ): Promise<TaskComment> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/comments`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskComment>(response);
+  },
+
+  updateComment: async (
+    taskId: string,
+    commentId: string,
+    data: UpdateTaskComment
+  ): Promise<TaskComment> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/comments/${commentId}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskComment>(response);
+  },
+
+  deleteComment: async (taskId: string, commentId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/comments/${commentId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
 };
 
 // Task Attempts APIs
@@ -327,14 +862,14 @@ export const attemptsApi = {
     return handleApiResponse<TaskRelationships>(response);
   },
 
-  getAll: async (taskId: string): Promise<TaskAttempt[]> => {
+  getAll: async (taskId: string): Promise<TaskAttemptSummary[]> => {
     const response = await makeRequest(`/api/task-attempts?task_id=${taskId}`);
-    return handleApiResponse<TaskAttempt[]>(response);
+    return handleApiResponse<TaskAttemptSummary[]>(response);
   },
 
-  get: async (attemptId: string): Promise<TaskAttempt> => {
+  get: async (attemptId: string): Promise<TaskAttemptDetails> => {
     const response = await makeRequest(`/api/task-attempts/${attemptId}`);
-    return handleApiResponse<TaskAttempt>(response);
+    return handleApiResponse<TaskAttemptDetails>(response);
   },
 
   create: async (data: CreateTaskAttemptBody): Promise<TaskAttempt> => {
@@ -345,6 +880,72 @@ export const attemptsApi = {
     return handleApiResponse<TaskAttempt>(response);
   },
 
+  bulkRerun: async (data: BulkRerunRequest): Promise<BulkRerunResponse> => {
+    const response = await makeRequest(`/api/task-attempts/bulk-rerun`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<BulkRerunResponse>(response);
+  },
+
+  startComparison: async (
+    data: StartAttemptComparison
+  ): Promise<AttemptComparisonDetails> => {
+    const response = await makeRequest(`/api/task-attempts/comparisons`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<AttemptComparisonDetails>(response);
+  },
+
+  getComparisons: async (taskId: string): Promise<AttemptComparison[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/comparisons?task_id=${encodeURIComponent(taskId)}`
+    );
+    return handleApiResponse<AttemptComparison[]>(response);
+  },
+
+  getComparison: async (
+    comparisonId: string
+  ): Promise<AttemptComparisonDetails> => {
+    const response = await makeRequest(
+      `/api/task-attempts/comparisons/${comparisonId}`
+    );
+    return handleApiResponse<AttemptComparisonDetails>(response);
+  },
+
+  chooseComparedAttempt: async (
+    comparisonId: string,
+    attemptId: string
+  ): Promise<AttemptComparisonDetails> => {
+    const response = await makeRequest(
+      `/api/task-attempts/comparisons/${comparisonId}/choose`,
+      {
+        method: 'POST',
+        body: JSON.stringify({ attempt_id: attemptId }),
+      }
+    );
+    return handleApiResponse<AttemptComparisonDetails>(response);
+  },
+
+  getTestResults: async (attemptId: string): Promise<TestRunResult[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/test-results`
+    );
+    return handleApiResponse<TestRunResult[]>(response);
+  },
+
+  setReview: async (
+    attemptId: string,
+    data: UpdateAttemptReview
+  ): Promise<TaskAttempt> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/review`,
+      { method: 'PUT', body: JSON.stringify(data) }
+    );
+    return handleApiResponse<TaskAttempt>(response);
+  },
+
   stop: async (attemptId: string): Promise<void> => {
     const response = await makeRequest(`/api/task-attempts/${attemptId}/stop`, {
       method: 'POST',
@@ -372,6 +973,28 @@ export const attemptsApi = {
     return handleApiResponse(response);
   },
 
+  getCheckpoints: async (attemptId: string): Promise<Checkpoint[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/checkpoints`
+    );
+    return handleApiResponse<Checkpoint[]>(response);
+  },
+
+  rollbackToCheckpoint: async (
+    attemptId: string,
+    processId: string,
+    data: RollbackToCheckpointRequest
+  ): Promise<RollbackResult> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/checkpoints/${processId}/rollback`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<RollbackResult>(response);
+  },
+
   followUp: async (
     attemptId: string,
     data: CreateFollowUpAttempt
@@ -458,6 +1081,44 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  getFile: async (attemptId: string, path: string): Promise<WorktreeFile> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/file?path=${encodeURIComponent(path)}`
+    );
+    return handleApiResponse<WorktreeFile>(response);
+  },
+
+  writeFile: async (
+    attemptId: string,
+    data: WriteWorktreeFileRequest
+  ): Promise<WriteWorktreeFileResult> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/file`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<WriteWorktreeFileResult>(response);
+  },
+
+  getTerminalSessions: async (attemptId: string): Promise<TerminalSession[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/terminal/sessions`
+    );
+    return handleApiResponse<TerminalSession[]>(response);
+  },
+
+  getTerminalTranscript: async (
+    attemptId: string,
+    sessionId: string
+  ): Promise<string> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/terminal/sessions/${sessionId}/transcript`
+    );
+    return handleApiResponse<string>(response);
+  },
+
   openEditor: async (
     attemptId: string,
     editorType?: EditorType,
@@ -486,13 +1147,48 @@ export const attemptsApi = {
     return handleApiResponse<BranchStatus>(response);
   },
 
-  merge: async (attemptId: string): Promise<void> => {
+  merge: async (
+    attemptId: string,
+    allowProtected: boolean = false
+  ): Promise<void> => {
+    const query = allowProtected ? '?allow_protected=true' : '';
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge${query}`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  getMerges: async (attemptId: string): Promise<Merge[]> => {
     const response = await makeRequest(
-      `/api/task-attempts/${attemptId}/merge`,
+      `/api/task-attempts/${attemptId}/merges`
+    );
+    return handleApiResponse<Merge[]>(response);
+  },
+
+  enqueueMerge: async (
+    attemptId: string,
+    allowProtected: boolean = false
+  ): Promise<MergeQueueEntry> => {
+    const query = allowProtected ? '?allow_protected=true' : '';
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge-queue${query}`,
       {
         method: 'POST',
       }
     );
+    return handleApiResponse<MergeQueueEntry>(response);
+  },
+
+  cancelMerge: async (attemptId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge-queue`,
+      {
+        method: 'DELETE',
+      }
+    );
     return handleApiResponse<void>(response);
   },
 
@@ -517,48 +1213,179 @@ export const attemptsApi = {
     return handleApiResponseAsResult<void, GitOperationError>(response);
   },
 
+  cherryPick: async (
+    attemptId: string,
+    data: CherryPickRequest
+  ): Promise<Result<CherryPickResult, GitOperationError>> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/cherry-pick`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponseAsResult<CherryPickResult, GitOperationError>(
+      response
+    );
+  },
+
+  getExportUrl: (attemptId: string, format: ExportFormat): string => {
+    return `/api/task-attempts/${attemptId}/export?format=${format}`;
+  },
+
+  // Zip with the output of every process the attempt ran
+  getLogsDownloadUrl: (attemptId: string): string => {
+    return `/api/task-attempts/${attemptId}/logs/download`;
+  },
+
+  getDiffFiles: async (
+    attemptId: string,
+    offset: number,
+    limit: number
+  ): Promise<DiffFilePage> => {
+    const params = new URLSearchParams({
+      offset: String(offset),
+      limit: String(limit),
+    });
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/diff/files?${params.toString()}`
+    );
+    return handleApiResponse<DiffFilePage>(response);
+  },
+
+  createShareLink: async (
+    attemptId: string,
+    data: CreateShareLink
+  ): Promise<ShareLink> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/share`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ShareLink>(response);
+  },
+
+  getDiffFile: async (attemptId: string, path: string): Promise<Diff> => {
+    const params = new URLSearchParams({ path });
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/diff/file?${params.toString()}`
+    );
+    return handleApiResponse<Diff>(response);
+  },
+
+  getDependencyDiff: async (
+    attemptId: string
+  ): Promise<LockfileDependencyDiff[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/diff/dependencies`
+    );
+    return handleApiResponse<LockfileDependencyDiff[]>(response);
+  },
+
   change_target_branch: async (
     attemptId: string,
     data: ChangeTargetBranchRequest
   ): Promise<ChangeTargetBranchResponse> => {
     const response = await makeRequest(
-      `/api/task-attempts/${attemptId}/change-target-branch`,
+      `/api/task-attempts/${attemptId}/change-target-branch`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ChangeTargetBranchResponse>(response);
+  },
+
+  abortConflicts: async (attemptId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/conflicts/abort`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  createPR: async (
+    attemptId: string,
+    data: CreateGitHubPrRequest
+  ): Promise<Result<string, GitHubServiceError>> => {
+    const response = await makeRequest(`/api/task-attempts/${attemptId}/pr`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponseAsResult<string, GitHubServiceError>(response);
+  },
+
+  createTag: async (
+    attemptId: string,
+    data: CreateTagRequest
+  ): Promise<Result<CreateTagResponse, GitHubServiceError>> => {
+    const response = await makeRequest(`/api/task-attempts/${attemptId}/tag`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponseAsResult<CreateTagResponse, GitHubServiceError>(
+      response
+    );
+  },
+
+  getDevServer: async (attemptId: string): Promise<DevServerStatus> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/dev-server`
+    );
+    return handleApiResponse<DevServerStatus>(response);
+  },
+
+  startDevServer: async (attemptId: string): Promise<DevServerStatus> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/dev-server/start`,
       {
         method: 'POST',
-        body: JSON.stringify(data),
       }
     );
-    return handleApiResponse<ChangeTargetBranchResponse>(response);
+    return handleApiResponse<DevServerStatus>(response);
   },
 
-  abortConflicts: async (attemptId: string): Promise<void> => {
+  stopDevServer: async (attemptId: string): Promise<DevServerStatus> => {
     const response = await makeRequest(
-      `/api/task-attempts/${attemptId}/conflicts/abort`,
+      `/api/task-attempts/${attemptId}/dev-server/stop`,
       {
         method: 'POST',
       }
     );
-    return handleApiResponse<void>(response);
+    return handleApiResponse<DevServerStatus>(response);
   },
+};
 
-  createPR: async (
-    attemptId: string,
-    data: CreateGitHubPrRequest
-  ): Promise<Result<string, GitHubServiceError>> => {
-    const response = await makeRequest(`/api/task-attempts/${attemptId}/pr`, {
-      method: 'POST',
-      body: JSON.stringify(data),
-    });
-    return handleApiResponseAsResult<string, GitHubServiceError>(response);
+// Read-only access to an attempt through a share link
+export const sharedApi = {
+  getAttempt: async (token: string): Promise<SharedAttempt> => {
+    const response = await makeRequest(`/api/shared/${token}`);
+    return handleApiResponse<SharedAttempt>(response);
+  },
+
+  getDiffFiles: async (token: string): Promise<DiffFilePage> => {
+    const response = await makeRequest(`/api/shared/${token}/diff/files`);
+    return handleApiResponse<DiffFilePage>(response);
   },
 
-  startDevServer: async (attemptId: string): Promise<void> => {
+  getProcessLogs: async (token: string, processId: string): Promise<string> => {
     const response = await makeRequest(
-      `/api/task-attempts/${attemptId}/start-dev-server`,
-      {
-        method: 'POST',
-      }
+      `/api/shared/${token}/processes/${processId}/logs`
     );
+    if (!response.ok) {
+      return handleApiResponse<string>(response);
+    }
+    return response.text();
+  },
+
+  revokeAll: async (): Promise<void> => {
+    const response = await makeRequest('/api/share-links', {
+      method: 'DELETE',
+    });
     return handleApiResponse<void>(response);
   },
 };
@@ -596,11 +1423,11 @@ export const commitsApi = {
 export const executionProcessesApi = {
   getExecutionProcesses: async (
     attemptId: string
-  ): Promise<ExecutionProcess[]> => {
+  ): Promise<ExecutionProcessWithUsage[]> => {
     const response = await makeRequest(
       `/api/execution-processes?task_attempt_id=${attemptId}`
     );
-    return handleApiResponse<ExecutionProcess[]>(response);
+    return handleApiResponse<ExecutionProcessWithUsage[]>(response);
   },
 
   getDetails: async (processId: string): Promise<ExecutionProcess> => {
@@ -608,6 +1435,53 @@ export const executionProcessesApi = {
     return handleApiResponse<ExecutionProcess>(response);
   },
 
+  stopAll: async (): Promise<StopSummary> => {
+    const response = await makeRequest('/api/execution-processes/stop-all', {
+      method: 'POST',
+    });
+    return handleApiResponse<StopSummary>(response);
+  },
+
+  searchLogs: async (
+    query: string,
+    projectId?: string
+  ): Promise<ExecutionProcessLogMatches[]> => {
+    const params = new URLSearchParams({ q: query });
+    if (projectId) params.set('project_id', projectId);
+    const response = await makeRequest(
+      `/api/execution-processes/search?${params.toString()}`
+    );
+    return handleApiResponse<ExecutionProcessLogMatches[]>(response);
+  },
+
+  grepLogs: async (
+    processId: string,
+    pattern: string,
+    options: { regex?: boolean; ignoreCase?: boolean } = {}
+  ): Promise<LogLine[]> => {
+    const params = new URLSearchParams({ pattern });
+    if (options.regex) params.set('regex', 'true');
+    if (options.ignoreCase) params.set('ignore_case', 'true');
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/logs/grep?${params.toString()}`
+    );
+    return handleApiResponse<LogLine[]>(response);
+  },
+
+  getLogsDownloadUrl: (processId: string, stream?: LogStream): string => {
+    const query = stream ? `?stream=${stream}` : '';
+    return `/api/execution-processes/${processId}/logs/download${query}`;
+  },
+
+  getMetrics: async (
+    processId: string
+  ): Promise<ExecutionProcessMetrics | null> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/metrics`
+    );
+    return handleApiResponse<ExecutionProcessMetrics | null>(response);
+  },
+
   stopExecutionProcess: async (processId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/execution-processes/${processId}/stop`,
@@ -617,6 +1491,23 @@ export const executionProcessesApi = {
     );
     return handleApiResponse<void>(response);
   },
+
+  getProcessTree: async (processId: string): Promise<ProcessTreeNode[]> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/processes`
+    );
+    return handleApiResponse<ProcessTreeNode[]>(response);
+  },
+
+  killChildProcess: async (processId: string, pid: number): Promise<void> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/processes/${pid}/kill`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
 };
 
 // File System APIs
@@ -651,6 +1542,86 @@ export const configApi = {
     });
     return handleApiResponse<Config>(response);
   },
+  getEffectiveConfig: async (): Promise<EffectiveConfig> => {
+    const response = await makeRequest('/api/config/effective');
+    return handleApiResponse<EffectiveConfig>(response);
+  },
+  getConfigOverrides: async (): Promise<ConfigOverride[]> => {
+    const response = await makeRequest('/api/config/overrides');
+    return handleApiResponse<ConfigOverride[]>(response);
+  },
+  setConfigOverride: async (
+    data: SetConfigOverride
+  ): Promise<EffectiveConfig> => {
+    const response = await makeRequest('/api/config/overrides', {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<EffectiveConfig>(response);
+  },
+  removeConfigOverride: async (path: string): Promise<EffectiveConfig> => {
+    const response = await makeRequest(
+      `/api/config/overrides/${encodeURIComponent(path)}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<EffectiveConfig>(response);
+  },
+  // Uses the given settings, so they can be checked before saving them
+  sendTestEmail: async (
+    emailConfig: EmailNotificationConfig
+  ): Promise<void> => {
+    const response = await makeRequest('/api/config/email/test', {
+      method: 'POST',
+      body: JSON.stringify(emailConfig),
+    });
+    return handleApiResponse<void>(response);
+  },
+  getStorage: async (): Promise<StorageInfo> => {
+    const response = await makeRequest('/api/admin/storage');
+    return handleApiResponse<StorageInfo>(response);
+  },
+  // Data is moved the next time the server starts
+  relocateStorage: async (
+    relocation: StorageRelocation
+  ): Promise<StorageInfo> => {
+    const response = await makeRequest('/api/admin/storage/relocation', {
+      method: 'POST',
+      body: JSON.stringify(relocation),
+    });
+    return handleApiResponse<StorageInfo>(response);
+  },
+  cancelStorageRelocation: async (): Promise<StorageInfo> => {
+    const response = await makeRequest('/api/admin/storage/relocation', {
+      method: 'DELETE',
+    });
+    return handleApiResponse<StorageInfo>(response);
+  },
+  getRateLimits: async (): Promise<RateLimitSnapshot> => {
+    const response = await makeRequest('/api/admin/rate-limits');
+    return handleApiResponse<RateLimitSnapshot>(response);
+  },
+  // Newest first; unset filters match every entry
+  getAuditLog: async (
+    filter: {
+      action?: AuditAction;
+      actor?: string;
+      project_id?: string;
+      task_id?: string;
+      task_attempt_id?: string;
+      after?: string;
+      before?: string;
+      limit?: number;
+    } = {}
+  ): Promise<AuditLogEntry[]> => {
+    const params = new URLSearchParams();
+    Object.entries(filter).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value));
+    });
+    const response = await makeRequest(
+      `/api/admin/audit-log?${params.toString()}`
+    );
+    return handleApiResponse<AuditLogEntry[]>(response);
+  },
 };
 
 // GitHub Device Auth APIs
@@ -718,6 +1689,337 @@ export const tagsApi = {
     });
     return handleApiResponse<void>(response);
   },
+
+  createTask: async (
+    tagId: string,
+    data: CreateTaskFromTemplate
+  ): Promise<Task> => {
+    const response = await makeRequest(`/api/tags/${tagId}/create-task`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Task>(response);
+  },
+};
+
+// Prompt Snippets APIs
+export const promptSnippetsApi = {
+  list: async (projectId?: string): Promise<PromptSnippet[]> => {
+    const queryParam = projectId
+      ? `?project_id=${encodeURIComponent(projectId)}`
+      : '';
+    const response = await makeRequest(`/api/prompt-snippets${queryParam}`);
+    return handleApiResponse<PromptSnippet[]>(response);
+  },
+
+  create: async (data: CreatePromptSnippet): Promise<PromptSnippet> => {
+    const response = await makeRequest('/api/prompt-snippets', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<PromptSnippet>(response);
+  },
+
+  update: async (
+    snippetId: string,
+    data: UpdatePromptSnippet
+  ): Promise<PromptSnippet> => {
+    const response = await makeRequest(`/api/prompt-snippets/${snippetId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<PromptSnippet>(response);
+  },
+
+  delete: async (snippetId: string): Promise<void> => {
+    const response = await makeRequest(`/api/prompt-snippets/${snippetId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+};
+
+// Workspaces APIs
+export const workspacesApi = {
+  list: async (): Promise<Workspace[]> => {
+    const response = await makeRequest('/api/workspaces');
+    return handleApiResponse<Workspace[]>(response);
+  },
+
+  getById: async (workspaceId: string): Promise<Workspace> => {
+    const response = await makeRequest(`/api/workspaces/${workspaceId}`);
+    return handleApiResponse<Workspace>(response);
+  },
+
+  create: async (data: CreateWorkspace): Promise<Workspace> => {
+    const response = await makeRequest('/api/workspaces', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Workspace>(response);
+  },
+
+  update: async (
+    workspaceId: string,
+    data: UpdateWorkspace
+  ): Promise<Workspace> => {
+    const response = await makeRequest(`/api/workspaces/${workspaceId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Workspace>(response);
+  },
+
+  delete: async (workspaceId: string): Promise<void> => {
+    const response = await makeRequest(`/api/workspaces/${workspaceId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  getTasks: async (workspaceId: string): Promise<TaskWithAttemptStatus[]> => {
+    const response = await makeRequest(`/api/workspaces/${workspaceId}/tasks`);
+    return handleApiResponse<TaskWithAttemptStatus[]>(response);
+  },
+};
+
+// Task Schedules APIs
+export const schedulesApi = {
+  list: async (projectId: string): Promise<TaskSchedule[]> => {
+    const response = await makeRequest(
+      `/api/schedules?project_id=${encodeURIComponent(projectId)}`
+    );
+    return handleApiResponse<TaskSchedule[]>(response);
+  },
+
+  create: async (data: CreateTaskSchedule): Promise<TaskSchedule> => {
+    const response = await makeRequest('/api/schedules', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskSchedule>(response);
+  },
+
+  update: async (
+    scheduleId: string,
+    data: UpdateTaskSchedule
+  ): Promise<TaskSchedule> => {
+    const response = await makeRequest(`/api/schedules/${scheduleId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<TaskSchedule>(response);
+  },
+
+  delete: async (scheduleId: string): Promise<void> => {
+    const response = await makeRequest(`/api/schedules/${scheduleId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  run: async (scheduleId: string): Promise<TaskAttempt> => {
+    const response = await makeRequest(`/api/schedules/${scheduleId}/run`, {
+      method: 'POST',
+    });
+    return handleApiResponse<TaskAttempt>(response);
+  },
+};
+
+// Automation Rules APIs
+export const automationRulesApi = {
+  list: async (projectId: string): Promise<AutomationRule[]> => {
+    const response = await makeRequest(
+      `/api/automation-rules?project_id=${encodeURIComponent(projectId)}`
+    );
+    return handleApiResponse<AutomationRule[]>(response);
+  },
+
+  create: async (data: CreateAutomationRule): Promise<AutomationRule> => {
+    const response = await makeRequest('/api/automation-rules', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<AutomationRule>(response);
+  },
+
+  update: async (
+    ruleId: string,
+    data: UpdateAutomationRule
+  ): Promise<AutomationRule> => {
+    const response = await makeRequest(`/api/automation-rules/${ruleId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<AutomationRule>(response);
+  },
+
+  delete: async (ruleId: string): Promise<void> => {
+    const response = await makeRequest(`/api/automation-rules/${ruleId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  getRuns: async (ruleId: string): Promise<AutomationRuleRun[]> => {
+    const response = await makeRequest(`/api/automation-rules/${ruleId}/runs`);
+    return handleApiResponse<AutomationRuleRun[]>(response);
+  },
+};
+
+// Webhooks APIs
+export const webhooksApi = {
+  /** All webhooks, or only the project's own when a project is given */
+  list: async (projectId?: string): Promise<Webhook[]> => {
+    const query = projectId
+      ? `?project_id=${encodeURIComponent(projectId)}`
+      : '';
+    const response = await makeRequest(`/api/webhooks${query}`);
+    return handleApiResponse<Webhook[]>(response);
+  },
+
+  create: async (data: CreateWebhook): Promise<Webhook> => {
+    const response = await makeRequest('/api/webhooks', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Webhook>(response);
+  },
+
+  update: async (webhookId: string, data: UpdateWebhook): Promise<Webhook> => {
+    const response = await makeRequest(`/api/webhooks/${webhookId}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Webhook>(response);
+  },
+
+  delete: async (webhookId: string): Promise<void> => {
+    const response = await makeRequest(`/api/webhooks/${webhookId}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+
+  getDeliveries: async (webhookId: string): Promise<WebhookDelivery[]> => {
+    const response = await makeRequest(
+      `/api/webhooks/${webhookId}/deliveries`
+    );
+    return handleApiResponse<WebhookDelivery[]>(response);
+  },
+
+  /** Delivery log of all webhooks */
+  getAllDeliveries: async (
+    status?: WebhookDeliveryStatus
+  ): Promise<WebhookDelivery[]> => {
+    const query = status ? `?status=${status}` : '';
+    const response = await makeRequest(`/api/admin/webhook-deliveries${query}`);
+    return handleApiResponse<WebhookDelivery[]>(response);
+  },
+
+  redeliver: async (deliveryId: string): Promise<WebhookDelivery> => {
+    const response = await makeRequest(
+      `/api/admin/webhook-deliveries/${deliveryId}/redeliver`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<WebhookDelivery>(response);
+  },
+};
+
+// Chat Channels APIs
+export const chatChannelsApi = {
+  list: async (projectId: string): Promise<ChatChannel[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/chat-channels`
+    );
+    return handleApiResponse<ChatChannel[]>(response);
+  },
+
+  create: async (
+    projectId: string,
+    data: CreateChatChannel
+  ): Promise<ChatChannel> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/chat-channels`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ChatChannel>(response);
+  },
+
+  update: async (
+    projectId: string,
+    channelId: string,
+    data: UpdateChatChannel
+  ): Promise<ChatChannel> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/chat-channels/${channelId}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ChatChannel>(response);
+  },
+
+  delete: async (projectId: string, channelId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/chat-channels/${channelId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  /** Post a test message to the channel */
+  test: async (projectId: string, channelId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/chat-channels/${channelId}/test`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<void>(response);
+  },
+};
+
+// Notifications APIs
+export const notificationsApi = {
+  /** Latest notifications, newest first */
+  list: async (): Promise<AppNotification[]> => {
+    const response = await makeRequest('/api/notifications');
+    return handleApiResponse<AppNotification[]>(response);
+  },
+
+  /** Call `onNotification` for every new notification until the source is closed */
+  subscribe: (
+    onNotification: (notification: AppNotification) => void
+  ): EventSource => {
+    const source = new EventSource('/api/notifications/stream');
+    source.addEventListener('notification', (event) => {
+      onNotification(JSON.parse((event as MessageEvent).data));
+    });
+    return source;
+  },
+};
+
+// Events APIs
+export const eventsApi = {
+  /**
+   * Patches from the event journal, oldest first. `since` is the id of the last
+   * entry already seen or an RFC 3339 timestamp.
+   */
+  getHistory: async (
+    since?: string,
+    limit?: number
+  ): Promise<EventJournalEntry[]> => {
+    const params = new URLSearchParams();
+    if (since) params.set('since', since);
+    if (limit !== undefined) params.set('limit', String(limit));
+    const response = await makeRequest(
+      `/api/events/history?${params.toString()}`
+    );
+    return handleApiResponse<EventJournalEntry[]>(response);
+  },
 };
 
 // MCP Servers APIs
@@ -770,6 +2072,10 @@ export const profilesApi = {
     });
     return handleApiResponse<string>(response);
   },
+  getVariants: async (): Promise<ExecutorVariants[]> => {
+    const response = await makeRequest('/api/profiles/variants');
+    return handleApiResponse<ExecutorVariants[]>(response);
+  },
 };
 
 // Images API
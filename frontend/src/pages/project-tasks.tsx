@@ -53,6 +53,9 @@ import TaskTableView from '@/components/tasks/TaskTableView';
 import type { TaskWithAttemptStatus } from 'shared/types';
 import type { DragEndEvent } from '@/components/ui/shadcn-io/kanban';
 import { useProjectTasks } from '@/hooks/useProjectTasks';
+import { useProjectColumns } from '@/hooks/useProjectColumns';
+import { useWipLimits } from '@/hooks/useWipLimits';
+import { useTaskSearch } from '@/hooks/useTaskSearch';
 import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert';
 import { useHotkeysContext } from 'react-hotkeys-hook';
 import { TasksLayout, type LayoutMode } from '@/components/layout/TasksLayout';
@@ -75,14 +78,6 @@ import { TaskPanelHeaderActions } from '@/components/panels/TaskPanelHeaderActio
 
 type Task = TaskWithAttemptStatus;
 
-const TASK_STATUSES = [
-  'todo',
-  'inprogress',
-  'inreview',
-  'done',
-  'cancelled',
-] as const;
-
 function DiffsPanelContainer({
   attempt,
   selectedTask,
@@ -158,6 +153,9 @@ export function ProjectTasks() {
   // Tag filtering state
   const [selectedTagIds, setSelectedTagIds] = useState<string[]>([]);
 
+  // Assignee filter: 'all', 'unassigned', or `user:<name>`
+  const [assigneeFilter, setAssigneeFilter] = useState('all');
+
   // View mode state (kanban or table)
   const [viewMode, setViewMode] = useState<'kanban' | 'table'>('kanban');
 
@@ -171,6 +169,22 @@ export function ProjectTasks() {
     error: streamError,
   } = useProjectTasks(projectId || '');
 
+  const { columns, loaded: columnsLoaded } = useProjectColumns(projectId);
+  const wipCounts = useWipLimits(projectId, tasks);
+
+  // Tasks outside any known column show in the first column for their status
+  const columnIdOf = useCallback(
+    (task: Task) => {
+      if (task.column_id && columns.some((c) => c.id === task.column_id)) {
+        return task.column_id;
+      }
+      return (
+        columns.find((column) => column.status === task.status) ?? columns[0]
+      ).id;
+    },
+    [columns]
+  );
+
   const selectedTask = useMemo(
     () => (taskId ? (tasksById[taskId] ?? null) : null),
     [taskId, tasksById]
@@ -312,6 +326,9 @@ export function ProjectTasks() {
     { scope: Scope.KANBAN }
   );
 
+  // Server-side matches also cover comments and tags, and word stems
+  const searchMatchIds = useTaskSearch(projectId, searchQuery);
+
   const filteredTasks = useMemo(() => {
     let result = tasks;
 
@@ -321,7 +338,9 @@ export function ProjectTasks() {
       result = result.filter(
         (task) =>
           task.title.toLowerCase().includes(query) ||
-          (task.description && task.description.toLowerCase().includes(query))
+          (task.description &&
+            task.description.toLowerCase().includes(query)) ||
+          searchMatchIds.has(task.id)
       );
     }
 
@@ -332,32 +351,54 @@ export function ProjectTasks() {
       );
     }
 
+    // Apply assignee filter
+    if (assigneeFilter === 'unassigned') {
+      result = result.filter((task) => !task.assignee);
+    } else if (assigneeFilter.startsWith('user:')) {
+      const assignee = assigneeFilter.slice('user:'.length);
+      result = result.filter((task) => task.assignee === assignee);
+    }
+
     // Apply sort mode
     if (sortMode === 'topological') {
       result = groupedTopologicalSort(result);
     } else if (sortMode === 'reverse-topological') {
       result = reverseTopologicalSort(result);
     }
-    // Note: if sortMode is 'date', tasks are already sorted by creation date from useProjectTasks
+    // Note: if sortMode is 'date', tasks are already in board order from useProjectTasks
 
     return result;
-  }, [tasks, searchQuery, selectedTagIds, sortMode]);
+  }, [
+    tasks,
+    searchQuery,
+    searchMatchIds,
+    selectedTagIds,
+    assigneeFilter,
+    sortMode,
+  ]);
+
+  const assignees = useMemo(
+    () =>
+      Array.from(
+        new Set(
+          tasks
+            .map((task) => task.assignee)
+            .filter((assignee): assignee is string => !!assignee)
+        )
+      ).sort(),
+    [tasks]
+  );
 
   const groupedFilteredTasks = useMemo(() => {
     const groups: Record<string, Task[]> = {};
-    TASK_STATUSES.forEach((status) => {
-      groups[status] = [];
+    columns.forEach((column) => {
+      groups[column.id] = [];
     });
     filteredTasks.forEach((task) => {
-      const normalizedStatus = task.status.toLowerCase();
-      if (groups[normalizedStatus]) {
-        groups[normalizedStatus].push(task);
-      } else {
-        groups['todo'].push(task);
-      }
+      groups[columnIdOf(task)].push(task);
     });
     return groups;
-  }, [filteredTasks]);
+  }, [filteredTasks, columns, columnIdOf]);
 
   useKeyNavUp(
     () => {
@@ -521,7 +562,8 @@ export function ProjectTasks() {
 
   const selectNextTask = useCallback(() => {
     if (selectedTask) {
-      const tasksInStatus = groupedFilteredTasks[selectedTask.status] || [];
+      const tasksInStatus =
+        groupedFilteredTasks[columnIdOf(selectedTask)] || [];
       const currentIndex = tasksInStatus.findIndex(
         (task) => task.id === selectedTask.id
       );
@@ -529,19 +571,26 @@ export function ProjectTasks() {
         handleViewTaskDetails(tasksInStatus[currentIndex + 1]);
       }
     } else {
-      for (const status of TASK_STATUSES) {
-        const tasks = groupedFilteredTasks[status];
+      for (const column of columns) {
+        const tasks = groupedFilteredTasks[column.id];
         if (tasks && tasks.length > 0) {
           handleViewTaskDetails(tasks[0]);
           break;
         }
       }
     }
-  }, [selectedTask, groupedFilteredTasks, handleViewTaskDetails]);
+  }, [
+    selectedTask,
+    groupedFilteredTasks,
+    columns,
+    columnIdOf,
+    handleViewTaskDetails,
+  ]);
 
   const selectPreviousTask = useCallback(() => {
     if (selectedTask) {
-      const tasksInStatus = groupedFilteredTasks[selectedTask.status] || [];
+      const tasksInStatus =
+        groupedFilteredTasks[columnIdOf(selectedTask)] || [];
       const currentIndex = tasksInStatus.findIndex(
         (task) => task.id === selectedTask.id
       );
@@ -549,61 +598,81 @@ export function ProjectTasks() {
         handleViewTaskDetails(tasksInStatus[currentIndex - 1]);
       }
     } else {
-      for (const status of TASK_STATUSES) {
-        const tasks = groupedFilteredTasks[status];
+      for (const column of columns) {
+        const tasks = groupedFilteredTasks[column.id];
         if (tasks && tasks.length > 0) {
           handleViewTaskDetails(tasks[0]);
           break;
         }
       }
     }
-  }, [selectedTask, groupedFilteredTasks, handleViewTaskDetails]);
+  }, [
+    selectedTask,
+    groupedFilteredTasks,
+    columns,
+    columnIdOf,
+    handleViewTaskDetails,
+  ]);
 
   const selectNextColumn = useCallback(() => {
     if (selectedTask) {
-      const currentIndex = TASK_STATUSES.findIndex(
-        (status) => status === selectedTask.status
+      const currentColumnId = columnIdOf(selectedTask);
+      const currentIndex = columns.findIndex(
+        (column) => column.id === currentColumnId
       );
-      for (let i = currentIndex + 1; i < TASK_STATUSES.length; i++) {
-        const tasks = groupedFilteredTasks[TASK_STATUSES[i]];
+      for (let i = currentIndex + 1; i < columns.length; i++) {
+        const tasks = groupedFilteredTasks[columns[i].id];
         if (tasks && tasks.length > 0) {
           handleViewTaskDetails(tasks[0]);
           return;
         }
       }
     } else {
-      for (const status of TASK_STATUSES) {
-        const tasks = groupedFilteredTasks[status];
+      for (const column of columns) {
+        const tasks = groupedFilteredTasks[column.id];
         if (tasks && tasks.length > 0) {
           handleViewTaskDetails(tasks[0]);
           break;
         }
       }
     }
-  }, [selectedTask, groupedFilteredTasks, handleViewTaskDetails]);
+  }, [
+    selectedTask,
+    groupedFilteredTasks,
+    columns,
+    columnIdOf,
+    handleViewTaskDetails,
+  ]);
 
   const selectPreviousColumn = useCallback(() => {
     if (selectedTask) {
-      const currentIndex = TASK_STATUSES.findIndex(
-        (status) => status === selectedTask.status
+      const currentColumnId = columnIdOf(selectedTask);
+      const currentIndex = columns.findIndex(
+        (column) => column.id === currentColumnId
       );
       for (let i = currentIndex - 1; i >= 0; i--) {
-        const tasks = groupedFilteredTasks[TASK_STATUSES[i]];
+        const tasks = groupedFilteredTasks[columns[i].id];
         if (tasks && tasks.length > 0) {
           handleViewTaskDetails(tasks[0]);
           return;
         }
       }
     } else {
-      for (const status of TASK_STATUSES) {
-        const tasks = groupedFilteredTasks[status];
+      for (const column of columns) {
+        const tasks = groupedFilteredTasks[column.id];
         if (tasks && tasks.length > 0) {
           handleViewTaskDetails(tasks[0]);
           break;
         }
       }
     }
-  }, [selectedTask, groupedFilteredTasks, handleViewTaskDetails]);
+  }, [
+    selectedTask,
+    groupedFilteredTasks,
+    columns,
+    columnIdOf,
+    handleViewTaskDetails,
+  ]);
 
   const handleDragEnd = useCallback(
     async (event: DragEndEvent) => {
@@ -611,24 +680,39 @@ export function ProjectTasks() {
       if (!over || !active.data.current) return;
 
       const draggedTaskId = active.id as string;
-      const newStatus = over.id as Task['status'];
+      const newColumn = columns.find((column) => column.id === over.id);
       const task = tasksById[draggedTaskId];
-      if (!task || task.status === newStatus) return;
+      if (!task || !newColumn || columnIdOf(task) === newColumn.id) return;
 
       try {
         await tasksApi.update(draggedTaskId, {
           title: task.title,
           description: task.description,
-          status: newStatus,
+          status: newColumn.status,
           parent_task_attempt: task.parent_task_attempt,
+          column_id: columnsLoaded ? newColumn.id : undefined,
           image_ids: null,
           tag_ids: null,
         });
+        // Dropped tasks go to the top of their new column
+        await tasksApi.reorder({
+          project_id: task.project_id,
+          task_ids: [
+            draggedTaskId,
+            ...tasks
+              .filter(
+                (other) =>
+                  columnIdOf(other) === newColumn.id &&
+                  other.id !== draggedTaskId
+              )
+              .map((other) => other.id),
+          ],
+        });
       } catch (err) {
         console.error('Failed to update task status:', err);
       }
     },
-    [tasksById]
+    [tasks, tasksById, columns, columnsLoaded, columnIdOf]
   );
 
   const isInitialTasksLoad = isLoading && tasks.length === 0;
@@ -686,6 +770,20 @@ export function ProjectTasks() {
             onTagsChange={setSelectedTagIds}
           />
           <div className="flex items-center gap-3">
+            <Select value={assigneeFilter} onValueChange={setAssigneeFilter}>
+              <SelectTrigger className="h-8 w-[160px] text-xs">
+                <SelectValue placeholder="Assignee..." />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="all">👥 Everyone</SelectItem>
+                <SelectItem value="unassigned">Unassigned</SelectItem>
+                {assignees.map((assignee) => (
+                  <SelectItem key={assignee} value={`user:${assignee}`}>
+                    {assignee}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
             <Select
               value={sortMode}
               onValueChange={(value) => setSortMode(value as 'date' | 'topological' | 'reverse-topological')}
@@ -751,7 +849,9 @@ export function ProjectTasks() {
             </div>
           ) : viewMode === 'kanban' ? (
             <TaskKanbanBoard
+              columns={columns}
               groupedTasks={groupedFilteredTasks}
+              wipCounts={wipCounts}
               onDragEnd={handleDragEnd}
               onViewTaskDetails={handleViewTaskDetails}
               selectedTask={selectedTask || undefined}
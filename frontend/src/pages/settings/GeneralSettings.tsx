@@ -29,8 +29,11 @@ import { Checkbox } from '@/components/ui/checkbox';
 import { ChevronDown, Key, Loader2, Volume2 } from 'lucide-react';
 import {
   BaseCodingAgent,
+  CloneFilter,
   EditorType,
   ExecutorProfileId,
+  ResourceLimits,
+  ResourceLimitsConfig,
   SoundFile,
   ThemeMode,
   UiLanguage,
@@ -43,6 +46,12 @@ import { useUserSystem } from '@/components/config-provider';
 import { TagManager } from '@/components/TagManager';
 import NiceModal from '@ebay/nice-modal-react';
 
+const RESOURCE_LIMIT_KINDS: (keyof ResourceLimitsConfig)[] = [
+  'coding_agent',
+  'scripts',
+  'dev_server',
+];
+
 export function GeneralSettings() {
   const { t } = useTranslation(['settings', 'common']);
 
@@ -428,6 +437,175 @@ export function GeneralSettings() {
               {t('settings.general.taskExecution.executor.helper')}
             </p>
           </div>
+
+          <div className="space-y-2">
+            <Label>{t('settings.general.taskExecution.limits.label')}</Label>
+            {RESOURCE_LIMIT_KINDS.map((kind) => {
+              const limits = draft?.resource_limits?.[kind];
+              const updateLimits = (patch: Partial<ResourceLimits>) =>
+                updateDraft({
+                  resource_limits: {
+                    ...draft!.resource_limits,
+                    [kind]: { ...draft!.resource_limits[kind], ...patch },
+                  },
+                });
+              return (
+                <div key={kind} className="grid grid-cols-4 items-center gap-2">
+                  <span className="text-sm">
+                    {t(`settings.general.taskExecution.limits.kinds.${kind}`)}
+                  </span>
+                  <Input
+                    type="number"
+                    min={1}
+                    aria-label={t(
+                      'settings.general.taskExecution.limits.timeout'
+                    )}
+                    placeholder={t(
+                      'settings.general.taskExecution.limits.timeout'
+                    )}
+                    value={
+                      limits?.timeout_secs ? limits.timeout_secs / 60 : ''
+                    }
+                    onChange={(e) => {
+                      const minutes = parseInt(e.target.value, 10);
+                      updateLimits({
+                        timeout_secs: minutes > 0 ? minutes * 60 : null,
+                      });
+                    }}
+                  />
+                  <Input
+                    type="number"
+                    min={1}
+                    aria-label={t(
+                      'settings.general.taskExecution.limits.memory'
+                    )}
+                    placeholder={t(
+                      'settings.general.taskExecution.limits.memory'
+                    )}
+                    value={limits?.memory_max_mb ?? ''}
+                    onChange={(e) => {
+                      const mb = parseInt(e.target.value, 10);
+                      updateLimits({ memory_max_mb: mb > 0 ? mb : null });
+                    }}
+                  />
+                  <Input
+                    type="number"
+                    min={-20}
+                    max={19}
+                    aria-label={t('settings.general.taskExecution.limits.nice')}
+                    placeholder={t(
+                      'settings.general.taskExecution.limits.nice'
+                    )}
+                    value={limits?.nice ?? ''}
+                    onChange={(e) => {
+                      const nice = parseInt(e.target.value, 10);
+                      updateLimits({
+                        nice: Number.isNaN(nice)
+                          ? null
+                          : Math.min(19, Math.max(-20, nice)),
+                      });
+                    }}
+                  />
+                </div>
+              );
+            })}
+            <p className="text-sm text-muted-foreground">
+              {t('settings.general.taskExecution.limits.helper')}
+            </p>
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="max-concurrent-coding-agents">
+              {t('settings.general.taskExecution.concurrency.label')}
+            </Label>
+            <Input
+              id="max-concurrent-coding-agents"
+              type="number"
+              min={1}
+              placeholder={t(
+                'settings.general.taskExecution.concurrency.placeholder'
+              )}
+              value={draft?.resource_limits?.max_concurrent_coding_agents ?? ''}
+              onChange={(e) => {
+                const max = parseInt(e.target.value, 10);
+                updateDraft({
+                  resource_limits: {
+                    ...draft!.resource_limits,
+                    max_concurrent_coding_agents: max > 0 ? max : null,
+                  },
+                });
+              }}
+            />
+            <p className="text-sm text-muted-foreground">
+              {t('settings.general.taskExecution.concurrency.helper')}
+            </p>
+          </div>
+
+          <div className="flex items-center space-x-2">
+            <Checkbox
+              id="dev-server-restart"
+              checked={draft?.dev_server.restart_on_crash}
+              onCheckedChange={(checked: boolean) =>
+                updateDraft({
+                  dev_server: {
+                    ...draft!.dev_server,
+                    restart_on_crash: checked,
+                  },
+                })
+              }
+            />
+            <div className="space-y-0.5">
+              <Label htmlFor="dev-server-restart" className="cursor-pointer">
+                {t('settings.general.taskExecution.devServer.restartLabel')}
+              </Label>
+              <p className="text-sm text-muted-foreground">
+                {t('settings.general.taskExecution.devServer.restartHelper')}
+              </p>
+            </div>
+          </div>
+          {draft?.dev_server.restart_on_crash && (
+            <div className="space-y-2">
+              <Label htmlFor="dev-server-max-restarts">
+                {t('settings.general.taskExecution.devServer.maxRestarts')}
+              </Label>
+              <Input
+                id="dev-server-max-restarts"
+                type="number"
+                min={1}
+                value={draft.dev_server.max_restarts}
+                onChange={(e) => {
+                  const max = parseInt(e.target.value, 10);
+                  if (max > 0) {
+                    updateDraft({
+                      dev_server: { ...draft!.dev_server, max_restarts: max },
+                    });
+                  }
+                }}
+              />
+            </div>
+          )}
+          <div className="flex items-center space-x-2">
+            <Checkbox
+              id="dev-server-resume"
+              checked={draft?.dev_server.resume_after_restart}
+              onCheckedChange={(checked: boolean) =>
+                updateDraft({
+                  dev_server: {
+                    ...draft!.dev_server,
+                    resume_after_restart: checked,
+                  },
+                })
+              }
+            />
+            <div className="space-y-0.5">
+              <Label htmlFor="dev-server-resume" className="cursor-pointer">
+                {t('settings.general.taskExecution.devServer.resumeLabel')}
+              </Label>
+              <p className="text-sm text-muted-foreground">
+                {t('settings.general.taskExecution.devServer.resumeHelper')}
+              </p>
+            </div>
+          </div>
         </CardContent>
       </Card>
 
@@ -684,6 +862,59 @@ export function GeneralSettings() {
               )}
             </p>
           </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="git-clone-filter">
+              {t('settings.general.git.clone.filter.label')}
+            </Label>
+            <Select
+              value={draft?.git_clone?.filter ?? CloneFilter.NONE}
+              onValueChange={(value: CloneFilter) =>
+                updateDraft({
+                  git_clone: { ...draft!.git_clone, filter: value },
+                })
+              }
+            >
+              <SelectTrigger id="git-clone-filter">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {Object.values(CloneFilter).map((filter) => (
+                  <SelectItem key={filter} value={filter}>
+                    {t(`settings.general.git.clone.filter.options.${filter}`)}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+            <p className="text-sm text-muted-foreground">
+              {t('settings.general.git.clone.filter.helper')}
+            </p>
+          </div>
+
+          <div className="space-y-2">
+            <Label htmlFor="git-clone-depth">
+              {t('settings.general.git.clone.depth.label')}
+            </Label>
+            <Input
+              id="git-clone-depth"
+              type="number"
+              min={1}
+              placeholder={t('settings.general.git.clone.depth.placeholder')}
+              value={draft?.git_clone?.depth ?? ''}
+              onChange={(e) => {
+                const depth = parseInt(e.target.value, 10);
+                updateDraft({
+                  git_clone: {
+                    ...draft!.git_clone,
+                    depth: depth > 0 ? depth : null,
+                  },
+                });
+              }}
+            />
+            <p className="text-sm text-muted-foreground">
+              {t('settings.general.git.clone.depth.helper')}
+            </p>
+          </div>
         </CardContent>
       </Card>
 
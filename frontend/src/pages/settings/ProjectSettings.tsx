@@ -18,6 +18,7 @@ import {
   SelectValue,
 } from '@/components/ui/select';
 import { Label } from '@/components/ui/label';
+import { Checkbox } from '@/components/ui/checkbox';
 import { Input } from '@/components/ui/input';
 import { Alert, AlertDescription } from '@/components/ui/alert';
 import { Loader2, Folder } from 'lucide-react';
@@ -27,7 +28,18 @@ import { useScriptPlaceholders } from '@/hooks/useScriptPlaceholders';
 import { CopyFilesField } from '@/components/projects/copy-files-field';
 import { AutoExpandingTextarea } from '@/components/ui/auto-expanding-textarea';
 import { showFolderPicker } from '@/lib/modals';
-import type { Project, UpdateProject } from 'shared/types';
+import { getLanguageOptions, uiLanguageToI18nCode } from '@/i18n/languages';
+import { EgressMode } from 'shared/types';
+import type {
+  CleanupFailureConfig,
+  Project,
+  QuietHours,
+  RetryPolicy,
+  SandboxConfig,
+  TestReportFormat,
+  TestRunConfig,
+  UpdateProject,
+} from 'shared/types';
 
 interface ProjectFormState {
   name: string;
@@ -35,9 +47,44 @@ interface ProjectFormState {
   setup_script: string;
   dev_script: string;
   cleanup_script: string;
+  verify_script: string;
+  post_merge_script: string;
+  cleanup_failure_enabled: boolean;
+  cleanup_failure_type: 'script' | 'follow_up';
+  cleanup_failure_value: string;
+  test_run_enabled: boolean;
+  test_run_command: string;
+  test_run_format: TestReportFormat;
+  test_run_report_path: string;
   copy_files: string;
+  push_remote: string;
+  base_remote: string;
+  protected_branches: string;
+  timezone: string;
+  locale: string;
+  quiet_hours_enabled: boolean;
+  quiet_hours_start: string;
+  quiet_hours_end: string;
+  quiet_hours_weekends: boolean;
+  retry_enabled: boolean;
+  retry_max_retries: string;
+  retry_initial_backoff_secs: string;
+  retry_max_backoff_secs: string;
+  block_merge_on_vulnerabilities: boolean;
+  sync_base_branch: boolean;
+  auto_rebase_on_sync: boolean;
+  sandbox_enabled: boolean;
+  sandbox_writable_paths: string;
+  sandbox_memory_max_mb: string;
+  sandbox_cpu_max_percent: string;
+  sandbox_pids_max: string;
+  sandbox_egress: EgressMode;
+  sandbox_allowed_hosts: string;
 }
 
+// Select value for projects that use the configured language
+const DEFAULT_LOCALE = 'default';
+
 function projectToFormState(project: Project): ProjectFormState {
   return {
     name: project.name,
@@ -45,7 +92,116 @@ function projectToFormState(project: Project): ProjectFormState {
     setup_script: project.setup_script ?? '',
     dev_script: project.dev_script ?? '',
     cleanup_script: project.cleanup_script ?? '',
+    verify_script: project.verify_script ?? '',
+    post_merge_script: project.post_merge_script ?? '',
+    cleanup_failure_enabled: project.on_cleanup_failure?.enabled ?? false,
+    cleanup_failure_type: project.on_cleanup_failure?.action.type ?? 'script',
+    cleanup_failure_value: failureActionText(project.on_cleanup_failure),
+    test_run_enabled: project.test_run?.enabled ?? false,
+    test_run_command: project.test_run?.command ?? '',
+    test_run_format: project.test_run?.format ?? 'cargo',
+    test_run_report_path: project.test_run?.report_path ?? '',
     copy_files: project.copy_files ?? '',
+    push_remote: project.push_remote ?? '',
+    base_remote: project.base_remote ?? '',
+    protected_branches: project.protected_branches ?? '',
+    timezone: project.timezone ?? '',
+    locale: project.locale ?? '',
+    quiet_hours_enabled: project.quiet_hours?.enabled ?? false,
+    quiet_hours_start: project.quiet_hours?.start ?? '22:00',
+    quiet_hours_end: project.quiet_hours?.end ?? '08:00',
+    quiet_hours_weekends: project.quiet_hours?.weekends ?? false,
+    retry_enabled: project.retry_policy?.enabled ?? false,
+    retry_max_retries: (project.retry_policy?.max_retries ?? 3).toString(),
+    retry_initial_backoff_secs: (
+      project.retry_policy?.initial_backoff_secs ?? 30
+    ).toString(),
+    retry_max_backoff_secs: (
+      project.retry_policy?.max_backoff_secs ?? 600
+    ).toString(),
+    block_merge_on_vulnerabilities: project.block_merge_on_vulnerabilities,
+    sync_base_branch: project.sync_base_branch,
+    auto_rebase_on_sync: project.auto_rebase_on_sync,
+    sandbox_enabled: project.sandbox?.enabled ?? false,
+    sandbox_writable_paths: project.sandbox?.writable_paths.join(', ') ?? '',
+    sandbox_memory_max_mb: project.sandbox?.memory_max_mb?.toString() ?? '',
+    sandbox_cpu_max_percent: project.sandbox?.cpu_max_percent?.toString() ?? '',
+    sandbox_pids_max: project.sandbox?.pids_max?.toString() ?? '',
+    sandbox_egress: project.sandbox?.egress ?? EgressMode.ALLOW,
+    sandbox_allowed_hosts: project.sandbox?.allowed_hosts.join(', ') ?? '',
+  };
+}
+
+function parseLimit(value: string): number | null {
+  const parsed = parseInt(value.trim(), 10);
+  return Number.isFinite(parsed) && parsed > 0 ? parsed : null;
+}
+
+function parseList(value: string): string[] {
+  return value
+    .split(',')
+    .map((item) => item.trim())
+    .filter(Boolean);
+}
+
+function formStateToSandbox(draft: ProjectFormState): SandboxConfig {
+  return {
+    enabled: draft.sandbox_enabled,
+    writable_paths: parseList(draft.sandbox_writable_paths),
+    memory_max_mb: parseLimit(draft.sandbox_memory_max_mb),
+    cpu_max_percent: parseLimit(draft.sandbox_cpu_max_percent),
+    pids_max: parseLimit(draft.sandbox_pids_max),
+    egress: draft.sandbox_egress,
+    allowed_hosts: parseList(draft.sandbox_allowed_hosts),
+  };
+}
+
+function formStateToQuietHours(draft: ProjectFormState): QuietHours {
+  return {
+    enabled: draft.quiet_hours_enabled,
+    start: draft.quiet_hours_start,
+    end: draft.quiet_hours_end,
+    weekends: draft.quiet_hours_weekends,
+  };
+}
+
+function formStateToRetryPolicy(draft: ProjectFormState): RetryPolicy {
+  return {
+    enabled: draft.retry_enabled,
+    max_retries: parseLimit(draft.retry_max_retries) ?? 0,
+    initial_backoff_secs: parseLimit(draft.retry_initial_backoff_secs) ?? 0,
+    max_backoff_secs: parseLimit(draft.retry_max_backoff_secs) ?? 0,
+  };
+}
+
+function failureActionText(config: CleanupFailureConfig | null): string {
+  if (!config) return '';
+  const { action } = config;
+  return action.type === 'follow_up' ? action.prompt : action.script;
+}
+
+function formStateToCleanupFailure(
+  draft: ProjectFormState
+): CleanupFailureConfig {
+  const value = draft.cleanup_failure_value.trim();
+  return {
+    enabled: draft.cleanup_failure_enabled,
+    action:
+      draft.cleanup_failure_type === 'follow_up'
+        ? { type: 'follow_up', prompt: value }
+        : { type: 'script', script: value },
+  };
+}
+
+function formStateToTestRun(draft: ProjectFormState): TestRunConfig {
+  return {
+    enabled: draft.test_run_enabled,
+    command: draft.test_run_command.trim(),
+    format: draft.test_run_format,
+    report_path:
+      draft.test_run_format === 'junit'
+        ? draft.test_run_report_path.trim() || null
+        : null,
   };
 }
 
@@ -53,6 +209,12 @@ export function ProjectSettings() {
   const [searchParams, setSearchParams] = useSearchParams();
   const projectIdParam = searchParams.get('projectId') ?? '';
   const { t } = useTranslation('settings');
+  const localeOptions = getLanguageOptions(
+    t('settings.projects.general.locale.default')
+  ).map((option) => ({
+    value: uiLanguageToI18nCode(option.value) ?? DEFAULT_LOCALE,
+    label: option.label,
+  }));
 
   // Fetch all projects
   const {
@@ -213,7 +375,22 @@ export function ProjectSettings() {
         setup_script: draft.setup_script.trim() || null,
         dev_script: draft.dev_script.trim() || null,
         cleanup_script: draft.cleanup_script.trim() || null,
+        verify_script: draft.verify_script.trim() || null,
+        post_merge_script: draft.post_merge_script.trim() || null,
+        on_cleanup_failure: formStateToCleanupFailure(draft),
+        test_run: formStateToTestRun(draft),
         copy_files: draft.copy_files.trim() || null,
+        push_remote: draft.push_remote.trim() || null,
+        base_remote: draft.base_remote.trim() || null,
+        protected_branches: draft.protected_branches.trim() || null,
+        timezone: draft.timezone.trim() || null,
+        locale: draft.locale || null,
+        quiet_hours: formStateToQuietHours(draft),
+        retry_policy: formStateToRetryPolicy(draft),
+        block_merge_on_vulnerabilities: draft.block_merge_on_vulnerabilities,
+        sync_base_branch: draft.sync_base_branch,
+        auto_rebase_on_sync: draft.auto_rebase_on_sync,
+        sandbox: formStateToSandbox(draft),
       };
 
       updateProject.mutate({
@@ -386,6 +563,248 @@ export function ProjectSettings() {
                   {t('settings.projects.general.repoPath.helper')}
                 </p>
               </div>
+
+              <div className="grid grid-cols-1 sm:grid-cols-2 gap-4">
+                <div className="space-y-2">
+                  <Label htmlFor="push-remote">
+                    {t('settings.projects.general.pushRemote.label')}
+                  </Label>
+                  <Input
+                    id="push-remote"
+                    type="text"
+                    value={draft.push_remote}
+                    onChange={(e) =>
+                      updateDraft({ push_remote: e.target.value })
+                    }
+                    placeholder="origin"
+                  />
+                  <p className="text-sm text-muted-foreground">
+                    {t('settings.projects.general.pushRemote.helper')}
+                  </p>
+                </div>
+                <div className="space-y-2">
+                  <Label htmlFor="base-remote">
+                    {t('settings.projects.general.baseRemote.label')}
+                  </Label>
+                  <Input
+                    id="base-remote"
+                    type="text"
+                    value={draft.base_remote}
+                    onChange={(e) =>
+                      updateDraft({ base_remote: e.target.value })
+                    }
+                    placeholder="upstream"
+                  />
+                  <p className="text-sm text-muted-foreground">
+                    {t('settings.projects.general.baseRemote.helper')}
+                  </p>
+                </div>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="protected-branches">
+                  {t('settings.projects.general.protectedBranches.label')}
+                </Label>
+                <Input
+                  id="protected-branches"
+                  type="text"
+                  value={draft.protected_branches}
+                  onChange={(e) =>
+                    updateDraft({ protected_branches: e.target.value })
+                  }
+                  placeholder="main, release/*"
+                />
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.projects.general.protectedBranches.helper')}
+                </p>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="timezone">
+                  {t('settings.projects.general.timezone.label')}
+                </Label>
+                <Input
+                  id="timezone"
+                  type="text"
+                  value={draft.timezone}
+                  onChange={(e) => updateDraft({ timezone: e.target.value })}
+                  placeholder={Intl.DateTimeFormat().resolvedOptions().timeZone}
+                />
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.projects.general.timezone.helper')}
+                </p>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="project-locale">
+                  {t('settings.projects.general.locale.label')}
+                </Label>
+                <Select
+                  value={draft.locale || DEFAULT_LOCALE}
+                  onValueChange={(value) =>
+                    updateDraft({
+                      locale: value === DEFAULT_LOCALE ? '' : value,
+                    })
+                  }
+                >
+                  <SelectTrigger id="project-locale">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {localeOptions.map((option) => (
+                      <SelectItem key={option.value} value={option.value}>
+                        {option.label}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.projects.general.locale.helper')}
+                </p>
+              </div>
+
+              <div className="space-y-4">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="quiet-hours-enabled"
+                    checked={draft.quiet_hours_enabled}
+                    onCheckedChange={(checked: boolean) =>
+                      updateDraft({ quiet_hours_enabled: checked })
+                    }
+                  />
+                  <div className="space-y-0.5">
+                    <Label
+                      htmlFor="quiet-hours-enabled"
+                      className="cursor-pointer"
+                    >
+                      {t('settings.projects.general.quietHours.label')}
+                    </Label>
+                    <p className="text-sm text-muted-foreground">
+                      {t('settings.projects.general.quietHours.helper')}
+                    </p>
+                  </div>
+                </div>
+
+                {draft.quiet_hours_enabled && (
+                  <div className="space-y-4 pl-6">
+                    <div className="grid grid-cols-2 gap-4">
+                      <div className="space-y-2">
+                        <Label htmlFor="quiet-hours-start">
+                          {t('settings.projects.general.quietHours.start')}
+                        </Label>
+                        <Input
+                          id="quiet-hours-start"
+                          type="time"
+                          value={draft.quiet_hours_start}
+                          onChange={(e) =>
+                            updateDraft({ quiet_hours_start: e.target.value })
+                          }
+                        />
+                      </div>
+                      <div className="space-y-2">
+                        <Label htmlFor="quiet-hours-end">
+                          {t('settings.projects.general.quietHours.end')}
+                        </Label>
+                        <Input
+                          id="quiet-hours-end"
+                          type="time"
+                          value={draft.quiet_hours_end}
+                          onChange={(e) =>
+                            updateDraft({ quiet_hours_end: e.target.value })
+                          }
+                        />
+                      </div>
+                    </div>
+                    <div className="flex items-center space-x-2">
+                      <Checkbox
+                        id="quiet-hours-weekends"
+                        checked={draft.quiet_hours_weekends}
+                        onCheckedChange={(checked: boolean) =>
+                          updateDraft({ quiet_hours_weekends: checked })
+                        }
+                      />
+                      <Label
+                        htmlFor="quiet-hours-weekends"
+                        className="cursor-pointer"
+                      >
+                        {t('settings.projects.general.quietHours.weekends')}
+                      </Label>
+                    </div>
+                  </div>
+                )}
+              </div>
+
+              <div className="space-y-4">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="retry-enabled"
+                    checked={draft.retry_enabled}
+                    onCheckedChange={(checked: boolean) =>
+                      updateDraft({ retry_enabled: checked })
+                    }
+                  />
+                  <div className="space-y-0.5">
+                    <Label htmlFor="retry-enabled" className="cursor-pointer">
+                      {t('settings.projects.general.retryPolicy.label')}
+                    </Label>
+                    <p className="text-sm text-muted-foreground">
+                      {t('settings.projects.general.retryPolicy.helper')}
+                    </p>
+                  </div>
+                </div>
+
+                {draft.retry_enabled && (
+                  <div className="grid grid-cols-3 gap-4 pl-6">
+                    <div className="space-y-2">
+                      <Label htmlFor="retry-max-retries">
+                        {t('settings.projects.general.retryPolicy.maxRetries')}
+                      </Label>
+                      <Input
+                        id="retry-max-retries"
+                        type="number"
+                        min={1}
+                        max={10}
+                        value={draft.retry_max_retries}
+                        onChange={(e) =>
+                          updateDraft({ retry_max_retries: e.target.value })
+                        }
+                      />
+                    </div>
+                    <div className="space-y-2">
+                      <Label htmlFor="retry-initial-backoff">
+                        {t(
+                          'settings.projects.general.retryPolicy.initialBackoff'
+                        )}
+                      </Label>
+                      <Input
+                        id="retry-initial-backoff"
+                        type="number"
+                        min={1}
+                        value={draft.retry_initial_backoff_secs}
+                        onChange={(e) =>
+                          updateDraft({
+                            retry_initial_backoff_secs: e.target.value,
+                          })
+                        }
+                      />
+                    </div>
+                    <div className="space-y-2">
+                      <Label htmlFor="retry-max-backoff">
+                        {t('settings.projects.general.retryPolicy.maxBackoff')}
+                      </Label>
+                      <Input
+                        id="retry-max-backoff"
+                        type="number"
+                        min={1}
+                        value={draft.retry_max_backoff_secs}
+                        onChange={(e) =>
+                          updateDraft({ retry_max_backoff_secs: e.target.value })
+                        }
+                      />
+                    </div>
+                  </div>
+                )}
+              </div>
             </CardContent>
           </Card>
 
@@ -452,6 +871,191 @@ export function ProjectSettings() {
                 </p>
               </div>
 
+              <div className="space-y-2">
+                <Label htmlFor="verify-script">
+                  {t('settings.projects.scripts.verify.label')}
+                </Label>
+                <AutoExpandingTextarea
+                  id="verify-script"
+                  value={draft.verify_script}
+                  onChange={(e) =>
+                    updateDraft({ verify_script: e.target.value })
+                  }
+                  placeholder={placeholders.verify}
+                  maxRows={12}
+                  className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                />
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.projects.scripts.verify.helper')}
+                </p>
+              </div>
+
+              <div className="space-y-2">
+                <Label htmlFor="post-merge-script">
+                  {t('settings.projects.scripts.postMerge.label')}
+                </Label>
+                <AutoExpandingTextarea
+                  id="post-merge-script"
+                  value={draft.post_merge_script}
+                  onChange={(e) =>
+                    updateDraft({ post_merge_script: e.target.value })
+                  }
+                  placeholder={placeholders.postMerge}
+                  maxRows={12}
+                  className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                />
+                <p className="text-sm text-muted-foreground">
+                  {t('settings.projects.scripts.postMerge.helper')}
+                </p>
+              </div>
+
+              <div className="space-y-4">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="cleanup-failure-enabled"
+                    checked={draft.cleanup_failure_enabled}
+                    onCheckedChange={(checked: boolean) =>
+                      updateDraft({ cleanup_failure_enabled: checked })
+                    }
+                  />
+                  <div className="space-y-0.5">
+                    <Label
+                      htmlFor="cleanup-failure-enabled"
+                      className="cursor-pointer"
+                    >
+                      {t('settings.projects.scripts.onCleanupFailure.label')}
+                    </Label>
+                    <p className="text-sm text-muted-foreground">
+                      {t('settings.projects.scripts.onCleanupFailure.helper')}
+                    </p>
+                  </div>
+                </div>
+
+                {draft.cleanup_failure_enabled && (
+                  <div className="space-y-2 pl-6">
+                    <Select
+                      value={draft.cleanup_failure_type}
+                      onValueChange={(value: 'script' | 'follow_up') =>
+                        updateDraft({ cleanup_failure_type: value })
+                      }
+                    >
+                      <SelectTrigger className="w-64">
+                        <SelectValue />
+                      </SelectTrigger>
+                      <SelectContent>
+                        <SelectItem value="script">
+                          {t(
+                            'settings.projects.scripts.onCleanupFailure.script'
+                          )}
+                        </SelectItem>
+                        <SelectItem value="follow_up">
+                          {t(
+                            'settings.projects.scripts.onCleanupFailure.followUp'
+                          )}
+                        </SelectItem>
+                      </SelectContent>
+                    </Select>
+                    <AutoExpandingTextarea
+                      id="cleanup-failure-value"
+                      value={draft.cleanup_failure_value}
+                      onChange={(e) =>
+                        updateDraft({ cleanup_failure_value: e.target.value })
+                      }
+                      placeholder={
+                        draft.cleanup_failure_type === 'follow_up'
+                          ? t(
+                              'settings.projects.scripts.onCleanupFailure.promptPlaceholder'
+                            )
+                          : placeholders.diagnostic
+                      }
+                      maxRows={12}
+                      className={`w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring ${draft.cleanup_failure_type === 'script' ? 'font-mono' : ''}`}
+                    />
+                  </div>
+                )}
+              </div>
+
+              <div className="space-y-4">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="test-run-enabled"
+                    checked={draft.test_run_enabled}
+                    onCheckedChange={(checked: boolean) =>
+                      updateDraft({ test_run_enabled: checked })
+                    }
+                  />
+                  <div className="space-y-0.5">
+                    <Label
+                      htmlFor="test-run-enabled"
+                      className="cursor-pointer"
+                    >
+                      {t('settings.projects.scripts.testRun.label')}
+                    </Label>
+                    <p className="text-sm text-muted-foreground">
+                      {t('settings.projects.scripts.testRun.helper')}
+                    </p>
+                  </div>
+                </div>
+
+                {draft.test_run_enabled && (
+                  <div className="space-y-2 pl-6">
+                    <AutoExpandingTextarea
+                      id="test-run-command"
+                      value={draft.test_run_command}
+                      onChange={(e) =>
+                        updateDraft({ test_run_command: e.target.value })
+                      }
+                      placeholder={t(
+                        'settings.projects.scripts.testRun.commandPlaceholder'
+                      )}
+                      maxRows={12}
+                      className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                    />
+                    <Select
+                      value={draft.test_run_format}
+                      onValueChange={(value: TestReportFormat) =>
+                        updateDraft({ test_run_format: value })
+                      }
+                    >
+                      <SelectTrigger className="w-64">
+                        <SelectValue />
+                      </SelectTrigger>
+                      <SelectContent>
+                        <SelectItem value="cargo">
+                          {t('settings.projects.scripts.testRun.formats.cargo')}
+                        </SelectItem>
+                        <SelectItem value="jest">
+                          {t('settings.projects.scripts.testRun.formats.jest')}
+                        </SelectItem>
+                        <SelectItem value="junit">
+                          {t('settings.projects.scripts.testRun.formats.junit')}
+                        </SelectItem>
+                      </SelectContent>
+                    </Select>
+                    {draft.test_run_format === 'junit' && (
+                      <div className="space-y-2">
+                        <Input
+                          id="test-run-report-path"
+                          value={draft.test_run_report_path}
+                          onChange={(e) =>
+                            updateDraft({
+                              test_run_report_path: e.target.value,
+                            })
+                          }
+                          placeholder="report.xml"
+                          className="font-mono"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t(
+                            'settings.projects.scripts.testRun.reportPathHelper'
+                          )}
+                        </p>
+                      </div>
+                    )}
+                  </div>
+                )}
+              </div>
+
               <div className="space-y-2">
                 <Label>{t('settings.projects.scripts.copyFiles.label')}</Label>
                 <CopyFilesField
@@ -463,6 +1067,234 @@ export function ProjectSettings() {
                   {t('settings.projects.scripts.copyFiles.helper')}
                 </p>
               </div>
+
+              <div className="flex items-center space-x-2">
+                <Checkbox
+                  id="block-merge-on-vulnerabilities"
+                  checked={draft.block_merge_on_vulnerabilities}
+                  onCheckedChange={(checked: boolean) =>
+                    updateDraft({ block_merge_on_vulnerabilities: checked })
+                  }
+                />
+                <div className="space-y-0.5">
+                  <Label
+                    htmlFor="block-merge-on-vulnerabilities"
+                    className="cursor-pointer"
+                  >
+                    {t(
+                      'settings.projects.scripts.blockMergeOnVulnerabilities.label'
+                    )}
+                  </Label>
+                  <p className="text-sm text-muted-foreground">
+                    {t(
+                      'settings.projects.scripts.blockMergeOnVulnerabilities.helper'
+                    )}
+                  </p>
+                </div>
+              </div>
+
+              <div className="space-y-4">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="sync-base-branch"
+                    checked={draft.sync_base_branch}
+                    onCheckedChange={(checked: boolean) =>
+                      updateDraft({ sync_base_branch: checked })
+                    }
+                  />
+                  <div className="space-y-0.5">
+                    <Label htmlFor="sync-base-branch" className="cursor-pointer">
+                      {t('settings.projects.scripts.syncBaseBranch.label')}
+                    </Label>
+                    <p className="text-sm text-muted-foreground">
+                      {t('settings.projects.scripts.syncBaseBranch.helper')}
+                    </p>
+                  </div>
+                </div>
+
+                {draft.sync_base_branch && (
+                  <div className="flex items-center space-x-2 pl-6">
+                    <Checkbox
+                      id="auto-rebase-on-sync"
+                      checked={draft.auto_rebase_on_sync}
+                      onCheckedChange={(checked: boolean) =>
+                        updateDraft({ auto_rebase_on_sync: checked })
+                      }
+                    />
+                    <div className="space-y-0.5">
+                      <Label
+                        htmlFor="auto-rebase-on-sync"
+                        className="cursor-pointer"
+                      >
+                        {t('settings.projects.scripts.autoRebaseOnSync.label')}
+                      </Label>
+                      <p className="text-sm text-muted-foreground">
+                        {t('settings.projects.scripts.autoRebaseOnSync.helper')}
+                      </p>
+                    </div>
+                  </div>
+                )}
+              </div>
+
+              <div className="space-y-4">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="sandbox-enabled"
+                    checked={draft.sandbox_enabled}
+                    onCheckedChange={(checked: boolean) =>
+                      updateDraft({ sandbox_enabled: checked })
+                    }
+                  />
+                  <div className="space-y-0.5">
+                    <Label htmlFor="sandbox-enabled" className="cursor-pointer">
+                      {t('settings.projects.scripts.sandbox.label')}
+                    </Label>
+                    <p className="text-sm text-muted-foreground">
+                      {t('settings.projects.scripts.sandbox.helper')}
+                    </p>
+                  </div>
+                </div>
+
+                {draft.sandbox_enabled && (
+                  <div className="space-y-4 pl-6">
+                    <div className="space-y-2">
+                      <Label htmlFor="sandbox-writable-paths">
+                        {t(
+                          'settings.projects.scripts.sandbox.writablePaths.label'
+                        )}
+                      </Label>
+                      <Input
+                        id="sandbox-writable-paths"
+                        value={draft.sandbox_writable_paths}
+                        onChange={(e) =>
+                          updateDraft({
+                            sandbox_writable_paths: e.target.value,
+                          })
+                        }
+                        placeholder="~/.claude, ~/.cache"
+                        className="font-mono"
+                      />
+                      <p className="text-sm text-muted-foreground">
+                        {t(
+                          'settings.projects.scripts.sandbox.writablePaths.helper'
+                        )}
+                      </p>
+                    </div>
+                    <div className="grid grid-cols-3 gap-4">
+                      <div className="space-y-2">
+                        <Label htmlFor="sandbox-memory-max-mb">
+                          {t(
+                            'settings.projects.scripts.sandbox.memoryMaxMb.label'
+                          )}
+                        </Label>
+                        <Input
+                          id="sandbox-memory-max-mb"
+                          type="number"
+                          min={1}
+                          value={draft.sandbox_memory_max_mb}
+                          onChange={(e) =>
+                            updateDraft({
+                              sandbox_memory_max_mb: e.target.value,
+                            })
+                          }
+                        />
+                      </div>
+                      <div className="space-y-2">
+                        <Label htmlFor="sandbox-cpu-max-percent">
+                          {t(
+                            'settings.projects.scripts.sandbox.cpuMaxPercent.label'
+                          )}
+                        </Label>
+                        <Input
+                          id="sandbox-cpu-max-percent"
+                          type="number"
+                          min={1}
+                          value={draft.sandbox_cpu_max_percent}
+                          onChange={(e) =>
+                            updateDraft({
+                              sandbox_cpu_max_percent: e.target.value,
+                            })
+                          }
+                        />
+                      </div>
+                      <div className="space-y-2">
+                        <Label htmlFor="sandbox-pids-max">
+                          {t('settings.projects.scripts.sandbox.pidsMax.label')}
+                        </Label>
+                        <Input
+                          id="sandbox-pids-max"
+                          type="number"
+                          min={1}
+                          value={draft.sandbox_pids_max}
+                          onChange={(e) =>
+                            updateDraft({ sandbox_pids_max: e.target.value })
+                          }
+                        />
+                      </div>
+                    </div>
+                    <div className="space-y-2">
+                      <Label htmlFor="sandbox-egress">
+                        {t('settings.projects.scripts.sandbox.egress.label')}
+                      </Label>
+                      <Select
+                        value={draft.sandbox_egress}
+                        onValueChange={(value) =>
+                          updateDraft({ sandbox_egress: value as EgressMode })
+                        }
+                      >
+                        <SelectTrigger id="sandbox-egress">
+                          <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                          <SelectItem value={EgressMode.ALLOW}>
+                            {t(
+                              'settings.projects.scripts.sandbox.egress.allow'
+                            )}
+                          </SelectItem>
+                          <SelectItem value={EgressMode.DENY_ALL}>
+                            {t(
+                              'settings.projects.scripts.sandbox.egress.denyAll'
+                            )}
+                          </SelectItem>
+                          <SelectItem value={EgressMode.ALLOWLIST}>
+                            {t(
+                              'settings.projects.scripts.sandbox.egress.allowlist'
+                            )}
+                          </SelectItem>
+                        </SelectContent>
+                      </Select>
+                      <p className="text-sm text-muted-foreground">
+                        {t('settings.projects.scripts.sandbox.egress.helper')}
+                      </p>
+                    </div>
+                    {draft.sandbox_egress === EgressMode.ALLOWLIST && (
+                      <div className="space-y-2">
+                        <Label htmlFor="sandbox-allowed-hosts">
+                          {t(
+                            'settings.projects.scripts.sandbox.allowedHosts.label'
+                          )}
+                        </Label>
+                        <Input
+                          id="sandbox-allowed-hosts"
+                          value={draft.sandbox_allowed_hosts}
+                          onChange={(e) =>
+                            updateDraft({
+                              sandbox_allowed_hosts: e.target.value,
+                            })
+                          }
+                          placeholder="api.anthropic.com, *.npmjs.org"
+                          className="font-mono"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t(
+                            'settings.projects.scripts.sandbox.allowedHosts.helper'
+                          )}
+                        </p>
+                      </div>
+                    )}
+                  </div>
+                )}
+              </div>
             </CardContent>
           </Card>
 
@@ -0,0 +1,140 @@
+import { useState } from 'react';
+import { useParams } from 'react-router-dom';
+import { useQuery } from '@tanstack/react-query';
+import { useTranslation } from 'react-i18next';
+import DiffCard from '@/components/DiffCard';
+import { Loader } from '@/components/ui/loader';
+import { ReviewProvider } from '@/contexts/ReviewProvider';
+import { sharedApi } from '@/lib/api';
+import { cn } from '@/lib/utils';
+
+/** Read-only view of an attempt opened from one of its share links */
+export function SharedAttemptPage() {
+  const { t } = useTranslation('tasks');
+  const { token = '' } = useParams<{ token: string }>();
+  const [expanded, setExpanded] = useState<Record<string, boolean>>({});
+  const [processId, setProcessId] = useState<string | null>(null);
+
+  const attempt = useQuery({
+    queryKey: ['sharedAttempt', token],
+    queryFn: () => sharedApi.getAttempt(token),
+    enabled: !!token,
+    retry: false,
+  });
+  const diffs = useQuery({
+    queryKey: ['sharedAttemptDiff', token],
+    queryFn: () => sharedApi.getDiffFiles(token),
+    enabled: attempt.isSuccess,
+  });
+  const logs = useQuery({
+    queryKey: ['sharedAttemptLogs', token, processId],
+    queryFn: () => sharedApi.getProcessLogs(token, processId!),
+    enabled: attempt.isSuccess && !!processId,
+  });
+
+  if (attempt.isLoading) {
+    return (
+      <div className="min-h-screen flex items-center justify-center">
+        <Loader message={t('sharedAttempt.loading')} size={32} />
+      </div>
+    );
+  }
+  if (!attempt.data) {
+    return (
+      <div className="min-h-screen flex items-center justify-center text-muted-foreground">
+        {t('sharedAttempt.unavailable', {
+          error: (attempt.error as Error | null)?.message ?? '',
+        })}
+      </div>
+    );
+  }
+
+  const shared = attempt.data;
+  return (
+    <ReviewProvider key={token}>
+      <div className="h-screen overflow-y-auto">
+        <main className="mx-auto w-full max-w-[70rem] p-6 space-y-6">
+          <header className="space-y-1">
+            <p className="text-xs text-muted-foreground">
+              {t('sharedAttempt.readOnly')}
+            </p>
+            <h1 className="text-xl font-semibold">{shared.task_title}</h1>
+            <p className="text-sm text-muted-foreground">
+              {shared.executor} ·{' '}
+              {t('sharedAttempt.branch', {
+                branch: shared.branch,
+                target: shared.target_branch,
+              })}
+            </p>
+            {shared.task_description && (
+              <p className="text-sm whitespace-pre-wrap">
+                {shared.task_description}
+              </p>
+            )}
+          </header>
+
+          <section className="space-y-2">
+            <h2 className="font-medium">{t('sharedAttempt.changes')}</h2>
+            {diffs.isLoading && <Loader size={16} />}
+            {diffs.data && diffs.data.files.length === 0 && (
+              <p className="text-sm text-muted-foreground">
+                {t('diff.noChanges')}
+              </p>
+            )}
+            {diffs.data?.files.map((diff, index) => {
+              const key = diff.newPath || diff.oldPath || String(index);
+              return (
+                <DiffCard
+                  key={key}
+                  diff={diff}
+                  expanded={expanded[key] ?? true}
+                  onToggle={() =>
+                    setExpanded((prev) => ({
+                      ...prev,
+                      [key]: !(prev[key] ?? true),
+                    }))
+                  }
+                  selectedAttempt={null}
+                />
+              );
+            })}
+          </section>
+
+          <section className="space-y-2">
+            <h2 className="font-medium">{t('sharedAttempt.processes')}</h2>
+            <div className="flex flex-wrap gap-2">
+              {shared.processes.map((process) => (
+                <button
+                  key={process.id}
+                  onClick={() => setProcessId(process.id)}
+                  className={cn(
+                    'rounded border px-2 py-1 text-xs',
+                    process.id === processId && 'bg-muted'
+                  )}
+                >
+                  {process.run_reason} · {process.status} ·{' '}
+                  {new Date(process.started_at).toLocaleString()}
+                </button>
+              ))}
+            </div>
+            {!processId && (
+              <p className="text-sm text-muted-foreground">
+                {t('sharedAttempt.selectProcess')}
+              </p>
+            )}
+            {processId && logs.isLoading && (
+              <p className="text-sm text-muted-foreground">
+                {t('sharedAttempt.loadingLogs')}
+              </p>
+            )}
+            {processId && logs.data !== undefined && (
+              <pre className="max-h-[60vh] overflow-auto rounded bg-muted p-3 text-xs whitespace-pre-wrap">
+                {logs.data || t('sharedAttempt.noOutput')}
+              </pre>
+            )}
+          </section>
+        </main>
+      </div>
+    </ReviewProvider>
+  );
+}
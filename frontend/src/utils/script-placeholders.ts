@@ -2,6 +2,9 @@ interface ScriptPlaceholders {
   setup: string;
   dev: string;
   cleanup: string;
+  verify: string;
+  postMerge: string;
+  diagnostic: string;
 }
 
 interface ScriptPlaceholderStrategy {
@@ -20,6 +23,15 @@ REM Add dev server start command here...`,
       cleanup: `@echo off
 REM Add cleanup commands here...
 REM This runs after coding agent execution - only if changes were made`,
+      verify: `@echo off
+npm test
+REM The merge queue only merges attempts for which this succeeds`,
+      postMerge: `@echo off
+REM Runs in the repository after an attempt is merged
+REM Trigger deploys or regenerate files here...`,
+      diagnostic: `@echo off
+REM Runs when the cleanup script fails
+git status`,
     };
   }
 }
@@ -36,6 +48,15 @@ npm run dev
       cleanup: `#!/bin/bash
 # Add cleanup commands here...
 # This runs after coding agent execution - only if changes were made`,
+      verify: `#!/bin/bash
+npm test
+# The merge queue only merges attempts for which this succeeds`,
+      postMerge: `#!/bin/bash
+# Runs in the repository after an attempt is merged
+# Trigger deploys or regenerate files here...`,
+      diagnostic: `#!/bin/bash
+# Runs when the cleanup script fails
+git status`,
     };
   }
 }
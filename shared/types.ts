@@ -8,109 +8,1366 @@ export type DirectoryEntry = { name: string, path: string, is_directory: boolean
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
-export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, 
+/**
+ * Script the merge queue runs in a rebased attempt's worktree; the attempt is only merged
+ * if it succeeds
+ */
+verify_script: string | null, 
+/**
+ * Script run in the project's repository after an attempt is merged, e.g. to trigger a
+ * deploy
+ */
+post_merge_script: string | null, copy_files: string | null, 
+/**
+ * Refuse to merge attempts with critical dependency vulnerability findings
+ */
+block_merge_on_vulnerabilities: boolean, 
+/**
+ * Periodically fetch attempts' base branches and record how far behind they are
+ */
+sync_base_branch: boolean, 
+/**
+ * Rebase clean, idle attempts onto their base branch when it moves ahead
+ */
+auto_rebase_on_sync: boolean, 
+/**
+ * Remote that attempt branches are pushed to; unset uses the repository's first remote
+ */
+push_remote: string | null, 
+/**
+ * Remote that base branches and pull requests live on; unset uses the repository's
+ * first remote
+ */
+base_remote: string | null, 
+/**
+ * Comma-separated branch patterns (e.g. `main, release/*`) that merges and pushes refuse
+ * to write to unless explicitly overridden
+ */
+protected_branches: string | null, 
+/**
+ * IANA time zone (e.g. `Europe/Berlin`) for quiet hours, schedules and report periods;
+ * unset uses the server's local time
+ */
+timezone: string | null, 
+/**
+ * Language (`en`, `es`, `ja` or `ko`) of the notifications and commit messages generated
+ * for the project; unset uses the configured language
+ */
+locale: string | null, quiet_hours: QuietHours | null, 
+/**
+ * Run the project's executor processes inside a Linux sandbox
+ */
+sandbox: SandboxConfig | null, 
+/**
+ * Re-run setup scripts and coding agents that fail with a transient error
+ */
+retry_policy: RetryPolicy | null, 
+/**
+ * Reuse the setup script's output between attempts while its inputs are unchanged
+ */
+setup_cache: SetupCacheConfig | null, 
+/**
+ * What to run when an attempt's cleanup script fails
+ */
+on_cleanup_failure: CleanupFailureConfig | null, 
+/**
+ * Test command run after each coding agent, whose results are shown on the task
+ */
+test_run: TestRunConfig | null, 
+/**
+ * Which of the coding agent's tool calls are approved or denied without asking the user
+ */
+approval_policy: ApprovalPolicy | null, created_at: Date, updated_at: Date, };
 
 export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, git_url: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
 
-export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, verify_script: string | null, post_merge_script: string | null, copy_files: string | null, 
+/**
+ * Leave unset to keep the current value
+ */
+block_merge_on_vulnerabilities?: boolean, 
+/**
+ * Leave unset to keep the current value
+ */
+sync_base_branch?: boolean, 
+/**
+ * Leave unset to keep the current value
+ */
+auto_rebase_on_sync?: boolean, push_remote: string | null, base_remote: string | null, protected_branches: string | null, timezone: string | null, locale: string | null, 
+/**
+ * Leave unset to keep the current value
+ */
+quiet_hours?: QuietHours, 
+/**
+ * Leave unset to keep the current value
+ */
+sandbox?: SandboxConfig, 
+/**
+ * Leave unset to keep the current value
+ */
+retry_policy?: RetryPolicy, 
+/**
+ * Leave unset to keep the current value
+ */
+setup_cache?: SetupCacheConfig, 
+/**
+ * Leave unset to keep the current value
+ */
+on_cleanup_failure?: CleanupFailureConfig, 
+/**
+ * Leave unset to keep the current value
+ */
+test_run?: TestRunConfig, 
+/**
+ * Leave unset to keep the current value
+ */
+approval_policy?: ApprovalPolicy, };
+
+export type QuietHours = { enabled: boolean, 
+/**
+ * Start of the window as `HH:MM` in the project's time zone
+ */
+start: string, 
+/**
+ * End of the window as `HH:MM`; earlier than `start` for windows that span midnight
+ */
+end: string, 
+/**
+ * Keep Saturdays and Sundays quiet all day
+ */
+weekends: boolean, };
+
+export type RetryPolicy = { enabled: boolean, 
+/**
+ * Retries after the first failure
+ */
+max_retries: number, 
+/**
+ * Delay before the first retry; doubled for every further retry
+ */
+initial_backoff_secs: bigint, 
+/**
+ * Upper bound for the delay between retries
+ */
+max_backoff_secs: bigint, };
+
+export type SetupCacheConfig = { enabled: boolean, 
+/**
+ * Directories or files the setup script creates, relative to the repository root, e.g.
+ * `node_modules`
+ */
+paths: Array<string>, 
+/**
+ * Files hashed together with the setup script to tell whether a saved copy is still
+ * valid. Common lockfiles are used when empty.
+ */
+key_files: Array<string>, };
+
+export type FailureAction = { "type": "script", script: string, } | { "type": "follow_up", prompt: string, };
+
+export type CleanupFailureConfig = { enabled: boolean, action: FailureAction, };
+
+export type TestReportFormat = "cargo" | "jest" | "junit";
+
+export type TestRunConfig = { enabled: boolean, 
+/**
+ * Run in the attempt's worktree after the coding agent and the cleanup script
+ */
+command: string, format: TestReportFormat, 
+/**
+ * JUnit report written by the command, relative to the worktree
+ */
+report_path: string | null, };
+
+export type ApprovalAction = "ask" | "approve" | "deny";
+
+export type ApprovalMatcher = { "type": "tool", name: string, } | { "type": "shell_command", pattern: string, } | { "type": "file_deletion" } | { "type": "git_push" };
+
+export type ApprovalRule = { matcher: ApprovalMatcher, action: ApprovalAction, };
+
+export type ApprovalPolicy = { enabled: boolean, rules: Array<ApprovalRule>, default_action: ApprovalAction, 
+/**
+ * How long the user has to answer; unset waits an hour
+ */
+timeout_secs: bigint | null, 
+/**
+ * Answer given when the user doesn't answer in time; only `approve` and `deny` apply
+ */
+on_timeout: ApprovalAction, };
+
+export type ReportPeriod = "day" | "week" | "month";
+
+export type OutcomeStats = { attempts: number, 
+/**
+ * Merged directly or through a merged pull request
+ */
+merged: number, 
+/**
+ * Pull request still awaiting review
+ */
+pr_open: number, 
+/**
+ * Pull request closed without merging
+ */
+pr_closed: number, 
+/**
+ * Neither merged nor submitted as a pull request
+ */
+unmerged: number, 
+/**
+ * Share of attempts that were merged, from 0 to 1
+ */
+merge_rate: number, avg_follow_ups: number, };
+
+export type PeriodStats = { 
+/**
+ * First day of the period in the report's time zone
+ */
+start: string, stats: OutcomeStats, };
+
+export type AgentConfigurationReport = { executor: string, variant: string | null, totals: OutcomeStats, 
+/**
+ * Only periods with at least one attempt, oldest first
+ */
+periods: Array<PeriodStats>, };
+
+export type AttemptReport = { since: string, period: ReportPeriod, 
+/**
+ * IANA time zone the periods follow; unset for the server's local time
+ */
+timezone: string | null, 
+/**
+ * Most used configuration first
+ */
+configurations: Array<AgentConfigurationReport>, };
+
+export type RepoHealthIssueKind = "object_corruption" | "stale_lock" | "stale_rebase" | "orphaned_worktree" | "broken_ref";
+
+export type RepoHealthIssue = { kind: RepoHealthIssueKind, 
+/**
+ * Ref name, worktree or file the issue is about
+ */
+target: string | null, detail: string, 
+/**
+ * Whether the repair action fixes this issue
+ */
+repairable: boolean, };
+
+export type RepoHealthReport = { healthy: boolean, issues: Array<RepoHealthIssue>, checked_at: string, };
+
+export type RepoRepairReport = { repaired: Array<RepoHealthIssue>, 
+/**
+ * State of the repository after the repair; repairs that failed show up here again
+ */
+health: RepoHealthReport, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
 export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
-export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
+export enum EgressMode { ALLOW = "ALLOW", DENY_ALL = "DENY_ALL", ALLOWLIST = "ALLOWLIST" }
+
+export type SandboxConfig = { 
+/**
+ * Run setup scripts, coding agents and dev servers inside the sandbox
+ */
+enabled: boolean, 
+/**
+ * Paths outside the worktree that stay writable, e.g. `~/.claude` or `~/.cache`
+ */
+writable_paths: Array<string>, 
+/**
+ * cgroup memory limit in megabytes
+ */
+memory_max_mb: number | null, 
+/**
+ * cgroup CPU limit as a percentage of one core (200 = two cores)
+ */
+cpu_max_percent: number | null, 
+/**
+ * cgroup limit on the number of processes and threads
+ */
+pids_max: number | null, 
+/**
+ * Outbound network policy
+ */
+egress: EgressMode, 
+/**
+ * Hosts reachable in `ALLOWLIST` mode; `*.example.com` matches any subdomain
+ */
+allowed_hosts: Array<string>, };
+
+export type ExecutorAction = { typ: ExecutorActionType, 
+/**
+ * Run after this action succeeds
+ */
+next_action: ExecutorAction | null, 
+/**
+ * Run instead of `next_action` if this action fails
+ */
+on_failure: ExecutorAction | null, };
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, preconfigured: JsonValue, is_toml_config: boolean, };
 
 export type ExecutorActionType = { "type": "CodingAgentInitialRequest" } & CodingAgentInitialRequest | { "type": "CodingAgentFollowUpRequest" } & CodingAgentFollowUpRequest | { "type": "ScriptRequest" } & ScriptRequest;
 
-export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer";
+export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "PostMergeScript" | "TestScript" | "DiagnosticScript";
 
-export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, };
+export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, 
+/**
+ * Extra environment variables for the script
+ */
+env: { [key in string]?: string }, };
 
 export type ScriptRequestLanguage = "Bash";
 
-export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT" }
+export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", CUSTOM = "CUSTOM", LOCAL_LLM = "LOCAL_LLM" }
+
+export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "CUSTOM": CustomAgent } | { "LOCAL_LLM": LocalLlm };
+
+export type PromptSnippet = { id: string, 
+/**
+ * Global snippets have no project
+ */
+project_id: string | null, name: string, content: string, created_at: string, updated_at: string, };
+
+export type CreatePromptSnippet = { project_id: string | null, name: string, content: string, };
+
+export type UpdatePromptSnippet = { name: string | null, content: string | null, };
+
+export type SetupCacheEntry = { project_id: string, 
+/**
+ * Hash of the setup script, the cached paths and the key files
+ */
+cache_key: string, size_bytes: bigint, 
+/**
+ * Attempts that reused this entry instead of running the setup script
+ */
+hit_count: bigint, created_at: string, last_used_at: string, };
+
+export type TemplateVariableKind = "text" | "url" | "number" | "boolean";
 
-export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot };
+export type TemplateVariable = { 
+/**
+ * Lowercase letters, digits and `_`, starting with a letter
+ */
+name: string, kind?: TemplateVariableKind, 
+/**
+ * Used when no value is given; variables without one are required
+ */
+default?: string, description?: string, };
 
-export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
+export type Tag = { id: string, tag_name: string, content: string, variables: Array<TemplateVariable>, created_at: string, updated_at: string, };
 
-export type CreateTag = { tag_name: string, content: string, };
+export type CreateTag = { tag_name: string, content: string, variables?: Array<TemplateVariable>, };
 
-export type UpdateTag = { tag_name: string | null, content: string | null, };
+export type UpdateTag = { tag_name: string | null, content: string | null, variables: Array<TemplateVariable> | null, };
 
 export type TagSearchParams = { search: string | null, };
 
-export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
+export type CreateTaskFromTemplate = { project_id: string, 
+/**
+ * May contain the tag's `{{name}}` placeholders
+ */
+title: string, 
+/**
+ * Values of the tag's variables by name; variables left out use their default
+ */
+values?: { [key in string]?: string }, };
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, };
+export type TaskSchedule = { id: string, project_id: string, name: string, 
+/**
+ * Five-field cron expression: minute, hour, day of month, month, day of week
+ */
+cron_expression: string, 
+/**
+ * Tag whose content becomes the description of a new task on every run
+ */
+tag_id: string | null, 
+/**
+ * Existing task that gets a new attempt on every run
+ */
+task_id: string | null, executor_profile_id: ExecutorProfileId, base_branch: string, enabled: boolean, 
+/**
+ * Unset while the schedule is disabled
+ */
+next_run_at: string | null, last_run_at: string | null, last_task_attempt_id: string | null, 
+/**
+ * Why the last run did not start
+ */
+last_error: string | null, created_at: string, updated_at: string, };
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, executor: string, tags: Array<Tag>, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, };
+export type CreateTaskSchedule = { project_id: string, name: string, cron_expression: string, 
+/**
+ * Set exactly one of `tag_id` and `task_id`
+ */
+tag_id: string | null, task_id: string | null, executor_profile_id: ExecutorProfileId, base_branch: string, enabled?: boolean, };
 
-export type TaskRelationships = { parent_task: Task | null, current_attempt: TaskAttempt, children: Array<Task>, };
+export type UpdateTaskSchedule = { name: string | null, cron_expression: string | null, executor_profile_id: ExecutorProfileId | null, base_branch: string | null, enabled: boolean | null, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, image_ids: Array<string> | null, tag_ids: Array<string> | null, };
+export type AutomationTrigger = "attempt_merged" | "coding_agent_completed" | "coding_agent_failed";
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, image_ids: Array<string> | null, tag_ids: Array<string> | null, };
+export type AutomationCondition = { "type": "task_status", status: TaskStatus, } | { "type": "has_tag", tag_name: string, } | { "type": "failed_runs_at_least", count: number, };
 
-export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+export type AutomationAction = { "type": "set_task_status", status: TaskStatus, } | { "type": "add_tag", tag_name: string, } | { "type": "close_github_issue" };
 
-export type CreateImage = { file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, };
+export type AutomationRule = { id: string, project_id: string, name: string, trigger: AutomationTrigger, conditions: Array<AutomationCondition>, actions: Array<AutomationAction>, enabled: boolean, created_at: string, updated_at: string, };
 
-export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+export type CreateAutomationRule = { project_id: string, name: string, trigger: AutomationTrigger, conditions?: Array<AutomationCondition>, actions: Array<AutomationAction>, enabled?: boolean, };
 
-export type UserSystemInfo = { config: Config, analytics_user_id: string, environment: Environment, 
+export type UpdateAutomationRule = { name: string | null, trigger: AutomationTrigger | null, conditions: Array<AutomationCondition> | null, actions: Array<AutomationAction> | null, enabled: boolean | null, };
+
+export type AutomationRuleRunStatus = "running" | "succeeded" | "failed";
+
+export type AutomationRuleRun = { id: string, rule_id: string, 
 /**
- * Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
+ * The event that fired the rule, such as `merge:<id>`
  */
-capabilities: { [key in string]?: Array<BaseAgentCapability> }, executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
+event_key: string, task_id: string, task_attempt_id: string | null, status: AutomationRuleRunStatus, 
+/**
+ * What the actions did, or why one of them failed
+ */
+message: string | null, created_at: string, completed_at: string | null, };
 
-export type Environment = { os_type: string, os_version: string, os_architecture: string, bitness: string, };
+export type WebhookEvent = "task_status_changed" | "execution_finished" | "merge_completed" | "approval_requested";
 
-export type McpServerQuery = { executor: BaseCodingAgent, };
+export type Webhook = { id: string, 
+/**
+ * Unset for webhooks that fire for every project
+ */
+project_id: string | null, url: string, 
+/**
+ * Events the webhook is called for; empty for all of them
+ */
+events: Array<WebhookEvent>, enabled: boolean, created_at: string, updated_at: string, };
 
-export type UpdateMcpServersBody = { servers: { [key in string]?: JsonValue }, };
+export type CreateWebhook = { 
+/**
+ * Leave unset for a webhook that fires for every project
+ */
+project_id: string | null, url: string, 
+/**
+ * Signs the payloads; never returned by the API
+ */
+secret: string, events?: Array<WebhookEvent>, enabled?: boolean, };
 
-export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string, };
+export type UpdateWebhook = { url: string | null, 
+/**
+ * Leave unset to keep the existing secret
+ */
+secret: string | null, events: Array<WebhookEvent> | null, enabled: boolean | null, };
 
-export type CreateFollowUpAttempt = { prompt: string, variant: string | null, image_ids: Array<string> | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
+export type WebhookDeliveryStatus = "pending" | "delivered" | "failed";
 
-export type DraftResponse = { task_attempt_id: string, draft_type: DraftType, retry_process_id: string | null, prompt: string, queued: boolean, variant: string | null, image_ids: Array<string> | null, version: bigint, };
+export type WebhookDelivery = { id: string, webhook_id: string, event: WebhookEvent, 
+/**
+ * The occurrence of the event, such as `merge:<id>`
+ */
+event_key: string, 
+/**
+ * JSON body sent to the webhook
+ */
+payload: string, status: WebhookDeliveryStatus, attempts: bigint, 
+/**
+ * HTTP status of the last attempt, unset if it got no response
+ */
+response_status: bigint | null, last_error: string | null, next_attempt_at: string | null, delivered_at: string | null, created_at: string, updated_at: string, };
 
-export type UpdateFollowUpDraftRequest = { prompt: string | null, variant: string | null | null, image_ids: Array<string> | null, version: bigint | null, };
+export type ChatChannelKind = "slack" | "discord";
 
-export type UpdateRetryFollowUpDraftRequest = { retry_process_id: string, prompt: string | null, variant: string | null | null, image_ids: Array<string> | null, version: bigint | null, };
+export type ChatNotificationEvent = "agent_completed" | "agent_failed" | "approval_requested";
 
-export type ChangeTargetBranchRequest = { new_target_branch: string, };
+export type ChatMessageTemplates = { agent_completed: string | null, agent_failed: string | null, approval_requested: string | null, };
 
-export type ChangeTargetBranchResponse = { new_target_branch: string, status: [number, number], };
+export type ChatChannel = { id: string, project_id: string, kind: ChatChannelKind, name: string, 
+/**
+ * Events posted to the channel; empty for all of them
+ */
+events: Array<ChatNotificationEvent>, 
+/**
+ * Templates may use `{task}`, `{project}`, `{executor}`, `{branch}` and `{link}`, the
+ * address of the attempt; approval requests also `{tool}`
+ */
+templates: ChatMessageTemplates, enabled: boolean, created_at: string, updated_at: string, };
 
-export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, base_branch: string, };
+export type CreateChatChannel = { kind: ChatChannelKind, name: string, 
+/**
+ * Incoming webhook URL of the channel; never returned by the API
+ */
+webhook_url: string, events?: Array<ChatNotificationEvent>, templates?: ChatMessageTemplates, enabled?: boolean, };
 
-export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, };
+export type UpdateChatChannel = { name: string | null, 
+/**
+ * Leave unset to keep the existing URL
+ */
+webhook_url: string | null, events: Array<ChatNotificationEvent> | null, templates: ChatMessageTemplates | null, enabled: boolean | null, };
 
-export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+export type ApprovalOutcome = "approved" | "denied" | "timed_out";
 
-export enum GitHubServiceError { TOKEN_INVALID = "TOKEN_INVALID", INSUFFICIENT_PERMISSIONS = "INSUFFICIENT_PERMISSIONS", REPO_NOT_FOUND_OR_NO_ACCESS = "REPO_NOT_FOUND_OR_NO_ACCESS" }
+export type ApprovalDecider = "user" | "policy" | "timeout";
+
+export type ApprovalDecision = { id: string, approval_id: string, execution_process_id: string, tool_name: string, tool_input: JsonValue, outcome: ApprovalOutcome, decided_by: ApprovalDecider, 
+/**
+ * Name of the user who answered, when known
+ */
+user_name: string | null, 
+/**
+ * Reason the user gave for a denial, or the policy rule that applied
+ */
+reason: string | null, created_at: string, };
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, };
+export type AuditAction = "force_reset" | "worktree_delete" | "branch_delete" | "merge" | "push" | "task_delete";
 
-export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
+export type AuditLogEntry = { id: string, action: AuditAction, 
+/**
+ * User the request acted for; unset for work the server started on its own
+ */
+actor: string | null, project_id: string | null, task_id: string | null, task_attempt_id: string | null, 
+/**
+ * Commit, worktree path, branch or task title the operation acted on
+ */
+target: string, parameters: JsonValue, 
+/**
+ * Why the operation failed; unset when it succeeded
+ */
+error: string | null, created_at: string, };
 
-export enum ThemeMode { LIGHT = "LIGHT", DARK = "DARK", SYSTEM = "SYSTEM" }
+export type NotificationSeverity = "info" | "success" | "warning" | "error";
 
-export type EditorConfig = { editor_type: EditorType, custom_command: string | null, remote_ssh_host: string | null, remote_ssh_user: string | null, };
+export type NotificationKind = "agent_completed" | "agent_failed" | "script_failed" | "approval_requested" | "merge_completed";
 
-export enum EditorType { VS_CODE = "VS_CODE", CURSOR = "CURSOR", WINDSURF = "WINDSURF", INTELLI_J = "INTELLI_J", ZED = "ZED", XCODE = "XCODE", CUSTOM = "CUSTOM" }
+export type AppNotification = { id: string, kind: NotificationKind, severity: NotificationSeverity, title: string, body: string, 
+/**
+ * Path of the page in the app the notification is about
+ */
+link: string, project_id: string, task_id: string, task_attempt_id: string, created_at: string, };
 
-export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
+export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
-export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
+export type TaskPriority = "urgent" | "high" | "normal" | "low";
 
-export type UiLanguage = "BROWSER" | "EN" | "JA" | "ES" | "KO";
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, parent_task_id: string | null, priority: TaskPriority, 
+/**
+ * Position within the task's board column, lowest first
+ */
+sort_order: bigint, 
+/**
+ * User the task is assigned to
+ */
+assignee: string | null, 
+/**
+ * Board column the task sits in; its status always matches the column's
+ */
+column_id: string | null, 
+/**
+ * Key of the issue in an external tracker (e.g. `ENG-42`) the task is synced with
+ */
+external_key: string | null, created_at: string, updated_at: string, };
 
-export type ShowcaseState = { seen_features: Array<string>, };
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, executor: string, tags: Array<Tag>, 
+/**
+ * Most recent preview deployment URL reported for any of the task's attempts
+ */
+preview_url: string | null, staleness: TaskStaleness, 
+/**
+ * Set while one of the task's running executions is blocked on the user
+ */
+needs_attention: AttentionReason | null, 
+/**
+ * Results of the task's most recent test run
+ */
+test_results: TestCounts | null, 
+/**
+ * Set while a task it depends on isn't done
+ */
+is_blocked: boolean, 
+/**
+ * How many of the task's subtasks are done, if it has any
+ */
+subtask_progress: SubtaskProgress | null, 
+/**
+ * Code-review state of the task's latest attempt
+ */
+review_state: AttemptReviewState | null, 
+/**
+ * Size of the latest attempt's changes, as of its last finished execution process
+ */
+diff_stats: AttemptDiffStats | null, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, parent_task_id: string | null, priority: TaskPriority, 
+/**
+ * Position within the task's board column, lowest first
+ */
+sort_order: bigint, 
+/**
+ * User the task is assigned to
+ */
+assignee: string | null, 
+/**
+ * Board column the task sits in; its status always matches the column's
+ */
+column_id: string | null, 
+/**
+ * Key of the issue in an external tracker (e.g. `ENG-42`) the task is synced with
+ */
+external_key: string | null, created_at: string, updated_at: string, };
+
+export type SubtaskProgress = { done: bigint, total: bigint, };
+
+export type TaskStaleness = { 
+/**
+ * Whole days since the most recent execution of any of the task's attempts
+ */
+days_since_last_execution: bigint | null, 
+/**
+ * Commits the latest attempt was behind its base branch at the last background sync
+ */
+base_branch_drift: bigint | null, 
+/**
+ * Whole hours since an unsent follow-up draft on one of the attempts was last edited
+ */
+follow_up_draft_age_hours: bigint | null, 
+/**
+ * Whether any of the above exceeds its threshold on a task that is still open
+ */
+is_stale: boolean, };
+
+export type AttentionReason = "pending_approval" | "stalled";
+
+export type StalenessThresholds = { 
+/**
+ * Days since the task's last execution
+ */
+execution_days: number, 
+/**
+ * Commits the latest attempt is behind its base branch
+ */
+base_branch_drift: number, 
+/**
+ * Hours an unsent follow-up draft has been left untouched
+ */
+draft_age_hours: number, };
+
+export type TaskRelationships = { parent_task: Task | null, current_attempt: TaskAttempt, children: Array<Task>, };
+
+export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, 
+/**
+ * Create the task as a subtask of this task
+ */
+parent_task_id?: string, 
+/**
+ * Defaults to normal
+ */
+priority?: TaskPriority, assignee?: string, image_ids: Array<string> | null, tag_ids: Array<string> | null, };
+
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, priority?: TaskPriority, 
+/**
+ * An empty string unassigns the task
+ */
+assignee?: string, 
+/**
+ * Moves the task into this board column, taking on the column's status
+ */
+column_id?: string, image_ids: Array<string> | null, tag_ids: Array<string> | null, };
+
+export type AddTaskDependencyRequest = { 
+/**
+ * Task that has to be done before this one can be started
+ */
+depends_on_task_id: string, };
+
+export type CreateSubtaskRequest = { title: string, description: string | null, };
+
+export type ProjectColumn = { id: string, project_id: string, name: string, 
+/**
+ * Status of the tasks in this column
+ */
+status: TaskStatus, 
+/**
+ * Position on the board, left to right
+ */
+position: bigint, created_at: string, updated_at: string, };
+
+export type CreateProjectColumn = { name: string, status: TaskStatus, };
+
+export type UpdateProjectColumn = { name: string | null, 
+/**
+ * Changing the status also changes it for every task in the column
+ */
+status: TaskStatus | null, };
+
+export type ReorderProjectColumnsRequest = { 
+/**
+ * All of the project's columns, left to right
+ */
+column_ids: Array<string>, };
+
+export type WipLimitEnforcement = "warn" | "refuse";
+
+export type WipLimit = { project_id: string, status: TaskStatus, max_tasks: bigint, enforcement: WipLimitEnforcement, created_at: string, updated_at: string, };
+
+export type UpsertWipLimit = { status: TaskStatus, max_tasks: bigint, enforcement?: WipLimitEnforcement, };
+
+export type WipUsage = { status: TaskStatus, count: bigint, 
+/**
+ * Unset when the status has no limit
+ */
+max_tasks: bigint | null, enforcement: WipLimitEnforcement | null, };
+
+export type TaskComment = { id: string, task_id: string, 
+/**
+ * Execution process the comment is about
+ */
+execution_process_id: string | null, 
+/**
+ * User who wrote the comment, unset when unknown
+ */
+author: string | null, body: string, created_at: string, updated_at: string, };
+
+export type CreateTaskComment = { body: string, execution_process_id?: string, };
+
+export type UpdateTaskComment = { body: string, };
+
+export type TaskSearchResult = { task_id: string, title: string, status: TaskStatus, 
+/**
+ * Excerpt of the best-matching field with the matched words in `**bold**`
+ */
+snippet: string, };
+
+export type TaskImportFormat = "csv" | "json";
+
+export type TaskImportMapping = { title?: string, description?: string, status?: string, tags?: string, };
+
+export type TaskImportRequest = { format: TaskImportFormat, 
+/**
+ * CSV with a header row, or a JSON array of objects
+ */
+content: string, mapping?: TaskImportMapping, 
+/**
+ * Check the rows and report what would be imported without creating anything
+ */
+dry_run?: boolean, };
+
+export type SkippedImportRow = { 
+/**
+ * 1-based, not counting the CSV header
+ */
+row: number, reason: string, };
+
+export type TaskImportSummary = { dry_run: boolean, total_rows: number, 
+/**
+ * Tasks created, or that would be created in a dry run
+ */
+created: number, skipped: Array<SkippedImportRow>, 
+/**
+ * Tags created, or that would be created, because no tag had their name
+ */
+new_tags: Array<string>, 
+/**
+ * The created tasks; empty in a dry run
+ */
+task_ids: Array<string>, };
+
+export type ProjectImportSummary = { project: Project, tasks: number, task_attempts: number, execution_processes: number, 
+/**
+ * Processes whose logs were in the archive
+ */
+logs: number, 
+/**
+ * Tags the archive's tasks had that don't exist here, so were left off
+ */
+missing_tags: Array<string>, };
+
+export type CloneProjectRequest = { 
+/**
+ * HTTPS, SSH (`ssh://` or `git@host:owner/repo`) or `git://` URL of the repository
+ */
+git_url: string, 
+/**
+ * Defaults to the repository's name
+ */
+name: string | null, };
+
+export type ProjectCloneStatus = "cloning" | "registering" | "completed" | "failed";
+
+export type ProjectClone = { id: string, git_url: string, name: string, 
+/**
+ * Where the repository is cloned to
+ */
+path: string, status: ProjectCloneStatus, 
+/**
+ * The phase git is in, e.g. "Receiving objects"
+ */
+phase: string | null, 
+/**
+ * Progress of the current phase
+ */
+percent: number | null, 
+/**
+ * The registered project, once completed
+ */
+project_id: string | null, error: string | null, };
+
+export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+
+export type CreateImage = { file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, };
+
+export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+
+export type UserSystemInfo = { config: Config, analytics_user_id: string, environment: Environment, 
+/**
+ * Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
+ */
+capabilities: { [key in string]?: Array<BaseAgentCapability> }, executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
+
+export type Environment = { os_type: string, os_version: string, os_architecture: string, bitness: string, };
+
+export type McpServerQuery = { executor: BaseCodingAgent, };
+
+export type UpdateMcpServersBody = { servers: { [key in string]?: JsonValue }, };
+
+export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string, };
+
+export type CreateFollowUpAttempt = { prompt: string, variant: string | null, image_ids: Array<string> | null, image_captions: { [key in string]?: string } | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
+
+export type DraftResponse = { task_attempt_id: string, draft_type: DraftType, retry_process_id: string | null, prompt: string, queued: boolean, variant: string | null, image_ids: Array<string> | null, image_captions: { [key in string]?: string } | null, version: bigint, };
+
+export type UpdateFollowUpDraftRequest = { prompt: string | null, variant: string | null | null, image_ids: Array<string> | null, image_captions: { [key in string]?: string } | null, version: bigint | null, };
+
+export type UpdateRetryFollowUpDraftRequest = { retry_process_id: string, prompt: string | null, variant: string | null | null, image_ids: Array<string> | null, image_captions: { [key in string]?: string } | null, version: bigint | null, };
+
+export type ChangeTargetBranchRequest = { new_target_branch: string, };
+
+export type ChangeTargetBranchResponse = { new_target_branch: string, status: [number, number], };
+
+export type DiffFilePage = { files: Array<Diff>, 
+/**
+ * Number of changed files across all pages
+ */
+total: number, };
+
+export type DependencyChangeKind = "added" | "removed" | "upgraded" | "downgraded";
+
+export type DependencyChange = { ecosystem: string, name: string, kind: DependencyChangeKind, old_version: string | null, new_version: string | null, };
+
+export type LockfileDependencyDiff = { path: string, changes: Array<DependencyChange>, 
+/**
+ * True when the lockfile was too large to load, so its changes are unknown
+ */
+content_omitted: boolean, };
+
+export type RerunMode = "fresh_attempt" | "follow_up";
+
+export type BulkRerunRequest = { project_id: string | null, 
+/**
+ * Only attempts created at or after this time
+ */
+created_after: string | null, 
+/**
+ * Only attempts created at or before this time
+ */
+created_before: string | null, executor: BaseCodingAgent | null, mode: RerunMode, 
+/**
+ * Prompt to send in `follow_up` mode
+ */
+prompt: string | null, 
+/**
+ * Only report which attempts would be re-run
+ */
+dry_run: boolean, };
+
+export type BulkRerunEntry = { task_id: string, task_title: string, 
+/**
+ * The failed attempt
+ */
+attempt_id: string, executor: string, 
+/**
+ * The attempt the re-run was started in; unset for dry runs and failures
+ */
+rerun_attempt_id: string | null, error: string | null, };
+
+export type BulkRerunResponse = { dry_run: boolean, entries: Array<BulkRerunEntry>, };
+
+export type AttemptComparison = { id: string, task_id: string, 
+/**
+ * The attempt that was kept; the others were discarded at `chosen_at`
+ */
+chosen_attempt_id: string | null, chosen_at: string | null, created_at: string, };
+
+export type CheckResult = "pending" | "passed" | "failed";
+
+export type DiffStats = { files_changed: number, additions: number, deletions: number, };
+
+export type ComparedAttempt = { attempt: TaskAttempt, 
+/**
+ * The profile the attempt was started with
+ */
+executor_profile_id: ExecutorProfileId, 
+/**
+ * Status of the latest coding agent run
+ */
+agent_status: ExecutionProcessStatus | null, 
+/**
+ * Unset when the changes could not be read, e.g. after the worktree was discarded
+ */
+diff: DiffStats | null, 
+/**
+ * Latest cleanup script run, which is where projects usually run their tests
+ */
+cleanup_script: CheckResult | null, 
+/**
+ * Latest GitHub Actions run dispatched for the attempt's branch
+ */
+ci: CheckResult | null, 
+/**
+ * From the first process starting to the last one finishing; unset while any still runs
+ */
+duration_ms: bigint | null, };
+
+export type AttemptComparisonDetails = { 
+/**
+ * In the order they were started
+ */
+attempts: Array<ComparedAttempt>, id: string, task_id: string, 
+/**
+ * The attempt that was kept; the others were discarded at `chosen_at`
+ */
+chosen_attempt_id: string | null, chosen_at: string | null, created_at: string, };
+
+export type StartAttemptComparison = { task_id: string, 
+/**
+ * One attempt is started per profile; the same profile may be listed more than once
+ */
+executor_profile_ids: Array<ExecutorProfileId>, base_branch: string, };
+
+export type AttemptComparisonQuery = { task_id: string, };
+
+export type ChooseComparedAttempt = { attempt_id: string, };
+
+export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, base_branch: string, };
+
+export type ReorderTasksRequest = { project_id: string, 
+/**
+ * A board column's tasks, top to bottom
+ */
+task_ids: Array<string>, };
+
+export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, };
+
+export type CreateTagRequest = { tag_name: string, 
+/**
+ * Tag annotation; defaults to the task title
+ */
+message: string | null, 
+/**
+ * Sign the tag with the configured commit signing key
+ */
+sign?: boolean, 
+/**
+ * Push the tag to the project's base remote
+ */
+push?: boolean, 
+/**
+ * Publish a GitHub release for the tag; the tag is pushed first
+ */
+release?: CreateReleaseOptions, };
+
+export type CreateReleaseOptions = { 
+/**
+ * Release title; defaults to the tag name
+ */
+name: string | null, body: string | null, draft: boolean, prerelease: boolean, };
+
+export type CreateTagResponse = { tag_name: string, commit_sha: string, pushed: boolean, release_url: string | null, };
+
+export type DispatchWorkflowRequest = { 
+/**
+ * Workflow file name (e.g. `ci.yml`) or numeric workflow id
+ */
+workflow_file: string, inputs: { [key in string]?: string } | null, };
+
+export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
+
+export enum GitHubServiceError { TOKEN_INVALID = "TOKEN_INVALID", INSUFFICIENT_PERMISSIONS = "INSUFFICIENT_PERMISSIONS", REPO_NOT_FOUND_OR_NO_ACCESS = "REPO_NOT_FOUND_OR_NO_ACCESS" }
+
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, telemetry: TelemetryConfig, commit_signing: CommitSigningConfig, vulnerability_scan: VulnerabilityScanConfig, stall_detection: StallDetectionConfig, staleness: StalenessThresholds, worktree_gc: WorktreeGcConfig, git_clone: GitCloneConfig, storage: StorageConfig, resource_limits: ResourceLimitsConfig, dev_server: DevServerConfig, log_retention: LogRetentionConfig, event_journal: EventJournalConfig, rate_limits: RateLimitConfig, chat_notifications: ChatNotificationConfig, email_notifications: EmailNotificationConfig, 
+/**
+ * Addresses or CIDR ranges of the reverse proxies whose forwarding headers are trusted
+ */
+trusted_proxies: Array<string>, };
+
+export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
+
+export enum ThemeMode { LIGHT = "LIGHT", DARK = "DARK", SYSTEM = "SYSTEM" }
+
+export type EditorConfig = { editor_type: EditorType, custom_command: string | null, remote_ssh_host: string | null, remote_ssh_user: string | null, };
+
+export enum EditorType { VS_CODE = "VS_CODE", CURSOR = "CURSOR", WINDSURF = "WINDSURF", INTELLI_J = "INTELLI_J", ZED = "ZED", XCODE = "XCODE", CUSTOM = "CUSTOM" }
+
+export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
+
+export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
+
+export type UiLanguage = "BROWSER" | "EN" | "JA" | "ES" | "KO";
+
+export type ShowcaseState = { seen_features: Array<string>, };
+
+export type TelemetryConfig = { 
+/**
+ * Opt in to aggregating anonymous usage statistics locally
+ */
+enabled: boolean, 
+/**
+ * Optional endpoint the aggregated statistics are periodically sent to
+ */
+collector_url: string | null, };
+
+export type CommitSigningConfig = { 
+/**
+ * Sign commits created by Vibe Kanban (agent commits, squash merges, initial commits)
+ */
+enabled: boolean, format: CommitSigningFormat, 
+/**
+ * GPG key id, or path to the SSH key used for signing
+ */
+signing_key: string | null, };
+
+export enum CommitSigningFormat { GPG = "GPG", SSH = "SSH" }
+
+export type VulnerabilityScanConfig = { 
+/**
+ * Scan dependencies added by an attempt once its coding agent finishes
+ */
+enabled: boolean, 
+/**
+ * Query the public OSV database (https://osv.dev) or run a local command
+ */
+scanner: VulnerabilityScanner, 
+/**
+ * Command that reads `{"packages": [...]}` on stdin and prints `{"findings": [...]}`
+ */
+command: string | null, };
+
+export enum VulnerabilityScanner { OSV = "OSV", COMMAND = "COMMAND" }
+
+export type StallDetectionConfig = { 
+/**
+ * Watch running coding agents for periods without any output
+ */
+enabled: boolean, 
+/**
+ * Seconds without output before a coding agent is considered stalled
+ */
+timeout_secs: number, action: StallAction, };
+
+export type WorktreeGcConfig = { 
+/**
+ * Periodically remove worktrees of attempts whose tasks are done or cancelled
+ */
+enabled: boolean, 
+/**
+ * Days a task must have been done or cancelled before its worktrees are removed
+ */
+retention_days: number, };
+
+export type GitCloneConfig = { 
+/**
+ * Partial clone filter used when cloning a project from a URL
+ */
+filter: CloneFilter, 
+/**
+ * Only fetch this many commits of history; older history is fetched when a merge or
+ * rebase needs it
+ */
+depth: number | null, };
+
+export enum CloneFilter { NONE = "NONE", BLOBLESS = "BLOBLESS", TREELESS = "TREELESS" }
+
+export type StorageConfig = { 
+/**
+ * Parent directory of task attempt worktrees
+ */
+worktree_dir: string | null, 
+/**
+ * Directory of images attached to tasks
+ */
+image_dir: string | null, 
+/**
+ * Directory of the SQLite database, which also holds execution logs
+ */
+database_dir: string | null, 
+/**
+ * Move requested through the relocation endpoint, carried out on the next start
+ */
+relocation: StorageRelocation | null, };
+
+export type StorageRelocation = { worktree_dir: string | null, image_dir: string | null, database_dir: string | null, };
+
+export type StorageInfo = { worktree_dir: string, image_dir: string, database_dir: string, 
+/**
+ * Move that will be carried out on the next start
+ */
+pending_relocation: StorageRelocation | null, };
+
+export type ResourceLimits = { 
+/**
+ * Stop the process once it has run this many seconds
+ */
+timeout_secs: number | null, 
+/**
+ * Stop the process once it and its children use more memory than this, in megabytes
+ */
+memory_max_mb: number | null, 
+/**
+ * CPU niceness from -20 to 19; higher values leave more CPU time to other programs
+ */
+nice: number | null, };
+
+export type ResourceLimitsConfig = { coding_agent: ResourceLimits, 
+/**
+ * Setup and cleanup scripts
+ */
+scripts: ResourceLimits, dev_server: ResourceLimits, 
+/**
+ * Coding agents allowed to run at once; further ones wait in a queue
+ */
+max_concurrent_coding_agents: number | null, };
+
+export type DevServerConfig = { 
+/**
+ * Start the dev server again when it exits with an error
+ */
+restart_on_crash: boolean, 
+/**
+ * Restarts allowed before giving up, counted from the last manual start
+ */
+max_restarts: number, 
+/**
+ * When the server starts, start the dev servers that were running when it last stopped
+ */
+resume_after_restart: boolean, };
+
+export type LogRetentionConfig = { 
+/**
+ * Periodically apply the policy below to the logs of finished execution processes
+ */
+enabled: boolean, 
+/**
+ * Compress logs that have not been written to for this many days
+ */
+compress_after_days: number | null, 
+/**
+ * Delete logs that have not been written to for this many days
+ */
+delete_after_days: number | null, 
+/**
+ * Delete the oldest logs once all logs together take up more than this, in megabytes
+ */
+max_total_mb: number | null, 
+/**
+ * Save logs to this directory as compressed JSONL before deleting them
+ */
+archive_dir: string | null, };
+
+export type ChatNotificationConfig = { 
+/**
+ * Address the app is reached at, used for links in Slack, Discord and email messages,
+ * e.g. `https://kanban.example.com`. When unset, links use the address the app was last
+ * reached at from another machine, as a reverse proxy forwards it, or else this machine.
+ */
+public_url: string | null, };
+
+export enum SmtpSecurity { START_TLS = "START_TLS", TLS = "TLS", NONE = "NONE" }
+
+export type EmailNotificationConfig = { 
+/**
+ * Email when the coding agent of an attempt finishes or fails
+ */
+enabled: boolean, smtp_host: string | null, 
+/**
+ * Defaults to the usual port of the security mode
+ */
+smtp_port: number | null, security: SmtpSecurity, username: string | null, password: string | null, 
+/**
+ * Sender, e.g. `Vibe Kanban <kanban@example.com>`
+ */
+from: string | null, 
+/**
+ * Recipients, separated by commas
+ */
+to: string | null, 
+/**
+ * Only email about runs that took at least this many minutes; shorter ones usually
+ * finish while the user is still watching
+ */
+min_duration_mins: number, };
+
+export type ConfigSource = "default" | "file" | "env" | "override";
+
+export type EffectiveConfig = { config: Config, 
+/**
+ * Layer of each setting that isn't at its default, by dotted path
+ */
+sources: { [key in string]?: ConfigSource }, };
+
+export type ConfigOverride = { 
+/**
+ * Dotted path of the setting, e.g. `worktree_gc.retention_days`
+ */
+path: string, value: JsonValue, 
+/**
+ * User the request acted for, when known
+ */
+updated_by: string | null, updated_at: string, };
+
+export type SetConfigOverride = { path: string, value: JsonValue, };
+
+export type LogRetentionReport = { 
+/**
+ * Processes whose logs were compressed
+ */
+compressed: number, 
+/**
+ * Bytes saved by compressing them
+ */
+compressed_bytes_saved: bigint, 
+/**
+ * Processes whose logs were saved to the archive directory before deletion
+ */
+archived: number, 
+/**
+ * Processes whose logs were deleted for being too old
+ */
+deleted_expired: number, 
+/**
+ * Processes whose logs were deleted to stay under the size cap
+ */
+deleted_over_cap: number, };
+
+export type EventJournalConfig = { 
+/**
+ * Keep the patches sent on the events stream, so recent board changes can be replayed
+ */
+enabled: boolean, 
+/**
+ * Delete patches older than this many hours
+ */
+retention_hours: number | null, 
+/**
+ * Delete the oldest patches once there are more than this many
+ */
+max_entries: number | null, };
+
+export type EventJournalEntry = { 
+/**
+ * Increases with every entry, so it can be used as a cursor
+ */
+id: bigint, 
+/**
+ * JSON Patch operations, as sent on the stream
+ */
+patch: JsonValue, created_at: string, };
+
+export type EventsWsStream = { "kind": "events", project_id?: string, task_id?: string, task_attempt_id?: string, 
+/**
+ * Comma-separated record types, e.g. `task,execution_process`
+ */
+record_types?: string, 
+/**
+ * Cursor of the last event received, to resume after it
+ */
+cursor?: string, } | { "kind": "diff", task_attempt_id: string, stats_only?: boolean, } | { "kind": "raw_logs", execution_process_id: string, } | { "kind": "normalized_logs", execution_process_id: string, };
+
+export type EventsWsClientMessage = { "type": "subscribe", id: string, stream: EventsWsStream, } | { "type": "unsubscribe", id: string, };
+
+export type EventsWsServerMessage = { "type": "message", id: string, msg: JsonValue, 
+/**
+ * Cursor of the event, for event subscriptions
+ */
+cursor?: string, } | { "type": "ended", id: string, } | { "type": "error", id?: string, message: string, };
+
+export type RateLimit = { per_minute: number, burst: number, };
+
+export type RateLimitConfig = { 
+/**
+ * Limit how often each client can call the routes below
+ */
+enabled: boolean, 
+/**
+ * Starting attempts, follow-ups and reruns; unlimited when unset
+ */
+attempt_starts: RateLimit | null, 
+/**
+ * Computing an attempt's diff; unlimited when unset
+ */
+diffs: RateLimit | null, 
+/**
+ * Opening log streams and downloading logs; unlimited when unset
+ */
+log_streams: RateLimit | null, };
+
+export type LimitedRoute = "attempt_start" | "diff" | "log_stream";
+
+export type RouteRateLimitStats = { route: LimitedRoute, 
+/**
+ * Unset when the route isn't limited
+ */
+limit: RateLimit | null, allowed: bigint, 
+/**
+ * Requests answered with 429 Too Many Requests
+ */
+limited: bigint, };
+
+export type RateLimitSnapshot = { enabled: boolean, routes: Array<RouteRateLimitStats>, 
+/**
+ * Clients currently tracked, per kind of route
+ */
+tracked_buckets: number, };
+
+export type CreateShareLink = { 
+/**
+ * Hours until the link expires, 24 when unset and at most 720
+ */
+expires_in_hours: number | null, };
+
+export type ShareLink = { token: string, task_attempt_id: string, expires_at: string, 
+/**
+ * Link to the read-only view of the attempt; unset when the address of the app is unknown
+ */
+url: string | null, };
+
+export type SharedExecutionProcess = { id: string, run_reason: ExecutionProcessRunReason, status: ExecutionProcessStatus, exit_code: bigint | null, started_at: string, completed_at: string | null, };
+
+export type SharedAttempt = { task_title: string, task_description: string | null, branch: string, target_branch: string, executor: string, created_at: string, processes: Array<SharedExecutionProcess>, };
+
+export type DevServer = { task_attempt_id: string, 
+/**
+ * Latest dev server process started for the attempt
+ */
+execution_process_id: string | null, port: number, 
+/**
+ * Automatic restarts after crashes since the dev server was last started by hand
+ */
+restart_count: number, updated_at: string, };
+
+export type DevServerStatus = { execution_process_id: string | null, status: ExecutionProcessStatus | null, port: number | null, 
+/**
+ * Set while the dev server is running
+ */
+url: string | null, restart_count: number, };
+
+export type CollectedWorktree = { task_attempt_id: string, task_id: string, worktree_path: string, };
+
+export type WorktreeGcReport = { retention_days: number, removed: Array<CollectedWorktree>, 
+/**
+ * Worktrees that could not be removed and will be retried on the next pass
+ */
+failed: number, };
+
+export enum StallAction { WARN = "WARN", CANCEL = "CANCEL" }
+
+export type TelemetrySnapshot = { enabled: boolean, disabled_by_env: boolean, collecting_since: string, version: string, features: { [key in string]?: number }, routes: { [key in string]?: RouteTelemetry }, total_requests: number, total_errors: number, error_rate: number, };
+
+export type RouteTelemetry = { requests: number, client_errors: number, server_errors: number, };
 
 export type DeviceFlowStartResponse = { user_code: string, verification_uri: string, expires_in: number, interval: number, };
 
@@ -125,13 +1382,34 @@ export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: st
  * True when file contents are intentionally omitted (e.g., too large)
  */
 contentOmitted: boolean, 
+/**
+ * True when the file is tracked by Git LFS; pointer contents are never shown
+ */
+lfs: boolean, 
 /**
  * Optional precomputed stats for omitted content
  */
-additions: number | null, deletions: number | null, };
+additions: number | null, deletions: number | null, 
+/**
+ * Precomputed hunks with intraline changes, present when contents are inline
+ */
+hunks?: Array<DiffHunk>, };
 
 export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
 
+export type DiffHunk = { oldStart: number, oldLines: number, newStart: number, newLines: number, lines: Array<DiffLine>, };
+
+export type DiffLine = { kind: DiffLineKind, oldLine: number | null, newLine: number | null, 
+/**
+ * Changed word ranges within the line, as UTF-16 offsets so they can be used
+ * directly with JavaScript string slicing
+ */
+changes: Array<DiffRange>, };
+
+export type DiffLineKind = "context" | "insert" | "delete";
+
+export type DiffRange = { start: number, end: number, };
+
 export type RepositoryInfo = { id: bigint, name: string, full_name: string, owner: string, description: string | null, clone_url: string, ssh_url: string, default_branch: string, private: boolean, };
 
 export type CommandBuilder = { 
@@ -144,6 +1422,36 @@ base: string,
  */
 params: Array<string> | null, };
 
+export type OutputParserConfig = { rules: Array<OutputParserRule>, 
+/**
+ * Entry type for lines that match no rule; unmatched lines are dropped when unset
+ */
+fallback?: OutputParserEntryType | null, };
+
+export type OutputParserRule = { 
+/**
+ * Regex matched against each output line; named and numbered groups are available to templates
+ */
+regex?: string | null, 
+/**
+ * JSON pointer into the line parsed as JSON; the rule matches when the pointer resolves
+ */
+json_pointer?: string | null, 
+/**
+ * Only match when the value at `json_pointer` equals this string
+ */
+equals?: string | null, entry_type: OutputParserEntryType, 
+/**
+ * Template for the entry content; defaults to the whole line
+ */
+content?: string | null, 
+/**
+ * Template for the tool name of `tool_use` entries
+ */
+tool_name?: string | null, };
+
+export type OutputParserEntryType = "assistant_message" | "thinking" | "system_message" | "error_message" | "tool_use" | "session_id" | "ignore";
+
 export type ExecutorProfileId = { 
 /**
  * The executor type (e.g., "CLAUDE_CODE", "AMP")
@@ -154,19 +1462,37 @@ executor: BaseCodingAgent,
  */
 variant: string | null, };
 
-export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } };
+export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "CUSTOM": CustomAgent } | { "LOCAL_LLM": LocalLlm } };
+
+export type VariantInfo = { 
+/**
+ * Value to store as the variant; `DEFAULT` is equivalent to no variant
+ */
+name: string, 
+/**
+ * Summary of the settings that set this configuration apart
+ */
+description: string, model: string | null, cost_hint: CostHint, };
+
+export type ExecutorVariants = { executor: BaseCodingAgent, 
+/**
+ * `DEFAULT` first, then the other variants by name
+ */
+variants: Array<VariantInfo>, };
+
+export type CostHint = "low" | "standard" | "high";
 
 export type BaseAgentCapability = "SESSION_FORK";
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
 export type GeminiModel = "default" | "flash";
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_plan_tool?: boolean | null, include_apply_patch_tool?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_plan_tool?: boolean | null, include_apply_patch_tool?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
 export type SandboxMode = "auto" | "read-only" | "workspace-write" | "danger-full-access";
 
@@ -178,13 +1504,23 @@ export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
 
 export type ReasoningSummaryFormat = "none" | "experimental";
 
-export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
+
+export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
+
+export type CustomAgent = { append_prompt: AppendPrompt, command: string, follow_up_command?: string | null, env?: { [key in string]?: string }, session_id_regex?: string | null, log_format: CustomLogFormat, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
-export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+/**
+ * Built-in normalizer reading a custom agent's output: plain text shown as the agent's reply,
+ * or JSON lines in the format of Claude Code's `--output-format stream-json`
+ */
+export type CustomLogFormat = "plain_text" | "claude_stream_json";
 
-export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type LocalLlm = { append_prompt: AppendPrompt, base_url: string, model: string, api_key_env?: string | null, max_turns?: number | null, allow_commands?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
+
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, output_parser?: OutputParserConfig | null, };
 
 export type AppendPrompt = string | null;
 
@@ -200,15 +1536,46 @@ export type CodingAgentFollowUpRequest = { prompt: string, session_id: string,
  */
 executor_profile_id: ExecutorProfileId, };
 
+export type TaskAttemptSummary = { 
+/**
+ * Size of the attempt's changes as of its last finished execution process, unset until one
+ * has finished
+ */
+diff_stats: AttemptDiffStats | null, id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, preview_url: string | null, started_by: string | null, review_state: AttemptReviewState | null, review_note: string | null, reviewed_by: string | null, reviewed_at: string | null, created_at: string, updated_at: string, };
+
+export type TaskAttemptDetails = { 
+/**
+ * Address of the attempt's dev server while it is running
+ */
+dev_server_url: string | null, id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, preview_url: string | null, started_by: string | null, review_state: AttemptReviewState | null, review_note: string | null, reviewed_by: string | null, reviewed_at: string | null, created_at: string, updated_at: string, };
+
 export type CreateTaskAttemptBody = { task_id: string, 
 /**
  * Executor profile specification
  */
 executor_profile_id: ExecutorProfileId, base_branch: string, };
 
-export type RebaseTaskAttemptRequest = { old_base_branch: string | null, new_base_branch: string | null, };
+export type RebaseTaskAttemptRequest = { old_base_branch: string | null, new_base_branch: string | null, 
+/**
+ * Stash uncommitted changes to tracked files before rebasing and reapply them afterwards
+ */
+auto_stash?: boolean, };
+
+export type GitOperationError = { "type": "merge_conflicts", message: string, op: ConflictOp, } | { "type": "rebase_in_progress" } | { "type": "stash_conflict", message: string, };
 
-export type GitOperationError = { "type": "merge_conflicts", message: string, op: ConflictOp, } | { "type": "rebase_in_progress" };
+export type CherryPickRequest = { 
+/**
+ * Attempt whose commits should be applied to this attempt's branch
+ */
+source_attempt_id: string, 
+/**
+ * Commits to apply, oldest first. Defaults to every commit on the source attempt's branch
+ */
+commits?: Array<string>, };
+
+export type CherryPickResult = { head_oid: string, applied_commits: Array<string>, };
+
+export type ExportFormat = "patch" | "bundle";
 
 export type ReplaceProcessRequest = { 
 /**
@@ -232,6 +1599,76 @@ force_when_dirty: boolean | null,
  */
 perform_git_reset: boolean | null, };
 
+export type Checkpoint = { execution_process_id: string, run_reason: ExecutionProcessRunReason, 
+/**
+ * First line of the coding agent's prompt, or the kind of script
+ */
+name: string, 
+/**
+ * HEAD before the process ran; unset if it was never recorded
+ */
+commit: string | null, 
+/**
+ * Agent session that follow-ups continue after rolling back here; unset starts a new one
+ */
+session_id: string | null, started_at: string, };
+
+export type RollbackToCheckpointRequest = { 
+/**
+ * Discard uncommitted changes in the worktree instead of refusing to roll back
+ */
+force_when_dirty?: boolean, };
+
+export type RollbackResult = { 
+/**
+ * The commit the worktree was reset to
+ */
+commit: string, 
+/**
+ * Processes dropped from the attempt's history, including the checkpoint's own
+ */
+dropped_count: bigint, 
+/**
+ * Agent session the next follow-up continues; unset starts a new one
+ */
+session_id: string | null, };
+
+export type WorktreeFile = { 
+/**
+ * Path relative to the worktree root
+ */
+path: string, content: string, };
+
+export type WriteWorktreeFileRequest = { 
+/**
+ * Path relative to the worktree root; missing parent directories are created
+ */
+path: string, content: string, 
+/**
+ * Commit just this file on the attempt's branch after writing it
+ */
+commit?: boolean, 
+/**
+ * Defaults to "Edit <path>"
+ */
+commit_message?: string, };
+
+export type WriteWorktreeFileResult = { 
+/**
+ * The commit made for the edit, if one was requested
+ */
+commit: string | null, };
+
+export type TerminalSession = { id: string, task_attempt_id: string, shell: string, 
+/**
+ * Unset while the shell runs, or if it was killed
+ */
+exit_code: bigint | null, started_at: string, ended_at: string | null, };
+
+export type TerminalSize = { cols: number, rows: number, };
+
+export type TerminalClientMessage = { "type": "input", data: string, } | { "type": "resize", cols: number, rows: number, };
+
 export type CommitInfo = { sha: string, subject: string, };
 
 export type BranchStatus = { commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
@@ -246,11 +1683,43 @@ conflict_op: ConflictOp | null,
 /**
  * List of files currently in conflicted (unmerged) state
  */
-conflicted_files: Array<string>, };
+conflicted_files: Array<string>, 
+/**
+ * Latest background check against the base branch, if the project enables base branch sync
+ */
+base_branch_sync: BaseBranchSync | null, 
+/**
+ * Merging into the target branch requires an explicit override
+ */
+target_branch_protected: boolean, };
+
+export type BaseBranchSync = { task_attempt_id: string, 
+/**
+ * Commits on the base branch that the attempt's branch does not have yet
+ */
+commits_behind: bigint, 
+/**
+ * Set when the attempt was last rebased automatically
+ */
+rebased_at: string | null, 
+/**
+ * Why the last fetch or automatic rebase failed, if it did
+ */
+error: string | null, synced_at: string, };
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
-export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
+export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, preview_url: string | null, started_by: string | null, review_state: AttemptReviewState | null, review_note: string | null, reviewed_by: string | null, reviewed_at: string | null, created_at: string, updated_at: string, };
+
+export type AttemptReviewState = "reviewed" | "approved" | "needs_changes";
+
+export type AttemptDiffStats = { files_changed: bigint, additions: bigint, deletions: bigint, };
+
+export type UpdateAttemptReview = { 
+/**
+ * Clears the review when unset
+ */
+state: AttemptReviewState | null, note: string | null, };
 
 export type ExecutionProcess = { id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, 
 /**
@@ -266,23 +1735,315 @@ after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: big
  * history view (due to restore/trimming). Hidden from logs/timeline;
  * still listed in the Processes tab.
  */
-dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+dropped: boolean, started_at: string, completed_at: string | null, 
+/**
+ * Set while a running coding agent has produced no output for longer
+ * than the configured stall timeout
+ */
+stalled_at: string | null, 
+/**
+ * Why the server stopped the process on its own, if it did
+ */
+kill_reason: KillReason | null, 
+/**
+ * 1-based place in the queue while the process is queued
+ */
+queue_position: number | null, 
+/**
+ * The failed process this one re-runs under the project's retry policy
+ */
+retry_of: string | null, 
+/**
+ * The user who started the process; unset when the server started it on its own
+ */
+started_by: string | null, created_at: string, updated_at: string, };
+
+export enum ExecutionProcessStatus { queued = "queued", running = "running", completed = "completed", failed = "failed", killed = "killed" }
+
+export type KillReason = { "type": "time_limit", limit_secs: number, } | { "type": "memory_limit", limit_mb: number, used_mb: number, } | { "type": "stalled", timeout_secs: number, } | { "type": "shutdown" };
+
+export type ProcessTreeNode = { pid: number, pgid: number, command: string, memory_kb: number, elapsed_secs: number, 
+/**
+ * False for processes that left the execution's process group
+ */
+in_process_group: boolean, children: Array<ProcessTreeNode>, };
+
+export type ExecutionProcessWithUsage = { 
+/**
+ * Latest CPU and memory sample while the process is running
+ */
+resource_usage: ExecutionResourceUsage | null, id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, 
+/**
+ * Git HEAD commit OID captured before the process starts
+ */
+before_head_commit: string | null, 
+/**
+ * Git HEAD commit OID captured after the process ends
+ */
+after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: bigint | null, 
+/**
+ * dropped: true if this process is excluded from the current
+ * history view (due to restore/trimming). Hidden from logs/timeline;
+ * still listed in the Processes tab.
+ */
+dropped: boolean, started_at: string, completed_at: string | null, 
+/**
+ * Set while a running coding agent has produced no output for longer
+ * than the configured stall timeout
+ */
+stalled_at: string | null, 
+/**
+ * Why the server stopped the process on its own, if it did
+ */
+kill_reason: KillReason | null, 
+/**
+ * 1-based place in the queue while the process is queued
+ */
+queue_position: number | null, 
+/**
+ * The failed process this one re-runs under the project's retry policy
+ */
+retry_of: string | null, 
+/**
+ * The user who started the process; unset when the server started it on its own
+ */
+started_by: string | null, created_at: string, updated_at: string, };
+
+export type ExecutionResourceUsage = { execution_process_id: string, 
+/**
+ * Share of one CPU core used since the previous sample; above 100 when the run keeps
+ * several cores busy
+ */
+cpu_percent: number, 
+/**
+ * Resident memory of all of the run's processes
+ */
+memory_kb: number, process_count: number, sampled_at: string, };
+
+export type StoppedProcess = { execution_process_id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, 
+/**
+ * Whether it was running or still waiting in the queue
+ */
+previous_status: ExecutionProcessStatus, };
+
+export type StopSummary = { stopped: Array<StoppedProcess>, 
+/**
+ * Processes that could not be stopped, usually because they exited in the meantime
+ */
+failed: Array<string>, };
+
+export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver" | "postmergescript" | "testscript";
+
+export type ExecutionProcessSearchResult = { id: string, task_attempt_id: string, task_id: string, task_title: string, executor: string, run_reason: ExecutionProcessRunReason, status: ExecutionProcessStatus, exit_code: bigint | null, started_at: string, completed_at: string | null, kill_reason: KillReason | null, retry_of: string | null, started_by: string | null, };
+
+export type ExecutionProcessPage = { processes: Array<ExecutionProcessSearchResult>, 
+/**
+ * Number of matching processes across all pages
+ */
+total: bigint, };
+
+export type LogStream = "stdout" | "stderr";
+
+export type LogLine = { 
+/**
+ * 1-based, counting stdout and stderr lines together in the order they were printed
+ */
+line_number: bigint, stream: LogStream, content: string, };
+
+export type ExecutionProcessLogMatches = { 
+/**
+ * Lines that matched, in output order; at most 20 are returned
+ */
+matches: Array<LogLine>, 
+/**
+ * Number of matching lines found, which may exceed `matches`
+ */
+match_count: number, id: string, task_attempt_id: string, task_id: string, task_title: string, executor: string, run_reason: ExecutionProcessRunReason, status: ExecutionProcessStatus, exit_code: bigint | null, started_at: string, completed_at: string | null, kill_reason: KillReason | null, retry_of: string | null, started_by: string | null, };
+
+export type ExecutionProcessMetrics = { execution_process_id: string, wall_time_ms: bigint, exit_code: bigint | null, stdout_bytes: bigint, stderr_bytes: bigint, 
+/**
+ * Token counts and cost are only set for coding agents that report them. Input tokens
+ * include cached ones.
+ */
+input_tokens: bigint | null, cached_input_tokens: bigint | null, output_tokens: bigint | null, cost_usd: number | null, created_at: string, };
+
+export type ExecutionMetricsAggregate = { executor: string, run_reason: ExecutionProcessRunReason, processes: bigint, 
+/**
+ * Processes that exited with a non-zero code
+ */
+failed: bigint, total_wall_time_ms: bigint, max_wall_time_ms: bigint, output_bytes: bigint, input_tokens: bigint, cached_input_tokens: bigint, output_tokens: bigint, 
+/**
+ * Unset when none of the processes reported a cost
+ */
+cost_usd: number | null, };
 
-export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
+export type ExitCodeCount = { run_reason: ExecutionProcessRunReason, exit_code: bigint, count: bigint, };
 
-export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+export type ExecutionMetricsReport = { since: string, aggregates: Array<ExecutionMetricsAggregate>, exit_codes: Array<ExitCodeCount>, };
 
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
-export type DirectMerge = { id: string, task_attempt_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
+export type DirectMerge = { id: string, task_attempt_id: string, merge_commit: string, strategy: MergeStrategy, target_branch_name: string, created_at: string, };
 
 export type PrMerge = { id: string, task_attempt_id: string, created_at: string, target_branch_name: string, pr_info: PullRequestInfo, };
 
 export type MergeStatus = "open" | "merged" | "closed" | "unknown";
 
+export type MergeStrategy = "squash" | "merge" | "rebase";
+
 export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
 
-export type Draft = { id: string, task_attempt_id: string, draft_type: DraftType, retry_process_id: string | null, prompt: string, queued: boolean, sending: boolean, variant: string | null, image_ids: Array<string> | null, created_at: string, updated_at: string, version: bigint, };
+export type MergeQueueStatus = "queued" | "rebasing" | "verifying" | "merging" | "merged" | "failed" | "cancelled";
+
+export type MergeQueueEntry = { id: string, project_id: string, task_attempt_id: string, status: MergeQueueStatus, 
+/**
+ * Why the rebase, verification or merge failed
+ */
+error: string | null, merge_commit: string | null, queued_at: string, updated_at: string, };
+
+export type WorkflowRun = { id: string, task_attempt_id: string, repo_owner: string, repo_name: string, workflow_file: string, git_ref: string, run_id: bigint | null, run_url: string | null, 
+/**
+ * `requested` until the run is found on GitHub, then GitHub's run status
+ */
+status: string, conclusion: string | null, created_at: string, updated_at: string, };
+
+export type TestCounts = { passed: bigint, failed: bigint, 
+/**
+ * Ignored, skipped and todo tests
+ */
+skipped: bigint, };
+
+export type TestRunResult = { execution_process_id: string, task_attempt_id: string, format: TestReportFormat, passed: bigint, failed: bigint, skipped: bigint, created_at: string, };
+
+export type DeployHook = { project_id: string, url_template: string, created_at: string, updated_at: string, };
+
+export type UpsertDeployHook = { url_template: string, 
+/**
+ * Leave unset to keep the existing token
+ */
+token: string | null, };
+
+export type ProjectEnvVarResponse = { key: string, 
+/**
+ * Not returned for secrets
+ */
+value: string | null, secret: boolean, updated_at: string, };
+
+export type UpsertProjectEnvVar = { key: string, value: string, secret: boolean, };
+
+export type TaskInbox = { project_id: string, 
+/**
+ * Part of the ingestion URL; anyone who knows it can create tasks
+ */
+secret: string, title_template: string, description_template: string | null, created_at: string, updated_at: string, };
+
+export type UpsertTaskInbox = { title_template: string, description_template: string | null, 
+/**
+ * Issue a new secret, invalidating the old ingestion URL
+ */
+rotate_secret: boolean, };
+
+export type GitHubIssueSync = { project_id: string, 
+/**
+ * Only issues with this label are imported; unset imports every issue
+ */
+label: string | null, last_synced_at: string | null, 
+/**
+ * Why the last sync failed, cleared by the next one that succeeds
+ */
+last_error: string | null, created_at: string, updated_at: string, };
+
+export type UpsertGitHubIssueSync = { label: string | null, };
+
+export type GitHubIssueSyncSummary = { 
+/**
+ * Open issues imported as new tasks
+ */
+imported: number, 
+/**
+ * Tasks marked done because their issue was closed
+ */
+closed: number, 
+/**
+ * Done tasks moved back to to do because their issue was reopened
+ */
+reopened: number, };
+
+export type IssueTrackerKind = "linear" | "jira";
+
+export type IssueTrackerConnection = { project_id: string, kind: IssueTrackerKind, 
+/**
+ * Jira site, e.g. `https://acme.atlassian.net`; unset for Linear
+ */
+base_url: string | null, 
+/**
+ * Key of the Linear team or Jira project whose issues are synced
+ */
+scope: string, 
+/**
+ * Jira account the API token belongs to; unset for Linear
+ */
+email: string | null, last_synced_at: string | null, 
+/**
+ * Why the last sync failed, cleared by the next one that succeeds
+ */
+last_error: string | null, created_at: string, updated_at: string, };
+
+export type UpsertIssueTrackerConnection = { kind: IssueTrackerKind, base_url: string | null, scope: string, email: string | null, 
+/**
+ * Keeps the current token when unset
+ */
+api_token: string | null, };
+
+export type IssueTrackerSyncSummary = { 
+/**
+ * Issues imported as new tasks
+ */
+imported: number, 
+/**
+ * Tasks moved because their issue changed status
+ */
+updated: number, 
+/**
+ * Issues moved because their task changed status
+ */
+pushed: number, };
+
+export type Workspace = { id: string, name: string, 
+/**
+ * Projects of the workspace, in board order
+ */
+project_ids: Array<string>, created_at: string, updated_at: string, };
+
+export type CreateWorkspace = { name: string, project_ids: Array<string>, };
+
+export type UpdateWorkspace = { name: string | null, 
+/**
+ * Replaces the workspace's projects, in this order
+ */
+project_ids: Array<string> | null, };
+
+export type VulnerabilityFinding = { id: string, task_attempt_id: string, 
+/**
+ * OSV ecosystem name, e.g. `npm`, `crates.io`, `PyPI`, `Go`
+ */
+ecosystem: string, package_name: string, package_version: string, 
+/**
+ * Manifest or lockfile the package was found in, relative to the repo root
+ */
+manifest_path: string, 
+/**
+ * Advisory id, e.g. `GHSA-xxxx-xxxx-xxxx` or `RUSTSEC-2024-0001`
+ */
+vulnerability_id: string, summary: string | null, severity: VulnerabilitySeverity, created_at: string, };
+
+export type VulnerabilitySeverity = "unknown" | "low" | "medium" | "high" | "critical";
+
+export type Draft = { id: string, task_attempt_id: string, draft_type: DraftType, retry_process_id: string | null, prompt: string, queued: boolean, sending: boolean, variant: string | null, image_ids: Array<string> | null, 
+/**
+ * Captions of attached images, given to the agent with each image
+ */
+image_captions: { [key in string]?: string } | null, created_at: string, updated_at: string, version: bigint, };
 
 export type DraftType = "follow_up" | "retry";
 
@@ -320,6 +2081,8 @@ export type ToolStatus = { "status": "created" } | { "status": "success" } | { "
 
 export type PatchType = { "type": "NORMALIZED_ENTRY", "content": NormalizedEntry } | { "type": "STDOUT", "content": string } | { "type": "STDERR", "content": string } | { "type": "DIFF", "content": Diff };
 
+export type TokenDelta = { entry_index: number, delta: string, };
+
 export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
 
 export type CreateApprovalRequest = { tool_name: string, tool_input: JsonValue, tool_call_id: string, };